@@ -20,6 +20,10 @@
 
 use crate::{
     fyrox::{
+        asset::{
+            io::{ArchiveCompression, ArchivePacker, ArchiveResourceIo},
+            registry::ResourceRegistry,
+        },
         core::{
             log::{Log, LogMessage, MessageKind},
             pool::Handle,
@@ -82,6 +86,14 @@ struct ExportOptions {
     include_used_assets: bool,
     assets_folders: Vec<PathBuf>,
     ignored_extensions: Vec<String>,
+    #[reflect(
+        description = "Pack all the assets into a single archive instead of copying them as \
+        loose files. PC only, other platforms always get loose files. Cannot be combined \
+        with `include_used_assets`."
+    )]
+    pack_assets: bool,
+    #[reflect(description = "Compress the assets in the archive, if they benefit from it.")]
+    compress_packed_assets: bool,
     #[reflect(hidden)]
     build_targets: Vec<String>,
     #[reflect(hidden)]
@@ -98,6 +110,8 @@ impl Default for ExportOptions {
             assets_folders: vec!["./data/".into()],
             include_used_assets: false,
             ignored_extensions: vec!["log".to_string()],
+            pack_assets: false,
+            compress_packed_assets: true,
             build_targets: vec!["default".to_string()],
             selected_build_target: 0,
             run_after_build: false,
@@ -171,6 +185,65 @@ where
     Ok(())
 }
 
+fn pack_assets(export_options: &ExportOptions) -> Result<(), String> {
+    let compression = if export_options.compress_packed_assets {
+        ArchiveCompression::Deflate
+    } else {
+        ArchiveCompression::None
+    };
+    let mut packer = ArchivePacker::new().with_compression(compression);
+
+    let filter = |path: &Path| {
+        path.extension().is_none_or(|ext| {
+            !export_options
+                .ignored_extensions
+                .iter()
+                .any(|ignored| ext == ignored.as_str())
+        })
+    };
+
+    for folder in export_options.assets_folders.iter() {
+        Log::info(format!(
+            "Trying to pack assets from {} folder...",
+            folder.display()
+        ));
+
+        let count = packer.add_folder(folder, &filter).map_err(|err| {
+            format!(
+                "Unable to pack {} folder. Reason: {err:?}",
+                folder.display()
+            )
+        })?;
+
+        Log::info(format!("{count} files were added to the archive."));
+    }
+
+    let registry_path = Path::new(ResourceRegistry::DEFAULT_PATH);
+    if registry_path.exists() {
+        packer
+            .add_registry(registry_path)
+            .map_err(|err| format!("Unable to pack the resource registry. Reason: {err:?}"))?;
+    }
+
+    let archive_path = export_options
+        .destination_folder
+        .join(ArchiveResourceIo::DEFAULT_PATH);
+    let index = packer.write_to_file(&archive_path).map_err(|err| {
+        format!(
+            "Unable to write the resource archive to {}. Reason: {err:?}",
+            archive_path.display()
+        )
+    })?;
+
+    Log::info(format!(
+        "{} files were successfully packed to {}",
+        index.len(),
+        archive_path.display()
+    ));
+
+    Ok(())
+}
+
 fn make_command(program: &str) -> std::process::Command {
     let mut command = std::process::Command::new(program);
     // Remove the `RUSTFLAGS` environment variable, which could be added to the child process
@@ -542,10 +615,33 @@ fn export(export_options: ExportOptions, cancel_flag: Arc<AtomicBool>) -> Result
 
     let package_dir_path = package.manifest_path.as_path().parent().unwrap();
 
+    if export_options.pack_assets {
+        if export_options.include_used_assets {
+            return Err(
+                "Packing the assets cannot be combined with `include_used_assets`, \
+                only the content of the assets folders can be packed."
+                    .to_string(),
+            );
+        }
+
+        if export_options.target_platform != TargetPlatform::PC {
+            Log::warn(format!(
+                "Packing the assets is not supported for {} target platform, the assets \
+                will be copied as loose files.",
+                export_options.target_platform
+            ));
+        }
+    }
+
     let mut temp_folders = Vec::new();
 
     // Copy assets
     match export_options.target_platform {
+        TargetPlatform::PC if export_options.pack_assets => {
+            Log::info("Trying to pack the assets...");
+
+            pack_assets(&export_options)?;
+        }
         TargetPlatform::PC | TargetPlatform::WebAssembly => {
             Log::info("Trying to copy the assets...");
 
@@ -177,6 +177,9 @@ impl SceneRenderPass for HighlightRenderPass {
                     fallback_resources: ctx.fallback_resources,
                     ambient_light: Default::default(),
                     scene_depth: Some(ctx.depth_texture),
+                    fog: None,
+                    light_clusters: None,
+                    lights_2d: None,
                     viewport: ctx.viewport,
                     uniform_memory_allocator: ctx.uniform_memory_allocator,
                 },
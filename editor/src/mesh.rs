@@ -23,7 +23,11 @@ use fyrox::gui::widget::WidgetMessage;
 use crate::{
     command::{Command, CommandGroup},
     fyrox::{
-        core::pool::Handle,
+        core::{
+            algebra::{Matrix4, Vector3},
+            math::Matrix4Ext,
+            pool::Handle,
+        },
         engine::Engine,
         graph::SceneGraph,
         gui::{
@@ -37,7 +41,10 @@ use crate::{
         },
         scene::{
             base::BaseBuilder,
-            collider::{ColliderBuilder, ColliderShape, ConvexPolyhedronShape, GeometrySource},
+            collider::{
+                ColliderBuilder, ColliderShape, CompoundShape, ConvexDecompositionParameters,
+                ConvexPolyhedronShape, GeometrySource,
+            },
             mesh::{
                 surface::{SurfaceBuilder, SurfaceResource},
                 Mesh, MeshBuilder,
@@ -62,6 +69,7 @@ pub struct MeshControlPanel {
     create_trimesh_collider: Handle<UiNode>,
     create_convex_collider: Handle<UiNode>,
     create_trimesh_rigid_body: Handle<UiNode>,
+    create_decomposed_rigid_body: Handle<UiNode>,
     add_convex_collider: Handle<UiNode>,
     add_trimesh_collider: Handle<UiNode>,
 }
@@ -106,6 +114,13 @@ impl MeshControlPanel {
             mesh(es) to it.",
             ctx,
         );
+        let create_decomposed_rigid_body = make_button(
+            "Create Decomposed Rigid Body",
+            "Creates a new dynamic rigid body with a compound collider, that consists of convex \
+            parts of the selected mesh(es) (approximate convex decomposition), and attaches the \
+            selected mesh(es) to it. It could take some time for complex meshes.",
+            ctx,
+        );
         let add_convex_collider = make_button(
             "Add Convex Collider",
             "Creates a new convex (polyhedron) collider and attaches it to an ancestor rigid \
@@ -126,6 +141,7 @@ impl MeshControlPanel {
                 .with_child(create_trimesh_collider)
                 .with_child(create_convex_collider)
                 .with_child(create_trimesh_rigid_body)
+                .with_child(create_decomposed_rigid_body)
                 .with_child(add_convex_collider)
                 .with_child(add_trimesh_collider),
         )
@@ -142,6 +158,7 @@ impl MeshControlPanel {
             create_trimesh_collider,
             create_convex_collider,
             create_trimesh_rigid_body,
+            create_decomposed_rigid_body,
             add_convex_collider,
             add_trimesh_collider,
         }
@@ -217,6 +234,46 @@ impl MeshControlPanel {
                         Command::new(LinkNodesCommand::new(collider_handle, rigid_body_handle)),
                     ]);
                 }
+            } else if message.destination() == self.create_decomposed_rigid_body {
+                let handles = scene
+                    .graph
+                    .generate_free_handles(2 * meshes_iter(selection, scene).count());
+
+                for (rb_collider_handles, (mesh_handle, mesh)) in
+                    handles.chunks(2).zip(meshes_iter(selection, scene))
+                {
+                    let rigid_body_handle = rb_collider_handles[0];
+                    let collider_handle = rb_collider_handles[1];
+
+                    // Physics engine does not support scaling, so bake it into the vertices.
+                    let global_transform = mesh.global_transform();
+                    let scale = Matrix4::new_nonuniform_scaling(&Vector3::new(
+                        global_transform.side().norm(),
+                        global_transform.up().norm(),
+                        global_transform.look().norm(),
+                    ));
+                    let shape = CompoundShape::convex_decomposition_of_mesh(
+                        mesh,
+                        &scale,
+                        &ConvexDecompositionParameters::default(),
+                    );
+
+                    let rigid_body =
+                        RigidBodyBuilder::new(BaseBuilder::new().with_name("RigidBody"))
+                            .with_body_type(RigidBodyType::Dynamic)
+                            .build_node();
+                    let collider =
+                        ColliderBuilder::new(BaseBuilder::new().with_name("CompoundCollider"))
+                            .with_shape(ColliderShape::Compound(shape))
+                            .build_node();
+                    commands.extend([
+                        Command::new(AddNodeCommand::new(rigid_body, mesh_handle, false)),
+                        Command::new(AddNodeCommand::new(collider, rigid_body_handle, false)),
+                        Command::new(LinkNodesCommand::new(rigid_body_handle, mesh.parent())),
+                        Command::new(LinkNodesCommand::new(mesh_handle, rigid_body_handle)),
+                        Command::new(LinkNodesCommand::new(collider_handle, rigid_body_handle)),
+                    ]);
+                }
             } else if message.destination() == self.add_convex_collider {
                 for (mesh_handle, _) in meshes_iter(selection, scene) {
                     if let Some((ancestor_rigid_body, _)) =
@@ -0,0 +1,117 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::{
+    fyrox::{
+        core::{algebra::Vector3, pool::Handle},
+        scene::{collider::ColliderShape, node::Node, Scene},
+    },
+    plugins::collider::{
+        make_handle, try_get_collider_shape, try_get_collider_shape_mut, ShapeGizmoTrait,
+        ShapeHandleValue,
+    },
+};
+
+pub struct CompoundShapeGizmo {
+    child_handles: Vec<Handle<Node>>,
+}
+
+impl CompoundShapeGizmo {
+    pub fn new(child_count: usize, root: Handle<Node>, visible: bool, scene: &mut Scene) -> Self {
+        Self {
+            child_handles: (0..child_count)
+                .map(|_| make_handle(scene, root, visible))
+                .collect(),
+        }
+    }
+
+    fn child_index(&self, handle: Handle<Node>) -> Option<usize> {
+        self.child_handles.iter().position(|h| *h == handle)
+    }
+}
+
+impl ShapeGizmoTrait for CompoundShapeGizmo {
+    fn for_each_handle(&self, func: &mut dyn FnMut(Handle<Node>)) {
+        for handle in self.child_handles.iter() {
+            func(*handle)
+        }
+    }
+
+    fn handle_local_position(
+        &self,
+        handle: Handle<Node>,
+        collider: Handle<Node>,
+        scene: &Scene,
+    ) -> Option<Vector3<f32>> {
+        let Some(ColliderShape::Compound(compound)) = try_get_collider_shape(collider, scene)
+        else {
+            return None;
+        };
+
+        // Force the gizmo to be re-created when the amount of children has changed.
+        if compound.children.len() != self.child_handles.len() {
+            return None;
+        }
+
+        self.child_index(handle)
+            .map(|index| compound.children[index].position)
+    }
+
+    fn value_by_handle(
+        &self,
+        handle: Handle<Node>,
+        collider: Handle<Node>,
+        scene: &Scene,
+    ) -> Option<ShapeHandleValue> {
+        let Some(ColliderShape::Compound(compound)) = try_get_collider_shape(collider, scene)
+        else {
+            return None;
+        };
+
+        self.child_index(handle)
+            .and_then(|index| compound.children.get(index))
+            .map(|child| ShapeHandleValue::Vector(child.position))
+    }
+
+    fn set_value_by_handle(
+        &self,
+        handle: Handle<Node>,
+        value: ShapeHandleValue,
+        collider: Handle<Node>,
+        scene: &mut Scene,
+        _initial_collider_local_position: Vector3<f32>,
+    ) {
+        let Some(ColliderShape::Compound(compound)) = try_get_collider_shape_mut(collider, scene)
+        else {
+            return;
+        };
+
+        if let Some(child) = self
+            .child_index(handle)
+            .and_then(|index| compound.children.get_mut(index))
+        {
+            child.position = value.into_vector();
+        }
+    }
+
+    fn is_vector_handle(&self, handle: Handle<Node>) -> bool {
+        self.child_index(handle).is_some()
+    }
+}
@@ -24,6 +24,8 @@ mod ball;
 mod ball2d;
 mod capsule;
 mod capsule2d;
+mod compound;
+mod compound2d;
 mod cone;
 mod cuboid;
 mod cuboid2d;
@@ -80,7 +82,8 @@ use crate::{
     plugin::EditorPlugin,
     plugins::collider::{
         ball::BallShapeGizmo, ball2d::Ball2DShapeGizmo, capsule::CapsuleShapeGizmo,
-        capsule2d::Capsule2DShapeGizmo, cone::ConeShapeGizmo, cuboid::CuboidShapeGizmo,
+        capsule2d::Capsule2DShapeGizmo, compound::CompoundShapeGizmo,
+        compound2d::Compound2DShapeGizmo, cone::ConeShapeGizmo, cuboid::CuboidShapeGizmo,
         cuboid2d::Cuboid2DShapeGizmo, cylinder::CylinderShapeGizmo, dummy::DummyShapeGizmo,
         panel::ColliderControlPanel, segment::SegmentShapeGizmo, segment2d::Segment2DShapeGizmo,
         triangle::TriangleShapeGizmo, triangle2d::Triangle2DShapeGizmo,
@@ -244,6 +247,12 @@ fn make_shape_gizmo(
             ColliderShape::Capsule(_) => Box::new(CapsuleShapeGizmo::new(visible, root, scene)),
             ColliderShape::Segment(_) => Box::new(SegmentShapeGizmo::new(root, visible, scene)),
             ColliderShape::Triangle(_) => Box::new(TriangleShapeGizmo::new(root, visible, scene)),
+            ColliderShape::Compound(compound) => Box::new(CompoundShapeGizmo::new(
+                compound.children.len(),
+                root,
+                visible,
+                scene,
+            )),
             ColliderShape::Trimesh(_)
             | ColliderShape::Heightfield(_)
             | ColliderShape::Polyhedron(_) => Box::new(DummyShapeGizmo),
@@ -260,6 +269,12 @@ fn make_shape_gizmo(
             ColliderShape::Capsule(_) => Box::new(Capsule2DShapeGizmo::new(visible, root, scene)),
             ColliderShape::Segment(_) => Box::new(Segment2DShapeGizmo::new(root, visible, scene)),
             ColliderShape::Triangle(_) => Box::new(Triangle2DShapeGizmo::new(root, visible, scene)),
+            ColliderShape::Compound(compound) => Box::new(Compound2DShapeGizmo::new(
+                compound.children.len(),
+                root,
+                visible,
+                scene,
+            )),
             ColliderShape::Trimesh(_)
             | ColliderShape::Heightfield(_)
             | ColliderShape::TileMap(_) => Box::new(DummyShapeGizmo),
@@ -89,16 +89,17 @@ use crate::{
                 Base, LevelOfDetail, LodGroup, Mobility, Property, PropertyValue, ScriptRecord,
             },
             camera::{
-                ColorGradingLut, Exposure, OrthographicProjection, PerspectiveProjection,
-                Projection, SkyBox,
+                ColorGradingLut, DepthOfField, Exposure, OrthographicProjection,
+                PerspectiveProjection, Projection, SkyBox,
             },
             collider::{
-                BallShape, BitMask, CapsuleShape, ColliderShape, ConeShape, ConvexPolyhedronShape,
-                CuboidShape, CylinderShape, GeometrySource, HeightfieldShape, InteractionGroups,
-                SegmentShape, TriangleShape, TrimeshShape,
+                BallShape, BitMask, CapsuleShape, ColliderShape, CompoundShape, CompoundShapeChild,
+                CompoundSubShape, ConeShape, ConvexHullShape, ConvexPolyhedronShape, CuboidShape,
+                CylinderShape, GeometrySource, HeightfieldShape, InteractionGroups, SegmentShape,
+                TriangleShape, TrimeshShape,
             },
             dim2,
-            graph::physics::CoefficientCombineRule,
+            graph::physics::{CharacterAutostep, CharacterController, CoefficientCombineRule},
             joint::*,
             light::{
                 directional::{CsmOptions, FrustumSplitOptions},
@@ -117,6 +118,10 @@ use crate::{
                 },
                 ParticleSystemRng,
             },
+            post_processing::{
+                BloomEffect, MaterialEffect, PostEffect, PostEffectStage, PostProcessingStack,
+            },
+            probe::ReflectionProbeShape,
             ragdoll::Limb,
             rigidbody::RigidBodyType,
             sound::{
@@ -134,14 +139,15 @@ use crate::{
             tilemap::TileCollider,
             tilemap::{tileset::TileSet, Tile},
             transform::Transform,
+            vehicle::Wheel,
         },
         scene::{
             camera::Camera, collider::Collider, decal::Decal, light::directional::DirectionalLight,
             light::point::PointLight, light::spot::SpotLight, mesh::Mesh,
             navmesh::NavigationalMesh, particle_system::ParticleSystem, pivot::Pivot,
-            ragdoll::Ragdoll, rigidbody::RigidBody, rigidbody::RigidBodyMassPropertiesType,
-            sound::listener::Listener, sound::Sound, sprite::Sprite, terrain::Terrain,
-            tilemap::TileDefinitionHandle, tilemap::TileMap,
+            probe::ReflectionProbe, ragdoll::Ragdoll, rigidbody::RigidBody,
+            rigidbody::RigidBodyMassPropertiesType, sound::listener::Listener, sound::Sound,
+            sprite::Sprite, terrain::Terrain, tilemap::TileDefinitionHandle, tilemap::TileMap,
         },
     },
     message::MessageSender,
@@ -303,6 +309,9 @@ pub fn make_property_editors_container(
     container.register_inheritable_vec_collection::<LevelOfDetail>();
     container.register_inheritable_inspectable::<LevelOfDetail>();
 
+    container.register_inheritable_vec_collection::<Wheel>();
+    container.register_inheritable_inspectable::<Wheel>();
+
     container.register_inheritable_vec_collection::<ErasedHandle>();
     container.register_inheritable_inspectable::<ErasedHandle>();
 
@@ -381,6 +390,13 @@ pub fn make_property_editors_container(
     container.register_inheritable_inspectable::<TileMapBrush>();
 
     container.register_inheritable_inspectable::<ColorGradingLut>();
+    container.register_inheritable_inspectable::<DepthOfField>();
+    container.register_inheritable_inspectable::<PostProcessingStack>();
+    container.register_inheritable_vec_collection::<PostEffect>();
+    container.register_inheritable_enum::<PostEffect, _>();
+    container.register_inheritable_enum::<PostEffectStage, _>();
+    container.register_inheritable_inspectable::<BloomEffect>();
+    container.register_inheritable_inspectable::<MaterialEffect>();
     container.register_inheritable_inspectable::<InteractionGroups>();
 
     container.register_inheritable_enum::<JointParams, _>();
@@ -393,9 +409,20 @@ pub fn make_property_editors_container(
     container.register_inheritable_inspectable::<RevoluteJoint>();
     container.register_inheritable_inspectable::<PrismaticJoint>();
     container.register_inheritable_inspectable::<dim2::joint::PrismaticJoint>();
+    container.register_inheritable_inspectable::<ConeTwistJoint>();
+    container.register_inheritable_inspectable::<RopeJoint>();
+    container.register_inheritable_inspectable::<dim2::joint::RopeJoint>();
+    container.register_inheritable_inspectable::<SpringJoint>();
+    container.register_inheritable_inspectable::<dim2::joint::SpringJoint>();
+    container.register_inheritable_inspectable::<GenericJoint>();
+    container.register_inheritable_inspectable::<dim2::joint::GenericJoint>();
+    container.register_inheritable_inspectable::<GenericJointAxis>();
+    container.register_inheritable_inspectable::<JointMotor>();
 
     container.register_inheritable_inspectable::<Base>();
     container.register_inheritable_inspectable::<BaseLight>();
+    container.register_inheritable_inspectable::<dim2::light::BaseLight2D>();
+    container.register_inheritable_enum::<dim2::occluder::OccluderShape, _>();
 
     container.insert(EnumPropertyEditorDefinition::<Effect>::new());
     container.insert(VecCollectionPropertyEditorDefinition::<Effect>::new());
@@ -431,8 +458,12 @@ pub fn make_property_editors_container(
     container.register_inheritable_option::<ColorGradingLut>();
     container.register_inheritable_option::<Biquad>();
     container.register_inheritable_option::<SkyBox>();
+    container.register_inheritable_option::<CharacterAutostep>();
 
     container.register_inheritable_inspectable::<SkyBox>();
+    container.register_inheritable_inspectable::<CharacterAutostep>();
+    container.register_inheritable_inspectable::<CharacterController>();
+    container.register_inheritable_inspectable::<dim2::physics::CharacterController>();
 
     container.register_inheritable_enum::<dim2::collider::ColliderShape, _>();
     container.register_inheritable_enum::<CoefficientCombineRule, _>();
@@ -442,10 +473,13 @@ pub fn make_property_editors_container(
     container.register_inheritable_enum::<TextureMinificationFilter, _>();
     container.register_inheritable_enum::<Projection, _>();
     container.register_inheritable_enum::<ColliderShape, _>();
+    container.register_inheritable_enum::<CompoundSubShape, _>();
+    container.register_inheritable_enum::<dim2::collider::CompoundSubShape, _>();
     container.register_inheritable_enum::<PropertyValue, _>();
     container.register_inheritable_enum::<Mobility, _>();
     container.register_inheritable_enum::<RigidBodyType, _>();
     container.register_inheritable_enum::<Exposure, _>();
+    container.register_inheritable_enum::<ReflectionProbeShape, _>();
     container.register_inheritable_enum::<FrustumSplitOptions, _>();
     container.register_inheritable_enum::<MaterialSearchOptions, _>();
     container.register_inheritable_enum::<DistanceModel, _>();
@@ -478,6 +512,13 @@ pub fn make_property_editors_container(
     container.register_inheritable_inspectable::<dim2::collider::HeightfieldShape>();
     container.register_inheritable_inspectable::<dim2::collider::TileMapShape>();
     container.register_inheritable_inspectable::<ConvexPolyhedronShape>();
+    container.register_inheritable_inspectable::<ConvexHullShape>();
+    container.register_inheritable_inspectable::<CompoundShape>();
+    container.register_inheritable_inspectable::<dim2::collider::CompoundShape>();
+    container.register_inheritable_vec_collection::<CompoundShapeChild>();
+    container.register_inheritable_inspectable::<CompoundShapeChild>();
+    container.register_inheritable_vec_collection::<dim2::collider::CompoundShapeChild>();
+    container.register_inheritable_inspectable::<dim2::collider::CompoundShapeChild>();
     container.insert(SpriteSheetFramesContainerEditorDefinition);
 
     container.insert(SurfaceDataPropertyEditorDefinition {
@@ -595,10 +636,14 @@ pub fn make_property_editors_container(
         Decal,
         Sprite,
         Pivot,
+        ReflectionProbe,
         dim2::collider::Collider,
         dim2::rigidbody::RigidBody,
         dim2::joint::Joint,
         dim2::rectangle::Rectangle,
+        dim2::light::PointLight,
+        dim2::light::SpotLight,
+        dim2::occluder::Occluder,
         SpotLight,
         DirectionalLight,
         PointLight,
@@ -48,7 +48,7 @@ use crate::{
             base::BaseBuilder,
             collider::{ColliderBuilder, ColliderShape, InteractionGroups},
             graph::Graph,
-            joint::{BallJoint, JointBuilder, JointParams, RevoluteJoint},
+            joint::{BallJoint, ConeTwistJoint, JointBuilder, JointParams, RevoluteJoint},
             node::Node,
             ragdoll::{Limb, RagdollBuilder},
             rigidbody::{RigidBodyBuilder, RigidBodyType},
@@ -185,33 +185,36 @@ enum AxisOffset {
     Z(f32),
 }
 
-struct BallJointLimits {
-    x: Range<f32>,
-    y: Range<f32>,
-    z: Range<f32>,
+struct ConeTwistLimits {
+    swing: f32,
+    twist: Range<f32>,
 }
 
 fn try_make_ball_joint(
     body1: Handle<Node>,
     body2: Handle<Node>,
     name: &str,
-    limits: Option<BallJointLimits>,
+    limits: Option<ConeTwistLimits>,
     offset_radius: AxisOffset,
     ragdoll: Handle<Node>,
     graph: &mut Graph,
 ) -> Handle<Node> {
     if body1.is_some() && body2.is_some() {
-        let mut joint = BallJoint::default();
-
-        if let Some(limits) = limits {
-            joint.x_limits_enabled = true;
-            joint.y_limits_enabled = true;
-            joint.z_limits_enabled = true;
-
-            joint.x_limits_angles = limits.x;
-            joint.y_limits_angles = limits.y;
-            joint.z_limits_angles = limits.z;
-        }
+        let (params, twist_axis_rotation) = match limits {
+            Some(limits) => (
+                JointParams::ConeTwistJoint(ConeTwistJoint {
+                    swing_y_span: limits.swing,
+                    swing_z_span: limits.swing,
+                    twist_limits: limits.twist,
+                }),
+                // Twist axis of the joint is its local X axis, align it with the bone.
+                UnitQuaternion::from_axis_angle(&Vector3::z_axis(), 90.0f32.to_radians()),
+            ),
+            None => (
+                JointParams::BallJoint(BallJoint::default()),
+                UnitQuaternion::identity(),
+            ),
+        };
 
         let body1_ref = &graph[body1];
 
@@ -238,16 +241,18 @@ fn try_make_ball_joint(
             BaseBuilder::new().with_name(name).with_local_transform(
                 TransformBuilder::new()
                     .with_local_position(body1_ref.global_position() - offset)
-                    .with_local_rotation(UnitQuaternion::from_matrix_eps(
-                        &graph[body1].global_transform().basis(),
-                        f32::EPSILON,
-                        16,
-                        Default::default(),
-                    ))
+                    .with_local_rotation(
+                        UnitQuaternion::from_matrix_eps(
+                            &graph[body1].global_transform().basis(),
+                            f32::EPSILON,
+                            16,
+                            Default::default(),
+                        ) * twist_axis_rotation,
+                    )
                     .build(),
             ),
         )
-        .with_params(JointParams::BallJoint(joint))
+        .with_params(params)
         .with_body1(body1.transmute())
         .with_body2(body2.transmute())
         .with_auto_rebinding_enabled(false)
@@ -712,10 +717,9 @@ impl RagdollPreset {
             left_up_leg,
             hips,
             "RagdollLeftUpLegHipsBallJoint",
-            Some(BallJointLimits {
-                x: (-80.0f32).to_radians()..80.0f32.to_radians(),
-                y: (-80.0f32).to_radians()..80.0f32.to_radians(),
-                z: (-80.0f32).to_radians()..80.0f32.to_radians(),
+            Some(ConeTwistLimits {
+                swing: 80.0f32.to_radians(),
+                twist: (-30.0f32).to_radians()..30.0f32.to_radians(),
             }),
             AxisOffset::None,
             ragdoll,
@@ -733,10 +737,9 @@ impl RagdollPreset {
             left_foot,
             left_leg,
             "RagdollLeftFootLeftLegBallJoint",
-            Some(BallJointLimits {
-                x: (-45.0f32).to_radians()..45.0f32.to_radians(),
-                y: (-45.0f32).to_radians()..45.0f32.to_radians(),
-                z: (-45.0f32).to_radians()..45.0f32.to_radians(),
+            Some(ConeTwistLimits {
+                swing: 45.0f32.to_radians(),
+                twist: (-20.0f32).to_radians()..20.0f32.to_radians(),
             }),
             AxisOffset::Y(-foot_radius),
             ragdoll,
@@ -748,10 +751,9 @@ impl RagdollPreset {
             right_up_leg,
             hips,
             "RagdollRightUpLegHipsBallJoint",
-            Some(BallJointLimits {
-                x: (-80.0f32).to_radians()..80.0f32.to_radians(),
-                y: (-80.0f32).to_radians()..80.0f32.to_radians(),
-                z: (-80.0f32).to_radians()..80.0f32.to_radians(),
+            Some(ConeTwistLimits {
+                swing: 80.0f32.to_radians(),
+                twist: (-30.0f32).to_radians()..30.0f32.to_radians(),
             }),
             AxisOffset::None,
             ragdoll,
@@ -769,10 +771,9 @@ impl RagdollPreset {
             right_foot,
             right_leg,
             "RagdollRightFootRightLegBallJoint",
-            Some(BallJointLimits {
-                x: (-45.0f32).to_radians()..45.0f32.to_radians(),
-                y: (-45.0f32).to_radians()..45.0f32.to_radians(),
-                z: (-45.0f32).to_radians()..45.0f32.to_radians(),
+            Some(ConeTwistLimits {
+                swing: 45.0f32.to_radians(),
+                twist: (-20.0f32).to_radians()..20.0f32.to_radians(),
             }),
             AxisOffset::Y(-foot_radius),
             ragdoll,
@@ -835,10 +836,9 @@ impl RagdollPreset {
             left_hand,
             left_fore_arm,
             "RagdollLeftForeArmLeftHandBallJoint",
-            Some(BallJointLimits {
-                x: (-45.0f32).to_radians()..45.0f32.to_radians(),
-                y: (-45.0f32).to_radians()..45.0f32.to_radians(),
-                z: (-45.0f32).to_radians()..45.0f32.to_radians(),
+            Some(ConeTwistLimits {
+                swing: 45.0f32.to_radians(),
+                twist: (-30.0f32).to_radians()..30.0f32.to_radians(),
             }),
             AxisOffset::X(hand_radius),
             ragdoll,
@@ -874,10 +874,9 @@ impl RagdollPreset {
             right_hand,
             right_fore_arm,
             "RagdollRightForeArmRightHandBallJoint",
-            Some(BallJointLimits {
-                x: (-45.0f32).to_radians()..45.0f32.to_radians(),
-                y: (-45.0f32).to_radians()..45.0f32.to_radians(),
-                z: (-45.0f32).to_radians()..45.0f32.to_radians(),
+            Some(ConeTwistLimits {
+                swing: 45.0f32.to_radians(),
+                twist: (-30.0f32).to_radians()..30.0f32.to_radians(),
             }),
             AxisOffset::X(-hand_radius),
             ragdoll,
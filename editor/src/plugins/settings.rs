@@ -47,7 +47,10 @@ use crate::{
             window::{WindowBuilder, WindowMessage, WindowTitle},
             HorizontalAlignment, Orientation, Thickness, UiNode, UserInterface,
         },
-        renderer::{CsmSettings, QualitySettings, ShadowMapPrecision},
+        renderer::{
+            ContactShadowsSettings, CsmSettings, MotionBlurSettings, OcclusionCullingMode,
+            QualitySettings, ShadowMapPrecision, SsrSettings,
+        },
     },
     menu::create_menu_item,
     message::MessageSender,
@@ -86,10 +89,14 @@ fn make_property_editors_container(
     container.insert(InspectablePropertyEditorDefinition::<GraphicsSettings>::new());
     container.insert(InspectablePropertyEditorDefinition::<SelectionSettings>::new());
     container.insert(EnumPropertyEditorDefinition::<ShadowMapPrecision>::new());
+    container.insert(EnumPropertyEditorDefinition::<OcclusionCullingMode>::new());
     container.insert(EnumPropertyEditorDefinition::<ScriptEditor>::new());
     container.insert(EnumPropertyEditorDefinition::<EditorStyle>::new());
     container.insert(InspectablePropertyEditorDefinition::<DebuggingSettings>::new());
     container.insert(InspectablePropertyEditorDefinition::<CsmSettings>::new());
+    container.insert(InspectablePropertyEditorDefinition::<SsrSettings>::new());
+    container.insert(InspectablePropertyEditorDefinition::<MotionBlurSettings>::new());
+    container.insert(InspectablePropertyEditorDefinition::<ContactShadowsSettings>::new());
     container.insert(InspectablePropertyEditorDefinition::<QualitySettings>::new());
     container.insert(InspectablePropertyEditorDefinition::<CameraSettings>::new());
     container.insert(InspectablePropertyEditorDefinition::<
@@ -56,6 +56,7 @@ use crate::{
             base::BaseBuilder,
             camera::{Camera, Projection},
             debug::{Line, SceneDrawingContext},
+            dim2,
             graph::{Graph, GraphUpdateSwitches},
             light::{point::PointLight, spot::SpotLight},
             mesh::RenderPath,
@@ -354,6 +355,8 @@ impl GameScene {
                 }
             } else if node.component_ref::<PointLight>().is_some()
                 || node.component_ref::<SpotLight>().is_some()
+                || node.component_ref::<dim2::light::BaseLight2D>().is_some()
+                || node.component_ref::<dim2::occluder::Occluder>().is_some()
             {
                 if settings.debugging.show_light_bounds {
                     node.debug_draw(ctx);
@@ -39,7 +39,9 @@ use crate::{
         },
         resource::texture::TextureResource,
         scene::{
+            atmosphere::{Atmosphere, CloudLayer},
             dim2,
+            fog::{Fog, FogMode, VolumetricFog},
             graph::{
                 physics::{IntegrationParameters, PhysicsWorld},
                 Graph, NodePool,
@@ -95,6 +97,11 @@ impl SceneSettingsWindow {
         container.register_inheritable_inspectable::<PhysicsWorld>();
         container.register_inheritable_inspectable::<dim2::physics::PhysicsWorld>();
         container.register_inheritable_inspectable::<SceneRenderingOptions>();
+        container.register_inheritable_inspectable::<Fog>();
+        container.register_inheritable_inspectable::<VolumetricFog>();
+        container.register_inheritable_enum::<FogMode, _>();
+        container.register_inheritable_inspectable::<Atmosphere>();
+        container.register_inheritable_inspectable::<CloudLayer>();
         container.insert(EnumPropertyEditorDefinition::<Color>::new_optional());
 
         Self {
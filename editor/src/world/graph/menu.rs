@@ -19,21 +19,23 @@
 // SOFTWARE.
 
 use crate::{
-    command::{Command, CommandGroup},
+    command::{Command, CommandGroup, SetPropertyCommand},
     fyrox::{
         asset::untyped::UntypedResource,
-        core::{algebra::Vector2, algebra::Vector3, pool::Handle, reflect::Reflect},
-        graph::BaseSceneGraph,
+        core::{algebra::Vector2, algebra::Vector3, log::Log, pool::Handle, reflect::Reflect},
+        engine::GraphicsContext,
+        graph::{BaseSceneGraph, SceneGraph},
         gui::{
-            file_browser::FileSelectorMessage,
+            file_browser::{FileBrowserMode, FileSelectorBuilder, FileSelectorMessage, Filter},
             menu::{ContextMenuBuilder, MenuItemBuilder, MenuItemContent, MenuItemMessage},
             message::UiMessage,
             popup::{Placement, PopupBuilder, PopupMessage},
             stack_panel::StackPanelBuilder,
             widget::{WidgetBuilder, WidgetMessage},
-            window::WindowMessage,
+            window::{WindowBuilder, WindowMessage, WindowTitle},
             BuildContext, RcUiNodeHandle, UiNode,
         },
+        scene::{node::Node, probe::ReflectionProbe},
     },
     make_save_file_selector,
     menu::{create::CreateEntityMenu, create_menu_item, create_menu_item_shortcut},
@@ -44,7 +46,7 @@ use crate::{
                 AddNodeCommand, LinkNodesCommand, MoveNodeCommand, ReplaceNodeCommand,
                 SetGraphRootCommand, SetNodeTransformCommand,
             },
-            make_delete_selection_command, RevertSceneNodePropertyCommand,
+            make_delete_selection_command, GameSceneContext, RevertSceneNodePropertyCommand,
         },
         controller::SceneController,
         GameScene, Selection,
@@ -57,7 +59,10 @@ use crate::{
 use fyrox::engine::SerializationContext;
 use fyrox::gui::constructor::WidgetConstructorContainer;
 use fyrox::gui::menu::SortingPredicate;
-use std::{any::TypeId, path::PathBuf};
+use std::{
+    any::TypeId,
+    path::{Path, PathBuf},
+};
 
 pub struct SceneNodeContextMenu {
     menu: RcUiNodeHandle,
@@ -69,6 +74,8 @@ pub struct SceneNodeContextMenu {
     placement_target: Handle<UiNode>,
     save_as_prefab: Handle<UiNode>,
     save_as_prefab_dialog: Handle<UiNode>,
+    bake_reflection_probe: Handle<UiNode>,
+    bake_reflection_probe_dialog: Handle<UiNode>,
     paste: Handle<UiNode>,
     make_root: Handle<UiNode>,
     open_asset: Handle<UiNode>,
@@ -97,6 +104,74 @@ fn resource_path_of_first_selected_node(
     None
 }
 
+fn first_selected_reflection_probe(
+    editor_selection: &Selection,
+    game_scene: &GameScene,
+    engine: &Engine,
+) -> Option<Handle<Node>> {
+    let first = *editor_selection.as_graph()?.nodes.first()?;
+    engine.scenes[game_scene.scene]
+        .graph
+        .try_get_of_type::<ReflectionProbe>(first)
+        .map(|_| first)
+}
+
+fn bake_reflection_probe(
+    probe: Handle<Node>,
+    path: &Path,
+    game_scene: &GameScene,
+    engine: &mut Engine,
+    sender: &MessageSender,
+) {
+    let GraphicsContext::Initialized(ref mut graphics_context) = engine.graphics_context else {
+        return;
+    };
+    let scene = &mut engine.scenes[game_scene.scene];
+
+    // The probe is baked with the exposure of the editor camera, so it will look the same as the
+    // scene in the viewport.
+    let exposure = scene.graph[game_scene.camera_controller.camera]
+        .as_camera()
+        .exposure();
+    let prev_environment = scene.graph[probe]
+        .as_reflection_probe()
+        .environment()
+        .cloned();
+
+    match graphics_context.renderer.bake_reflection_probe(
+        scene,
+        probe,
+        exposure,
+        &engine.resource_manager,
+        path,
+    ) {
+        Ok(texture) => {
+            // Set the environment back and assign the baked one using a command, so the change
+            // could be undone.
+            scene.graph[probe]
+                .as_reflection_probe_mut()
+                .set_environment(prev_environment);
+            sender.do_command(SetPropertyCommand::new(
+                "environment".into(),
+                Box::new(Some(texture)) as Box<dyn Reflect>,
+                move |ctx| {
+                    ctx.get_mut::<GameSceneContext>()
+                        .scene
+                        .graph
+                        .node_mut(probe)
+                },
+            ));
+            Log::info(format!(
+                "Reflection probe was successfully baked to {}",
+                path.display()
+            ));
+        }
+        Err(err) => Log::err(format!(
+            "Unable to bake the reflection probe. Reason: {err:?}"
+        )),
+    }
+}
+
 impl SceneNodeContextMenu {
     pub fn new(
         serialization_context: &SerializationContext,
@@ -106,6 +181,7 @@ impl SceneNodeContextMenu {
         let delete_selection;
         let copy_selection;
         let save_as_prefab;
+        let bake_reflection_probe;
         let paste;
         let make_root;
         let open_asset;
@@ -143,6 +219,11 @@ impl SceneNodeContextMenu {
                             save_as_prefab = create_menu_item("Save As Prefab...", vec![], ctx);
                             save_as_prefab
                         })
+                        .with_child({
+                            bake_reflection_probe =
+                                create_menu_item("Bake Reflection Probe...", vec![], ctx);
+                            bake_reflection_probe
+                        })
                         .with_child({
                             create_parent = MenuItemBuilder::new(
                                 WidgetBuilder::new().with_min_size(Vector2::new(120.0, 22.0)),
@@ -206,6 +287,8 @@ impl SceneNodeContextMenu {
             placement_target: Default::default(),
             save_as_prefab,
             save_as_prefab_dialog: Default::default(),
+            bake_reflection_probe,
+            bake_reflection_probe_dialog: Default::default(),
             replace_with_menu,
             paste,
             make_root,
@@ -350,6 +433,32 @@ impl SceneNodeContextMenu {
                         MessageDirection::ToWidget,
                         Some(std::env::current_dir().unwrap()),
                     ));
+                } else if message.destination() == self.bake_reflection_probe {
+                    let ui = engine.user_interfaces.first_mut();
+
+                    self.bake_reflection_probe_dialog = FileSelectorBuilder::new(
+                        WindowBuilder::new(
+                            WidgetBuilder::new().with_width(300.0).with_height(400.0),
+                        )
+                        .with_title(WindowTitle::text("Bake Reflection Probe"))
+                        .open(false)
+                        .with_remove_on_close(true),
+                    )
+                    .with_mode(FileBrowserMode::Save {
+                        default_file_name: PathBuf::from("probe.dds"),
+                    })
+                    .with_path("./")
+                    .with_filter(Filter::new(|p: &Path| {
+                        p.is_dir() || p.extension().is_some_and(|ext| ext == "dds")
+                    }))
+                    .build(&mut ui.build_ctx());
+
+                    ui.send_message(WindowMessage::open_modal(
+                        self.bake_reflection_probe_dialog,
+                        MessageDirection::ToWidget,
+                        true,
+                        true,
+                    ));
                 } else if message.destination() == self.make_root {
                     if let Some(graph_selection) = editor_selection.as_graph() {
                         if let Some(first) = graph_selection.nodes.first() {
@@ -432,10 +541,26 @@ impl SceneNodeContextMenu {
                             )
                             .is_some_and(|p| utils::is_native_scene(&p)),
                         ));
+
+                    engine
+                        .user_interfaces
+                        .first()
+                        .send_message(WidgetMessage::enabled(
+                            self.bake_reflection_probe,
+                            MessageDirection::ToWidget,
+                            first_selected_reflection_probe(editor_selection, game_scene, engine)
+                                .is_some(),
+                        ));
                 }
             } else if let Some(FileSelectorMessage::Commit(path)) = message.data() {
                 if message.destination() == self.save_as_prefab_dialog {
                     sender.send(Message::SaveSelectionAsPrefab(path.clone()));
+                } else if message.destination() == self.bake_reflection_probe_dialog {
+                    if let Some(probe) =
+                        first_selected_reflection_probe(editor_selection, game_scene, engine)
+                    {
+                        bake_reflection_probe(probe, path, game_scene, engine, sender);
+                    }
                 }
             }
         }
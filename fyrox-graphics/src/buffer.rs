@@ -21,13 +21,14 @@
 //! Buffer is a type-agnostic data storage located directly in GPU memory. It could be considered
 //! as a data block which content is a pile of bytes, whose meaning is defined externally.
 
+use crate::core::visitor::prelude::*;
 use crate::define_shared_wrapper;
 use crate::error::FrameworkError;
 use bytemuck::Pod;
 use fyrox_core::{array_as_u8_slice, array_as_u8_slice_mut, define_as_any_trait};
 
 /// GPU buffer kind.
-#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Visit)]
 pub enum BufferKind {
     /// Vertex buffer. It is used to supply vertex data (such as positions, normals, texture
     /// coordinates, etc.) to GPU.
@@ -48,7 +49,7 @@ pub enum BufferKind {
 }
 
 /// A hint for video driver that allows it to optimize buffer's content for more efficient use.
-#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Visit)]
 pub enum BufferUsage {
     /// The buffer contents will be modified once and used at most a few times.
     /// The buffer contents are modified by the application, and used as the source for
@@ -0,0 +1,391 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::{
+    capture::{
+        resources::{
+            CaptureBuffer, CaptureGeometryBuffer, CaptureProgram, CaptureSampler, CaptureTexture,
+            Recorder,
+        },
+        AttachmentRecord, BindGroupRecord, BindingRecord, BlitRecord, CaptureCommand, ClearRecord,
+        DrawRecord, FrameCapture,
+    },
+    core::{color::Color, math::Rect},
+    error::FrameworkError,
+    framebuffer::{
+        Attachment, DrawCallStatistics, GpuFrameBuffer, GpuFrameBufferTrait, ReadTarget,
+        ResourceBindGroup, ResourceBinding,
+    },
+    geometry_buffer::GpuGeometryBuffer,
+    gpu_program::GpuProgram,
+    gpu_texture::CubeMapFace,
+    DrawParameters, ElementRange,
+};
+use std::rc::Rc;
+
+pub(crate) struct CaptureFrameBuffer {
+    pub(crate) inner: GpuFrameBuffer,
+    id: u64,
+    recorder: Rc<Recorder>,
+    depth_attachment: Option<Attachment>,
+    color_attachments: Vec<Attachment>,
+}
+
+fn clone_attachment(attachment: &Attachment) -> Attachment {
+    Attachment {
+        kind: attachment.kind,
+        texture: attachment.texture.clone(),
+    }
+}
+
+fn attachment_record(attachment: &Attachment) -> AttachmentRecord {
+    AttachmentRecord {
+        kind: attachment.kind,
+        texture: CaptureTexture::cast(&attachment.texture).map_or(0, |texture| texture.id()),
+    }
+}
+
+impl CaptureFrameBuffer {
+    pub(crate) fn new(
+        inner: GpuFrameBuffer,
+        depth_attachment: Option<Attachment>,
+        color_attachments: Vec<Attachment>,
+        recorder: Rc<Recorder>,
+    ) -> Self {
+        let framebuffer = Self {
+            inner,
+            id: recorder.next_id(),
+            recorder,
+            depth_attachment,
+            color_attachments,
+        };
+        framebuffer
+            .recorder
+            .push_creation(framebuffer.id, || framebuffer.creation());
+        framebuffer
+    }
+
+    /// The back buffer is never recorded as a resource, it always has [`FrameCapture::BACK_BUFFER_ID`]
+    /// id and its attachments are the attachments of the underlying back buffer.
+    pub(crate) fn back_buffer(inner: GpuFrameBuffer, recorder: Rc<Recorder>) -> Self {
+        Self {
+            depth_attachment: inner.depth_attachment().map(clone_attachment),
+            color_attachments: inner
+                .color_attachments()
+                .iter()
+                .map(clone_attachment)
+                .collect(),
+            inner,
+            id: FrameCapture::BACK_BUFFER_ID,
+            recorder,
+        }
+    }
+
+    pub(crate) fn cast(framebuffer: &GpuFrameBuffer) -> Result<&Self, FrameworkError> {
+        framebuffer.as_any().downcast_ref::<Self>().ok_or_else(|| {
+            FrameworkError::Custom(
+                "The frame buffer was not created by a capture server!".to_string(),
+            )
+        })
+    }
+
+    fn creation(&self) -> CaptureCommand {
+        CaptureCommand::CreateFrameBuffer {
+            id: self.id,
+            depth_attachment: self.depth_attachment.as_ref().map(attachment_record),
+            color_attachments: self
+                .color_attachments
+                .iter()
+                .map(attachment_record)
+                .collect(),
+        }
+    }
+
+    fn ensure_recorded(&self) {
+        if self.id == FrameCapture::BACK_BUFFER_ID || !self.recorder.is_capturing() {
+            return;
+        }
+        for attachment in self.depth_attachment.iter().chain(&self.color_attachments) {
+            if let Ok(texture) = CaptureTexture::cast(&attachment.texture) {
+                texture.ensure_recorded();
+            }
+        }
+        self.recorder.ensure_recorded(self.id, || self.creation());
+    }
+
+    fn draw_internal(
+        &self,
+        instance_count: Option<usize>,
+        geometry: &GpuGeometryBuffer,
+        viewport: Rect<i32>,
+        program: &GpuProgram,
+        params: &DrawParameters,
+        resources: &[ResourceBindGroup],
+        element_range: ElementRange,
+    ) -> Result<DrawCallStatistics, FrameworkError> {
+        let capture_geometry = CaptureGeometryBuffer::cast(geometry)?;
+        let capture_program = CaptureProgram::cast(program)?;
+
+        // Map the resources to the objects of the underlying server.
+        let mut inner_bindings = Vec::with_capacity(resources.len());
+        for group in resources {
+            let mut bindings = Vec::with_capacity(group.bindings.len());
+            for binding in group.bindings {
+                bindings.push(match binding {
+                    ResourceBinding::Texture {
+                        texture,
+                        sampler,
+                        binding,
+                    } => ResourceBinding::Texture {
+                        texture: CaptureTexture::cast(texture)?.inner.clone(),
+                        sampler: CaptureSampler::cast(sampler)?.inner.clone(),
+                        binding: *binding,
+                    },
+                    ResourceBinding::Buffer {
+                        buffer,
+                        binding,
+                        data_usage,
+                    } => ResourceBinding::Buffer {
+                        buffer: CaptureBuffer::cast(buffer)?.inner.clone(),
+                        binding: *binding,
+                        data_usage: *data_usage,
+                    },
+                });
+            }
+            inner_bindings.push(bindings);
+        }
+        let inner_resources = inner_bindings
+            .iter()
+            .map(|bindings| ResourceBindGroup { bindings })
+            .collect::<Vec<_>>();
+
+        let statistics = match instance_count {
+            Some(instance_count) => self.inner.draw_instances(
+                instance_count,
+                &capture_geometry.inner,
+                viewport,
+                &capture_program.inner,
+                params,
+                &inner_resources,
+                element_range,
+            ),
+            None => self.inner.draw(
+                &capture_geometry.inner,
+                viewport,
+                &capture_program.inner,
+                params,
+                &inner_resources,
+                element_range,
+            ),
+        }?;
+
+        if self.recorder.is_capturing() {
+            self.ensure_recorded();
+            capture_geometry.ensure_recorded();
+            capture_program.ensure_recorded();
+
+            let mut group_records = Vec::with_capacity(resources.len());
+            for group in resources {
+                let mut bindings = Vec::with_capacity(group.bindings.len());
+                for binding in group.bindings {
+                    bindings.push(match binding {
+                        ResourceBinding::Texture {
+                            texture,
+                            sampler,
+                            binding,
+                        } => {
+                            let texture = CaptureTexture::cast(texture)?;
+                            let sampler = CaptureSampler::cast(sampler)?;
+                            texture.ensure_recorded();
+                            sampler.ensure_recorded();
+                            BindingRecord::Texture {
+                                binding: *binding,
+                                texture: texture.id(),
+                                sampler: sampler.id(),
+                            }
+                        }
+                        ResourceBinding::Buffer {
+                            buffer,
+                            binding,
+                            data_usage,
+                        } => {
+                            let buffer = CaptureBuffer::cast(buffer)?;
+                            buffer.ensure_recorded();
+                            BindingRecord::Buffer {
+                                binding: *binding,
+                                buffer: buffer.id(),
+                                data_usage: *data_usage,
+                            }
+                        }
+                    });
+                }
+                group_records.push(BindGroupRecord { bindings });
+            }
+
+            self.recorder.push(CaptureCommand::Draw(DrawRecord {
+                framebuffer: self.id,
+                geometry: capture_geometry.id(),
+                program: capture_program.id(),
+                viewport,
+                params: params.clone(),
+                resources: group_records,
+                element_range,
+                instance_count,
+            }));
+        }
+
+        Ok(statistics)
+    }
+}
+
+impl GpuFrameBufferTrait for CaptureFrameBuffer {
+    fn color_attachments(&self) -> &[Attachment] {
+        &self.color_attachments
+    }
+
+    fn depth_attachment(&self) -> Option<&Attachment> {
+        self.depth_attachment.as_ref()
+    }
+
+    fn set_cubemap_face(&self, attachment_index: usize, face: CubeMapFace, level: usize) {
+        self.inner.set_cubemap_face(attachment_index, face, level);
+        if self.recorder.is_capturing() {
+            self.ensure_recorded();
+            self.recorder.push(CaptureCommand::SetCubeMapFace {
+                framebuffer: self.id,
+                attachment_index,
+                face,
+                level,
+            });
+        }
+    }
+
+    fn blit_to(
+        &self,
+        dest: &GpuFrameBuffer,
+        src_x0: i32,
+        src_y0: i32,
+        src_x1: i32,
+        src_y1: i32,
+        dst_x0: i32,
+        dst_y0: i32,
+        dst_x1: i32,
+        dst_y1: i32,
+        copy_color: bool,
+        copy_depth: bool,
+        copy_stencil: bool,
+    ) {
+        let Ok(dest) = Self::cast(dest) else {
+            return;
+        };
+        self.inner.blit_to(
+            &dest.inner,
+            src_x0,
+            src_y0,
+            src_x1,
+            src_y1,
+            dst_x0,
+            dst_y0,
+            dst_x1,
+            dst_y1,
+            copy_color,
+            copy_depth,
+            copy_stencil,
+        );
+        if self.recorder.is_capturing() {
+            self.ensure_recorded();
+            dest.ensure_recorded();
+            self.recorder.push(CaptureCommand::Blit(BlitRecord {
+                source: self.id,
+                dest: dest.id,
+                source_rect: [src_x0, src_y0, src_x1, src_y1],
+                dest_rect: [dst_x0, dst_y0, dst_x1, dst_y1],
+                copy_color,
+                copy_depth,
+                copy_stencil,
+            }));
+        }
+    }
+
+    fn clear(
+        &self,
+        viewport: Rect<i32>,
+        color: Option<Color>,
+        depth: Option<f32>,
+        stencil: Option<i32>,
+    ) {
+        self.inner.clear(viewport, color, depth, stencil);
+        if self.recorder.is_capturing() {
+            self.ensure_recorded();
+            self.recorder.push(CaptureCommand::Clear(ClearRecord {
+                framebuffer: self.id,
+                viewport,
+                color,
+                depth,
+                stencil,
+            }));
+        }
+    }
+
+    fn read_pixels(&self, read_target: ReadTarget) -> Option<Vec<u8>> {
+        self.inner.read_pixels(read_target)
+    }
+
+    fn draw(
+        &self,
+        geometry: &GpuGeometryBuffer,
+        viewport: Rect<i32>,
+        program: &GpuProgram,
+        params: &DrawParameters,
+        resources: &[ResourceBindGroup],
+        element_range: ElementRange,
+    ) -> Result<DrawCallStatistics, FrameworkError> {
+        self.draw_internal(
+            None,
+            geometry,
+            viewport,
+            program,
+            params,
+            resources,
+            element_range,
+        )
+    }
+
+    fn draw_instances(
+        &self,
+        instance_count: usize,
+        geometry: &GpuGeometryBuffer,
+        viewport: Rect<i32>,
+        program: &GpuProgram,
+        params: &DrawParameters,
+        resources: &[ResourceBindGroup],
+        element_range: ElementRange,
+    ) -> Result<DrawCallStatistics, FrameworkError> {
+        self.draw_internal(
+            Some(instance_count),
+            geometry,
+            viewport,
+            program,
+            params,
+            resources,
+            element_range,
+        )
+    }
+}
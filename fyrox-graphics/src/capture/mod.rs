@@ -0,0 +1,617 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Frame capture allows to record everything that a renderer submits to a graphics server during
+//! a frame (resource creations, data uploads, clears, blits and draw calls) and replay it later,
+//! step by step, on any other graphics server. It is useful to find out why a frame looks wrong.
+//! See [`server::CaptureGraphicsServer`] and [`replay::FrameReplayer`] docs for more info.
+
+#![warn(missing_docs)]
+
+use crate::{
+    buffer::{BufferKind, BufferUsage},
+    core::{
+        color::Color,
+        math::Rect,
+        visitor::{
+            pod::{Pod, PodVecView},
+            prelude::*,
+        },
+    },
+    framebuffer::{AttachmentKind, BufferDataUsage},
+    geometry_buffer::AttributeDefinition,
+    gpu_program::ShaderResourceDefinition,
+    gpu_texture::{CubeMapFace, GpuTextureKind, PixelKind},
+    sampler::GpuSamplerDescriptor,
+    DrawParameters, ElementKind, ElementRange, PolygonFace, PolygonFillMode,
+};
+use std::path::Path;
+
+mod framebuffer;
+mod resources;
+
+pub mod replay;
+pub mod server;
+
+/// A plain array of data, that is serialized as a single binary blob.
+#[derive(Default, Clone, Debug, PartialEq)]
+pub struct CaptureData<T: Pod>(pub Vec<T>);
+
+impl<T: Pod> Visit for CaptureData<T> {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        PodVecView::from_pod_vec(&mut self.0).visit(name, visitor)
+    }
+}
+
+/// State of a GPU buffer.
+#[derive(Clone, Debug, PartialEq, Visit)]
+pub struct BufferRecord {
+    /// Size of the buffer in bytes.
+    pub size: usize,
+    /// Kind of the buffer.
+    pub kind: BufferKind,
+    /// Usage of the buffer.
+    pub usage: BufferUsage,
+    /// Content of the buffer. Could be empty if no data was written to the buffer.
+    pub data: CaptureData<u8>,
+}
+
+impl Default for BufferRecord {
+    fn default() -> Self {
+        Self {
+            size: 0,
+            kind: BufferKind::Uniform,
+            usage: BufferUsage::StreamDraw,
+            data: Default::default(),
+        }
+    }
+}
+
+/// State of a GPU texture.
+#[derive(Clone, Debug, PartialEq, Visit)]
+pub struct TextureRecord {
+    /// Kind of the texture.
+    pub kind: GpuTextureKind,
+    /// Pixel kind of the texture.
+    pub pixel_kind: PixelKind,
+    /// Total number of mip levels of the texture.
+    pub mip_count: usize,
+    /// Index of the lowest defined mip level.
+    pub base_level: usize,
+    /// Index of the highest defined mip level.
+    pub max_level: usize,
+    /// Content of the texture. [`None`] means that the content is undefined (render targets, for
+    /// example).
+    pub data: Option<CaptureData<u8>>,
+}
+
+impl Default for TextureRecord {
+    fn default() -> Self {
+        Self {
+            kind: GpuTextureKind::Rectangle {
+                width: 1,
+                height: 1,
+            },
+            pixel_kind: PixelKind::RGBA8,
+            mip_count: 1,
+            base_level: 0,
+            max_level: 1000,
+            data: None,
+        }
+    }
+}
+
+/// An attachment of a frame buffer.
+#[derive(Default, Clone, Debug, PartialEq, Visit)]
+pub struct AttachmentRecord {
+    /// Kind of the attachment.
+    pub kind: AttachmentKind,
+    /// Id of the attached texture.
+    pub texture: u64,
+}
+
+/// A vertex buffer of a geometry buffer.
+#[derive(Clone, Debug, PartialEq, Visit)]
+pub struct VertexBufferRecord {
+    /// Usage of the buffer.
+    pub usage: BufferUsage,
+    /// Layout of a single vertex.
+    pub attributes: Vec<AttributeDefinition>,
+    /// Size of a single vertex in bytes.
+    pub element_size: usize,
+    /// Content of the buffer.
+    pub data: CaptureData<u8>,
+}
+
+impl Default for VertexBufferRecord {
+    fn default() -> Self {
+        Self {
+            usage: BufferUsage::StaticDraw,
+            attributes: Default::default(),
+            element_size: 0,
+            data: Default::default(),
+        }
+    }
+}
+
+/// State of a geometry buffer.
+#[derive(Clone, Debug, PartialEq, Visit)]
+pub struct GeometryBufferRecord {
+    /// Usage of the element buffer.
+    pub usage: BufferUsage,
+    /// Kind of the elements of the buffer.
+    pub element_kind: ElementKind,
+    /// Vertex buffers.
+    pub buffers: Vec<VertexBufferRecord>,
+    /// A flat list of vertex indices of all the elements.
+    pub elements: CaptureData<u32>,
+}
+
+impl Default for GeometryBufferRecord {
+    fn default() -> Self {
+        Self {
+            usage: BufferUsage::StaticDraw,
+            element_kind: Default::default(),
+            buffers: Default::default(),
+            elements: Default::default(),
+        }
+    }
+}
+
+/// Source code and resources of a GPU program.
+#[derive(Default, Clone, Debug, PartialEq, Visit)]
+pub struct ProgramRecord {
+    /// Name of the program.
+    pub name: String,
+    /// Source code of the vertex shader.
+    pub vertex_source: String,
+    /// Line offset of the vertex shader.
+    pub vertex_source_line_offset: isize,
+    /// Source code of the fragment shader.
+    pub fragment_source: String,
+    /// Line offset of the fragment shader.
+    pub fragment_source_line_offset: isize,
+    /// Resources of the program.
+    pub resources: Vec<ShaderResourceDefinition>,
+}
+
+/// A resource, that was bound to the pipeline during a draw call.
+#[derive(Clone, Debug, PartialEq, Visit)]
+pub enum BindingRecord {
+    /// Texture binding.
+    Texture {
+        /// Binding point of the texture.
+        binding: usize,
+        /// Id of the texture.
+        texture: u64,
+        /// Id of the sampler.
+        sampler: u64,
+    },
+    /// Buffer binding.
+    Buffer {
+        /// Binding point of the buffer.
+        binding: usize,
+        /// Id of the buffer.
+        buffer: u64,
+        /// Used portion of the buffer.
+        data_usage: BufferDataUsage,
+    },
+}
+
+impl Default for BindingRecord {
+    fn default() -> Self {
+        Self::Buffer {
+            binding: 0,
+            buffer: 0,
+            data_usage: Default::default(),
+        }
+    }
+}
+
+/// A group of resource bindings (see [`crate::framebuffer::ResourceBindGroup`]).
+#[derive(Default, Clone, Debug, PartialEq, Visit)]
+pub struct BindGroupRecord {
+    /// Bindings of the group.
+    pub bindings: Vec<BindingRecord>,
+}
+
+/// A draw call.
+#[derive(Default, Clone, Debug, PartialEq, Visit)]
+pub struct DrawRecord {
+    /// Id of the target frame buffer.
+    pub framebuffer: u64,
+    /// Id of the drawn geometry buffer.
+    pub geometry: u64,
+    /// Id of the used program.
+    pub program: u64,
+    /// Viewport of the draw call.
+    pub viewport: Rect<i32>,
+    /// Pipeline state of the draw call.
+    pub params: DrawParameters,
+    /// Resources bound to the pipeline.
+    pub resources: Vec<BindGroupRecord>,
+    /// Drawn range of elements.
+    pub element_range: ElementRange,
+    /// Number of instances for instanced draw calls, [`None`] for regular ones.
+    pub instance_count: Option<usize>,
+}
+
+/// A frame buffer clear.
+#[derive(Default, Clone, Debug, PartialEq, Visit)]
+pub struct ClearRecord {
+    /// Id of the frame buffer.
+    pub framebuffer: u64,
+    /// Viewport of the clear.
+    pub viewport: Rect<i32>,
+    /// Clear color.
+    pub color: Option<Color>,
+    /// Clear depth.
+    pub depth: Option<f32>,
+    /// Clear stencil value.
+    pub stencil: Option<i32>,
+}
+
+/// A copy of the content of a frame buffer to another.
+#[derive(Default, Clone, Debug, PartialEq, Visit)]
+pub struct BlitRecord {
+    /// Id of the source frame buffer.
+    pub source: u64,
+    /// Id of the destination frame buffer.
+    pub dest: u64,
+    /// Source rectangle in `[x0, y0, x1, y1]` format.
+    pub source_rect: [i32; 4],
+    /// Destination rectangle in `[x0, y0, x1, y1]` format.
+    pub dest_rect: [i32; 4],
+    /// Whether the color was copied or not.
+    pub copy_color: bool,
+    /// Whether the depth was copied or not.
+    pub copy_depth: bool,
+    /// Whether the stencil was copied or not.
+    pub copy_stencil: bool,
+}
+
+/// A single command, that was submitted to a graphics server.
+#[derive(Clone, Debug, PartialEq, Visit)]
+pub enum CaptureCommand {
+    /// Creation of a GPU buffer.
+    CreateBuffer {
+        /// Id of the buffer.
+        #[visit(rename = "ObjectId")]
+        id: u64,
+        /// Initial state of the buffer.
+        buffer: BufferRecord,
+    },
+    /// Upload of the data to a GPU buffer.
+    WriteBuffer {
+        /// Id of the buffer.
+        #[visit(rename = "ObjectId")]
+        id: u64,
+        /// New content of the buffer.
+        data: CaptureData<u8>,
+    },
+    /// Creation of a texture.
+    CreateTexture {
+        /// Id of the texture.
+        #[visit(rename = "ObjectId")]
+        id: u64,
+        /// Initial state of the texture.
+        texture: TextureRecord,
+    },
+    /// Upload of the data to a texture.
+    SetTextureData {
+        /// Id of the texture.
+        #[visit(rename = "ObjectId")]
+        id: u64,
+        /// New state of the texture.
+        texture: TextureRecord,
+    },
+    /// Creation of a sampler.
+    CreateSampler {
+        /// Id of the sampler.
+        #[visit(rename = "ObjectId")]
+        id: u64,
+        /// Sampler options.
+        descriptor: GpuSamplerDescriptor,
+    },
+    /// Creation of a frame buffer.
+    CreateFrameBuffer {
+        /// Id of the frame buffer.
+        #[visit(rename = "ObjectId")]
+        id: u64,
+        /// Optional depth attachment.
+        depth_attachment: Option<AttachmentRecord>,
+        /// Color attachments.
+        color_attachments: Vec<AttachmentRecord>,
+    },
+    /// Change of a cube map face that is used as a render target.
+    SetCubeMapFace {
+        /// Id of the frame buffer.
+        framebuffer: u64,
+        /// Index of the color attachment.
+        attachment_index: usize,
+        /// New face.
+        face: CubeMapFace,
+        /// Mip level of the face.
+        level: usize,
+    },
+    /// Creation of a geometry buffer.
+    CreateGeometryBuffer {
+        /// Id of the geometry buffer.
+        #[visit(rename = "ObjectId")]
+        id: u64,
+        /// Initial state of the geometry buffer.
+        geometry: GeometryBufferRecord,
+    },
+    /// Upload of the data to a vertex buffer of a geometry buffer.
+    SetGeometryBufferData {
+        /// Id of the geometry buffer.
+        #[visit(rename = "ObjectId")]
+        id: u64,
+        /// Index of the vertex buffer.
+        buffer: usize,
+        /// New content of the vertex buffer.
+        data: CaptureData<u8>,
+    },
+    /// Upload of the elements of a geometry buffer.
+    SetElements {
+        /// Id of the geometry buffer.
+        #[visit(rename = "ObjectId")]
+        id: u64,
+        /// Kind of the elements.
+        element_kind: ElementKind,
+        /// A flat list of vertex indices of all the elements.
+        elements: CaptureData<u32>,
+    },
+    /// Creation of a GPU program.
+    CreateProgram {
+        /// Id of the program.
+        #[visit(rename = "ObjectId")]
+        id: u64,
+        /// Source code of the program.
+        program: ProgramRecord,
+    },
+    /// Change of the polygon fill mode.
+    SetPolygonFillMode {
+        /// Affected polygon face.
+        face: PolygonFace,
+        /// New fill mode.
+        mode: PolygonFillMode,
+    },
+    /// Frame buffer clear.
+    Clear(ClearRecord),
+    /// Copy of a frame buffer content.
+    Blit(BlitRecord),
+    /// A draw call.
+    Draw(DrawRecord),
+}
+
+impl Default for CaptureCommand {
+    // Required for deserialization only.
+    fn default() -> Self {
+        Self::SetPolygonFillMode {
+            face: Default::default(),
+            mode: Default::default(),
+        }
+    }
+}
+
+/// Everything that was submitted to a graphics server during a frame. Resources that were created
+/// before the capture has started and used during the frame are recorded as well (with their
+/// state at the moment of the first use). See [`server::CaptureGraphicsServer`] docs for more info.
+#[derive(Default, Clone, Debug, PartialEq, Visit)]
+pub struct FrameCapture {
+    /// Width of the back buffer.
+    pub frame_width: u32,
+    /// Height of the back buffer.
+    pub frame_height: u32,
+    /// A list of the submitted commands in submission order.
+    pub commands: Vec<CaptureCommand>,
+}
+
+impl FrameCapture {
+    /// Id of the back buffer in the commands.
+    pub const BACK_BUFFER_ID: u64 = 0;
+
+    /// Serializes the capture into a binary blob.
+    pub fn save_to_vec(&mut self) -> Result<Vec<u8>, VisitError> {
+        let mut visitor = Visitor::new();
+        self.visit("FrameCapture", &mut visitor)?;
+        visitor.save_binary_to_vec()
+    }
+
+    /// Saves the capture to a file with the given path.
+    pub fn save(&mut self, path: impl AsRef<Path>) -> VisitResult {
+        let mut visitor = Visitor::new();
+        self.visit("FrameCapture", &mut visitor)?;
+        visitor.save_binary_to_file(path)
+    }
+
+    /// Deserializes a capture from the data created by [`Self::save_to_vec`] or [`Self::save`].
+    pub fn load_from_memory(data: &[u8]) -> Result<Self, VisitError> {
+        let mut visitor = Visitor::load_binary_from_memory(data)?;
+        let mut capture = Self::default();
+        capture.visit("FrameCapture", &mut visitor)?;
+        Ok(capture)
+    }
+
+    /// Loads a capture from a file with the given path.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, VisitError> {
+        Self::load_from_memory(&std::fs::read(path)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        buffer::{BufferKind, BufferUsage},
+        capture::{
+            replay::FrameReplayer, server::CaptureGraphicsServer, CaptureCommand, FrameCapture,
+        },
+        core::{
+            algebra::Vector4,
+            color::Color,
+            math::{Rect, TriangleDefinition},
+        },
+        framebuffer::{BufferDataUsage, ReadTarget, ResourceBindGroup, ResourceBinding},
+        geometry_buffer::{
+            AttributeDefinition, AttributeKind, ElementsDescriptor, GeometryBufferDescriptor,
+            VertexBufferData, VertexBufferDescriptor,
+        },
+        server::GraphicsServer,
+        software::{
+            server::SoftwareGraphicsServer,
+            shader::{SoftwareShader, Varyings},
+        },
+        uniform::StaticUniformBuffer,
+        DrawParameters, ElementRange,
+    };
+    use std::rc::Rc;
+
+    fn software_server() -> Rc<SoftwareGraphicsServer> {
+        let server = SoftwareGraphicsServer::new((4, 4));
+        server.register_shader(
+            "SolidColor",
+            SoftwareShader::new(
+                |_, vertex| (vertex.position(0), Varyings::new()).into(),
+                |ctx, _| ctx.uniforms(0).read::<Vector4<f32>>(),
+            ),
+        );
+        server
+    }
+
+    #[test]
+    fn test_capture_and_replay() {
+        let software = software_server();
+        let server = CaptureGraphicsServer::new(software.clone());
+
+        // Created before the capture, must be recorded on first use.
+        let vertices = [-1.0f32, -1.0, 0.0, 1.0, -1.0, 0.0, -1.0, 1.0, 0.0];
+        let geometry = server
+            .create_geometry_buffer(GeometryBufferDescriptor {
+                buffers: &[VertexBufferDescriptor {
+                    usage: BufferUsage::StaticDraw,
+                    attributes: &[AttributeDefinition {
+                        location: 0,
+                        kind: AttributeKind::Float,
+                        component_count: 3,
+                        normalized: false,
+                        divisor: 0,
+                    }],
+                    data: VertexBufferData {
+                        element_size: 3 * size_of::<f32>(),
+                        bytes: Some(crate::core::array_as_u8_slice(&vertices)),
+                    },
+                }],
+                usage: BufferUsage::StaticDraw,
+                elements: ElementsDescriptor::Triangles(&[TriangleDefinition([0, 1, 2])]),
+            })
+            .unwrap();
+        let program = server
+            .create_program(
+                "SolidColor",
+                "void main() {}".to_string(),
+                0,
+                "void main() {}".to_string(),
+                0,
+                &[],
+            )
+            .unwrap();
+        // Not used during the frame, must not be recorded.
+        let _unused = server
+            .create_buffer(16, BufferKind::Uniform, BufferUsage::StaticDraw)
+            .unwrap();
+
+        server.capture_next_frame();
+        server.swap_buffers().unwrap();
+        assert!(server.is_capturing());
+
+        let color = server
+            .create_buffer(16, BufferKind::Uniform, BufferUsage::StreamDraw)
+            .unwrap();
+        color
+            .write_data(
+                &StaticUniformBuffer::<16>::new()
+                    .with(&Vector4::new(1.0f32, 0.0, 0.0, 1.0))
+                    .finish(),
+            )
+            .unwrap();
+        let back_buffer = server.back_buffer();
+        back_buffer.clear(Rect::new(0, 0, 4, 4), Some(Color::BLUE), Some(1.0), None);
+        let stats = back_buffer
+            .draw(
+                &geometry,
+                Rect::new(0, 0, 4, 4),
+                &program,
+                &DrawParameters::default(),
+                &[ResourceBindGroup {
+                    bindings: &[ResourceBinding::buffer(
+                        &color,
+                        0,
+                        BufferDataUsage::UseEverything,
+                    )],
+                }],
+                ElementRange::Full,
+            )
+            .unwrap();
+        assert_eq!(stats.triangles, 1);
+        let expected = back_buffer.read_pixels(ReadTarget::Color(0)).unwrap();
+
+        server.swap_buffers().unwrap();
+        assert!(!server.is_capturing());
+
+        let mut capture = server.take_capture().unwrap();
+        assert_eq!((capture.frame_width, capture.frame_height), (4, 4));
+        assert!(matches!(
+            capture.commands.as_slice(),
+            [
+                CaptureCommand::CreateBuffer { .. },
+                CaptureCommand::WriteBuffer { .. },
+                CaptureCommand::Clear(_),
+                CaptureCommand::CreateGeometryBuffer { .. },
+                CaptureCommand::CreateProgram { .. },
+                CaptureCommand::Draw(_),
+            ]
+        ));
+
+        let data = capture.save_to_vec().unwrap();
+        let loaded = FrameCapture::load_from_memory(&data).unwrap();
+        assert_eq!(loaded, capture);
+
+        let replay_server = software_server();
+        let mut replayer = FrameReplayer::new(replay_server.clone(), loaded);
+        let dumps = replayer.run_and_dump().unwrap();
+        assert!(replayer.is_finished());
+        assert_eq!(dumps.len(), 2);
+        assert_eq!(dumps[0].position, 3);
+        assert_eq!(dumps[0].framebuffer, FrameCapture::BACK_BUFFER_ID);
+        assert!(dumps[0].color_attachments[0]
+            .pixels
+            .chunks(4)
+            .all(|pixel| pixel == [0, 0, 255, 255]));
+        assert_eq!(dumps[1].color_attachments[0].pixels, expected);
+        assert_eq!(
+            replay_server
+                .back_buffer()
+                .read_pixels(ReadTarget::Color(0))
+                .unwrap(),
+            expected
+        );
+    }
+}
@@ -0,0 +1,484 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Replay of frame captures. See [`FrameReplayer`] docs for more info.
+
+use crate::{
+    buffer::GpuBuffer,
+    capture::{
+        AttachmentRecord, BindingRecord, CaptureCommand, DrawRecord, FrameCapture,
+        GeometryBufferRecord, TextureRecord,
+    },
+    core::math::TriangleDefinition,
+    error::FrameworkError,
+    framebuffer::{
+        Attachment, AttachmentKind, GpuFrameBuffer, ReadTarget, ResourceBindGroup, ResourceBinding,
+    },
+    geometry_buffer::{
+        ElementsDescriptor, GeometryBufferDescriptor, GpuGeometryBuffer, VertexBufferData,
+        VertexBufferDescriptor,
+    },
+    gpu_program::GpuProgram,
+    gpu_texture::{GpuTexture, GpuTextureDescriptor, GpuTextureKind, PixelKind},
+    sampler::GpuSampler,
+    server::SharedGraphicsServer,
+    ElementKind,
+};
+use fxhash::FxHashMap;
+
+/// Content of a single attachment of a frame buffer.
+#[derive(Clone, Debug)]
+pub struct AttachmentDump {
+    /// Kind of the attachment.
+    pub kind: AttachmentKind,
+    /// Kind of the attachment texture.
+    pub texture_kind: GpuTextureKind,
+    /// Pixel kind of the attachment texture.
+    pub pixel_kind: PixelKind,
+    /// Raw pixels of the attachment.
+    pub pixels: Vec<u8>,
+}
+
+/// Content of a frame buffer at a particular step of a replay.
+#[derive(Clone, Debug)]
+pub struct FrameBufferDump {
+    /// Index of the command after which the dump was made.
+    pub position: usize,
+    /// Id of the frame buffer.
+    pub framebuffer: u64,
+    /// Content of the color attachments.
+    pub color_attachments: Vec<AttachmentDump>,
+    /// Content of the depth attachment, if any.
+    pub depth_attachment: Option<AttachmentDump>,
+}
+
+/// Frame replayer executes the commands of a [`FrameCapture`] on the given graphics server one by
+/// one. It allows inspecting the contents of the frame buffers between the commands, which helps
+/// to find which draw call breaks the frame.
+///
+/// ## Example
+///
+/// ```rust
+/// use fyrox_graphics::{
+///     capture::{replay::FrameReplayer, FrameCapture},
+///     null::server::NullGraphicsServer,
+/// };
+///
+/// fn inspect(capture: FrameCapture) {
+///     let mut replayer = FrameReplayer::new(NullGraphicsServer::new((1, 1)), capture);
+///     while let Ok(true) = replayer.step() {
+///         println!("{:?}", replayer.commands()[replayer.position() - 1]);
+///     }
+/// }
+/// ```
+pub struct FrameReplayer {
+    server: SharedGraphicsServer,
+    capture: FrameCapture,
+    position: usize,
+    buffers: FxHashMap<u64, GpuBuffer>,
+    textures: FxHashMap<u64, GpuTexture>,
+    samplers: FxHashMap<u64, GpuSampler>,
+    framebuffers: FxHashMap<u64, GpuFrameBuffer>,
+    geometries: FxHashMap<u64, GpuGeometryBuffer>,
+    programs: FxHashMap<u64, GpuProgram>,
+}
+
+fn get<T: Clone>(map: &FxHashMap<u64, T>, id: u64, kind: &str) -> Result<T, FrameworkError> {
+    map.get(&id).cloned().ok_or_else(|| {
+        FrameworkError::Custom(format!("The capture refers to an unknown {kind} {id}!"))
+    })
+}
+
+fn create_elements<R>(
+    kind: ElementKind,
+    elements: &[u32],
+    func: impl FnOnce(ElementsDescriptor) -> R,
+) -> R {
+    match kind {
+        ElementKind::Triangle => func(ElementsDescriptor::Triangles(
+            &elements
+                .chunks_exact(3)
+                .map(|t| TriangleDefinition([t[0], t[1], t[2]]))
+                .collect::<Vec<_>>(),
+        )),
+        ElementKind::Line => func(ElementsDescriptor::Lines(
+            &elements
+                .chunks_exact(2)
+                .map(|l| [l[0], l[1]])
+                .collect::<Vec<_>>(),
+        )),
+        ElementKind::Point => func(ElementsDescriptor::Points(elements)),
+    }
+}
+
+impl FrameReplayer {
+    /// Creates a new replayer for the given capture. The frame size of the server is set to the
+    /// frame size of the capture.
+    pub fn new(server: SharedGraphicsServer, capture: FrameCapture) -> Self {
+        server.set_frame_size((capture.frame_width, capture.frame_height));
+        Self {
+            server,
+            capture,
+            position: 0,
+            buffers: Default::default(),
+            textures: Default::default(),
+            samplers: Default::default(),
+            framebuffers: Default::default(),
+            geometries: Default::default(),
+            programs: Default::default(),
+        }
+    }
+
+    /// Returns the commands of the capture.
+    pub fn commands(&self) -> &[CaptureCommand] {
+        &self.capture.commands
+    }
+
+    /// Returns the index of the next command to execute.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns `true` if all the commands were executed.
+    pub fn is_finished(&self) -> bool {
+        self.position >= self.capture.commands.len()
+    }
+
+    /// Returns the server on which the commands are executed.
+    pub fn server(&self) -> &SharedGraphicsServer {
+        &self.server
+    }
+
+    /// Returns a frame buffer with the given id, if it was created already.
+    pub fn frame_buffer(&self, id: u64) -> Option<GpuFrameBuffer> {
+        if id == FrameCapture::BACK_BUFFER_ID {
+            Some(self.server.back_buffer())
+        } else {
+            self.framebuffers.get(&id).cloned()
+        }
+    }
+
+    /// Returns a texture with the given id, if it was created already.
+    pub fn texture(&self, id: u64) -> Option<GpuTexture> {
+        self.textures.get(&id).cloned()
+    }
+
+    fn create_texture(&self, record: &TextureRecord) -> Result<GpuTexture, FrameworkError> {
+        self.server.create_texture(GpuTextureDescriptor {
+            kind: record.kind,
+            pixel_kind: record.pixel_kind,
+            mip_count: record.mip_count,
+            data: record.data.as_ref().map(|data| data.0.as_slice()),
+            base_level: record.base_level,
+            max_level: record.max_level,
+        })
+    }
+
+    fn create_geometry(
+        &self,
+        record: &GeometryBufferRecord,
+    ) -> Result<GpuGeometryBuffer, FrameworkError> {
+        let buffers = record
+            .buffers
+            .iter()
+            .map(|buffer| VertexBufferDescriptor {
+                usage: buffer.usage,
+                attributes: &buffer.attributes,
+                data: VertexBufferData {
+                    element_size: buffer.element_size,
+                    bytes: Some(&buffer.data.0),
+                },
+            })
+            .collect::<Vec<_>>();
+        create_elements(record.element_kind, &record.elements.0, |elements| {
+            self.server
+                .create_geometry_buffer(GeometryBufferDescriptor {
+                    buffers: &buffers,
+                    usage: record.usage,
+                    elements,
+                })
+        })
+    }
+
+    fn attachment(&self, record: &AttachmentRecord) -> Result<Attachment, FrameworkError> {
+        Ok(Attachment {
+            kind: record.kind,
+            texture: get(&self.textures, record.texture, "texture")?,
+        })
+    }
+
+    fn get_frame_buffer(&self, id: u64) -> Result<GpuFrameBuffer, FrameworkError> {
+        self.frame_buffer(id).ok_or_else(|| {
+            FrameworkError::Custom(format!(
+                "The capture refers to an unknown frame buffer {id}!"
+            ))
+        })
+    }
+
+    fn draw(&self, draw: &DrawRecord) -> Result<(), FrameworkError> {
+        let framebuffer = self.get_frame_buffer(draw.framebuffer)?;
+        let geometry = get(&self.geometries, draw.geometry, "geometry buffer")?;
+        let program = get(&self.programs, draw.program, "program")?;
+        let mut groups = Vec::with_capacity(draw.resources.len());
+        for group in draw.resources.iter() {
+            let mut bindings = Vec::with_capacity(group.bindings.len());
+            for binding in group.bindings.iter() {
+                bindings.push(match binding {
+                    BindingRecord::Texture {
+                        binding,
+                        texture,
+                        sampler,
+                    } => ResourceBinding::Texture {
+                        texture: get(&self.textures, *texture, "texture")?,
+                        sampler: get(&self.samplers, *sampler, "sampler")?,
+                        binding: *binding,
+                    },
+                    BindingRecord::Buffer {
+                        binding,
+                        buffer,
+                        data_usage,
+                    } => ResourceBinding::Buffer {
+                        buffer: get(&self.buffers, *buffer, "buffer")?,
+                        binding: *binding,
+                        data_usage: *data_usage,
+                    },
+                });
+            }
+            groups.push(bindings);
+        }
+        let resources = groups
+            .iter()
+            .map(|bindings| ResourceBindGroup { bindings })
+            .collect::<Vec<_>>();
+        let _statistics = match draw.instance_count {
+            Some(instance_count) => framebuffer.draw_instances(
+                instance_count,
+                &geometry,
+                draw.viewport,
+                &program,
+                &draw.params,
+                &resources,
+                draw.element_range,
+            )?,
+            None => framebuffer.draw(
+                &geometry,
+                draw.viewport,
+                &program,
+                &draw.params,
+                &resources,
+                draw.element_range,
+            )?,
+        };
+        Ok(())
+    }
+
+    fn execute(&mut self, index: usize) -> Result<(), FrameworkError> {
+        let command = &self.capture.commands[index];
+        match command {
+            CaptureCommand::CreateBuffer { id, buffer } => {
+                let gpu_buffer =
+                    self.server
+                        .create_buffer(buffer.size, buffer.kind, buffer.usage)?;
+                if !buffer.data.0.is_empty() {
+                    gpu_buffer.write_data(&buffer.data.0)?;
+                }
+                self.buffers.insert(*id, gpu_buffer);
+            }
+            CaptureCommand::WriteBuffer { id, data } => {
+                get(&self.buffers, *id, "buffer")?.write_data(&data.0)?;
+            }
+            CaptureCommand::CreateTexture { id, texture } => {
+                let gpu_texture = self.create_texture(texture)?;
+                self.textures.insert(*id, gpu_texture);
+            }
+            CaptureCommand::SetTextureData { id, texture } => {
+                get(&self.textures, *id, "texture")?.set_data(
+                    texture.kind,
+                    texture.pixel_kind,
+                    texture.mip_count,
+                    texture.data.as_ref().map(|data| data.0.as_slice()),
+                )?;
+            }
+            CaptureCommand::CreateSampler { id, descriptor } => {
+                let sampler = self.server.create_sampler(*descriptor)?;
+                self.samplers.insert(*id, sampler);
+            }
+            CaptureCommand::CreateFrameBuffer {
+                id,
+                depth_attachment,
+                color_attachments,
+            } => {
+                let framebuffer = self.server.create_frame_buffer(
+                    depth_attachment
+                        .as_ref()
+                        .map(|attachment| self.attachment(attachment))
+                        .transpose()?,
+                    color_attachments
+                        .iter()
+                        .map(|attachment| self.attachment(attachment))
+                        .collect::<Result<Vec<_>, _>>()?,
+                )?;
+                self.framebuffers.insert(*id, framebuffer);
+            }
+            CaptureCommand::SetCubeMapFace {
+                framebuffer,
+                attachment_index,
+                face,
+                level,
+            } => {
+                self.get_frame_buffer(*framebuffer)?.set_cubemap_face(
+                    *attachment_index,
+                    *face,
+                    *level,
+                );
+            }
+            CaptureCommand::CreateGeometryBuffer { id, geometry } => {
+                let gpu_geometry = self.create_geometry(geometry)?;
+                self.geometries.insert(*id, gpu_geometry);
+            }
+            CaptureCommand::SetGeometryBufferData { id, buffer, data } => {
+                get(&self.geometries, *id, "geometry buffer")?.set_buffer_data(*buffer, &data.0);
+            }
+            CaptureCommand::SetElements {
+                id,
+                element_kind,
+                elements,
+            } => {
+                let geometry = get(&self.geometries, *id, "geometry buffer")?;
+                create_elements(*element_kind, &elements.0, |elements| match elements {
+                    ElementsDescriptor::Triangles(triangles) => geometry.set_triangles(triangles),
+                    ElementsDescriptor::Lines(lines) => geometry.set_lines(lines),
+                    ElementsDescriptor::Points(points) => geometry.set_points(points),
+                });
+            }
+            CaptureCommand::CreateProgram { id, program } => {
+                let gpu_program = self.server.create_program(
+                    &program.name,
+                    program.vertex_source.clone(),
+                    program.vertex_source_line_offset,
+                    program.fragment_source.clone(),
+                    program.fragment_source_line_offset,
+                    &program.resources,
+                )?;
+                self.programs.insert(*id, gpu_program);
+            }
+            CaptureCommand::SetPolygonFillMode { face, mode } => {
+                self.server.set_polygon_fill_mode(*face, *mode);
+            }
+            CaptureCommand::Clear(clear) => {
+                self.get_frame_buffer(clear.framebuffer)?.clear(
+                    clear.viewport,
+                    clear.color,
+                    clear.depth,
+                    clear.stencil,
+                );
+            }
+            CaptureCommand::Blit(blit) => {
+                let dest = self.get_frame_buffer(blit.dest)?;
+                let [src_x0, src_y0, src_x1, src_y1] = blit.source_rect;
+                let [dst_x0, dst_y0, dst_x1, dst_y1] = blit.dest_rect;
+                self.get_frame_buffer(blit.source)?.blit_to(
+                    &dest,
+                    src_x0,
+                    src_y0,
+                    src_x1,
+                    src_y1,
+                    dst_x0,
+                    dst_y0,
+                    dst_x1,
+                    dst_y1,
+                    blit.copy_color,
+                    blit.copy_depth,
+                    blit.copy_stencil,
+                );
+            }
+            CaptureCommand::Draw(draw) => self.draw(draw)?,
+        }
+        Ok(())
+    }
+
+    /// Executes the next command. Returns `false` if there are no more commands to execute.
+    pub fn step(&mut self) -> Result<bool, FrameworkError> {
+        if self.is_finished() {
+            return Ok(false);
+        }
+        let index = self.position;
+        self.position += 1;
+        self.execute(index)?;
+        Ok(true)
+    }
+
+    /// Executes the commands until the given position is reached (the command at the position
+    /// is not executed).
+    pub fn run_to(&mut self, position: usize) -> Result<(), FrameworkError> {
+        while self.position < position && self.step()? {}
+        Ok(())
+    }
+
+    /// Executes all the remaining commands.
+    pub fn run(&mut self) -> Result<(), FrameworkError> {
+        while self.step()? {}
+        Ok(())
+    }
+
+    /// Reads the current content of the given frame buffer. Returns [`None`] if there is no such
+    /// frame buffer (yet).
+    pub fn dump_frame_buffer(&self, id: u64) -> Option<FrameBufferDump> {
+        let framebuffer = self.frame_buffer(id)?;
+        let dump = |attachment: &Attachment, target: ReadTarget| AttachmentDump {
+            kind: attachment.kind,
+            texture_kind: attachment.texture.kind(),
+            pixel_kind: attachment.texture.pixel_kind(),
+            pixels: framebuffer.read_pixels(target).unwrap_or_default(),
+        };
+        Some(FrameBufferDump {
+            position: self.position,
+            framebuffer: id,
+            color_attachments: framebuffer
+                .color_attachments()
+                .iter()
+                .enumerate()
+                .map(|(i, attachment)| dump(attachment, ReadTarget::Color(i)))
+                .collect(),
+            depth_attachment: framebuffer
+                .depth_attachment()
+                .map(|attachment| dump(attachment, ReadTarget::Depth)),
+        })
+    }
+
+    /// Executes all the remaining commands and dumps the content of the target frame buffer after
+    /// every clear, blit and draw call.
+    pub fn run_and_dump(&mut self) -> Result<Vec<FrameBufferDump>, FrameworkError> {
+        let mut dumps = Vec::new();
+        while !self.is_finished() {
+            let target = match &self.capture.commands[self.position] {
+                CaptureCommand::Clear(clear) => Some(clear.framebuffer),
+                CaptureCommand::Blit(blit) => Some(blit.dest),
+                CaptureCommand::Draw(draw) => Some(draw.framebuffer),
+                _ => None,
+            };
+            self.step()?;
+            if let Some(dump) = target.and_then(|target| self.dump_frame_buffer(target)) {
+                dumps.push(dump);
+            }
+        }
+        Ok(dumps)
+    }
+}
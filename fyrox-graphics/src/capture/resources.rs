@@ -0,0 +1,535 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Wrappers over the objects of a graphics server, that record every change of the objects.
+
+use crate::{
+    buffer::{BufferKind, BufferUsage, GpuBuffer, GpuBufferTrait},
+    capture::{
+        framebuffer::CaptureFrameBuffer, BufferRecord, CaptureCommand, CaptureData,
+        GeometryBufferRecord, ProgramRecord, TextureRecord, VertexBufferRecord,
+    },
+    core::math::{Rect, TriangleDefinition},
+    error::FrameworkError,
+    framebuffer::GpuFrameBufferTrait,
+    geometry_buffer::{
+        ElementsDescriptor, GeometryBufferDescriptor, GpuGeometryBuffer, GpuGeometryBufferTrait,
+    },
+    gpu_program::{GpuProgram, GpuProgramTrait},
+    gpu_texture::{GpuTexture, GpuTextureKind, GpuTextureTrait, PixelKind},
+    read_buffer::{GpuAsyncReadBuffer, GpuAsyncReadBufferTrait},
+    sampler::{GpuSampler, GpuSamplerDescriptor, GpuSamplerTrait},
+    ElementKind,
+};
+use fxhash::FxHashSet;
+use std::{
+    cell::{Cell, RefCell},
+    fmt::{Debug, Formatter},
+    rc::Rc,
+};
+
+/// Shared state of a capture, that is used by all the objects created by a capture server.
+pub(crate) struct Recorder {
+    next_id: Cell<u64>,
+    commands: RefCell<Option<Vec<CaptureCommand>>>,
+    recorded: RefCell<FxHashSet<u64>>,
+}
+
+impl Recorder {
+    pub(crate) fn new(first_id: u64) -> Self {
+        Self {
+            next_id: Cell::new(first_id),
+            commands: Default::default(),
+            recorded: Default::default(),
+        }
+    }
+
+    pub(crate) fn next_id(&self) -> u64 {
+        let id = self.next_id.get();
+        self.next_id.set(id + 1);
+        id
+    }
+
+    pub(crate) fn is_capturing(&self) -> bool {
+        self.commands.borrow().is_some()
+    }
+
+    pub(crate) fn begin(&self) {
+        *self.commands.borrow_mut() = Some(Vec::new());
+        self.recorded.borrow_mut().clear();
+    }
+
+    pub(crate) fn end(&self) -> Option<Vec<CaptureCommand>> {
+        self.recorded.borrow_mut().clear();
+        self.commands.borrow_mut().take()
+    }
+
+    pub(crate) fn push(&self, command: CaptureCommand) {
+        if let Some(commands) = self.commands.borrow_mut().as_mut() {
+            commands.push(command);
+        }
+    }
+
+    /// Records a creation of a new object.
+    pub(crate) fn push_creation(&self, id: u64, command: impl FnOnce() -> CaptureCommand) {
+        if self.is_capturing() {
+            self.recorded.borrow_mut().insert(id);
+            self.push(command());
+        }
+    }
+
+    /// Records the current state of an object, that was created before the capture has started.
+    /// Does nothing if the object is already recorded.
+    pub(crate) fn ensure_recorded(&self, id: u64, command: impl FnOnce() -> CaptureCommand) {
+        if self.is_capturing() && self.recorded.borrow_mut().insert(id) {
+            let command = command();
+            self.push(command);
+        }
+    }
+}
+
+fn foreign_object_error(kind: &str) -> FrameworkError {
+    FrameworkError::Custom(format!("The {kind} was not created by a capture server!"))
+}
+
+pub(crate) struct CaptureBuffer {
+    pub(crate) inner: GpuBuffer,
+    id: u64,
+    recorder: Rc<Recorder>,
+    record: RefCell<BufferRecord>,
+}
+
+impl CaptureBuffer {
+    pub(crate) fn new(inner: GpuBuffer, recorder: Rc<Recorder>) -> Self {
+        let buffer = Self {
+            id: recorder.next_id(),
+            record: RefCell::new(BufferRecord {
+                size: inner.size(),
+                kind: inner.kind(),
+                usage: inner.usage(),
+                data: Default::default(),
+            }),
+            inner,
+            recorder,
+        };
+        buffer
+            .recorder
+            .push_creation(buffer.id, || buffer.creation());
+        buffer
+    }
+
+    pub(crate) fn cast(buffer: &GpuBuffer) -> Result<&Self, FrameworkError> {
+        buffer
+            .as_any()
+            .downcast_ref::<Self>()
+            .ok_or_else(|| foreign_object_error("buffer"))
+    }
+
+    /// Returns a unique id of the buffer, that is used in capture records.
+    pub(crate) fn id(&self) -> u64 {
+        self.id
+    }
+
+    fn creation(&self) -> CaptureCommand {
+        CaptureCommand::CreateBuffer {
+            id: self.id,
+            buffer: self.record.borrow().clone(),
+        }
+    }
+
+    pub(crate) fn ensure_recorded(&self) {
+        self.recorder.ensure_recorded(self.id, || self.creation());
+    }
+}
+
+impl GpuBufferTrait for CaptureBuffer {
+    fn usage(&self) -> BufferUsage {
+        self.inner.usage()
+    }
+
+    fn kind(&self) -> BufferKind {
+        self.inner.kind()
+    }
+
+    fn size(&self) -> usize {
+        self.inner.size()
+    }
+
+    fn write_data(&self, data: &[u8]) -> Result<(), FrameworkError> {
+        self.inner.write_data(data)?;
+        self.ensure_recorded();
+        {
+            let mut record = self.record.borrow_mut();
+            record.size = self.inner.size();
+            record.data.0.clear();
+            record.data.0.extend_from_slice(data);
+        }
+        self.recorder.push(CaptureCommand::WriteBuffer {
+            id: self.id,
+            data: CaptureData(data.to_vec()),
+        });
+        Ok(())
+    }
+
+    fn read_data(&self, data: &mut [u8]) -> Result<(), FrameworkError> {
+        self.inner.read_data(data)
+    }
+}
+
+pub(crate) struct CaptureTexture {
+    pub(crate) inner: GpuTexture,
+    id: u64,
+    recorder: Rc<Recorder>,
+    record: RefCell<TextureRecord>,
+}
+
+impl CaptureTexture {
+    pub(crate) fn new(inner: GpuTexture, record: TextureRecord, recorder: Rc<Recorder>) -> Self {
+        let texture = Self {
+            inner,
+            id: recorder.next_id(),
+            recorder,
+            record: RefCell::new(record),
+        };
+        texture
+            .recorder
+            .push_creation(texture.id, || texture.creation());
+        texture
+    }
+
+    pub(crate) fn cast(texture: &GpuTexture) -> Result<&Self, FrameworkError> {
+        texture
+            .as_any()
+            .downcast_ref::<Self>()
+            .ok_or_else(|| foreign_object_error("texture"))
+    }
+
+    /// Returns a unique id of the texture, that is used in capture records.
+    pub(crate) fn id(&self) -> u64 {
+        self.id
+    }
+
+    fn creation(&self) -> CaptureCommand {
+        CaptureCommand::CreateTexture {
+            id: self.id,
+            texture: self.record.borrow().clone(),
+        }
+    }
+
+    pub(crate) fn ensure_recorded(&self) {
+        self.recorder.ensure_recorded(self.id, || self.creation());
+    }
+}
+
+impl GpuTextureTrait for CaptureTexture {
+    fn set_data(
+        &self,
+        kind: GpuTextureKind,
+        pixel_kind: PixelKind,
+        mip_count: usize,
+        data: Option<&[u8]>,
+    ) -> Result<(), FrameworkError> {
+        self.inner.set_data(kind, pixel_kind, mip_count, data)?;
+        self.ensure_recorded();
+        let record = {
+            let mut record = self.record.borrow_mut();
+            record.kind = kind;
+            record.pixel_kind = pixel_kind;
+            record.mip_count = mip_count;
+            record.data = data.map(|data| CaptureData(data.to_vec()));
+            record.clone()
+        };
+        self.recorder.push(CaptureCommand::SetTextureData {
+            id: self.id,
+            texture: record,
+        });
+        Ok(())
+    }
+
+    fn kind(&self) -> GpuTextureKind {
+        self.inner.kind()
+    }
+
+    fn pixel_kind(&self) -> PixelKind {
+        self.inner.pixel_kind()
+    }
+}
+
+pub(crate) struct CaptureSampler {
+    pub(crate) inner: GpuSampler,
+    id: u64,
+    recorder: Rc<Recorder>,
+    descriptor: GpuSamplerDescriptor,
+}
+
+impl Debug for CaptureSampler {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CaptureSampler")
+            .field("id", &self.id)
+            .field("descriptor", &self.descriptor)
+            .finish()
+    }
+}
+
+impl CaptureSampler {
+    pub(crate) fn new(
+        inner: GpuSampler,
+        descriptor: GpuSamplerDescriptor,
+        recorder: Rc<Recorder>,
+    ) -> Self {
+        let sampler = Self {
+            inner,
+            id: recorder.next_id(),
+            recorder,
+            descriptor,
+        };
+        sampler
+            .recorder
+            .push_creation(sampler.id, || sampler.creation());
+        sampler
+    }
+
+    pub(crate) fn cast(sampler: &GpuSampler) -> Result<&Self, FrameworkError> {
+        sampler
+            .as_any()
+            .downcast_ref::<Self>()
+            .ok_or_else(|| foreign_object_error("sampler"))
+    }
+
+    /// Returns a unique id of the sampler, that is used in capture records.
+    pub(crate) fn id(&self) -> u64 {
+        self.id
+    }
+
+    fn creation(&self) -> CaptureCommand {
+        CaptureCommand::CreateSampler {
+            id: self.id,
+            descriptor: self.descriptor,
+        }
+    }
+
+    pub(crate) fn ensure_recorded(&self) {
+        self.recorder.ensure_recorded(self.id, || self.creation());
+    }
+}
+
+impl GpuSamplerTrait for CaptureSampler {}
+
+pub(crate) struct CaptureGeometryBuffer {
+    pub(crate) inner: GpuGeometryBuffer,
+    id: u64,
+    recorder: Rc<Recorder>,
+    record: RefCell<GeometryBufferRecord>,
+}
+
+fn flatten_elements(elements: &ElementsDescriptor) -> Vec<u32> {
+    match elements {
+        ElementsDescriptor::Triangles(triangles) => {
+            triangles.iter().flat_map(|t| t.0).collect::<Vec<_>>()
+        }
+        ElementsDescriptor::Lines(lines) => lines.iter().flatten().cloned().collect(),
+        ElementsDescriptor::Points(points) => points.to_vec(),
+    }
+}
+
+pub(crate) fn geometry_record(desc: &GeometryBufferDescriptor) -> GeometryBufferRecord {
+    GeometryBufferRecord {
+        usage: desc.usage,
+        element_kind: desc.elements.element_kind(),
+        buffers: desc
+            .buffers
+            .iter()
+            .map(|buffer| VertexBufferRecord {
+                usage: buffer.usage,
+                attributes: buffer.attributes.to_vec(),
+                element_size: buffer.data.element_size,
+                data: CaptureData(buffer.data.bytes.unwrap_or_default().to_vec()),
+            })
+            .collect(),
+        elements: CaptureData(flatten_elements(&desc.elements)),
+    }
+}
+
+impl CaptureGeometryBuffer {
+    pub(crate) fn new(
+        inner: GpuGeometryBuffer,
+        record: GeometryBufferRecord,
+        recorder: Rc<Recorder>,
+    ) -> Self {
+        let geometry = Self {
+            inner,
+            id: recorder.next_id(),
+            recorder,
+            record: RefCell::new(record),
+        };
+        geometry
+            .recorder
+            .push_creation(geometry.id, || geometry.creation());
+        geometry
+    }
+
+    pub(crate) fn cast(geometry: &GpuGeometryBuffer) -> Result<&Self, FrameworkError> {
+        geometry
+            .as_any()
+            .downcast_ref::<Self>()
+            .ok_or_else(|| foreign_object_error("geometry buffer"))
+    }
+
+    /// Returns a unique id of the geometry buffer, that is used in capture records.
+    pub(crate) fn id(&self) -> u64 {
+        self.id
+    }
+
+    fn creation(&self) -> CaptureCommand {
+        CaptureCommand::CreateGeometryBuffer {
+            id: self.id,
+            geometry: self.record.borrow().clone(),
+        }
+    }
+
+    pub(crate) fn ensure_recorded(&self) {
+        self.recorder.ensure_recorded(self.id, || self.creation());
+    }
+
+    fn record_elements(&self, element_kind: ElementKind, elements: Vec<u32>) {
+        self.ensure_recorded();
+        self.record.borrow_mut().elements = CaptureData(elements.clone());
+        self.recorder.push(CaptureCommand::SetElements {
+            id: self.id,
+            element_kind,
+            elements: CaptureData(elements),
+        });
+    }
+}
+
+impl GpuGeometryBufferTrait for CaptureGeometryBuffer {
+    fn set_buffer_data(&self, buffer: usize, data: &[u8]) {
+        self.inner.set_buffer_data(buffer, data);
+        self.ensure_recorded();
+        if let Some(record) = self.record.borrow_mut().buffers.get_mut(buffer) {
+            record.data.0.clear();
+            record.data.0.extend_from_slice(data);
+        }
+        self.recorder.push(CaptureCommand::SetGeometryBufferData {
+            id: self.id,
+            buffer,
+            data: CaptureData(data.to_vec()),
+        });
+    }
+
+    fn element_count(&self) -> usize {
+        self.inner.element_count()
+    }
+
+    fn set_triangles(&self, triangles: &[TriangleDefinition]) {
+        self.inner.set_triangles(triangles);
+        self.record_elements(
+            ElementKind::Triangle,
+            triangles.iter().flat_map(|t| t.0).collect(),
+        );
+    }
+
+    fn set_lines(&self, lines: &[[u32; 2]]) {
+        self.inner.set_lines(lines);
+        self.record_elements(ElementKind::Line, lines.iter().flatten().cloned().collect());
+    }
+
+    fn set_points(&self, points: &[u32]) {
+        self.inner.set_points(points);
+        self.record_elements(ElementKind::Point, points.to_vec());
+    }
+}
+
+pub(crate) struct CaptureProgram {
+    pub(crate) inner: GpuProgram,
+    id: u64,
+    recorder: Rc<Recorder>,
+    record: ProgramRecord,
+}
+
+impl CaptureProgram {
+    pub(crate) fn new(inner: GpuProgram, record: ProgramRecord, recorder: Rc<Recorder>) -> Self {
+        let program = Self {
+            inner,
+            id: recorder.next_id(),
+            recorder,
+            record,
+        };
+        program
+            .recorder
+            .push_creation(program.id, || program.creation());
+        program
+    }
+
+    pub(crate) fn cast(program: &GpuProgram) -> Result<&Self, FrameworkError> {
+        program
+            .as_any()
+            .downcast_ref::<Self>()
+            .ok_or_else(|| foreign_object_error("program"))
+    }
+
+    /// Returns a unique id of the program, that is used in capture records.
+    pub(crate) fn id(&self) -> u64 {
+        self.id
+    }
+
+    fn creation(&self) -> CaptureCommand {
+        CaptureCommand::CreateProgram {
+            id: self.id,
+            program: self.record.clone(),
+        }
+    }
+
+    pub(crate) fn ensure_recorded(&self) {
+        self.recorder.ensure_recorded(self.id, || self.creation());
+    }
+}
+
+impl GpuProgramTrait for CaptureProgram {}
+
+/// Read buffers are not recorded, the wrapper just passes the underlying frame buffer to the
+/// underlying read buffer.
+pub(crate) struct CaptureAsyncReadBuffer {
+    pub(crate) inner: GpuAsyncReadBuffer,
+}
+
+impl GpuAsyncReadBufferTrait for CaptureAsyncReadBuffer {
+    fn schedule_pixels_transfer(
+        &self,
+        framebuffer: &dyn GpuFrameBufferTrait,
+        color_buffer_index: u32,
+        rect: Option<Rect<i32>>,
+    ) -> Result<(), FrameworkError> {
+        let framebuffer = framebuffer
+            .as_any()
+            .downcast_ref::<CaptureFrameBuffer>()
+            .ok_or_else(|| foreign_object_error("frame buffer"))?;
+        self.inner
+            .schedule_pixels_transfer(&*framebuffer.inner.0, color_buffer_index, rect)
+    }
+
+    fn is_request_running(&self) -> bool {
+        self.inner.is_request_running()
+    }
+
+    fn try_read(&self) -> Option<Vec<u8>> {
+        self.inner.try_read()
+    }
+}
@@ -0,0 +1,361 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Graphics server, that records everything that was submitted to another graphics server. See
+//! [`CaptureGraphicsServer`] docs for more info.
+
+use crate::{
+    buffer::{BufferKind, BufferUsage, GpuBuffer},
+    capture::{
+        framebuffer::CaptureFrameBuffer,
+        resources::{
+            geometry_record, CaptureAsyncReadBuffer, CaptureBuffer, CaptureGeometryBuffer,
+            CaptureProgram, CaptureSampler, CaptureTexture, Recorder,
+        },
+        CaptureCommand, CaptureData, FrameCapture, ProgramRecord, TextureRecord,
+    },
+    error::FrameworkError,
+    framebuffer::{Attachment, GpuFrameBuffer},
+    geometry_buffer::{GeometryBufferDescriptor, GpuGeometryBuffer},
+    gpu_program::{GpuProgram, GpuShader, ShaderKind, ShaderResourceDefinition},
+    gpu_texture::{GpuTexture, GpuTextureDescriptor, GpuTextureKind},
+    query::GpuQuery,
+    read_buffer::GpuAsyncReadBuffer,
+    sampler::{GpuSampler, GpuSamplerDescriptor},
+    server::{GraphicsServer, ServerCapabilities, SharedGraphicsServer},
+    stats::PipelineStatistics,
+    PolygonFace, PolygonFillMode,
+};
+use std::{
+    cell::{Cell, RefCell},
+    rc::{Rc, Weak},
+};
+
+/// Capture graphics server wraps any other graphics server and records resource creations, data
+/// uploads, clears, blits and draw calls into a [`FrameCapture`]. The capture could be saved to
+/// a file and then replayed step-by-step using [`super::replay::FrameReplayer`].
+///
+/// Every object created by this server is a thin wrapper over an object of the underlying
+/// server, so the capture server could be used as a drop-in replacement of the underlying server.
+/// Objects that were created before a capture has started are recorded with their current
+/// state when they are used for the first time during the capture. Keep in mind, that the
+/// content of render targets is not tracked, only the uploaded data is.
+///
+/// ## Example
+///
+/// ```rust
+/// use fyrox_graphics::{
+///     capture::server::CaptureGraphicsServer, core::math::Rect, null::server::NullGraphicsServer,
+///     server::GraphicsServer,
+/// };
+///
+/// let server = CaptureGraphicsServer::new(NullGraphicsServer::new((64, 64)));
+/// server.begin_capture();
+/// server
+///     .back_buffer()
+///     .clear(Rect::new(0, 0, 64, 64), None, Some(1.0), None);
+/// let capture = server.end_capture().unwrap();
+/// assert_eq!(capture.commands.len(), 1);
+/// ```
+pub struct CaptureGraphicsServer {
+    inner: SharedGraphicsServer,
+    recorder: Rc<Recorder>,
+    frame_size: Cell<(u32, u32)>,
+    fill_mode: Cell<Option<(PolygonFace, PolygonFillMode)>>,
+    capture_next_frame: Cell<bool>,
+    frame_capture_running: Cell<bool>,
+    last_capture: RefCell<Option<FrameCapture>>,
+    this: RefCell<Option<Weak<CaptureGraphicsServer>>>,
+}
+
+impl CaptureGraphicsServer {
+    /// Creates a new capture server that wraps the given server. The current frame size of the
+    /// server is taken from its back buffer.
+    pub fn new(inner: SharedGraphicsServer) -> Rc<Self> {
+        let frame_size = inner
+            .back_buffer()
+            .color_attachments()
+            .first()
+            .and_then(|attachment| match attachment.texture.kind() {
+                GpuTextureKind::Rectangle { width, height } => Some((width as u32, height as u32)),
+                _ => None,
+            })
+            .unwrap_or_default();
+        let server = Rc::new(Self {
+            inner,
+            recorder: Rc::new(Recorder::new(FrameCapture::BACK_BUFFER_ID + 1)),
+            frame_size: Cell::new(frame_size),
+            fill_mode: Default::default(),
+            capture_next_frame: Default::default(),
+            frame_capture_running: Default::default(),
+            last_capture: Default::default(),
+            this: Default::default(),
+        });
+        *server.this.borrow_mut() = Some(Rc::downgrade(&server));
+        server
+    }
+
+    /// Returns a reference to the underlying graphics server.
+    pub fn inner(&self) -> &SharedGraphicsServer {
+        &self.inner
+    }
+
+    /// Sets the size of the frame, that will be written to captures. Use this method if the
+    /// underlying server does not provide real back buffer attachments (OpenGL, for example).
+    pub fn set_capture_frame_size(&self, frame_size: (u32, u32)) {
+        self.frame_size.set(frame_size);
+    }
+
+    /// Starts a new capture. All the commands from the previous capture (if any) are discarded.
+    pub fn begin_capture(&self) {
+        self.recorder.begin();
+        if let Some((face, mode)) = self.fill_mode.get() {
+            self.recorder
+                .push(CaptureCommand::SetPolygonFillMode { face, mode });
+        }
+    }
+
+    /// Returns `true` if the server is recording commands right now.
+    pub fn is_capturing(&self) -> bool {
+        self.recorder.is_capturing()
+    }
+
+    /// Finishes the current capture and returns it. Returns [`None`] if there was no capture
+    /// running.
+    pub fn end_capture(&self) -> Option<FrameCapture> {
+        self.frame_capture_running.set(false);
+        let (frame_width, frame_height) = self.frame_size.get();
+        self.recorder.end().map(|commands| FrameCapture {
+            frame_width,
+            frame_height,
+            commands,
+        })
+    }
+
+    /// Requests a capture of the next frame. The capture starts at the next call of
+    /// [`GraphicsServer::swap_buffers`] and ends at the call after it. The result could be
+    /// obtained using [`Self::take_capture`].
+    pub fn capture_next_frame(&self) {
+        self.capture_next_frame.set(true);
+    }
+
+    /// Takes the last frame captured by [`Self::capture_next_frame`] request.
+    pub fn take_capture(&self) -> Option<FrameCapture> {
+        self.last_capture.borrow_mut().take()
+    }
+}
+
+impl GraphicsServer for CaptureGraphicsServer {
+    fn create_buffer(
+        &self,
+        size: usize,
+        buffer_kind: BufferKind,
+        buffer_usage: BufferUsage,
+    ) -> Result<GpuBuffer, FrameworkError> {
+        let inner = self.inner.create_buffer(size, buffer_kind, buffer_usage)?;
+        Ok(GpuBuffer(Rc::new(CaptureBuffer::new(
+            inner,
+            self.recorder.clone(),
+        ))))
+    }
+
+    fn create_texture(&self, desc: GpuTextureDescriptor) -> Result<GpuTexture, FrameworkError> {
+        let record = TextureRecord {
+            kind: desc.kind,
+            pixel_kind: desc.pixel_kind,
+            mip_count: desc.mip_count,
+            base_level: desc.base_level,
+            max_level: desc.max_level,
+            data: desc.data.map(|data| CaptureData(data.to_vec())),
+        };
+        let inner = self.inner.create_texture(desc)?;
+        Ok(GpuTexture(Rc::new(CaptureTexture::new(
+            inner,
+            record,
+            self.recorder.clone(),
+        ))))
+    }
+
+    fn create_sampler(&self, desc: GpuSamplerDescriptor) -> Result<GpuSampler, FrameworkError> {
+        let inner = self.inner.create_sampler(desc)?;
+        Ok(GpuSampler(Rc::new(CaptureSampler::new(
+            inner,
+            desc,
+            self.recorder.clone(),
+        ))))
+    }
+
+    fn create_frame_buffer(
+        &self,
+        depth_attachment: Option<Attachment>,
+        color_attachments: Vec<Attachment>,
+    ) -> Result<GpuFrameBuffer, FrameworkError> {
+        let unwrap = |attachment: &Attachment| -> Result<Attachment, FrameworkError> {
+            Ok(Attachment {
+                kind: attachment.kind,
+                texture: CaptureTexture::cast(&attachment.texture)?.inner.clone(),
+            })
+        };
+        let inner = self.inner.create_frame_buffer(
+            depth_attachment.as_ref().map(unwrap).transpose()?,
+            color_attachments
+                .iter()
+                .map(unwrap)
+                .collect::<Result<Vec<_>, _>>()?,
+        )?;
+        Ok(GpuFrameBuffer(Rc::new(CaptureFrameBuffer::new(
+            inner,
+            depth_attachment,
+            color_attachments,
+            self.recorder.clone(),
+        ))))
+    }
+
+    fn back_buffer(&self) -> GpuFrameBuffer {
+        GpuFrameBuffer(Rc::new(CaptureFrameBuffer::back_buffer(
+            self.inner.back_buffer(),
+            self.recorder.clone(),
+        )))
+    }
+
+    fn create_query(&self) -> Result<GpuQuery, FrameworkError> {
+        self.inner.create_query()
+    }
+
+    fn create_shader(
+        &self,
+        name: String,
+        kind: ShaderKind,
+        source: String,
+        resources: &[ShaderResourceDefinition],
+        line_offset: isize,
+    ) -> Result<GpuShader, FrameworkError> {
+        self.inner
+            .create_shader(name, kind, source, resources, line_offset)
+    }
+
+    fn create_program(
+        &self,
+        name: &str,
+        vertex_source: String,
+        vertex_source_line_offset: isize,
+        fragment_source: String,
+        fragment_source_line_offset: isize,
+        resources: &[ShaderResourceDefinition],
+    ) -> Result<GpuProgram, FrameworkError> {
+        let record = ProgramRecord {
+            name: name.to_string(),
+            vertex_source: vertex_source.clone(),
+            vertex_source_line_offset,
+            fragment_source: fragment_source.clone(),
+            fragment_source_line_offset,
+            resources: resources.to_vec(),
+        };
+        let inner = self.inner.create_program(
+            name,
+            vertex_source,
+            vertex_source_line_offset,
+            fragment_source,
+            fragment_source_line_offset,
+            resources,
+        )?;
+        Ok(GpuProgram(Rc::new(CaptureProgram::new(
+            inner,
+            record,
+            self.recorder.clone(),
+        ))))
+    }
+
+    fn create_async_read_buffer(
+        &self,
+        pixel_size: usize,
+        pixel_count: usize,
+    ) -> Result<GpuAsyncReadBuffer, FrameworkError> {
+        let inner = self
+            .inner
+            .create_async_read_buffer(pixel_size, pixel_count)?;
+        Ok(GpuAsyncReadBuffer(Rc::new(CaptureAsyncReadBuffer {
+            inner,
+        })))
+    }
+
+    fn create_geometry_buffer(
+        &self,
+        desc: GeometryBufferDescriptor,
+    ) -> Result<GpuGeometryBuffer, FrameworkError> {
+        let record = geometry_record(&desc);
+        let inner = self.inner.create_geometry_buffer(desc)?;
+        Ok(GpuGeometryBuffer(Rc::new(CaptureGeometryBuffer::new(
+            inner,
+            record,
+            self.recorder.clone(),
+        ))))
+    }
+
+    fn weak(self: Rc<Self>) -> Weak<dyn GraphicsServer> {
+        self.this.borrow().as_ref().unwrap().clone()
+    }
+
+    fn flush(&self) {
+        self.inner.flush()
+    }
+
+    fn finish(&self) {
+        self.inner.finish()
+    }
+
+    fn invalidate_resource_bindings_cache(&self) {
+        self.inner.invalidate_resource_bindings_cache()
+    }
+
+    fn pipeline_statistics(&self) -> PipelineStatistics {
+        self.inner.pipeline_statistics()
+    }
+
+    fn swap_buffers(&self) -> Result<(), FrameworkError> {
+        let result = self.inner.swap_buffers();
+        if self.frame_capture_running.get() {
+            *self.last_capture.borrow_mut() = self.end_capture();
+        } else if self.capture_next_frame.replace(false) {
+            self.begin_capture();
+            self.frame_capture_running.set(true);
+        }
+        result
+    }
+
+    fn set_frame_size(&self, new_size: (u32, u32)) {
+        self.frame_size.set(new_size);
+        self.inner.set_frame_size(new_size)
+    }
+
+    fn capabilities(&self) -> ServerCapabilities {
+        self.inner.capabilities()
+    }
+
+    fn set_polygon_fill_mode(&self, polygon_face: PolygonFace, polygon_fill_mode: PolygonFillMode) {
+        self.inner
+            .set_polygon_fill_mode(polygon_face, polygon_fill_mode);
+        self.fill_mode.set(Some((polygon_face, polygon_fill_mode)));
+        self.recorder.push(CaptureCommand::SetPolygonFillMode {
+            face: polygon_face,
+            mode: polygon_fill_mode,
+        });
+    }
+}
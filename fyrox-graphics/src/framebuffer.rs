@@ -27,7 +27,7 @@
 use crate::sampler::GpuSampler;
 use crate::{
     buffer::GpuBuffer,
-    core::{color::Color, math::Rect},
+    core::{color::Color, math::Rect, visitor::prelude::*},
     define_shared_wrapper,
     error::FrameworkError,
     geometry_buffer::GpuGeometryBuffer,
@@ -39,10 +39,11 @@ use bytemuck::Pod;
 use fyrox_core::define_as_any_trait;
 
 /// Frame buffer attachment kind.
-#[derive(Copy, Clone, PartialOrd, PartialEq, Hash, Debug, Eq)]
+#[derive(Copy, Clone, PartialOrd, PartialEq, Hash, Debug, Eq, Default, Visit)]
 pub enum AttachmentKind {
     /// Color attachment, it should have a format that supports rendering (for example it cannot be
     /// a compressed texture format).
+    #[default]
     Color,
     /// Combined depth + stencil (usually it is 24 bits for depth and 8 for stencil) attachment.
     DepthStencil,
@@ -85,7 +86,7 @@ impl Attachment {
 }
 
 /// Defines a range of data in a particular buffer.
-#[derive(Default)]
+#[derive(Default, Copy, Clone, Debug, PartialEq, Eq, Visit)]
 pub enum BufferDataUsage {
     /// Use everything at once.
     #[default]
@@ -182,8 +183,9 @@ pub trait GpuFrameBufferTrait: GpuFrameBufferAsAny {
     /// Returns an optional depth/stencil attachment.
     fn depth_attachment(&self) -> Option<&Attachment>;
 
-    /// Sets an active face of a cube map (only for frame buffers that using cube maps for rendering).
-    fn set_cubemap_face(&self, attachment_index: usize, face: CubeMapFace);
+    /// Sets an active face and mip level of a cube map (only for frame buffers that using cube maps
+    /// for rendering). Keep in mind, that the viewport must match the size of the selected mip level.
+    fn set_cubemap_face(&self, attachment_index: usize, face: CubeMapFace, level: usize);
 
     /// Performs data transfer from one frame buffer to another with scaling. It copies a region
     /// defined by `src_x0`, `src_y0`, `src_x1`, `src_y1` coordinates from the frame buffer and
@@ -25,7 +25,7 @@
 
 use crate::{
     buffer::BufferUsage,
-    core::{array_as_u8_slice, math::TriangleDefinition},
+    core::{array_as_u8_slice, math::TriangleDefinition, visitor::prelude::*},
     define_shared_wrapper, ElementKind,
 };
 use bytemuck::Pod;
@@ -33,10 +33,11 @@ use fyrox_core::define_as_any_trait;
 use std::mem::size_of;
 
 /// Attribute kind of a vertex.
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Visit)]
 #[allow(dead_code)]
 pub enum AttributeKind {
     /// Floating point 32-bit number.
+    #[default]
     Float,
     /// Integer unsigned 8-bit number.
     UnsignedByte,
@@ -47,6 +48,7 @@ pub enum AttributeKind {
 }
 
 /// Vertex attribute definition.
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Visit)]
 pub struct AttributeDefinition {
     /// Binding point of the vertex attribute.
     pub location: u32,
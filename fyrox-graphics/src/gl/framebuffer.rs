@@ -174,7 +174,7 @@ impl GpuFrameBufferTrait for GlFrameBuffer {
         self.depth_attachment.as_ref()
     }
 
-    fn set_cubemap_face(&self, attachment_index: usize, face: CubeMapFace) {
+    fn set_cubemap_face(&self, attachment_index: usize, face: CubeMapFace, level: usize) {
         let server = self.state.upgrade().unwrap();
 
         unsafe {
@@ -191,7 +191,7 @@ impl GpuFrameBufferTrait for GlFrameBuffer {
                 glow::COLOR_ATTACHMENT0 + attachment_index as u32,
                 face.into_gl(),
                 Some(texture.id()),
-                0,
+                level as i32,
             );
         }
     }
@@ -497,7 +497,7 @@ impl GpuFrameBufferTrait for GlFrameBuffer {
         } else {
             let index_per_element = geometry.element_kind.index_per_element();
             let start_index = offset * index_per_element;
-            let index_count = geometry.element_count.get() * index_per_element;
+            let index_count = element_count * index_per_element;
 
             unsafe {
                 if index_count > 0 {
@@ -514,7 +514,7 @@ impl GpuFrameBufferTrait for GlFrameBuffer {
             }
 
             Ok(DrawCallStatistics {
-                triangles: geometry.element_count.get() * instance_count,
+                triangles: element_count * instance_count,
             })
         }
     }
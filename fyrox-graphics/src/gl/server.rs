@@ -497,6 +497,13 @@ impl GlGraphicsServer {
         unsafe {
             context.depth_func(CompareFunc::default().into_gl());
 
+            // Filtering across cube map faces is always enabled on GLES 3.0+, but must be requested
+            // explicitly on desktop GL. Prefiltered environment maps rely on it, otherwise the seams
+            // between faces will be visible on rough surfaces.
+            if gl_kind == GlKind::OpenGL {
+                context.enable(glow::TEXTURE_CUBE_MAP_SEAMLESS);
+            }
+
             #[cfg(debug_assertions)]
             {
                 use crate::core::log::{Log, MessageKind};
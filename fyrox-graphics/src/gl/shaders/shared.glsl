@@ -102,6 +102,45 @@ vec3 S_FresnelSchlick(float cosTheta, vec3 F0)
     return F0 + (1.0 - F0) * pow(max(1.0 - cosTheta, 0.0), 5.0);
 }
 
+// Fresnel-Schlick approximation for image-based lighting. It takes roughness into account, because
+// the light reflected by a rough surface comes from a wide cone of directions.
+vec3 S_FresnelSchlickRoughness(float cosTheta, vec3 F0, float roughness)
+{
+    return F0 + (max(vec3(1.0 - roughness), F0) - F0) * pow(max(1.0 - cosTheta, 0.0), 5.0);
+}
+
+// Returns i-th point of the Hammersley low-discrepancy sequence of the given size.
+vec2 S_Hammersley(uint i, uint count)
+{
+    uint bits = i;
+    bits = (bits << 16u) | (bits >> 16u);
+    bits = ((bits & 0x55555555u) << 1u) | ((bits & 0xAAAAAAAAu) >> 1u);
+    bits = ((bits & 0x33333333u) << 2u) | ((bits & 0xCCCCCCCCu) >> 2u);
+    bits = ((bits & 0x0F0F0F0Fu) << 4u) | ((bits & 0xF0F0F0F0u) >> 4u);
+    bits = ((bits & 0x00FF00FFu) << 8u) | ((bits & 0xFF00FF00u) >> 8u);
+    float radicalInverse = float(bits) * 2.3283064365386963e-10;
+    return vec2(float(i) / float(count), radicalInverse);
+}
+
+// Maps a point of the unit square to a half vector around the given normal, so the half vectors
+// are distributed according to the GGX normal distribution function.
+vec3 S_ImportanceSampleGGX(vec2 Xi, vec3 N, float roughness)
+{
+    float a = roughness * roughness;
+
+    float phi = 2.0 * PI * Xi.x;
+    float cosTheta = sqrt((1.0 - Xi.y) / (1.0 + (a * a - 1.0) * Xi.y));
+    float sinTheta = sqrt(1.0 - cosTheta * cosTheta);
+
+    vec3 H = vec3(cos(phi) * sinTheta, sin(phi) * sinTheta, cosTheta);
+
+    vec3 up = abs(N.z) < 0.999 ? vec3(0.0, 0.0, 1.0) : vec3(1.0, 0.0, 0.0);
+    vec3 tangent = normalize(cross(up, N));
+    vec3 bitangent = cross(N, tangent);
+
+    return normalize(tangent * H.x + bitangent * H.y + N * H.z);
+}
+
 struct TPBRContext {
     vec3 lightColor;
     vec3 viewVector;
@@ -275,6 +314,208 @@ in sampler2D spotShadowTexture)
     }
 }
 
+// Poisson disk, that is used by percentage-closer soft shadows.
+const int S_POISSON_DISK_SIZE = 16;
+const vec2 S_POISSON_DISK[S_POISSON_DISK_SIZE] = vec2[S_POISSON_DISK_SIZE](
+vec2(-0.94201624, -0.39906216), vec2(0.94558609, -0.76890725), vec2(-0.09418410, -0.92938870), vec2(0.34495938, 0.29387760),
+vec2(-0.91588581, 0.45771432), vec2(-0.81544232, -0.87912464), vec2(-0.38277543, 0.27676845), vec2(0.97484398, 0.75648379),
+vec2(0.44323325, -0.97511554), vec2(0.53742981, -0.47373420), vec2(-0.26496911, -0.41893023), vec2(0.79197514, 0.19090188),
+vec2(-0.24188840, 0.99706507), vec2(-0.81409955, 0.91437590), vec2(0.19984126, 0.78641367), vec2(0.14383161, -0.14100790)
+);
+
+// Maximum radius of penumbra of percentage-closer soft shadows in shadow map texture coordinates.
+const float S_PCSS_MAX_RADIUS = 0.05;
+
+// Converts depth from a shadow map of a perspective projection to the distance from the light.
+float Internal_LinearizeShadowDepth(float depth, float zNear, float zFar)
+{
+    float z = depth * 2.0 - 1.0;
+    return 2.0 * zNear * zFar / (zFar + zNear - z * (zFar - zNear));
+}
+
+// Calculates spot light shadow factor using percentage-closer soft shadows, where 1.0 - no shadow,
+// 0.0 - fully in shadow. `lightSizeUV` is the size of the light source divided by the width of the
+// frustum of the light at unit distance, `zNear` and `zFar` are the planes of the frustum of the light.
+float S_SpotShadowFactorPCSS(
+    bool shadowsEnabled,
+    float shadowBias,
+    vec3 fragmentPosition,
+    mat4 lightViewProjMatrix,
+    float shadowMapInvSize,
+    float lightSizeUV,
+    float zNear,
+    float zFar,
+in sampler2D spotShadowTexture)
+{
+    if (!shadowsEnabled) {
+        return 1.0;
+    }
+
+    vec3 lightSpacePosition = S_Project(fragmentPosition, lightViewProjMatrix);
+    float biasedLightSpaceFragmentDepth = lightSpacePosition.z - shadowBias;
+    float receiverDistance = Internal_LinearizeShadowDepth(biasedLightSpaceFragmentDepth, zNear, zFar);
+
+    // Find average distance to the occluders, that could block the light source.
+    float searchRadius = clamp(lightSizeUV / receiverDistance, shadowMapInvSize, S_PCSS_MAX_RADIUS);
+    float blockerDistance = 0.0;
+    int blockerCount = 0;
+    for (int i = 0; i < S_POISSON_DISK_SIZE; ++i) {
+        float depth = texture(spotShadowTexture, lightSpacePosition.xy + S_POISSON_DISK[i] * searchRadius).r;
+        if (depth < biasedLightSpaceFragmentDepth) {
+            blockerDistance += Internal_LinearizeShadowDepth(depth, zNear, zFar);
+            blockerCount += 1;
+        }
+    }
+    if (blockerCount == 0) {
+        return 1.0;
+    }
+    blockerDistance /= float(blockerCount);
+
+    // The penumbra is wider when the occluders are far from the receiver.
+    float penumbra = lightSizeUV * (receiverDistance - blockerDistance) / (blockerDistance * receiverDistance);
+    float filterRadius = clamp(penumbra, shadowMapInvSize, S_PCSS_MAX_RADIUS);
+    float accumulator = 0.0;
+    for (int i = 0; i < S_POISSON_DISK_SIZE; ++i) {
+        vec2 fetchTexCoord = lightSpacePosition.xy + S_POISSON_DISK[i] * filterRadius;
+        if (biasedLightSpaceFragmentDepth > texture(spotShadowTexture, fetchTexCoord).r) {
+            accumulator += 1.0;
+        }
+    }
+    return clamp(1.0 - accumulator / float(S_POISSON_DISK_SIZE), 0.0, 1.0);
+}
+
+// Calculates directional light shadow factor using percentage-closer soft shadows, where 1.0 - no
+// shadow, 0.0 - fully in shadow. Shadow maps of directional lights use orthographic projection, so
+// `penumbraScale` is the width of the penumbra (in texture coordinates) per unit of depth difference
+// between an occluder and a receiver.
+float S_DirectionalShadowFactorPCSS(
+    bool shadowsEnabled,
+    float shadowBias,
+    vec3 fragmentPosition,
+    mat4 lightViewProjMatrix,
+    float shadowMapInvSize,
+    float penumbraScale,
+in sampler2D shadowTexture)
+{
+    if (!shadowsEnabled) {
+        return 1.0;
+    }
+
+    vec3 lightSpacePosition = S_Project(fragmentPosition, lightViewProjMatrix);
+    float biasedLightSpaceFragmentDepth = lightSpacePosition.z - shadowBias;
+
+    float searchRadius = clamp(penumbraScale * biasedLightSpaceFragmentDepth, shadowMapInvSize, S_PCSS_MAX_RADIUS);
+    float blockerDepth = 0.0;
+    int blockerCount = 0;
+    for (int i = 0; i < S_POISSON_DISK_SIZE; ++i) {
+        float depth = texture(shadowTexture, lightSpacePosition.xy + S_POISSON_DISK[i] * searchRadius).r;
+        if (depth < biasedLightSpaceFragmentDepth) {
+            blockerDepth += depth;
+            blockerCount += 1;
+        }
+    }
+    if (blockerCount == 0) {
+        return 1.0;
+    }
+    blockerDepth /= float(blockerCount);
+
+    float penumbra = penumbraScale * (biasedLightSpaceFragmentDepth - blockerDepth);
+    float filterRadius = clamp(penumbra, shadowMapInvSize, S_PCSS_MAX_RADIUS);
+    float accumulator = 0.0;
+    for (int i = 0; i < S_POISSON_DISK_SIZE; ++i) {
+        vec2 fetchTexCoord = lightSpacePosition.xy + S_POISSON_DISK[i] * filterRadius;
+        if (biasedLightSpaceFragmentDepth > texture(shadowTexture, fetchTexCoord).r) {
+            accumulator += 1.0;
+        }
+    }
+    return clamp(1.0 - accumulator / float(S_POISSON_DISK_SIZE), 0.0, 1.0);
+}
+
+// Calculates point shadow factor using percentage-closer soft shadows, where 1.0 - no shadow,
+// 0.0 - fully in shadow. `sourceSize` is the diameter of the light source in world units.
+float S_PointShadowPCSS(
+    bool shadowsEnabled,
+    float fragmentDistance,
+    float shadowBias,
+    float sourceSize,
+    vec3 toLight,
+in samplerCube shadowMap)
+{
+    if (!shadowsEnabled) {
+        return 1.0;
+    }
+
+    float biasedFragmentDistance = fragmentDistance - shadowBias;
+
+    // Build a basis around the direction from the light to the fragment, the disk is placed in the
+    // plane perpendicular to that direction.
+    vec3 direction = -toLight;
+    vec3 up = abs(direction.y) < 0.99 ? vec3(0.0, 1.0, 0.0) : vec3(1.0, 0.0, 0.0);
+    vec3 tangent = normalize(cross(up, direction));
+    vec3 bitangent = cross(direction, tangent);
+
+    // Radii are angular here, because the cube map is addressed by directions.
+    float searchRadius = min(sourceSize / max(biasedFragmentDistance, 0.0001), S_PCSS_MAX_RADIUS);
+    float blockerDistance = 0.0;
+    int blockerCount = 0;
+    for (int i = 0; i < S_POISSON_DISK_SIZE; ++i) {
+        vec2 offset = S_POISSON_DISK[i] * searchRadius;
+        float occluderDistance = texture(shadowMap, direction + tangent * offset.x + bitangent * offset.y).r;
+        if (occluderDistance < biasedFragmentDistance) {
+            blockerDistance += occluderDistance;
+            blockerCount += 1;
+        }
+    }
+    if (blockerCount == 0) {
+        return 1.0;
+    }
+    blockerDistance /= float(blockerCount);
+
+    float penumbra = sourceSize * (biasedFragmentDistance - blockerDistance) / max(blockerDistance * biasedFragmentDistance, 0.0001);
+    float filterRadius = clamp(penumbra, 0.0005, S_PCSS_MAX_RADIUS);
+    float accumulator = 0.0;
+    for (int i = 0; i < S_POISSON_DISK_SIZE; ++i) {
+        vec2 offset = S_POISSON_DISK[i] * filterRadius;
+        if (biasedFragmentDistance > texture(shadowMap, direction + tangent * offset.x + bitangent * offset.y).r) {
+            accumulator += 1.0;
+        }
+    }
+    return clamp(1.0 - accumulator / float(S_POISSON_DISK_SIZE), 0.0, 1.0);
+}
+
+// Calculates screen-space contact shadow factor, where 1.0 - no shadow, 0.0 - fully in shadow. A short
+// ray is marched from the fragment towards the light through the depth buffer, the fragment is shadowed
+// if the ray goes behind a surface, but no further than `thickness`. The shadow fades out along the ray.
+float S_ContactShadow(
+    sampler2D depthTexture,
+    vec3 fragmentPosition,
+    vec3 fragmentToLight,
+    vec3 cameraPosition,
+    mat4 viewProjectionMatrix,
+    mat4 invViewProjectionMatrix,
+    float maxDistance,
+    int stepCount,
+    float thickness)
+{
+    float stepLength = maxDistance / float(max(stepCount, 1));
+    // Start a bit above the surface to prevent self-shadowing.
+    vec3 position = fragmentPosition + fragmentToLight * (0.5 * stepLength);
+    for (int i = 0; i < stepCount; ++i) {
+        position += fragmentToLight * stepLength;
+        vec3 screenPosition = S_Project(position, viewProjectionMatrix);
+        if (any(lessThan(screenPosition.xy, vec2(0.0))) || any(greaterThan(screenPosition.xy, vec2(1.0)))) {
+            break;
+        }
+        float sceneDepth = texture(depthTexture, screenPosition.xy).r;
+        vec3 scenePosition = S_UnProject(vec3(screenPosition.xy, sceneDepth), invViewProjectionMatrix);
+        float difference = distance(position, cameraPosition) - distance(scenePosition, cameraPosition);
+        if (difference > 0.001 && difference < thickness) {
+            return float(i) / float(max(stepCount, 1));
+        }
+    }
+    return 1.0;
+}
+
 float Internal_FetchHeight(in sampler2D heightTexture, vec2 texCoords, float center) {
     return clamp(texture(heightTexture, texCoords).r - center, 0.0, 1.0);
 }
@@ -372,4 +613,335 @@ vec2 S_RotateVec2(vec2 v, float angle)
     float s = sin(angle);
     mat2 m = mat2(c, -s, s, c);
     return m * v;
-}
\ No newline at end of file
+}
+
+// Returns a direction that corresponds to the given point of a face of a cube map. Faces are
+// indexed in the following order: +X, -X, +Y, -Y, +Z, -Z. The point is given in [0; 1] range,
+// where (0, 0) is the first texel of the face.
+vec3 S_CubeFaceDirection(int face, vec2 uv)
+{
+    vec2 p = uv * 2.0 - 1.0;
+    vec3 direction;
+    if (face == 0) {
+        direction = vec3(1.0, -p.y, -p.x);
+    } else if (face == 1) {
+        direction = vec3(-1.0, -p.y, p.x);
+    } else if (face == 2) {
+        direction = vec3(p.x, 1.0, p.y);
+    } else if (face == 3) {
+        direction = vec3(p.x, -1.0, -p.y);
+    } else if (face == 4) {
+        direction = vec3(p.x, -p.y, 1.0);
+    } else {
+        direction = vec3(-p.x, -p.y, -1.0);
+    }
+    return normalize(direction);
+}
+
+// Returns opacity of the fog between the observer and the given point, the result is in [0; 1]
+// range. Fog modes: 0 - no fog, 1 - linear (params.x - start, params.y - end), 2 - exponential
+// (params.x - density), 3 - exponential height (params.x - density, params.y - base height,
+// params.z - height falloff).
+float S_FogOpacity(int mode, vec4 params, vec3 observer, vec3 point)
+{
+    float distance = length(point - observer);
+    float opacity = 0.0;
+    if (mode == 1) {
+        opacity = (distance - params.x) / max(params.y - params.x, 0.0001);
+    } else if (mode == 2) {
+        opacity = 1.0 - exp(-params.x * distance);
+    } else if (mode == 3) {
+        float deltaHeight = params.z * (point.y - observer.y);
+        float heightFactor = abs(deltaHeight) > 0.0001 ? (1.0 - exp(-deltaHeight)) / deltaHeight : 1.0;
+        float integral = params.x * distance * exp(-params.z * (observer.y - params.y)) * heightFactor;
+        opacity = 1.0 - exp(-integral);
+    }
+    return clamp(opacity, 0.0, 1.0);
+}
+
+// Layout of the froxel grid of the volumetric fog. Slices of the grid are stored in a 2D atlas,
+// S_FROXEL_ATLAS_COLUMNS slices per row. The far boundary of the slice N is located at
+// maxDistance * ((N + 1) / S_FROXEL_GRID_DEPTH)^2 distance from the observer.
+const int S_FROXEL_GRID_WIDTH = 128;
+const int S_FROXEL_GRID_HEIGHT = 72;
+const int S_FROXEL_GRID_DEPTH = 64;
+const int S_FROXEL_ATLAS_COLUMNS = 8;
+
+vec4 Internal_FetchFroxelSlice(sampler2D volume, vec2 gridUv, int slice)
+{
+    vec2 tile = vec2(slice % S_FROXEL_ATLAS_COLUMNS, slice / S_FROXEL_ATLAS_COLUMNS);
+    vec2 atlasSize = vec2(S_FROXEL_ATLAS_COLUMNS, S_FROXEL_GRID_DEPTH / S_FROXEL_ATLAS_COLUMNS);
+    return texture(volume, (tile + gridUv) / atlasSize);
+}
+
+// Samples integrated volumetric fog at the given screen position and distance from the observer.
+// Returns in-scattered light (rgb) and transmittance (a).
+vec4 S_SampleVolumetricFog(sampler2D volume, vec2 screenUv, float distance, float maxDistance)
+{
+    vec2 halfTexel = 0.5 / vec2(S_FROXEL_GRID_WIDTH, S_FROXEL_GRID_HEIGHT);
+    // Prevent bleeding of neighbouring slices in the atlas.
+    vec2 gridUv = clamp(screenUv, halfTexel, 1.0 - halfTexel);
+    float z = sqrt(clamp(distance / maxDistance, 0.0, 1.0)) * float(S_FROXEL_GRID_DEPTH);
+    // Integrated value of a slice is defined at its far boundary.
+    float c = z - 1.0;
+    if (c < 0.0) {
+        return mix(vec4(0.0, 0.0, 0.0, 1.0), Internal_FetchFroxelSlice(volume, gridUv, 0), z);
+    }
+    int s0 = int(floor(c));
+    int s1 = min(s0 + 1, S_FROXEL_GRID_DEPTH - 1);
+    return mix(Internal_FetchFroxelSlice(volume, gridUv, s0), Internal_FetchFroxelSlice(volume, gridUv, s1), fract(c));
+}
+
+vec3 Internal_ApplySceneFog(
+    vec3 color,
+    vec3 worldPosition,
+    vec3 cameraPosition,
+    mat4 viewProjectionMatrix,
+    int mode,
+    vec4 fogColor,
+    vec4 params,
+    bool useEnvironment,
+    samplerCube environment,
+    float environmentLod,
+    bool volumetric,
+    sampler2D volume,
+    float volumetricDistance)
+{
+    if (mode != 0) {
+        float opacity = min(S_FogOpacity(mode, params, cameraPosition, worldPosition), fogColor.a);
+        vec3 fogRgb = useEnvironment
+            ? textureLod(environment, normalize(worldPosition - cameraPosition), environmentLod).rgb
+            : fogColor.rgb;
+        color = mix(color, fogRgb, opacity);
+    }
+    if (volumetric) {
+        vec2 screenUv = S_Project(worldPosition, viewProjectionMatrix).xy;
+        vec4 scattering = S_SampleVolumetricFog(volume, screenUv, length(worldPosition - cameraPosition), volumetricDistance);
+        color = color * scattering.a + scattering.rgb;
+    }
+    return color;
+}
+
+// Applies the fog of the scene to the linear color of a fragment, rendered in a forward pass. Shaders,
+// that use it, must declare `fyrox_cameraData`, `fyrox_fogData`, `fyrox_fogEnvironment` and
+// `fyrox_volumetricFog` built-in resources.
+#define S_ApplySceneFog(color, worldPosition) Internal_ApplySceneFog(color, worldPosition, fyrox_cameraData.position, fyrox_cameraData.viewProjectionMatrix, fyrox_fogData.mode, fyrox_fogData.color, fyrox_fogData.params, fyrox_fogData.useEnvironment, fyrox_fogEnvironment, fyrox_fogData.environmentLod, fyrox_fogData.volumetric, fyrox_volumetricFog, fyrox_fogData.volumetricDistance)
+
+// Layout of the light clusters. The screen is split into S_CLUSTER_GRID_WIDTH x S_CLUSTER_GRID_HEIGHT
+// tiles, the view depth is split into S_CLUSTER_GRID_DEPTH slices, the near boundary of the slice N
+// is located at zNear * (zFar / zNear)^(N / S_CLUSTER_GRID_DEPTH) distance from the observer. Cluster
+// data textures are addressed linearly, S_CLUSTER_DATA_WIDTH texels per row.
+const int S_CLUSTER_GRID_WIDTH = 16;
+const int S_CLUSTER_GRID_HEIGHT = 9;
+const int S_CLUSTER_GRID_DEPTH = 24;
+const int S_CLUSTER_DATA_WIDTH = 1024;
+
+struct TClusteredLight {
+    vec3 position;
+    float radius;
+    vec3 color;
+    bool spot;
+    vec3 direction;
+    float halfHotspotConeAngleCos;
+    float halfConeAngleCos;
+    // Whether the light has its own deferred pass (for shadows or cookies) or not.
+    bool shadedSeparately;
+};
+
+ivec2 Internal_ClusterDataCoords(int index)
+{
+    return ivec2(index % S_CLUSTER_DATA_WIDTH, index / S_CLUSTER_DATA_WIDTH);
+}
+
+// Returns the index of the cluster that contains the given world-space position.
+int S_ClusterIndex(vec3 worldPosition, mat4 viewMatrix, mat4 viewProjectionMatrix, float zNear, float zFar)
+{
+    vec2 screenPos = clamp(S_Project(worldPosition, viewProjectionMatrix).xy, 0.0, 0.9999);
+    float depth = -(viewMatrix * vec4(worldPosition, 1.0)).z;
+    int slice = int(log(max(depth, zNear) / zNear) / log(zFar / zNear) * float(S_CLUSTER_GRID_DEPTH));
+    ivec3 cluster = ivec3(
+        int(screenPos.x * float(S_CLUSTER_GRID_WIDTH)),
+        int(screenPos.y * float(S_CLUSTER_GRID_HEIGHT)),
+        clamp(slice, 0, S_CLUSTER_GRID_DEPTH - 1)
+    );
+    return cluster.x + S_CLUSTER_GRID_WIDTH * (cluster.y + S_CLUSTER_GRID_HEIGHT * cluster.z);
+}
+
+// Returns the offset (x) of the light index list of the given cluster and the length of the list (y).
+ivec2 S_ClusterLightRange(usampler2D clusters, int clusterIndex)
+{
+    uint header = texelFetch(clusters, Internal_ClusterDataCoords(clusterIndex), 0).r;
+    return ivec2(int(header >> 8u), int(header & 255u));
+}
+
+// Returns the index of the light at the given position of the light index list.
+int S_FetchClusteredLightIndex(usampler2D clusters, int offset)
+{
+    return int(texelFetch(clusters, Internal_ClusterDataCoords(offset), 0).r);
+}
+
+TClusteredLight S_FetchClusteredLight(sampler2D lights, int lightIndex)
+{
+    vec4 t0 = texelFetch(lights, Internal_ClusterDataCoords(4 * lightIndex), 0);
+    vec4 t1 = texelFetch(lights, Internal_ClusterDataCoords(4 * lightIndex + 1), 0);
+    vec4 t2 = texelFetch(lights, Internal_ClusterDataCoords(4 * lightIndex + 2), 0);
+    vec4 t3 = texelFetch(lights, Internal_ClusterDataCoords(4 * lightIndex + 3), 0);
+
+    TClusteredLight light;
+    light.position = t0.xyz;
+    light.radius = t0.w;
+    light.color = t1.rgb;
+    light.spot = t1.w > 0.5;
+    light.direction = t2.xyz;
+    light.halfHotspotConeAngleCos = t2.w;
+    light.halfConeAngleCos = t3.x;
+    light.shadedSeparately = t3.y > 0.5;
+    return light;
+}
+
+// Returns distance and cone attenuation of the clustered light at the given position. Normalized
+// direction from the fragment to the light is written to `fragmentToLight`.
+float S_ClusteredLightAttenuation(TClusteredLight light, vec3 fragmentPosition, out vec3 fragmentToLight)
+{
+    vec3 toLight = light.position - fragmentPosition;
+    float lightDistance = length(toLight);
+    fragmentToLight = toLight / max(lightDistance, 0.0001);
+    float attenuation = S_LightDistanceAttenuation(lightDistance, light.radius);
+    if (light.spot) {
+        float spotAngleCos = dot(light.direction, fragmentToLight);
+        attenuation *= smoothstep(light.halfConeAngleCos, light.halfHotspotConeAngleCos, spotAngleCos);
+    }
+    return attenuation;
+}
+
+vec3 Internal_ClusteredLighting(
+    vec3 worldPosition,
+    bool enabled,
+    mat4 viewMatrix,
+    mat4 viewProjectionMatrix,
+    float zNear,
+    float zFar,
+    usampler2D clusters,
+    sampler2D lights)
+{
+    vec3 lighting = vec3(0.0);
+    if (!enabled) {
+        return lighting;
+    }
+    int clusterIndex = S_ClusterIndex(worldPosition, viewMatrix, viewProjectionMatrix, zNear, zFar);
+    ivec2 range = S_ClusterLightRange(clusters, clusterIndex);
+    for (int i = 0; i < range.y; ++i) {
+        TClusteredLight light = S_FetchClusteredLight(lights, S_FetchClusteredLightIndex(clusters, range.x + i));
+        vec3 fragmentToLight;
+        lighting += light.color * S_ClusteredLightAttenuation(light, worldPosition, fragmentToLight);
+    }
+    return lighting;
+}
+
+// Returns the sum of the colors of all point and spot lights affecting the given world-space position,
+// multiplied by their attenuation. Shaders, that use it, must declare `fyrox_clusterData`,
+// `fyrox_lightClusters` and `fyrox_clusteredLights` built-in resources.
+#define S_ClusteredLighting(worldPosition) Internal_ClusteredLighting(worldPosition, fyrox_clusterData.enabled, fyrox_clusterData.viewMatrix, fyrox_clusterData.viewProjectionMatrix, fyrox_clusterData.zNear, fyrox_clusterData.zFar, fyrox_lightClusters, fyrox_clusteredLights)
+
+// Layout of the 2D lights. Every light occupies three consecutive texels of the lights texture:
+// (position.xy, radius, height), (color.rgb, shadow map row or -1), (direction.xy,
+// halfHotspotConeAngleCos, halfConeAngleCos). Point lights have -1.0 cone angle cosines. Every shadow casting light has its own row in the
+// polar shadow map, S_SHADOW_MAP_2D_SIZE texels wide. A texel of the row contains the distance to
+// the closest occluder in the respective direction, divided by the radius of the light.
+const int S_LIGHT_2D_TEXELS = 3;
+const int S_SHADOW_MAP_2D_SIZE = 512;
+
+struct TLight2D {
+    vec2 position;
+    float radius;
+    float height;
+    vec3 color;
+    int shadowRow;
+    vec2 direction;
+    float halfHotspotConeAngleCos;
+    float halfConeAngleCos;
+};
+
+TLight2D S_FetchLight2D(sampler2D lights, int lightIndex)
+{
+    vec4 t0 = texelFetch(lights, ivec2(S_LIGHT_2D_TEXELS * lightIndex, 0), 0);
+    vec4 t1 = texelFetch(lights, ivec2(S_LIGHT_2D_TEXELS * lightIndex + 1, 0), 0);
+    vec4 t2 = texelFetch(lights, ivec2(S_LIGHT_2D_TEXELS * lightIndex + 2, 0), 0);
+
+    TLight2D light;
+    light.position = t0.xy;
+    light.radius = t0.z;
+    light.height = t0.w;
+    light.color = t1.rgb;
+    light.shadowRow = int(t1.w);
+    light.direction = t2.xy;
+    light.halfHotspotConeAngleCos = t2.z;
+    light.halfConeAngleCos = t2.w;
+    return light;
+}
+
+// Returns visibility of the given world-space position from the given 2D light, 1.0 - fully lit,
+// 0.0 - fully shadowed. Three neighbouring directions are sampled to smooth the shadow edges.
+float S_Shadow2D(sampler2D shadowMap, TLight2D light, vec2 worldPosition)
+{
+    if (light.shadowRow < 0) {
+        return 1.0;
+    }
+    vec2 toFragment = worldPosition - light.position;
+    float distance = length(toFragment) / light.radius;
+    float angle = atan(toFragment.y, toFragment.x);
+    int bin = int((angle + PI) / (2.0 * PI) * float(S_SHADOW_MAP_2D_SIZE));
+    float visibility = 0.0;
+    for (int i = -1; i <= 1; ++i) {
+        int x = (bin + i + S_SHADOW_MAP_2D_SIZE) % S_SHADOW_MAP_2D_SIZE;
+        float occluderDistance = texelFetch(shadowMap, ivec2(x, light.shadowRow), 0).r;
+        visibility += distance <= occluderDistance + 0.005 ? 1.0 : 0.0;
+    }
+    return visibility / 3.0;
+}
+
+vec3 Internal_Lighting2D(
+    vec3 worldPosition,
+    vec3 normal,
+    vec2 tangent,
+    vec2 bitangent,
+    int lightCount,
+    sampler2D lights,
+    sampler2D shadowMap)
+{
+    vec3 lighting = vec3(0.0);
+    for (int i = 0; i < lightCount; ++i) {
+        TLight2D light = S_FetchLight2D(lights, i);
+
+        vec2 toLight = light.position - worldPosition.xy;
+        float distance = length(toLight);
+        vec2 toLightNormalized = toLight / max(distance, 0.0001);
+        float attenuation = S_LightDistanceAttenuation(distance, light.radius);
+        if (light.halfConeAngleCos > -1.0) {
+            float spotAngleCos = dot(light.direction, -toLightNormalized);
+            attenuation *= smoothstep(light.halfConeAngleCos, light.halfHotspotConeAngleCos, spotAngleCos);
+        }
+        if (attenuation <= 0.0) {
+            continue;
+        }
+
+        // The light is lifted above the plane of the sprite by its height, the normal is compared
+        // with the direction to the light in the space of the sprite. Flat normal gives exactly
+        // the same result as if no normal map is used.
+        float normalFactor = 1.0;
+        if (light.height > 0.0) {
+            vec3 L = normalize(vec3(dot(toLight, tangent), dot(toLight, bitangent), light.height));
+            normalFactor = clamp(dot(normal, L) / L.z, 0.0, 2.0);
+        }
+
+        lighting += light.color * attenuation * normalFactor * S_Shadow2D(shadowMap, light, worldPosition.xy);
+    }
+    return lighting;
+}
+
+// Returns the sum of the colors of all 2D lights affecting the given world-space position,
+// multiplied by their attenuation and shadows. `normal` is a tangent-space normal (fetched
+// from a normal map, for example), `tangent` and `bitangent` are normalized world-space X and Y
+// axes of the sprite. Shaders, that use it, must declare `fyrox_lights2DData`, `fyrox_lights2D`
+// and `fyrox_shadowMap2D` built-in resources.
+#define S_Lighting2D(worldPosition, normal, tangent, bitangent) Internal_Lighting2D(worldPosition, normal, tangent, bitangent, fyrox_lights2DData.lightCount, fyrox_lights2D, fyrox_shadowMap2D)
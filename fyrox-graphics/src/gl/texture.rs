@@ -22,8 +22,8 @@ use crate::{
     error::FrameworkError,
     gl::{server::GlGraphicsServer, ToGlConstant},
     gpu_texture::{
-        image_1d_size_bytes, image_2d_size_bytes, image_3d_size_bytes, CubeMapFace,
-        GpuTextureDescriptor, GpuTextureKind, GpuTextureTrait, PixelKind,
+        image_1d_size_bytes, image_2d_size_bytes, image_3d_size_bytes, texture_data_size_bytes,
+        CubeMapFace, GpuTextureDescriptor, GpuTextureKind, GpuTextureTrait, PixelKind,
     },
 };
 use glow::{HasContext, PixelUnpackData, COMPRESSED_RED_RGTC1, COMPRESSED_RG_RGTC2};
@@ -321,54 +321,7 @@ impl GpuTextureTrait for GlTexture {
     ) -> Result<(), FrameworkError> {
         let mip_count = mip_count.max(1);
 
-        let mut desired_byte_count = 0;
-
-        'mip_loop: for mip in 0..mip_count {
-            match kind {
-                GpuTextureKind::Line { length } => {
-                    if let Some(length) = length.checked_shr(mip as u32) {
-                        desired_byte_count += image_1d_size_bytes(pixel_kind, length);
-                    } else {
-                        break 'mip_loop;
-                    }
-                }
-                GpuTextureKind::Rectangle { width, height } => {
-                    if let (Some(width), Some(height)) = (
-                        width.checked_shr(mip as u32),
-                        height.checked_shr(mip as u32),
-                    ) {
-                        desired_byte_count += image_2d_size_bytes(pixel_kind, width, height);
-                    } else {
-                        break 'mip_loop;
-                    }
-                }
-                GpuTextureKind::Cube { width, height } => {
-                    if let (Some(width), Some(height)) = (
-                        width.checked_shr(mip as u32),
-                        height.checked_shr(mip as u32),
-                    ) {
-                        desired_byte_count += 6 * image_2d_size_bytes(pixel_kind, width, height);
-                    } else {
-                        break 'mip_loop;
-                    }
-                }
-                GpuTextureKind::Volume {
-                    width,
-                    height,
-                    depth,
-                } => {
-                    if let (Some(width), Some(height), Some(depth)) = (
-                        width.checked_shr(mip as u32),
-                        height.checked_shr(mip as u32),
-                        depth.checked_shr(mip as u32),
-                    ) {
-                        desired_byte_count += image_3d_size_bytes(pixel_kind, width, height, depth);
-                    } else {
-                        break 'mip_loop;
-                    }
-                }
-            };
-        }
+        let desired_byte_count = texture_data_size_bytes(kind, pixel_kind, mip_count);
 
         if let Some(data) = data {
             let actual_data_size = data.len();
@@ -23,11 +23,11 @@
 
 #![warn(missing_docs)]
 
-use crate::{define_shared_wrapper, error::FrameworkError};
+use crate::{core::visitor::prelude::*, define_shared_wrapper, error::FrameworkError};
 use fyrox_core::define_as_any_trait;
 
 /// A kind of GPU texture.
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Visit)]
 pub enum GpuTextureKind {
     /// 1D texture.
     Line {
@@ -60,7 +60,7 @@ pub enum GpuTextureKind {
 }
 
 /// Pixel kind of GPU texture.
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Visit)]
 pub enum PixelKind {
     /// Floating point 32-bit pixel.
     R32F,
@@ -391,10 +391,70 @@ pub fn image_1d_size_bytes(pixel_kind: PixelKind, length: usize) -> usize {
     }
 }
 
+/// Calculates total size in bytes of a texture of the given kind, pixel kind and the amount of mips.
+/// Mip levels which size goes below a pixel are not counted.
+pub fn texture_data_size_bytes(
+    kind: GpuTextureKind,
+    pixel_kind: PixelKind,
+    mip_count: usize,
+) -> usize {
+    let mut desired_byte_count = 0;
+
+    'mip_loop: for mip in 0..mip_count.max(1) {
+        match kind {
+            GpuTextureKind::Line { length } => {
+                if let Some(length) = length.checked_shr(mip as u32) {
+                    desired_byte_count += image_1d_size_bytes(pixel_kind, length);
+                } else {
+                    break 'mip_loop;
+                }
+            }
+            GpuTextureKind::Rectangle { width, height } => {
+                if let (Some(width), Some(height)) = (
+                    width.checked_shr(mip as u32),
+                    height.checked_shr(mip as u32),
+                ) {
+                    desired_byte_count += image_2d_size_bytes(pixel_kind, width, height);
+                } else {
+                    break 'mip_loop;
+                }
+            }
+            GpuTextureKind::Cube { width, height } => {
+                if let (Some(width), Some(height)) = (
+                    width.checked_shr(mip as u32),
+                    height.checked_shr(mip as u32),
+                ) {
+                    desired_byte_count += 6 * image_2d_size_bytes(pixel_kind, width, height);
+                } else {
+                    break 'mip_loop;
+                }
+            }
+            GpuTextureKind::Volume {
+                width,
+                height,
+                depth,
+            } => {
+                if let (Some(width), Some(height), Some(depth)) = (
+                    width.checked_shr(mip as u32),
+                    height.checked_shr(mip as u32),
+                    depth.checked_shr(mip as u32),
+                ) {
+                    desired_byte_count += image_3d_size_bytes(pixel_kind, width, height, depth);
+                } else {
+                    break 'mip_loop;
+                }
+            }
+        };
+    }
+
+    desired_byte_count
+}
+
 /// Face of a cube map.
-#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Visit)]
 pub enum CubeMapFace {
     /// +X face.
+    #[default]
     PositiveX,
     /// -X face.
     NegativeX,
@@ -28,16 +28,19 @@ use serde::{Deserialize, Serialize};
 use strum_macros::{AsRefStr, EnumString, VariantNames};
 
 pub mod buffer;
+pub mod capture;
 pub mod error;
 pub mod framebuffer;
 pub mod geometry_buffer;
 pub mod gl;
 pub mod gpu_program;
 pub mod gpu_texture;
+pub mod null;
 pub mod query;
 pub mod read_buffer;
 pub mod sampler;
 pub mod server;
+pub mod software;
 pub mod stats;
 pub mod uniform;
 
@@ -542,7 +545,7 @@ impl Default for DrawParameters {
 }
 
 /// A range of elements (usually it's triangles) to draw in a draw call.
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Default)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Default, Visit)]
 pub enum ElementRange {
     /// All available elements. This is the default option.
     #[default]
@@ -558,9 +561,10 @@ pub enum ElementRange {
 }
 
 /// Element kind of geometry.
-#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default, Visit)]
 pub enum ElementKind {
     /// Triangles.
+    #[default]
     Triangle,
     /// Lines.
     Line,
@@ -569,7 +573,7 @@ pub enum ElementKind {
 }
 
 impl ElementKind {
-    fn index_per_element(self) -> usize {
+    pub(crate) fn index_per_element(self) -> usize {
         match self {
             ElementKind::Triangle => 3,
             ElementKind::Line => 2,
@@ -0,0 +1,103 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::{
+    buffer::{BufferKind, BufferUsage, GpuBufferTrait},
+    error::FrameworkError,
+    null::server::NullGraphicsServer,
+};
+use std::cell::{Ref, RefCell};
+
+pub struct NullBuffer {
+    id: u64,
+    kind: BufferKind,
+    usage: BufferUsage,
+    data: RefCell<Vec<u8>>,
+}
+
+impl NullBuffer {
+    pub fn new(
+        server: &NullGraphicsServer,
+        size: usize,
+        kind: BufferKind,
+        usage: BufferUsage,
+    ) -> Self {
+        Self {
+            id: server.next_id(),
+            kind,
+            usage,
+            data: RefCell::new(vec![0; size]),
+        }
+    }
+
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Returns the current content of the buffer.
+    pub fn data(&self) -> Ref<'_, Vec<u8>> {
+        self.data.borrow()
+    }
+}
+
+impl GpuBufferTrait for NullBuffer {
+    fn usage(&self) -> BufferUsage {
+        self.usage
+    }
+
+    fn kind(&self) -> BufferKind {
+        self.kind
+    }
+
+    fn size(&self) -> usize {
+        self.data.borrow().len()
+    }
+
+    fn write_data(&self, data: &[u8]) -> Result<(), FrameworkError> {
+        if data.is_empty() {
+            return Ok(());
+        }
+
+        let mut storage = self.data.borrow_mut();
+        if data.len() <= storage.len() {
+            // Update the data.
+            storage[..data.len()].copy_from_slice(data);
+        } else {
+            // Realloc the internal storage.
+            storage.clear();
+            storage.extend_from_slice(data);
+        }
+
+        Ok(())
+    }
+
+    fn read_data(&self, data: &mut [u8]) -> Result<(), FrameworkError> {
+        let storage = self.data.borrow();
+        let Some(source) = storage.get(..data.len()) else {
+            return Err(FrameworkError::Custom(format!(
+                "Unable to read {} bytes from a buffer of {} bytes!",
+                data.len(),
+                storage.len()
+            )));
+        };
+        data.copy_from_slice(source);
+        Ok(())
+    }
+}
@@ -0,0 +1,442 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::{
+    core::{color::Color, math::Rect},
+    error::FrameworkError,
+    framebuffer::{
+        Attachment, AttachmentKind, BufferDataUsage, DrawCallStatistics, GpuFrameBuffer,
+        GpuFrameBufferTrait, ReadTarget, ResourceBindGroup, ResourceBinding,
+    },
+    geometry_buffer::{GpuGeometryBuffer, GpuGeometryBufferTrait},
+    gpu_program::GpuProgram,
+    gpu_texture::{
+        image_2d_size_bytes, CubeMapFace, GpuTexture, GpuTextureKind, GpuTextureTrait, PixelKind,
+    },
+    null::{
+        buffer::NullBuffer,
+        geometry_buffer::NullGeometryBuffer,
+        program::NullProgram,
+        sampler::NullSampler,
+        server::{DrawCallRecord, NullGraphicsServer, ResourceBindingRecord},
+        texture::NullTexture,
+    },
+    DrawParameters, ElementRange,
+};
+use std::rc::Weak;
+
+pub(crate) fn null_texture(texture: &GpuTexture) -> Result<&NullTexture, FrameworkError> {
+    texture
+        .as_any()
+        .downcast_ref::<NullTexture>()
+        .ok_or_else(|| {
+            FrameworkError::Custom("The texture was not created by a null server!".to_string())
+        })
+}
+
+fn is_depth_format(pixel_kind: PixelKind) -> bool {
+    matches!(
+        pixel_kind,
+        PixelKind::D16 | PixelKind::D24S8 | PixelKind::D32F
+    )
+}
+
+fn validate_attachments(
+    depth_attachment: Option<&Attachment>,
+    color_attachments: &[Attachment],
+) -> Result<(), FrameworkError> {
+    if depth_attachment.is_none() && color_attachments.is_empty() {
+        return Err(FrameworkError::InvalidFrameBuffer);
+    }
+
+    if let Some(depth_attachment) = depth_attachment {
+        null_texture(&depth_attachment.texture)?;
+        let pixel_kind = depth_attachment.texture.pixel_kind();
+        let is_valid = match depth_attachment.kind {
+            AttachmentKind::Color => false,
+            AttachmentKind::Depth => is_depth_format(pixel_kind),
+            AttachmentKind::DepthStencil => pixel_kind == PixelKind::D24S8,
+        };
+        if !is_valid {
+            return Err(FrameworkError::Custom(format!(
+                "{:?} texture cannot be used as {:?} attachment!",
+                pixel_kind, depth_attachment.kind
+            )));
+        }
+    }
+
+    for color_attachment in color_attachments {
+        null_texture(&color_attachment.texture)?;
+        let pixel_kind = color_attachment.texture.pixel_kind();
+        if color_attachment.kind != AttachmentKind::Color
+            || is_depth_format(pixel_kind)
+            || pixel_kind.is_compressed()
+        {
+            return Err(FrameworkError::Custom(format!(
+                "{:?} texture cannot be used as {:?} attachment!",
+                pixel_kind, color_attachment.kind
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Copies a region of the first mip of the source texture to the destination texture with nearest
+/// filtering. Does nothing if the textures have different pixel formats.
+fn blit_texture(src: &NullTexture, dest: &NullTexture, src_rect: [i32; 4], dest_rect: [i32; 4]) {
+    let (
+        GpuTextureKind::Rectangle {
+            width: src_width,
+            height: src_height,
+        },
+        GpuTextureKind::Rectangle {
+            width: dest_width,
+            height: dest_height,
+        },
+    ) = (src.kind(), dest.kind())
+    else {
+        return;
+    };
+
+    let pixel_kind = src.pixel_kind();
+    if pixel_kind != dest.pixel_kind() || pixel_kind.is_compressed() {
+        return;
+    }
+
+    let pixel_size = image_2d_size_bytes(pixel_kind, 1, 1);
+    let [src_x0, src_y0, src_x1, src_y1] = src_rect;
+    let [dest_x0, dest_y0, dest_x1, dest_y1] = dest_rect;
+    let dest_w = (dest_x1 - dest_x0).max(1);
+    let dest_h = (dest_y1 - dest_y0).max(1);
+
+    let source = src.first_mip_data();
+    let mut dest_data = dest.data_mut();
+    for y in dest_y0.max(0)..dest_y1.min(dest_height as i32) {
+        let sy = src_y0 + (y - dest_y0) * (src_y1 - src_y0) / dest_h;
+        if sy < 0 || sy >= src_height as i32 {
+            continue;
+        }
+        for x in dest_x0.max(0)..dest_x1.min(dest_width as i32) {
+            let sx = src_x0 + (x - dest_x0) * (src_x1 - src_x0) / dest_w;
+            if sx < 0 || sx >= src_width as i32 {
+                continue;
+            }
+            let src_offset = (sy as usize * src_width + sx as usize) * pixel_size;
+            let dest_offset = (y as usize * dest_width + x as usize) * pixel_size;
+            dest_data[dest_offset..dest_offset + pixel_size]
+                .copy_from_slice(&source[src_offset..src_offset + pixel_size]);
+        }
+    }
+}
+
+pub struct NullFrameBuffer {
+    id: u64,
+    server: Weak<NullGraphicsServer>,
+    depth_attachment: Option<Attachment>,
+    color_attachments: Vec<Attachment>,
+}
+
+impl NullFrameBuffer {
+    pub fn new(
+        server: &NullGraphicsServer,
+        depth_attachment: Option<Attachment>,
+        color_attachments: Vec<Attachment>,
+    ) -> Result<Self, FrameworkError> {
+        validate_attachments(depth_attachment.as_ref(), &color_attachments)?;
+
+        Ok(Self {
+            id: server.next_id(),
+            server: server.weak(),
+            depth_attachment,
+            color_attachments,
+        })
+    }
+
+    pub fn backbuffer(server: &NullGraphicsServer) -> Self {
+        Self {
+            id: NullGraphicsServer::BACK_BUFFER_ID,
+            server: server.weak(),
+            depth_attachment: None,
+            color_attachments: Default::default(),
+        }
+    }
+
+    /// Creates a back buffer with the given attachments, it is used by graphics servers that
+    /// actually render something to the back buffer.
+    pub(crate) fn backbuffer_with_attachments(
+        server: &NullGraphicsServer,
+        depth_attachment: Option<Attachment>,
+        color_attachments: Vec<Attachment>,
+    ) -> Result<Self, FrameworkError> {
+        validate_attachments(depth_attachment.as_ref(), &color_attachments)?;
+
+        Ok(Self {
+            depth_attachment,
+            color_attachments,
+            ..Self::backbuffer(server)
+        })
+    }
+
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    fn record_draw(
+        &self,
+        instance_count: usize,
+        geometry: &GpuGeometryBuffer,
+        viewport: Rect<i32>,
+        program: &GpuProgram,
+        params: &DrawParameters,
+        resources: &[ResourceBindGroup],
+        element_range: ElementRange,
+    ) -> Result<DrawCallStatistics, FrameworkError> {
+        let server = self
+            .server
+            .upgrade()
+            .ok_or(FrameworkError::GraphicsServerUnavailable)?;
+        let geometry = geometry
+            .as_any()
+            .downcast_ref::<NullGeometryBuffer>()
+            .ok_or_else(|| {
+                FrameworkError::Custom(
+                    "The geometry buffer was not created by a null server!".to_string(),
+                )
+            })?;
+        let program = program
+            .as_any()
+            .downcast_ref::<NullProgram>()
+            .ok_or_else(|| {
+                FrameworkError::Custom("The program was not created by a null server!".to_string())
+            })?;
+
+        let (offset, element_count) = match element_range {
+            ElementRange::Full => (0, geometry.element_count()),
+            ElementRange::Specific { offset, count } => (offset, count),
+        };
+
+        let last_element_index = offset + element_count;
+
+        if last_element_index > geometry.element_count() {
+            return Err(FrameworkError::InvalidElementRange {
+                start: offset,
+                end: last_element_index,
+                total: geometry.element_count(),
+            });
+        }
+
+        let mut bindings = Vec::new();
+        for resource in resources.iter().flat_map(|group| group.bindings) {
+            match resource {
+                ResourceBinding::Texture {
+                    texture,
+                    sampler,
+                    binding,
+                } => {
+                    let sampler =
+                        sampler
+                            .as_any()
+                            .downcast_ref::<NullSampler>()
+                            .ok_or_else(|| {
+                                FrameworkError::Custom(
+                                    "The sampler was not created by a null server!".to_string(),
+                                )
+                            })?;
+                    bindings.push(ResourceBindingRecord::Texture {
+                        binding: *binding,
+                        texture: null_texture(texture)?.id(),
+                        sampler: sampler.id(),
+                    });
+                }
+                ResourceBinding::Buffer {
+                    buffer,
+                    binding,
+                    data_usage,
+                } => {
+                    let null_buffer =
+                        buffer
+                            .as_any()
+                            .downcast_ref::<NullBuffer>()
+                            .ok_or_else(|| {
+                                FrameworkError::Custom(
+                                    "The buffer was not created by a null server!".to_string(),
+                                )
+                            })?;
+                    let (offset, size) = match data_usage {
+                        BufferDataUsage::UseEverything => (0, buffer.size()),
+                        BufferDataUsage::UseSegment { offset, size } => (*offset, *size),
+                    };
+                    if offset + size > buffer.size() {
+                        return Err(FrameworkError::Custom(format!(
+                            "Buffer segment {}..{} is out of bounds of the buffer with {} bytes!",
+                            offset,
+                            offset + size,
+                            buffer.size()
+                        )));
+                    }
+                    bindings.push(ResourceBindingRecord::Buffer {
+                        binding: *binding,
+                        buffer: null_buffer.id(),
+                        offset,
+                        size,
+                        data: null_buffer.data()[offset..offset + size].to_vec(),
+                    });
+                }
+            }
+        }
+
+        server.push_draw_call(
+            geometry.id(),
+            program.id(),
+            DrawCallRecord {
+                framebuffer: self.id,
+                program: program.name().to_string(),
+                viewport,
+                params: params.clone(),
+                element_range,
+                element_count,
+                instance_count,
+                bindings,
+            },
+        );
+
+        Ok(DrawCallStatistics {
+            triangles: element_count * instance_count,
+        })
+    }
+}
+
+impl GpuFrameBufferTrait for NullFrameBuffer {
+    fn color_attachments(&self) -> &[Attachment] {
+        &self.color_attachments
+    }
+
+    fn depth_attachment(&self) -> Option<&Attachment> {
+        self.depth_attachment.as_ref()
+    }
+
+    fn set_cubemap_face(&self, _attachment_index: usize, _face: CubeMapFace, _level: usize) {}
+
+    fn blit_to(
+        &self,
+        dest: &GpuFrameBuffer,
+        src_x0: i32,
+        src_y0: i32,
+        src_x1: i32,
+        src_y1: i32,
+        dst_x0: i32,
+        dst_y0: i32,
+        dst_x1: i32,
+        dst_y1: i32,
+        copy_color: bool,
+        copy_depth: bool,
+        _copy_stencil: bool,
+    ) {
+        let src_rect = [src_x0, src_y0, src_x1, src_y1];
+        let dest_rect = [dst_x0, dst_y0, dst_x1, dst_y1];
+
+        let mut pairs = Vec::new();
+        if copy_color {
+            if let (Some(src), Some(dest)) = (
+                self.color_attachments.first(),
+                dest.color_attachments().first(),
+            ) {
+                pairs.push((&src.texture, &dest.texture));
+            }
+        }
+        if copy_depth {
+            if let (Some(src), Some(dest)) = (&self.depth_attachment, dest.depth_attachment()) {
+                pairs.push((&src.texture, &dest.texture));
+            }
+        }
+
+        for (src, dest) in pairs {
+            if let (Ok(src), Ok(dest)) = (null_texture(src), null_texture(dest)) {
+                if src.id() != dest.id() {
+                    blit_texture(src, dest, src_rect, dest_rect);
+                }
+            }
+        }
+    }
+
+    fn clear(
+        &self,
+        _viewport: Rect<i32>,
+        _color: Option<Color>,
+        _depth: Option<f32>,
+        _stencil: Option<i32>,
+    ) {
+    }
+
+    fn read_pixels(&self, read_target: ReadTarget) -> Option<Vec<u8>> {
+        let texture = match read_target {
+            ReadTarget::Depth | ReadTarget::Stencil => &self.depth_attachment.as_ref()?.texture,
+            ReadTarget::Color(index) => &self.color_attachments.get(index)?.texture,
+        };
+
+        if let GpuTextureKind::Rectangle { .. } = texture.kind() {
+            Some(null_texture(texture).ok()?.first_mip_data())
+        } else {
+            None
+        }
+    }
+
+    fn draw(
+        &self,
+        geometry: &GpuGeometryBuffer,
+        viewport: Rect<i32>,
+        program: &GpuProgram,
+        params: &DrawParameters,
+        resources: &[ResourceBindGroup],
+        element_range: ElementRange,
+    ) -> Result<DrawCallStatistics, FrameworkError> {
+        self.record_draw(
+            1,
+            geometry,
+            viewport,
+            program,
+            params,
+            resources,
+            element_range,
+        )
+    }
+
+    fn draw_instances(
+        &self,
+        instance_count: usize,
+        geometry: &GpuGeometryBuffer,
+        viewport: Rect<i32>,
+        program: &GpuProgram,
+        params: &DrawParameters,
+        resources: &[ResourceBindGroup],
+        element_range: ElementRange,
+    ) -> Result<DrawCallStatistics, FrameworkError> {
+        self.record_draw(
+            instance_count,
+            geometry,
+            viewport,
+            program,
+            params,
+            resources,
+            element_range,
+        )
+    }
+}
@@ -0,0 +1,181 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::{
+    buffer::BufferUsage,
+    core::{array_as_u8_slice, math::TriangleDefinition},
+    error::FrameworkError,
+    geometry_buffer::{
+        AttributeDefinition, ElementsDescriptor, GeometryBufferDescriptor, GpuGeometryBufferTrait,
+        VertexBufferDescriptor,
+    },
+    null::server::NullGraphicsServer,
+    ElementKind,
+};
+use std::cell::{Cell, Ref, RefCell};
+
+/// CPU-side copy of a vertex buffer of a geometry buffer.
+pub struct NullVertexBuffer {
+    /// Usage of the vertex buffer.
+    pub usage: BufferUsage,
+    /// Attributes of the vertex buffer.
+    pub attributes: Vec<AttributeDefinition>,
+    /// Size of a single vertex in bytes.
+    pub element_size: usize,
+    /// Raw vertex data.
+    pub data: Vec<u8>,
+}
+
+fn validate_vertex_buffer(
+    buffer: &VertexBufferDescriptor,
+    locations: &mut Vec<u32>,
+) -> Result<(), FrameworkError> {
+    let mut vertex_size = 0;
+    for attribute in buffer.attributes {
+        if !(1..=4).contains(&attribute.component_count) || locations.contains(&attribute.location)
+        {
+            return Err(FrameworkError::InvalidAttributeDescriptor);
+        }
+        locations.push(attribute.location);
+        vertex_size += attribute.kind.size() * attribute.component_count;
+    }
+
+    if vertex_size > buffer.data.element_size {
+        return Err(FrameworkError::InvalidAttributeDescriptor);
+    }
+
+    if let Some(bytes) = buffer.data.bytes {
+        if buffer.data.element_size == 0 || bytes.len() % buffer.data.element_size != 0 {
+            return Err(FrameworkError::Custom(format!(
+                "Vertex buffer data size {} is not a multiple of the vertex size {}!",
+                bytes.len(),
+                buffer.data.element_size
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+pub struct NullGeometryBuffer {
+    id: u64,
+    element_kind: ElementKind,
+    element_count: Cell<usize>,
+    buffers: RefCell<Vec<NullVertexBuffer>>,
+    elements: RefCell<Vec<u32>>,
+}
+
+impl NullGeometryBuffer {
+    pub fn new(
+        server: &NullGraphicsServer,
+        desc: GeometryBufferDescriptor,
+    ) -> Result<Self, FrameworkError> {
+        let mut locations = Vec::new();
+        let mut buffers = Vec::with_capacity(desc.buffers.len());
+        for buffer in desc.buffers {
+            validate_vertex_buffer(buffer, &mut locations)?;
+            buffers.push(NullVertexBuffer {
+                usage: buffer.usage,
+                attributes: buffer.attributes.to_vec(),
+                element_size: buffer.data.element_size,
+                data: buffer.data.bytes.map(|b| b.to_vec()).unwrap_or_default(),
+            });
+        }
+
+        let element_kind = desc.elements.element_kind();
+        let (element_count, elements) = match desc.elements {
+            ElementsDescriptor::Triangles(triangles) => {
+                (triangles.len(), array_as_u8_slice(triangles))
+            }
+            ElementsDescriptor::Lines(lines) => (lines.len(), array_as_u8_slice(lines)),
+            ElementsDescriptor::Points(points) => (points.len(), array_as_u8_slice(points)),
+        };
+
+        let geometry = Self {
+            id: server.next_id(),
+            element_kind,
+            element_count: Cell::new(element_count),
+            buffers: RefCell::new(buffers),
+            elements: Default::default(),
+        };
+
+        geometry.set_elements(elements);
+
+        Ok(geometry)
+    }
+
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn element_kind(&self) -> ElementKind {
+        self.element_kind
+    }
+
+    /// Returns vertex buffers of the geometry buffer.
+    pub fn buffers(&self) -> Ref<'_, Vec<NullVertexBuffer>> {
+        self.buffers.borrow()
+    }
+
+    /// Returns a flat list of vertex indices of all the elements.
+    pub fn indices(&self) -> Ref<'_, Vec<u32>> {
+        self.elements.borrow()
+    }
+
+    fn set_elements(&self, data: &[u8]) {
+        let mut elements = self.elements.borrow_mut();
+        elements.clear();
+        elements.extend(
+            data.chunks_exact(size_of::<u32>())
+                .map(|bytes| u32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])),
+        );
+    }
+}
+
+impl GpuGeometryBufferTrait for NullGeometryBuffer {
+    fn set_buffer_data(&self, buffer: usize, data: &[u8]) {
+        let mut buffers = self.buffers.borrow_mut();
+        let storage = &mut buffers[buffer].data;
+        storage.clear();
+        storage.extend_from_slice(data);
+    }
+
+    fn element_count(&self) -> usize {
+        self.element_count.get()
+    }
+
+    fn set_triangles(&self, triangles: &[TriangleDefinition]) {
+        assert_eq!(self.element_kind, ElementKind::Triangle);
+        self.element_count.set(triangles.len());
+        self.set_elements(array_as_u8_slice(triangles));
+    }
+
+    fn set_lines(&self, lines: &[[u32; 2]]) {
+        assert_eq!(self.element_kind, ElementKind::Line);
+        self.element_count.set(lines.len());
+        self.set_elements(array_as_u8_slice(lines));
+    }
+
+    fn set_points(&self, points: &[u32]) {
+        assert_eq!(self.element_kind, ElementKind::Point);
+        self.element_count.set(points.len());
+        self.set_elements(array_as_u8_slice(points));
+    }
+}
@@ -0,0 +1,130 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::{
+    error::FrameworkError,
+    gpu_program::{GpuProgramTrait, GpuShaderTrait, ShaderKind, ShaderResourceDefinition},
+    null::server::NullGraphicsServer,
+};
+
+fn validate_source(name: &str, source: &str) -> Result<(), FrameworkError> {
+    if source.contains('\0') {
+        Err(FrameworkError::FaultyShaderSource)
+    } else if source.trim().is_empty() {
+        Err(FrameworkError::ShaderCompilationFailed {
+            shader_name: name.to_string(),
+            error_message: "Shader source is empty.".to_string(),
+        })
+    } else {
+        Ok(())
+    }
+}
+
+pub struct NullShader {
+    id: u64,
+    name: String,
+    kind: ShaderKind,
+    source: String,
+}
+
+impl NullShader {
+    pub fn new(
+        server: &NullGraphicsServer,
+        name: String,
+        kind: ShaderKind,
+        source: String,
+    ) -> Result<Self, FrameworkError> {
+        validate_source(&name, &source)?;
+        Ok(Self {
+            id: server.next_id(),
+            name,
+            kind,
+            source,
+        })
+    }
+
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn kind(&self) -> &ShaderKind {
+        &self.kind
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+}
+
+impl GpuShaderTrait for NullShader {}
+
+pub struct NullProgram {
+    id: u64,
+    name: String,
+    vertex_source: String,
+    fragment_source: String,
+    resources: Vec<ShaderResourceDefinition>,
+}
+
+impl NullProgram {
+    pub fn new(
+        server: &NullGraphicsServer,
+        name: &str,
+        vertex_source: String,
+        fragment_source: String,
+        resources: &[ShaderResourceDefinition],
+    ) -> Result<Self, FrameworkError> {
+        validate_source(name, &vertex_source)?;
+        validate_source(name, &fragment_source)?;
+        Ok(Self {
+            id: server.next_id(),
+            name: name.to_string(),
+            vertex_source,
+            fragment_source,
+            resources: resources.to_vec(),
+        })
+    }
+
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn vertex_source(&self) -> &str {
+        &self.vertex_source
+    }
+
+    pub fn fragment_source(&self) -> &str {
+        &self.fragment_source
+    }
+
+    pub fn resources(&self) -> &[ShaderResourceDefinition] {
+        &self.resources
+    }
+}
+
+impl GpuProgramTrait for NullProgram {}
@@ -0,0 +1,58 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::query::{GpuQueryTrait, QueryKind, QueryResult};
+use std::cell::Cell;
+
+/// A query that does not measure anything and always reports that everything is visible. This
+/// way the renderer never skips anything because of occlusion culling.
+#[derive(Debug, Default)]
+pub struct NullQuery {
+    active_query: Cell<Option<QueryKind>>,
+    is_finished: Cell<bool>,
+}
+
+impl GpuQueryTrait for NullQuery {
+    fn begin(&self, kind: QueryKind) {
+        self.active_query.set(Some(kind));
+        self.is_finished.set(false);
+    }
+
+    fn end(&self) {
+        if self.active_query.get().is_some() {
+            self.is_finished.set(true);
+        }
+    }
+
+    fn is_started(&self) -> bool {
+        self.active_query.get().is_some()
+    }
+
+    fn try_get_result(&self) -> Option<QueryResult> {
+        if !self.is_finished.get() {
+            return None;
+        }
+
+        self.active_query.get().map(|kind| match kind {
+            QueryKind::SamplesPassed => QueryResult::SamplesPassed(u32::MAX),
+            QueryKind::AnySamplesPassed => QueryResult::AnySamplesPassed(true),
+        })
+    }
+}
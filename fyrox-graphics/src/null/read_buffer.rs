@@ -0,0 +1,121 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::{
+    core::math::Rect,
+    error::FrameworkError,
+    framebuffer::GpuFrameBufferTrait,
+    gpu_texture::{image_2d_size_bytes, GpuTextureKind},
+    null::framebuffer::null_texture,
+    read_buffer::GpuAsyncReadBufferTrait,
+};
+use std::cell::RefCell;
+
+/// Read buffer that copies the pixels immediately, so the result is available right after the
+/// transfer was scheduled.
+pub struct NullAsyncReadBuffer {
+    pixel_size: usize,
+    pixel_count: usize,
+    result: RefCell<Option<Vec<u8>>>,
+}
+
+impl NullAsyncReadBuffer {
+    pub fn new(pixel_size: usize, pixel_count: usize) -> Self {
+        Self {
+            pixel_size,
+            pixel_count,
+            result: Default::default(),
+        }
+    }
+}
+
+impl GpuAsyncReadBufferTrait for NullAsyncReadBuffer {
+    fn schedule_pixels_transfer(
+        &self,
+        framebuffer: &dyn GpuFrameBufferTrait,
+        color_buffer_index: u32,
+        rect: Option<Rect<i32>>,
+    ) -> Result<(), FrameworkError> {
+        if self.result.borrow().is_some() {
+            return Ok(());
+        }
+
+        let color_attachment = &framebuffer
+            .color_attachments()
+            .get(color_buffer_index as usize)
+            .ok_or_else(|| {
+                FrameworkError::Custom(format!(
+                    "Framebuffer does not have {color_buffer_index} color attachment!"
+                ))
+            })?
+            .texture;
+
+        let GpuTextureKind::Rectangle { width, height } = color_attachment.kind() else {
+            return Err(FrameworkError::Custom(
+                "Only rectangular textures can be read from GPU!".to_string(),
+            ));
+        };
+
+        let pixel_kind = color_attachment.pixel_kind();
+        let actual_size = image_2d_size_bytes(pixel_kind, width, height);
+        let self_bytes_count = self.pixel_count * self.pixel_size;
+        if actual_size != self_bytes_count {
+            return Err(FrameworkError::Custom(format!(
+                "Pixel buffer size {self_bytes_count} does not match the size {actual_size} of the \
+                color attachment {color_buffer_index} of the frame buffer",
+            )));
+        }
+
+        let target_rect = rect.unwrap_or_else(|| Rect::new(0, 0, width as i32, height as i32));
+
+        let pixel_size = image_2d_size_bytes(pixel_kind, 1, 1);
+        let source = null_texture(color_attachment)?.first_mip_data();
+        let mut buffer = vec![0; self_bytes_count];
+        let row_size = target_rect.size.x.max(0) as usize * pixel_size;
+        for (row, y) in
+            (target_rect.position.y..target_rect.position.y + target_rect.size.y).enumerate()
+        {
+            let x = target_rect.position.x;
+            if y < 0 || y >= height as i32 || x < 0 || x + target_rect.size.x > width as i32 {
+                continue;
+            }
+            let src_offset = (y as usize * width + x as usize) * pixel_size;
+            let dest_offset = row * row_size;
+            if let (Some(dest), Some(src)) = (
+                buffer.get_mut(dest_offset..dest_offset + row_size),
+                source.get(src_offset..src_offset + row_size),
+            ) {
+                dest.copy_from_slice(src);
+            }
+        }
+
+        *self.result.borrow_mut() = Some(buffer);
+
+        Ok(())
+    }
+
+    fn is_request_running(&self) -> bool {
+        self.result.borrow().is_some()
+    }
+
+    fn try_read(&self) -> Option<Vec<u8>> {
+        self.result.borrow_mut().take()
+    }
+}
@@ -0,0 +1,647 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::{
+    buffer::{BufferKind, BufferUsage, GpuBuffer},
+    core::math::Rect,
+    error::FrameworkError,
+    framebuffer::{Attachment, GpuFrameBuffer},
+    geometry_buffer::{GeometryBufferDescriptor, GpuGeometryBuffer},
+    gpu_program::{GpuProgram, GpuShader, ShaderKind, ShaderResourceDefinition},
+    gpu_texture::{GpuTexture, GpuTextureDescriptor},
+    null::{
+        buffer::NullBuffer, framebuffer::NullFrameBuffer, geometry_buffer::NullGeometryBuffer,
+        program::NullProgram, program::NullShader, query::NullQuery,
+        read_buffer::NullAsyncReadBuffer, sampler::NullSampler, texture::NullTexture,
+    },
+    query::GpuQuery,
+    read_buffer::GpuAsyncReadBuffer,
+    sampler::{GpuSampler, GpuSamplerDescriptor},
+    server::{GraphicsServer, ServerCapabilities},
+    stats::PipelineStatistics,
+    BlendParameters, DrawParameters, ElementRange, PolygonFace, PolygonFillMode,
+};
+use std::{
+    cell::{Ref, RefCell},
+    rc::{Rc, Weak},
+};
+
+/// A resource that was bound to the pipeline during a draw call.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ResourceBindingRecord {
+    /// Texture binding.
+    Texture {
+        /// Binding point of the texture.
+        binding: usize,
+        /// Id of the texture.
+        texture: u64,
+        /// Id of the sampler.
+        sampler: u64,
+    },
+    /// Buffer binding.
+    Buffer {
+        /// Binding point of the buffer.
+        binding: usize,
+        /// Id of the buffer.
+        buffer: u64,
+        /// Offset of the used data segment in bytes.
+        offset: usize,
+        /// Size of the used data segment in bytes.
+        size: usize,
+        /// Content of the used data segment at the moment of the draw call.
+        data: Vec<u8>,
+    },
+}
+
+/// A draw call that was issued to the null graphics server.
+#[derive(Clone, Debug)]
+pub struct DrawCallRecord {
+    /// Id of the target frame buffer. Back buffer has [`NullGraphicsServer::BACK_BUFFER_ID`] id.
+    pub framebuffer: u64,
+    /// Name of the program that was used to draw.
+    pub program: String,
+    /// Viewport of the draw call.
+    pub viewport: Rect<i32>,
+    /// Pipeline state of the draw call.
+    pub params: DrawParameters,
+    /// Requested range of elements.
+    pub element_range: ElementRange,
+    /// Actual number of drawn elements (per instance).
+    pub element_count: usize,
+    /// Number of drawn instances. It is always 1 for non-instanced draw calls.
+    pub instance_count: usize,
+    /// Resources that were bound to the pipeline.
+    pub bindings: Vec<ResourceBindingRecord>,
+}
+
+#[derive(Default)]
+struct NullServerState {
+    next_id: u64,
+    frame_size: (u32, u32),
+    frame_count: usize,
+    polygon_face: PolygonFace,
+    polygon_fill_mode: PolygonFillMode,
+    framebuffer: Option<u64>,
+    program: Option<u64>,
+    geometry: Option<u64>,
+    blend: Option<BlendParameters>,
+    textures: Vec<Option<u64>>,
+    frame_statistics: PipelineStatistics,
+    draw_calls: Vec<DrawCallRecord>,
+}
+
+/// Graphics server that does not require a GPU. All the objects are stored in CPU memory, all
+/// descriptors are validated the same way as a real graphics server would do it, but nothing
+/// is actually rendered. Every draw call is recorded with its pipeline state and resource bindings,
+/// so the recorded calls can be inspected later. It is mostly useful for testing.
+///
+/// ```rust
+/// use fyrox_graphics::{null::server::NullGraphicsServer, server::GraphicsServer};
+///
+/// let server = NullGraphicsServer::new((640, 480));
+/// let back_buffer = server.back_buffer();
+/// // Render something here.
+/// assert_eq!(server.draw_calls().len(), 0);
+/// ```
+pub struct NullGraphicsServer {
+    state: RefCell<NullServerState>,
+    this: RefCell<Option<Weak<NullGraphicsServer>>>,
+}
+
+impl NullGraphicsServer {
+    /// Id of the back buffer.
+    pub const BACK_BUFFER_ID: u64 = 0;
+
+    /// Creates a new null graphics server with the given back buffer size.
+    pub fn new(frame_size: (u32, u32)) -> Rc<Self> {
+        let server = Rc::new(Self {
+            state: RefCell::new(NullServerState {
+                // Zero is reserved for the back buffer.
+                next_id: Self::BACK_BUFFER_ID + 1,
+                frame_size,
+                ..Default::default()
+            }),
+            this: Default::default(),
+        });
+
+        *server.this.borrow_mut() = Some(Rc::downgrade(&server));
+
+        server
+    }
+
+    pub fn weak(&self) -> Weak<Self> {
+        self.this.borrow().as_ref().unwrap().clone()
+    }
+
+    pub(crate) fn next_id(&self) -> u64 {
+        let mut state = self.state.borrow_mut();
+        let id = state.next_id;
+        state.next_id += 1;
+        id
+    }
+
+    pub(crate) fn push_draw_call(&self, geometry: u64, program: u64, record: DrawCallRecord) {
+        let mut state = self.state.borrow_mut();
+
+        if state.framebuffer != Some(record.framebuffer) {
+            state.framebuffer = Some(record.framebuffer);
+            state.frame_statistics.framebuffer_binding_changes += 1;
+        }
+
+        if state.program != Some(program) {
+            state.program = Some(program);
+            state.frame_statistics.program_binding_changes += 1;
+        }
+
+        if state.geometry != Some(geometry) {
+            state.geometry = Some(geometry);
+            state.frame_statistics.vao_binding_changes += 1;
+        }
+
+        if state.blend != record.params.blend {
+            state.blend.clone_from(&record.params.blend);
+            state.frame_statistics.blend_state_changes += 1;
+        }
+
+        for binding in record.bindings.iter() {
+            if let ResourceBindingRecord::Texture {
+                binding, texture, ..
+            } = *binding
+            {
+                if state.textures.len() <= binding {
+                    state.textures.resize(binding + 1, None);
+                }
+                if state.textures[binding] != Some(texture) {
+                    state.textures[binding] = Some(texture);
+                    state.frame_statistics.texture_binding_changes += 1;
+                }
+            }
+        }
+
+        state.draw_calls.push(record);
+    }
+
+    /// Returns all the draw calls recorded since the last call of [`Self::take_draw_calls`].
+    pub fn draw_calls(&self) -> Ref<'_, [DrawCallRecord]> {
+        Ref::map(self.state.borrow(), |state| state.draw_calls.as_slice())
+    }
+
+    /// Returns all the recorded draw calls and clears the internal list.
+    pub fn take_draw_calls(&self) -> Vec<DrawCallRecord> {
+        std::mem::take(&mut self.state.borrow_mut().draw_calls)
+    }
+
+    /// Returns the number of frames presented by [`GraphicsServer::swap_buffers`].
+    pub fn frame_count(&self) -> usize {
+        self.state.borrow().frame_count
+    }
+
+    /// Returns current size of the back buffer.
+    pub fn frame_size(&self) -> (u32, u32) {
+        self.state.borrow().frame_size
+    }
+
+    /// Returns current polygon face and fill mode.
+    pub fn polygon_fill_mode(&self) -> (PolygonFace, PolygonFillMode) {
+        let state = self.state.borrow();
+        (state.polygon_face, state.polygon_fill_mode)
+    }
+
+    pub(crate) fn max_lod_bias(&self) -> f32 {
+        16.0
+    }
+}
+
+impl GraphicsServer for NullGraphicsServer {
+    fn create_buffer(
+        &self,
+        size: usize,
+        buffer_kind: BufferKind,
+        buffer_usage: BufferUsage,
+    ) -> Result<GpuBuffer, FrameworkError> {
+        Ok(GpuBuffer(Rc::new(NullBuffer::new(
+            self,
+            size,
+            buffer_kind,
+            buffer_usage,
+        ))))
+    }
+
+    fn create_texture(&self, desc: GpuTextureDescriptor) -> Result<GpuTexture, FrameworkError> {
+        Ok(GpuTexture(Rc::new(NullTexture::new(self, desc)?)))
+    }
+
+    fn create_sampler(&self, desc: GpuSamplerDescriptor) -> Result<GpuSampler, FrameworkError> {
+        Ok(GpuSampler(Rc::new(NullSampler::new(self, desc))))
+    }
+
+    fn create_frame_buffer(
+        &self,
+        depth_attachment: Option<Attachment>,
+        color_attachments: Vec<Attachment>,
+    ) -> Result<GpuFrameBuffer, FrameworkError> {
+        Ok(GpuFrameBuffer(Rc::new(NullFrameBuffer::new(
+            self,
+            depth_attachment,
+            color_attachments,
+        )?)))
+    }
+
+    fn back_buffer(&self) -> GpuFrameBuffer {
+        GpuFrameBuffer(Rc::new(NullFrameBuffer::backbuffer(self)))
+    }
+
+    fn create_query(&self) -> Result<GpuQuery, FrameworkError> {
+        Ok(GpuQuery(Rc::new(NullQuery::default())))
+    }
+
+    fn create_shader(
+        &self,
+        name: String,
+        kind: ShaderKind,
+        source: String,
+        _resources: &[ShaderResourceDefinition],
+        _line_offset: isize,
+    ) -> Result<GpuShader, FrameworkError> {
+        Ok(GpuShader(Rc::new(NullShader::new(
+            self, name, kind, source,
+        )?)))
+    }
+
+    fn create_program(
+        &self,
+        name: &str,
+        vertex_source: String,
+        _vertex_source_line_offset: isize,
+        fragment_source: String,
+        _fragment_source_line_offset: isize,
+        resources: &[ShaderResourceDefinition],
+    ) -> Result<GpuProgram, FrameworkError> {
+        Ok(GpuProgram(Rc::new(NullProgram::new(
+            self,
+            name,
+            vertex_source,
+            fragment_source,
+            resources,
+        )?)))
+    }
+
+    fn create_async_read_buffer(
+        &self,
+        pixel_size: usize,
+        pixel_count: usize,
+    ) -> Result<GpuAsyncReadBuffer, FrameworkError> {
+        Ok(GpuAsyncReadBuffer(Rc::new(NullAsyncReadBuffer::new(
+            pixel_size,
+            pixel_count,
+        ))))
+    }
+
+    fn create_geometry_buffer(
+        &self,
+        desc: GeometryBufferDescriptor,
+    ) -> Result<GpuGeometryBuffer, FrameworkError> {
+        Ok(GpuGeometryBuffer(Rc::new(NullGeometryBuffer::new(
+            self, desc,
+        )?)))
+    }
+
+    fn weak(self: Rc<Self>) -> Weak<dyn GraphicsServer> {
+        self.this.borrow().as_ref().unwrap().clone()
+    }
+
+    fn flush(&self) {}
+
+    fn finish(&self) {}
+
+    fn invalidate_resource_bindings_cache(&self) {
+        let mut state = self.state.borrow_mut();
+        state.textures.clear();
+        state.program = None;
+        state.frame_statistics = Default::default();
+    }
+
+    fn pipeline_statistics(&self) -> PipelineStatistics {
+        self.state.borrow().frame_statistics
+    }
+
+    fn swap_buffers(&self) -> Result<(), FrameworkError> {
+        self.state.borrow_mut().frame_count += 1;
+        Ok(())
+    }
+
+    fn set_frame_size(&self, new_size: (u32, u32)) {
+        self.state.borrow_mut().frame_size = new_size;
+    }
+
+    fn capabilities(&self) -> ServerCapabilities {
+        ServerCapabilities {
+            max_uniform_block_size: 65536,
+            uniform_buffer_offset_alignment: 256,
+            max_lod_bias: self.max_lod_bias(),
+        }
+    }
+
+    fn set_polygon_fill_mode(&self, polygon_face: PolygonFace, polygon_fill_mode: PolygonFillMode) {
+        let mut state = self.state.borrow_mut();
+        state.polygon_face = polygon_face;
+        state.polygon_fill_mode = polygon_fill_mode;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        core::math::TriangleDefinition,
+        framebuffer::{BufferDataUsage, ReadTarget, ResourceBindGroup, ResourceBinding},
+        geometry_buffer::{
+            AttributeDefinition, AttributeKind, ElementsDescriptor, VertexBufferData,
+            VertexBufferDescriptor,
+        },
+        gpu_texture::{GpuTextureKind, PixelKind},
+    };
+
+    fn triangle(server: &NullGraphicsServer) -> GpuGeometryBuffer {
+        let vertices = [0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0];
+        server
+            .create_geometry_buffer(GeometryBufferDescriptor {
+                buffers: &[VertexBufferDescriptor {
+                    usage: BufferUsage::StaticDraw,
+                    attributes: &[AttributeDefinition {
+                        location: 0,
+                        kind: AttributeKind::Float,
+                        component_count: 3,
+                        normalized: false,
+                        divisor: 0,
+                    }],
+                    data: VertexBufferData {
+                        element_size: 3 * size_of::<f32>(),
+                        bytes: Some(crate::core::array_as_u8_slice(&vertices)),
+                    },
+                }],
+                usage: BufferUsage::StaticDraw,
+                elements: ElementsDescriptor::Triangles(&[TriangleDefinition([0, 1, 2])]),
+            })
+            .unwrap()
+    }
+
+    fn program(server: &NullGraphicsServer, name: &str) -> GpuProgram {
+        server
+            .create_program(
+                name,
+                "void main() {}".to_string(),
+                0,
+                "void main() {}".to_string(),
+                0,
+                &[],
+            )
+            .unwrap()
+    }
+
+    fn render_target(server: &NullGraphicsServer) -> GpuFrameBuffer {
+        let color = server
+            .create_2d_render_target(PixelKind::RGBA8, 4, 4)
+            .unwrap();
+        let depth = server
+            .create_2d_render_target(PixelKind::D24S8, 4, 4)
+            .unwrap();
+        server
+            .create_frame_buffer(
+                Some(Attachment::depth_stencil(depth)),
+                vec![Attachment::color(color)],
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn test_descriptor_validation() {
+        let server = NullGraphicsServer::new((4, 4));
+
+        assert!(matches!(
+            server.create_texture(GpuTextureDescriptor {
+                kind: GpuTextureKind::Rectangle {
+                    width: 2,
+                    height: 2
+                },
+                data: Some(&[0; 3]),
+                ..Default::default()
+            }),
+            Err(FrameworkError::InvalidTextureData {
+                expected_data_size: 16,
+                actual_data_size: 3
+            })
+        ));
+
+        assert!(matches!(
+            server.create_frame_buffer(None, vec![]),
+            Err(FrameworkError::InvalidFrameBuffer)
+        ));
+
+        let color = server
+            .create_2d_render_target(PixelKind::RGBA8, 4, 4)
+            .unwrap();
+        assert!(server
+            .create_frame_buffer(Some(Attachment::depth(color)), vec![])
+            .is_err());
+
+        let depth = server
+            .create_2d_render_target(PixelKind::D32F, 4, 4)
+            .unwrap();
+        assert!(server
+            .create_frame_buffer(Some(Attachment::depth_stencil(depth)), vec![])
+            .is_err());
+
+        assert!(matches!(
+            server.create_geometry_buffer(GeometryBufferDescriptor {
+                buffers: &[VertexBufferDescriptor {
+                    usage: BufferUsage::StaticDraw,
+                    attributes: &[AttributeDefinition {
+                        location: 0,
+                        kind: AttributeKind::Float,
+                        component_count: 4,
+                        normalized: false,
+                        divisor: 0,
+                    }],
+                    data: VertexBufferData::new::<[f32; 3]>(None),
+                }],
+                usage: BufferUsage::StaticDraw,
+                elements: ElementsDescriptor::Points(&[]),
+            }),
+            Err(FrameworkError::InvalidAttributeDescriptor)
+        ));
+
+        assert!(server
+            .create_program("Empty", String::new(), 0, String::new(), 0, &[])
+            .is_err());
+    }
+
+    #[test]
+    fn test_draw_call_recording() {
+        let server = NullGraphicsServer::new((4, 4));
+        let framebuffer = render_target(&server);
+        let geometry = triangle(&server);
+        let program_a = program(&server, "A");
+        let program_b = program(&server, "B");
+        let texture = server
+            .create_2d_render_target(PixelKind::RGBA8, 1, 1)
+            .unwrap();
+        let sampler = server.create_sampler(Default::default()).unwrap();
+        let buffer = server
+            .create_buffer(64, BufferKind::Uniform, BufferUsage::StreamDraw)
+            .unwrap();
+        let viewport = Rect::new(0, 0, 4, 4);
+
+        let bindings = [
+            ResourceBinding::texture(&texture, &sampler, 0),
+            ResourceBinding::buffer(
+                &buffer,
+                1,
+                BufferDataUsage::UseSegment {
+                    offset: 16,
+                    size: 32,
+                },
+            ),
+        ];
+
+        let stats = framebuffer
+            .draw(
+                &geometry,
+                viewport,
+                &program_a,
+                &DrawParameters::default(),
+                &[ResourceBindGroup {
+                    bindings: &bindings,
+                }],
+                ElementRange::Full,
+            )
+            .unwrap();
+        assert_eq!(stats.triangles, 1);
+
+        let stats = server
+            .back_buffer()
+            .draw_instances(
+                10,
+                &geometry,
+                viewport,
+                &program_b,
+                &DrawParameters {
+                    blend: Some(Default::default()),
+                    ..Default::default()
+                },
+                &[],
+                ElementRange::Full,
+            )
+            .unwrap();
+        assert_eq!(stats.triangles, 10);
+
+        assert!(matches!(
+            framebuffer.draw(
+                &geometry,
+                viewport,
+                &program_a,
+                &DrawParameters::default(),
+                &[],
+                ElementRange::Specific {
+                    offset: 1,
+                    count: 1
+                },
+            ),
+            Err(FrameworkError::InvalidElementRange {
+                start: 1,
+                end: 2,
+                total: 1
+            })
+        ));
+
+        let draw_calls = server.take_draw_calls();
+        assert_eq!(draw_calls.len(), 2);
+        assert_eq!(draw_calls[0].program, "A");
+        assert_eq!(draw_calls[0].bindings.len(), 2);
+        assert!(matches!(
+            draw_calls[0].bindings[1],
+            ResourceBindingRecord::Buffer {
+                binding: 1,
+                offset: 16,
+                size: 32,
+                ..
+            }
+        ));
+        assert_eq!(
+            draw_calls[1].framebuffer,
+            NullGraphicsServer::BACK_BUFFER_ID
+        );
+        assert_eq!(draw_calls[1].instance_count, 10);
+        assert!(draw_calls[1].params.blend.is_some());
+        assert!(server.draw_calls().is_empty());
+
+        let stats = server.pipeline_statistics();
+        assert_eq!(stats.framebuffer_binding_changes, 2);
+        assert_eq!(stats.program_binding_changes, 2);
+        assert_eq!(stats.blend_state_changes, 1);
+        assert_eq!(stats.texture_binding_changes, 1);
+
+        server.invalidate_resource_bindings_cache();
+        assert_eq!(server.pipeline_statistics().program_binding_changes, 0);
+    }
+
+    #[test]
+    fn test_read_back() {
+        let server = NullGraphicsServer::new((2, 1));
+        let pixels = [1, 2, 3, 4, 5, 6, 7, 8];
+        let source_texture = server
+            .create_texture(GpuTextureDescriptor {
+                kind: GpuTextureKind::Rectangle {
+                    width: 2,
+                    height: 1,
+                },
+                data: Some(&pixels),
+                ..Default::default()
+            })
+            .unwrap();
+        let source = server
+            .create_frame_buffer(None, vec![Attachment::color(source_texture)])
+            .unwrap();
+        assert_eq!(
+            source.read_pixels(ReadTarget::Color(0)).unwrap(),
+            pixels.to_vec()
+        );
+
+        let dest_texture = server
+            .create_2d_render_target(PixelKind::RGBA8, 2, 1)
+            .unwrap();
+        let dest = server
+            .create_frame_buffer(None, vec![Attachment::color(dest_texture)])
+            .unwrap();
+        source.blit_to(&dest, 0, 0, 2, 1, 0, 0, 2, 1, true, false, false);
+        assert_eq!(
+            dest.read_pixels(ReadTarget::Color(0)).unwrap(),
+            pixels.to_vec()
+        );
+
+        let read_buffer = server.create_async_read_buffer(4, 2).unwrap();
+        read_buffer
+            .schedule_pixels_transfer(&*dest.0, 0, None)
+            .unwrap();
+        assert!(read_buffer.is_request_running());
+        assert_eq!(read_buffer.try_read().unwrap(), pixels.to_vec());
+        assert!(!read_buffer.is_request_running());
+    }
+}
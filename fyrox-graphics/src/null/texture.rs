@@ -0,0 +1,138 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::{
+    error::FrameworkError,
+    gpu_texture::{
+        texture_data_size_bytes, GpuTextureDescriptor, GpuTextureKind, GpuTextureTrait, PixelKind,
+    },
+    null::server::NullGraphicsServer,
+};
+use std::cell::{BorrowError, BorrowMutError, Cell, Ref, RefCell, RefMut};
+
+pub struct NullTexture {
+    id: u64,
+    kind: Cell<GpuTextureKind>,
+    pixel_kind: Cell<PixelKind>,
+    mip_count: Cell<usize>,
+    data: RefCell<Vec<u8>>,
+}
+
+impl NullTexture {
+    /// Creates new texture of specified kind. The data layout is exactly the same as for the
+    /// OpenGL textures. If `data` is [`None`], the texture storage is filled with zeros.
+    pub fn new(
+        server: &NullGraphicsServer,
+        desc: GpuTextureDescriptor,
+    ) -> Result<Self, FrameworkError> {
+        let texture = Self {
+            id: server.next_id(),
+            kind: Cell::new(desc.kind),
+            pixel_kind: Cell::new(desc.pixel_kind),
+            mip_count: Cell::new(1),
+            data: Default::default(),
+        };
+
+        texture.set_data(desc.kind, desc.pixel_kind, desc.mip_count, desc.data)?;
+
+        Ok(texture)
+    }
+
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn mip_count(&self) -> usize {
+        self.mip_count.get()
+    }
+
+    /// Returns the content of the texture, which includes all its mip levels.
+    pub fn data(&self) -> Ref<'_, Vec<u8>> {
+        self.data.borrow()
+    }
+
+    /// Returns a mutable reference to the content of the texture.
+    pub fn data_mut(&self) -> RefMut<'_, Vec<u8>> {
+        self.data.borrow_mut()
+    }
+
+    /// Tries to borrow the content of the texture. Fails if the content is mutably borrowed at the
+    /// moment.
+    pub fn try_data(&self) -> Result<Ref<'_, Vec<u8>>, BorrowError> {
+        self.data.try_borrow()
+    }
+
+    /// Tries to mutably borrow the content of the texture. Fails if the content is borrowed at
+    /// the moment.
+    pub fn try_data_mut(&self) -> Result<RefMut<'_, Vec<u8>>, BorrowMutError> {
+        self.data.try_borrow_mut()
+    }
+
+    /// Returns the content of the first mip level of the texture.
+    pub fn first_mip_data(&self) -> Vec<u8> {
+        let size = texture_data_size_bytes(self.kind.get(), self.pixel_kind.get(), 1);
+        let data = self.data.borrow();
+        data[..size.min(data.len())].to_vec()
+    }
+}
+
+impl GpuTextureTrait for NullTexture {
+    fn set_data(
+        &self,
+        kind: GpuTextureKind,
+        pixel_kind: PixelKind,
+        mip_count: usize,
+        data: Option<&[u8]>,
+    ) -> Result<(), FrameworkError> {
+        let mip_count = mip_count.max(1);
+
+        let desired_byte_count = texture_data_size_bytes(kind, pixel_kind, mip_count);
+
+        let mut storage = self.data.borrow_mut();
+        if let Some(data) = data {
+            let actual_data_size = data.len();
+            if actual_data_size != desired_byte_count {
+                return Err(FrameworkError::InvalidTextureData {
+                    expected_data_size: desired_byte_count,
+                    actual_data_size,
+                });
+            }
+            storage.clear();
+            storage.extend_from_slice(data);
+        } else {
+            storage.clear();
+            storage.resize(desired_byte_count, 0);
+        }
+
+        self.kind.set(kind);
+        self.pixel_kind.set(pixel_kind);
+        self.mip_count.set(mip_count);
+
+        Ok(())
+    }
+
+    fn kind(&self) -> GpuTextureKind {
+        self.kind.get()
+    }
+
+    fn pixel_kind(&self) -> PixelKind {
+        self.pixel_kind.get()
+    }
+}
@@ -18,13 +18,14 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
-use crate::define_shared_wrapper;
+use crate::{core::visitor::prelude::*, define_shared_wrapper};
 use fyrox_core::define_as_any_trait;
 use std::fmt::Debug;
 
 define_as_any_trait!(GpuSamplerAsAny => GpuSamplerTrait);
 
 /// A set of potential options that can be used to configure a GPU sampler.
+#[derive(Copy, Clone, Debug, PartialEq, Visit)]
 pub struct GpuSamplerDescriptor {
     /// Minification filter of the texture. See [`MinificationFilter`] docs for more info.
     pub min_filter: MinificationFilter,
@@ -90,7 +91,7 @@ define_shared_wrapper!(GpuSampler<dyn GpuSamplerTrait>);
 
 /// The texture magnification function is used when the pixel being textured maps to an area
 /// less than or equal to one texture element.
-#[derive(Default, Copy, Clone, PartialOrd, PartialEq, Eq, Hash, Debug)]
+#[derive(Default, Copy, Clone, PartialOrd, PartialEq, Eq, Hash, Debug, Visit)]
 #[repr(u32)]
 pub enum MagnificationFilter {
     /// Returns the value of the texture element that is nearest to the center of the pixel
@@ -104,7 +105,7 @@ pub enum MagnificationFilter {
 
 /// The texture minifying function is used whenever the pixel being textured maps to an area
 /// greater than one texture element.
-#[derive(Default, Copy, Clone, PartialOrd, PartialEq, Eq, Hash, Debug)]
+#[derive(Default, Copy, Clone, PartialOrd, PartialEq, Eq, Hash, Debug, Visit)]
 pub enum MinificationFilter {
     /// Returns the value of the texture element that is nearest to the center of the pixel
     /// being textured.
@@ -134,7 +135,7 @@ pub enum MinificationFilter {
 }
 
 /// Defines a law of texture coordinate modification.
-#[derive(Default, Copy, Clone, Eq, PartialEq, Debug)]
+#[derive(Default, Copy, Clone, Eq, PartialEq, Debug, Visit)]
 pub enum WrapMode {
     /// Causes the integer part of a coordinate to be ignored; GPU uses only the fractional part,
     /// thereby creating a repeating pattern.
@@ -0,0 +1,323 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::{
+    core::{color::Color, math::Rect},
+    error::FrameworkError,
+    framebuffer::{
+        Attachment, BufferDataUsage, DrawCallStatistics, GpuFrameBuffer, GpuFrameBufferTrait,
+        ReadTarget, ResourceBindGroup, ResourceBinding,
+    },
+    geometry_buffer::GpuGeometryBuffer,
+    gpu_program::GpuProgram,
+    gpu_texture::{CubeMapFace, GpuTextureKind},
+    null::{
+        buffer::NullBuffer,
+        framebuffer::{null_texture, NullFrameBuffer},
+        geometry_buffer::NullGeometryBuffer,
+        program::NullProgram,
+        sampler::NullSampler,
+    },
+    software::{
+        raster::{self, Rasterizer, RenderTarget},
+        server::SoftwareGraphicsServer,
+        shader::{BoundTexture, ShaderContext, VertexInput},
+    },
+    DrawParameters, ElementKind, ElementRange,
+};
+use fxhash::FxHashMap;
+use std::rc::Weak;
+
+/// Frame buffer of the software graphics server. It renders only to the first color attachment
+/// and the depth attachment, and only if they are rectangle textures.
+pub struct SoftwareFrameBuffer {
+    inner: NullFrameBuffer,
+    server: Weak<SoftwareGraphicsServer>,
+}
+
+fn lock_attachment(attachment: &Attachment) -> Option<raster::Attachment<'_>> {
+    let GpuTextureKind::Rectangle { width, height } = attachment.texture.kind() else {
+        return None;
+    };
+    let texture = null_texture(&attachment.texture).ok()?;
+    Some(raster::Attachment {
+        pixel_kind: attachment.texture.pixel_kind(),
+        width,
+        height,
+        data: texture.try_data_mut().ok()?,
+    })
+}
+
+fn shader_context(resources: &[ResourceBindGroup]) -> ShaderContext {
+    let mut context = ShaderContext::default();
+    for resource in resources.iter().flat_map(|group| group.bindings) {
+        match resource {
+            ResourceBinding::Texture {
+                texture,
+                sampler,
+                binding,
+            } => {
+                if let Some(sampler) = sampler.as_any().downcast_ref::<NullSampler>() {
+                    context.textures.push(BoundTexture {
+                        binding: *binding,
+                        texture: texture.clone(),
+                        sampler: *sampler.descriptor(),
+                    })
+                }
+            }
+            ResourceBinding::Buffer {
+                buffer,
+                binding,
+                data_usage,
+            } => {
+                if let Some(null_buffer) = buffer.as_any().downcast_ref::<NullBuffer>() {
+                    let data = null_buffer.data();
+                    let bytes = match data_usage {
+                        BufferDataUsage::UseEverything => data.as_slice(),
+                        BufferDataUsage::UseSegment { offset, size } => {
+                            data.get(*offset..*offset + *size).unwrap_or_default()
+                        }
+                    };
+                    context.buffers.push((*binding, bytes.to_vec()));
+                }
+            }
+        }
+    }
+    context
+}
+
+impl SoftwareFrameBuffer {
+    pub(crate) fn new(inner: NullFrameBuffer, server: Weak<SoftwareGraphicsServer>) -> Self {
+        Self { inner, server }
+    }
+
+    /// Returns id of the frame buffer, that is used in the draw call records of the underlying
+    /// null graphics server.
+    pub fn id(&self) -> u64 {
+        self.inner.id()
+    }
+
+    fn render_target(&self) -> RenderTarget<'_> {
+        RenderTarget {
+            color: self
+                .inner
+                .color_attachments()
+                .first()
+                .and_then(lock_attachment),
+            depth: self.inner.depth_attachment().and_then(lock_attachment),
+        }
+    }
+
+    fn rasterize(
+        &self,
+        instance_count: usize,
+        geometry: &GpuGeometryBuffer,
+        viewport: Rect<i32>,
+        program: &GpuProgram,
+        params: &DrawParameters,
+        resources: &[ResourceBindGroup],
+        element_range: ElementRange,
+    ) {
+        let Some(server) = self.server.upgrade() else {
+            return;
+        };
+        let (Some(geometry), Some(program)) = (
+            geometry.as_any().downcast_ref::<NullGeometryBuffer>(),
+            program.as_any().downcast_ref::<NullProgram>(),
+        ) else {
+            return;
+        };
+        let Some(shader) = server.shader(program.name()) else {
+            return;
+        };
+
+        let context = shader_context(resources);
+        let buffers = geometry.buffers();
+        let indices = geometry.indices();
+        let element_kind = geometry.element_kind();
+        let index_per_element = element_kind.index_per_element();
+        let (first, count) = match element_range {
+            ElementRange::Full => (0, indices.len() / index_per_element),
+            ElementRange::Specific { offset, count } => (offset, count),
+        };
+        let Some(indices) =
+            indices.get(first * index_per_element..(first + count) * index_per_element)
+        else {
+            return;
+        };
+
+        let mut target = self.render_target();
+        let mut rasterizer = Rasterizer {
+            shader: &shader,
+            context: &context,
+            params,
+            viewport,
+            fill_mode: server.null_server().polygon_fill_mode().1,
+            target: &mut target,
+        };
+
+        let mut vertices = FxHashMap::default();
+        for instance_index in 0..instance_count {
+            vertices.clear();
+            for element in indices.chunks_exact(index_per_element) {
+                for &index in element {
+                    vertices.entry(index).or_insert_with(|| {
+                        let input = VertexInput {
+                            buffers: &buffers,
+                            vertex_index: index as usize,
+                            instance_index,
+                        };
+                        (shader.vertex)(&context, &input)
+                    });
+                }
+                match element_kind {
+                    ElementKind::Triangle => rasterizer.triangle([
+                        &vertices[&element[0]],
+                        &vertices[&element[1]],
+                        &vertices[&element[2]],
+                    ]),
+                    ElementKind::Line => {
+                        rasterizer.line([&vertices[&element[0]], &vertices[&element[1]]])
+                    }
+                    ElementKind::Point => rasterizer.point(&vertices[&element[0]]),
+                }
+            }
+        }
+    }
+}
+
+impl GpuFrameBufferTrait for SoftwareFrameBuffer {
+    fn color_attachments(&self) -> &[Attachment] {
+        self.inner.color_attachments()
+    }
+
+    fn depth_attachment(&self) -> Option<&Attachment> {
+        self.inner.depth_attachment()
+    }
+
+    fn set_cubemap_face(&self, attachment_index: usize, face: CubeMapFace, level: usize) {
+        self.inner.set_cubemap_face(attachment_index, face, level)
+    }
+
+    fn blit_to(
+        &self,
+        dest: &GpuFrameBuffer,
+        src_x0: i32,
+        src_y0: i32,
+        src_x1: i32,
+        src_y1: i32,
+        dst_x0: i32,
+        dst_y0: i32,
+        dst_x1: i32,
+        dst_y1: i32,
+        copy_color: bool,
+        copy_depth: bool,
+        copy_stencil: bool,
+    ) {
+        self.inner.blit_to(
+            dest,
+            src_x0,
+            src_y0,
+            src_x1,
+            src_y1,
+            dst_x0,
+            dst_y0,
+            dst_x1,
+            dst_y1,
+            copy_color,
+            copy_depth,
+            copy_stencil,
+        )
+    }
+
+    fn clear(
+        &self,
+        _viewport: Rect<i32>,
+        color: Option<Color>,
+        depth: Option<f32>,
+        stencil: Option<i32>,
+    ) {
+        self.render_target().clear(color, depth, stencil);
+    }
+
+    fn read_pixels(&self, read_target: ReadTarget) -> Option<Vec<u8>> {
+        self.inner.read_pixels(read_target)
+    }
+
+    fn draw(
+        &self,
+        geometry: &GpuGeometryBuffer,
+        viewport: Rect<i32>,
+        program: &GpuProgram,
+        params: &DrawParameters,
+        resources: &[ResourceBindGroup],
+        element_range: ElementRange,
+    ) -> Result<DrawCallStatistics, FrameworkError> {
+        let statistics = self.inner.draw(
+            geometry,
+            viewport,
+            program,
+            params,
+            resources,
+            element_range,
+        )?;
+        self.rasterize(
+            1,
+            geometry,
+            viewport,
+            program,
+            params,
+            resources,
+            element_range,
+        );
+        Ok(statistics)
+    }
+
+    fn draw_instances(
+        &self,
+        instance_count: usize,
+        geometry: &GpuGeometryBuffer,
+        viewport: Rect<i32>,
+        program: &GpuProgram,
+        params: &DrawParameters,
+        resources: &[ResourceBindGroup],
+        element_range: ElementRange,
+    ) -> Result<DrawCallStatistics, FrameworkError> {
+        let statistics = self.inner.draw_instances(
+            instance_count,
+            geometry,
+            viewport,
+            program,
+            params,
+            resources,
+            element_range,
+        )?;
+        self.rasterize(
+            instance_count,
+            geometry,
+            viewport,
+            program,
+            params,
+            resources,
+            element_range,
+        );
+        Ok(statistics)
+    }
+}
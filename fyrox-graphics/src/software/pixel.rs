@@ -0,0 +1,303 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Conversion of pixels of various formats to normalized floating-point colors and back.
+
+use crate::{
+    core::algebra::Vector4,
+    gpu_texture::{image_2d_size_bytes, PixelKind},
+};
+
+pub fn pixel_size(pixel_kind: PixelKind) -> usize {
+    image_2d_size_bytes(pixel_kind, 1, 1)
+}
+
+fn f16_to_f32(half: u16) -> f32 {
+    let sign = if half & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = ((half >> 10) & 0x1F) as i32;
+    let mantissa = (half & 0x3FF) as f32;
+    match exponent {
+        0 => sign * mantissa * 2.0f32.powi(-24),
+        31 => {
+            if mantissa == 0.0 {
+                sign * f32::INFINITY
+            } else {
+                f32::NAN
+            }
+        }
+        _ => sign * (1.0 + mantissa / 1024.0) * 2.0f32.powi(exponent - 15),
+    }
+}
+
+fn f32_to_f16(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    if value.is_nan() {
+        return sign | 0x7E00;
+    }
+    let abs = value.abs();
+    if abs >= 65520.0 {
+        sign | 0x7C00
+    } else if abs < 2.0f32.powi(-14) {
+        sign | (abs / 2.0f32.powi(-24)).round() as u16
+    } else {
+        let exponent = abs.log2().floor() as i32;
+        let mantissa = ((abs / 2.0f32.powi(exponent) - 1.0) * 1024.0).round() as u32;
+        // Rounding of the mantissa could overflow into the exponent, which is fine.
+        sign | ((((exponent + 15) as u32) << 10) + mantissa) as u16
+    }
+}
+
+fn unorm8(value: u8) -> f32 {
+    value as f32 / u8::MAX as f32
+}
+
+fn unorm16(bytes: &[u8]) -> f32 {
+    u16::from_ne_bytes([bytes[0], bytes[1]]) as f32 / u16::MAX as f32
+}
+
+fn half(bytes: &[u8]) -> f32 {
+    f16_to_f32(u16::from_ne_bytes([bytes[0], bytes[1]]))
+}
+
+fn float(bytes: &[u8]) -> f32 {
+    f32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+/// Converts a pixel to a color. Returns [`None`] for compressed, integer and packed formats.
+pub fn decode(pixel_kind: PixelKind, bytes: &[u8]) -> Option<Vector4<f32>> {
+    let b = bytes;
+    Some(match pixel_kind {
+        PixelKind::RGBA8 | PixelKind::SRGBA8 => {
+            Vector4::new(unorm8(b[0]), unorm8(b[1]), unorm8(b[2]), unorm8(b[3]))
+        }
+        PixelKind::BGRA8 => Vector4::new(unorm8(b[2]), unorm8(b[1]), unorm8(b[0]), unorm8(b[3])),
+        PixelKind::RGB8 | PixelKind::SRGB8 => {
+            Vector4::new(unorm8(b[0]), unorm8(b[1]), unorm8(b[2]), 1.0)
+        }
+        PixelKind::BGR8 => Vector4::new(unorm8(b[2]), unorm8(b[1]), unorm8(b[0]), 1.0),
+        PixelKind::RG8 => Vector4::new(unorm8(b[0]), unorm8(b[1]), 0.0, 1.0),
+        PixelKind::R8 => Vector4::new(unorm8(b[0]), 0.0, 0.0, 1.0),
+        PixelKind::L8 => {
+            let l = unorm8(b[0]);
+            Vector4::new(l, l, l, 1.0)
+        }
+        PixelKind::LA8 => {
+            let l = unorm8(b[0]);
+            Vector4::new(l, l, l, unorm8(b[1]))
+        }
+        PixelKind::R16 => Vector4::new(unorm16(b), 0.0, 0.0, 1.0),
+        PixelKind::L16 => {
+            let l = unorm16(b);
+            Vector4::new(l, l, l, 1.0)
+        }
+        PixelKind::LA16 => {
+            let l = unorm16(b);
+            Vector4::new(l, l, l, unorm16(&b[2..]))
+        }
+        PixelKind::RG16 => Vector4::new(unorm16(b), unorm16(&b[2..]), 0.0, 1.0),
+        PixelKind::RGB16 => Vector4::new(unorm16(b), unorm16(&b[2..]), unorm16(&b[4..]), 1.0),
+        PixelKind::RGBA16 => Vector4::new(
+            unorm16(b),
+            unorm16(&b[2..]),
+            unorm16(&b[4..]),
+            unorm16(&b[6..]),
+        ),
+        PixelKind::R16F => Vector4::new(half(b), 0.0, 0.0, 1.0),
+        PixelKind::RGB16F => Vector4::new(half(b), half(&b[2..]), half(&b[4..]), 1.0),
+        PixelKind::RGBA16F => Vector4::new(half(b), half(&b[2..]), half(&b[4..]), half(&b[6..])),
+        PixelKind::R32F | PixelKind::D32F => Vector4::new(float(b), 0.0, 0.0, 1.0),
+        PixelKind::RGB32F => Vector4::new(float(b), float(&b[4..]), float(&b[8..]), 1.0),
+        PixelKind::RGBA32F => {
+            Vector4::new(float(b), float(&b[4..]), float(&b[8..]), float(&b[12..]))
+        }
+        PixelKind::D16 => Vector4::new(unorm16(b), 0.0, 0.0, 1.0),
+        PixelKind::D24S8 => {
+            let packed = u32::from_ne_bytes([b[0], b[1], b[2], b[3]]);
+            Vector4::new((packed >> 8) as f32 / 0xFFFFFF as f32, 0.0, 0.0, 1.0)
+        }
+        PixelKind::R32UI
+        | PixelKind::R8UI
+        | PixelKind::R11G11B10F
+        | PixelKind::RGB10A2
+        | PixelKind::DXT1RGB
+        | PixelKind::DXT1RGBA
+        | PixelKind::DXT3RGBA
+        | PixelKind::DXT5RGBA
+        | PixelKind::R8RGTC
+        | PixelKind::RG8RGTC => return None,
+    })
+}
+
+fn to_unorm8(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0) * u8::MAX as f32).round() as u8
+}
+
+fn to_unorm16(value: f32) -> [u8; 2] {
+    ((value.clamp(0.0, 1.0) * u16::MAX as f32).round() as u16).to_ne_bytes()
+}
+
+/// Converts a color to a pixel. Returns `false` if the pixel kind is not supported, the pixel
+/// is left untouched in this case.
+pub fn encode(pixel_kind: PixelKind, color: Vector4<f32>, bytes: &mut [u8]) -> bool {
+    let c = color;
+    let mut write = |components: &[[u8; 2]]| {
+        for (i, component) in components.iter().enumerate() {
+            bytes[i * 2..i * 2 + 2].copy_from_slice(component);
+        }
+    };
+    match pixel_kind {
+        PixelKind::RGBA8 | PixelKind::SRGBA8 => {
+            bytes[..4].copy_from_slice(&[
+                to_unorm8(c.x),
+                to_unorm8(c.y),
+                to_unorm8(c.z),
+                to_unorm8(c.w),
+            ]);
+        }
+        PixelKind::BGRA8 => {
+            bytes[..4].copy_from_slice(&[
+                to_unorm8(c.z),
+                to_unorm8(c.y),
+                to_unorm8(c.x),
+                to_unorm8(c.w),
+            ]);
+        }
+        PixelKind::RGB8 | PixelKind::SRGB8 => {
+            bytes[..3].copy_from_slice(&[to_unorm8(c.x), to_unorm8(c.y), to_unorm8(c.z)]);
+        }
+        PixelKind::BGR8 => {
+            bytes[..3].copy_from_slice(&[to_unorm8(c.z), to_unorm8(c.y), to_unorm8(c.x)]);
+        }
+        PixelKind::RG8 => bytes[..2].copy_from_slice(&[to_unorm8(c.x), to_unorm8(c.y)]),
+        PixelKind::R8 | PixelKind::L8 => bytes[0] = to_unorm8(c.x),
+        PixelKind::LA8 => bytes[..2].copy_from_slice(&[to_unorm8(c.x), to_unorm8(c.w)]),
+        PixelKind::R16 | PixelKind::L16 => write(&[to_unorm16(c.x)]),
+        PixelKind::LA16 => write(&[to_unorm16(c.x), to_unorm16(c.w)]),
+        PixelKind::RG16 => write(&[to_unorm16(c.x), to_unorm16(c.y)]),
+        PixelKind::RGB16 => write(&[to_unorm16(c.x), to_unorm16(c.y), to_unorm16(c.z)]),
+        PixelKind::RGBA16 => write(&[
+            to_unorm16(c.x),
+            to_unorm16(c.y),
+            to_unorm16(c.z),
+            to_unorm16(c.w),
+        ]),
+        PixelKind::R16F => write(&[f32_to_f16(c.x).to_ne_bytes()]),
+        PixelKind::RGB16F => write(&[
+            f32_to_f16(c.x).to_ne_bytes(),
+            f32_to_f16(c.y).to_ne_bytes(),
+            f32_to_f16(c.z).to_ne_bytes(),
+        ]),
+        PixelKind::RGBA16F => write(&[
+            f32_to_f16(c.x).to_ne_bytes(),
+            f32_to_f16(c.y).to_ne_bytes(),
+            f32_to_f16(c.z).to_ne_bytes(),
+            f32_to_f16(c.w).to_ne_bytes(),
+        ]),
+        PixelKind::R32F => bytes[..4].copy_from_slice(&c.x.to_ne_bytes()),
+        PixelKind::RGB32F => {
+            for i in 0..3 {
+                bytes[i * 4..i * 4 + 4].copy_from_slice(&c[i].to_ne_bytes());
+            }
+        }
+        PixelKind::RGBA32F => {
+            for i in 0..4 {
+                bytes[i * 4..i * 4 + 4].copy_from_slice(&c[i].to_ne_bytes());
+            }
+        }
+        PixelKind::D32F
+        | PixelKind::D16
+        | PixelKind::D24S8
+        | PixelKind::R32UI
+        | PixelKind::R8UI
+        | PixelKind::R11G11B10F
+        | PixelKind::RGB10A2
+        | PixelKind::DXT1RGB
+        | PixelKind::DXT1RGBA
+        | PixelKind::DXT3RGBA
+        | PixelKind::DXT5RGBA
+        | PixelKind::R8RGTC
+        | PixelKind::RG8RGTC => return false,
+    }
+    true
+}
+
+/// Depth and stencil values of a pixel of a depth attachment.
+#[derive(Copy, Clone)]
+pub struct DepthStencil {
+    pub depth: f32,
+    pub stencil: u8,
+}
+
+pub fn decode_depth_stencil(pixel_kind: PixelKind, bytes: &[u8]) -> DepthStencil {
+    match pixel_kind {
+        PixelKind::D32F => DepthStencil {
+            depth: float(bytes),
+            stencil: 0,
+        },
+        PixelKind::D16 => DepthStencil {
+            depth: unorm16(bytes),
+            stencil: 0,
+        },
+        _ => {
+            let packed = u32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+            DepthStencil {
+                depth: (packed >> 8) as f32 / 0xFFFFFF as f32,
+                stencil: (packed & 0xFF) as u8,
+            }
+        }
+    }
+}
+
+pub fn encode_depth_stencil(pixel_kind: PixelKind, value: DepthStencil, bytes: &mut [u8]) {
+    match pixel_kind {
+        PixelKind::D32F => bytes[..4].copy_from_slice(&value.depth.to_ne_bytes()),
+        PixelKind::D16 => bytes[..2].copy_from_slice(&to_unorm16(value.depth)),
+        _ => {
+            let depth = (value.depth.clamp(0.0, 1.0) * 0xFFFFFF as f32).round() as u32;
+            let packed = (depth << 8) | value.stencil as u32;
+            bytes[..4].copy_from_slice(&packed.to_ne_bytes());
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_half_conversion() {
+        for value in [0.0, 1.0, -2.5, 0.333, 1024.0, 0.00001] {
+            let converted = f16_to_f32(f32_to_f16(value));
+            assert!((converted - value).abs() <= value.abs() * 0.001 + 0.0000001);
+        }
+    }
+
+    #[test]
+    fn test_pixel_round_trip() {
+        let color = Vector4::new(1.0, 0.5, 0.25, 0.0);
+        for pixel_kind in [PixelKind::RGBA8, PixelKind::RGBA16, PixelKind::RGBA16F] {
+            let mut bytes = vec![0; pixel_size(pixel_kind)];
+            assert!(encode(pixel_kind, color, &mut bytes));
+            let decoded = decode(pixel_kind, &bytes).unwrap();
+            assert!((decoded - color).norm() < 0.01);
+        }
+    }
+}
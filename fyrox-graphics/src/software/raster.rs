@@ -0,0 +1,523 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Rasterization of primitives and per-fragment operations.
+
+use crate::{
+    core::{
+        algebra::{Vector3, Vector4},
+        color::Color,
+        math::Rect,
+    },
+    gpu_texture::PixelKind,
+    software::{
+        pixel::{self, DepthStencil},
+        shader::{FragmentInput, ShaderContext, SoftwareShader, Varyings, VertexOutput},
+    },
+    BlendFactor, BlendMode, CompareFunc, CullFace, DrawParameters, PolygonFillMode, StencilAction,
+};
+use std::cell::RefMut;
+
+pub struct Attachment<'a> {
+    pub pixel_kind: PixelKind,
+    pub width: usize,
+    pub height: usize,
+    pub data: RefMut<'a, Vec<u8>>,
+}
+
+impl Attachment<'_> {
+    fn pixel(&mut self, x: usize, y: usize) -> Option<&mut [u8]> {
+        let size = pixel::pixel_size(self.pixel_kind);
+        let offset = (y * self.width + x) * size;
+        self.data.get_mut(offset..offset + size)
+    }
+
+    fn pixels(&mut self) -> impl Iterator<Item = &mut [u8]> {
+        let size = pixel::pixel_size(self.pixel_kind);
+        let count = self.width * self.height;
+        self.data.chunks_exact_mut(size).take(count)
+    }
+}
+
+/// A set of attachments of a frame buffer that are locked for rendering.
+pub struct RenderTarget<'a> {
+    pub color: Option<Attachment<'a>>,
+    pub depth: Option<Attachment<'a>>,
+}
+
+impl RenderTarget<'_> {
+    fn size(&self) -> (usize, usize) {
+        self.color
+            .iter()
+            .chain(self.depth.iter())
+            .map(|a| (a.width, a.height))
+            .reduce(|a, b| (a.0.min(b.0), a.1.min(b.1)))
+            .unwrap_or_default()
+    }
+
+    pub fn clear(&mut self, color: Option<Color>, depth: Option<f32>, stencil: Option<i32>) {
+        if let (Some(attachment), Some(color)) = (self.color.as_mut(), color) {
+            let pixel_kind = attachment.pixel_kind;
+            let color = Vector4::from(color.as_frgba());
+            for pixel in attachment.pixels() {
+                pixel::encode(pixel_kind, color, pixel);
+            }
+        }
+        if let Some(attachment) = self.depth.as_mut() {
+            if depth.is_none() && stencil.is_none() {
+                return;
+            }
+            let pixel_kind = attachment.pixel_kind;
+            for pixel in attachment.pixels() {
+                let mut value = pixel::decode_depth_stencil(pixel_kind, pixel);
+                if let Some(depth) = depth {
+                    value.depth = depth;
+                }
+                if let Some(stencil) = stencil {
+                    value.stencil = stencil as u8;
+                }
+                pixel::encode_depth_stencil(pixel_kind, value, pixel);
+            }
+        }
+    }
+}
+
+/// A vertex after perspective division and viewport transform.
+struct WindowVertex {
+    position: Vector3<f32>,
+    inv_w: f32,
+    // Varyings are pre-multiplied by `inv_w` for perspective-correct interpolation.
+    varyings: Varyings,
+}
+
+fn compare(func: CompareFunc, a: f32, b: f32) -> bool {
+    match func {
+        CompareFunc::Never => false,
+        CompareFunc::Less => a < b,
+        CompareFunc::Equal => a == b,
+        CompareFunc::LessOrEqual => a <= b,
+        CompareFunc::Greater => a > b,
+        CompareFunc::NotEqual => a != b,
+        CompareFunc::GreaterOrEqual => a >= b,
+        CompareFunc::Always => true,
+    }
+}
+
+fn apply_stencil_action(action: StencilAction, value: u8, ref_value: u8) -> u8 {
+    match action {
+        StencilAction::Keep => value,
+        StencilAction::Zero => 0,
+        StencilAction::Replace => ref_value,
+        StencilAction::Incr => value.saturating_add(1),
+        StencilAction::IncrWrap => value.wrapping_add(1),
+        StencilAction::Decr => value.saturating_sub(1),
+        StencilAction::DecrWrap => value.wrapping_sub(1),
+        StencilAction::Invert => !value,
+    }
+}
+
+fn blend_factor(factor: BlendFactor, src: Vector4<f32>, dst: Vector4<f32>) -> Vector4<f32> {
+    // Blend color is always zero, since there's no way to change it. The same is applied to the
+    // second output of a fragment shader, since dual-source blending is not supported.
+    let constant = Vector4::<f32>::zeros();
+    let src1 = Vector4::<f32>::zeros();
+    let one = Vector4::repeat(1.0);
+    match factor {
+        BlendFactor::Zero => Vector4::zeros(),
+        BlendFactor::One => one,
+        BlendFactor::SrcColor => src,
+        BlendFactor::OneMinusSrcColor => one - src,
+        BlendFactor::DstColor => dst,
+        BlendFactor::OneMinusDstColor => one - dst,
+        BlendFactor::SrcAlpha => Vector4::repeat(src.w),
+        BlendFactor::OneMinusSrcAlpha => Vector4::repeat(1.0 - src.w),
+        BlendFactor::DstAlpha => Vector4::repeat(dst.w),
+        BlendFactor::OneMinusDstAlpha => Vector4::repeat(1.0 - dst.w),
+        BlendFactor::ConstantColor => constant,
+        BlendFactor::OneMinusConstantColor => one - constant,
+        BlendFactor::ConstantAlpha => Vector4::repeat(constant.w),
+        BlendFactor::OneMinusConstantAlpha => Vector4::repeat(1.0 - constant.w),
+        BlendFactor::SrcAlphaSaturate => {
+            let f = src.w.min(1.0 - dst.w);
+            Vector4::new(f, f, f, 1.0)
+        }
+        BlendFactor::Src1Color => src1,
+        BlendFactor::OneMinusSrc1Color => one - src1,
+        BlendFactor::Src1Alpha => Vector4::repeat(src1.w),
+        BlendFactor::OneMinusSrc1Alpha => Vector4::repeat(1.0 - src1.w),
+    }
+}
+
+fn blend_equation(mode: BlendMode, src: f32, dst: f32, src_factor: f32, dst_factor: f32) -> f32 {
+    match mode {
+        BlendMode::Add => src * src_factor + dst * dst_factor,
+        BlendMode::Subtract => src * src_factor - dst * dst_factor,
+        BlendMode::ReverseSubtract => dst * dst_factor - src * src_factor,
+        BlendMode::Min => src.min(dst),
+        BlendMode::Max => src.max(dst),
+    }
+}
+
+fn edge(a: &Vector3<f32>, b: &Vector3<f32>, x: f32, y: f32) -> f32 {
+    (b.x - a.x) * (y - a.y) - (b.y - a.y) * (x - a.x)
+}
+
+// Implements the top-left fill rule for counter-clockwise triangles in a coordinate system with
+// Y axis pointing up, so the pixels on the edges shared by adjacent triangles are drawn only once.
+fn is_top_left(a: &Vector3<f32>, b: &Vector3<f32>) -> bool {
+    let dx = b.x - a.x;
+    let dy = b.y - a.y;
+    dy < 0.0 || (dy == 0.0 && dx < 0.0)
+}
+
+fn lerp_vertex(a: &VertexOutput, b: &VertexOutput, t: f32) -> VertexOutput {
+    VertexOutput {
+        position: a.position.lerp(&b.position, t),
+        varyings: a
+            .varyings
+            .iter()
+            .zip(b.varyings.iter())
+            .map(|(a, b)| a + (b - a) * t)
+            .collect(),
+    }
+}
+
+/// Clips a polygon against the near plane (`z >= -w`).
+fn clip_near(vertices: &[VertexOutput]) -> Vec<VertexOutput> {
+    let distance = |v: &VertexOutput| v.position.z + v.position.w;
+    let mut result = Vec::with_capacity(vertices.len() + 1);
+    for (i, current) in vertices.iter().enumerate() {
+        let next = &vertices[(i + 1) % vertices.len()];
+        let (d_current, d_next) = (distance(current), distance(next));
+        if d_current >= 0.0 {
+            result.push(current.clone());
+        }
+        if (d_current >= 0.0) != (d_next >= 0.0) {
+            result.push(lerp_vertex(current, next, d_current / (d_current - d_next)));
+        }
+    }
+    result
+}
+
+pub struct Rasterizer<'a, 'b> {
+    pub shader: &'a SoftwareShader,
+    pub context: &'a ShaderContext,
+    pub params: &'a DrawParameters,
+    pub viewport: Rect<i32>,
+    pub fill_mode: PolygonFillMode,
+    pub target: &'a mut RenderTarget<'b>,
+}
+
+impl Rasterizer<'_, '_> {
+    fn bounds(&self) -> (i32, i32, i32, i32) {
+        let (width, height) = self.target.size();
+        let mut bounds = (
+            self.viewport.x().max(0),
+            self.viewport.y().max(0),
+            (self.viewport.x() + self.viewport.w()).min(width as i32),
+            (self.viewport.y() + self.viewport.h()).min(height as i32),
+        );
+        if let Some(scissor) = self.params.scissor_box {
+            bounds.0 = bounds.0.max(scissor.x);
+            bounds.1 = bounds.1.max(scissor.y);
+            bounds.2 = bounds.2.min(scissor.x + scissor.width);
+            bounds.3 = bounds.3.min(scissor.y + scissor.height);
+        }
+        bounds
+    }
+
+    fn to_window(&self, vertex: &VertexOutput) -> WindowVertex {
+        let inv_w = 1.0 / vertex.position.w;
+        let ndc = vertex.position.xyz() * inv_w;
+        let viewport = &self.viewport;
+        WindowVertex {
+            position: Vector3::new(
+                viewport.x() as f32 + (ndc.x + 1.0) * 0.5 * viewport.w() as f32,
+                viewport.y() as f32 + (ndc.y + 1.0) * 0.5 * viewport.h() as f32,
+                (ndc.z + 1.0) * 0.5,
+            ),
+            inv_w,
+            varyings: vertex.varyings.iter().map(|v| v * inv_w).collect(),
+        }
+    }
+
+    pub fn triangle(&mut self, vertices: [&VertexOutput; 3]) {
+        let clipped = clip_near(&vertices.map(|v| v.clone()));
+        if clipped.len() < 3 {
+            return;
+        }
+        let window = clipped
+            .iter()
+            .map(|v| self.to_window(v))
+            .collect::<Vec<_>>();
+        for i in 1..window.len() - 1 {
+            let (a, b, c) = (&window[0], &window[i], &window[i + 1]);
+            match self.fill_mode {
+                PolygonFillMode::Fill => self.fill_triangle([a, b, c]),
+                PolygonFillMode::Line => {
+                    self.draw_line(a, b);
+                    self.draw_line(b, c);
+                    self.draw_line(c, a);
+                }
+                PolygonFillMode::Point => {
+                    for vertex in [a, b, c] {
+                        self.draw_point(vertex);
+                    }
+                }
+            }
+        }
+    }
+
+    fn fill_triangle(&mut self, vertices: [&WindowVertex; 3]) {
+        let [mut v0, mut v1, v2] = vertices;
+        let area = edge(&v0.position, &v1.position, v2.position.x, v2.position.y);
+        if area == 0.0 || !area.is_finite() {
+            return;
+        }
+        let front_facing = area > 0.0;
+        match self.params.cull_face {
+            Some(CullFace::Back) if !front_facing => return,
+            Some(CullFace::Front) if front_facing => return,
+            _ => (),
+        }
+        if !front_facing {
+            std::mem::swap(&mut v0, &mut v1);
+        }
+        let area = area.abs();
+        let (p0, p1, p2) = (&v0.position, &v1.position, &v2.position);
+
+        let bounds = self.bounds();
+        let min_x = (p0.x.min(p1.x).min(p2.x).floor() as i32).max(bounds.0);
+        let min_y = (p0.y.min(p1.y).min(p2.y).floor() as i32).max(bounds.1);
+        let max_x = (p0.x.max(p1.x).max(p2.x).ceil() as i32).min(bounds.2);
+        let max_y = (p0.y.max(p1.y).max(p2.y).ceil() as i32).min(bounds.3);
+
+        let inside =
+            |w: f32, a: &Vector3<f32>, b: &Vector3<f32>| w > 0.0 || (w == 0.0 && is_top_left(a, b));
+
+        let mut varyings = Varyings::new();
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+                let w0 = edge(p1, p2, px, py);
+                let w1 = edge(p2, p0, px, py);
+                let w2 = edge(p0, p1, px, py);
+                if !inside(w0, p1, p2) || !inside(w1, p2, p0) || !inside(w2, p0, p1) {
+                    continue;
+                }
+                let (l0, l1, l2) = (w0 / area, w1 / area, w2 / area);
+                let z = l0 * p0.z + l1 * p1.z + l2 * p2.z;
+                let inv_w = l0 * v0.inv_w + l1 * v1.inv_w + l2 * v2.inv_w;
+                varyings.clear();
+                for ((a, b), c) in v0
+                    .varyings
+                    .iter()
+                    .zip(v1.varyings.iter())
+                    .zip(v2.varyings.iter())
+                {
+                    varyings.push((l0 * a + l1 * b + l2 * c) / inv_w);
+                }
+                self.fragment(
+                    x,
+                    y,
+                    Vector4::new(px, py, z, inv_w),
+                    &varyings,
+                    front_facing,
+                );
+            }
+        }
+    }
+
+    pub fn line(&mut self, vertices: [&VertexOutput; 2]) {
+        let clipped = clip_near(&vertices.map(|v| v.clone()));
+        if clipped.len() < 2 {
+            return;
+        }
+        let a = self.to_window(&clipped[0]);
+        let b = self.to_window(&clipped[1]);
+        self.draw_line(&a, &b);
+    }
+
+    fn draw_line(&mut self, a: &WindowVertex, b: &WindowVertex) {
+        let delta = b.position - a.position;
+        let steps = delta.x.abs().max(delta.y.abs()).ceil().max(1.0) as usize;
+        let bounds = self.bounds();
+        let mut varyings = Varyings::new();
+        for step in 0..=steps {
+            let t = step as f32 / steps as f32;
+            let position = a.position + delta * t;
+            let (x, y) = (position.x.floor() as i32, position.y.floor() as i32);
+            if x < bounds.0 || y < bounds.1 || x >= bounds.2 || y >= bounds.3 {
+                continue;
+            }
+            let inv_w = a.inv_w + (b.inv_w - a.inv_w) * t;
+            varyings.clear();
+            for (va, vb) in a.varyings.iter().zip(b.varyings.iter()) {
+                varyings.push((va + (vb - va) * t) / inv_w);
+            }
+            let frag_coord = Vector4::new(x as f32 + 0.5, y as f32 + 0.5, position.z, inv_w);
+            self.fragment(x, y, frag_coord, &varyings, true);
+        }
+    }
+
+    pub fn point(&mut self, vertex: &VertexOutput) {
+        if vertex.position.z + vertex.position.w < 0.0 {
+            return;
+        }
+        let vertex = self.to_window(vertex);
+        self.draw_point(&vertex);
+    }
+
+    fn draw_point(&mut self, vertex: &WindowVertex) {
+        let bounds = self.bounds();
+        let (x, y) = (
+            vertex.position.x.floor() as i32,
+            vertex.position.y.floor() as i32,
+        );
+        if x < bounds.0 || y < bounds.1 || x >= bounds.2 || y >= bounds.3 {
+            return;
+        }
+        let varyings = vertex
+            .varyings
+            .iter()
+            .map(|v| v / vertex.inv_w)
+            .collect::<Varyings>();
+        let frag_coord = Vector4::new(
+            x as f32 + 0.5,
+            y as f32 + 0.5,
+            vertex.position.z,
+            vertex.inv_w,
+        );
+        self.fragment(x, y, frag_coord, &varyings, true);
+    }
+
+    fn fragment(
+        &mut self,
+        x: i32,
+        y: i32,
+        position: Vector4<f32>,
+        varyings: &[f32],
+        front_facing: bool,
+    ) {
+        // Far plane clipping.
+        if position.z > 1.0 {
+            return;
+        }
+        let input = FragmentInput {
+            position,
+            varyings,
+            front_facing,
+        };
+        let Some(color) = (self.shader.fragment)(self.context, &input) else {
+            return;
+        };
+        let (x, y) = (x as usize, y as usize);
+        let params = self.params;
+        let depth = position.z.clamp(0.0, 1.0);
+
+        if let Some(attachment) = self.target.depth.as_mut() {
+            let pixel_kind = attachment.pixel_kind;
+            let has_stencil = pixel_kind == PixelKind::D24S8;
+            if let Some(bytes) = attachment.pixel(x, y) {
+                let stored = pixel::decode_depth_stencil(pixel_kind, bytes);
+                let stencil = params.stencil_test.filter(|_| has_stencil);
+                let ref_value = stencil.map(|s| s.ref_value as u8).unwrap_or_default();
+                let mut new = stored;
+                let update_stencil = |action: StencilAction, stored: &mut DepthStencil| {
+                    let mask = params.stencil_op.write_mask as u8;
+                    let value = apply_stencil_action(action, stored.stencil, ref_value);
+                    stored.stencil = (stored.stencil & !mask) | (value & mask);
+                };
+
+                if let Some(stencil) = stencil {
+                    let mask = stencil.mask as u8;
+                    if !compare(
+                        stencil.func,
+                        (ref_value & mask) as f32,
+                        (stored.stencil & mask) as f32,
+                    ) {
+                        update_stencil(params.stencil_op.fail, &mut new);
+                        pixel::encode_depth_stencil(pixel_kind, new, bytes);
+                        return;
+                    }
+                }
+
+                if let Some(depth_func) = params.depth_test {
+                    if !compare(depth_func, depth, stored.depth) {
+                        if stencil.is_some() {
+                            update_stencil(params.stencil_op.zfail, &mut new);
+                            pixel::encode_depth_stencil(pixel_kind, new, bytes);
+                        }
+                        return;
+                    }
+                    if params.depth_write {
+                        new.depth = depth;
+                    }
+                }
+
+                if stencil.is_some() {
+                    update_stencil(params.stencil_op.zpass, &mut new);
+                }
+
+                pixel::encode_depth_stencil(pixel_kind, new, bytes);
+            }
+        }
+
+        let Some(attachment) = self.target.color.as_mut() else {
+            return;
+        };
+        let pixel_kind = attachment.pixel_kind;
+        let Some(bytes) = attachment.pixel(x, y) else {
+            return;
+        };
+        let Some(dst) = pixel::decode(pixel_kind, bytes) else {
+            return;
+        };
+        let mut result = color;
+        if let Some(blend) = params.blend.as_ref() {
+            let func = &blend.func;
+            let src_rgb = blend_factor(func.sfactor, color, dst);
+            let dst_rgb = blend_factor(func.dfactor, color, dst);
+            let src_alpha = blend_factor(func.alpha_sfactor, color, dst);
+            let dst_alpha = blend_factor(func.alpha_dfactor, color, dst);
+            for i in 0..3 {
+                result[i] =
+                    blend_equation(blend.equation.rgb, color[i], dst[i], src_rgb[i], dst_rgb[i]);
+            }
+            result.w = blend_equation(
+                blend.equation.alpha,
+                color.w,
+                dst.w,
+                src_alpha.w,
+                dst_alpha.w,
+            );
+        }
+        let mask = params.color_write;
+        for (i, write) in [mask.red, mask.green, mask.blue, mask.alpha]
+            .into_iter()
+            .enumerate()
+        {
+            if !write {
+                result[i] = dst[i];
+            }
+        }
+        pixel::encode(pixel_kind, result, bytes);
+    }
+}
@@ -0,0 +1,556 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::{
+    buffer::{BufferKind, BufferUsage, GpuBuffer},
+    error::FrameworkError,
+    framebuffer::{Attachment, GpuFrameBuffer},
+    geometry_buffer::{GeometryBufferDescriptor, GpuGeometryBuffer},
+    gpu_program::{GpuProgram, GpuShader, ShaderKind, ShaderResourceDefinition},
+    gpu_texture::{GpuTexture, GpuTextureDescriptor, GpuTextureKind, PixelKind},
+    null::{framebuffer::NullFrameBuffer, server::NullGraphicsServer},
+    query::GpuQuery,
+    read_buffer::GpuAsyncReadBuffer,
+    sampler::{GpuSampler, GpuSamplerDescriptor},
+    server::{GraphicsServer, ServerCapabilities},
+    software::{framebuffer::SoftwareFrameBuffer, shader::SoftwareShader},
+    stats::PipelineStatistics,
+    PolygonFace, PolygonFillMode,
+};
+use fxhash::FxHashMap;
+use std::{
+    cell::RefCell,
+    rc::{Rc, Weak},
+};
+
+/// Graphics server that renders on CPU. It is built on top of [`NullGraphicsServer`], which means
+/// that all the objects are stored in CPU memory, every descriptor is validated and every draw call
+/// is recorded. In addition to that, draw calls are rasterized to the textures of the target frame
+/// buffer using the shaders registered with [`Self::register_shader`]. Draw calls with programs,
+/// that have no registered shader are skipped. The back buffer has an `RGBA8` color attachment and
+/// `D24S8` depth-stencil attachment.
+///
+/// The server implements a subset of the pipeline: only the first color attachment of a frame
+/// buffer is written to, textures are sampled from their first mip level, and only rectangle
+/// textures can be rendered to or sampled from. Even with these limitations, it is enough to render
+/// simple scenes, user interface and previews in environments without a GPU (for example, to make
+/// golden-image tests).
+///
+/// ```rust
+/// use fyrox_graphics::{
+///     core::color::Color, framebuffer::ReadTarget, server::GraphicsServer,
+///     software::server::SoftwareGraphicsServer,
+/// };
+///
+/// let server = SoftwareGraphicsServer::new((4, 4));
+/// let back_buffer = server.back_buffer();
+/// back_buffer.clear(Default::default(), Some(Color::RED), Some(1.0), None);
+/// let pixels = back_buffer.read_pixels(ReadTarget::Color(0)).unwrap();
+/// assert_eq!(&pixels[0..4], &[255, 0, 0, 255]);
+/// ```
+pub struct SoftwareGraphicsServer {
+    null: Rc<NullGraphicsServer>,
+    back_buffer: RefCell<Option<GpuFrameBuffer>>,
+    shaders: RefCell<FxHashMap<String, Rc<SoftwareShader>>>,
+    this: RefCell<Option<Weak<SoftwareGraphicsServer>>>,
+}
+
+impl SoftwareGraphicsServer {
+    /// Creates a new software graphics server with the given back buffer size.
+    pub fn new(frame_size: (u32, u32)) -> Rc<Self> {
+        let server = Rc::new(Self {
+            null: NullGraphicsServer::new(frame_size),
+            back_buffer: Default::default(),
+            shaders: Default::default(),
+            this: Default::default(),
+        });
+
+        *server.this.borrow_mut() = Some(Rc::downgrade(&server));
+
+        server.set_frame_size(frame_size);
+
+        server
+    }
+
+    fn weak_self(&self) -> Weak<Self> {
+        self.this.borrow().as_ref().unwrap().clone()
+    }
+
+    /// Returns the underlying null graphics server, that could be used to inspect recorded draw
+    /// calls.
+    pub fn null_server(&self) -> &NullGraphicsServer {
+        &self.null
+    }
+
+    /// Registers a shader for a GPU program with the given name. Every program created with this
+    /// name will be executed using the shader. Previously registered shader with the same name will
+    /// be replaced.
+    pub fn register_shader(&self, program_name: impl Into<String>, shader: SoftwareShader) {
+        self.shaders
+            .borrow_mut()
+            .insert(program_name.into(), Rc::new(shader));
+    }
+
+    /// Returns a shader registered for a GPU program with the given name.
+    pub fn shader(&self, program_name: &str) -> Option<Rc<SoftwareShader>> {
+        self.shaders.borrow().get(program_name).cloned()
+    }
+
+    fn create_back_buffer(
+        &self,
+        (width, height): (u32, u32),
+    ) -> Result<GpuFrameBuffer, FrameworkError> {
+        let kind = GpuTextureKind::Rectangle {
+            width: (width as usize).max(1),
+            height: (height as usize).max(1),
+        };
+        let color = self.null.create_texture(GpuTextureDescriptor {
+            kind,
+            pixel_kind: PixelKind::RGBA8,
+            ..Default::default()
+        })?;
+        let depth = self.null.create_texture(GpuTextureDescriptor {
+            kind,
+            pixel_kind: PixelKind::D24S8,
+            ..Default::default()
+        })?;
+        Ok(GpuFrameBuffer(Rc::new(SoftwareFrameBuffer::new(
+            NullFrameBuffer::backbuffer_with_attachments(
+                &self.null,
+                Some(Attachment::depth_stencil(depth)),
+                vec![Attachment::color(color)],
+            )?,
+            self.weak_self(),
+        ))))
+    }
+}
+
+impl GraphicsServer for SoftwareGraphicsServer {
+    fn create_buffer(
+        &self,
+        size: usize,
+        buffer_kind: BufferKind,
+        buffer_usage: BufferUsage,
+    ) -> Result<GpuBuffer, FrameworkError> {
+        self.null.create_buffer(size, buffer_kind, buffer_usage)
+    }
+
+    fn create_texture(&self, desc: GpuTextureDescriptor) -> Result<GpuTexture, FrameworkError> {
+        self.null.create_texture(desc)
+    }
+
+    fn create_sampler(&self, desc: GpuSamplerDescriptor) -> Result<GpuSampler, FrameworkError> {
+        self.null.create_sampler(desc)
+    }
+
+    fn create_frame_buffer(
+        &self,
+        depth_attachment: Option<Attachment>,
+        color_attachments: Vec<Attachment>,
+    ) -> Result<GpuFrameBuffer, FrameworkError> {
+        Ok(GpuFrameBuffer(Rc::new(SoftwareFrameBuffer::new(
+            NullFrameBuffer::new(&self.null, depth_attachment, color_attachments)?,
+            self.weak_self(),
+        ))))
+    }
+
+    fn back_buffer(&self) -> GpuFrameBuffer {
+        self.back_buffer
+            .borrow()
+            .clone()
+            .expect("Back buffer must always exist!")
+    }
+
+    fn create_query(&self) -> Result<GpuQuery, FrameworkError> {
+        self.null.create_query()
+    }
+
+    fn create_shader(
+        &self,
+        name: String,
+        kind: ShaderKind,
+        source: String,
+        resources: &[ShaderResourceDefinition],
+        line_offset: isize,
+    ) -> Result<GpuShader, FrameworkError> {
+        self.null
+            .create_shader(name, kind, source, resources, line_offset)
+    }
+
+    fn create_program(
+        &self,
+        name: &str,
+        vertex_source: String,
+        vertex_source_line_offset: isize,
+        fragment_source: String,
+        fragment_source_line_offset: isize,
+        resources: &[ShaderResourceDefinition],
+    ) -> Result<GpuProgram, FrameworkError> {
+        self.null.create_program(
+            name,
+            vertex_source,
+            vertex_source_line_offset,
+            fragment_source,
+            fragment_source_line_offset,
+            resources,
+        )
+    }
+
+    fn create_async_read_buffer(
+        &self,
+        pixel_size: usize,
+        pixel_count: usize,
+    ) -> Result<GpuAsyncReadBuffer, FrameworkError> {
+        self.null.create_async_read_buffer(pixel_size, pixel_count)
+    }
+
+    fn create_geometry_buffer(
+        &self,
+        desc: GeometryBufferDescriptor,
+    ) -> Result<GpuGeometryBuffer, FrameworkError> {
+        self.null.create_geometry_buffer(desc)
+    }
+
+    fn weak(self: Rc<Self>) -> Weak<dyn GraphicsServer> {
+        self.weak_self()
+    }
+
+    fn flush(&self) {}
+
+    fn finish(&self) {}
+
+    fn invalidate_resource_bindings_cache(&self) {
+        self.null.invalidate_resource_bindings_cache()
+    }
+
+    fn pipeline_statistics(&self) -> PipelineStatistics {
+        self.null.pipeline_statistics()
+    }
+
+    fn swap_buffers(&self) -> Result<(), FrameworkError> {
+        self.null.swap_buffers()
+    }
+
+    fn set_frame_size(&self, new_size: (u32, u32)) {
+        self.null.set_frame_size(new_size);
+        *self.back_buffer.borrow_mut() = Some(
+            self.create_back_buffer(new_size)
+                .expect("Back buffer creation must never fail!"),
+        );
+    }
+
+    fn capabilities(&self) -> ServerCapabilities {
+        self.null.capabilities()
+    }
+
+    fn set_polygon_fill_mode(&self, polygon_face: PolygonFace, polygon_fill_mode: PolygonFillMode) {
+        self.null
+            .set_polygon_fill_mode(polygon_face, polygon_fill_mode)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        core::{
+            algebra::{Vector2, Vector4},
+            color::Color,
+            math::{Rect, TriangleDefinition},
+        },
+        framebuffer::{BufferDataUsage, ReadTarget, ResourceBindGroup, ResourceBinding},
+        geometry_buffer::{
+            AttributeDefinition, AttributeKind, ElementsDescriptor, VertexBufferData,
+            VertexBufferDescriptor,
+        },
+        software::shader::Varyings,
+        uniform::StaticUniformBuffer,
+        BlendFactor, BlendFunc, BlendParameters, CompareFunc, CullFace, DrawParameters,
+        ElementRange,
+    };
+
+    // A triangle that covers the bottom-left half of the viewport.
+    fn triangle(server: &SoftwareGraphicsServer, z: f32) -> GpuGeometryBuffer {
+        let vertices = [-1.0f32, -1.0, z, 1.0, -1.0, z, -1.0, 1.0, z];
+        server
+            .create_geometry_buffer(GeometryBufferDescriptor {
+                buffers: &[VertexBufferDescriptor {
+                    usage: BufferUsage::StaticDraw,
+                    attributes: &[AttributeDefinition {
+                        location: 0,
+                        kind: AttributeKind::Float,
+                        component_count: 3,
+                        normalized: false,
+                        divisor: 0,
+                    }],
+                    data: VertexBufferData {
+                        element_size: 3 * size_of::<f32>(),
+                        bytes: Some(crate::core::array_as_u8_slice(&vertices)),
+                    },
+                }],
+                usage: BufferUsage::StaticDraw,
+                elements: ElementsDescriptor::Triangles(&[TriangleDefinition([0, 1, 2])]),
+            })
+            .unwrap()
+    }
+
+    fn color_buffer(server: &SoftwareGraphicsServer, color: Vector4<f32>) -> GpuBuffer {
+        let buffer = server
+            .create_buffer(16, BufferKind::Uniform, BufferUsage::StreamDraw)
+            .unwrap();
+        buffer
+            .write_data(&StaticUniformBuffer::<16>::new().with(&color).finish())
+            .unwrap();
+        buffer
+    }
+
+    fn register_solid_color_shader(server: &SoftwareGraphicsServer) -> GpuProgram {
+        server.register_shader(
+            "SolidColor",
+            SoftwareShader::new(
+                |_, vertex| (vertex.position(0), Varyings::new()).into(),
+                |ctx, _| ctx.uniforms(0).read::<Vector4<f32>>(),
+            ),
+        );
+        server
+            .create_program(
+                "SolidColor",
+                "void main() {}".to_string(),
+                0,
+                "void main() {}".to_string(),
+                0,
+                &[],
+            )
+            .unwrap()
+    }
+
+    fn pixel(pixels: &[u8], x: usize, y: usize) -> &[u8] {
+        let offset = (y * 4 + x) * 4;
+        &pixels[offset..offset + 4]
+    }
+
+    #[test]
+    fn test_triangle_rasterization() {
+        let server = SoftwareGraphicsServer::new((4, 4));
+        let program = register_solid_color_shader(&server);
+        let geometry = triangle(&server, 0.0);
+        let color = color_buffer(&server, Vector4::new(0.0, 1.0, 0.0, 1.0));
+        let back_buffer = server.back_buffer();
+        back_buffer.clear(Default::default(), Some(Color::BLACK), Some(1.0), None);
+        let stats = back_buffer
+            .draw(
+                &geometry,
+                Rect::new(0, 0, 4, 4),
+                &program,
+                &DrawParameters::default(),
+                &[ResourceBindGroup {
+                    bindings: &[ResourceBinding::buffer(
+                        &color,
+                        0,
+                        BufferDataUsage::UseEverything,
+                    )],
+                }],
+                ElementRange::Full,
+            )
+            .unwrap();
+        assert_eq!(stats.triangles, 1);
+
+        let pixels = back_buffer.read_pixels(ReadTarget::Color(0)).unwrap();
+        // The origin is at the bottom left corner.
+        assert_eq!(pixel(&pixels, 0, 0), &[0, 255, 0, 255]);
+        assert_eq!(pixel(&pixels, 2, 0), &[0, 255, 0, 255]);
+        assert_eq!(pixel(&pixels, 0, 2), &[0, 255, 0, 255]);
+        assert_eq!(pixel(&pixels, 3, 3), &[0, 0, 0, 255]);
+        assert_eq!(pixel(&pixels, 2, 2), &[0, 0, 0, 255]);
+        assert_eq!(server.null_server().draw_calls().len(), 1);
+
+        // The triangle is front-facing, so it must be culled.
+        let params = DrawParameters {
+            cull_face: Some(CullFace::Front),
+            depth_test: None,
+            ..Default::default()
+        };
+        back_buffer.clear(Default::default(), Some(Color::BLACK), None, None);
+        let stats = back_buffer
+            .draw(
+                &geometry,
+                Rect::new(0, 0, 4, 4),
+                &program,
+                &params,
+                &[ResourceBindGroup {
+                    bindings: &[ResourceBinding::buffer(
+                        &color,
+                        0,
+                        BufferDataUsage::UseEverything,
+                    )],
+                }],
+                ElementRange::Full,
+            )
+            .unwrap();
+        assert_eq!(stats.triangles, 1);
+        let pixels = back_buffer.read_pixels(ReadTarget::Color(0)).unwrap();
+        assert!(pixels.chunks(4).all(|p| p == [0, 0, 0, 255]));
+    }
+
+    #[test]
+    fn test_depth_test_and_blending() {
+        let server = SoftwareGraphicsServer::new((4, 4));
+        let program = register_solid_color_shader(&server);
+        let near = triangle(&server, 0.0);
+        let far = triangle(&server, 0.5);
+        let red = color_buffer(&server, Vector4::new(1.0, 0.0, 0.0, 1.0));
+        let blue = color_buffer(&server, Vector4::new(0.0, 0.0, 1.0, 0.5));
+        let back_buffer = server.back_buffer();
+        back_buffer.clear(Default::default(), Some(Color::BLACK), Some(1.0), None);
+
+        for (geometry, color, params) in [
+            (&near, &red, DrawParameters::default()),
+            // Must be rejected by the depth test.
+            (&far, &blue, DrawParameters::default()),
+            (
+                &far,
+                &blue,
+                DrawParameters {
+                    depth_test: Some(CompareFunc::Always),
+                    blend: Some(BlendParameters {
+                        func: BlendFunc::new(BlendFactor::SrcAlpha, BlendFactor::OneMinusSrcAlpha),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                },
+            ),
+        ] {
+            let stats = back_buffer
+                .draw(
+                    geometry,
+                    Rect::new(0, 0, 4, 4),
+                    &program,
+                    &params,
+                    &[ResourceBindGroup {
+                        bindings: &[ResourceBinding::buffer(
+                            color,
+                            0,
+                            BufferDataUsage::UseEverything,
+                        )],
+                    }],
+                    ElementRange::Full,
+                )
+                .unwrap();
+            assert_eq!(stats.triangles, 1);
+        }
+
+        let pixels = back_buffer.read_pixels(ReadTarget::Color(0)).unwrap();
+        assert_eq!(pixel(&pixels, 0, 0), &[128, 0, 128, 191]);
+        assert_eq!(pixel(&pixels, 3, 3), &[0, 0, 0, 255]);
+        let depth = back_buffer
+            .read_pixels_of_type::<u32>(ReadTarget::Depth)
+            .unwrap();
+        // The last draw call has overwritten the depth.
+        assert_eq!(depth[0] >> 8, 0xBFFFFF);
+    }
+
+    #[test]
+    fn test_texture_sampling() {
+        let server = SoftwareGraphicsServer::new((2, 1));
+        let pixels = [255, 0, 0, 255, 0, 0, 255, 255];
+        let texture = server
+            .create_texture(GpuTextureDescriptor {
+                kind: GpuTextureKind::Rectangle {
+                    width: 2,
+                    height: 1,
+                },
+                data: Some(&pixels),
+                ..Default::default()
+            })
+            .unwrap();
+        let sampler = server.create_sampler(Default::default()).unwrap();
+        server.register_shader(
+            "Textured",
+            SoftwareShader::new(
+                |_, vertex| {
+                    let position = vertex.position(0);
+                    let mut varyings = Varyings::new();
+                    varyings.push((position.x + 1.0) * 0.5);
+                    varyings.push((position.y + 1.0) * 0.5);
+                    (position, varyings).into()
+                },
+                |ctx, fragment| {
+                    let uv = Vector2::new(fragment.varyings[0], fragment.varyings[1]);
+                    Some(ctx.sample(0, uv))
+                },
+            ),
+        );
+        let program = server
+            .create_program(
+                "Textured",
+                "void main() {}".to_string(),
+                0,
+                "void main() {}".to_string(),
+                0,
+                &[],
+            )
+            .unwrap();
+
+        let vertices = [-1.0f32, -1.0, 1.0, -1.0, 1.0, 1.0, -1.0, 1.0];
+        let quad = server
+            .create_geometry_buffer(GeometryBufferDescriptor {
+                buffers: &[VertexBufferDescriptor {
+                    usage: BufferUsage::StaticDraw,
+                    attributes: &[AttributeDefinition {
+                        location: 0,
+                        kind: AttributeKind::Float,
+                        component_count: 2,
+                        normalized: false,
+                        divisor: 0,
+                    }],
+                    data: VertexBufferData {
+                        element_size: 2 * size_of::<f32>(),
+                        bytes: Some(crate::core::array_as_u8_slice(&vertices)),
+                    },
+                }],
+                usage: BufferUsage::StaticDraw,
+                elements: ElementsDescriptor::Triangles(&[
+                    TriangleDefinition([0, 1, 2]),
+                    TriangleDefinition([0, 2, 3]),
+                ]),
+            })
+            .unwrap();
+
+        let back_buffer = server.back_buffer();
+        back_buffer.clear(Default::default(), None, Some(1.0), None);
+        let stats = back_buffer
+            .draw(
+                &quad,
+                Rect::new(0, 0, 2, 1),
+                &program,
+                &DrawParameters::default(),
+                &[ResourceBindGroup {
+                    bindings: &[ResourceBinding::texture(&texture, &sampler, 0)],
+                }],
+                ElementRange::Full,
+            )
+            .unwrap();
+        assert_eq!(stats.triangles, 2);
+        assert_eq!(
+            back_buffer.read_pixels(ReadTarget::Color(0)).unwrap(),
+            pixels.to_vec()
+        );
+    }
+}
@@ -0,0 +1,324 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Shader programs of the software graphics server are expressed as a pair of Rust closures.
+//! See [`SoftwareShader`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::{Vector2, Vector4},
+        arrayvec::ArrayVec,
+    },
+    geometry_buffer::{AttributeDefinition, AttributeKind},
+    gpu_texture::{GpuTexture, GpuTextureKind},
+    null::{framebuffer::null_texture, geometry_buffer::NullVertexBuffer},
+    sampler::{GpuSamplerDescriptor, MagnificationFilter, WrapMode},
+    software::pixel,
+    uniform::UniformReader,
+};
+
+/// Maximum number of interpolated floats that can be passed from a vertex shader to a fragment
+/// shader.
+pub const MAX_VARYINGS: usize = 32;
+
+/// A set of values that will be interpolated across a primitive and passed to a fragment shader.
+pub type Varyings = ArrayVec<f32, MAX_VARYINGS>;
+
+/// Vertex shader closure. It takes shader resources and a vertex and returns a transformed vertex.
+pub type VertexShader = dyn Fn(&ShaderContext, &VertexInput) -> VertexOutput;
+
+/// Fragment shader closure. It takes shader resources and a fragment and returns an optional color
+/// of the fragment. [`None`] discards the fragment.
+pub type FragmentShader = dyn Fn(&ShaderContext, &FragmentInput) -> Option<Vector4<f32>>;
+
+/// A shader program, that is expressed as a pair of Rust closures. It is a replacement of the
+/// GLSL source code of a GPU program with the same name.
+///
+/// ## Example
+///
+/// The following example shows how to "implement" a shader, that draws a mesh with a texture.
+///
+/// ```rust
+/// use fyrox_graphics::{
+///     core::algebra::{Matrix4, Vector2, Vector4},
+///     software::shader::{SoftwareShader, Varyings},
+/// };
+///
+/// let shader = SoftwareShader::new(
+///     |ctx, vertex| {
+///         let wvp = ctx.uniforms(0).read::<Matrix4<f32>>().unwrap_or_default();
+///         let tex_coord = vertex.attribute(1);
+///         let mut varyings = Varyings::new();
+///         varyings.push(tex_coord.x);
+///         varyings.push(tex_coord.y);
+///         (wvp * vertex.position(0), varyings).into()
+///     },
+///     |ctx, fragment| {
+///         let tex_coord = Vector2::new(fragment.varyings[0], fragment.varyings[1]);
+///         Some(ctx.sample(0, tex_coord))
+///     },
+/// );
+/// ```
+pub struct SoftwareShader {
+    pub(crate) vertex: Box<VertexShader>,
+    pub(crate) fragment: Box<FragmentShader>,
+}
+
+impl SoftwareShader {
+    /// Creates a new shader from the given pair of vertex and fragment shaders.
+    pub fn new<V, F>(vertex: V, fragment: F) -> Self
+    where
+        V: Fn(&ShaderContext, &VertexInput) -> VertexOutput + 'static,
+        F: Fn(&ShaderContext, &FragmentInput) -> Option<Vector4<f32>> + 'static,
+    {
+        Self {
+            vertex: Box::new(vertex),
+            fragment: Box::new(fragment),
+        }
+    }
+}
+
+/// Output of a vertex shader.
+#[derive(Clone, Debug, Default)]
+pub struct VertexOutput {
+    /// Clip-space position of the vertex (the same as `gl_Position`).
+    pub position: Vector4<f32>,
+    /// Values that will be interpolated and passed to a fragment shader.
+    pub varyings: Varyings,
+}
+
+impl From<(Vector4<f32>, Varyings)> for VertexOutput {
+    fn from((position, varyings): (Vector4<f32>, Varyings)) -> Self {
+        Self { position, varyings }
+    }
+}
+
+/// Input of a vertex shader.
+pub struct VertexInput<'a> {
+    pub(crate) buffers: &'a [NullVertexBuffer],
+    pub(crate) vertex_index: usize,
+    pub(crate) instance_index: usize,
+}
+
+fn read_component(kind: AttributeKind, normalized: bool, bytes: &[u8]) -> f32 {
+    match kind {
+        AttributeKind::Float => f32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+        AttributeKind::UnsignedByte => {
+            let value = bytes[0] as f32;
+            if normalized {
+                value / u8::MAX as f32
+            } else {
+                value
+            }
+        }
+        AttributeKind::UnsignedShort => {
+            let value = u16::from_ne_bytes([bytes[0], bytes[1]]) as f32;
+            if normalized {
+                value / u16::MAX as f32
+            } else {
+                value
+            }
+        }
+        AttributeKind::UnsignedInt => {
+            let value = u32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f32;
+            if normalized {
+                value / u32::MAX as f32
+            } else {
+                value
+            }
+        }
+    }
+}
+
+impl VertexInput<'_> {
+    /// Index of the vertex (the same as `gl_VertexID`).
+    pub fn vertex_index(&self) -> usize {
+        self.vertex_index
+    }
+
+    /// Index of the instance (the same as `gl_InstanceID`).
+    pub fn instance_index(&self) -> usize {
+        self.instance_index
+    }
+
+    /// Fetches a vertex attribute at the given location. Missing components are filled with
+    /// `(0, 0, 0, 1)` just like GPU does. Missing attributes are returned as zero vectors.
+    pub fn attribute(&self, location: u32) -> Vector4<f32> {
+        let mut result = Vector4::new(0.0, 0.0, 0.0, 1.0);
+        let Some((buffer, attribute, offset)) = self.find(location) else {
+            return Vector4::default();
+        };
+        let index = if attribute.divisor == 0 {
+            self.vertex_index
+        } else {
+            self.instance_index / attribute.divisor as usize
+        };
+        let component_size = attribute.kind.size();
+        let start = index * buffer.element_size + offset;
+        for i in 0..attribute.component_count.min(4) {
+            let begin = start + i * component_size;
+            if let Some(bytes) = buffer.data.get(begin..begin + component_size) {
+                result[i] = read_component(attribute.kind, attribute.normalized, bytes);
+            }
+        }
+        result
+    }
+
+    /// Fetches a position-like vertex attribute at the given location. It is the same as
+    /// [`Self::attribute`], but the `w` component is always `1.0`.
+    pub fn position(&self, location: u32) -> Vector4<f32> {
+        let mut position = self.attribute(location);
+        position.w = 1.0;
+        position
+    }
+
+    fn find(&self, location: u32) -> Option<(&NullVertexBuffer, &AttributeDefinition, usize)> {
+        for buffer in self.buffers {
+            let mut offset = 0;
+            for attribute in buffer.attributes.iter() {
+                if attribute.location == location {
+                    return Some((buffer, attribute, offset));
+                }
+                offset += attribute.kind.size() * attribute.component_count;
+            }
+        }
+        None
+    }
+}
+
+/// Input of a fragment shader.
+pub struct FragmentInput<'a> {
+    /// Window-relative coordinates of the fragment (the same as `gl_FragCoord`). `x` and `y` are
+    /// coordinates of the pixel center, `z` - depth, `w` - inverse of clip-space `w`.
+    pub position: Vector4<f32>,
+    /// Interpolated values of the vertex shader.
+    pub varyings: &'a [f32],
+    /// `true` if the fragment belongs to a front-facing primitive (the same as `gl_FrontFacing`).
+    pub front_facing: bool,
+}
+
+pub(crate) struct BoundTexture {
+    pub(crate) binding: usize,
+    pub(crate) texture: GpuTexture,
+    pub(crate) sampler: GpuSamplerDescriptor,
+}
+
+/// A set of resources that are bound to the pipeline during a draw call.
+#[derive(Default)]
+pub struct ShaderContext {
+    pub(crate) buffers: Vec<(usize, Vec<u8>)>,
+    pub(crate) textures: Vec<BoundTexture>,
+}
+
+fn wrap(coordinate: i64, size: usize, mode: WrapMode) -> Option<usize> {
+    let size = size as i64;
+    let wrapped = match mode {
+        WrapMode::Repeat => coordinate.rem_euclid(size),
+        WrapMode::ClampToEdge => coordinate.clamp(0, size - 1),
+        WrapMode::ClampToBorder => {
+            if coordinate < 0 || coordinate >= size {
+                return None;
+            } else {
+                coordinate
+            }
+        }
+        WrapMode::MirroredRepeat => {
+            let period = coordinate.rem_euclid(2 * size);
+            if period < size {
+                period
+            } else {
+                2 * size - 1 - period
+            }
+        }
+        WrapMode::MirrorClampToEdge => {
+            let mirrored = if coordinate < 0 {
+                -coordinate - 1
+            } else {
+                coordinate
+            };
+            mirrored.clamp(0, size - 1)
+        }
+    };
+    Some(wrapped as usize)
+}
+
+impl ShaderContext {
+    /// Returns a reader of a uniform buffer bound at the given binding point. If there's no such
+    /// buffer, the reader will return [`None`] on every read.
+    pub fn uniforms(&self, binding: usize) -> UniformReader<'_> {
+        let bytes = self
+            .buffers
+            .iter()
+            .find_map(|(b, bytes)| (*b == binding).then_some(bytes.as_slice()))
+            .unwrap_or_default();
+        UniformReader::new(bytes)
+    }
+
+    /// Samples a 2D texture bound at the given binding point using normalized texture coordinates.
+    /// The sampling is performed using the first mip level and the filtering options of the
+    /// sampler. Returns transparent black if there's no such texture.
+    pub fn sample(&self, binding: usize, tex_coord: Vector2<f32>) -> Vector4<f32> {
+        let Some(bound) = self.textures.iter().find(|t| t.binding == binding) else {
+            return Vector4::default();
+        };
+        let GpuTextureKind::Rectangle { width, height } = bound.texture.kind() else {
+            return Vector4::default();
+        };
+        if width == 0 || height == 0 {
+            return Vector4::default();
+        }
+        let Ok(texture) = null_texture(&bound.texture) else {
+            return Vector4::default();
+        };
+        // The texture could be bound as a render target at the same time.
+        let Ok(data) = texture.try_data() else {
+            return Vector4::default();
+        };
+        let pixel_kind = bound.texture.pixel_kind();
+        let pixel_size = pixel::pixel_size(pixel_kind);
+        let fetch = |x: i64, y: i64| -> Vector4<f32> {
+            let (Some(x), Some(y)) = (
+                wrap(x, width, bound.sampler.s_wrap_mode),
+                wrap(y, height, bound.sampler.t_wrap_mode),
+            ) else {
+                return Vector4::default();
+            };
+            let offset = (y * width + x) * pixel_size;
+            data.get(offset..offset + pixel_size)
+                .and_then(|bytes| pixel::decode(pixel_kind, bytes))
+                .unwrap_or_default()
+        };
+
+        let u = tex_coord.x * width as f32;
+        let v = tex_coord.y * height as f32;
+        match bound.sampler.mag_filter {
+            MagnificationFilter::Nearest => fetch(u.floor() as i64, v.floor() as i64),
+            MagnificationFilter::Linear => {
+                let (u, v) = (u - 0.5, v - 0.5);
+                let (x, y) = (u.floor(), v.floor());
+                let (tx, ty) = (u - x, v - y);
+                let (x, y) = (x as i64, y as i64);
+                let top = fetch(x, y).lerp(&fetch(x + 1, y), tx);
+                let bottom = fetch(x, y + 1).lerp(&fetch(x + 1, y + 1), tx);
+                top.lerp(&bottom, ty)
+            }
+        }
+    }
+}
@@ -376,11 +376,139 @@ where
     }
 }
 
+/// A trait for entities that can be read from a storage with `std140` data layout.
+pub trait Std140Read: Sized {
+    /// Alignment of the value in bytes.
+    const ALIGNMENT: usize;
+    /// Size of the value in bytes.
+    const SIZE: usize;
+
+    /// Reads the value from the given bytes. The slice is guaranteed to have [`Self::SIZE`] bytes.
+    fn read_bytes(bytes: &[u8]) -> Self;
+}
+
+fn read_f32s<const N: usize>(bytes: &[u8]) -> [f32; N] {
+    let mut values = [0.0; N];
+    for (value, chunk) in values.iter_mut().zip(bytes.chunks_exact(size_of::<f32>())) {
+        *value = f32::from_ne_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+    }
+    values
+}
+
+macro_rules! impl_std140_read {
+    ($ty:ty, $alignment:expr, |$bytes:ident| $body:expr) => {
+        impl Std140Read for $ty {
+            const ALIGNMENT: usize = $alignment;
+            const SIZE: usize = size_of::<$ty>();
+
+            fn read_bytes($bytes: &[u8]) -> Self {
+                $body
+            }
+        }
+    };
+}
+
+impl_std140_read!(f32, 4, |bytes| read_f32s::<1>(bytes)[0]);
+impl_std140_read!(u32, 4, |bytes| u32::from_ne_bytes([
+    bytes[0], bytes[1], bytes[2], bytes[3]
+]));
+impl_std140_read!(i32, 4, |bytes| i32::from_ne_bytes([
+    bytes[0], bytes[1], bytes[2], bytes[3]
+]));
+impl_std140_read!(Vector2<f32>, 8, |bytes| Vector2::from(read_f32s::<2>(
+    bytes
+)));
+impl_std140_read!(Vector3<f32>, 16, |bytes| Vector3::from(read_f32s::<3>(
+    bytes
+)));
+impl_std140_read!(Vector4<f32>, 16, |bytes| Vector4::from(read_f32s::<4>(
+    bytes
+)));
+impl_std140_read!(Matrix4<f32>, 16, |bytes| Matrix4::from_column_slice(
+    &read_f32s::<16>(bytes)
+));
+
+impl Std140Read for bool {
+    const ALIGNMENT: usize = 4;
+    const SIZE: usize = size_of::<i32>();
+
+    fn read_bytes(bytes: &[u8]) -> Self {
+        i32::read_bytes(bytes) != 0
+    }
+}
+
+/// Uniform reader is the counterpart of [`UniformBuffer`], it allows reading values from a byte
+/// storage with `std140` data layout. Values must be read in the same order they were written.
+///
+/// ## Examples
+///
+/// ```rust
+/// # use fyrox_core::{algebra::{Matrix4, Vector4}, color::Color};
+/// # use fyrox_graphics::uniform::{StaticUniformBuffer, UniformReader};
+/// let bytes = StaticUniformBuffer::<256>::new()
+///     .with(&Matrix4::identity())
+///     .with(&Color::WHITE)
+///     .with(&1.0f32)
+///     .finish();
+///
+/// let mut reader = UniformReader::new(&bytes);
+/// assert_eq!(reader.read::<Matrix4<f32>>(), Some(Matrix4::identity()));
+/// // Colors are stored as four floats.
+/// assert_eq!(reader.read::<Vector4<f32>>(), Some(Vector4::repeat(1.0)));
+/// assert_eq!(reader.read::<f32>(), Some(1.0));
+/// ```
+pub struct UniformReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> UniformReader<'a> {
+    /// Creates a new reader for the given bytes.
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, position: 0 }
+    }
+
+    /// Returns current read position in bytes.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Skips the bytes to ensure that the current position is a multiple of the given alignment.
+    /// The alignment must be power of two.
+    pub fn skip_padding(&mut self, alignment: usize) {
+        debug_assert!(alignment.is_power_of_two());
+        let remainder = (alignment - 1) & self.position;
+        if remainder > 0 {
+            self.position += alignment - remainder;
+        }
+    }
+
+    /// Reads a value with respect to its alignment. Returns [`None`] if there's not enough data.
+    pub fn read<T: Std140Read>(&mut self) -> Option<T> {
+        self.skip_padding(T::ALIGNMENT);
+        let bytes = self.bytes.get(self.position..self.position + T::SIZE)?;
+        self.position += T::SIZE;
+        Some(T::read_bytes(bytes))
+    }
+
+    /// Reads an array of the given length, that was written by
+    /// [`UniformBuffer::push_slice_with_max_size`].
+    pub fn read_slice<T: Std140Read>(&mut self, len: usize) -> Option<Vec<T>> {
+        let mut values = Vec::with_capacity(len);
+        for _ in 0..len {
+            self.skip_padding(16);
+            values.push(self.read()?);
+            self.skip_padding(16);
+        }
+        Some(values)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::{
         core::algebra::{Matrix3, Vector3, Vector4},
-        uniform::DynamicUniformBuffer,
+        uniform::{DynamicUniformBuffer, UniformReader},
     };
     use fyrox_core::transmute_slice;
 
@@ -437,4 +565,30 @@ mod test {
         let floats: &[f32] = transmute_slice(buffer.storage().as_slice());
         assert_eq!(floats, &[1.0, 0.0, 0.0, 0.0,]);
     }
+
+    #[test]
+    fn test_uniform_reader() {
+        let mut buffer = DynamicUniformBuffer::default();
+        buffer.push(&123.321);
+        buffer.push(&Vector3::new(1.0, 2.0, 3.0));
+        buffer.push(&true);
+        buffer.push_slice_with_max_size(&[1.0, 2.0], 3);
+        buffer.push(&Vector4::new(1.0, 2.0, 3.0, 4.0));
+        let bytes = buffer.finish();
+
+        let mut reader = UniformReader::new(&bytes);
+        assert_eq!(reader.read::<f32>(), Some(123.321));
+        assert_eq!(
+            reader.read::<Vector3<f32>>(),
+            Some(Vector3::new(1.0, 2.0, 3.0))
+        );
+        assert_eq!(reader.read::<bool>(), Some(true));
+        assert_eq!(reader.read_slice::<f32>(3), Some(vec![1.0, 2.0, 0.0]));
+        assert_eq!(
+            reader.read::<Vector4<f32>>(),
+            Some(Vector4::new(1.0, 2.0, 3.0, 4.0))
+        );
+        assert_eq!(reader.position(), bytes.len());
+        assert_eq!(reader.read::<f32>(), None);
+    }
 }
@@ -40,7 +40,7 @@ use crate::{
 };
 use clap::Parser;
 use fyrox_core::pool::Handle;
-use fyrox_resource::io::FsResourceIo;
+use fyrox_resource::io::{FsResourceIo, ResourceIo};
 use fyrox_ui::constructor::new_widget_constructor_container;
 use std::cell::Cell;
 use std::{
@@ -94,7 +94,7 @@ impl Executor {
     ) -> Self {
         let serialization_context = Arc::new(SerializationContext::new());
         let task_pool = Arc::new(TaskPool::new());
-        let io = Arc::new(FsResourceIo);
+        let io = Self::make_resource_io();
         let engine = Engine::new(EngineInitParams {
             graphics_context_params,
             resource_manager: ResourceManager::new(io, task_pool.clone()),
@@ -114,6 +114,38 @@ impl Executor {
         }
     }
 
+    /// Creates resource IO for the game. If there's a resource archive (see
+    /// [`fyrox_resource::io::ArchiveResourceIo`]) in the working directory, then the resources will
+    /// be loaded from it. Otherwise the resources will be loaded directly from the file system.
+    fn make_resource_io() -> Arc<dyn ResourceIo> {
+        #[cfg(all(not(target_os = "android"), not(target_arch = "wasm32")))]
+        {
+            use fyrox_resource::io::ArchiveResourceIo;
+
+            let archive_path = std::path::Path::new(ArchiveResourceIo::DEFAULT_PATH);
+            if archive_path.exists() {
+                match ArchiveResourceIo::open(archive_path) {
+                    Ok(io) => {
+                        Log::info(format!(
+                            "Using resource archive {} with {} files.",
+                            archive_path.display(),
+                            io.index().len()
+                        ));
+                        return Arc::new(io);
+                    }
+                    Err(err) => Log::err(format!(
+                        "Unable to open resource archive {}. Falling back to the file system. \
+                        Reason: {:?}",
+                        archive_path.display(),
+                        err
+                    )),
+                }
+            }
+        }
+
+        Arc::new(FsResourceIo)
+    }
+
     /// Creates new game executor using default window and with vsync turned on. For more flexible
     /// way to create an executor see [`Executor::from_params`]. To run the engine in headless mode,
     /// pass [`None`] to the `event_loop` argument.
@@ -218,6 +218,9 @@
 //! - `DirectionalShadow` - A pass that emits depth values for an object, later this depth map will be
 //! used to render shadows for directional cascaded shadows.
 //!
+//! - `PostEffect` - A pass that is drawn on a full-screen quad, when a material is used as a custom
+//! effect of a post-processing stack (see [`crate::scene::post_processing`]).
+//!
 //! # Resources
 //!
 //! Each shader requires a specific set of external resources that will be used during the rendering.
@@ -272,6 +275,18 @@
 //! |----------|-------------|---------------|
 //! | matrices | `mat4[256]` | Bone matrices |
 //!
+//! ### `fyrox_instanceMatrices`
+//!
+//! Property group. Contains local transforms of instances of an instanced draw call, that should
+//! be fetched using `gl_InstanceID`. The renderer draws a bundle of surfaces using instanced draw
+//! calls only if its shader defines this group and none of the instances use skeletal animation or
+//! blend shapes. In this case, `fyrox_instanceData` contains identity world matrix and the
+//! view-projection matrix of the observer. For regular draw calls the first matrix is identity.
+//!
+//! | Name     | Type        | Description                 |
+//! |----------|-------------|-----------------------------|
+//! | matrices | `mat4[255]` | Local-to-world transforms   |
+//!
 //!
 //! ### `fyrox_cameraData`
 //!
@@ -318,6 +333,86 @@
 //! |--------|------------|---------------------------------------------------|
 //! | usePom | `bool`     | Whether to use parallax occlusion mapping or not. |
 //!
+//! ### `fyrox_fogData`
+//!
+//! Property group. Contains fog settings of the scene (see [`crate::scene::fog::Fog`]). Available
+//! only in forward passes. The simplest way to apply the fog is to use `S_ApplySceneFog(color,
+//! worldPosition)` function, which requires `fyrox_cameraData`, `fyrox_fogData`,
+//! `fyrox_fogEnvironment` and `fyrox_volumetricFog` resources to be defined in the shader.
+//!
+//! | Name               | Type    | Description                                                               |
+//! |--------------------|---------|---------------------------------------------------------------------------|
+//! | mode               | `int`   | 0 - no fog, 1 - linear, 2 - exponential, 3 - exponential height.           |
+//! | color              | `vec4`  | Linear color of the fog (xyz) and its maximum opacity (w).                |
+//! | params             | `vec4`  | Parameters of the fog mode, see `S_FogOpacity` for more info.             |
+//! | useEnvironment     | `bool`  | Whether to take the color of the fog from `fyrox_fogEnvironment` or not.  |
+//! | environmentLod     | `float` | Mip level of `fyrox_fogEnvironment`, that should be used.                 |
+//! | volumetric         | `bool`  | Whether the volumetric fog is enabled or not.                             |
+//! | volumetricDistance | `float` | Max distance covered by the volumetric fog.                               |
+//!
+//! ### `fyrox_fogEnvironment`
+//!
+//! Cube map texture. Blurred skybox of the camera, that is used as the color of the fog, when
+//! `useEnvironment` from `fyrox_fogData` is set. Should be defined as
+//! `Texture(kind: SamplerCube, fallback: Black)`.
+//!
+//! ### `fyrox_volumetricFog`
+//!
+//! Texture. Contains integrated volumetric fog of the scene, should be sampled using
+//! `S_SampleVolumetricFog`. Should be defined as `Texture(kind: Sampler2D, fallback: Black)`.
+//!
+//! ### `fyrox_clusterData`
+//!
+//! Property group. Contains parameters of the light clusters of the camera. Available only in
+//! forward passes. Point and spot lights are binned into the clusters (froxels) of the view frustum,
+//! which allows forward passes to use all the lights of the scene, not only the first 16 from
+//! `fyrox_lightsBlock`. The simplest way to use the clusters is `S_ClusteredLighting(worldPosition)`
+//! function, which requires `fyrox_clusterData`, `fyrox_lightClusters` and `fyrox_clusteredLights`
+//! resources to be defined in the shader. Individual lights can be fetched using
+//! `S_ClusterIndex`, `S_ClusterLightRange`, `S_FetchClusteredLightIndex` and `S_FetchClusteredLight`
+//! functions.
+//!
+//! | Name                 | Type    | Description                                                 |
+//! |----------------------|---------|-------------------------------------------------------------|
+//! | enabled              | `bool`  | Whether the clustered lighting is enabled or not.           |
+//! | viewMatrix           | `mat4`  | View matrix of the camera, for which the clusters are built. |
+//! | viewProjectionMatrix | `mat4`  | View-projection matrix of the camera.                       |
+//! | zNear                | `float` | Distance to the near boundary of the first slice.           |
+//! | zFar                 | `float` | Distance to the far boundary of the last slice.             |
+//!
+//! ### `fyrox_lightClusters`
+//!
+//! Texture. Contains the light index lists of the clusters. Should be defined as
+//! `Texture(kind: USampler2D, fallback: Black)`.
+//!
+//! ### `fyrox_clusteredLights`
+//!
+//! Texture. Contains the parameters of the clustered lights. Should be defined as
+//! `Texture(kind: Sampler2D, fallback: Black)`.
+//!
+//! ### `fyrox_lights2DData`
+//!
+//! Property group. Contains the amount of 2D lights (see [`crate::scene::dim2::light`]) of the
+//! scene. Available only in forward passes. The simplest way to use 2D lights is
+//! `S_Lighting2D(worldPosition, normal, tangent, bitangent)` function, which requires
+//! `fyrox_lights2DData`, `fyrox_lights2D` and `fyrox_shadowMap2D` resources to be defined in the
+//! shader. Ambient lighting of 2D scenes is taken from `ambientLightColor` of `fyrox_lightData`,
+//! which is set by [`crate::scene::SceneRenderingOptions::ambient_lighting_color`].
+//!
+//! | Name       | Type  | Description                   |
+//! |------------|-------|-------------------------------|
+//! | lightCount | `int` | Amount of 2D lights to shade. |
+//!
+//! ### `fyrox_lights2D`
+//!
+//! Texture. Contains the parameters of the 2D lights, should be read using `S_FetchLight2D`.
+//! Should be defined as `Texture(kind: Sampler2D, fallback: Black)`.
+//!
+//! ### `fyrox_shadowMap2D`
+//!
+//! Texture. Contains polar shadow maps of the shadow casting 2D lights, should be read using
+//! `S_Shadow2D`. Should be defined as `Texture(kind: Sampler2D, fallback: White)`.
+//!
 //! ### `fyrox_sceneDepth`
 //!
 //! Texture. Contains depth values of scene. Available **only** after opaque geometry is
@@ -331,6 +426,30 @@
 //! ),
 //! ```
 //!
+//! ### `fyrox_sceneColor`
+//!
+//! Texture. Available only in `PostEffect` pass, contains the frame of the scene before the post
+//! effect. The frame is in linear HDR space for effects of `Hdr` stage and in sRGB space for
+//! effects of `Ldr` stage. Should be defined as `Texture(kind: Sampler2D, fallback: Black)`.
+//! `fyrox_sceneDepth` is also available in this pass.
+//!
+//! ### `fyrox_gbufferDiffuse`, `fyrox_gbufferNormal`, `fyrox_gbufferMaterial`
+//!
+//! Textures. Available only in `PostEffect` pass, contain diffuse color, packed world-space normals
+//! and material properties (metallic (r), roughness (g), ambient occlusion (b)) of the opaque
+//! geometry from the G-Buffer. Should be defined as `Texture(kind: Sampler2D, fallback: Black)`.
+//!
+//! ### `fyrox_postEffectData`
+//!
+//! Property group. Available only in `PostEffect` pass.
+//!
+//! | Name                    | Type    | Description                                              |
+//! |-------------------------|---------|----------------------------------------------------------|
+//! | worldViewProjection     | `mat4`  | Transforms the vertices of the full-screen quad.         |
+//! | invViewProjectionMatrix | `mat4`  | Inverse view-projection matrix of the camera.            |
+//! | frameSize               | `vec2`  | Size of the viewport of the camera in pixels.            |
+//! | elapsedTime             | `float` | Time (in seconds) since the start of the engine.         |
+//!
 //! # Code generation
 //!
 //! Fyrox automatically generates code for resource bindings. This is made specifically to prevent
@@ -606,6 +725,9 @@ impl ShaderDefinition {
     /// Maximum amount of blend shape weight groups (packed weights of blend shapes into vec4).
     pub const MAX_BLEND_SHAPE_WEIGHT_GROUPS: usize = 32;
 
+    /// Maximum amount of instances, that could be drawn in a single instanced draw call.
+    pub const MAX_INSTANCES_PER_DRAW_CALL: usize = 255;
+
     fn find_shader_line_locations(&mut self, str: &str) {
         let mut line_ends = Vec::new();
         for (i, ch) in str.bytes().enumerate() {
@@ -753,6 +875,76 @@ impl ShaderDefinition {
                         ),
                     ])
                 }
+                "fyrox_fogData" => {
+                    properties.clear();
+                    properties.extend([
+                        ShaderProperty::new("mode", Int { value: 0 }),
+                        ShaderProperty::new(
+                            "color",
+                            Vector4 {
+                                value: Default::default(),
+                            },
+                        ),
+                        ShaderProperty::new(
+                            "params",
+                            Vector4 {
+                                value: Default::default(),
+                            },
+                        ),
+                        ShaderProperty::new("useEnvironment", Bool { value: false }),
+                        ShaderProperty::new("environmentLod", Float { value: 0.0 }),
+                        ShaderProperty::new("volumetric", Bool { value: false }),
+                        ShaderProperty::new("volumetricDistance", Float { value: 0.0 }),
+                    ]);
+                }
+                "fyrox_clusterData" => {
+                    properties.clear();
+                    properties.extend([
+                        ShaderProperty::new("enabled", Bool { value: false }),
+                        ShaderProperty::new(
+                            "viewMatrix",
+                            Matrix4 {
+                                value: algebra::Matrix4::identity(),
+                            },
+                        ),
+                        ShaderProperty::new(
+                            "viewProjectionMatrix",
+                            Matrix4 {
+                                value: algebra::Matrix4::identity(),
+                            },
+                        ),
+                        ShaderProperty::new("zNear", Float { value: 0.0 }),
+                        ShaderProperty::new("zFar", Float { value: 0.0 }),
+                    ]);
+                }
+                "fyrox_lights2DData" => {
+                    properties.clear();
+                    properties.extend([ShaderProperty::new("lightCount", Int { value: 0 })]);
+                }
+                "fyrox_postEffectData" => {
+                    properties.clear();
+                    properties.extend([
+                        ShaderProperty::new(
+                            "worldViewProjection",
+                            Matrix4 {
+                                value: algebra::Matrix4::identity(),
+                            },
+                        ),
+                        ShaderProperty::new(
+                            "invViewProjectionMatrix",
+                            Matrix4 {
+                                value: algebra::Matrix4::identity(),
+                            },
+                        ),
+                        ShaderProperty::new(
+                            "frameSize",
+                            Vector2 {
+                                value: Default::default(),
+                            },
+                        ),
+                        ShaderProperty::new("elapsedTime", Float { value: 0.0 }),
+                    ]);
+                }
                 "fyrox_instanceData" => {
                     properties.clear();
                     properties.extend([
@@ -789,6 +981,16 @@ impl ShaderDefinition {
                         },
                     )])
                 }
+                "fyrox_instanceMatrices" => {
+                    properties.clear();
+                    properties.extend([ShaderProperty::new(
+                        "matrices",
+                        Matrix4Array {
+                            value: Default::default(),
+                            max_len: Self::MAX_INSTANCES_PER_DRAW_CALL,
+                        },
+                    )])
+                }
                 _ => (),
             }
         }
@@ -112,6 +112,30 @@
             ]),
             binding: 5
         ),
+        (
+            name: "fyrox_fogEnvironment",
+            kind: Texture(kind: SamplerCube, fallback: Black),
+            binding: 9
+        ),
+        (
+            name: "fyrox_volumetricFog",
+            kind: Texture(kind: Sampler2D, fallback: Black),
+            binding: 10
+        ),
+        (
+            name: "fyrox_fogData",
+            kind: PropertyGroup([
+                // Autogenerated
+            ]),
+            binding: 6
+        ),
+        (
+            name: "fyrox_instanceMatrices",
+            kind: PropertyGroup([
+                // Autogenerated
+            ]),
+            binding: 7
+        ),
     ],
 
     passes: [
@@ -206,6 +230,12 @@
                         localTangent = inputTangent;
                     }
 
+                    // Per-instance transform of instanced draw calls, it is identity otherwise.
+                    mat4 instanceMatrix = fyrox_instanceMatrices.matrices[gl_InstanceID];
+                    localPosition = instanceMatrix * localPosition;
+                    localNormal = mat3(instanceMatrix) * localNormal;
+                    localTangent = mat3(instanceMatrix) * localTangent;
+
                     mat3 nm = mat3(fyrox_instanceData.worldMatrix);
                     normal = normalize(nm * localNormal);
                     tangent = normalize(nm * localTangent);
@@ -350,8 +380,13 @@
                     {
                         localPosition = inputPosition;
                     }
+
+                    // Per-instance transform of instanced draw calls, it is identity otherwise.
+                    mat4 instanceMatrix = fyrox_instanceMatrices.matrices[gl_InstanceID];
+                    localPosition = instanceMatrix * localPosition;
                     gl_Position = fyrox_instanceData.worldViewProjection * localPosition;
                     texCoord = vertexTexCoord;
+                    position = (fyrox_instanceData.worldMatrix * localPosition).xyz;
                 }
                "#,
 
@@ -359,11 +394,13 @@
                r#"
                 out vec4 FragColor;
 
+                in vec3 position;
                 in vec2 texCoord;
 
                 void main()
                 {
                     FragColor = properties.diffuseColor * texture(diffuseTexture, texCoord);
+                    FragColor.rgb = S_ApplySceneFog(FragColor.rgb, position);
                 }
                "#,
         ),
@@ -431,6 +468,10 @@
                         localPosition = inputPosition;
                     }
 
+                    // Per-instance transform of instanced draw calls, it is identity otherwise.
+                    mat4 instanceMatrix = fyrox_instanceMatrices.matrices[gl_InstanceID];
+                    localPosition = instanceMatrix * localPosition;
+
                     gl_Position = fyrox_instanceData.worldViewProjection * localPosition;
                     texCoord = vertexTexCoord;
                 }
@@ -510,6 +551,10 @@
                         localPosition = inputPosition;
                     }
 
+                    // Per-instance transform of instanced draw calls, it is identity otherwise.
+                    mat4 instanceMatrix = fyrox_instanceMatrices.matrices[gl_InstanceID];
+                    localPosition = instanceMatrix * localPosition;
+
                     gl_Position = fyrox_instanceData.worldViewProjection * localPosition;
                     texCoord = vertexTexCoord;
                 }
@@ -590,6 +635,10 @@
                         localPosition = inputPosition;
                     }
 
+                    // Per-instance transform of instanced draw calls, it is identity otherwise.
+                    mat4 instanceMatrix = fyrox_instanceMatrices.matrices[gl_InstanceID];
+                    localPosition = instanceMatrix * localPosition;
+
                     gl_Position = fyrox_instanceData.worldViewProjection * localPosition;
                     worldPosition = (fyrox_instanceData.worldMatrix * localPosition).xyz;
                     texCoord = vertexTexCoord;
@@ -54,6 +54,40 @@
             ]),
             binding: 4
         ),
+        (
+            name: "fyrox_fogEnvironment",
+            kind: Texture(kind: SamplerCube, fallback: Black),
+            binding: 2
+        ),
+        (
+            name: "fyrox_volumetricFog",
+            kind: Texture(kind: Sampler2D, fallback: Black),
+            binding: 3
+        ),
+        (
+            name: "fyrox_fogData",
+            kind: PropertyGroup([
+                // Autogenerated
+            ]),
+            binding: 5
+        ),
+        (
+            name: "fyrox_clusterData",
+            kind: PropertyGroup([
+                // Autogenerated
+            ]),
+            binding: 6
+        ),
+        (
+            name: "fyrox_lightClusters",
+            kind: Texture(kind: USampler2D, fallback: Black),
+            binding: 4
+        ),
+        (
+            name: "fyrox_clusteredLights",
+            kind: Texture(kind: Sampler2D, fallback: Black),
+            binding: 5
+        ),
     ],
 
     disabled_passes: ["GBuffer", "DirectionalShadow", "PointShadow", "SpotShadow"],
@@ -150,6 +184,11 @@
                           vec3 lightPosition = fyrox_lightsBlock.lightsPosition[i];
                           vec3 direction = fyrox_lightsBlock.lightsDirection[i];
 
+                          // Point and spot lights are taken from the clusters, if they're available.
+                          if (fyrox_clusterData.enabled && !isinf(radius)) {
+                              continue;
+                          }
+
                           // Calculate lighting.
                           vec3 toFragment = fragmentPosition - lightPosition;
                           float distance = length(toFragment);
@@ -159,12 +198,14 @@
                           float directionalAttenuation = smoothstep(halfConeAngleCos, halfHotspotAngleCos, spotAngleCos);
                           lighting += lightColor * (distanceAttenuation * directionalAttenuation);
                       }
+                      lighting += S_ClusteredLighting(fragmentPosition);
                    } else {
                       lighting = vec3(1.0);
                    }
 
                    FragColor = vec4(lighting, 1.0) * color * S_SRGBToLinear(texture(diffuseTexture, texCoord)).r;
                    FragColor.a *= depthOpacity;
+                   FragColor.rgb = S_ApplySceneFog(FragColor.rgb, fragmentPosition);
                }
                "#,
         )
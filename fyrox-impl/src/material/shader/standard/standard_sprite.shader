@@ -21,6 +21,23 @@
             ]),
             binding: 1
         ),
+        (
+            name: "fyrox_fogEnvironment",
+            kind: Texture(kind: SamplerCube, fallback: Black),
+            binding: 1
+        ),
+        (
+            name: "fyrox_volumetricFog",
+            kind: Texture(kind: Sampler2D, fallback: Black),
+            binding: 2
+        ),
+        (
+            name: "fyrox_fogData",
+            kind: PropertyGroup([
+                // Autogenerated
+            ]),
+            binding: 2
+        ),
     ],
 
     disabled_passes: ["GBuffer", "DirectionalShadow", "PointShadow", "SpotShadow"],
@@ -68,6 +85,7 @@
 
                 out vec2 texCoord;
                 out vec4 color;
+                out vec3 fragmentPosition;
 
                 void main()
                 {
@@ -79,7 +97,9 @@
                     vec2 vertexOffset = S_RotateVec2(vertexTexCoord * 2.0 - 1.0, rotation);
                     vec4 worldPosition = fyrox_instanceData.worldMatrix * vec4(vertexPosition, 1.0);
                     vec3 offset = (vertexOffset.x * fyrox_cameraData.sideVector + vertexOffset.y * fyrox_cameraData.upVector) * size;
-                    gl_Position = fyrox_cameraData.viewProjectionMatrix * (worldPosition + vec4(offset.x, offset.y, offset.z, 0.0));
+                    vec4 finalPosition = worldPosition + vec4(offset.x, offset.y, offset.z, 0.0);
+                    fragmentPosition = finalPosition.xyz;
+                    gl_Position = fyrox_cameraData.viewProjectionMatrix * finalPosition;
                 }
                "#,
 
@@ -89,10 +109,12 @@
 
                 in vec2 texCoord;
                 in vec4 color;
+                in vec3 fragmentPosition;
 
                 void main()
                 {
                     FragColor = color * S_SRGBToLinear(texture(diffuseTexture, texCoord));
+                    FragColor.rgb = S_ApplySceneFog(FragColor.rgb, fragmentPosition);
                 }
                "#,
         )
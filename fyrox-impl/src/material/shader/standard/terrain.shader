@@ -119,6 +119,23 @@
             ]),
             binding: 4
         ),
+        (
+            name: "fyrox_fogEnvironment",
+            kind: Texture(kind: SamplerCube, fallback: Black),
+            binding: 11
+        ),
+        (
+            name: "fyrox_volumetricFog",
+            kind: Texture(kind: Sampler2D, fallback: Black),
+            binding: 12
+        ),
+        (
+            name: "fyrox_fogData",
+            kind: PropertyGroup([
+                // Autogenerated
+            ]),
+            binding: 5
+        ),
     ],
 
     passes: [
@@ -312,6 +329,7 @@
 
                     gl_Position = fyrox_instanceData.worldViewProjection * finalVertexPosition;
                     texCoord = actualTexCoords;
+                    position = (fyrox_instanceData.worldMatrix * finalVertexPosition).xyz;
                 }
                "#,
 
@@ -321,12 +339,14 @@
 
                 out vec4 FragColor;
 
+                in vec3 position;
                 in vec2 texCoord;
 
                 void main()
                 {
                     if (texture(holeMaskTexture, texCoord).r < 0.5) discard;
                     FragColor = properties.diffuseColor * texture(diffuseTexture, texCoord);
+                    FragColor.rgb = S_ApplySceneFog(FragColor.rgb, position);
                 }
                "#,
         ),
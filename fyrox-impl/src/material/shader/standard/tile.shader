@@ -7,6 +7,21 @@
             kind: Texture(kind: Sampler2D, fallback: White),
             binding: 0
         ),
+        (
+            name: "normalTexture",
+            kind: Texture(kind: Sampler2D, fallback: Normal),
+            binding: 1
+        ),
+        (
+            name: "fyrox_lights2D",
+            kind: Texture(kind: Sampler2D, fallback: Black),
+            binding: 2
+        ),
+        (
+            name: "fyrox_shadowMap2D",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 3
+        ),
         (
             name: "fyrox_instanceData",
             kind: PropertyGroup([
@@ -28,6 +43,13 @@
             ]),
             binding: 2
         ),
+        (
+            name: "fyrox_lights2DData",
+            kind: PropertyGroup([
+                // Autogenerated
+            ]),
+            binding: 3
+        ),
     ],
 
     disabled_passes: ["GBuffer", "DirectionalShadow", "PointShadow", "SpotShadow"],
@@ -75,6 +97,8 @@
                 out vec2 texCoord;
                 out vec4 color;
                 out vec3 fragmentPosition;
+                out vec2 tangent;
+                out vec2 bitangent;
 
                 void main()
                 {
@@ -82,6 +106,8 @@
                     fragmentPosition = (fyrox_instanceData.worldMatrix * vec4(vertexPosition, 1.0)).xyz;
                     gl_Position = fyrox_instanceData.worldViewProjection * vec4(vertexPosition, 1.0);
                     color = vertexColor;
+                    tangent = normalize(fyrox_instanceData.worldMatrix[0].xy);
+                    bitangent = normalize(fyrox_instanceData.worldMatrix[1].xy);
                 }
                "#,
 
@@ -92,6 +118,8 @@
                 in vec2 texCoord;
                 in vec4 color;
                 in vec3 fragmentPosition;
+                in vec2 tangent;
+                in vec2 bitangent;
 
                 void main()
                 {
@@ -115,6 +143,9 @@
                         lighting += lightColor * (distanceAttenuation * directionalAttenuation);
                     }
 
+                    vec3 normal = normalize(texture(normalTexture, texCoord).xyz * 2.0 - 1.0);
+                    lighting += S_Lighting2D(fragmentPosition, normal, tangent, bitangent);
+
                     FragColor = vec4(lighting, 1.0) * color * S_SRGBToLinear(texture(diffuseTexture, texCoord));
                 }
                "#,
@@ -0,0 +1,280 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Renders procedural sky of scenes (see [`Atmosphere`]) into cube maps and generates environment
+//! maps for image-based lighting from them. Every scene has its own cube map, which is re-rendered
+//! only when the parameters of the atmosphere change.
+
+use crate::{
+    core::{
+        algebra::{Vector2, Vector3},
+        math::Rect,
+        pool::Handle,
+        sstorage::ImmutableString,
+    },
+    renderer::{
+        cache::{
+            shader::{binding, property, PropertyGroup, RenderMaterial, RenderPassContainer},
+            uniform::UniformBufferCache,
+            TimeToLive,
+        },
+        framework::{
+            buffer::BufferUsage,
+            error::FrameworkError,
+            framebuffer::{Attachment, AttachmentKind, GpuFrameBuffer},
+            geometry_buffer::GpuGeometryBuffer,
+            gpu_texture::{
+                CubeMapFace, GpuTexture, GpuTextureDescriptor, GpuTextureKind, PixelKind,
+            },
+            sampler::{
+                GpuSampler, GpuSamplerDescriptor, MagnificationFilter, MinificationFilter, WrapMode,
+            },
+            server::GraphicsServer,
+            GeometryBufferExt,
+        },
+        ibl::{EnvironmentMaps, ImageBasedLightingRenderer},
+        make_viewport_matrix, RenderPassStatistics,
+    },
+    scene::{atmosphere::Atmosphere, mesh::surface::SurfaceData, Scene},
+};
+use fxhash::FxHashMap;
+
+/// Size of a face of the sky cube map.
+const SKY_MAP_SIZE: usize = 128;
+
+const FACES: [CubeMapFace; 6] = [
+    CubeMapFace::PositiveX,
+    CubeMapFace::NegativeX,
+    CubeMapFace::PositiveY,
+    CubeMapFace::NegativeY,
+    CubeMapFace::PositiveZ,
+    CubeMapFace::NegativeZ,
+];
+
+/// Rendered sky of a scene.
+pub struct AtmosphereMaps {
+    /// Cube map with the sky in linear color space.
+    pub sky: GpuTexture,
+    /// Environment maps, that were generated from the sky.
+    pub environment: EnvironmentMaps,
+}
+
+/// Everything that affects the look of the sky, it is used to detect changes.
+#[derive(PartialEq)]
+struct AtmosphereState {
+    atmosphere: Atmosphere,
+    sun_direction: Vector3<f32>,
+    cloud_offset: Vector2<f32>,
+}
+
+struct CacheEntry {
+    frame_buffer: GpuFrameBuffer,
+    maps: AtmosphereMaps,
+    state: AtmosphereState,
+    time_to_live: TimeToLive,
+}
+
+pub struct AtmosphereRenderer {
+    shader: RenderPassContainer,
+    quad: GpuGeometryBuffer,
+    sampler: GpuSampler,
+    cache: FxHashMap<Handle<Scene>, CacheEntry>,
+}
+
+impl AtmosphereRenderer {
+    pub fn new(server: &dyn GraphicsServer) -> Result<Self, FrameworkError> {
+        Ok(Self {
+            shader: RenderPassContainer::from_str(
+                server,
+                include_str!("shaders/atmosphere.shader"),
+            )?,
+            quad: GpuGeometryBuffer::from_surface_data(
+                &SurfaceData::make_unit_xy_quad(),
+                BufferUsage::StaticDraw,
+                server,
+            )?,
+            sampler: server.create_sampler(GpuSamplerDescriptor {
+                min_filter: MinificationFilter::Linear,
+                mag_filter: MagnificationFilter::Linear,
+                s_wrap_mode: WrapMode::ClampToEdge,
+                t_wrap_mode: WrapMode::ClampToEdge,
+                r_wrap_mode: WrapMode::ClampToEdge,
+                ..Default::default()
+            })?,
+            cache: Default::default(),
+        })
+    }
+
+    /// Returns a sampler, that should be used to sample the sky cube map.
+    pub fn sampler(&self) -> &GpuSampler {
+        &self.sampler
+    }
+
+    /// Returns the rendered sky of the given scene, if its atmosphere is enabled.
+    pub fn get(&self, scene: Handle<Scene>) -> Option<&AtmosphereMaps> {
+        self.cache.get(&scene).map(|entry| &entry.maps)
+    }
+
+    /// Renders the sky of the given scene and generates its environment maps, if the atmosphere
+    /// has changed since the last call. Does nothing and forgets the sky of the scene, if the
+    /// atmosphere is disabled.
+    #[allow(clippy::too_many_arguments)]
+    pub fn prepare(
+        &mut self,
+        server: &dyn GraphicsServer,
+        scene: Handle<Scene>,
+        atmosphere: &Atmosphere,
+        sun_direction: Vector3<f32>,
+        elapsed_time: f32,
+        ibl_renderer: &ImageBasedLightingRenderer,
+        uniform_buffer_cache: &mut UniformBufferCache,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        let mut stats = RenderPassStatistics::default();
+
+        if !atmosphere.enabled {
+            self.cache.remove(&scene);
+            return Ok(stats);
+        }
+
+        let cloud_offset = if atmosphere.clouds.enabled {
+            // Wind velocity is in meters per second, while the sky is measured in kilometers.
+            atmosphere.clouds.wind * (elapsed_time / 1000.0)
+        } else {
+            Vector2::default()
+        };
+        let state = AtmosphereState {
+            atmosphere: atmosphere.clone(),
+            sun_direction,
+            cloud_offset,
+        };
+
+        if let Some(entry) = self.cache.get_mut(&scene) {
+            entry.time_to_live = TimeToLive::default();
+            if entry.state == state {
+                return Ok(stats);
+            }
+        }
+
+        let frame_buffer = match self.cache.remove(&scene) {
+            Some(entry) => entry.frame_buffer,
+            None => server.create_frame_buffer(
+                None,
+                vec![Attachment {
+                    kind: AttachmentKind::Color,
+                    texture: server.create_texture(GpuTextureDescriptor {
+                        kind: GpuTextureKind::Cube {
+                            width: SKY_MAP_SIZE,
+                            height: SKY_MAP_SIZE,
+                        },
+                        pixel_kind: PixelKind::RGBA16F,
+                        ..Default::default()
+                    })?,
+                }],
+            )?,
+        };
+
+        stats += self.render_sky(&frame_buffer, &state, uniform_buffer_cache)?;
+
+        let sky = frame_buffer.color_attachments()[0].texture.clone();
+        let (environment, generation_stats) =
+            ibl_renderer.generate(server, &sky, &self.sampler, uniform_buffer_cache)?;
+        stats += generation_stats;
+
+        self.cache.insert(
+            scene,
+            CacheEntry {
+                frame_buffer,
+                maps: AtmosphereMaps { sky, environment },
+                state,
+                time_to_live: TimeToLive::default(),
+            },
+        );
+
+        Ok(stats)
+    }
+
+    fn render_sky(
+        &self,
+        frame_buffer: &GpuFrameBuffer,
+        state: &AtmosphereState,
+        uniform_buffer_cache: &mut UniformBufferCache,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        let mut stats = RenderPassStatistics::default();
+
+        let atmosphere = &state.atmosphere;
+        let clouds = &atmosphere.clouds;
+        let viewport = Rect::new(0, 0, SKY_MAP_SIZE as i32, SKY_MAP_SIZE as i32);
+        let frame_matrix = make_viewport_matrix(viewport);
+        let face_size = SKY_MAP_SIZE as f32;
+        let atmosphere_radius = atmosphere.planet_radius + atmosphere.atmosphere_height;
+        let ground_color = atmosphere.ground_color.srgb_to_linear_f32().xyz();
+        let cloud_color = clouds.color.srgb_to_linear_f32().xyz();
+        for (face_index, face) in FACES.into_iter().enumerate() {
+            frame_buffer.set_cubemap_face(0, face, 0);
+            let face_index = face_index as i32;
+            let properties = PropertyGroup::from([
+                property("worldViewProjection", &frame_matrix),
+                property("sunDirection", &state.sun_direction),
+                property("rayleighScattering", &atmosphere.rayleigh_scattering),
+                property("groundColor", &ground_color),
+                property("cloudColor", &cloud_color),
+                property("cloudOffset", &state.cloud_offset),
+                property("faceIndex", &face_index),
+                property("faceSize", &face_size),
+                property("sunIntensity", &atmosphere.sun_intensity),
+                property("sunDiskRadius", &atmosphere.sun_disk_radius),
+                property("planetRadius", &atmosphere.planet_radius),
+                property("atmosphereRadius", &atmosphere_radius),
+                property("observerAltitude", &atmosphere.observer_altitude),
+                property("rayleighScaleHeight", &atmosphere.rayleigh_scale_height),
+                property("mieScattering", &atmosphere.mie_scattering),
+                property("mieScaleHeight", &atmosphere.mie_scale_height),
+                property("mieAnisotropy", &atmosphere.mie_anisotropy),
+                property("cloudCoverage", &clouds.coverage),
+                property("cloudOpacity", &clouds.opacity),
+                property("cloudAltitude", &clouds.altitude),
+                property("cloudScale", &clouds.scale),
+                property("cloudsEnabled", &clouds.enabled),
+            ]);
+            let material = RenderMaterial::from([binding("properties", &properties)]);
+            stats += self.shader.run_pass(
+                1,
+                &ImmutableString::new("Primary"),
+                frame_buffer,
+                &self.quad,
+                viewport,
+                &material,
+                uniform_buffer_cache,
+                Default::default(),
+                None,
+            )?;
+        }
+
+        Ok(stats)
+    }
+
+    /// Removes the sky of scenes, that were not rendered for a while.
+    pub fn update(&mut self, dt: f32) {
+        self.cache.retain(|_, entry| {
+            *entry.time_to_live -= dt;
+            *entry.time_to_live > 0.0
+        });
+    }
+}
@@ -38,7 +38,7 @@ use crate::{
     },
 };
 
-mod blur;
+pub(crate) mod blur;
 
 pub struct BloomRenderer {
     shader: RenderPassContainer,
@@ -81,6 +81,7 @@ impl BloomRenderer {
         &self,
         quad: &GpuGeometryBuffer,
         hdr_scene_frame: &GpuTexture,
+        threshold: f32,
         uniform_buffer_cache: &mut UniformBufferCache,
         fallback_resources: &FallbackResources,
     ) -> Result<RenderPassStatistics, FrameworkError> {
@@ -89,7 +90,10 @@ impl BloomRenderer {
         let viewport = Rect::new(0, 0, self.width as i32, self.height as i32);
 
         let wvp = make_viewport_matrix(viewport);
-        let properties = PropertyGroup::from([property("worldViewProjection", &wvp)]);
+        let properties = PropertyGroup::from([
+            property("worldViewProjection", &wvp),
+            property("threshold", &threshold),
+        ]);
         let material = RenderMaterial::from([
             binding(
                 "hdrSampler",
@@ -24,7 +24,7 @@
 
 use crate::{
     core::{
-        algebra::{Matrix4, Vector3, Vector4},
+        algebra::{Matrix4, Vector2, Vector3, Vector4},
         arrayvec::ArrayVec,
         color,
         color::Color,
@@ -44,20 +44,25 @@ use crate::{
             uniform::{UniformBlockLocation, UniformMemoryAllocator},
             TimeToLive,
         },
+        cluster::LightClusters,
         framework::{
             error::FrameworkError,
             framebuffer::{GpuFrameBuffer, ResourceBindGroup, ResourceBinding},
             gpu_program::{ShaderProperty, ShaderPropertyKind, ShaderResourceKind},
             gpu_texture::GpuTexture,
+            sampler::GpuSampler,
             server::GraphicsServer,
             uniform::StaticUniformBuffer,
             uniform::{ByteStorage, UniformBuffer},
             ElementRange,
         },
+        light_2d::Lights2D,
         DynamicSurfaceCache, FallbackResources, LightData, RenderPassStatistics,
     },
     resource::texture::TextureResource,
     scene::{
+        dim2::{self, light::BaseLight2D, occluder::Occluder},
+        fog::{Fog, FogMode},
         graph::Graph,
         light::{
             directional::{CsmOptions, DirectionalLight},
@@ -71,6 +76,7 @@ use crate::{
             RenderPath,
         },
         node::{Node, RdcControlFlow},
+        tilemap::TileMap,
     },
 };
 use fxhash::{FxBuildHasher, FxHashMap, FxHasher};
@@ -149,9 +155,62 @@ pub struct BundleRenderContext<'a> {
     // TODO: Add depth pre-pass to remove Option here. Current architecture allows only forward
     // renderer to have access to depth buffer that is available from G-Buffer.
     pub scene_depth: Option<&'a GpuTexture>,
+    /// Fog of the scene. It is [`None`] for passes, that should not be fogged (shadow passes, for
+    /// example).
+    pub fog: Option<FogRenderData<'a>>,
+    /// Point and spot lights binned into the clusters of the camera. It is [`None`] for passes,
+    /// that do not need lighting or if the clustered lighting is disabled.
+    pub light_clusters: Option<&'a LightClusters>,
+    /// 2D lights of the scene with their shadow maps. It is [`None`] for passes, that do not need
+    /// 2D lighting.
+    pub lights_2d: Option<&'a Lights2D>,
     pub fallback_resources: &'a FallbackResources,
 }
 
+/// Fog parameters, that are passed to shaders via `fyrox_fogData`, `fyrox_fogEnvironment` and
+/// `fyrox_volumetricFog` built-in resources.
+#[derive(Clone, Copy)]
+pub struct FogRenderData<'a> {
+    /// Fog settings of the scene.
+    pub fog: &'a Fog,
+    /// Blurred skybox of the camera, its sampler and the mip level, that should be used as the fog
+    /// color. It is [`None`] if the fog uses its own color.
+    pub environment: Option<(&'a GpuTexture, &'a GpuSampler, f32)>,
+    /// Integrated volumetric fog. It is [`None`] if the volumetric fog is disabled.
+    pub volume: Option<&'a GpuTexture>,
+}
+
+impl FogRenderData<'_> {
+    /// Returns the index of the fog mode and its parameters in the format of `S_FogOpacity`
+    /// shader function.
+    pub fn mode_and_params(&self) -> (i32, Vector4<f32>) {
+        if !self.fog.enabled {
+            return (0, Vector4::default());
+        }
+        match self.fog.mode {
+            FogMode::Linear { start, end } => (1, Vector4::new(start, end, 0.0, 0.0)),
+            FogMode::Exponential { density } => (2, Vector4::new(density, 0.0, 0.0, 0.0)),
+            FogMode::ExponentialHeight {
+                density,
+                base_height,
+                height_falloff,
+            } => (3, Vector4::new(density, base_height, height_falloff, 0.0)),
+        }
+    }
+
+    /// Returns linear color of the fog (xyz) with its maximum opacity (w).
+    pub fn color(&self) -> Vector4<f32> {
+        let mut color = self.fog.color.srgb_to_linear_f32();
+        color.w = self.fog.max_opacity;
+        color
+    }
+
+    /// Returns mip level of the environment map, that should be used as the fog color.
+    pub fn environment_lod(&self) -> f32 {
+        self.environment.map_or(0.0, |(_, _, lod)| lod)
+    }
+}
+
 /// A set of data of a surface for rendering.
 pub struct SurfaceInstanceData {
     /// A world matrix.
@@ -207,12 +266,19 @@ impl Debug for RenderDataBundle {
 }
 
 /// Describes where to the actual uniform data is located in the memory backed by the uniform
-/// memory allocator on per-instance basis.
+/// memory allocator on per-draw-call basis. A draw call renders either a single instance or a
+/// batch of instances, if the bundle is drawn using instancing.
 pub struct InstanceUniformData {
     /// Instance info block location.
     pub instance_block: UniformBlockLocation,
     /// Bone matrices block location. Could be [`None`], if there's no bone matrices.
     pub bone_matrices_block: Option<UniformBlockLocation>,
+    /// Instance matrices block location. Could be [`None`], if the draw call is not instanced.
+    pub instance_matrices_block: Option<UniformBlockLocation>,
+    /// Amount of instances to draw.
+    pub instance_count: usize,
+    /// A range of elements to draw for each instance.
+    pub element_range: ElementRange,
 }
 
 /// Describes where to the actual uniform data is located in the memory backed by the uniform
@@ -222,7 +288,7 @@ pub struct BundleUniformData {
     pub material_property_group_blocks: Vec<(usize, UniformBlockLocation)>,
     /// Lights info block location.
     pub light_data_block: UniformBlockLocation,
-    /// Block locations for each instance in a bundle.
+    /// Block locations for each draw call of a bundle.
     pub instance_blocks: Vec<InstanceUniformData>,
 }
 
@@ -233,6 +299,12 @@ pub struct GlobalUniformData {
     pub lights_block: UniformBlockLocation,
     /// Graphics settings block location.
     pub graphics_settings_block: UniformBlockLocation,
+    /// Fog settings block location.
+    pub fog_block: UniformBlockLocation,
+    /// Light clusters block location.
+    pub cluster_block: UniformBlockLocation,
+    /// 2D lights block location.
+    pub lights_2d_block: UniformBlockLocation,
 }
 
 pub fn write_with_material<T, C, G>(
@@ -348,12 +420,34 @@ pub fn write_shader_values<T: ByteStorage>(
 }
 
 impl RenderDataBundle {
-    /// Writes all the required uniform data of the bundle to uniform memory allocator.
-    pub fn write_uniforms(
+    /// Checks whether the bundle could be drawn using instanced draw calls. It is possible only if
+    /// the shader supports instancing (defines `fyrox_instanceMatrices` property group), there's
+    /// more than one instance and all the instances share the same element range and do not use
+    /// skeletal animation or blend shapes.
+    pub fn is_instanceable(&self, shader: &ShaderDefinition) -> bool {
+        self.instances.len() > 1
+            && shader
+                .resources
+                .iter()
+                .any(|resource| resource.name.as_str() == "fyrox_instanceMatrices")
+            && self.instances.iter().all(|instance| {
+                instance.bone_matrices.is_empty()
+                    && instance.blend_shapes_weights.is_empty()
+                    && instance.element_range == self.instances[0].element_range
+            })
+    }
+
+    /// Writes all the required uniform data of the bundle to uniform memory allocator. Instances
+    /// that do not pass the given filter will not be rendered.
+    pub fn write_uniforms<F>(
         &self,
         view_projection_matrix: &Matrix4<f32>,
         render_context: &mut BundleRenderContext,
-    ) -> Option<BundleUniformData> {
+        instance_filter: &mut F,
+    ) -> Option<BundleUniformData>
+    where
+        F: FnMut(&SurfaceInstanceData) -> bool,
+    {
         let mut material_state = self.material.state();
         let material = material_state.data()?;
 
@@ -402,9 +496,69 @@ impl RenderDataBundle {
             .with(&render_context.ambient_light.as_frgba());
         let light_data_block = render_context.uniform_memory_allocator.allocate(light_data);
 
+        const INIT: Matrix4<f32> = Matrix4::new(
+            0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0,
+        );
+
+        if self.is_instanceable(&shader.definition) {
+            // All the instances share the same instance info block, world transforms are
+            // fetched from instance matrices instead.
+            let instance_buffer = StaticUniformBuffer::<1024>::new()
+                .with(&Matrix4::<f32>::identity())
+                .with(view_projection_matrix)
+                .with(&0i32)
+                .with(&false)
+                .with_slice_with_max_size(
+                    &[Vector4::<f32>::default(); ShaderDefinition::MAX_BLEND_SHAPE_WEIGHT_GROUPS],
+                    ShaderDefinition::MAX_BLEND_SHAPE_WEIGHT_GROUPS,
+                );
+            let instance_block = render_context
+                .uniform_memory_allocator
+                .allocate(instance_buffer);
+
+            let world_transforms = self
+                .instances
+                .iter()
+                .filter(|instance| instance_filter(instance))
+                .map(|instance| instance.world_transform)
+                .collect::<Vec<_>>();
+
+            let instance_blocks = world_transforms
+                .chunks(ShaderDefinition::MAX_INSTANCES_PER_DRAW_CALL)
+                .map(|chunk| {
+                    let mut matrices = [INIT; ShaderDefinition::MAX_INSTANCES_PER_DRAW_CALL];
+                    const SIZE: usize =
+                        ShaderDefinition::MAX_INSTANCES_PER_DRAW_CALL * size_of::<Matrix4<f32>>();
+                    matrices[0..chunk.len()].copy_from_slice(chunk);
+
+                    InstanceUniformData {
+                        instance_block,
+                        bone_matrices_block: None,
+                        instance_matrices_block: Some(
+                            render_context
+                                .uniform_memory_allocator
+                                .allocate(StaticUniformBuffer::<SIZE>::new().with(&matrices)),
+                        ),
+                        instance_count: chunk.len(),
+                        element_range: self.instances[0].element_range,
+                    }
+                })
+                .collect();
+
+            return Some(BundleUniformData {
+                material_property_group_blocks,
+                light_data_block,
+                instance_blocks,
+            });
+        }
+
         // Upload instance uniforms.
         let mut instance_blocks = Vec::with_capacity(self.instances.len());
         for instance in self.instances.iter() {
+            if !instance_filter(instance) {
+                continue;
+            }
+
             let mut packed_blend_shape_weights =
                 [Vector4::<f32>::default(); ShaderDefinition::MAX_BLEND_SHAPE_WEIGHT_GROUPS];
 
@@ -429,12 +583,12 @@ impl RenderDataBundle {
                     .uniform_memory_allocator
                     .allocate(instance_buffer),
                 bone_matrices_block: None,
+                instance_matrices_block: None,
+                instance_count: 1,
+                element_range: instance.element_range,
             };
 
             if !instance.bone_matrices.is_empty() {
-                const INIT: Matrix4<f32> = Matrix4::new(
-                    0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0,
-                );
                 let mut matrices = [INIT; ShaderDefinition::MAX_BONE_MATRICES];
                 const SIZE: usize = ShaderDefinition::MAX_BONE_MATRICES * size_of::<Matrix4<f32>>();
                 matrices[0..instance.bone_matrices.len()].copy_from_slice(&instance.bone_matrices);
@@ -456,19 +610,15 @@ impl RenderDataBundle {
     }
 
     /// Draws the entire bundle to the specified frame buffer with the specified rendering environment.
-    pub fn render_to_frame_buffer<F>(
+    pub fn render_to_frame_buffer(
         &self,
         server: &dyn GraphicsServer,
         geometry_cache: &mut GeometryCache,
         shader_cache: &mut ShaderCache,
-        instance_filter: &mut F,
         render_context: &mut BundleRenderContext,
         bundle_uniform_data: BundleUniformData,
         global_uniform_data: &GlobalUniformData,
-    ) -> Result<RenderPassStatistics, FrameworkError>
-    where
-        F: FnMut(&SurfaceInstanceData) -> bool,
-    {
+    ) -> Result<RenderPassStatistics, FrameworkError> {
         let mut stats = RenderPassStatistics::default();
 
         let mut material_state = self.material.state();
@@ -579,6 +729,96 @@ impl RenderDataBundle {
                         ),
                     );
                 }
+                "fyrox_fogData" => {
+                    material_bindings.push(
+                        render_context.uniform_memory_allocator.block_to_binding(
+                            global_uniform_data.fog_block,
+                            resource_definition.binding,
+                        ),
+                    );
+                }
+                "fyrox_fogEnvironment" => {
+                    let fallback_resources = render_context.fallback_resources;
+                    let (texture, sampler) =
+                        render_context.fog.and_then(|fog| fog.environment).map_or(
+                            (
+                                &fallback_resources.environment_dummy,
+                                &fallback_resources.linear_clamp_sampler,
+                            ),
+                            |(texture, sampler, _)| (texture, sampler),
+                        );
+                    material_bindings.push(ResourceBinding::texture(
+                        texture,
+                        sampler,
+                        resource_definition.binding,
+                    ));
+                }
+                "fyrox_clusterData" => {
+                    material_bindings.push(
+                        render_context.uniform_memory_allocator.block_to_binding(
+                            global_uniform_data.cluster_block,
+                            resource_definition.binding,
+                        ),
+                    );
+                }
+                "fyrox_lightClusters" => {
+                    material_bindings.push(ResourceBinding::texture(
+                        render_context
+                            .light_clusters
+                            .map(|clusters| clusters.clusters_texture())
+                            .unwrap_or(&render_context.fallback_resources.black_dummy),
+                        &render_context.fallback_resources.nearest_clamp_sampler,
+                        resource_definition.binding,
+                    ));
+                }
+                "fyrox_clusteredLights" => {
+                    material_bindings.push(ResourceBinding::texture(
+                        render_context
+                            .light_clusters
+                            .map(|clusters| clusters.lights_texture())
+                            .unwrap_or(&render_context.fallback_resources.black_dummy),
+                        &render_context.fallback_resources.nearest_clamp_sampler,
+                        resource_definition.binding,
+                    ));
+                }
+                "fyrox_lights2DData" => {
+                    material_bindings.push(
+                        render_context.uniform_memory_allocator.block_to_binding(
+                            global_uniform_data.lights_2d_block,
+                            resource_definition.binding,
+                        ),
+                    );
+                }
+                "fyrox_lights2D" => {
+                    material_bindings.push(ResourceBinding::texture(
+                        render_context
+                            .lights_2d
+                            .map(|lights| lights.lights_texture())
+                            .unwrap_or(&render_context.fallback_resources.black_dummy),
+                        &render_context.fallback_resources.nearest_clamp_sampler,
+                        resource_definition.binding,
+                    ));
+                }
+                "fyrox_shadowMap2D" => {
+                    material_bindings.push(ResourceBinding::texture(
+                        render_context
+                            .lights_2d
+                            .map(|lights| lights.shadow_map())
+                            .unwrap_or(&render_context.fallback_resources.white_dummy),
+                        &render_context.fallback_resources.nearest_clamp_sampler,
+                        resource_definition.binding,
+                    ));
+                }
+                "fyrox_volumetricFog" => {
+                    material_bindings.push(ResourceBinding::texture(
+                        render_context
+                            .fog
+                            .and_then(|fog| fog.volume)
+                            .unwrap_or(&render_context.fallback_resources.black_dummy),
+                        &render_context.fallback_resources.linear_clamp_sampler,
+                        resource_definition.binding,
+                    ));
+                }
                 _ => match resource_definition.kind {
                     ShaderResourceKind::Texture { fallback, .. } => {
                         let fallback = render_context.fallback_resources.sampler_fallback(fallback);
@@ -638,14 +878,7 @@ impl RenderDataBundle {
             }
         }
 
-        for (instance, uniform_data) in self
-            .instances
-            .iter()
-            .zip(bundle_uniform_data.instance_blocks)
-        {
-            if !instance_filter(instance) {
-                continue;
-            }
+        for uniform_data in bundle_uniform_data.instance_blocks {
             let mut instance_bindings = ArrayVec::<ResourceBinding, 32>::new();
 
             for resource_definition in shader.definition.resources.iter() {
@@ -682,25 +915,58 @@ impl RenderDataBundle {
                             }
                         }
                     }
+                    "fyrox_instanceMatrices" => match uniform_data.instance_matrices_block {
+                        Some(block) => {
+                            instance_bindings.push(
+                                render_context
+                                    .uniform_memory_allocator
+                                    .block_to_binding(block, resource_definition.binding),
+                            );
+                        }
+                        None => {
+                            instance_bindings.push(ResourceBinding::Buffer {
+                                buffer: render_context
+                                    .fallback_resources
+                                    .instance_matrices_stub_uniform_buffer
+                                    .clone(),
+                                binding: resource_definition.binding,
+                                data_usage: Default::default(),
+                            });
+                        }
+                    },
                     _ => (),
                 };
             }
 
-            stats += render_context.frame_buffer.draw(
-                geometry,
-                render_context.viewport,
-                &render_pass.program,
-                &render_pass.draw_params,
-                &[
-                    ResourceBindGroup {
-                        bindings: &material_bindings,
-                    },
-                    ResourceBindGroup {
-                        bindings: &instance_bindings,
-                    },
-                ],
-                instance.element_range,
-            )?;
+            let resources = [
+                ResourceBindGroup {
+                    bindings: &material_bindings,
+                },
+                ResourceBindGroup {
+                    bindings: &instance_bindings,
+                },
+            ];
+
+            stats += if uniform_data.instance_matrices_block.is_some() {
+                render_context.frame_buffer.draw_instances(
+                    uniform_data.instance_count,
+                    geometry,
+                    render_context.viewport,
+                    &render_pass.program,
+                    &render_pass.draw_params,
+                    &resources,
+                    uniform_data.element_range,
+                )?
+            } else {
+                render_context.frame_buffer.draw(
+                    geometry,
+                    render_context.viewport,
+                    &render_pass.program,
+                    &render_pass.draw_params,
+                    &resources,
+                    uniform_data.element_range,
+                )?
+            };
         }
 
         Ok(stats)
@@ -786,6 +1052,24 @@ pub struct LightSource {
     pub intensity: f32,
     pub scatter_enabled: bool,
     pub scatter: Vector3<f32>,
+    pub source_size: f32,
+}
+
+/// 2D light source (see [`crate::scene::dim2::light`]) prepared for rendering.
+pub struct LightSource2D {
+    pub handle: Handle<Node>,
+    pub position: Vector2<f32>,
+    /// Normalized direction of the light in world space. Only used by spot lights.
+    pub direction: Vector2<f32>,
+    pub color: Color,
+    pub intensity: f32,
+    pub radius: f32,
+    pub height: f32,
+    pub cast_shadows: bool,
+    /// Full angle of the cone of the light, [`std::f32::consts::TAU`] for point lights.
+    pub full_cone_angle: f32,
+    /// Angle of the cone of the light in which the light has full intensity.
+    pub hotspot_cone_angle: f32,
 }
 
 /// Bundle storage handles bundle generation for a scene before rendering. It is used to optimize
@@ -796,6 +1080,11 @@ pub struct RenderDataBundleStorage {
     /// A sorted list of bundles.
     pub bundles: Vec<RenderDataBundle>,
     pub light_sources: Vec<LightSource>,
+    /// A list of visible 2D light sources.
+    pub light_sources_2d: Vec<LightSource2D>,
+    /// World-space edges of all 2D light occluders. Only collected when there is at least one
+    /// 2D light that casts shadows.
+    pub occluder_edges_2d: Vec<[Vector2<f32>; 2]>,
 }
 
 pub struct RenderDataBundleStorageOptions {
@@ -817,6 +1106,8 @@ impl RenderDataBundleStorage {
             observer_info,
             bundles: Default::default(),
             light_sources: Default::default(),
+            light_sources_2d: Default::default(),
+            occluder_edges_2d: Default::default(),
         }
     }
 
@@ -838,6 +1129,8 @@ impl RenderDataBundleStorage {
             observer_info: observer_info.clone(),
             bundles: Vec::with_capacity(capacity),
             light_sources: Default::default(),
+            light_sources_2d: Default::default(),
+            occluder_edges_2d: Default::default(),
         };
 
         let frustum = Frustum::from_view_projection_matrix(
@@ -846,6 +1139,7 @@ impl RenderDataBundleStorage {
         .unwrap_or_default();
 
         let mut lod_filter = vec![true; graph.capacity() as usize];
+        let mut occluders_2d = Vec::new();
         for (node_handle, node) in graph.pair_iter() {
             if let Some(lod_group) = node.lod_group() {
                 for level in lod_group.levels.iter() {
@@ -905,11 +1199,65 @@ impl RenderDataBundleStorage {
                             intensity: base_light.intensity(),
                             scatter_enabled: base_light.is_scatter_enabled(),
                             scatter: base_light.scatter(),
+                            source_size: base_light.source_size(),
                         };
 
                         storage.light_sources.push(source);
                     }
                 }
+
+                if let Some(base_light) = node.component_ref::<BaseLight2D>() {
+                    if frustum.is_intersects_aabb(&node.world_bounding_box())
+                        && base_light.global_visibility()
+                        && base_light.is_globally_enabled()
+                    {
+                        let (full_cone_angle, hotspot_cone_angle) =
+                            if let Some(spot_light) = node.cast::<dim2::light::SpotLight>() {
+                                (
+                                    spot_light.full_cone_angle(),
+                                    spot_light.hotspot_cone_angle(),
+                                )
+                            } else {
+                                (std::f32::consts::TAU, std::f32::consts::TAU)
+                            };
+
+                        storage.light_sources_2d.push(LightSource2D {
+                            handle: node_handle,
+                            position: base_light.global_position().xy(),
+                            direction: base_light
+                                .side_vector()
+                                .xy()
+                                .try_normalize(f32::EPSILON)
+                                .unwrap_or_else(Vector2::x),
+                            color: base_light.color(),
+                            intensity: base_light.intensity(),
+                            radius: base_light.radius(),
+                            height: base_light.height(),
+                            cast_shadows: base_light.cast_shadows(),
+                            full_cone_angle,
+                            hotspot_cone_angle,
+                        });
+                    }
+                }
+
+                // Occluders are not frustum-culled, because they can cast shadows from lights
+                // that are visible even if the occluders themselves are off-screen.
+                if node.is_globally_enabled()
+                    && (node.cast::<Occluder>().is_some() || node.cast::<TileMap>().is_some())
+                {
+                    occluders_2d.push(node_handle);
+                }
+            }
+        }
+
+        if storage.light_sources_2d.iter().any(|l| l.cast_shadows) {
+            for handle in occluders_2d {
+                let node = &graph[handle];
+                if let Some(occluder) = node.cast::<Occluder>() {
+                    occluder.collect_edges(&mut storage.occluder_edges_2d);
+                } else if let Some(tile_map) = node.cast::<TileMap>() {
+                    tile_map.collect_occluder_edges(&mut storage.occluder_edges_2d);
+                }
             }
         }
 
@@ -1033,10 +1381,65 @@ impl RenderDataBundleStorage {
             .uniform_memory_allocator
             .allocate(graphics_settings);
 
+        let fog = render_context.fog.as_ref();
+        let (fog_mode, fog_params) = fog.map(|fog| fog.mode_and_params()).unwrap_or_default();
+        let fog_data = StaticUniformBuffer::<256>::new()
+            .with(&fog_mode)
+            .with(&fog.map(|fog| fog.color()).unwrap_or_default())
+            .with(&fog_params)
+            .with(&fog.is_some_and(|fog| fog.environment.is_some()))
+            .with(&fog.map(|fog| fog.environment_lod()).unwrap_or_default())
+            .with(&fog.is_some_and(|fog| fog.volume.is_some()))
+            .with(
+                &fog.map(|fog| fog.fog.volumetric.distance)
+                    .unwrap_or_default(),
+            );
+        let fog_block = render_context.uniform_memory_allocator.allocate(fog_data);
+
+        let clusters = render_context.light_clusters;
+        let cluster_data = StaticUniformBuffer::<256>::new()
+            .with(&clusters.is_some())
+            .with(
+                &clusters
+                    .map(|clusters| *clusters.view_matrix())
+                    .unwrap_or_else(Matrix4::identity),
+            )
+            .with(
+                &clusters
+                    .map(|clusters| *clusters.view_projection_matrix())
+                    .unwrap_or_else(Matrix4::identity),
+            )
+            .with(
+                &clusters
+                    .map(|clusters| clusters.z_near())
+                    .unwrap_or_default(),
+            )
+            .with(
+                &clusters
+                    .map(|clusters| clusters.z_far())
+                    .unwrap_or_default(),
+            );
+        let cluster_block = render_context
+            .uniform_memory_allocator
+            .allocate(cluster_data);
+
+        let lights_2d_data = StaticUniformBuffer::<256>::new().with(
+            &(render_context
+                .lights_2d
+                .map(|lights| lights.light_count())
+                .unwrap_or_default() as i32),
+        );
+        let lights_2d_block = render_context
+            .uniform_memory_allocator
+            .allocate(lights_2d_data);
+
         GlobalUniformData {
             camera_block,
             lights_block,
             graphics_settings_block,
+            fog_block,
+            cluster_block,
+            lights_2d_block,
         }
     }
 
@@ -1062,8 +1465,11 @@ impl RenderDataBundleStorage {
             if !bundle_filter(bundle) {
                 continue;
             }
-            bundle_uniform_data_set
-                .push(bundle.write_uniforms(&view_projection, &mut render_context));
+            bundle_uniform_data_set.push(bundle.write_uniforms(
+                &view_projection,
+                &mut render_context,
+                &mut instance_filter,
+            ));
         }
         render_context.uniform_memory_allocator.upload(server)?;
 
@@ -1079,7 +1485,6 @@ impl RenderDataBundleStorage {
                     server,
                     geometry_cache,
                     shader_cache,
-                    &mut instance_filter,
                     &mut render_context,
                     bundle_uniform_data,
                     &global_uniforms,
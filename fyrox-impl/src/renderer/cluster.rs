@@ -0,0 +1,389 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Clustered light culling. The view frustum is split into a grid of clusters (froxels): the
+//! screen is split into tiles and the view depth is split into exponentially distributed slices.
+//! Every frame the point and spot lights are binned into the clusters they intersect on CPU, and
+//! the result is uploaded into two textures: the first one contains the parameters of the lights,
+//! the second one contains the light index list of every cluster. Shaders find the cluster of a
+//! fragment and iterate only over the lights affecting it (see `S_FetchClusteredLight` and
+//! `S_ClusterLightRange` shader functions), so hundreds of small lights can be shaded in one pass.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Point3, Vector3, Vector4},
+        array_as_u8_slice,
+        math::aabb::AxisAlignedBoundingBox,
+    },
+    renderer::{
+        bundle::{LightSource, LightSourceKind},
+        framework::{
+            error::FrameworkError,
+            gpu_texture::{GpuTexture, GpuTextureDescriptor, GpuTextureKind, PixelKind},
+            server::GraphicsServer,
+        },
+    },
+};
+
+/// Amount of clusters along X axis of the screen. Must match `S_CLUSTER_GRID_WIDTH` in shaders.
+pub const CLUSTER_GRID_WIDTH: usize = 16;
+/// Amount of clusters along Y axis of the screen. Must match `S_CLUSTER_GRID_HEIGHT` in shaders.
+pub const CLUSTER_GRID_HEIGHT: usize = 9;
+/// Amount of cluster slices along the view direction. Must match `S_CLUSTER_GRID_DEPTH` in shaders.
+pub const CLUSTER_GRID_DEPTH: usize = 24;
+/// Width of the textures with cluster data, they're addressed linearly. Must match
+/// `S_CLUSTER_DATA_WIDTH` in shaders.
+pub const CLUSTER_DATA_WIDTH: usize = 1024;
+/// Maximum amount of lights that could affect a single cluster, the rest is ignored.
+pub const MAX_LIGHTS_PER_CLUSTER: usize = 255;
+/// Maximum amount of lights that could be binned in the clusters, the rest is ignored.
+pub const MAX_CLUSTERED_LIGHTS: usize = 16384;
+/// Amount of texels used to store parameters of a single light.
+const LIGHT_TEXELS: usize = 4;
+const CLUSTER_COUNT: usize = CLUSTER_GRID_WIDTH * CLUSTER_GRID_HEIGHT * CLUSTER_GRID_DEPTH;
+
+/// Returns the radius of the sphere that bounds the given light, or [`None`] if the light cannot
+/// be clustered (directional lights, for example).
+pub fn clustered_light_radius(light: &LightSource) -> Option<f32> {
+    let radius = match light.kind {
+        LightSourceKind::Spot { distance, .. } => distance,
+        LightSourceKind::Point { radius, .. } => radius,
+        LightSourceKind::Directional { .. } | LightSourceKind::Unknown => return None,
+    };
+    let scale = light.local_scale;
+    Some(scale.x.max(scale.y).max(scale.z) * radius)
+}
+
+/// Point and spot lights binned into the clusters of the view frustum of a camera.
+pub struct LightClusters {
+    lights_texture: GpuTexture,
+    clusters_texture: GpuTexture,
+    lights: Vec<Vector4<f32>>,
+    clusters: Vec<u32>,
+    cluster_lights: Vec<Vec<u32>>,
+    cluster_bounds: Vec<AxisAlignedBoundingBox>,
+    light_count: usize,
+    view_matrix: Matrix4<f32>,
+    view_projection_matrix: Matrix4<f32>,
+    z_near: f32,
+    z_far: f32,
+}
+
+impl LightClusters {
+    /// Creates empty light clusters.
+    pub fn new(server: &dyn GraphicsServer) -> Result<Self, FrameworkError> {
+        let empty_clusters = vec![0u32; CLUSTER_DATA_WIDTH];
+        let empty_lights = vec![Vector4::<f32>::default(); LIGHT_TEXELS];
+        Ok(Self {
+            lights_texture: server.create_texture(GpuTextureDescriptor {
+                kind: GpuTextureKind::Rectangle {
+                    width: LIGHT_TEXELS,
+                    height: 1,
+                },
+                pixel_kind: PixelKind::RGBA32F,
+                data: Some(array_as_u8_slice(&empty_lights)),
+                ..Default::default()
+            })?,
+            clusters_texture: server.create_texture(GpuTextureDescriptor {
+                kind: GpuTextureKind::Rectangle {
+                    width: CLUSTER_DATA_WIDTH,
+                    height: 1,
+                },
+                pixel_kind: PixelKind::R32UI,
+                data: Some(array_as_u8_slice(&empty_clusters)),
+                ..Default::default()
+            })?,
+            lights: Default::default(),
+            clusters: Default::default(),
+            cluster_lights: vec![Vec::new(); CLUSTER_COUNT],
+            cluster_bounds: Vec::with_capacity(CLUSTER_COUNT),
+            light_count: 0,
+            view_matrix: Matrix4::identity(),
+            view_projection_matrix: Matrix4::identity(),
+            z_near: 0.025,
+            z_far: 2048.0,
+        })
+    }
+
+    /// Texture with the parameters of the clustered lights, every light takes four RGBA32F texels:
+    ///
+    /// - position (xyz) and radius (w),
+    /// - linear color multiplied by the intensity (xyz) and kind (w, 0 - point, 1 - spot),
+    /// - emit direction (xyz) and cosine of the half of the hotspot cone angle (w),
+    /// - cosine of the half of the cone angle (x) and whether the light must be skipped by the
+    ///   clustered deferred pass (y, because it is shaded by its own pass with shadows and cookies).
+    pub fn lights_texture(&self) -> &GpuTexture {
+        &self.lights_texture
+    }
+
+    /// R32UI texture, where the first texels store packed offset (upper 24 bits) and light count
+    /// (lower 8 bits) of every cluster. The offset points to the list of light indices stored in
+    /// the same texture.
+    pub fn clusters_texture(&self) -> &GpuTexture {
+        &self.clusters_texture
+    }
+
+    /// Amount of clustered lights.
+    pub fn light_count(&self) -> usize {
+        self.light_count
+    }
+
+    /// View matrix of the camera, for which the clusters were built.
+    pub fn view_matrix(&self) -> &Matrix4<f32> {
+        &self.view_matrix
+    }
+
+    /// View-projection matrix of the camera, for which the clusters were built.
+    pub fn view_projection_matrix(&self) -> &Matrix4<f32> {
+        &self.view_projection_matrix
+    }
+
+    /// Distance to the near boundary of the first slice of the clusters.
+    pub fn z_near(&self) -> f32 {
+        self.z_near
+    }
+
+    /// Distance to the far boundary of the last slice of the clusters.
+    pub fn z_far(&self) -> f32 {
+        self.z_far
+    }
+
+    /// Returns the index of the slice, that contains the given view depth.
+    fn slice(&self, depth: f32) -> usize {
+        let k = (depth / self.z_near).ln() / (self.z_far / self.z_near).ln();
+        ((k * CLUSTER_GRID_DEPTH as f32).max(0.0) as usize).min(CLUSTER_GRID_DEPTH - 1)
+    }
+
+    /// Returns view depth of the near boundary of the given slice.
+    fn slice_depth(&self, slice: usize) -> f32 {
+        self.z_near * (self.z_far / self.z_near).powf(slice as f32 / CLUSTER_GRID_DEPTH as f32)
+    }
+
+    fn calculate_cluster_bounds(&mut self, projection_matrix: &Matrix4<f32>) {
+        let inv_projection = projection_matrix.try_inverse().unwrap_or_default();
+        let unproject = |x: f32, y: f32, z: f32| -> Vector3<f32> {
+            inv_projection.transform_point(&Point3::new(x, y, z)).coords
+        };
+
+        self.cluster_bounds.clear();
+        for z in 0..CLUSTER_GRID_DEPTH {
+            let slice_near = self.slice_depth(z);
+            let slice_far = self.slice_depth(z + 1);
+            for y in 0..CLUSTER_GRID_HEIGHT {
+                for x in 0..CLUSTER_GRID_WIDTH {
+                    let mut bounds = AxisAlignedBoundingBox::default();
+                    for (tx, ty) in [(x, y), (x + 1, y), (x, y + 1), (x + 1, y + 1)] {
+                        let ndc_x = 2.0 * tx as f32 / CLUSTER_GRID_WIDTH as f32 - 1.0;
+                        let ndc_y = 2.0 * ty as f32 / CLUSTER_GRID_HEIGHT as f32 - 1.0;
+                        // View-space points of the ray of the corner are linear in view depth.
+                        let near = unproject(ndc_x, ndc_y, -1.0);
+                        let far = unproject(ndc_x, ndc_y, 1.0);
+                        let ray_depth = (-far.z) - (-near.z);
+                        for depth in [slice_near, slice_far] {
+                            let t = if ray_depth.abs() > f32::EPSILON {
+                                (depth + near.z) / ray_depth
+                            } else {
+                                0.0
+                            };
+                            bounds.add_point(near.lerp(&far, t));
+                        }
+                    }
+                    self.cluster_bounds.push(bounds);
+                }
+            }
+        }
+    }
+
+    /// Bins the given lights into the clusters of the given camera and uploads the result to GPU.
+    /// Every light is paired with a flag, that tells whether the light is shaded by its own
+    /// deferred pass or not. Returns the amount of visible lights, that are not shaded separately.
+    pub fn update<'a>(
+        &mut self,
+        view_matrix: Matrix4<f32>,
+        projection_matrix: Matrix4<f32>,
+        z_near: f32,
+        z_far: f32,
+        lights: impl Iterator<Item = (&'a LightSource, bool)>,
+    ) -> Result<usize, FrameworkError> {
+        self.view_matrix = view_matrix;
+        self.view_projection_matrix = projection_matrix * view_matrix;
+        self.z_near = z_near.max(0.01);
+        self.z_far = z_far.max(self.z_near + 0.01);
+        self.calculate_cluster_bounds(&projection_matrix);
+
+        for cluster in self.cluster_lights.iter_mut() {
+            cluster.clear();
+        }
+        self.lights.clear();
+        self.light_count = 0;
+        let mut shaded_count = 0;
+
+        for (light, shaded_separately) in lights {
+            if self.light_count >= MAX_CLUSTERED_LIGHTS {
+                break;
+            }
+            let Some(radius) = clustered_light_radius(light) else {
+                continue;
+            };
+
+            let center = view_matrix
+                .transform_point(&Point3::from(light.position))
+                .coords;
+            let depth = -center.z;
+            if depth + radius < self.z_near || depth - radius > self.z_far {
+                continue;
+            }
+
+            // Find screen-space bounds of the light by projecting its bounding box, the light
+            // covers the entire screen if the camera is inside of the box.
+            let (mut min_x, mut min_y) = (0, 0);
+            let (mut max_x, mut max_y) = (CLUSTER_GRID_WIDTH - 1, CLUSTER_GRID_HEIGHT - 1);
+            let bounds = AxisAlignedBoundingBox::from_min_max(
+                center - Vector3::repeat(radius),
+                center + Vector3::repeat(radius),
+            );
+            let corners = bounds.corners();
+            if corners.iter().all(|corner| corner.z < -self.z_near * 0.5) {
+                let (mut ndc_min_x, mut ndc_min_y) = (f32::MAX, f32::MAX);
+                let (mut ndc_max_x, mut ndc_max_y) = (f32::MIN, f32::MIN);
+                for corner in corners {
+                    let clip = projection_matrix * corner.push(1.0);
+                    let ndc = clip.xy() / clip.w;
+                    ndc_min_x = ndc_min_x.min(ndc.x);
+                    ndc_min_y = ndc_min_y.min(ndc.y);
+                    ndc_max_x = ndc_max_x.max(ndc.x);
+                    ndc_max_y = ndc_max_y.max(ndc.y);
+                }
+                if ndc_max_x < -1.0 || ndc_min_x > 1.0 || ndc_max_y < -1.0 || ndc_min_y > 1.0 {
+                    continue;
+                }
+                let tile = |ndc: f32, count: usize| -> usize {
+                    ((((ndc * 0.5 + 0.5) * count as f32).max(0.0)) as usize).min(count - 1)
+                };
+                min_x = tile(ndc_min_x, CLUSTER_GRID_WIDTH);
+                max_x = tile(ndc_max_x, CLUSTER_GRID_WIDTH);
+                min_y = tile(ndc_min_y, CLUSTER_GRID_HEIGHT);
+                max_y = tile(ndc_max_y, CLUSTER_GRID_HEIGHT);
+            }
+            let min_z = self.slice(depth - radius);
+            let max_z = self.slice(depth + radius);
+
+            let light_index = self.light_count as u32;
+            let mut visible = false;
+            for z in min_z..=max_z {
+                for y in min_y..=max_y {
+                    for x in min_x..=max_x {
+                        let cluster_index = x + CLUSTER_GRID_WIDTH * (y + CLUSTER_GRID_HEIGHT * z);
+                        if !self.cluster_bounds[cluster_index].is_intersects_sphere(center, radius)
+                        {
+                            continue;
+                        }
+                        let cluster = &mut self.cluster_lights[cluster_index];
+                        if cluster.len() < MAX_LIGHTS_PER_CLUSTER {
+                            cluster.push(light_index);
+                            visible = true;
+                        }
+                    }
+                }
+            }
+            if !visible {
+                continue;
+            }
+
+            let color = light.color.srgb_to_linear_f32().xyz() * light.intensity;
+            let direction = light
+                .up_vector
+                .try_normalize(f32::EPSILON)
+                .unwrap_or_else(Vector3::z);
+            let (kind, half_hotspot_cone_angle_cos, half_cone_angle_cos) = match light.kind {
+                LightSourceKind::Spot {
+                    full_cone_angle,
+                    hotspot_cone_angle,
+                    ..
+                } => (
+                    1.0,
+                    (hotspot_cone_angle * 0.5).cos(),
+                    (full_cone_angle * 0.5).cos(),
+                ),
+                _ => (0.0, -1.0, -1.0),
+            };
+            self.lights.extend([
+                light.position.push(radius),
+                color.push(kind),
+                direction.push(half_hotspot_cone_angle_cos),
+                Vector4::new(
+                    half_cone_angle_cos,
+                    if shaded_separately { 1.0 } else { 0.0 },
+                    0.0,
+                    0.0,
+                ),
+            ]);
+            self.light_count += 1;
+            if !shaded_separately {
+                shaded_count += 1;
+            }
+        }
+
+        self.upload()?;
+
+        Ok(shaded_count)
+    }
+
+    fn upload(&mut self) -> Result<(), FrameworkError> {
+        self.clusters.clear();
+        self.clusters.resize(CLUSTER_COUNT, 0);
+        for (cluster_index, cluster) in self.cluster_lights.iter().enumerate() {
+            let offset = self.clusters.len() as u32;
+            self.clusters[cluster_index] = (offset << 8) | cluster.len() as u32;
+            self.clusters.extend_from_slice(cluster);
+        }
+        let clusters_height = self.clusters.len().div_ceil(CLUSTER_DATA_WIDTH);
+        self.clusters
+            .resize(clusters_height * CLUSTER_DATA_WIDTH, 0);
+        self.clusters_texture.set_data(
+            GpuTextureKind::Rectangle {
+                width: CLUSTER_DATA_WIDTH,
+                height: clusters_height,
+            },
+            PixelKind::R32UI,
+            1,
+            Some(array_as_u8_slice(&self.clusters)),
+        )?;
+
+        if self.lights.is_empty() {
+            self.lights.resize(LIGHT_TEXELS, Vector4::default());
+        }
+        let lights_width = self.lights.len().min(CLUSTER_DATA_WIDTH);
+        let lights_height = self.lights.len().div_ceil(lights_width);
+        self.lights
+            .resize(lights_width * lights_height, Vector4::default());
+        self.lights_texture.set_data(
+            GpuTextureKind::Rectangle {
+                width: lights_width,
+                height: lights_height,
+            },
+            PixelKind::RGBA32F,
+            1,
+            Some(array_as_u8_slice(&self.lights)),
+        )?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,365 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Scene fog. The regular fog is applied to the lit opaque geometry by a full-screen pass, while
+//! transparent objects apply it by themselves in their forward passes (see `fyrox_fogData`
+//! built-in property group), so the both paths produce the same result.
+//!
+//! Volumetric fog is computed in a frustum-aligned voxel grid (froxels). At first, the light
+//! in-scattered in every froxel is computed (the first directional light of the scene with its
+//! cascaded shadow maps plus the ambient light), then it is integrated along the view rays. The
+//! result is sampled by the full-screen pass and by the forward passes. Froxels are stored in 2D
+//! atlases, because the renderer can't render into 3D textures.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Vector3},
+        math::Rect,
+        sstorage::ImmutableString,
+    },
+    renderer::{
+        bundle::FogRenderData,
+        cache::{
+            shader::{binding, property, PropertyGroup, RenderMaterial, RenderPassContainer},
+            uniform::UniformBufferCache,
+        },
+        framework::{
+            error::FrameworkError,
+            framebuffer::{Attachment, AttachmentKind, GpuFrameBuffer},
+            geometry_buffer::GpuGeometryBuffer,
+            gpu_texture::{GpuTexture, PixelKind},
+            server::GraphicsServer,
+        },
+        make_viewport_matrix,
+        shadow::csm::CsmRenderer,
+        FallbackResources, RenderPassStatistics,
+    },
+    scene::{camera::Camera, fog::VolumetricFog},
+};
+
+/// Amount of froxels along X axis of the screen. Must match `S_FROXEL_GRID_WIDTH` in shaders.
+pub const FROXEL_GRID_WIDTH: usize = 128;
+/// Amount of froxels along Y axis of the screen. Must match `S_FROXEL_GRID_HEIGHT` in shaders.
+pub const FROXEL_GRID_HEIGHT: usize = 72;
+/// Amount of froxel slices along the view direction. Must match `S_FROXEL_GRID_DEPTH` in shaders.
+pub const FROXEL_GRID_DEPTH: usize = 64;
+/// Amount of slices in a row of the atlas. Must match `S_FROXEL_ATLAS_COLUMNS` in shaders.
+pub const FROXEL_ATLAS_COLUMNS: usize = 8;
+
+/// Directional light, that lits the volumetric fog.
+pub(crate) struct VolumetricFogLight<'a> {
+    /// Direction to the light source.
+    pub direction: Vector3<f32>,
+    /// Linear color of the light multiplied by its intensity.
+    pub color: Vector3<f32>,
+    /// Cascaded shadow maps of the light with the shadow bias. [`None`] if the light does not cast
+    /// shadows.
+    pub shadows: Option<(&'a CsmRenderer, f32)>,
+}
+
+pub(crate) struct VolumetricFogScatteringContext<'a> {
+    pub quad: &'a GpuGeometryBuffer,
+    pub camera: &'a Camera,
+    pub settings: &'a VolumetricFog,
+    /// Linear ambient lighting color of the scene.
+    pub ambient_color: Vector3<f32>,
+    pub light: Option<VolumetricFogLight<'a>>,
+    pub uniform_buffer_cache: &'a mut UniformBufferCache,
+    pub fallback_resources: &'a FallbackResources,
+}
+
+pub(crate) struct FogRenderContext<'a> {
+    pub quad: &'a GpuGeometryBuffer,
+    pub viewport: Rect<i32>,
+    pub camera: &'a Camera,
+    /// Projection matrix that was used to fill the G-Buffer (it is jittered when TAA is enabled).
+    pub projection_matrix: Matrix4<f32>,
+    pub depth_texture: &'a GpuTexture,
+    /// Frame buffer with the lit scene, the fog is blended on top of it.
+    pub frame_buffer: &'a GpuFrameBuffer,
+    pub fog: FogRenderData<'a>,
+    pub uniform_buffer_cache: &'a mut UniformBufferCache,
+    pub fallback_resources: &'a FallbackResources,
+}
+
+pub struct FogRenderer {
+    shader: RenderPassContainer,
+    scattering_shader: RenderPassContainer,
+    integration_shader: RenderPassContainer,
+    scattering: GpuFrameBuffer,
+    volume: GpuFrameBuffer,
+}
+
+fn make_froxel_atlas(server: &dyn GraphicsServer) -> Result<GpuFrameBuffer, FrameworkError> {
+    let (width, height) = froxel_atlas_size();
+    server.create_frame_buffer(
+        None,
+        vec![Attachment {
+            kind: AttachmentKind::Color,
+            texture: server.create_2d_render_target(PixelKind::RGBA16F, width, height)?,
+        }],
+    )
+}
+
+fn froxel_atlas_size() -> (usize, usize) {
+    (
+        FROXEL_GRID_WIDTH * FROXEL_ATLAS_COLUMNS,
+        FROXEL_GRID_HEIGHT * FROXEL_GRID_DEPTH / FROXEL_ATLAS_COLUMNS,
+    )
+}
+
+fn froxel_atlas_viewport() -> Rect<i32> {
+    let (width, height) = froxel_atlas_size();
+    Rect::new(0, 0, width as i32, height as i32)
+}
+
+impl FogRenderer {
+    pub fn new(server: &dyn GraphicsServer) -> Result<Self, FrameworkError> {
+        Ok(Self {
+            shader: RenderPassContainer::from_str(server, include_str!("shaders/fog.shader"))?,
+            scattering_shader: RenderPassContainer::from_str(
+                server,
+                include_str!("shaders/volumetric_fog_scattering.shader"),
+            )?,
+            integration_shader: RenderPassContainer::from_str(
+                server,
+                include_str!("shaders/volumetric_fog_integration.shader"),
+            )?,
+            scattering: make_froxel_atlas(server)?,
+            volume: make_froxel_atlas(server)?,
+        })
+    }
+
+    /// Returns integrated volumetric fog: in-scattered light (rgb) and transmittance (a) from the
+    /// observer to the far boundary of each froxel.
+    pub fn volume(&self) -> &GpuTexture {
+        &self.volume.color_attachments()[0].texture
+    }
+
+    /// Computes light in-scattered in every froxel of the volumetric fog.
+    pub(crate) fn render_scattering(
+        &self,
+        ctx: VolumetricFogScatteringContext,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        let VolumetricFogScatteringContext {
+            quad,
+            camera,
+            settings,
+            ambient_color,
+            light,
+            uniform_buffer_cache,
+            fallback_resources,
+        } = ctx;
+
+        let viewport = froxel_atlas_viewport();
+        let frame_matrix = make_viewport_matrix(viewport);
+        let inv_view_projection = camera
+            .view_projection_matrix()
+            .try_inverse()
+            .unwrap_or_default();
+        let view_matrix = camera.view_matrix();
+        let camera_position = camera.global_position();
+        let albedo = settings.albedo.srgb_to_linear_f32().xyz();
+        let light_enabled = light.is_some();
+        let (light_direction, light_color) = light
+            .as_ref()
+            .map(|light| (light.direction, light.color))
+            .unwrap_or_default();
+        let shadows = light.as_ref().and_then(|light| light.shadows);
+        let shadows_enabled = shadows.is_some();
+        let shadow_bias = shadows.map(|(_, bias)| bias).unwrap_or_default();
+        let cascades = shadows.map(|(csm, _)| csm.cascades());
+        let distances = cascades.map_or([0.0; 3], |cascades| {
+            [cascades[0].z_far, cascades[1].z_far, cascades[2].z_far]
+        });
+        let matrices = cascades.map_or([Matrix4::identity(); 3], |cascades| {
+            [
+                cascades[0].view_proj_matrix,
+                cascades[1].view_proj_matrix,
+                cascades[2].view_proj_matrix,
+            ]
+        });
+        let cascade_texture = |index: usize| {
+            (
+                cascades.map_or(&fallback_resources.white_dummy, |cascades| {
+                    cascades[index].texture()
+                }),
+                &fallback_resources.nearest_clamp_sampler,
+            )
+        };
+
+        let properties = PropertyGroup::from([
+            property("worldViewProjection", &frame_matrix),
+            property("invViewProj", &inv_view_projection),
+            property("viewMatrix", &view_matrix),
+            property("cameraPosition", &camera_position),
+            property("maxDistance", &settings.distance),
+            property("density", &settings.density),
+            property("albedo", &albedo),
+            property("anisotropy", &settings.anisotropy),
+            property("ambientColor", &ambient_color),
+            property("lightEnabled", &light_enabled),
+            property("lightDirection", &light_direction),
+            property("lightColor", &light_color),
+            property("shadowsEnabled", &shadows_enabled),
+            property("shadowBias", &shadow_bias),
+            property("lightViewProjMatrices", matrices.as_slice()),
+            property("cascadeDistances", distances.as_slice()),
+        ]);
+        let material = RenderMaterial::from([
+            binding("shadowCascade0", cascade_texture(0)),
+            binding("shadowCascade1", cascade_texture(1)),
+            binding("shadowCascade2", cascade_texture(2)),
+            binding("properties", &properties),
+        ]);
+
+        let mut stats = RenderPassStatistics::default();
+        stats += self.scattering_shader.run_pass(
+            1,
+            &ImmutableString::new("Primary"),
+            &self.scattering,
+            quad,
+            viewport,
+            &material,
+            uniform_buffer_cache,
+            Default::default(),
+            None,
+        )?;
+        Ok(stats)
+    }
+
+    /// Integrates in-scattered light along the view rays.
+    pub(crate) fn integrate(
+        &self,
+        quad: &GpuGeometryBuffer,
+        settings: &VolumetricFog,
+        uniform_buffer_cache: &mut UniformBufferCache,
+        fallback_resources: &FallbackResources,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        let viewport = froxel_atlas_viewport();
+        let frame_matrix = make_viewport_matrix(viewport);
+        let properties = PropertyGroup::from([
+            property("worldViewProjection", &frame_matrix),
+            property("maxDistance", &settings.distance),
+        ]);
+        let material = RenderMaterial::from([
+            binding(
+                "scatteringTexture",
+                (
+                    &self.scattering.color_attachments()[0].texture,
+                    &fallback_resources.nearest_clamp_sampler,
+                ),
+            ),
+            binding("properties", &properties),
+        ]);
+
+        let mut stats = RenderPassStatistics::default();
+        stats += self.integration_shader.run_pass(
+            1,
+            &ImmutableString::new("Primary"),
+            &self.volume,
+            quad,
+            viewport,
+            &material,
+            uniform_buffer_cache,
+            Default::default(),
+            None,
+        )?;
+        Ok(stats)
+    }
+
+    /// Blends the fog with the lit opaque geometry of the frame.
+    pub(crate) fn render(
+        &self,
+        ctx: FogRenderContext,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        let FogRenderContext {
+            quad,
+            viewport,
+            camera,
+            projection_matrix,
+            depth_texture,
+            frame_buffer,
+            fog,
+            uniform_buffer_cache,
+            fallback_resources,
+        } = ctx;
+
+        let frame_matrix = make_viewport_matrix(viewport);
+        let inv_view_projection = (projection_matrix * camera.view_matrix())
+            .try_inverse()
+            .unwrap_or_default();
+        let camera_position = camera.global_position();
+        let (mode, params) = fog.mode_and_params();
+        let color = fog.color();
+        let use_environment = fog.environment.is_some();
+        let environment_lod = fog.environment_lod();
+        let volumetric = fog.volume.is_some();
+        let properties = PropertyGroup::from([
+            property("worldViewProjection", &frame_matrix),
+            property("invViewProj", &inv_view_projection),
+            property("cameraPosition", &camera_position),
+            property("mode", &mode),
+            property("color", &color),
+            property("params", &params),
+            property("useEnvironment", &use_environment),
+            property("environmentLod", &environment_lod),
+            property("volumetric", &volumetric),
+            property("volumetricDistance", &fog.fog.volumetric.distance),
+        ]);
+        let material = RenderMaterial::from([
+            binding(
+                "depthTexture",
+                (depth_texture, &fallback_resources.nearest_clamp_sampler),
+            ),
+            binding(
+                "environmentTexture",
+                fog.environment.map_or(
+                    (
+                        &fallback_resources.environment_dummy,
+                        &fallback_resources.linear_clamp_sampler,
+                    ),
+                    |(texture, sampler, _)| (texture, sampler),
+                ),
+            ),
+            binding(
+                "volumeTexture",
+                (
+                    fog.volume.unwrap_or(&fallback_resources.black_dummy),
+                    &fallback_resources.linear_clamp_sampler,
+                ),
+            ),
+            binding("properties", &properties),
+        ]);
+
+        let mut stats = RenderPassStatistics::default();
+        stats += self.shader.run_pass(
+            1,
+            &ImmutableString::new("Primary"),
+            frame_buffer,
+            quad,
+            viewport,
+            &material,
+            uniform_buffer_cache,
+            Default::default(),
+            None,
+        )?;
+        Ok(stats)
+    }
+}
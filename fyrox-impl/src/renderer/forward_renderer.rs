@@ -23,9 +23,11 @@
 use crate::{
     core::{color::Color, math::Rect, sstorage::ImmutableString},
     renderer::{
-        bundle::{BundleRenderContext, RenderDataBundleStorage},
+        bundle::{BundleRenderContext, FogRenderData, RenderDataBundleStorage},
         cache::{shader::ShaderCache, texture::TextureCache, uniform::UniformMemoryAllocator},
+        cluster::LightClusters,
         framework::{error::FrameworkError, server::GraphicsServer},
+        light_2d::Lights2D,
         FallbackResources, GeometryCache, QualitySettings, RenderPassStatistics,
     },
     scene::mesh::RenderPath,
@@ -35,6 +37,7 @@ use fyrox_graphics::gpu_texture::GpuTexture;
 
 pub(crate) struct ForwardRenderer {
     render_pass_name: ImmutableString,
+    lights_2d: Lights2D,
 }
 
 pub(crate) struct ForwardRenderContext<'a> {
@@ -49,18 +52,26 @@ pub(crate) struct ForwardRenderContext<'a> {
     pub fallback_resources: &'a FallbackResources,
     pub scene_depth: &'a GpuTexture,
     pub ambient_light: Color,
+    pub fog: Option<FogRenderData<'a>>,
+    pub light_clusters: Option<&'a LightClusters>,
     pub uniform_memory_allocator: &'a mut UniformMemoryAllocator,
 }
 
 impl ForwardRenderer {
-    pub(crate) fn new() -> Self {
-        Self {
+    pub(crate) fn new(server: &dyn GraphicsServer) -> Result<Self, FrameworkError> {
+        Ok(Self {
             render_pass_name: ImmutableString::new("Forward"),
-        }
+            lights_2d: Lights2D::new(server)?,
+        })
+    }
+
+    #[cfg(test)]
+    pub(crate) fn lights_2d(&self) -> &Lights2D {
+        &self.lights_2d
     }
 
     pub(crate) fn render(
-        &self,
+        &mut self,
         args: ForwardRenderContext,
     ) -> Result<RenderPassStatistics, FrameworkError> {
         let mut statistics = RenderPassStatistics::default();
@@ -77,9 +88,16 @@ impl ForwardRenderer {
             fallback_resources,
             scene_depth,
             ambient_light,
+            fog,
+            light_clusters,
             uniform_memory_allocator,
         } = args;
 
+        self.lights_2d.update(
+            &bundle_storage.light_sources_2d,
+            &bundle_storage.occluder_edges_2d,
+        )?;
+
         statistics += bundle_storage.render_to_frame_buffer(
             state,
             geom_cache,
@@ -97,6 +115,9 @@ impl ForwardRenderer {
                 fallback_resources,
                 ambient_light,
                 scene_depth: Some(scene_depth),
+                fog,
+                light_clusters,
+                lights_2d: Some(&self.lights_2d),
             },
         )?;
 
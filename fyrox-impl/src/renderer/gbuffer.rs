@@ -34,6 +34,7 @@ use crate::{
         algebra::{Matrix4, Vector2},
         color::Color,
         math::Rect,
+        pool::Handle,
         sstorage::ImmutableString,
     },
     renderer::{
@@ -54,14 +55,18 @@ use crate::{
             server::GraphicsServer,
             GeometryBufferExt,
         },
-        occlusion::OcclusionTester,
-        FallbackResources, GeometryCache, QualitySettings, RenderPassStatistics, TextureCache,
+        occlusion::{HiZOcclusionTester, OcclusionTester},
+        stats::OcclusionStatistics,
+        velocity::VelocityBufferRenderer,
+        FallbackResources, GeometryCache, OcclusionCullingMode, QualitySettings,
+        RenderPassStatistics, TextureCache,
     },
     scene::{
         camera::Camera,
         decal::Decal,
         graph::Graph,
         mesh::{surface::SurfaceData, RenderPath},
+        node::Node,
     },
 };
 use fxhash::FxHashSet;
@@ -75,10 +80,13 @@ pub struct GBuffer {
     decal_shader: RenderPassContainer,
     render_pass_name: ImmutableString,
     occlusion_tester: OcclusionTester,
+    hiz_occlusion_tester: HiZOcclusionTester,
+    velocity_renderer: VelocityBufferRenderer,
 }
 
 pub(crate) struct GBufferRenderContext<'a, 'b> {
     pub server: &'a dyn GraphicsServer,
+    pub camera_handle: Handle<Node>,
     pub camera: &'b Camera,
     pub geom_cache: &'a mut GeometryCache,
     pub bundle_storage: &'a RenderDataBundleStorage,
@@ -102,10 +110,11 @@ impl GBuffer {
     ) -> Result<Self, FrameworkError> {
         let diffuse_texture = server.create_2d_render_target(PixelKind::RGBA8, width, height)?;
         let normal_texture = server.create_2d_render_target(PixelKind::RGBA8, width, height)?;
+        let depth_stencil = server.create_2d_render_target(PixelKind::D24S8, width, height)?;
         let framebuffer = server.create_frame_buffer(
             Some(Attachment {
                 kind: AttachmentKind::DepthStencil,
-                texture: server.create_2d_render_target(PixelKind::D24S8, width, height)?,
+                texture: depth_stencil.clone(),
             }),
             vec![
                 Attachment {
@@ -161,6 +170,8 @@ impl GBuffer {
             decal_framebuffer,
             render_pass_name: ImmutableString::new("GBuffer"),
             occlusion_tester: OcclusionTester::new(server, width, height, 16)?,
+            hiz_occlusion_tester: HiZOcclusionTester::new(server, width, height)?,
+            velocity_renderer: VelocityBufferRenderer::new(server, depth_stencil, width, height)?,
         })
     }
 
@@ -192,14 +203,22 @@ impl GBuffer {
         &self.framebuffer.color_attachments()[4].texture
     }
 
+    /// Screen-space motion of the pixels (in texture coordinates) since the previous frame. The
+    /// content is valid only if temporal anti-aliasing is enabled.
+    pub fn velocity_texture(&self) -> &GpuTexture {
+        self.velocity_renderer.velocity_texture()
+    }
+
     pub(crate) fn fill(
         &mut self,
         args: GBufferRenderContext,
-    ) -> Result<RenderPassStatistics, FrameworkError> {
+    ) -> Result<(RenderPassStatistics, OcclusionStatistics), FrameworkError> {
         let mut statistics = RenderPassStatistics::default();
+        let mut occlusion_statistics = OcclusionStatistics::default();
 
         let GBufferRenderContext {
             server,
+            camera_handle,
             camera,
             geom_cache,
             bundle_storage,
@@ -214,12 +233,26 @@ impl GBuffer {
             ..
         } = args;
 
-        let view_projection = camera.view_projection_matrix();
-
-        if quality_settings.use_occlusion_culling {
-            self.occlusion_tester.try_query_visibility_results(graph);
+        let view_projection = if quality_settings.use_taa {
+            camera.jittered_view_projection_matrix()
+        } else {
+            camera.view_projection_matrix()
         };
 
+        let occlusion_culling_mode = quality_settings
+            .use_occlusion_culling
+            .then_some(quality_settings.occlusion_culling_mode);
+
+        match occlusion_culling_mode {
+            Some(OcclusionCullingMode::Tiled) => {
+                self.occlusion_tester.try_query_visibility_results(graph)
+            }
+            Some(OcclusionCullingMode::HierarchicalZ) => {
+                self.hiz_occlusion_tester.try_query_depth_pyramid()
+            }
+            None => (),
+        }
+
         let viewport = Rect::new(0, 0, self.width, self.height);
         self.framebuffer.clear(
             viewport,
@@ -228,15 +261,38 @@ impl GBuffer {
             Some(0),
         );
 
-        let grid_cell = self
-            .occlusion_tester
-            .grid_cache
-            .cell(camera.global_position());
+        let mut objects = FxHashSet::default();
+        let mut occluded_objects = FxHashSet::default();
+        if let Some(occlusion_culling_mode) = occlusion_culling_mode {
+            for bundle in bundle_storage.bundles.iter() {
+                for instance in bundle.instances.iter() {
+                    objects.insert(instance.node_handle);
+                }
+            }
 
-        let instance_filter = |instance: &SurfaceInstanceData| {
-            !quality_settings.use_occlusion_culling
-                || grid_cell.is_none_or(|cell| cell.is_visible(instance.node_handle))
-        };
+            let observer_position = camera.global_position();
+            let grid_cell = self.occlusion_tester.grid_cache.cell(observer_position);
+            for object in objects.iter() {
+                let is_visible = match occlusion_culling_mode {
+                    OcclusionCullingMode::Tiled => {
+                        grid_cell.is_none_or(|cell| cell.is_visible(*object))
+                    }
+                    OcclusionCullingMode::HierarchicalZ => {
+                        self.hiz_occlusion_tester
+                            .is_visible(graph, *object, observer_position)
+                    }
+                };
+                if !is_visible {
+                    occluded_objects.insert(*object);
+                }
+            }
+
+            occlusion_statistics.objects_tested += objects.len();
+            occlusion_statistics.objects_culled += occluded_objects.len();
+        }
+
+        let instance_filter =
+            |instance: &SurfaceInstanceData| !occluded_objects.contains(&instance.node_handle);
 
         statistics += bundle_storage.render_to_frame_buffer(
             server,
@@ -255,30 +311,54 @@ impl GBuffer {
                 fallback_resources,
                 ambient_light: Color::WHITE, // TODO
                 scene_depth: None,           // TODO. Add z-pre-pass.
+                fog: None,
+                light_clusters: None,
+                lights_2d: None,
             },
         )?;
 
-        if quality_settings.use_occlusion_culling {
-            let mut objects = FxHashSet::default();
-            for bundle in bundle_storage.bundles.iter() {
-                for instance in bundle.instances.iter() {
-                    objects.insert(instance.node_handle);
-                }
-            }
-
-            self.occlusion_tester.try_run_visibility_test(
+        // Both temporal anti-aliasing and motion blur need per-pixel velocity.
+        if quality_settings.use_taa || quality_settings.motion_blur_settings.enabled {
+            statistics += self.velocity_renderer.render(
+                server,
+                camera_handle,
+                camera,
                 graph,
-                None,
-                unit_quad,
-                objects.iter(),
-                &self.framebuffer,
-                camera.global_position(),
-                view_projection,
+                viewport,
+                bundle_storage,
+                geom_cache,
+                shader_cache,
+                &self.render_pass_name,
                 uniform_buffer_cache,
-                fallback_resources,
             )?;
         }
 
+        match occlusion_culling_mode {
+            Some(OcclusionCullingMode::Tiled) => {
+                statistics += self.occlusion_tester.try_run_visibility_test(
+                    graph,
+                    None,
+                    unit_quad,
+                    objects.iter(),
+                    &self.framebuffer,
+                    camera.global_position(),
+                    view_projection,
+                    uniform_buffer_cache,
+                    fallback_resources,
+                )?;
+            }
+            Some(OcclusionCullingMode::HierarchicalZ) => {
+                statistics += self.hiz_occlusion_tester.try_build_depth_pyramid(
+                    &self.framebuffer.depth_attachment().unwrap().texture,
+                    view_projection,
+                    unit_quad,
+                    uniform_buffer_cache,
+                    fallback_resources,
+                )?;
+            }
+            None => (),
+        }
+
         let inv_view_proj = view_projection.try_inverse().unwrap_or_default();
         let depth = self.depth();
         let decal_mask = self.decal_mask_texture();
@@ -355,6 +435,6 @@ impl GBuffer {
             )?;
         }
 
-        Ok(statistics)
+        Ok((statistics, occlusion_statistics))
     }
 }
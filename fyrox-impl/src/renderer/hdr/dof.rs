@@ -0,0 +1,242 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Depth of field. The radius of the circle of confusion of each pixel is calculated from the
+//! depth using the thin lens model of the camera (see [`DepthOfField`]). Then the blurred near
+//! and far fields are gathered separately using a disk-shaped (bokeh) kernel and composited on
+//! top of the sharp frame. The far field does not spread onto the sharp foreground, while the
+//! near field spreads over everything behind it.
+
+use crate::{
+    core::{algebra::Vector2, math::Rect, sstorage::ImmutableString},
+    renderer::{
+        cache::{
+            shader::{binding, property, PropertyGroup, RenderMaterial, RenderPassContainer},
+            uniform::UniformBufferCache,
+        },
+        framework::{
+            error::FrameworkError,
+            framebuffer::{Attachment, AttachmentKind, GpuFrameBuffer},
+            geometry_buffer::GpuGeometryBuffer,
+            gpu_texture::{GpuTexture, PixelKind},
+            server::GraphicsServer,
+        },
+        make_viewport_matrix, FallbackResources, RenderPassStatistics,
+    },
+    scene::camera::{Camera, DepthOfField},
+};
+
+pub struct DepthOfFieldRenderer {
+    coc_shader: RenderPassContainer,
+    gather_shader: RenderPassContainer,
+    composite_shader: RenderPassContainer,
+    /// Color of the frame with the signed radius of the circle of confusion in the alpha channel.
+    coc: GpuFrameBuffer,
+    /// Blurred far field (first attachment) and near field with its coverage (second attachment).
+    fields: GpuFrameBuffer,
+    result: GpuFrameBuffer,
+    width: usize,
+    height: usize,
+}
+
+pub(crate) struct DepthOfFieldRenderContext<'a> {
+    pub quad: &'a GpuGeometryBuffer,
+    pub viewport: Rect<i32>,
+    pub camera: &'a Camera,
+    pub frame_texture: &'a GpuTexture,
+    pub depth_texture: &'a GpuTexture,
+    pub uniform_buffer_cache: &'a mut UniformBufferCache,
+    pub fallback_resources: &'a FallbackResources,
+}
+
+impl DepthOfFieldRenderer {
+    pub fn new(
+        server: &dyn GraphicsServer,
+        width: usize,
+        height: usize,
+    ) -> Result<Self, FrameworkError> {
+        let make_attachment = || -> Result<Attachment, FrameworkError> {
+            Ok(Attachment {
+                kind: AttachmentKind::Color,
+                texture: server.create_2d_render_target(PixelKind::RGBA16F, width, height)?,
+            })
+        };
+        Ok(Self {
+            coc_shader: RenderPassContainer::from_str(
+                server,
+                include_str!("../shaders/dof_coc.shader"),
+            )?,
+            gather_shader: RenderPassContainer::from_str(
+                server,
+                include_str!("../shaders/dof_gather.shader"),
+            )?,
+            composite_shader: RenderPassContainer::from_str(
+                server,
+                include_str!("../shaders/dof_composite.shader"),
+            )?,
+            coc: server.create_frame_buffer(None, vec![make_attachment()?])?,
+            fields: server
+                .create_frame_buffer(None, vec![make_attachment()?, make_attachment()?])?,
+            result: server.create_frame_buffer(None, vec![make_attachment()?])?,
+            width,
+            height,
+        })
+    }
+
+    /// Returns the frame with depth of field applied. The content is valid only if the depth of
+    /// field is enabled.
+    pub fn result(&self) -> &GpuTexture {
+        &self.result.color_attachments()[0].texture
+    }
+
+    pub(crate) fn render(
+        &self,
+        ctx: DepthOfFieldRenderContext,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        let DepthOfFieldRenderContext {
+            quad,
+            viewport,
+            camera,
+            frame_texture,
+            depth_texture,
+            uniform_buffer_cache,
+            fallback_resources,
+        } = ctx;
+
+        let mut stats = RenderPassStatistics::default();
+
+        let settings = camera.depth_of_field();
+        let frame_matrix = make_viewport_matrix(viewport);
+        let inverse_frame_size = Vector2::new(1.0 / self.width as f32, 1.0 / self.height as f32);
+        let viewport_origin = Vector2::new(viewport.x() as f32, viewport.y() as f32);
+        let viewport_size = Vector2::new(viewport.w() as f32, viewport.h() as f32);
+        let inverse_projection = camera.projection_matrix().try_inverse().unwrap_or_default();
+        // Radius of the circle of confusion (in pixels) at infinity.
+        let coc_scale = 0.5 * settings.coc_scale() / DepthOfField::SENSOR_HEIGHT * viewport_size.y;
+        let max_radius = settings.max_blur_radius;
+
+        let properties = PropertyGroup::from([
+            property("worldViewProjection", &frame_matrix),
+            property("inverseProjectionMatrix", &inverse_projection),
+            property("viewportOrigin", &viewport_origin),
+            property("viewportSize", &viewport_size),
+            property("inverseFrameSize", &inverse_frame_size),
+            property("focalDistance", &settings.focal_distance),
+            property("cocScale", &coc_scale),
+            property("maxRadius", &max_radius),
+        ]);
+        let material = RenderMaterial::from([
+            binding(
+                "frameTexture",
+                (frame_texture, &fallback_resources.nearest_clamp_sampler),
+            ),
+            binding(
+                "depthTexture",
+                (depth_texture, &fallback_resources.nearest_clamp_sampler),
+            ),
+            binding("properties", &properties),
+        ]);
+        stats += self.coc_shader.run_pass(
+            1,
+            &ImmutableString::new("Primary"),
+            &self.coc,
+            quad,
+            viewport,
+            &material,
+            uniform_buffer_cache,
+            Default::default(),
+            None,
+        )?;
+
+        // Keep the samples inside of the viewport, so the neighbouring viewports do not bleed in.
+        let viewport_min =
+            (viewport_origin + Vector2::repeat(0.5)).component_mul(&inverse_frame_size);
+        let viewport_max = (viewport_origin + viewport_size - Vector2::repeat(0.5))
+            .component_mul(&inverse_frame_size);
+        let coc_texture = &self.coc.color_attachments()[0].texture;
+        let properties = PropertyGroup::from([
+            property("worldViewProjection", &frame_matrix),
+            property("viewportMin", &viewport_min),
+            property("viewportMax", &viewport_max),
+            property("inverseFrameSize", &inverse_frame_size),
+            property("maxRadius", &max_radius),
+        ]);
+        let material = RenderMaterial::from([
+            binding(
+                "cocTexture",
+                (coc_texture, &fallback_resources.linear_clamp_sampler),
+            ),
+            binding("properties", &properties),
+        ]);
+        stats += self.gather_shader.run_pass(
+            1,
+            &ImmutableString::new("Primary"),
+            &self.fields,
+            quad,
+            viewport,
+            &material,
+            uniform_buffer_cache,
+            Default::default(),
+            None,
+        )?;
+
+        let properties = PropertyGroup::from([
+            property("worldViewProjection", &frame_matrix),
+            property("inverseFrameSize", &inverse_frame_size),
+        ]);
+        let material = RenderMaterial::from([
+            binding(
+                "frameTexture",
+                (frame_texture, &fallback_resources.nearest_clamp_sampler),
+            ),
+            binding(
+                "cocTexture",
+                (coc_texture, &fallback_resources.nearest_clamp_sampler),
+            ),
+            binding(
+                "farTexture",
+                (
+                    &self.fields.color_attachments()[0].texture,
+                    &fallback_resources.nearest_clamp_sampler,
+                ),
+            ),
+            binding(
+                "nearTexture",
+                (
+                    &self.fields.color_attachments()[1].texture,
+                    &fallback_resources.linear_clamp_sampler,
+                ),
+            ),
+            binding("properties", &properties),
+        ]);
+        stats += self.composite_shader.run_pass(
+            1,
+            &ImmutableString::new("Primary"),
+            &self.result,
+            quad,
+            viewport,
+            &material,
+            uniform_buffer_cache,
+            Default::default(),
+            None,
+        )?;
+
+        Ok(stats)
+    }
+}
@@ -47,6 +47,7 @@ use crate::{
 use fyrox_graphics::framebuffer::ReadTarget;
 
 mod adaptation;
+pub mod dof;
 mod luminance;
 
 #[allow(dead_code)] // TODO
@@ -314,6 +315,7 @@ impl HighDynamicRangeRenderer {
         server: &dyn GraphicsServer,
         hdr_scene_frame: &GpuTexture,
         bloom_texture: &GpuTexture,
+        bloom_intensity: f32,
         ldr_framebuffer: &GpuFrameBuffer,
         viewport: Rect<i32>,
         quad: &GpuGeometryBuffer,
@@ -352,6 +354,7 @@ impl HighDynamicRangeRenderer {
             property("maxLuminance", &max_luminance),
             property("autoExposure", &is_auto),
             property("fixedExposure", &fixed_exposure),
+            property("bloomIntensity", &bloom_intensity),
         ]);
         let material = RenderMaterial::from([
             binding(
@@ -391,6 +394,7 @@ impl HighDynamicRangeRenderer {
         server: &dyn GraphicsServer,
         hdr_scene_frame: &GpuTexture,
         bloom_texture: &GpuTexture,
+        bloom_intensity: f32,
         ldr_framebuffer: &GpuFrameBuffer,
         viewport: Rect<i32>,
         quad: &GpuGeometryBuffer,
@@ -416,6 +420,7 @@ impl HighDynamicRangeRenderer {
             server,
             hdr_scene_frame,
             bloom_texture,
+            bloom_intensity,
             ldr_framebuffer,
             viewport,
             quad,
@@ -0,0 +1,465 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Image-based lighting. Environment cube maps (the environment map of a camera or baked cube maps
+//! of reflection probes) are converted to a set of maps, that are used by the ambient lighting
+//! pass:
+//!
+//! - Irradiance map - a small cube map with cosine-weighted convolution of the environment, it is
+//! used for diffuse ambient lighting.
+//! - Prefiltered specular map - a cube map, where each mip level contains the environment convolved
+//! with the GGX distribution of increasing roughness (from zero at the first mip to one at the last).
+//! - BRDF lookup table - a 2D texture with scale (red) and bias (green) of the Fresnel reflectance
+//! for the split-sum approximation. It does not depend on the environment and is generated once.
+//!
+//! The maps are generated on demand and cached for every environment texture resource. They are
+//! regenerated when the data of the texture changes.
+
+use crate::{
+    core::{color::Color, math::Rect, sstorage::ImmutableString},
+    renderer::{
+        cache::{
+            shader::{binding, property, PropertyGroup, RenderMaterial, RenderPassContainer},
+            uniform::UniformBufferCache,
+            TimeToLive,
+        },
+        framework::{
+            buffer::BufferUsage,
+            error::FrameworkError,
+            framebuffer::{Attachment, AttachmentKind, GpuFrameBuffer},
+            geometry_buffer::GpuGeometryBuffer,
+            gpu_texture::{
+                CubeMapFace, GpuTexture, GpuTextureDescriptor, GpuTextureKind, PixelElementKind,
+                PixelKind,
+            },
+            sampler::{
+                GpuSampler, GpuSamplerDescriptor, MagnificationFilter, MinificationFilter, WrapMode,
+            },
+            server::GraphicsServer,
+            GeometryBufferExt,
+        },
+        make_viewport_matrix, RenderPassStatistics, TextureCache,
+    },
+    resource::texture::TextureResource,
+    scene::mesh::surface::SurfaceData,
+};
+use fxhash::FxHashMap;
+
+/// Amount of mip levels in prefiltered specular maps. Keep in sync with the ambient light shader.
+pub const SPECULAR_MAP_MIP_COUNT: usize = 6;
+/// Size of the first mip level of prefiltered specular maps. Every environment is resampled to
+/// this size, so all prefiltered maps share the same mip chain.
+const SPECULAR_MAP_SIZE: usize = 128;
+const IRRADIANCE_MAP_SIZE: usize = 32;
+/// Environments are copied to a temporary cube map with a full mip chain, which is then used as
+/// the source for the convolutions. Larger environments are downsampled to this size.
+const RADIANCE_MAP_MAX_SIZE: usize = 256;
+const BRDF_LUT_SIZE: usize = 128;
+/// Maximum number of taps (per axis) that is used to downsample a texel of an environment.
+const MAX_TAP_COUNT: usize = 16;
+
+const FACES: [CubeMapFace; 6] = [
+    CubeMapFace::PositiveX,
+    CubeMapFace::NegativeX,
+    CubeMapFace::PositiveY,
+    CubeMapFace::NegativeY,
+    CubeMapFace::PositiveZ,
+    CubeMapFace::NegativeZ,
+];
+
+/// A set of maps, that is generated from an environment cube map.
+#[derive(Clone)]
+pub struct EnvironmentMaps {
+    /// Cube map with diffuse irradiance.
+    pub irradiance: GpuTexture,
+    /// Cube map with [`SPECULAR_MAP_MIP_COUNT`] mip levels, each level is prefiltered for specific
+    /// roughness.
+    pub specular: GpuTexture,
+}
+
+struct CacheEntry {
+    maps: EnvironmentMaps,
+    modifications_count: u64,
+    time_to_live: TimeToLive,
+}
+
+pub struct ImageBasedLightingRenderer {
+    radiance_shader: RenderPassContainer,
+    irradiance_shader: RenderPassContainer,
+    prefilter_shader: RenderPassContainer,
+    brdf_shader: RenderPassContainer,
+    quad: GpuGeometryBuffer,
+    brdf_lut: GpuFrameBuffer,
+    brdf_lut_ready: bool,
+    linear_sampler: GpuSampler,
+    mip_sampler: GpuSampler,
+    cache: FxHashMap<u64, CacheEntry>,
+}
+
+fn cube_frame_buffer(
+    server: &dyn GraphicsServer,
+    size: usize,
+    mip_count: usize,
+) -> Result<GpuFrameBuffer, FrameworkError> {
+    let texture = server.create_texture(GpuTextureDescriptor {
+        kind: GpuTextureKind::Cube {
+            width: size,
+            height: size,
+        },
+        pixel_kind: PixelKind::RGBA16F,
+        mip_count,
+        ..Default::default()
+    })?;
+    server.create_frame_buffer(
+        None,
+        vec![Attachment {
+            kind: AttachmentKind::Color,
+            texture,
+        }],
+    )
+}
+
+fn face_viewport(size: usize, level: usize) -> Rect<i32> {
+    let size = (size >> level).max(1) as i32;
+    Rect::new(0, 0, size, size)
+}
+
+impl ImageBasedLightingRenderer {
+    pub fn new(server: &dyn GraphicsServer) -> Result<Self, FrameworkError> {
+        Ok(Self {
+            radiance_shader: RenderPassContainer::from_str(
+                server,
+                include_str!("shaders/ibl_radiance.shader"),
+            )?,
+            irradiance_shader: RenderPassContainer::from_str(
+                server,
+                include_str!("shaders/ibl_irradiance.shader"),
+            )?,
+            prefilter_shader: RenderPassContainer::from_str(
+                server,
+                include_str!("shaders/ibl_prefilter.shader"),
+            )?,
+            brdf_shader: RenderPassContainer::from_str(
+                server,
+                include_str!("shaders/ibl_brdf.shader"),
+            )?,
+            quad: GpuGeometryBuffer::from_surface_data(
+                &SurfaceData::make_unit_xy_quad(),
+                BufferUsage::StaticDraw,
+                server,
+            )?,
+            brdf_lut: server.create_frame_buffer(
+                None,
+                vec![Attachment {
+                    kind: AttachmentKind::Color,
+                    texture: server.create_2d_render_target(
+                        PixelKind::RGBA16F,
+                        BRDF_LUT_SIZE,
+                        BRDF_LUT_SIZE,
+                    )?,
+                }],
+            )?,
+            brdf_lut_ready: false,
+            linear_sampler: server.create_sampler(GpuSamplerDescriptor {
+                min_filter: MinificationFilter::Linear,
+                mag_filter: MagnificationFilter::Linear,
+                s_wrap_mode: WrapMode::ClampToEdge,
+                t_wrap_mode: WrapMode::ClampToEdge,
+                r_wrap_mode: WrapMode::ClampToEdge,
+                ..Default::default()
+            })?,
+            mip_sampler: server.create_sampler(GpuSamplerDescriptor {
+                min_filter: MinificationFilter::LinearMipMapLinear,
+                mag_filter: MagnificationFilter::Linear,
+                s_wrap_mode: WrapMode::ClampToEdge,
+                t_wrap_mode: WrapMode::ClampToEdge,
+                r_wrap_mode: WrapMode::ClampToEdge,
+                ..Default::default()
+            })?,
+            cache: Default::default(),
+        })
+    }
+
+    /// Returns the BRDF lookup table. It is filled on the first call of [`Self::prepare`].
+    pub fn brdf_lut(&self) -> &GpuTexture {
+        &self.brdf_lut.color_attachments()[0].texture
+    }
+
+    /// Returns a trilinear sampler, that should be used to sample the environment maps and the
+    /// BRDF lookup table.
+    pub fn sampler(&self) -> &GpuSampler {
+        &self.mip_sampler
+    }
+
+    /// Returns the maps of the given environment, if they were generated.
+    pub fn get(&self, environment: &TextureResource) -> Option<&EnvironmentMaps> {
+        self.cache.get(&environment.key()).map(|entry| &entry.maps)
+    }
+
+    /// Generates the maps for every given environment, that has no maps yet (or its data has
+    /// changed since the last generation). Textures, that are not cube maps, are ignored.
+    pub fn prepare<'a>(
+        &mut self,
+        server: &dyn GraphicsServer,
+        textures: &mut TextureCache,
+        environments: impl IntoIterator<Item = &'a TextureResource>,
+        uniform_buffer_cache: &mut UniformBufferCache,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        let mut stats = RenderPassStatistics::default();
+
+        if !self.brdf_lut_ready {
+            stats += self.render_brdf_lut(uniform_buffer_cache)?;
+            self.brdf_lut_ready = true;
+        }
+
+        for environment in environments {
+            let Some(modifications_count) = environment
+                .state()
+                .data()
+                .map(|texture| texture.modifications_count())
+            else {
+                continue;
+            };
+
+            let key = environment.key();
+            if let Some(entry) = self.cache.get_mut(&key) {
+                if entry.modifications_count == modifications_count {
+                    entry.time_to_live = TimeToLive::default();
+                    continue;
+                }
+            }
+
+            let Some(source) = textures.get(server, environment) else {
+                continue;
+            };
+            if !matches!(source.gpu_texture.kind(), GpuTextureKind::Cube { .. }) {
+                continue;
+            }
+            let (source_texture, source_sampler) =
+                (source.gpu_texture.clone(), source.gpu_sampler.clone());
+
+            let (maps, generation_stats) = self.generate(
+                server,
+                &source_texture,
+                &source_sampler,
+                uniform_buffer_cache,
+            )?;
+            stats += generation_stats;
+
+            self.cache.insert(
+                key,
+                CacheEntry {
+                    maps,
+                    modifications_count,
+                    time_to_live: TimeToLive::default(),
+                },
+            );
+        }
+
+        Ok(stats)
+    }
+
+    /// Generates irradiance and prefiltered specular maps from the given cube map. 8-bit cube maps
+    /// are considered to be in sRGB color space (the same way as skyboxes), floating-point ones are
+    /// considered to be linear.
+    pub fn generate(
+        &self,
+        server: &dyn GraphicsServer,
+        source: &GpuTexture,
+        source_sampler: &GpuSampler,
+        uniform_buffer_cache: &mut UniformBufferCache,
+    ) -> Result<(EnvironmentMaps, RenderPassStatistics), FrameworkError> {
+        let GpuTextureKind::Cube { width, .. } = source.kind() else {
+            return Err(FrameworkError::Custom(
+                "Environment maps can be generated only from cube maps!".to_string(),
+            ));
+        };
+
+        let mut stats = RenderPassStatistics::default();
+
+        // Copy the source to a linear cube map with full mip chain.
+        let radiance_size = width.clamp(1, RADIANCE_MAP_MAX_SIZE);
+        let radiance_mip_count = radiance_size.ilog2() as usize + 1;
+        let radiance = cube_frame_buffer(server, radiance_size, radiance_mip_count)?;
+        let decode_srgb = !matches!(source.pixel_kind().element_kind(), PixelElementKind::Float);
+        for level in 0..radiance_mip_count {
+            // The first level is a copy of the source, the rest levels are downsampled from the
+            // first one. Only the first level is accessed by the linear sampler, so it is safe to
+            // render to the other levels of the same texture.
+            let (texture, sampler, tap_count, decode_srgb) = if level == 0 {
+                (source, source_sampler, width / radiance_size, decode_srgb)
+            } else {
+                (
+                    &radiance.color_attachments()[0].texture,
+                    &self.linear_sampler,
+                    1 << level,
+                    false,
+                )
+            };
+            let tap_count = tap_count.clamp(1, MAX_TAP_COUNT) as i32;
+            let viewport = face_viewport(radiance_size, level);
+            let face_size = viewport.w() as f32;
+            let frame_matrix = make_viewport_matrix(viewport);
+            for (face_index, face) in FACES.into_iter().enumerate() {
+                radiance.set_cubemap_face(0, face, level);
+                let face_index = face_index as i32;
+                let properties = PropertyGroup::from([
+                    property("worldViewProjection", &frame_matrix),
+                    property("faceIndex", &face_index),
+                    property("faceSize", &face_size),
+                    property("tapCount", &tap_count),
+                    property("decodeSrgb", &decode_srgb),
+                ]);
+                let material = RenderMaterial::from([
+                    binding("sourceTexture", (texture, sampler)),
+                    binding("properties", &properties),
+                ]);
+                stats += self.radiance_shader.run_pass(
+                    1,
+                    &ImmutableString::new("Primary"),
+                    &radiance,
+                    &self.quad,
+                    viewport,
+                    &material,
+                    uniform_buffer_cache,
+                    Default::default(),
+                    None,
+                )?;
+            }
+        }
+        let radiance_texture = &radiance.color_attachments()[0].texture;
+        let radiance_size_f32 = radiance_size as f32;
+
+        // Diffuse irradiance.
+        let irradiance = cube_frame_buffer(server, IRRADIANCE_MAP_SIZE, 1)?;
+        let viewport = face_viewport(IRRADIANCE_MAP_SIZE, 0);
+        let face_size = IRRADIANCE_MAP_SIZE as f32;
+        let source_lod = (radiance_size_f32 / face_size).log2().max(0.0);
+        let frame_matrix = make_viewport_matrix(viewport);
+        for (face_index, face) in FACES.into_iter().enumerate() {
+            irradiance.set_cubemap_face(0, face, 0);
+            let face_index = face_index as i32;
+            let properties = PropertyGroup::from([
+                property("worldViewProjection", &frame_matrix),
+                property("faceIndex", &face_index),
+                property("faceSize", &face_size),
+                property("sourceLod", &source_lod),
+            ]);
+            let material = RenderMaterial::from([
+                binding("sourceTexture", (radiance_texture, &self.mip_sampler)),
+                binding("properties", &properties),
+            ]);
+            stats += self.irradiance_shader.run_pass(
+                1,
+                &ImmutableString::new("Primary"),
+                &irradiance,
+                &self.quad,
+                viewport,
+                &material,
+                uniform_buffer_cache,
+                Default::default(),
+                None,
+            )?;
+        }
+
+        // Prefiltered specular.
+        let specular = cube_frame_buffer(server, SPECULAR_MAP_SIZE, SPECULAR_MAP_MIP_COUNT)?;
+        let source_lod = (radiance_size_f32 / SPECULAR_MAP_SIZE as f32)
+            .log2()
+            .max(0.0);
+        for level in 0..SPECULAR_MAP_MIP_COUNT {
+            let roughness = level as f32 / (SPECULAR_MAP_MIP_COUNT - 1) as f32;
+            let viewport = face_viewport(SPECULAR_MAP_SIZE, level);
+            let face_size = viewport.w() as f32;
+            let frame_matrix = make_viewport_matrix(viewport);
+            for (face_index, face) in FACES.into_iter().enumerate() {
+                specular.set_cubemap_face(0, face, level);
+                let face_index = face_index as i32;
+                let properties = PropertyGroup::from([
+                    property("worldViewProjection", &frame_matrix),
+                    property("faceIndex", &face_index),
+                    property("faceSize", &face_size),
+                    property("sourceSize", &radiance_size_f32),
+                    property("sourceLod", &source_lod),
+                    property("roughness", &roughness),
+                ]);
+                let material = RenderMaterial::from([
+                    binding("sourceTexture", (radiance_texture, &self.mip_sampler)),
+                    binding("properties", &properties),
+                ]);
+                stats += self.prefilter_shader.run_pass(
+                    1,
+                    &ImmutableString::new("Primary"),
+                    &specular,
+                    &self.quad,
+                    viewport,
+                    &material,
+                    uniform_buffer_cache,
+                    Default::default(),
+                    None,
+                )?;
+            }
+        }
+
+        Ok((
+            EnvironmentMaps {
+                irradiance: irradiance.color_attachments()[0].texture.clone(),
+                specular: specular.color_attachments()[0].texture.clone(),
+            },
+            stats,
+        ))
+    }
+
+    fn render_brdf_lut(
+        &self,
+        uniform_buffer_cache: &mut UniformBufferCache,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        let mut stats = RenderPassStatistics::default();
+        let viewport = face_viewport(BRDF_LUT_SIZE, 0);
+        self.brdf_lut
+            .clear(viewport, Some(Color::TRANSPARENT), None, None);
+        let frame_matrix = make_viewport_matrix(viewport);
+        let lut_size = BRDF_LUT_SIZE as f32;
+        let properties = PropertyGroup::from([
+            property("worldViewProjection", &frame_matrix),
+            property("lutSize", &lut_size),
+        ]);
+        let material = RenderMaterial::from([binding("properties", &properties)]);
+        stats += self.brdf_shader.run_pass(
+            1,
+            &ImmutableString::new("Primary"),
+            &self.brdf_lut,
+            &self.quad,
+            viewport,
+            &material,
+            uniform_buffer_cache,
+            Default::default(),
+            None,
+        )?;
+        Ok(stats)
+    }
+
+    /// Removes the maps of environments, that were not used for a while.
+    pub fn update(&mut self, dt: f32) {
+        self.cache.retain(|_, entry| {
+            *entry.time_to_live -= dt;
+            *entry.time_to_live > 0.0
+        });
+    }
+}
@@ -21,30 +21,35 @@
 use crate::renderer::DynamicSurfaceCache;
 use crate::{
     core::{
-        algebra::{Matrix4, Point3, UnitQuaternion, Vector2, Vector3},
+        algebra::{Matrix4, Point3, UnitQuaternion, Vector2, Vector3, Vector4},
         color::Color,
         math::{frustum::Frustum, Matrix4Ext, Rect, TriangleDefinition},
+        pool::Handle,
         ImmutableString,
     },
     renderer::{
-        bundle::{LightSourceKind, RenderDataBundleStorage},
+        atmosphere::AtmosphereRenderer,
+        bundle::{FogRenderData, LightSource, LightSourceKind, RenderDataBundleStorage},
         cache::{
             shader::{
                 binding, property, PropertyGroup, RenderMaterial, RenderPassContainer, ShaderCache,
             },
             uniform::{UniformBufferCache, UniformMemoryAllocator},
         },
+        cluster::LightClusters,
+        fog::{FogRenderer, VolumetricFogLight, VolumetricFogScatteringContext},
         framework::{
             buffer::BufferUsage, error::FrameworkError, framebuffer::GpuFrameBuffer,
-            geometry_buffer::GpuGeometryBuffer, server::GraphicsServer, ColorMask, CompareFunc,
-            CullFace, DrawParameters, ElementRange, GeometryBufferExt, StencilAction, StencilFunc,
-            StencilOp,
+            geometry_buffer::GpuGeometryBuffer, gpu_texture::GpuTexture, sampler::GpuSampler,
+            server::GraphicsServer, ColorMask, CompareFunc, CullFace, DrawParameters, ElementRange,
+            GeometryBufferExt, StencilAction, StencilFunc, StencilOp,
         },
         gbuffer::GBuffer,
+        ibl::{EnvironmentMaps, ImageBasedLightingRenderer, SPECULAR_MAP_MIP_COUNT},
         light_volume::LightVolumeRenderer,
         make_viewport_matrix,
         shadow::{
-            csm::{CsmRenderContext, CsmRenderer},
+            csm::{Cascade, CsmRenderContext, CsmRenderer},
             point::{PointShadowMapRenderContext, PointShadowMapRenderer},
             spot::SpotShadowMapRenderer,
         },
@@ -53,23 +58,90 @@ use crate::{
         FallbackResources, GeometryCache, LightingStatistics, QualitySettings,
         RenderPassStatistics, TextureCache,
     },
+    resource::texture::TextureResource,
     scene::{
         camera::Camera,
+        fog::Fog,
         mesh::{
             buffer::{TriangleBuffer, VertexBuffer},
             surface::SurfaceData,
             vertex::SimpleVertex,
         },
+        node::NodeTrait,
+        probe::{ReflectionProbe, ReflectionProbeShape},
         Scene,
     },
 };
 
+/// Maximum amount of reflection probes that can affect a frame. Keep in sync with the ambient light
+/// shader. Every probe needs two samplers (irradiance and prefiltered specular maps), so the
+/// amount is limited by the minimal amount of texture units guaranteed by OpenGL.
+const MAX_REFLECTION_PROBES: usize = 3;
+
+/// Near clipping plane of the projection of spot light shadow maps.
+const SPOT_SHADOW_Z_NEAR: f32 = 0.01;
+
+type TextureBinding<'a> = (&'a GpuTexture, &'a GpuSampler);
+
+/// Returns bindings of irradiance and prefiltered specular maps, the dummy texture is used when
+/// there are no maps.
+fn environment_maps_binding<'a>(
+    maps: Option<&'a EnvironmentMaps>,
+    dummy: &'a GpuTexture,
+    sampler: &'a GpuSampler,
+) -> (TextureBinding<'a>, TextureBinding<'a>) {
+    match maps {
+        Some(maps) => ((&maps.irradiance, sampler), (&maps.specular, sampler)),
+        None => ((dummy, sampler), (dummy, sampler)),
+    }
+}
+
+/// Returns `true` if the given light must be shaded by its own pass, because it needs a shadow map
+/// or a cookie texture, which cannot be used by the clustered lighting pass.
+fn needs_separate_pass(
+    light: &LightSource,
+    settings: &QualitySettings,
+    camera_position: Vector3<f32>,
+) -> bool {
+    let distance_to_camera = light.position.metric_distance(&camera_position);
+    match light.kind {
+        LightSourceKind::Spot {
+            ref cookie_texture, ..
+        } => {
+            cookie_texture.is_some()
+                || (light.cast_shadows
+                    && settings.spot_shadows_enabled
+                    && distance_to_camera <= settings.spot_shadows_distance)
+        }
+        LightSourceKind::Point { .. } => {
+            light.cast_shadows
+                && settings.point_shadows_enabled
+                && distance_to_camera <= settings.point_shadows_distance
+        }
+        LightSourceKind::Directional { .. } | LightSourceKind::Unknown => true,
+    }
+}
+
+/// Returns the skybox of the camera, if the fog should take its color from it.
+fn fog_skybox<'a>(fog: &Fog, camera: &'a Camera) -> Option<&'a TextureResource> {
+    if fog.enabled && fog.use_skybox_color {
+        camera.skybox_ref().and_then(|skybox| skybox.cubemap_ref())
+    } else {
+        None
+    }
+}
+
 pub struct DeferredLightRenderer {
     pub ssao_renderer: ScreenSpaceAmbientOcclusionRenderer,
+    pub ibl_renderer: ImageBasedLightingRenderer,
+    pub atmosphere_renderer: AtmosphereRenderer,
+    pub fog_renderer: FogRenderer,
     spot_light_shader: RenderPassContainer,
     point_light_shader: RenderPassContainer,
     directional_light_shader: RenderPassContainer,
     ambient_light_shader: RenderPassContainer,
+    clustered_light_shader: RenderPassContainer,
+    light_clusters: LightClusters,
     quad: GpuGeometryBuffer,
     sphere: GpuGeometryBuffer,
     cone: GpuGeometryBuffer,
@@ -86,6 +158,7 @@ pub struct DeferredLightRenderer {
 pub(crate) struct DeferredRendererContext<'a> {
     pub elapsed_time: f32,
     pub server: &'a dyn GraphicsServer,
+    pub scene_handle: Handle<Scene>,
     pub scene: &'a Scene,
     pub camera: &'a Camera,
     pub gbuffer: &'a mut GBuffer,
@@ -150,6 +223,9 @@ impl DeferredLightRenderer {
                 frame_size.0 as usize,
                 frame_size.1 as usize,
             )?,
+            ibl_renderer: ImageBasedLightingRenderer::new(server)?,
+            atmosphere_renderer: AtmosphereRenderer::new(server)?,
+            fog_renderer: FogRenderer::new(server)?,
             spot_light_shader: RenderPassContainer::from_str(
                 server,
                 include_str!("shaders/deferred_spot_light.shader"),
@@ -166,6 +242,11 @@ impl DeferredLightRenderer {
                 server,
                 include_str!("shaders/ambient_light.shader"),
             )?,
+            clustered_light_shader: RenderPassContainer::from_str(
+                server,
+                include_str!("shaders/deferred_clustered_light.shader"),
+            )?,
+            light_clusters: LightClusters::new(server)?,
             quad: GpuGeometryBuffer::from_surface_data(
                 &SurfaceData::make_unit_xy_quad(),
                 BufferUsage::StaticDraw,
@@ -287,6 +368,71 @@ impl DeferredLightRenderer {
         Ok(())
     }
 
+    /// Returns point and spot lights of the last rendered camera binned into the clusters of its
+    /// view frustum. The clusters are valid only if the clustered lighting is enabled.
+    pub fn light_clusters(&self) -> &LightClusters {
+        &self.light_clusters
+    }
+
+    /// Renders the sky of the given scene, if its atmosphere is enabled. Must be called before
+    /// [`Self::render`] for every scene.
+    pub fn prepare_atmosphere(
+        &mut self,
+        server: &dyn GraphicsServer,
+        scene_handle: Handle<Scene>,
+        scene: &Scene,
+        elapsed_time: f32,
+        uniform_buffer_cache: &mut UniformBufferCache,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        let atmosphere = &scene.rendering_options.atmosphere;
+        self.atmosphere_renderer.prepare(
+            server,
+            scene_handle,
+            atmosphere,
+            atmosphere.calculate_sun_direction(&scene.graph),
+            elapsed_time,
+            &self.ibl_renderer,
+            uniform_buffer_cache,
+        )
+    }
+
+    /// Collects the data, that is needed to render the given fog. Returns [`None`] if the fog is
+    /// disabled. Must be called after [`Self::render`], which prepares the data.
+    pub fn fog_render_data<'a>(
+        &'a self,
+        fog: &'a Fog,
+        camera: &Camera,
+        scene_handle: Handle<Scene>,
+    ) -> Option<FogRenderData<'a>> {
+        if !fog.enabled {
+            return None;
+        }
+
+        // The atmosphere replaces skyboxes of cameras, so it takes precedence.
+        let atmosphere = self
+            .atmosphere_renderer
+            .get(scene_handle)
+            .filter(|_| fog.use_skybox_color)
+            .map(|atmosphere| &atmosphere.environment);
+
+        Some(FogRenderData {
+            fog,
+            environment: atmosphere
+                .or_else(|| {
+                    fog_skybox(fog, camera).and_then(|skybox| self.ibl_renderer.get(skybox))
+                })
+                .map(|maps| {
+                    (
+                        &maps.specular,
+                        self.ibl_renderer.sampler(),
+                        // Blurry enough to hide the details of the sky.
+                        (SPECULAR_MAP_MIP_COUNT - 2) as f32,
+                    )
+                }),
+            volume: fog.volumetric.enabled.then(|| self.fog_renderer.volume()),
+        })
+    }
+
     pub(crate) fn render(
         &mut self,
         args: DeferredRendererContext,
@@ -297,6 +443,7 @@ impl DeferredLightRenderer {
         let DeferredRendererContext {
             elapsed_time,
             server,
+            scene_handle,
             scene,
             camera,
             gbuffer,
@@ -320,11 +467,18 @@ impl DeferredLightRenderer {
 
         let frame_matrix = make_viewport_matrix(viewport);
 
-        let projection_matrix = camera.projection_matrix();
-        let view_projection = camera.view_projection_matrix();
+        // G-Buffer is filled using jittered projection when TAA is enabled, so the same matrix must
+        // be used to reconstruct positions from the depth.
+        let projection_matrix = if settings.use_taa {
+            camera.jittered_projection_matrix()
+        } else {
+            camera.projection_matrix()
+        };
+        let view_projection = projection_matrix * camera.view_matrix();
         let inv_projection = projection_matrix.try_inverse().unwrap_or_default();
         let inv_view_projection = view_projection.try_inverse().unwrap_or_default();
         let camera_global_position = camera.global_position();
+        let contact_shadows_steps = settings.contact_shadows.step_count as i32;
 
         // Fill SSAO map.
         if settings.use_ssao {
@@ -337,43 +491,44 @@ impl DeferredLightRenderer {
             )?;
         }
 
-        // Render skybox (if any).
-        if let Some(skybox) = camera.skybox_ref() {
-            if let Some(texture_sampler_pair) = skybox
-                .cubemap_ref()
+        // Render skybox (if any). Procedural sky of the scene replaces skyboxes of cameras.
+        let atmosphere = self.atmosphere_renderer.get(scene_handle);
+        let sky = match atmosphere {
+            Some(atmosphere) => Some((&atmosphere.sky, self.atmosphere_renderer.sampler(), false)),
+            None => camera
+                .skybox_ref()
+                .and_then(|skybox| skybox.cubemap_ref())
                 .and_then(|cube_map| textures.get(server, cube_map))
-            {
-                let size = camera.projection().z_far() / 2.0f32.sqrt();
-                let scale = Matrix4::new_scaling(size);
-                let wvp = Matrix4::new_translation(&camera.global_position()) * scale;
-                let wvp = view_projection * wvp;
-                let properties = PropertyGroup::from([property("worldViewProjection", &wvp)]);
-                let material = RenderMaterial::from([
-                    binding(
-                        "cubemapTexture",
-                        (
-                            &texture_sampler_pair.gpu_texture,
-                            &texture_sampler_pair.gpu_sampler,
-                        ),
-                    ),
-                    binding("properties", &properties),
-                ]);
+                .map(|pair| (&pair.gpu_texture, &pair.gpu_sampler, true)),
+        };
+        if let Some((sky_texture, sky_sampler, decode_srgb)) = sky {
+            let size = camera.projection().z_far() / 2.0f32.sqrt();
+            let scale = Matrix4::new_scaling(size);
+            let wvp = Matrix4::new_translation(&camera.global_position()) * scale;
+            let wvp = view_projection * wvp;
+            let properties = PropertyGroup::from([
+                property("worldViewProjection", &wvp),
+                property("decodeSrgb", &decode_srgb),
+            ]);
+            let material = RenderMaterial::from([
+                binding("cubemapTexture", (sky_texture, sky_sampler)),
+                binding("properties", &properties),
+            ]);
 
-                pass_stats += self.skybox_shader.run_pass(
-                    1,
-                    &ImmutableString::new("Primary"),
-                    frame_buffer,
-                    &self.skybox,
-                    viewport,
-                    &material,
-                    uniform_buffer_cache,
-                    ElementRange::Specific {
-                        offset: 0,
-                        count: 12,
-                    },
-                    None,
-                )?;
-            }
+            pass_stats += self.skybox_shader.run_pass(
+                1,
+                &ImmutableString::new("Primary"),
+                frame_buffer,
+                &self.skybox,
+                viewport,
+                &material,
+                uniform_buffer_cache,
+                ElementRange::Specific {
+                    offset: 0,
+                    count: 12,
+                },
+                None,
+            )?;
         }
 
         // Ambient light.
@@ -384,10 +539,92 @@ impl DeferredLightRenderer {
         let gbuffer_ambient_map = gbuffer.ambient_texture();
         let ao_map = self.ssao_renderer.ao_map();
 
+        // Pick the closest visible reflection probes, the rest is ignored.
+        let mut probes = scene
+            .graph
+            .linear_iter()
+            .filter_map(|node| node.cast::<ReflectionProbe>())
+            .filter(|probe| {
+                probe.is_globally_enabled()
+                    && probe.environment().is_some()
+                    && frustum.is_intersects_aabb(&probe.world_bounding_box())
+            })
+            .collect::<Vec<_>>();
+        probes.sort_by(|a, b| {
+            let a = a.global_position().metric_distance(&camera_global_position);
+            let b = b.global_position().metric_distance(&camera_global_position);
+            a.total_cmp(&b)
+        });
+        probes.truncate(MAX_REFLECTION_PROBES);
+
+        // Make sure that every environment has its irradiance and prefiltered specular maps.
+        pass_stats += self.ibl_renderer.prepare(
+            server,
+            textures,
+            camera
+                .environment_ref()
+                .into_iter()
+                .chain(probes.iter().filter_map(|probe| probe.environment()))
+                .chain(fog_skybox(&scene.rendering_options.fog, camera)),
+            uniform_buffer_cache,
+        )?;
+        let probes = probes
+            .into_iter()
+            .filter_map(|probe| Some((probe, self.ibl_renderer.get(probe.environment()?)?)))
+            .collect::<Vec<_>>();
+
+        let mut probe_world = Vec::with_capacity(probes.len());
+        let mut probe_inv_world = Vec::with_capacity(probes.len());
+        let mut probe_shape = Vec::with_capacity(probes.len());
+        let mut probe_params = Vec::with_capacity(probes.len());
+        for (probe, _) in probes.iter() {
+            let world = probe.global_transform();
+            probe_world.push(world);
+            probe_inv_world.push(world.try_inverse().unwrap_or_default());
+            probe_shape.push(match probe.shape() {
+                ReflectionProbeShape::Box { half_extents } => half_extents.push(0.0),
+                ReflectionProbeShape::Sphere { radius } => Vector4::new(radius, 0.0, 0.0, 1.0),
+            });
+            probe_params.push(Vector4::new(
+                probe.blend_distance(),
+                if probe.is_box_projection_enabled() {
+                    1.0
+                } else {
+                    0.0
+                },
+                probe.intensity(),
+                0.0,
+            ));
+        }
+        let probe_count = probes.len() as i32;
+
+        let ibl_sampler = self.ibl_renderer.sampler();
+        let environment = camera
+            .environment_ref()
+            .and_then(|environment| self.ibl_renderer.get(environment))
+            .or(atmosphere.map(|atmosphere| &atmosphere.environment));
+        let dummy = &fallback_resources.environment_dummy;
+        let (environment_irradiance, environment_specular) =
+            environment_maps_binding(environment, dummy, ibl_sampler);
+        let probe_maps = |index: usize| probes.get(index).map(|(_, maps)| *maps);
+        let (probe0_irradiance, probe0_specular) =
+            environment_maps_binding(probe_maps(0), dummy, ibl_sampler);
+        let (probe1_irradiance, probe1_specular) =
+            environment_maps_binding(probe_maps(1), dummy, ibl_sampler);
+        let (probe2_irradiance, probe2_specular) =
+            environment_maps_binding(probe_maps(2), dummy, ibl_sampler);
+
         let ambient_color = ambient_color.srgb_to_linear_f32();
         let properties = PropertyGroup::from([
             property("worldViewProjection", &frame_matrix),
             property("ambientColor", &ambient_color),
+            property("invViewProj", &inv_view_projection),
+            property("cameraPosition", &camera_global_position),
+            property("probeCount", &probe_count),
+            property("probeWorld", probe_world.as_slice()),
+            property("probeInvWorld", probe_inv_world.as_slice()),
+            property("probeShape", probe_shape.as_slice()),
+            property("probeParams", probe_params.as_slice()),
         ]);
         let material = RenderMaterial::from([
             binding(
@@ -415,6 +652,39 @@ impl DeferredLightRenderer {
                     &fallback_resources.nearest_clamp_sampler,
                 ),
             ),
+            binding(
+                "depthTexture",
+                (gbuffer_depth_map, &fallback_resources.nearest_clamp_sampler),
+            ),
+            binding(
+                "normalTexture",
+                (
+                    gbuffer_normal_map,
+                    &fallback_resources.nearest_clamp_sampler,
+                ),
+            ),
+            binding(
+                "materialTexture",
+                (
+                    gbuffer_material_map,
+                    &fallback_resources.nearest_clamp_sampler,
+                ),
+            ),
+            binding(
+                "brdfLut",
+                (
+                    self.ibl_renderer.brdf_lut(),
+                    &fallback_resources.linear_clamp_sampler,
+                ),
+            ),
+            binding("environmentIrradiance", environment_irradiance),
+            binding("environmentSpecular", environment_specular),
+            binding("probe0Irradiance", probe0_irradiance),
+            binding("probe0Specular", probe0_specular),
+            binding("probe1Irradiance", probe1_irradiance),
+            binding("probe1Specular", probe1_specular),
+            binding("probe2Irradiance", probe2_irradiance),
+            binding("probe2Specular", probe2_specular),
             binding("properties", &properties),
         ]);
 
@@ -430,8 +700,108 @@ impl DeferredLightRenderer {
             None,
         )?;
 
+        // Point and spot lights without shadows and cookies are shaded by a single pass, the rest
+        // of the lights is binned as well, so forward passes could use them.
+        if settings.use_clustered_lighting {
+            let clustered_lights = self.light_clusters.update(
+                camera.view_matrix(),
+                camera.projection_matrix(),
+                camera.projection().z_near(),
+                camera.projection().z_far(),
+                render_data_bundle.light_sources.iter().map(|light| {
+                    (
+                        light,
+                        needs_separate_pass(light, settings, camera_global_position),
+                    )
+                }),
+            )?;
+
+            if clustered_lights > 0 {
+                light_stats.clustered_lights_rendered += clustered_lights;
+
+                let view_matrix = camera.view_matrix();
+                let z_near = self.light_clusters.z_near();
+                let z_far = self.light_clusters.z_far();
+                let properties = PropertyGroup::from([
+                    property("worldViewProjection", &frame_matrix),
+                    property("invViewProj", &inv_view_projection),
+                    property("viewMatrix", &view_matrix),
+                    property(
+                        "clusterViewProjection",
+                        self.light_clusters.view_projection_matrix(),
+                    ),
+                    property("cameraPosition", &camera_global_position),
+                    property("zNear", &z_near),
+                    property("zFar", &z_far),
+                ]);
+                let material = RenderMaterial::from([
+                    binding(
+                        "depthTexture",
+                        (gbuffer_depth_map, &fallback_resources.nearest_clamp_sampler),
+                    ),
+                    binding(
+                        "colorTexture",
+                        (
+                            gbuffer_diffuse_map,
+                            &fallback_resources.nearest_clamp_sampler,
+                        ),
+                    ),
+                    binding(
+                        "normalTexture",
+                        (
+                            gbuffer_normal_map,
+                            &fallback_resources.nearest_clamp_sampler,
+                        ),
+                    ),
+                    binding(
+                        "materialTexture",
+                        (
+                            gbuffer_material_map,
+                            &fallback_resources.nearest_clamp_sampler,
+                        ),
+                    ),
+                    binding(
+                        "clustersTexture",
+                        (
+                            self.light_clusters.clusters_texture(),
+                            &fallback_resources.nearest_clamp_sampler,
+                        ),
+                    ),
+                    binding(
+                        "lightsTexture",
+                        (
+                            self.light_clusters.lights_texture(),
+                            &fallback_resources.nearest_clamp_sampler,
+                        ),
+                    ),
+                    binding("properties", &properties),
+                ]);
+
+                pass_stats += self.clustered_light_shader.run_pass(
+                    1,
+                    &ImmutableString::new("Primary"),
+                    frame_buffer,
+                    &self.quad,
+                    viewport,
+                    &material,
+                    uniform_buffer_cache,
+                    Default::default(),
+                    None,
+                )?;
+            }
+        }
+
+        // Volumetric fog is lit by the first directional light, it must be done right after its
+        // cascaded shadow maps are rendered, because they're shared by all directional lights.
+        let volumetric_fog = Some(&scene.rendering_options.fog)
+            .filter(|fog| fog.enabled && fog.volumetric.enabled)
+            .map(|fog| &fog.volumetric);
+        let mut volumetric_fog_lit = false;
+
         for light in render_data_bundle.light_sources.iter() {
             let distance_to_camera = (light.position - camera.global_position()).norm();
+            let clustered = settings.use_clustered_lighting
+                && !needs_separate_pass(light, settings, camera_global_position);
 
             let (
                 raw_radius,
@@ -534,50 +904,54 @@ impl DeferredLightRenderer {
 
             let mut light_view_projection = Matrix4::identity();
 
-            // Mark lit areas in stencil buffer to do light calculations only on them.
-            let shape_wvp_matrix = view_projection * bounding_shape_matrix;
-            for (cull_face, stencil_action) in [
-                (CullFace::Front, StencilAction::Incr),
-                (CullFace::Back, StencilAction::Decr),
-            ] {
-                let draw_params = DrawParameters {
-                    cull_face: Some(cull_face),
-                    color_write: ColorMask::all(false),
-                    depth_write: false,
-                    stencil_test: Some(StencilFunc {
-                        func: CompareFunc::Always,
-                        ..Default::default()
-                    }),
-                    stencil_op: StencilOp {
-                        zfail: stencil_action,
-                        ..Default::default()
-                    },
-                    depth_test: Some(CompareFunc::Less),
-                    blend: None,
-                    scissor_box: None,
-                };
-                let properties =
-                    PropertyGroup::from([property("worldViewProjection", &shape_wvp_matrix)]);
-                let material = RenderMaterial::from([binding("properties", &properties)]);
-                pass_stats += self.volume_marker.run_pass(
-                    1,
-                    &ImmutableString::new("Primary"),
-                    frame_buffer,
-                    bounding_shape,
-                    viewport,
-                    &material,
-                    uniform_buffer_cache,
-                    Default::default(),
-                    Some(&draw_params),
-                )?;
+            // Mark lit areas in stencil buffer to do light calculations only on them. Clustered
+            // lights are already shaded by the clustered pass.
+            if !clustered {
+                let shape_wvp_matrix = view_projection * bounding_shape_matrix;
+                for (cull_face, stencil_action) in [
+                    (CullFace::Front, StencilAction::Incr),
+                    (CullFace::Back, StencilAction::Decr),
+                ] {
+                    let draw_params = DrawParameters {
+                        cull_face: Some(cull_face),
+                        color_write: ColorMask::all(false),
+                        depth_write: false,
+                        stencil_test: Some(StencilFunc {
+                            func: CompareFunc::Always,
+                            ..Default::default()
+                        }),
+                        stencil_op: StencilOp {
+                            zfail: stencil_action,
+                            ..Default::default()
+                        },
+                        depth_test: Some(CompareFunc::Less),
+                        blend: None,
+                        scissor_box: None,
+                    };
+                    let properties =
+                        PropertyGroup::from([property("worldViewProjection", &shape_wvp_matrix)]);
+                    let material = RenderMaterial::from([binding("properties", &properties)]);
+                    pass_stats += self.volume_marker.run_pass(
+                        1,
+                        &ImmutableString::new("Primary"),
+                        frame_buffer,
+                        bounding_shape,
+                        viewport,
+                        &material,
+                        uniform_buffer_cache,
+                        Default::default(),
+                        Some(&draw_params),
+                    )?;
+                }
             }
 
             // Directional light sources cannot be optimized via occlusion culling, because they're
             // usually cover the entire screen anyway. TODO: This might still be optimizable, but
             // for now we'll skip it, since this optimization could be useful only for scenes with
             // mixed indoor/outdoor environment.
-            let mut needs_lighting = true;
-            if !matches!(light.kind, LightSourceKind::Directional { .. })
+            let mut needs_lighting = !clustered;
+            if needs_lighting
+                && !matches!(light.kind, LightSourceKind::Directional { .. })
                 && settings.use_light_occlusion_culling
             {
                 if visibility_cache.needs_occlusion_query(camera_global_position, light.handle) {
@@ -611,7 +985,7 @@ impl DeferredLightRenderer {
                     LightSourceKind::Spot {
                         full_cone_angle, ..
                     } => {
-                        let z_near = 0.01;
+                        let z_near = SPOT_SHADOW_Z_NEAR;
                         let z_far = light_radius;
                         let light_projection_matrix =
                             Matrix4::new_perspective(1.0, full_cone_angle, z_near, z_far);
@@ -735,6 +1109,11 @@ impl DeferredLightRenderer {
 
                         let half_hotspot_cone_angle_cos = (hotspot_cone_angle * 0.5).cos();
                         let half_cone_angle_cos = (full_cone_angle * 0.5).cos();
+                        // Size of the light source relative to the width of the light frustum at
+                        // unit distance, this is what the blocker search of PCSS operates on.
+                        let light_size_uv = light.source_size
+                            / (2.0 * (full_cone_angle * 0.5).tan()).max(f32::EPSILON);
+                        let contact_shadows = settings.contact_shadows.enabled;
                         let properties = PropertyGroup::from([
                             property("worldViewProjection", &frame_matrix),
                             property("lightViewProjMatrix", &light_view_projection),
@@ -753,6 +1132,21 @@ impl DeferredLightRenderer {
                             property("cookieEnabled", &cookie_enabled),
                             property("shadowsEnabled", &shadows_enabled),
                             property("softShadows", &settings.spot_soft_shadows),
+                            property("viewProjection", &view_projection),
+                            property("lightSize", &light_size_uv),
+                            property("lightZNear", &SPOT_SHADOW_Z_NEAR),
+                            property("lightZFar", &light_radius),
+                            property("pcss", &settings.spot_pcss),
+                            property("contactShadows", &contact_shadows),
+                            property(
+                                "contactShadowsDistance",
+                                &settings.contact_shadows.max_distance,
+                            ),
+                            property(
+                                "contactShadowsThickness",
+                                &settings.contact_shadows.thickness,
+                            ),
+                            property("contactShadowsSteps", &contact_shadows_steps),
                         ]);
                         let material = RenderMaterial::from([
                             binding(
@@ -806,6 +1200,8 @@ impl DeferredLightRenderer {
                     LightSourceKind::Point { shadow_bias, .. } => {
                         light_stats.point_lights_rendered += 1;
 
+                        let contact_shadows = settings.contact_shadows.enabled;
+
                         let properties = PropertyGroup::from([
                             property("worldViewProjection", &frame_matrix),
                             property("invViewProj", &inv_view_projection),
@@ -818,6 +1214,19 @@ impl DeferredLightRenderer {
                             property("shadowAlpha", &shadows_alpha),
                             property("shadowsEnabled", &shadows_enabled),
                             property("softShadows", &settings.point_soft_shadows),
+                            property("viewProjection", &view_projection),
+                            property("lightSize", &light.source_size),
+                            property("pcss", &settings.point_pcss),
+                            property("contactShadows", &contact_shadows),
+                            property(
+                                "contactShadowsDistance",
+                                &settings.contact_shadows.max_distance,
+                            ),
+                            property(
+                                "contactShadowsThickness",
+                                &settings.contact_shadows.thickness,
+                            ),
+                            property("contactShadowsSteps", &contact_shadows_steps),
                         ]);
                         let material = RenderMaterial::from([
                             binding(
@@ -881,7 +1290,19 @@ impl DeferredLightRenderer {
                             self.csm_renderer.cascades()[1].view_proj_matrix,
                             self.csm_renderer.cascades()[2].view_proj_matrix,
                         ];
+                        // Directional lights are infinitely far away, so their source size is an
+                        // angle and the penumbra grows linearly with the distance to an occluder.
+                        let penumbra_scale = |cascade: &Cascade| {
+                            light.source_size * cascade.extents.z
+                                / cascade.extents.x.max(cascade.extents.y).max(f32::EPSILON)
+                        };
+                        let penumbra_scales = [
+                            penumbra_scale(&self.csm_renderer.cascades()[0]),
+                            penumbra_scale(&self.csm_renderer.cascades()[1]),
+                            penumbra_scale(&self.csm_renderer.cascades()[2]),
+                        ];
                         let shadow_map_inv_size = 1.0 / (self.csm_renderer.size() as f32);
+                        let contact_shadows = settings.csm_settings.contact_shadows;
                         let shadow_bias = csm_options.shadow_bias();
                         let view_matrix = camera.view_matrix();
                         let properties = PropertyGroup::from([
@@ -898,6 +1319,19 @@ impl DeferredLightRenderer {
                             property("softShadows", &settings.csm_settings.pcf),
                             property("shadowMapInvSize", &shadow_map_inv_size),
                             property("cascadeDistances", distances.as_slice()),
+                            property("viewProjection", &view_projection),
+                            property("penumbraScales", penumbra_scales.as_slice()),
+                            property("pcss", &settings.csm_settings.pcss),
+                            property("contactShadows", &contact_shadows),
+                            property(
+                                "contactShadowsDistance",
+                                &settings.contact_shadows.max_distance,
+                            ),
+                            property(
+                                "contactShadowsThickness",
+                                &settings.contact_shadows.thickness,
+                            ),
+                            property("contactShadowsSteps", &contact_shadows_steps),
                         ]);
                         let cascades = self.csm_renderer.cascades();
                         let material = RenderMaterial::from([
@@ -966,6 +1400,30 @@ impl DeferredLightRenderer {
                 };
             }
 
+            if let (Some(volumetric_fog), LightSourceKind::Directional { ref csm_options }) =
+                (volumetric_fog, &light.kind)
+            {
+                if !volumetric_fog_lit {
+                    pass_stats +=
+                        self.fog_renderer
+                            .render_scattering(VolumetricFogScatteringContext {
+                                quad: &self.quad,
+                                camera,
+                                settings: volumetric_fog,
+                                ambient_color: ambient_color.xyz(),
+                                light: Some(VolumetricFogLight {
+                                    direction: emit_direction,
+                                    color: light.color.srgb_to_linear_f32().xyz() * light.intensity,
+                                    shadows: shadows_enabled
+                                        .then(|| (&self.csm_renderer, csm_options.shadow_bias())),
+                                }),
+                                uniform_buffer_cache,
+                                fallback_resources,
+                            })?;
+                    volumetric_fog_lit = true;
+                }
+            }
+
             // Light scattering should still be renderer no matter if there's no pixels lit by the
             // light source.
             if settings.light_scatter_enabled && light.scatter_enabled {
@@ -985,6 +1443,28 @@ impl DeferredLightRenderer {
             }
         }
 
+        if let Some(volumetric_fog) = volumetric_fog {
+            if !volumetric_fog_lit {
+                pass_stats +=
+                    self.fog_renderer
+                        .render_scattering(VolumetricFogScatteringContext {
+                            quad: &self.quad,
+                            camera,
+                            settings: volumetric_fog,
+                            ambient_color: ambient_color.xyz(),
+                            light: None,
+                            uniform_buffer_cache,
+                            fallback_resources,
+                        })?;
+            }
+            pass_stats += self.fog_renderer.integrate(
+                &self.quad,
+                volumetric_fog,
+                uniform_buffer_cache,
+                fallback_resources,
+            )?;
+        }
+
         Ok((pass_stats, light_stats))
     }
 }
@@ -0,0 +1,275 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! 2D lighting. Every frame the 2D lights of a scene are packed into a texture, and shadows of the
+//! shadow casting lights are calculated on CPU in polar space: for every light the circle around it
+//! is split into [`SHADOW_MAP_2D_SIZE`] directions and the distance to the closest occluder edge is
+//! found for every direction. The result is stored as a row of the shadow map texture, shaders
+//! compare the distance from a fragment to the light with the stored one (see `S_Lighting2D` shader
+//! function).
+
+use crate::{
+    core::{
+        algebra::{Vector2, Vector4},
+        array_as_u8_slice,
+    },
+    renderer::{
+        bundle::LightSource2D,
+        framework::{
+            error::FrameworkError,
+            gpu_texture::{GpuTexture, GpuTextureDescriptor, GpuTextureKind, PixelKind},
+            server::GraphicsServer,
+        },
+    },
+};
+use std::f32::consts::{PI, TAU};
+
+/// Maximum amount of 2D lights that could be rendered at once, the rest is ignored.
+pub const MAX_LIGHTS_2D: usize = 256;
+/// Maximum amount of 2D lights that could cast shadows at once, the rest is rendered without
+/// shadows.
+pub const MAX_SHADOW_CASTING_LIGHTS_2D: usize = 64;
+/// Amount of directions in a row of the polar shadow map. Must match `S_SHADOW_MAP_2D_SIZE` in
+/// shaders.
+pub const SHADOW_MAP_2D_SIZE: usize = 512;
+/// Amount of texels used to store parameters of a single light. Must match `S_LIGHT_2D_TEXELS` in
+/// shaders.
+const LIGHT_TEXELS: usize = 3;
+
+fn cross(a: Vector2<f32>, b: Vector2<f32>) -> f32 {
+    a.x * b.y - a.y * b.x
+}
+
+fn direction_index(angle: f32) -> usize {
+    (((angle + PI) / TAU * SHADOW_MAP_2D_SIZE as f32) as usize).min(SHADOW_MAP_2D_SIZE - 1)
+}
+
+/// Writes normalized distances to the closest occluder edges in every direction from the given
+/// light to the given row of the polar shadow map.
+fn calculate_shadow_row(light: &LightSource2D, edges: &[[Vector2<f32>; 2]], row: &mut [f32]) {
+    row.fill(1.0);
+
+    let radius = light.radius.max(f32::EPSILON);
+    for [begin, end] in edges {
+        let a = begin - light.position;
+        let b = end - light.position;
+        let edge = b - a;
+
+        // Skip the edges that are out of the reach of the light.
+        let length_sqr = edge.norm_squared();
+        let t = if length_sqr > f32::EPSILON {
+            (-a.dot(&edge) / length_sqr).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        if (a + edge * t).norm() > radius {
+            continue;
+        }
+
+        // Find the directions covered by the edge, going along the shortest arc.
+        let angle_a = a.y.atan2(a.x);
+        let mut span = b.y.atan2(b.x) - angle_a;
+        if span > PI {
+            span -= TAU;
+        } else if span < -PI {
+            span += TAU;
+        }
+        let start = if span >= 0.0 { angle_a } else { angle_a + span };
+        let first = direction_index(start);
+        let count = (span.abs() / TAU * SHADOW_MAP_2D_SIZE as f32).ceil() as usize + 2;
+
+        for k in 0..count.min(SHADOW_MAP_2D_SIZE) {
+            let index = (first + k) % SHADOW_MAP_2D_SIZE;
+            let angle = (index as f32 + 0.5) / SHADOW_MAP_2D_SIZE as f32 * TAU - PI;
+            let ray = Vector2::new(angle.cos(), angle.sin());
+            let denominator = cross(ray, edge);
+            if denominator.abs() <= f32::EPSILON {
+                continue;
+            }
+            // Solve `ray * distance = a + edge * s`.
+            let s = cross(a, ray) / denominator;
+            let distance = cross(a, edge) / denominator;
+            if !(0.0..=1.0).contains(&s) || distance < 0.0 {
+                continue;
+            }
+            let texel = &mut row[index];
+            *texel = texel.min(distance / radius);
+        }
+    }
+}
+
+/// 2D lights of a scene packed into textures, along with their polar shadow maps.
+pub struct Lights2D {
+    lights_texture: GpuTexture,
+    shadow_map: GpuTexture,
+    lights: Vec<Vector4<f32>>,
+    shadows: Vec<f32>,
+    light_count: usize,
+    shadow_caster_count: usize,
+}
+
+impl Lights2D {
+    /// Creates empty 2D lights.
+    pub fn new(server: &dyn GraphicsServer) -> Result<Self, FrameworkError> {
+        let empty_lights = vec![Vector4::<f32>::default(); LIGHT_TEXELS];
+        let empty_shadows = vec![1.0f32; SHADOW_MAP_2D_SIZE];
+        Ok(Self {
+            lights_texture: server.create_texture(GpuTextureDescriptor {
+                kind: GpuTextureKind::Rectangle {
+                    width: LIGHT_TEXELS,
+                    height: 1,
+                },
+                pixel_kind: PixelKind::RGBA32F,
+                data: Some(array_as_u8_slice(&empty_lights)),
+                ..Default::default()
+            })?,
+            shadow_map: server.create_texture(GpuTextureDescriptor {
+                kind: GpuTextureKind::Rectangle {
+                    width: SHADOW_MAP_2D_SIZE,
+                    height: 1,
+                },
+                pixel_kind: PixelKind::R32F,
+                data: Some(array_as_u8_slice(&empty_shadows)),
+                ..Default::default()
+            })?,
+            lights: Default::default(),
+            shadows: Default::default(),
+            light_count: 0,
+            shadow_caster_count: 0,
+        })
+    }
+
+    /// Returns the texture with the parameters of the lights.
+    pub fn lights_texture(&self) -> &GpuTexture {
+        &self.lights_texture
+    }
+
+    /// Returns the texture with the polar shadow maps, one row per shadow caster.
+    pub fn shadow_map(&self) -> &GpuTexture {
+        &self.shadow_map
+    }
+
+    /// Returns the amount of 2D lights, that were packed during the last update.
+    pub fn light_count(&self) -> usize {
+        self.light_count
+    }
+
+    /// Returns the amount of 2D lights with shadows, that were packed during the last update.
+    pub fn shadow_caster_count(&self) -> usize {
+        self.shadow_caster_count
+    }
+
+    /// Returns the row of the shadow map of the given shadow caster. Each texel of the row is the
+    /// distance to the closest occluder in the respective direction divided by the radius of the
+    /// light.
+    pub fn shadow_row(&self, shadow_caster: usize) -> Option<&[f32]> {
+        (shadow_caster < self.shadow_caster_count).then(|| {
+            &self.shadows
+                [shadow_caster * SHADOW_MAP_2D_SIZE..(shadow_caster + 1) * SHADOW_MAP_2D_SIZE]
+        })
+    }
+
+    /// Packs the given lights and calculates shadows for the ones that cast shadows.
+    pub fn update(
+        &mut self,
+        lights: &[LightSource2D],
+        occluder_edges: &[[Vector2<f32>; 2]],
+    ) -> Result<(), FrameworkError> {
+        self.lights.clear();
+        self.shadows.clear();
+        self.light_count = 0;
+        self.shadow_caster_count = 0;
+
+        for light in lights.iter().take(MAX_LIGHTS_2D) {
+            let shadow_row = if light.cast_shadows
+                && !occluder_edges.is_empty()
+                && self.shadow_caster_count < MAX_SHADOW_CASTING_LIGHTS_2D
+            {
+                let row_index = self.shadow_caster_count;
+                self.shadows
+                    .resize((row_index + 1) * SHADOW_MAP_2D_SIZE, 1.0);
+                calculate_shadow_row(
+                    light,
+                    occluder_edges,
+                    &mut self.shadows[row_index * SHADOW_MAP_2D_SIZE..],
+                );
+                self.shadow_caster_count += 1;
+                row_index as f32
+            } else {
+                -1.0
+            };
+
+            let color = light.color.srgb_to_linear_f32().xyz() * light.intensity;
+            let (half_hotspot_cone_angle_cos, half_cone_angle_cos) = if light.full_cone_angle >= TAU
+            {
+                (-1.0, -1.0)
+            } else {
+                (
+                    (light.hotspot_cone_angle * 0.5).cos(),
+                    (light.full_cone_angle * 0.5).cos(),
+                )
+            };
+            self.lights.extend([
+                Vector4::new(
+                    light.position.x,
+                    light.position.y,
+                    light.radius,
+                    light.height,
+                ),
+                color.push(shadow_row),
+                Vector4::new(
+                    light.direction.x,
+                    light.direction.y,
+                    half_hotspot_cone_angle_cos,
+                    half_cone_angle_cos,
+                ),
+            ]);
+            self.light_count += 1;
+        }
+
+        if self.lights.is_empty() {
+            self.lights.resize(LIGHT_TEXELS, Vector4::default());
+        }
+        self.lights_texture.set_data(
+            GpuTextureKind::Rectangle {
+                width: self.lights.len(),
+                height: 1,
+            },
+            PixelKind::RGBA32F,
+            1,
+            Some(array_as_u8_slice(&self.lights)),
+        )?;
+
+        if self.shadows.is_empty() {
+            self.shadows.resize(SHADOW_MAP_2D_SIZE, 1.0);
+        }
+        self.shadow_map.set_data(
+            GpuTextureKind::Rectangle {
+                width: SHADOW_MAP_2D_SIZE,
+                height: self.shadows.len() / SHADOW_MAP_2D_SIZE,
+            },
+            PixelKind::R32F,
+            1,
+            Some(array_as_u8_slice(&self.shadows)),
+        )?;
+
+        Ok(())
+    }
+}
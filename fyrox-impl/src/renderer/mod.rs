@@ -32,27 +32,39 @@ pub mod framework;
 
 pub mod bundle;
 pub mod cache;
+pub mod cluster;
 pub mod debug_renderer;
+pub mod light_2d;
+pub mod outline;
+pub mod software;
 pub mod storage;
 pub mod ui_renderer;
 pub mod visibility;
 
+mod atmosphere;
 mod bloom;
+mod fog;
 mod forward_renderer;
 mod fxaa;
 mod gbuffer;
 mod hdr;
+mod ibl;
 mod light;
 mod light_volume;
+mod motion_blur;
 mod occlusion;
+mod post_effect;
 mod shadow;
 mod ssao;
+mod ssr;
 mod stats;
+mod taa;
+mod velocity;
 
 use crate::{
     asset::{event::ResourceEvent, manager::ResourceManager},
     core::{
-        algebra::{Matrix4, Vector2, Vector3, Vector4},
+        algebra::{Matrix4, UnitQuaternion, Vector2, Vector3, Vector4},
         array_as_u8_slice,
         color::Color,
         instant,
@@ -64,7 +76,7 @@ use crate::{
         uuid_provider,
     },
     engine::error::EngineError,
-    graph::SceneGraph,
+    graph::{BaseSceneGraph, SceneGraph},
     gui::draw::DrawingContext,
     material::shader::{Shader, ShaderDefinition},
     renderer::{
@@ -79,11 +91,14 @@ use crate::{
             uniform::{UniformBufferCache, UniformMemoryAllocator},
         },
         debug_renderer::DebugRenderer,
+        fog::FogRenderContext,
         forward_renderer::{ForwardRenderContext, ForwardRenderer},
         framework::{
             buffer::{BufferKind, BufferUsage, GpuBuffer},
             error::FrameworkError,
-            framebuffer::{Attachment, AttachmentKind, DrawCallStatistics, GpuFrameBuffer},
+            framebuffer::{
+                Attachment, AttachmentKind, DrawCallStatistics, GpuFrameBuffer, ReadTarget,
+            },
             geometry_buffer::GpuGeometryBuffer,
             gpu_program::SamplerFallback,
             gpu_texture::{GpuTexture, GpuTextureDescriptor, GpuTextureKind, PixelKind},
@@ -92,18 +107,32 @@ use crate::{
         },
         fxaa::FxaaRenderer,
         gbuffer::{GBuffer, GBufferRenderContext},
-        hdr::HighDynamicRangeRenderer,
+        hdr::{
+            dof::{DepthOfFieldRenderContext, DepthOfFieldRenderer},
+            HighDynamicRangeRenderer,
+        },
         light::{DeferredLightRenderer, DeferredRendererContext},
+        motion_blur::{MotionBlurRenderContext, MotionBlurRenderer},
+        post_effect::{render_material_effect, PostEffectRenderContext, PostEffectRenderer},
+        ssr::{ScreenSpaceReflectionsRenderer, SsrRenderContext},
+        taa::{TaaRenderContext, TemporalAntiAliasingRenderer},
         ui_renderer::{UiRenderContext, UiRenderer},
         visibility::VisibilityCache,
     },
-    resource::texture::{Texture, TextureKind, TextureResource},
+    resource::texture::{
+        Texture, TextureKind, TexturePixelKind, TextureResource, TextureResourceExtension,
+    },
     scene::{
-        camera::Camera,
+        base::BaseBuilder,
+        camera::{Camera, CameraBuilder, Exposure, PerspectiveProjection, Projection, SkyBoxKind},
         mesh::{
             buffer::{BytesStorage, TriangleBuffer, VertexAttributeDescriptor, VertexBuffer},
             surface::{SurfaceData, SurfaceResource},
         },
+        node::Node,
+        post_processing::{PostEffect, PostEffectStage},
+        probe::ReflectionProbe,
+        transform::TransformBuilder,
         Scene, SceneContainer,
     },
 };
@@ -117,7 +146,7 @@ use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
 pub use stats::*;
 use std::{
-    any::TypeId, cell::RefCell, collections::hash_map::Entry, hash::Hash, rc::Rc,
+    any::TypeId, cell::RefCell, collections::hash_map::Entry, hash::Hash, path::Path, rc::Rc,
     sync::mpsc::Receiver,
 };
 use strum_macros::{AsRefStr, EnumString, VariantNames};
@@ -167,6 +196,41 @@ pub enum ShadowMapPrecision {
 
 uuid_provider!(ShadowMapPrecision = "f9b2755b-248e-46ba-bcab-473eac1acdb8");
 
+/// Defines how occlusion culling finds objects, that are hidden behind other objects.
+#[derive(
+    Copy,
+    Clone,
+    Hash,
+    PartialOrd,
+    PartialEq,
+    Eq,
+    Ord,
+    Debug,
+    Default,
+    Serialize,
+    Deserialize,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+)]
+pub enum OcclusionCullingMode {
+    /// Bounding boxes of objects are rasterized against the depth of the previous frame, the
+    /// results are gathered per screen tile and read back from GPU. Visibility is cached in a
+    /// volumetric grid around the observer, so the results are reused while the observer stays in
+    /// the same cell.
+    #[default]
+    Tiled,
+    /// A depth pyramid (Hi-Z), where every texel contains the farthest depth of the area that it
+    /// covers, is built on GPU from the depth of the previous frame and read back asynchronously.
+    /// Bounding boxes of objects are then tested against the pyramid on CPU, which takes constant
+    /// amount of texel fetches per object. The results lag behind the camera by a few frames, so
+    /// objects may appear with a small delay when they become visible.
+    HierarchicalZ,
+}
+
+uuid_provider!(OcclusionCullingMode = "2c1f5d0b-7e5c-4b89-9f0c-8d9a3c6e4b21");
+
 /// Cascaded-shadow maps settings.
 #[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize, Reflect, Eq)]
 pub struct CsmSettings {
@@ -182,6 +246,18 @@ pub struct CsmSettings {
 
     /// Whether to use Percentage-Closer Filtering or not.
     pub pcf: bool,
+
+    /// Whether to use percentage-closer soft shadows or not. Unlike fixed-size PCF, the width of
+    /// the penumbra depends on the distance between an occluder and a receiver and on the size of
+    /// the light source (see [`crate::scene::light::BaseLight::set_source_size`]). Overrides
+    /// [`Self::pcf`] when enabled.
+    #[serde(default)]
+    pub pcss: bool,
+
+    /// Whether to add screen-space contact shadows to the shadows of directional lights or not.
+    /// Parameters of the contact shadows are taken from [`QualitySettings::contact_shadows`].
+    #[serde(default)]
+    pub contact_shadows: bool,
 }
 
 impl Default for CsmSettings {
@@ -191,6 +267,111 @@ impl Default for CsmSettings {
             size: 2048,
             precision: ShadowMapPrecision::Full,
             pcf: true,
+            pcss: false,
+            contact_shadows: false,
+        }
+    }
+}
+
+/// Screen-space reflections settings.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize, Reflect)]
+pub struct SsrSettings {
+    /// Whether screen-space reflections enabled or not.
+    pub enabled: bool,
+
+    /// Maximum amount of ray marching steps per pixel. The more steps the more precise reflections
+    /// are, but the slower the effect is.
+    pub max_steps: usize,
+
+    /// Maximum length of a reflected ray in world units.
+    pub max_distance: f32,
+
+    /// Depth range (in world units) behind the surface in which a ray is considered as intersecting
+    /// it. Too low values cause gaps in reflections, too high values cause reflections of objects
+    /// that should be occluded.
+    pub thickness: f32,
+
+    /// Surfaces with roughness above this value do not trace rays and reflect only the environment
+    /// (the environment map of the camera or reflection probes).
+    pub max_roughness: f32,
+
+    /// Whether to blur reflections on rough surfaces or not.
+    pub blur: bool,
+}
+
+impl Default for SsrSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_steps: 32,
+            max_distance: 20.0,
+            thickness: 0.5,
+            max_roughness: 0.7,
+            blur: true,
+        }
+    }
+}
+
+/// Screen-space contact shadows settings. Contact shadows are calculated by marching a short ray
+/// from every pixel towards a light source through the depth buffer. They add small-scale shadows,
+/// that shadow maps cannot capture because of their limited resolution and depth bias, so objects
+/// do not look detached from the surfaces they stand on.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize, Reflect)]
+pub struct ContactShadowsSettings {
+    /// Whether contact shadows of point and spot lights are enabled or not. Directional lights
+    /// use [`CsmSettings::contact_shadows`] instead.
+    pub enabled: bool,
+
+    /// Length of the ray in world units. Contact shadows are meant for small details, long rays
+    /// cause artifacts at the edges of the screen.
+    pub max_distance: f32,
+
+    /// Amount of ray marching steps per pixel.
+    pub step_count: usize,
+
+    /// Depth range (in world units) behind a surface in which the ray is considered as occluded
+    /// by it.
+    pub thickness: f32,
+}
+
+impl Default for ContactShadowsSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_distance: 0.25,
+            step_count: 16,
+            thickness: 0.05,
+        }
+    }
+}
+
+/// Motion blur settings.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize, Reflect)]
+pub struct MotionBlurSettings {
+    /// Whether motion blur is enabled or not.
+    pub enabled: bool,
+
+    /// Angle (in degrees) of the virtual rotary shutter of the camera. It defines the fraction of
+    /// the frame time during which the shutter is open, 360 degrees means the whole frame time,
+    /// 180 degrees means a half of it. The larger the angle, the longer the blur.
+    pub shutter_angle: f32,
+
+    /// Maximum length of the blur in pixels. It cannot exceed the size of the tiles used to
+    /// classify the screen, which is 20 pixels.
+    pub max_blur_radius: f32,
+
+    /// Amount of samples taken along the motion vector of each pixel. The more samples, the
+    /// smoother the blur is, but the slower the effect is.
+    pub sample_count: usize,
+}
+
+impl Default for MotionBlurSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            shutter_angle: 180.0,
+            max_blur_radius: 20.0,
+            sample_count: 12,
         }
     }
 }
@@ -204,6 +385,10 @@ pub struct QualitySettings {
     pub point_shadow_map_size: usize,
     /// Use or not percentage close filtering (smoothing) for point shadows.
     pub point_soft_shadows: bool,
+    /// Whether to use percentage-closer soft shadows for point lights or not. See
+    /// [`Self::spot_pcss`] for more info. Overrides [`Self::point_soft_shadows`] when enabled.
+    #[serde(default)]
+    pub point_pcss: bool,
     /// Point shadows enabled or not.
     pub point_shadows_enabled: bool,
     /// Maximum distance from camera to draw shadows.
@@ -221,6 +406,12 @@ pub struct QualitySettings {
     pub spot_shadow_map_size: usize,
     /// Use or not percentage close filtering (smoothing) for spot shadows.
     pub spot_soft_shadows: bool,
+    /// Whether to use percentage-closer soft shadows for spot lights or not. The width of the
+    /// penumbra depends on the distance between an occluder and a receiver and on the size of
+    /// the light source (see [`crate::scene::light::BaseLight::set_source_size`]). Overrides
+    /// [`Self::spot_soft_shadows`] when enabled.
+    #[serde(default)]
+    pub spot_pcss: bool,
     /// Spot shadows enabled or not.
     pub spot_shadows_enabled: bool,
     /// Maximum distance from camera to draw shadows.
@@ -235,12 +426,20 @@ pub struct QualitySettings {
     /// Cascaded-shadow maps settings.
     pub csm_settings: CsmSettings,
 
+    /// Screen-space contact shadows settings.
+    #[serde(default)]
+    pub contact_shadows: ContactShadowsSettings,
+
     /// Whether to use screen space ambient occlusion or not.
     pub use_ssao: bool,
     /// Radius of sampling hemisphere used in SSAO, it defines much ambient
     /// occlusion will be in your scene.
     pub ssao_radius: f32,
 
+    /// Screen-space reflections settings.
+    #[serde(default)]
+    pub ssr_settings: SsrSettings,
+
     /// Global switch to enable or disable light scattering. Each light can have
     /// its own scatter switch, but this one is able to globally disable scatter.
     pub light_scatter_enabled: bool,
@@ -248,6 +447,17 @@ pub struct QualitySettings {
     /// Whether to use Fast Approximate AntiAliasing or not.
     pub fxaa: bool,
 
+    /// Whether to use Temporal AntiAliasing or not. TAA accumulates jittered frames over time, which
+    /// gives much more stable image on thin geometry and specular highlights than FXAA, at the cost
+    /// of slight blur and ghosting on fast-moving objects. It could be combined with FXAA.
+    #[serde(default)]
+    pub use_taa: bool,
+
+    /// Per-object and camera motion blur settings. The blur uses the velocity buffer, so it
+    /// accounts for the movement of objects as well as for the animation of skinned meshes.
+    #[serde(default)]
+    pub motion_blur_settings: MotionBlurSettings,
+
     /// Whether to use Parallax Mapping or not.
     pub use_parallax_mapping: bool,
 
@@ -259,10 +469,26 @@ pub struct QualitySettings {
     #[serde(default)]
     pub use_occlusion_culling: bool,
 
+    /// Defines how occlusion culling for geometry works, see [`OcclusionCullingMode`] docs for
+    /// more info. It is used only if [`Self::use_occlusion_culling`] is set.
+    #[serde(default)]
+    pub occlusion_culling_mode: OcclusionCullingMode,
+
     /// Whether to use occlusion culling for light sources or not. Warning: this is experimental
     /// feature that may have bugs and unstable behavior. Disabled by default.
     #[serde(default)]
     pub use_light_occlusion_culling: bool,
+
+    /// Whether to use clustered lighting or not. Point and spot lights without shadows and cookies
+    /// are binned into the clusters of the view frustum and shaded in a single full-screen pass,
+    /// instead of a separate pass per light. This is much faster for scenes with many small lights.
+    /// Forward passes use the same clusters, so transparent objects are lit by all the lights.
+    ///
+    /// Clustered lighting is opt-in: it is disabled in every preset, so the default settings and
+    /// the settings loaded from older files (where the field is missing) use the same per-light
+    /// path.
+    #[serde(default)]
+    pub use_clustered_lighting: bool,
 }
 
 impl Default for QualitySettings {
@@ -278,17 +504,27 @@ impl QualitySettings {
             point_shadow_map_size: 2048,
             point_shadows_distance: 20.0,
             point_shadows_enabled: true,
+            point_pcss: true,
             point_soft_shadows: true,
             point_shadows_fade_out_range: 1.0,
 
             spot_shadow_map_size: 2048,
             spot_shadows_distance: 20.0,
             spot_shadows_enabled: true,
+            spot_pcss: true,
             spot_soft_shadows: true,
             spot_shadows_fade_out_range: 1.0,
 
             use_ssao: true,
             ssao_radius: 0.5,
+            ssr_settings: SsrSettings {
+                enabled: true,
+                max_steps: 64,
+                max_distance: 30.0,
+                thickness: 0.5,
+                max_roughness: 0.8,
+                blur: true,
+            },
 
             light_scatter_enabled: true,
 
@@ -296,15 +532,30 @@ impl QualitySettings {
             spot_shadow_map_precision: ShadowMapPrecision::Full,
 
             fxaa: true,
+            use_taa: false,
+            motion_blur_settings: MotionBlurSettings::default(),
 
             use_bloom: true,
 
             use_parallax_mapping: true,
 
-            csm_settings: Default::default(),
+            csm_settings: CsmSettings {
+                enabled: true,
+                size: 2048,
+                precision: ShadowMapPrecision::Full,
+                pcf: true,
+                pcss: true,
+                contact_shadows: true,
+            },
+            contact_shadows: ContactShadowsSettings {
+                enabled: true,
+                ..Default::default()
+            },
 
             use_occlusion_culling: false,
+            occlusion_culling_mode: Default::default(),
             use_light_occlusion_culling: false,
+            use_clustered_lighting: false,
         }
     }
 
@@ -314,17 +565,27 @@ impl QualitySettings {
             point_shadow_map_size: 1024,
             point_shadows_distance: 15.0,
             point_shadows_enabled: true,
+            point_pcss: false,
             point_soft_shadows: true,
             point_shadows_fade_out_range: 1.0,
 
             spot_shadow_map_size: 1024,
             spot_shadows_distance: 15.0,
             spot_shadows_enabled: true,
+            spot_pcss: false,
             spot_soft_shadows: true,
             spot_shadows_fade_out_range: 1.0,
 
             use_ssao: true,
             ssao_radius: 0.5,
+            ssr_settings: SsrSettings {
+                enabled: false,
+                max_steps: 32,
+                max_distance: 20.0,
+                thickness: 0.5,
+                max_roughness: 0.7,
+                blur: true,
+            },
 
             light_scatter_enabled: true,
 
@@ -332,6 +593,8 @@ impl QualitySettings {
             spot_shadow_map_precision: ShadowMapPrecision::Full,
 
             fxaa: true,
+            use_taa: false,
+            motion_blur_settings: MotionBlurSettings::default(),
 
             use_bloom: true,
 
@@ -342,10 +605,15 @@ impl QualitySettings {
                 size: 2048,
                 precision: ShadowMapPrecision::Full,
                 pcf: true,
+                pcss: false,
+                contact_shadows: false,
             },
+            contact_shadows: Default::default(),
 
             use_occlusion_culling: false,
+            occlusion_culling_mode: Default::default(),
             use_light_occlusion_culling: false,
+            use_clustered_lighting: false,
         }
     }
 
@@ -355,17 +623,27 @@ impl QualitySettings {
             point_shadow_map_size: 512,
             point_shadows_distance: 5.0,
             point_shadows_enabled: true,
+            point_pcss: false,
             point_soft_shadows: false,
             point_shadows_fade_out_range: 1.0,
 
             spot_shadow_map_size: 512,
             spot_shadows_distance: 5.0,
             spot_shadows_enabled: true,
+            spot_pcss: false,
             spot_soft_shadows: false,
             spot_shadows_fade_out_range: 1.0,
 
             use_ssao: true,
             ssao_radius: 0.5,
+            ssr_settings: SsrSettings {
+                enabled: false,
+                max_steps: 24,
+                max_distance: 15.0,
+                thickness: 0.5,
+                max_roughness: 0.6,
+                blur: false,
+            },
 
             light_scatter_enabled: false,
 
@@ -373,6 +651,8 @@ impl QualitySettings {
             spot_shadow_map_precision: ShadowMapPrecision::Half,
 
             fxaa: true,
+            use_taa: false,
+            motion_blur_settings: MotionBlurSettings::default(),
 
             use_bloom: true,
 
@@ -383,10 +663,15 @@ impl QualitySettings {
                 size: 512,
                 precision: ShadowMapPrecision::Full,
                 pcf: false,
+                pcss: false,
+                contact_shadows: false,
             },
+            contact_shadows: Default::default(),
 
             use_occlusion_culling: false,
+            occlusion_culling_mode: Default::default(),
             use_light_occlusion_culling: false,
+            use_clustered_lighting: false,
         }
     }
 
@@ -396,17 +681,27 @@ impl QualitySettings {
             point_shadow_map_size: 1, // Zero is unsupported.
             point_shadows_distance: 0.0,
             point_shadows_enabled: false,
+            point_pcss: false,
             point_soft_shadows: false,
             point_shadows_fade_out_range: 1.0,
 
             spot_shadow_map_size: 1,
             spot_shadows_distance: 0.0,
             spot_shadows_enabled: false,
+            spot_pcss: false,
             spot_soft_shadows: false,
             spot_shadows_fade_out_range: 1.0,
 
             use_ssao: false,
             ssao_radius: 0.5,
+            ssr_settings: SsrSettings {
+                enabled: false,
+                max_steps: 16,
+                max_distance: 10.0,
+                thickness: 0.5,
+                max_roughness: 0.5,
+                blur: false,
+            },
 
             light_scatter_enabled: false,
 
@@ -414,6 +709,8 @@ impl QualitySettings {
             spot_shadow_map_precision: ShadowMapPrecision::Half,
 
             fxaa: false,
+            use_taa: false,
+            motion_blur_settings: MotionBlurSettings::default(),
 
             use_bloom: false,
 
@@ -424,10 +721,15 @@ impl QualitySettings {
                 size: 512,
                 precision: ShadowMapPrecision::Half,
                 pcf: false,
+                pcss: false,
+                contact_shadows: false,
             },
+            contact_shadows: Default::default(),
 
             use_occlusion_culling: false,
+            occlusion_culling_mode: Default::default(),
             use_light_occlusion_culling: false,
+            use_clustered_lighting: false,
         }
     }
 }
@@ -438,6 +740,7 @@ impl Statistics {
         self.frame_start_time = instant::Instant::now();
         self.geometry = Default::default();
         self.lighting = Default::default();
+        self.occlusion = Default::default();
     }
 
     /// Must be called before SwapBuffers but after all rendering is done.
@@ -474,6 +777,7 @@ impl Default for Statistics {
             pipeline: Default::default(),
             lighting: Default::default(),
             geometry: Default::default(),
+            occlusion: Default::default(),
             pure_frame_time: 0.0,
             capped_frame_time: 0.0,
             frames_per_second: 0,
@@ -510,6 +814,23 @@ pub struct AssociatedSceneData {
     /// bleeding effect (glow effect).
     pub bloom_renderer: BloomRenderer,
 
+    /// Temporal anti-aliasing renderer has to be created per scene, because it contains
+    /// accumulated frames of the scene.
+    pub taa_renderer: TemporalAntiAliasingRenderer,
+
+    /// Screen-space reflections renderer, it is sized to match the scene frame.
+    pub ssr_renderer: ScreenSpaceReflectionsRenderer,
+
+    /// Depth of field renderer, it is sized to match the scene frame.
+    pub dof_renderer: DepthOfFieldRenderer,
+
+    /// Motion blur renderer, it is sized to match the scene frame.
+    pub motion_blur_renderer: MotionBlurRenderer,
+
+    /// Renderer of material-based post effects of the camera, it is sized to match the scene
+    /// frame.
+    pub post_effect_renderer: PostEffectRenderer,
+
     /// Rendering statistics for a scene.
     pub statistics: SceneStatistics,
 }
@@ -577,6 +898,11 @@ impl AssociatedSceneData {
             gbuffer: GBuffer::new(server, width, height)?,
             hdr_renderer: HighDynamicRangeRenderer::new(server)?,
             bloom_renderer: BloomRenderer::new(server, width, height)?,
+            taa_renderer: TemporalAntiAliasingRenderer::new(server, width, height)?,
+            ssr_renderer: ScreenSpaceReflectionsRenderer::new(server, width, height)?,
+            dof_renderer: DepthOfFieldRenderer::new(server, width, height)?,
+            motion_blur_renderer: MotionBlurRenderer::new(server, width, height)?,
+            post_effect_renderer: PostEffectRenderer::new(server, width, height)?,
             hdr_scene_framebuffer,
             ldr_scene_framebuffer,
             ldr_temp_framebuffer,
@@ -653,6 +979,8 @@ pub struct FallbackResources {
     pub volume_dummy: GpuTexture,
     /// A stub uniform buffer for situation when there's no actual bone matrices.
     pub bone_matrices_stub_uniform_buffer: GpuBuffer,
+    /// A stub uniform buffer with identity instance matrix, that is used for non-instanced draw calls.
+    pub instance_matrices_stub_uniform_buffer: GpuBuffer,
     /// A sampler with the linear filtration that clamps incoming UVs to `[0;1]` range.
     pub linear_clamp_sampler: GpuSampler,
     /// A sampler with the linear filtration.
@@ -1088,6 +1416,17 @@ impl Renderer {
                 buffer.write_data(array_as_u8_slice(&zeros))?;
                 buffer
             },
+            instance_matrices_stub_uniform_buffer: {
+                let buffer = server.create_buffer(
+                    ShaderDefinition::MAX_INSTANCES_PER_DRAW_CALL * size_of::<Matrix4<f32>>(),
+                    BufferKind::Uniform,
+                    BufferUsage::StaticDraw,
+                )?;
+                let matrices =
+                    [Matrix4::<f32>::identity(); ShaderDefinition::MAX_INSTANCES_PER_DRAW_CALL];
+                buffer.write_data(array_as_u8_slice(&matrices))?;
+                buffer
+            },
             linear_clamp_sampler: server.create_sampler(GpuSamplerDescriptor {
                 min_filter: MinificationFilter::Linear,
                 mag_filter: MagnificationFilter::Linear,
@@ -1138,7 +1477,7 @@ impl Renderer {
             backbuffer_clear_color: Color::BLACK,
             texture_cache: Default::default(),
             geometry_cache: Default::default(),
-            forward_renderer: ForwardRenderer::new(),
+            forward_renderer: ForwardRenderer::new(&*server)?,
             ui_frame_buffers: Default::default(),
             fxaa_renderer: FxaaRenderer::new(&*server)?,
             statistics: Statistics::default(),
@@ -1377,6 +1716,8 @@ impl Renderer {
         self.update_texture_cache(dt);
         self.update_shader_cache(dt);
         self.geometry_cache.update(dt);
+        self.deferred_light_renderer.ibl_renderer.update(dt);
+        self.deferred_light_renderer.atmosphere_renderer.update(dt);
     }
 
     /// Unconditionally renders a scene and returns a reference to a [`AssociatedSceneData`] instance
@@ -1460,6 +1801,20 @@ impl Renderer {
             )?;
         }
 
+        scene_associated_data.statistics += self.deferred_light_renderer.prepare_atmosphere(
+            server,
+            scene_handle,
+            scene,
+            elapsed_time,
+            &mut self.uniform_buffer_cache,
+        )?;
+
+        if self.quality_settings.use_taa {
+            scene_associated_data.taa_renderer.begin_frame();
+        } else {
+            scene_associated_data.taa_renderer.reset_history();
+        }
+
         for (camera_handle, camera) in graph.pair_iter().filter_map(|(handle, node)| {
             if node.is_globally_enabled() {
                 if let Some(camera) = node.cast::<Camera>() {
@@ -1482,7 +1837,11 @@ impl Renderer {
                     z_near: camera.projection().z_near(),
                     z_far: camera.projection().z_far(),
                     view_matrix: camera.view_matrix(),
-                    projection_matrix: camera.projection_matrix(),
+                    projection_matrix: if self.quality_settings.use_taa {
+                        camera.jittered_projection_matrix()
+                    } else {
+                        camera.projection_matrix()
+                    },
                 },
                 GBUFFER_PASS_NAME.clone(),
                 RenderDataBundleStorageOptions {
@@ -1496,9 +1855,10 @@ impl Renderer {
                 scene.rendering_options.polygon_rasterization_mode,
             );
 
-            scene_associated_data.statistics +=
+            let (pass_stats, occlusion_stats) =
                 scene_associated_data.gbuffer.fill(GBufferRenderContext {
                     server,
+                    camera_handle,
                     camera,
                     geom_cache: &mut self.geometry_cache,
                     bundle_storage: &bundle_storage,
@@ -1513,6 +1873,9 @@ impl Renderer {
                     unit_quad: &self.quad,
                 })?;
 
+            scene_associated_data.statistics += pass_stats;
+            scene_associated_data.statistics += occlusion_stats;
+
             server.set_polygon_fill_mode(PolygonFace::FrontAndBack, PolygonFillMode::Fill);
 
             scene_associated_data.copy_depth_stencil_to_scene_framebuffer();
@@ -1534,6 +1897,7 @@ impl Renderer {
                     .render(DeferredRendererContext {
                         elapsed_time,
                         server,
+                        scene_handle,
                         scene,
                         camera,
                         gbuffer: &mut scene_associated_data.gbuffer,
@@ -1554,6 +1918,58 @@ impl Renderer {
             scene_associated_data.statistics += light_stats;
             scene_associated_data.statistics += pass_stats;
 
+            // Reflections are added on top of the lit opaque geometry, so transparent objects
+            // rendered by the forward renderer are not reflected and do not receive reflections.
+            if self.quality_settings.ssr_settings.enabled {
+                scene_associated_data.statistics +=
+                    scene_associated_data
+                        .ssr_renderer
+                        .render(SsrRenderContext {
+                            quad: &self.quad,
+                            viewport,
+                            camera,
+                            projection_matrix: if self.quality_settings.use_taa {
+                                camera.jittered_projection_matrix()
+                            } else {
+                                camera.projection_matrix()
+                            },
+                            gbuffer: &scene_associated_data.gbuffer,
+                            frame_buffer: &scene_associated_data.hdr_scene_framebuffer,
+                            settings: &self.quality_settings.ssr_settings,
+                            uniform_buffer_cache: &mut self.uniform_buffer_cache,
+                            fallback_resources: &self.fallback_resources,
+                        })?;
+            }
+
+            let fog = self.deferred_light_renderer.fog_render_data(
+                &scene.rendering_options.fog,
+                camera,
+                scene_handle,
+            );
+
+            // Fog is applied to the opaque geometry here, the forward renderer applies it to
+            // transparent objects by itself.
+            if let Some(fog) = fog {
+                scene_associated_data.statistics += self
+                    .deferred_light_renderer
+                    .fog_renderer
+                    .render(FogRenderContext {
+                        quad: &self.quad,
+                        viewport,
+                        camera,
+                        projection_matrix: if self.quality_settings.use_taa {
+                            camera.jittered_projection_matrix()
+                        } else {
+                            camera.projection_matrix()
+                        },
+                        depth_texture: scene_associated_data.gbuffer.depth(),
+                        frame_buffer: &scene_associated_data.hdr_scene_framebuffer,
+                        fog,
+                        uniform_buffer_cache: &mut self.uniform_buffer_cache,
+                        fallback_resources: &self.fallback_resources,
+                    })?;
+            }
+
             let depth = scene_associated_data.gbuffer.depth();
 
             scene_associated_data.statistics +=
@@ -1569,6 +1985,11 @@ impl Renderer {
                     fallback_resources: &self.fallback_resources,
                     scene_depth: depth,
                     ambient_light: scene.rendering_options.ambient_lighting_color,
+                    fog,
+                    light_clusters: self
+                        .quality_settings
+                        .use_clustered_lighting
+                        .then(|| self.deferred_light_renderer.light_clusters()),
                     uniform_memory_allocator: &mut self.uniform_memory_allocator,
                 })?;
 
@@ -1602,19 +2023,125 @@ impl Renderer {
 
             let quad = &self.quad;
 
-            // Prepare glow map.
-            scene_associated_data.statistics += scene_associated_data.bloom_renderer.render(
-                quad,
-                scene_associated_data.hdr_scene_frame_texture(),
-                &mut self.uniform_buffer_cache,
-                &self.fallback_resources,
-            )?;
+            // Resolve temporal anti-aliasing in HDR, so bloom and tone mapping get stable input.
+            let hdr_frame_texture = if self.quality_settings.use_taa {
+                scene_associated_data.statistics +=
+                    scene_associated_data
+                        .taa_renderer
+                        .render(TaaRenderContext {
+                            quad,
+                            viewport,
+                            camera,
+                            frame_texture: &scene_associated_data
+                                .hdr_scene_framebuffer
+                                .color_attachments()[0]
+                                .texture,
+                            depth_texture: scene_associated_data.gbuffer.depth(),
+                            velocity_texture: scene_associated_data.gbuffer.velocity_texture(),
+                            uniform_buffer_cache: &mut self.uniform_buffer_cache,
+                            fallback_resources: &self.fallback_resources,
+                        })?;
+                scene_associated_data.taa_renderer.result().clone()
+            } else {
+                scene_associated_data.hdr_scene_frame_texture().clone()
+            };
+
+            // Motion blur is applied after temporal anti-aliasing, otherwise the blurred edges
+            // would be clipped away by the history rectification.
+            let hdr_frame_texture = if self.quality_settings.motion_blur_settings.enabled {
+                scene_associated_data.statistics += scene_associated_data
+                    .motion_blur_renderer
+                    .render(MotionBlurRenderContext {
+                        quad,
+                        viewport,
+                        camera,
+                        settings: &self.quality_settings.motion_blur_settings,
+                        frame_texture: &hdr_frame_texture,
+                        depth_texture: scene_associated_data.gbuffer.depth(),
+                        velocity_texture: scene_associated_data.gbuffer.velocity_texture(),
+                        uniform_buffer_cache: &mut self.uniform_buffer_cache,
+                        fallback_resources: &self.fallback_resources,
+                    })?;
+                scene_associated_data.motion_blur_renderer.result().clone()
+            } else {
+                hdr_frame_texture
+            };
+
+            // Depth of field is applied before bloom and tone mapping, so bright out-of-focus
+            // spots keep their intensity and form bokeh.
+            let hdr_frame_texture = if camera.depth_of_field().enabled {
+                scene_associated_data.statistics +=
+                    scene_associated_data
+                        .dof_renderer
+                        .render(DepthOfFieldRenderContext {
+                            quad,
+                            viewport,
+                            camera,
+                            frame_texture: &hdr_frame_texture,
+                            depth_texture: scene_associated_data.gbuffer.depth(),
+                            uniform_buffer_cache: &mut self.uniform_buffer_cache,
+                            fallback_resources: &self.fallback_resources,
+                        })?;
+                scene_associated_data.dof_renderer.result().clone()
+            } else {
+                hdr_frame_texture
+            };
+
+            // Apply high dynamic range effects of the post-processing stack of the camera. Bloom
+            // only prepares the glow map here, it is added to the frame during tone mapping.
+            let mut hdr_frame_texture = hdr_frame_texture;
+            let mut bloom_intensity = None;
+            for effect in camera.post_processing().stage_effects(PostEffectStage::Hdr) {
+                match effect {
+                    PostEffect::Bloom(bloom) => {
+                        if !self.quality_settings.use_bloom || bloom_intensity.is_some() {
+                            continue;
+                        }
+
+                        scene_associated_data.statistics +=
+                            scene_associated_data.bloom_renderer.render(
+                                quad,
+                                &hdr_frame_texture,
+                                bloom.threshold,
+                                &mut self.uniform_buffer_cache,
+                                &self.fallback_resources,
+                            )?;
+                        bloom_intensity = Some(bloom.intensity);
+                    }
+                    PostEffect::Material(effect) => {
+                        scene_associated_data.statistics += scene_associated_data
+                            .post_effect_renderer
+                            .render_hdr(PostEffectRenderContext {
+                                server,
+                                quad,
+                                viewport,
+                                camera,
+                                elapsed_time,
+                                gbuffer: &scene_associated_data.gbuffer,
+                                material: &effect.material,
+                                frame_texture: &hdr_frame_texture,
+                                shader_cache: &mut self.shader_cache,
+                                texture_cache: &mut self.texture_cache,
+                                uniform_buffer_cache: &mut self.uniform_buffer_cache,
+                                fallback_resources: &self.fallback_resources,
+                            })?;
+                        hdr_frame_texture =
+                            scene_associated_data.post_effect_renderer.result().clone();
+                    }
+                    PostEffect::Fxaa => (),
+                }
+            }
 
             // Convert high dynamic range frame to low dynamic range (sRGB) with tone mapping and gamma correction.
             scene_associated_data.statistics += scene_associated_data.hdr_renderer.render(
                 server,
-                scene_associated_data.hdr_scene_frame_texture(),
-                scene_associated_data.bloom_renderer.result(),
+                &hdr_frame_texture,
+                if bloom_intensity.is_some() {
+                    scene_associated_data.bloom_renderer.result()
+                } else {
+                    &self.fallback_resources.black_dummy
+                },
+                bloom_intensity.unwrap_or_default(),
                 &scene_associated_data.ldr_scene_framebuffer,
                 viewport,
                 quad,
@@ -1627,17 +2154,45 @@ impl Renderer {
                 &self.fallback_resources,
             )?;
 
-            // Apply FXAA if needed.
-            if self.quality_settings.fxaa {
-                scene_associated_data.statistics += self.fxaa_renderer.render(
-                    viewport,
-                    scene_associated_data.ldr_scene_frame_texture(),
-                    &scene_associated_data.ldr_temp_framebuffer,
-                    &mut self.uniform_buffer_cache,
-                    &self.fallback_resources,
-                )?;
+            // Apply low dynamic range effects of the post-processing stack. Each effect is
+            // rendered to the temporary frame buffer and then copied back to the scene frame.
+            for effect in camera.post_processing().stage_effects(PostEffectStage::Ldr) {
+                match effect {
+                    PostEffect::Fxaa => {
+                        if !self.quality_settings.fxaa {
+                            continue;
+                        }
+
+                        scene_associated_data.statistics += self.fxaa_renderer.render(
+                            viewport,
+                            scene_associated_data.ldr_scene_frame_texture(),
+                            &scene_associated_data.ldr_temp_framebuffer,
+                            &mut self.uniform_buffer_cache,
+                            &self.fallback_resources,
+                        )?;
+                    }
+                    PostEffect::Material(effect) => {
+                        scene_associated_data.statistics += render_material_effect(
+                            PostEffectRenderContext {
+                                server,
+                                quad,
+                                viewport,
+                                camera,
+                                elapsed_time,
+                                gbuffer: &scene_associated_data.gbuffer,
+                                material: &effect.material,
+                                frame_texture: scene_associated_data.ldr_scene_frame_texture(),
+                                shader_cache: &mut self.shader_cache,
+                                texture_cache: &mut self.texture_cache,
+                                uniform_buffer_cache: &mut self.uniform_buffer_cache,
+                                fallback_resources: &self.fallback_resources,
+                            },
+                            &scene_associated_data.ldr_temp_framebuffer,
+                        )?;
+                    }
+                    PostEffect::Bloom(_) => continue,
+                }
 
-                let quad = &self.quad;
                 let temp_frame_texture = scene_associated_data.ldr_temp_frame_texture();
                 scene_associated_data.statistics += blit_pixels(
                     &mut self.uniform_buffer_cache,
@@ -1709,6 +2264,152 @@ impl Renderer {
         Ok(scene_associated_data)
     }
 
+    /// Renders the scene from the position of the given reflection probe into a cube map, saves the
+    /// cube map to the given path (in DDS format) and assigns it to the probe. The resulting texture
+    /// resource is registered in the resource manager, so the scene will reference it as a regular
+    /// external resource.
+    ///
+    /// Every active camera of the scene is temporarily disabled while baking, the skybox and the
+    /// environment map of the first active camera are used for the probe. The scene is rendered with
+    /// current quality settings of the renderer and the given exposure. Each face is rendered
+    /// separately, so [`Exposure::Auto`] could produce visible seams between the faces; use the
+    /// manual exposure of the camera the probe will be seen from, if possible.
+    pub fn bake_reflection_probe(
+        &mut self,
+        scene: &mut Scene,
+        probe: Handle<Node>,
+        exposure: Exposure,
+        resource_manager: &ResourceManager,
+        path: impl AsRef<Path>,
+    ) -> Result<TextureResource, FrameworkError> {
+        let probe_ref = scene
+            .graph
+            .try_get_of_type::<ReflectionProbe>(probe)
+            .ok_or_else(|| FrameworkError::Custom(format!("{probe} is not a reflection probe!")))?;
+        let resolution = probe_ref.resolution();
+        let position = probe_ref.global_position();
+        let projection = Projection::Perspective(PerspectiveProjection {
+            fov: std::f32::consts::FRAC_PI_2,
+            z_near: probe_ref.z_near(),
+            z_far: probe_ref.z_far(),
+        });
+
+        let mut skybox = None;
+        let mut environment = None;
+        let mut disabled_cameras = Vec::new();
+        for (handle, node) in scene.graph.pair_iter_mut() {
+            if let Some(camera) = node.cast_mut::<Camera>() {
+                if camera.is_enabled() {
+                    if disabled_cameras.is_empty() {
+                        skybox = camera.skybox_ref().cloned();
+                        environment = camera.environment_map();
+                    }
+                    camera.set_enabled(false);
+                    disabled_cameras.push(handle);
+                }
+            }
+        }
+
+        let prev_render_target = scene
+            .rendering_options
+            .render_target
+            .replace(TextureResource::new_render_target(resolution, resolution));
+
+        // The faces must match the layout of cube maps.
+        let faces = [
+            (Vector3::x(), -Vector3::y()),
+            (-Vector3::x(), -Vector3::y()),
+            (Vector3::y(), Vector3::z()),
+            (-Vector3::y(), -Vector3::z()),
+            (Vector3::z(), -Vector3::y()),
+            (-Vector3::z(), -Vector3::y()),
+        ];
+        let mut result = Ok(Vec::new());
+        for (look, up) in faces {
+            let mut builder = CameraBuilder::new(
+                BaseBuilder::new().with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(position)
+                        .with_local_rotation(UnitQuaternion::face_towards(&look, &up))
+                        .build(),
+                ),
+            )
+            .with_projection(projection.clone())
+            .with_exposure(exposure)
+            .with_specific_skybox(
+                skybox
+                    .clone()
+                    .map_or(SkyBoxKind::None, SkyBoxKind::Specific),
+            );
+            if let Some(environment) = environment.clone() {
+                builder = builder.with_environment(environment);
+            }
+            let camera = builder.build(&mut scene.graph);
+            scene.graph.update_hierarchical_data();
+            scene.graph[camera]
+                .as_camera_mut()
+                .calculate_matrices(Vector2::repeat(resolution as f32));
+
+            // Each face uses its own scene data, so temporal effects won't mix the faces.
+            let face = self
+                .render_scene(Handle::NONE, scene, 0.0, 0.0)
+                .and_then(|data| {
+                    data.ldr_scene_framebuffer
+                        .read_pixels(ReadTarget::Color(0))
+                        .ok_or_else(|| {
+                            FrameworkError::Custom("Unable to read the pixels of a face!".into())
+                        })
+                });
+            self.scene_data_map.remove(&Handle::NONE);
+            scene.graph.remove_node(camera);
+
+            match face {
+                Ok(pixels) => {
+                    if let Ok(bytes) = result.as_mut() {
+                        bytes.extend_from_slice(&pixels);
+                    }
+                }
+                Err(err) => {
+                    result = Err(err);
+                    break;
+                }
+            }
+        }
+
+        scene.rendering_options.render_target = prev_render_target;
+        for handle in disabled_cameras {
+            scene.graph[handle].as_camera_mut().set_enabled(true);
+        }
+
+        let mut bytes = result?;
+        // The alpha channel of the frame has no meaning for the probe.
+        for pixel in bytes.chunks_exact_mut(4) {
+            pixel[3] = 255;
+        }
+        let texture = Texture::from_bytes(
+            TextureKind::Cube {
+                width: resolution,
+                height: resolution,
+            },
+            TexturePixelKind::RGBA8,
+            bytes,
+        )
+        .ok_or_else(|| FrameworkError::Custom("Invalid size of the cube map!".into()))?;
+        let texture = TextureResource::new_ok(Uuid::new_v4(), ResourceKind::Embedded, texture);
+        texture
+            .save(path.as_ref())
+            .map_err(|err| FrameworkError::Custom(err.to_string()))?;
+        resource_manager
+            .register(texture.clone().into_untyped(), path)
+            .map_err(|err| FrameworkError::Custom(err.to_string()))?;
+
+        if let Some(probe) = scene.graph.try_get_mut_of_type::<ReflectionProbe>(probe) {
+            probe.set_environment(Some(texture.clone()));
+        }
+
+        Ok(texture)
+    }
+
     fn render_frame<'a>(
         &mut self,
         scenes: &SceneContainer,
@@ -1801,3 +2502,1070 @@ impl Renderer {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        asset::io::FsResourceIo,
+        material::{shader::ShaderResource, Material, MaterialResource},
+        renderer::{
+            framework::{
+                null::server::{DrawCallRecord, NullGraphicsServer, ResourceBindingRecord},
+                uniform::UniformReader,
+            },
+            outline::{OutlineGroup, OutlineMode, OutlineRenderPass},
+        },
+        scene::{
+            atmosphere::Atmosphere,
+            base::BaseBuilder,
+            camera::{CameraBuilder, DepthOfField},
+            dim2::{
+                light::{BaseLight2DBuilder, PointLightBuilder as PointLight2DBuilder},
+                occluder::{OccluderBuilder, OccluderShape},
+                rectangle::RectangleBuilder,
+            },
+            fog::{Fog, FogMode},
+            light::{
+                directional::DirectionalLightBuilder, point::PointLightBuilder,
+                spot::SpotLightBuilder, BaseLightBuilder,
+            },
+            mesh::{surface::SurfaceBuilder, Mesh, MeshBuilder},
+            post_processing::{MaterialEffect, PostProcessingStack},
+            probe::ReflectionProbeBuilder,
+        },
+    };
+    use std::sync::Arc;
+
+    fn test_renderer() -> (Rc<NullGraphicsServer>, Renderer) {
+        let server = NullGraphicsServer::new((64, 64));
+        let resource_manager =
+            ResourceManager::new(Arc::new(FsResourceIo), Arc::new(Default::default()));
+        let renderer = Renderer::new(server.clone(), (64, 64), &resource_manager).unwrap();
+        (server, renderer)
+    }
+
+    fn make_test_scene() -> Scene {
+        let mut scene = Scene::new();
+        CameraBuilder::new(BaseBuilder::new()).build(&mut scene.graph);
+        MeshBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                crate::scene::transform::TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 0.0, 3.0))
+                    .build(),
+            ),
+        )
+        .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_embedded(
+            SurfaceData::make_cube(Matrix4::identity()),
+        ))
+        .build()])
+        .build(&mut scene.graph);
+        scene
+            .graph
+            .update(Vector2::new(64.0, 64.0), 0.0, Default::default());
+        scene
+    }
+
+    /// Renders the scene and returns all the draw calls, that were issued while rendering it.
+    fn render(
+        server: &NullGraphicsServer,
+        renderer: &mut Renderer,
+        scene: &Scene,
+    ) -> Vec<DrawCallRecord> {
+        server.take_draw_calls();
+        renderer
+            .render_scene(Handle::new(0, 1), scene, 0.0, 0.0)
+            .unwrap();
+        server.take_draw_calls()
+    }
+
+    fn count(draw_calls: &[DrawCallRecord], program: &str) -> usize {
+        draw_calls
+            .iter()
+            .filter(|draw_call| draw_call.program == program)
+            .count()
+    }
+
+    /// Returns a reader of the uniform block, that was bound to the given binding point of the
+    /// draw call.
+    fn uniforms(draw_call: &DrawCallRecord, binding: usize) -> UniformReader<'_> {
+        draw_call
+            .bindings
+            .iter()
+            .find_map(|record| match record {
+                ResourceBindingRecord::Buffer {
+                    binding: buffer_binding,
+                    data,
+                    ..
+                } if *buffer_binding == binding => Some(UniformReader::new(data)),
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    /// Returns id of the texture, that was bound to the given binding point of the draw call.
+    fn texture(draw_call: &DrawCallRecord, binding: usize) -> u64 {
+        draw_call
+            .bindings
+            .iter()
+            .find_map(|record| match *record {
+                ResourceBindingRecord::Texture {
+                    binding: texture_binding,
+                    texture,
+                    ..
+                } if texture_binding == binding => Some(texture),
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_render_scene_with_null_server() {
+        let (server, mut renderer) = test_renderer();
+        let scene = make_test_scene();
+
+        server.invalidate_resource_bindings_cache();
+        let draw_calls = render(&server, &mut renderer, &scene);
+
+        let statistics = &renderer.scene_data_map[&Handle::new(0, 1)].statistics;
+        assert!(statistics.geometry.draw_calls > 0);
+        assert!(draw_calls.len() >= statistics.geometry.draw_calls);
+        assert!(statistics.pipeline.framebuffer_binding_changes > 0);
+        assert!(draw_calls
+            .iter()
+            .any(|draw_call| draw_call.params.depth_write));
+    }
+
+    #[test]
+    fn test_render_scene_with_taa() {
+        let (server, mut renderer) = test_renderer();
+        renderer
+            .set_quality_settings(&QualitySettings {
+                use_taa: true,
+                ..Default::default()
+            })
+            .unwrap();
+        let mut scene = make_test_scene();
+
+        let mut frames = Vec::new();
+        for _ in 0..3 {
+            let draw_calls = render(&server, &mut renderer, &scene);
+            assert!(count(&draw_calls, "Velocity_Primary") > 0);
+            assert_eq!(count(&draw_calls, "TAA_Primary"), 1);
+            let taa = draw_calls
+                .iter()
+                .find(|draw_call| draw_call.program == "TAA_Primary")
+                .unwrap();
+            let mut reader = uniforms(taa, 0);
+            for _ in 0..3 {
+                reader.read::<Matrix4<f32>>();
+            }
+            for _ in 0..3 {
+                reader.read::<Vector2<f32>>();
+            }
+            assert_eq!(reader.read::<f32>(), Some(0.9));
+            let history_valid = reader.read::<bool>().unwrap();
+            frames.push((taa.framebuffer, texture(taa, 1), history_valid));
+            scene
+                .graph
+                .update(Vector2::new(64.0, 64.0), 0.0, Default::default());
+        }
+
+        // History buffers are swapped every frame: the frame is accumulated into one of them, while
+        // the other one holds the result of the previous frame.
+        assert_ne!(frames[0].0, frames[1].0);
+        assert_eq!(frames[0].0, frames[2].0);
+        assert_ne!(frames[0].1, frames[1].1);
+        assert_eq!(frames[0].1, frames[2].1);
+        // There's nothing to accumulate in the first frame.
+        assert_eq!(
+            frames.iter().map(|frame| frame.2).collect::<Vec<_>>(),
+            [false, true, true]
+        );
+
+        // The camera must be jittered differently every frame.
+        let camera = scene
+            .graph
+            .linear_iter()
+            .find_map(|node| node.cast::<Camera>())
+            .unwrap();
+        assert_ne!(
+            camera.jittered_projection_matrix(),
+            camera.projection_matrix()
+        );
+    }
+
+    #[test]
+    fn test_velocity_history_per_camera() {
+        let (server, mut renderer) = test_renderer();
+        renderer
+            .set_quality_settings(&QualitySettings {
+                use_taa: true,
+                ..Default::default()
+            })
+            .unwrap();
+        let mut scene = make_test_scene();
+        CameraBuilder::new(BaseBuilder::new()).build(&mut scene.graph);
+        scene
+            .graph
+            .update(Vector2::new(64.0, 64.0), 0.0, Default::default());
+        let mesh = scene
+            .graph
+            .pair_iter()
+            .find_map(|(handle, node)| node.cast::<Mesh>().map(|_| handle))
+            .unwrap();
+
+        // Returns the translation along X axis of the current and the previous world matrices of
+        // the first instance for every velocity draw call.
+        let mut translations = |scene: &Scene| {
+            render(&server, &mut renderer, scene)
+                .iter()
+                .filter(|draw_call| draw_call.program == "Velocity_Primary")
+                .map(|draw_call| {
+                    let mut reader = uniforms(draw_call, 1);
+                    let current = reader.read_slice::<Matrix4<f32>>(127).unwrap();
+                    let previous = reader.read_slice::<Matrix4<f32>>(127).unwrap();
+                    (current[0][(0, 3)], previous[0][(0, 3)])
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(translations(&scene), [(0.0, 0.0), (0.0, 0.0)]);
+
+        scene.graph[mesh]
+            .local_transform_mut()
+            .set_position(Vector3::new(1.0, 0.0, 3.0));
+        scene
+            .graph
+            .update(Vector2::new(64.0, 64.0), 0.0, Default::default());
+
+        // Both cameras must see the previous position of the mesh.
+        assert_eq!(translations(&scene), [(1.0, 0.0), (1.0, 0.0)]);
+    }
+
+    #[test]
+    fn test_render_scene_with_ssr() {
+        let (server, mut renderer) = test_renderer();
+        let scene = make_test_scene();
+
+        // Bloom uses the same blur, so only the extra blur passes are counted.
+        let mut base_blur_passes = None;
+        for (enabled, blur) in [(false, false), (true, false), (true, true)] {
+            renderer
+                .set_quality_settings(&QualitySettings {
+                    ssr_settings: SsrSettings {
+                        enabled,
+                        blur,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .unwrap();
+
+            let draw_calls = render(&server, &mut renderer, &scene);
+            assert_eq!(
+                count(&draw_calls, "ScreenSpaceReflections_Primary"),
+                enabled as usize
+            );
+            assert_eq!(
+                count(&draw_calls, "ScreenSpaceReflectionsComposite_Primary"),
+                enabled as usize
+            );
+            let blur_passes = count(&draw_calls, "GaussianBlur_Primary");
+            let base_blur_passes = *base_blur_passes.get_or_insert(blur_passes);
+            assert_eq!(blur_passes - base_blur_passes, if blur { 2 } else { 0 });
+        }
+
+        // Only the presets with enabled reflections must render them, using the preset settings.
+        for settings in [
+            QualitySettings::ultra(),
+            QualitySettings::high(),
+            QualitySettings::medium(),
+            QualitySettings::low(),
+        ] {
+            renderer.set_quality_settings(&settings).unwrap();
+
+            let draw_calls = render(&server, &mut renderer, &scene);
+            let ssr = draw_calls
+                .iter()
+                .filter(|draw_call| draw_call.program == "ScreenSpaceReflections_Primary")
+                .collect::<Vec<_>>();
+            assert_eq!(ssr.len(), settings.ssr_settings.enabled as usize);
+            for draw_call in ssr {
+                let mut reader = uniforms(draw_call, 0);
+                for _ in 0..3 {
+                    reader.read::<Matrix4<f32>>();
+                }
+                // 3x3 matrix is stored as three columns aligned as 4D vectors.
+                for _ in 0..3 {
+                    reader.read::<Vector4<f32>>();
+                }
+                let settings = &settings.ssr_settings;
+                assert_eq!(reader.read::<i32>(), Some(settings.max_steps as i32));
+                assert_eq!(reader.read::<f32>(), Some(settings.max_distance));
+                assert_eq!(reader.read::<f32>(), Some(settings.thickness));
+                assert_eq!(reader.read::<f32>(), Some(settings.max_roughness));
+            }
+        }
+    }
+
+    #[test]
+    fn test_render_scene_with_fog() {
+        let (server, mut renderer) = test_renderer();
+        let mut scene = make_test_scene();
+        DirectionalLightBuilder::new(BaseLightBuilder::new(BaseBuilder::new()))
+            .build(&mut scene.graph);
+
+        for (enabled, volumetric) in [(false, false), (false, true), (true, false), (true, true)] {
+            let mut fog = Fog {
+                enabled,
+                mode: FogMode::Exponential { density: 0.25 },
+                ..Default::default()
+            };
+            fog.volumetric.enabled = volumetric;
+            scene.rendering_options.fog = fog;
+
+            let draw_calls = render(&server, &mut renderer, &scene);
+            assert_eq!(count(&draw_calls, "Fog_Primary"), enabled as usize);
+            let volumetric = enabled && volumetric;
+            assert_eq!(
+                count(&draw_calls, "VolumetricFogScattering_Primary"),
+                volumetric as usize
+            );
+            assert_eq!(
+                count(&draw_calls, "VolumetricFogIntegration_Primary"),
+                volumetric as usize
+            );
+
+            if let Some(draw_call) = draw_calls
+                .iter()
+                .find(|draw_call| draw_call.program == "Fog_Primary")
+            {
+                let mut reader = uniforms(draw_call, 0);
+                for _ in 0..2 {
+                    reader.read::<Matrix4<f32>>();
+                }
+                reader.read::<Vector3<f32>>();
+                assert_eq!(reader.read::<i32>(), Some(2));
+                reader.read::<Vector4<f32>>();
+                assert_eq!(
+                    reader.read::<Vector4<f32>>(),
+                    Some(Vector4::new(0.25, 0.0, 0.0, 0.0))
+                );
+                reader.read::<bool>();
+                reader.read::<f32>();
+                assert_eq!(reader.read::<bool>(), Some(volumetric));
+            }
+        }
+    }
+
+    #[test]
+    fn test_render_scene_with_depth_of_field() {
+        let (server, mut renderer) = test_renderer();
+        let mut scene = make_test_scene();
+
+        for enabled in [false, true] {
+            let depth_of_field = DepthOfField {
+                enabled,
+                focal_distance: 4.0,
+                max_blur_radius: 6.0,
+                ..Default::default()
+            };
+            scene
+                .graph
+                .linear_iter_mut()
+                .find_map(|node| node.cast_mut::<Camera>())
+                .unwrap()
+                .set_depth_of_field(depth_of_field.clone());
+
+            let draw_calls = render(&server, &mut renderer, &scene);
+            for name in [
+                "DepthOfFieldCoc_Primary",
+                "DepthOfFieldGather_Primary",
+                "DepthOfFieldComposite_Primary",
+            ] {
+                assert_eq!(count(&draw_calls, name), enabled as usize);
+            }
+
+            if let Some(draw_call) = draw_calls
+                .iter()
+                .find(|draw_call| draw_call.program == "DepthOfFieldCoc_Primary")
+            {
+                let mut reader = uniforms(draw_call, 0);
+                for _ in 0..2 {
+                    reader.read::<Matrix4<f32>>();
+                }
+                for _ in 0..3 {
+                    reader.read::<Vector2<f32>>();
+                }
+                // Circle of confusion at infinity is measured in pixels of the 64 pixels high frame.
+                let coc_scale =
+                    0.5 * depth_of_field.coc_scale() / DepthOfField::SENSOR_HEIGHT * 64.0;
+                assert_eq!(reader.read::<f32>(), Some(4.0));
+                assert_eq!(reader.read::<f32>(), Some(coc_scale));
+                assert_eq!(reader.read::<f32>(), Some(6.0));
+            }
+        }
+    }
+
+    #[test]
+    fn test_render_scene_with_motion_blur() {
+        let (server, mut renderer) = test_renderer();
+        let scene = make_test_scene();
+
+        for enabled in [false, true] {
+            renderer
+                .set_quality_settings(&QualitySettings {
+                    use_taa: false,
+                    motion_blur_settings: MotionBlurSettings {
+                        enabled,
+                        sample_count: 7,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .unwrap();
+
+            let draw_calls = render(&server, &mut renderer, &scene);
+            // The velocity buffer is required for motion blur even without TAA.
+            for name in [
+                "Velocity_Primary",
+                "MotionBlurVelocity_Primary",
+                "MotionBlurTileMax_Primary",
+                "MotionBlurNeighborMax_Primary",
+                "MotionBlur_Primary",
+            ] {
+                assert_eq!(count(&draw_calls, name) > 0, enabled);
+            }
+
+            if let Some(draw_call) = draw_calls
+                .iter()
+                .find(|draw_call| draw_call.program == "MotionBlur_Primary")
+            {
+                let mut reader = uniforms(draw_call, 0);
+                reader.read::<Matrix4<f32>>();
+                for _ in 0..3 {
+                    reader.read::<Vector2<f32>>();
+                }
+                reader.read::<i32>();
+                assert_eq!(reader.read::<i32>(), Some(7));
+            }
+        }
+    }
+
+    #[test]
+    fn test_render_scene_with_post_processing_stack() {
+        let (server, mut renderer) = test_renderer();
+        let mut scene = make_test_scene();
+
+        let shader = Shader::from_string(
+            r#"(
+                name: "TestPostEffect",
+                resources: [
+                    (
+                        name: "fyrox_sceneColor",
+                        kind: Texture(kind: Sampler2D, fallback: Black),
+                        binding: 0
+                    ),
+                    (
+                        name: "fyrox_sceneDepth",
+                        kind: Texture(kind: Sampler2D, fallback: White),
+                        binding: 1
+                    ),
+                    (
+                        name: "properties",
+                        kind: PropertyGroup([
+                            (name: "strength", kind: Float(value: 0.5)),
+                        ]),
+                        binding: 0
+                    ),
+                    (
+                        name: "fyrox_postEffectData",
+                        kind: PropertyGroup([]),
+                        binding: 1
+                    ),
+                ],
+                passes: [
+                    (
+                        name: "PostEffect",
+                        draw_parameters: DrawParameters(
+                            cull_face: None,
+                            color_write: ColorMask(red: true, green: true, blue: true, alpha: true),
+                            depth_write: false,
+                            stencil_test: None,
+                            depth_test: None,
+                            blend: None,
+                            stencil_op: StencilOp(
+                                fail: Keep,
+                                zfail: Keep,
+                                zpass: Keep,
+                                write_mask: 0xFFFF_FFFF,
+                            ),
+                            scissor_box: None
+                        ),
+                        vertex_shader: "void main() {}",
+                        fragment_shader: "void main() {}",
+                    )
+                ],
+            )"#,
+        )
+        .unwrap();
+        let material = MaterialResource::new_ok(
+            Uuid::new_v4(),
+            Default::default(),
+            Material::from_shader(ShaderResource::new_ok(
+                Uuid::new_v4(),
+                Default::default(),
+                shader,
+            )),
+        );
+
+        // Default stack contains bloom and FXAA.
+        let draw_calls = render(&server, &mut renderer, &scene);
+        assert_eq!(count(&draw_calls, "Bloom_Primary"), 1);
+        assert_eq!(count(&draw_calls, "FXAA_Primary"), 1);
+
+        let camera = scene
+            .graph
+            .linear_iter_mut()
+            .find_map(|node| node.cast_mut::<Camera>())
+            .unwrap();
+        camera.set_post_processing(PostProcessingStack {
+            effects: vec![
+                PostEffect::Material(MaterialEffect {
+                    material: material.clone(),
+                    stage: PostEffectStage::Hdr,
+                }),
+                PostEffect::Material(MaterialEffect {
+                    material: material.clone(),
+                    stage: PostEffectStage::Ldr,
+                }),
+                PostEffect::Material(MaterialEffect {
+                    material,
+                    stage: PostEffectStage::Hdr,
+                }),
+            ],
+        });
+
+        let draw_calls = render(&server, &mut renderer, &scene);
+        assert_eq!(count(&draw_calls, "TestPostEffect_PostEffect"), 3);
+        assert_eq!(count(&draw_calls, "Bloom_Primary"), 0);
+        assert_eq!(count(&draw_calls, "FXAA_Primary"), 0);
+    }
+
+    #[test]
+    fn test_render_scene_with_clustered_lighting() {
+        let (server, mut renderer) = test_renderer();
+        let mut scene = make_test_scene();
+
+        // Many small lights without shadows and one light with shadows.
+        let light_count = ShaderDefinition::MAX_LIGHTS * 3;
+        for i in 0..=light_count {
+            PointLightBuilder::new(BaseLightBuilder::new(
+                BaseBuilder::new()
+                    .with_cast_shadows(i == light_count)
+                    .with_local_transform(
+                        crate::scene::transform::TransformBuilder::new()
+                            .with_local_position(Vector3::new(
+                                (i % 8) as f32 * 0.25 - 1.0,
+                                (i / 8) as f32 * 0.25 - 1.0,
+                                2.0,
+                            ))
+                            .build(),
+                    ),
+            ))
+            .with_radius(1.0)
+            .build(&mut scene.graph);
+        }
+        scene
+            .graph
+            .update(Vector2::new(64.0, 64.0), 0.0, Default::default());
+
+        for use_clustered_lighting in [false, true] {
+            renderer
+                .set_quality_settings(&QualitySettings {
+                    use_clustered_lighting,
+                    ..Default::default()
+                })
+                .unwrap();
+
+            let draw_calls = render(&server, &mut renderer, &scene);
+            let statistics = &renderer.scene_data_map[&Handle::new(0, 1)].statistics;
+
+            if use_clustered_lighting {
+                // All the lights without shadows are shaded by a single pass.
+                assert_eq!(count(&draw_calls, "DeferredClusteredLight_Primary"), 1);
+                assert_eq!(count(&draw_calls, "DeferredPointLight_Primary"), 1);
+                assert_eq!(statistics.lighting.clustered_lights_rendered, light_count);
+                assert_eq!(
+                    renderer
+                        .deferred_light_renderer
+                        .light_clusters()
+                        .light_count(),
+                    light_count + 1
+                );
+
+                // Clusters are distributed between the clipping planes of the camera.
+                let draw_call = draw_calls
+                    .iter()
+                    .find(|draw_call| draw_call.program == "DeferredClusteredLight_Primary")
+                    .unwrap();
+                let mut reader = uniforms(draw_call, 0);
+                for _ in 0..4 {
+                    reader.read::<Matrix4<f32>>();
+                }
+                reader.read::<Vector3<f32>>();
+                let projection = scene
+                    .graph
+                    .linear_iter()
+                    .find_map(|node| node.cast::<Camera>())
+                    .unwrap()
+                    .projection();
+                assert_eq!(reader.read::<f32>(), Some(projection.z_near()));
+                assert_eq!(reader.read::<f32>(), Some(projection.z_far()));
+            } else {
+                assert_eq!(count(&draw_calls, "DeferredClusteredLight_Primary"), 0);
+                assert_eq!(
+                    count(&draw_calls, "DeferredPointLight_Primary"),
+                    light_count + 1
+                );
+                assert_eq!(statistics.lighting.clustered_lights_rendered, 0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_render_scene_with_2d_lights() {
+        let (server, mut renderer) = test_renderer();
+        let mut scene = Scene::new();
+        CameraBuilder::new(BaseBuilder::new()).build(&mut scene.graph);
+        let transform = |x: f32| {
+            crate::scene::transform::TransformBuilder::new()
+                .with_local_position(Vector3::new(x, 0.0, 3.0))
+                .build()
+        };
+        RectangleBuilder::new(BaseBuilder::new().with_local_transform(transform(0.0)))
+            .build(&mut scene.graph);
+        PointLight2DBuilder::new(
+            BaseLight2DBuilder::new(BaseBuilder::new().with_local_transform(transform(0.0)))
+                .with_radius(5.0)
+                .with_cast_shadows(true),
+        )
+        .build(&mut scene.graph);
+        // An occluder to the right of the light, its closest edge is 0.75 units away.
+        OccluderBuilder::new(BaseBuilder::new().with_local_transform(transform(1.0)))
+            .with_shape(OccluderShape::Rectangle {
+                size: Vector2::new(0.5, 2.0),
+            })
+            .build(&mut scene.graph);
+        scene
+            .graph
+            .update(Vector2::new(64.0, 64.0), 0.0, Default::default());
+
+        let draw_calls = render(&server, &mut renderer, &scene);
+        assert_eq!(count(&draw_calls, "Standard2DShader_Forward"), 1);
+        let draw_call = draw_calls
+            .iter()
+            .find(|draw_call| draw_call.program == "Standard2DShader_Forward")
+            .unwrap();
+        // Amount of the 2D lights to shade.
+        assert_eq!(uniforms(draw_call, 3).read::<i32>(), Some(1));
+
+        let lights_2d = renderer.forward_renderer.lights_2d();
+        assert_eq!(lights_2d.light_count(), 1);
+        assert_eq!(lights_2d.shadow_caster_count(), 1);
+        let row = lights_2d.shadow_row(0).unwrap();
+        // Direction along +X axis is blocked by the occluder, the opposite one is free.
+        let size = light_2d::SHADOW_MAP_2D_SIZE;
+        assert!((row[size / 2] - 0.75 / 5.0).abs() < 0.01);
+        assert_eq!(row[0], 1.0);
+    }
+
+    #[test]
+    fn test_render_scene_with_outline() {
+        let (server, mut renderer) = test_renderer();
+        let scene = make_test_scene();
+        let mesh = scene
+            .graph
+            .pair_iter()
+            .find_map(|(handle, node)| node.is_mesh().then_some(handle))
+            .unwrap();
+
+        let pass = Rc::new(RefCell::new(
+            OutlineRenderPass::new(&*renderer.server, TypeId::of::<Renderer>()).unwrap(),
+        ));
+        renderer.add_render_pass(pass.clone());
+
+        // Nothing to outline.
+        let draw_calls = render(&server, &mut renderer, &scene);
+        assert_eq!(count(&draw_calls, "Outline_Primary"), 0);
+        assert_eq!(count(&draw_calls, "StandardShader_Forward"), 0);
+
+        pass.borrow_mut()
+            .groups
+            .push(OutlineGroup::new(Color::RED).with_nodes([mesh]));
+        pass.borrow_mut().groups.push(
+            OutlineGroup::new(Color::GREEN)
+                .with_width(4.0)
+                .with_mode(OutlineMode::SeeThrough)
+                .with_nodes([mesh]),
+        );
+        // Every group draws its objects into the mask and then draws the outline itself.
+        let draw_calls = render(&server, &mut renderer, &scene);
+        assert_eq!(count(&draw_calls, "Outline_Primary"), 2);
+        assert_eq!(count(&draw_calls, "StandardShader_Forward"), 2);
+
+        // Every outline is drawn with the parameters of its group.
+        let outlines = draw_calls
+            .iter()
+            .filter(|draw_call| draw_call.program == "Outline_Primary")
+            .map(|draw_call| {
+                let mut reader = uniforms(draw_call, 0);
+                reader.read::<Matrix4<f32>>();
+                (
+                    reader.read::<Vector4<f32>>().unwrap(),
+                    reader.read::<f32>().unwrap(),
+                    reader.read::<bool>().unwrap(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            outlines,
+            [
+                (Color::RED.as_frgba(), 2.0, false),
+                (Color::GREEN.as_frgba(), 4.0, true)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_scene_with_soft_and_contact_shadows() {
+        let (server, mut renderer) = test_renderer();
+        let mut scene = make_test_scene();
+
+        let light_builder = || {
+            BaseLightBuilder::new(BaseBuilder::new().with_cast_shadows(true)).with_source_size(0.5)
+        };
+        PointLightBuilder::new(light_builder())
+            .with_radius(5.0)
+            .build(&mut scene.graph);
+        SpotLightBuilder::new(light_builder())
+            .with_distance(5.0)
+            .build(&mut scene.graph);
+        DirectionalLightBuilder::new(light_builder()).build(&mut scene.graph);
+        scene
+            .graph
+            .update(Vector2::new(64.0, 64.0), 0.0, Default::default());
+
+        for soft_shadows in [true, false] {
+            let mut settings = QualitySettings::ultra();
+            settings.point_pcss = soft_shadows;
+            settings.spot_pcss = soft_shadows;
+            settings.csm_settings.pcss = soft_shadows;
+            settings.csm_settings.contact_shadows = soft_shadows;
+            settings.contact_shadows.enabled = soft_shadows;
+            renderer.set_quality_settings(&settings).unwrap();
+
+            let draw_calls = render(&server, &mut renderer, &scene);
+
+            // Soft and contact shadows are resolved in the same pass as the light itself.
+            assert_eq!(count(&draw_calls, "DeferredPointLight_Primary"), 1);
+            assert_eq!(count(&draw_calls, "DeferredSpotLight_Primary"), 1);
+            assert_eq!(count(&draw_calls, "DeferredDirectionalLight_Primary"), 1);
+
+            let draw_call = draw_calls
+                .iter()
+                .find(|draw_call| draw_call.program == "DeferredPointLight_Primary")
+                .unwrap();
+            let mut reader = uniforms(draw_call, 0);
+            for _ in 0..3 {
+                reader.read::<Matrix4<f32>>();
+            }
+            reader.read::<Vector4<f32>>();
+            for _ in 0..2 {
+                reader.read::<Vector3<f32>>();
+            }
+            for _ in 0..4 {
+                reader.read::<f32>();
+            }
+            for _ in 0..2 {
+                reader.read::<bool>();
+            }
+            // Size of the light source, whether PCSS is used and whether contact shadows are used.
+            assert_eq!(reader.read::<f32>(), Some(0.5));
+            assert_eq!(reader.read::<bool>(), Some(soft_shadows));
+            assert_eq!(reader.read::<bool>(), Some(soft_shadows));
+        }
+    }
+
+    #[test]
+    fn test_render_scene_with_atmosphere() {
+        let (server, mut renderer) = test_renderer();
+        let mut scene = make_test_scene();
+        let sun = DirectionalLightBuilder::new(BaseLightBuilder::new(BaseBuilder::new()))
+            .build(&mut scene.graph);
+        scene.rendering_options.atmosphere = Atmosphere {
+            enabled: true,
+            sun,
+            ..Default::default()
+        };
+
+        // Returns the sun direction and the index of every rendered face of the sky together with
+        // the amount of drawn sky boxes.
+        let mut draw = |scene: &Scene| {
+            let draw_calls = render(&server, &mut renderer, scene);
+            let faces = draw_calls
+                .iter()
+                .filter(|draw_call| draw_call.program == "Atmosphere_Primary")
+                .map(|draw_call| {
+                    let mut reader = uniforms(draw_call, 0);
+                    reader.read::<Matrix4<f32>>();
+                    let sun_direction = reader.read::<Vector3<f32>>().unwrap();
+                    for _ in 0..3 {
+                        reader.read::<Vector3<f32>>();
+                    }
+                    reader.read::<Vector2<f32>>();
+                    (sun_direction, reader.read::<i32>().unwrap())
+                })
+                .collect::<Vec<_>>();
+            (faces, count(&draw_calls, "SkyBox_Primary"))
+        };
+        let face_indices = |faces: &[(Vector3<f32>, i32)]| {
+            faces.iter().map(|(_, index)| *index).collect::<Vec<_>>()
+        };
+
+        // Every face of the sky is rendered once and then reused, while the atmosphere is the same.
+        let (faces, sky_boxes) = draw(&scene);
+        assert_eq!(face_indices(&faces), [0, 1, 2, 3, 4, 5]);
+        assert_eq!(sky_boxes, 1);
+        let sun_direction = faces[0].0;
+        assert_eq!(draw(&scene), (vec![], 1));
+
+        // Rotation of the sun changes the time of day.
+        scene.graph[sun]
+            .local_transform_mut()
+            .set_rotation(UnitQuaternion::from_axis_angle(&Vector3::x_axis(), 1.0));
+        scene
+            .graph
+            .update(Vector2::new(64.0, 64.0), 0.0, Default::default());
+        let (faces, sky_boxes) = draw(&scene);
+        assert_eq!(face_indices(&faces), [0, 1, 2, 3, 4, 5]);
+        assert_eq!(sky_boxes, 1);
+        assert_ne!(faces[0].0, sun_direction);
+
+        scene.rendering_options.atmosphere.enabled = false;
+        assert_eq!(draw(&scene), (vec![], 1));
+    }
+
+    #[test]
+    fn test_render_scene_with_hierarchical_z_occlusion_culling() {
+        let (server, mut renderer) = test_renderer();
+        renderer
+            .set_quality_settings(&QualitySettings {
+                use_occlusion_culling: true,
+                occlusion_culling_mode: OcclusionCullingMode::HierarchicalZ,
+                ..QualitySettings::low()
+            })
+            .unwrap();
+        let scene = make_test_scene();
+
+        let mut draw = || {
+            let draw_calls = render(&server, &mut renderer, &scene);
+            let occlusion = renderer.scene_data_map[&Handle::new(0, 1)]
+                .statistics
+                .occlusion;
+            // The depth buffer is reduced to the size of each level of the pyramid.
+            let levels = draw_calls
+                .iter()
+                .filter(|draw_call| draw_call.program == "HiZDownsample_Primary")
+                .map(|draw_call| {
+                    let mut reader = uniforms(draw_call, 0);
+                    reader.read::<Matrix4<f32>>();
+                    reader.read::<Vector2<f32>>().unwrap()
+                })
+                .collect::<Vec<_>>();
+            (
+                count(&draw_calls, "StandardShader_GBuffer"),
+                levels,
+                occlusion.objects_tested,
+                occlusion.objects_culled,
+            )
+        };
+
+        let levels = vec![Vector2::new(64.0, 64.0)];
+        // There's no depth pyramid yet, so everything is visible.
+        assert_eq!(draw(), (1, levels.clone(), 1, 0));
+        // The null server returns zeroed depth, which is closer than any object, so the cube is
+        // considered occluded.
+        assert_eq!(draw(), (0, levels, 1, 1));
+    }
+
+    #[test]
+    fn test_render_instanced_bundle() {
+        let (server, mut renderer) = test_renderer();
+        renderer
+            .set_quality_settings(&QualitySettings {
+                use_taa: true,
+                ..Default::default()
+            })
+            .unwrap();
+        let mut scene = make_test_scene();
+
+        // Surfaces with the same data and material are put in the same bundle.
+        let data = SurfaceResource::new_embedded(SurfaceData::make_cube(Matrix4::identity()));
+        let material =
+            MaterialResource::new_ok(Uuid::new_v4(), Default::default(), Material::standard());
+        let instance_count = ShaderDefinition::MAX_INSTANCES_PER_DRAW_CALL + 45;
+        for i in 0..instance_count {
+            MeshBuilder::new(
+                BaseBuilder::new().with_local_transform(
+                    crate::scene::transform::TransformBuilder::new()
+                        .with_local_position(Vector3::new(0.0, 0.0, 3.0 + i as f32 * 0.01))
+                        .build(),
+                ),
+            )
+            .with_surfaces(vec![SurfaceBuilder::new(data.clone())
+                .with_material(material.clone())
+                .build()])
+            .build(&mut scene.graph);
+        }
+        scene
+            .graph
+            .update(Vector2::new(64.0, 64.0), 0.0, Default::default());
+
+        let draw_calls = render(&server, &mut renderer, &scene);
+        let instanced_draw_calls = |program: &str| {
+            draw_calls
+                .iter()
+                .filter(|draw_call| draw_call.program == program && draw_call.instance_count > 1)
+                .map(|draw_call| draw_call.instance_count)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            instanced_draw_calls("StandardShader_GBuffer"),
+            [ShaderDefinition::MAX_INSTANCES_PER_DRAW_CALL, 45]
+        );
+        // The velocity pass draws the same instances with its own limit of instances per draw call.
+        assert_eq!(instanced_draw_calls("Velocity_Primary"), [127, 127, 46]);
+
+        // Every instance must be drawn with its own world matrix.
+        let mut positions = draw_calls
+            .iter()
+            .filter(|draw_call| {
+                draw_call.program == "StandardShader_GBuffer" && draw_call.instance_count > 1
+            })
+            .flat_map(|draw_call| {
+                uniforms(draw_call, 7)
+                    .read_slice::<Matrix4<f32>>(draw_call.instance_count)
+                    .unwrap()
+            })
+            .map(|world_matrix| world_matrix[(2, 3)])
+            .collect::<Vec<_>>();
+        positions.sort_by(f32::total_cmp);
+        assert_eq!(
+            positions,
+            (0..instance_count)
+                .map(|i| 3.0 + i as f32 * 0.01)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_bake_reflection_probe() {
+        let (server, mut renderer) = test_renderer();
+        let resource_manager =
+            ResourceManager::new(Arc::new(FsResourceIo), Arc::new(Default::default()));
+        let mut scene = make_test_scene();
+        let probe = ReflectionProbeBuilder::new(BaseBuilder::new())
+            .with_resolution(8)
+            .build(&mut scene.graph);
+
+        let path = std::env::temp_dir().join("fyrox_bake_reflection_probe.dds");
+        let texture = renderer
+            .bake_reflection_probe(
+                &mut scene,
+                probe,
+                Exposure::Manual(std::f32::consts::E),
+                &resource_manager,
+                &path,
+            )
+            .unwrap();
+
+        assert!(matches!(
+            texture.data_ref().kind(),
+            TextureKind::Cube {
+                width: 8,
+                height: 8
+            }
+        ));
+        assert_eq!(
+            scene.graph[probe]
+                .as_reflection_probe()
+                .environment()
+                .cloned(),
+            Some(texture)
+        );
+        // Temporary cameras must be removed and the original one must be enabled back.
+        let cameras = scene
+            .graph
+            .linear_iter()
+            .filter_map(|node| node.cast::<Camera>())
+            .collect::<Vec<_>>();
+        assert_eq!(cameras.len(), 1);
+        assert!(cameras[0].is_enabled());
+        assert!(scene.rendering_options.render_target.is_none());
+
+        let loaded =
+            Texture::load_from_memory(&std::fs::read(path).unwrap(), Default::default()).unwrap();
+        assert_eq!(loaded.pixel_kind(), TexturePixelKind::RGBA8);
+        assert_eq!(loaded.data().len(), 6 * 8 * 8 * 4);
+
+        // The scene must be renderable with the baked probe.
+        let draw_calls = render(&server, &mut renderer, &scene);
+        assert_eq!(count(&draw_calls, "AmbientLight_Primary"), 1);
+    }
+
+    #[test]
+    fn test_environment_maps_generation() {
+        let (server, mut renderer) = test_renderer();
+        let mut scene = make_test_scene();
+
+        let environment = TextureResource::new_ok(
+            Uuid::new_v4(),
+            ResourceKind::Embedded,
+            Texture::from_bytes(
+                TextureKind::Cube {
+                    width: 16,
+                    height: 16,
+                },
+                TexturePixelKind::RGBA8,
+                vec![255; 6 * 16 * 16 * 4],
+            )
+            .unwrap(),
+        );
+        for node in scene.graph.linear_iter_mut() {
+            if let Some(camera) = node.cast_mut::<Camera>() {
+                camera.set_environment(Some(environment.clone()));
+            }
+        }
+
+        let mut draw = || {
+            let draw_calls = render(&server, &mut renderer, &scene);
+            [
+                count(&draw_calls, "IblBrdf_Primary"),
+                count(&draw_calls, "IblRadiance_Primary"),
+                count(&draw_calls, "IblIrradiance_Primary"),
+                count(&draw_calls, "IblPrefilter_Primary"),
+            ]
+        };
+
+        // 16x16 environment has 5 mips in its radiance map, prefiltered map has 6 mips, every mip
+        // has 6 faces.
+        assert_eq!(draw(), [1, 6 * 5, 6, 6 * 6]);
+        // The maps must be cached.
+        assert_eq!(draw(), [0, 0, 0, 0]);
+        // And regenerated when the environment changes.
+        environment.data_ref().modify();
+        assert_eq!(draw(), [0, 6 * 5, 6, 6 * 6]);
+    }
+}
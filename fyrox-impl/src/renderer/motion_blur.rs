@@ -0,0 +1,309 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Motion blur. The velocity of each pixel is taken from the velocity buffer (it accounts for the
+//! movement of objects and the animation of skinned meshes) or reconstructed from the depth and
+//! the camera matrices of the previous frame. The screen is split into tiles, and the dominant
+//! velocity of every tile and its neighbours is found, then each pixel gathers the samples along
+//! the dominant velocity of its neighbourhood, weighting them by depth and velocity. This way
+//! moving objects are blurred over the static background and vice versa.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Vector2},
+        math::Rect,
+        sstorage::ImmutableString,
+    },
+    renderer::{
+        cache::{
+            shader::{binding, property, PropertyGroup, RenderMaterial, RenderPassContainer},
+            uniform::UniformBufferCache,
+        },
+        framework::{
+            error::FrameworkError,
+            framebuffer::{Attachment, AttachmentKind, GpuFrameBuffer},
+            geometry_buffer::GpuGeometryBuffer,
+            gpu_texture::{GpuTexture, PixelKind},
+            server::GraphicsServer,
+        },
+        make_viewport_matrix, FallbackResources, MotionBlurSettings, RenderPassStatistics,
+    },
+    scene::camera::Camera,
+};
+
+pub struct MotionBlurRenderer {
+    velocity_shader: RenderPassContainer,
+    tile_max_shader: RenderPassContainer,
+    neighbor_max_shader: RenderPassContainer,
+    reconstruction_shader: RenderPassContainer,
+    /// Blur radius (in pixels) of each pixel with its linear depth.
+    velocity: GpuFrameBuffer,
+    /// Dominant blur radius of each tile.
+    tile_max: GpuFrameBuffer,
+    /// Dominant blur radius of each tile and its neighbours.
+    neighbor_max: GpuFrameBuffer,
+    result: GpuFrameBuffer,
+    width: usize,
+    height: usize,
+}
+
+pub(crate) struct MotionBlurRenderContext<'a> {
+    pub quad: &'a GpuGeometryBuffer,
+    pub viewport: Rect<i32>,
+    pub camera: &'a Camera,
+    pub settings: &'a MotionBlurSettings,
+    pub frame_texture: &'a GpuTexture,
+    pub depth_texture: &'a GpuTexture,
+    pub velocity_texture: &'a GpuTexture,
+    pub uniform_buffer_cache: &'a mut UniformBufferCache,
+    pub fallback_resources: &'a FallbackResources,
+}
+
+impl MotionBlurRenderer {
+    /// Size of a tile in pixels. It limits the maximum blur radius.
+    pub const TILE_SIZE: usize = 20;
+
+    pub fn new(
+        server: &dyn GraphicsServer,
+        width: usize,
+        height: usize,
+    ) -> Result<Self, FrameworkError> {
+        let make_frame_buffer = |width: usize, height: usize| {
+            server.create_frame_buffer(
+                None,
+                vec![Attachment {
+                    kind: AttachmentKind::Color,
+                    texture: server.create_2d_render_target(PixelKind::RGBA16F, width, height)?,
+                }],
+            )
+        };
+        let w_tiles = width.div_ceil(Self::TILE_SIZE);
+        let h_tiles = height.div_ceil(Self::TILE_SIZE);
+        Ok(Self {
+            velocity_shader: RenderPassContainer::from_str(
+                server,
+                include_str!("shaders/motion_blur_velocity.shader"),
+            )?,
+            tile_max_shader: RenderPassContainer::from_str(
+                server,
+                include_str!("shaders/motion_blur_tile_max.shader"),
+            )?,
+            neighbor_max_shader: RenderPassContainer::from_str(
+                server,
+                include_str!("shaders/motion_blur_neighbor_max.shader"),
+            )?,
+            reconstruction_shader: RenderPassContainer::from_str(
+                server,
+                include_str!("shaders/motion_blur.shader"),
+            )?,
+            velocity: make_frame_buffer(width, height)?,
+            tile_max: make_frame_buffer(w_tiles, h_tiles)?,
+            neighbor_max: make_frame_buffer(w_tiles, h_tiles)?,
+            result: make_frame_buffer(width, height)?,
+            width,
+            height,
+        })
+    }
+
+    /// Returns the frame with motion blur applied. The content is valid only if motion blur is
+    /// enabled.
+    pub fn result(&self) -> &GpuTexture {
+        &self.result.color_attachments()[0].texture
+    }
+
+    pub(crate) fn render(
+        &self,
+        ctx: MotionBlurRenderContext,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        let MotionBlurRenderContext {
+            quad,
+            viewport,
+            camera,
+            settings,
+            frame_texture,
+            depth_texture,
+            velocity_texture,
+            uniform_buffer_cache,
+            fallback_resources,
+        } = ctx;
+
+        let mut stats = RenderPassStatistics::default();
+
+        let tile_size = Self::TILE_SIZE as i32;
+        let frame_matrix = make_viewport_matrix(viewport);
+        let inverse_frame_size = Vector2::new(1.0 / self.width as f32, 1.0 / self.height as f32);
+        let viewport_origin = Vector2::new(viewport.x() as f32, viewport.y() as f32);
+        let viewport_size = Vector2::new(viewport.w() as f32, viewport.h() as f32);
+        let inv_view_projection = camera
+            .view_projection_matrix()
+            .try_inverse()
+            .unwrap_or_else(Matrix4::identity);
+        let prev_view_projection = camera.prev_view_projection_matrix();
+        let inverse_projection = camera.projection_matrix().try_inverse().unwrap_or_default();
+        let velocity_scale = settings.shutter_angle.clamp(0.0, 360.0) / 360.0;
+        let max_radius = settings.max_blur_radius.clamp(0.0, Self::TILE_SIZE as f32);
+
+        let properties = PropertyGroup::from([
+            property("worldViewProjection", &frame_matrix),
+            property("invViewProj", &inv_view_projection),
+            property("prevViewProj", &prev_view_projection),
+            property("inverseProjectionMatrix", &inverse_projection),
+            property("viewportOrigin", &viewport_origin),
+            property("viewportSize", &viewport_size),
+            property("inverseFrameSize", &inverse_frame_size),
+            property("velocityScale", &velocity_scale),
+            property("maxRadius", &max_radius),
+        ]);
+        let material = RenderMaterial::from([
+            binding(
+                "velocityTexture",
+                (velocity_texture, &fallback_resources.nearest_clamp_sampler),
+            ),
+            binding(
+                "depthTexture",
+                (depth_texture, &fallback_resources.nearest_clamp_sampler),
+            ),
+            binding("properties", &properties),
+        ]);
+        stats += self.velocity_shader.run_pass(
+            1,
+            &ImmutableString::new("Primary"),
+            &self.velocity,
+            quad,
+            viewport,
+            &material,
+            uniform_buffer_cache,
+            Default::default(),
+            None,
+        )?;
+
+        // Tiles that are at least partially covered by the viewport.
+        let tile_x = viewport.x() / tile_size;
+        let tile_y = viewport.y() / tile_size;
+        let tile_viewport = Rect::new(
+            tile_x,
+            tile_y,
+            (viewport.x() + viewport.w() + tile_size - 1) / tile_size - tile_x,
+            (viewport.y() + viewport.h() + tile_size - 1) / tile_size - tile_y,
+        );
+        let tile_frame_matrix = make_viewport_matrix(tile_viewport);
+
+        let velocity = &self.velocity.color_attachments()[0].texture;
+        let properties = PropertyGroup::from([
+            property("worldViewProjection", &tile_frame_matrix),
+            property("viewportOrigin", &viewport_origin),
+            property("viewportSize", &viewport_size),
+            property("tileSize", &tile_size),
+        ]);
+        let material = RenderMaterial::from([
+            binding(
+                "velocityTexture",
+                (velocity, &fallback_resources.nearest_clamp_sampler),
+            ),
+            binding("properties", &properties),
+        ]);
+        stats += self.tile_max_shader.run_pass(
+            1,
+            &ImmutableString::new("Primary"),
+            &self.tile_max,
+            quad,
+            tile_viewport,
+            &material,
+            uniform_buffer_cache,
+            Default::default(),
+            None,
+        )?;
+
+        let tile_viewport_origin = Vector2::new(tile_viewport.x() as f32, tile_viewport.y() as f32);
+        let tile_viewport_size = Vector2::new(tile_viewport.w() as f32, tile_viewport.h() as f32);
+        let properties = PropertyGroup::from([
+            property("worldViewProjection", &tile_frame_matrix),
+            property("tileViewportOrigin", &tile_viewport_origin),
+            property("tileViewportSize", &tile_viewport_size),
+        ]);
+        let material = RenderMaterial::from([
+            binding(
+                "tileMaxTexture",
+                (
+                    &self.tile_max.color_attachments()[0].texture,
+                    &fallback_resources.nearest_clamp_sampler,
+                ),
+            ),
+            binding("properties", &properties),
+        ]);
+        stats += self.neighbor_max_shader.run_pass(
+            1,
+            &ImmutableString::new("Primary"),
+            &self.neighbor_max,
+            quad,
+            tile_viewport,
+            &material,
+            uniform_buffer_cache,
+            Default::default(),
+            None,
+        )?;
+
+        // Keep the samples inside of the viewport, so the neighbouring viewports do not bleed in.
+        let viewport_min =
+            (viewport_origin + Vector2::repeat(0.5)).component_mul(&inverse_frame_size);
+        let viewport_max = (viewport_origin + viewport_size - Vector2::repeat(0.5))
+            .component_mul(&inverse_frame_size);
+        let sample_count = settings.sample_count as i32;
+        let properties = PropertyGroup::from([
+            property("worldViewProjection", &frame_matrix),
+            property("viewportMin", &viewport_min),
+            property("viewportMax", &viewport_max),
+            property("inverseFrameSize", &inverse_frame_size),
+            property("tileSize", &tile_size),
+            property("sampleCount", &sample_count),
+        ]);
+        let material = RenderMaterial::from([
+            binding(
+                "frameTexture",
+                (frame_texture, &fallback_resources.nearest_clamp_sampler),
+            ),
+            binding(
+                "velocityTexture",
+                (velocity, &fallback_resources.nearest_clamp_sampler),
+            ),
+            binding(
+                "neighborMaxTexture",
+                (
+                    &self.neighbor_max.color_attachments()[0].texture,
+                    &fallback_resources.nearest_clamp_sampler,
+                ),
+            ),
+            binding("properties", &properties),
+        ]);
+        stats += self.reconstruction_shader.run_pass(
+            1,
+            &ImmutableString::new("Primary"),
+            &self.result,
+            quad,
+            viewport,
+            &material,
+            uniform_buffer_cache,
+            Default::default(),
+            None,
+        )?;
+
+        Ok(stats)
+    }
+}
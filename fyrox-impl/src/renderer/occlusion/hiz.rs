@@ -0,0 +1,391 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Hierarchical-Z (Hi-Z) occlusion culling. Depth of the previous frame is reduced on GPU into a
+//! small texture, where every texel contains the farthest depth of the area it covers. This
+//! texture is read back asynchronously and the rest of the depth pyramid is built on CPU. Bounding
+//! boxes of objects are then projected on screen and their nearest depth is compared with the
+//! farthest depth of a pyramid level, where the projected rectangle covers at most 2x2 texels.
+//!
+//! Since the read back is asynchronous, the pyramid lags behind the actual frame by a few frames.
+//! To keep the test consistent, bounding boxes are projected using the view-projection matrix that
+//! was used to render the depth the pyramid was built from.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Vector2, Vector3, Vector4},
+        math::{aabb::AxisAlignedBoundingBox, Rect},
+        pool::Handle,
+        ImmutableString,
+    },
+    renderer::{
+        cache::{
+            shader::{binding, property, PropertyGroup, RenderMaterial, RenderPassContainer},
+            uniform::UniformBufferCache,
+        },
+        framework::{
+            error::FrameworkError,
+            framebuffer::{Attachment, AttachmentKind, GpuFrameBuffer},
+            geometry_buffer::GpuGeometryBuffer,
+            gpu_texture::{GpuTexture, PixelKind},
+            read_buffer::GpuAsyncReadBuffer,
+            server::GraphicsServer,
+            stats::RenderPassStatistics,
+        },
+        make_viewport_matrix,
+        occlusion::inflated_world_aabb,
+        FallbackResources,
+    },
+    scene::{graph::Graph, node::Node},
+};
+
+/// Maximum size of the depth texture, that is read back from GPU. GPU reduces the depth of the
+/// frame until both sides of it are less or equal than this value.
+const MAX_READBACK_SIZE: usize = 128;
+
+struct DepthPyramidLevel {
+    width: usize,
+    height: usize,
+    depth: Vec<f32>,
+}
+
+impl DepthPyramidLevel {
+    fn max_depth(&self, min: Vector2<usize>, max: Vector2<usize>) -> f32 {
+        let mut max_depth = 0.0f32;
+        for y in min.y..=max.y.min(self.height - 1) {
+            for x in min.x..=max.x.min(self.width - 1) {
+                max_depth = max_depth.max(self.depth[y * self.width + x]);
+            }
+        }
+        max_depth
+    }
+}
+
+/// CPU-side depth pyramid. Every level is twice smaller than the previous one and every texel of
+/// it contains the farthest depth of the four texels of the previous level. Rows are stored
+/// bottom-up, just like OpenGL returns them.
+pub struct DepthPyramid {
+    levels: Vec<DepthPyramidLevel>,
+}
+
+impl DepthPyramid {
+    /// Creates a new depth pyramid from the given depth values. The amount of values must be equal
+    /// to `width * height`.
+    pub fn new(width: usize, height: usize, depth: Vec<f32>) -> Self {
+        assert_eq!(width * height, depth.len());
+
+        let mut levels = vec![DepthPyramidLevel {
+            width: width.max(1),
+            height: height.max(1),
+            depth,
+        }];
+
+        loop {
+            let prev = levels.last().unwrap();
+            if prev.width == 1 && prev.height == 1 {
+                break;
+            }
+
+            let width = prev.width.div_ceil(2);
+            let height = prev.height.div_ceil(2);
+            let mut depth = Vec::with_capacity(width * height);
+            for y in 0..height {
+                for x in 0..width {
+                    depth.push(prev.max_depth(
+                        Vector2::new(x * 2, y * 2),
+                        Vector2::new(x * 2 + 1, y * 2 + 1),
+                    ));
+                }
+            }
+
+            levels.push(DepthPyramidLevel {
+                width,
+                height,
+                depth,
+            });
+        }
+
+        Self { levels }
+    }
+
+    /// Returns `true` if the given bounding box is potentially visible from the point of view
+    /// described by the given view-projection matrix. Boxes that cross the near plane or lie
+    /// outside the frustum are always treated as visible, they can't be tested reliably.
+    pub fn is_visible(
+        &self,
+        aabb: &AxisAlignedBoundingBox,
+        view_projection: &Matrix4<f32>,
+    ) -> bool {
+        let mut min = Vector2::repeat(f32::MAX);
+        let mut max = Vector2::repeat(-f32::MAX);
+        let mut min_depth = f32::MAX;
+        for corner in aabb.corners() {
+            let clip = view_projection * Vector4::new(corner.x, corner.y, corner.z, 1.0);
+            if clip.w <= f32::EPSILON {
+                return true;
+            }
+            let ndc = clip.xyz().scale(1.0 / clip.w);
+            min = min.inf(&ndc.xy());
+            max = max.sup(&ndc.xy());
+            min_depth = min_depth.min(ndc.z * 0.5 + 0.5);
+        }
+
+        if min_depth < 0.0 || max.x < -1.0 || max.y < -1.0 || min.x > 1.0 || min.y > 1.0 {
+            return true;
+        }
+
+        let base = &self.levels[0];
+        let to_texel = |ndc: f32, size: usize| {
+            (((ndc.clamp(-1.0, 1.0) * 0.5 + 0.5) * size as f32) as usize).min(size - 1)
+        };
+        let min = Vector2::new(to_texel(min.x, base.width), to_texel(min.y, base.height));
+        let max = Vector2::new(to_texel(max.x, base.width), to_texel(max.y, base.height));
+
+        // Pick the finest level, where the rectangle covers at most 2x2 texels.
+        let mut level = 0;
+        while level + 1 < self.levels.len()
+            && ((max.x >> level) - (min.x >> level) > 1 || (max.y >> level) - (min.y >> level) > 1)
+        {
+            level += 1;
+        }
+
+        let max_depth = self.levels[level].max_depth(
+            Vector2::new(min.x >> level, min.y >> level),
+            Vector2::new(max.x >> level, max.y >> level),
+        );
+
+        min_depth <= max_depth
+    }
+}
+
+struct ReductionLevel {
+    framebuffer: GpuFrameBuffer,
+    size: Vector2<usize>,
+}
+
+impl ReductionLevel {
+    fn texture(&self) -> &GpuTexture {
+        &self.framebuffer.color_attachments()[0].texture
+    }
+}
+
+pub struct HiZOcclusionTester {
+    levels: Vec<ReductionLevel>,
+    shader: RenderPassContainer,
+    pixel_buffer: GpuAsyncReadBuffer,
+    pending_view_projection: Matrix4<f32>,
+    view_projection: Matrix4<f32>,
+    pyramid: Option<DepthPyramid>,
+}
+
+impl HiZOcclusionTester {
+    pub fn new(
+        server: &dyn GraphicsServer,
+        width: usize,
+        height: usize,
+    ) -> Result<Self, FrameworkError> {
+        let mut levels = Vec::<ReductionLevel>::new();
+        let mut size = Vector2::new(width.max(1), height.max(1));
+        loop {
+            if size.x > MAX_READBACK_SIZE || size.y > MAX_READBACK_SIZE {
+                size = size.map(|s| (s / 2).max(1));
+            }
+
+            let texture = server.create_2d_render_target(PixelKind::R32F, size.x, size.y)?;
+            levels.push(ReductionLevel {
+                framebuffer: server.create_frame_buffer(
+                    None,
+                    vec![Attachment {
+                        kind: AttachmentKind::Color,
+                        texture,
+                    }],
+                )?,
+                size,
+            });
+
+            if size.x <= MAX_READBACK_SIZE && size.y <= MAX_READBACK_SIZE {
+                break;
+            }
+        }
+
+        Ok(Self {
+            pixel_buffer: server.create_async_read_buffer(size_of::<f32>(), size.x * size.y)?,
+            levels,
+            shader: RenderPassContainer::from_str(
+                server,
+                include_str!("../shaders/hiz_downsample.shader"),
+            )?,
+            pending_view_projection: Default::default(),
+            view_projection: Default::default(),
+            pyramid: None,
+        })
+    }
+
+    /// Fetches the depth, that was read back from GPU (if any) and builds a new depth pyramid
+    /// from it.
+    pub fn try_query_depth_pyramid(&mut self) {
+        let Some(depth) = self.pixel_buffer.try_read_of_type::<f32>() else {
+            return;
+        };
+
+        let size = self.levels.last().unwrap().size;
+        if depth.len() == size.x * size.y {
+            self.pyramid = Some(DepthPyramid::new(size.x, size.y, depth));
+            self.view_projection = self.pending_view_projection;
+        }
+    }
+
+    /// Returns `true` if the given object is potentially visible. Objects are always visible
+    /// until the first depth pyramid is read back from GPU.
+    pub fn is_visible(
+        &self,
+        graph: &Graph,
+        object: Handle<Node>,
+        observer_position: Vector3<f32>,
+    ) -> bool {
+        let Some(pyramid) = self.pyramid.as_ref() else {
+            return true;
+        };
+        let Some(aabb) = inflated_world_aabb(graph, object) else {
+            return true;
+        };
+        if aabb.is_invalid_or_degenerate() || aabb.is_contains_point(observer_position) {
+            return true;
+        }
+        pyramid.is_visible(&aabb, &self.view_projection)
+    }
+
+    /// Reduces the given depth texture on GPU and schedules the read back of the result. Does
+    /// nothing if the previous result wasn't read yet.
+    pub fn try_build_depth_pyramid(
+        &mut self,
+        depth: &GpuTexture,
+        view_projection: Matrix4<f32>,
+        unit_quad: &GpuGeometryBuffer,
+        uniform_buffer_cache: &mut UniformBufferCache,
+        fallback_resources: &FallbackResources,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        let mut stats = RenderPassStatistics::default();
+
+        if self.pixel_buffer.is_request_running() {
+            return Ok(stats);
+        }
+
+        for i in 0..self.levels.len() {
+            let (prev, rest) = self.levels.split_at(i);
+            let level = &rest[0];
+            let source = prev.last().map_or(depth, |prev| prev.texture());
+
+            let viewport = Rect::new(0, 0, level.size.x as i32, level.size.y as i32);
+            let matrix = make_viewport_matrix(viewport);
+            let destination_size = level.size.cast::<f32>();
+            let properties = PropertyGroup::from([
+                property("worldViewProjection", &matrix),
+                property("destinationSize", &destination_size),
+            ]);
+            let material = RenderMaterial::from([
+                binding(
+                    "sourceTexture",
+                    (source, &fallback_resources.nearest_clamp_sampler),
+                ),
+                binding("properties", &properties),
+            ]);
+
+            stats += self.shader.run_pass(
+                1,
+                &ImmutableString::new("Primary"),
+                &level.framebuffer,
+                unit_quad,
+                viewport,
+                &material,
+                uniform_buffer_cache,
+                Default::default(),
+                None,
+            )?;
+        }
+
+        self.pixel_buffer.schedule_pixels_transfer(
+            &*self.levels.last().unwrap().framebuffer,
+            0,
+            None,
+        )?;
+        self.pending_view_projection = view_projection;
+
+        Ok(stats)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::DepthPyramid;
+    use crate::core::{
+        algebra::{Matrix4, Point3, Vector3},
+        math::aabb::AxisAlignedBoundingBox,
+    };
+
+    #[test]
+    fn test_depth_pyramid() {
+        // Left half of the screen is covered by a near occluder, the right half is empty.
+        let (width, height) = (7, 5);
+        let mut depth = vec![1.0; width * height];
+        for y in 0..height {
+            for x in 0..width / 2 {
+                depth[y * width + x] = 0.5;
+            }
+        }
+        let pyramid = DepthPyramid::new(width, height, depth);
+        assert_eq!(pyramid.levels.len(), 4);
+        let last = pyramid.levels.last().unwrap();
+        assert_eq!((last.width, last.height), (1, 1));
+        assert_eq!(last.depth[0], 1.0);
+
+        let view_projection = Matrix4::new_perspective(1.0, 90.0f32.to_radians(), 0.1, 100.0)
+            * Matrix4::look_at_rh(
+                &Point3::new(0.0, 0.0, 0.0),
+                &Point3::new(0.0, 0.0, -1.0),
+                &Vector3::y(),
+            );
+        let left = |z: f32| {
+            AxisAlignedBoundingBox::from_min_max(
+                Vector3::new(-0.9 * z, -0.1, -z - 0.1),
+                Vector3::new(-0.6 * z, 0.1, -z),
+            )
+        };
+
+        // Far box behind the occluder.
+        assert!(!pyramid.is_visible(&left(50.0), &view_projection));
+        // Box in front of the occluder.
+        assert!(pyramid.is_visible(&left(0.15), &view_projection));
+        // Box that crosses the near plane.
+        assert!(pyramid.is_visible(
+            &AxisAlignedBoundingBox::from_min_max(
+                Vector3::new(-1.0, -1.0, -1.0),
+                Vector3::new(1.0, 1.0, 1.0)
+            ),
+            &view_projection
+        ));
+        // Far box on the empty side.
+        let right = AxisAlignedBoundingBox::from_min_max(
+            Vector3::new(20.0, -5.0, -50.0),
+            Vector3::new(40.0, 5.0, -45.0),
+        );
+        assert!(pyramid.is_visible(&right, &view_projection));
+    }
+}
@@ -21,8 +21,11 @@
 //! Full algorithm explained - <https://fyrox.rs/blog/post/tile-based-occlusion-culling/>
 
 mod grid;
+mod hiz;
 mod optimizer;
 
+pub use hiz::HiZOcclusionTester;
+
 use crate::renderer::FallbackResources;
 use crate::{
     core::{
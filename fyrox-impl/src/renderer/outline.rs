@@ -0,0 +1,342 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Screen-space outline of scene objects. It could be used to highlight interactable or selected
+//! objects in games. See [`OutlineRenderPass`] docs for more info.
+
+use crate::{
+    core::{color::Color, math::Rect, pool::Handle, sstorage::ImmutableString},
+    graph::{BaseSceneGraph, SceneGraph},
+    renderer::{
+        bundle::{BundleRenderContext, ObserverInfo, RenderContext, RenderDataBundleStorage},
+        cache::shader::{binding, property, PropertyGroup, RenderMaterial, RenderPassContainer},
+        framework::{
+            buffer::BufferUsage,
+            error::FrameworkError,
+            framebuffer::{Attachment, AttachmentKind, GpuFrameBuffer},
+            geometry_buffer::GpuGeometryBuffer,
+            gpu_texture::{GpuTextureKind, PixelKind},
+            server::GraphicsServer,
+            GeometryBufferExt,
+        },
+        make_viewport_matrix, RenderPassStatistics, SceneRenderPass, SceneRenderPassContext,
+    },
+    scene::{mesh::surface::SurfaceData, node::Node, Scene},
+};
+use fxhash::FxHashSet;
+use std::any::TypeId;
+
+/// Maximum width of an outline in pixels. Must match `MAX_WIDTH` in the outline shader.
+pub const MAX_OUTLINE_WIDTH: f32 = 16.0;
+
+/// Defines how an outline behaves when outlined objects are occluded by other objects.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum OutlineMode {
+    /// The outline is drawn only around visible parts of the objects.
+    #[default]
+    Occluded,
+    /// The outline is drawn around the entire objects, even if they're hidden behind other
+    /// objects. It could be used to show characters behind walls, for example.
+    SeeThrough,
+}
+
+/// A set of nodes outlined with the same color, width and mode. Every node is outlined together
+/// with all its descendants.
+#[derive(Clone, Debug)]
+pub struct OutlineGroup {
+    /// A set of nodes to outline.
+    pub nodes: FxHashSet<Handle<Node>>,
+    /// Color of the outline.
+    pub color: Color,
+    /// Width of the outline in pixels. It is clamped to `[1.0; MAX_OUTLINE_WIDTH]` range.
+    pub width: f32,
+    /// Visibility mode of the outline.
+    pub mode: OutlineMode,
+}
+
+impl Default for OutlineGroup {
+    fn default() -> Self {
+        Self {
+            nodes: Default::default(),
+            color: Color::ORANGE,
+            width: 2.0,
+            mode: Default::default(),
+        }
+    }
+}
+
+impl OutlineGroup {
+    /// Creates a new outline group with the given color and default width.
+    pub fn new(color: Color) -> Self {
+        Self {
+            color,
+            ..Default::default()
+        }
+    }
+
+    /// Sets the nodes of the group.
+    pub fn with_nodes(mut self, nodes: impl IntoIterator<Item = Handle<Node>>) -> Self {
+        self.nodes = nodes.into_iter().collect();
+        self
+    }
+
+    /// Sets the width of the outline in pixels.
+    pub fn with_width(mut self, width: f32) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Sets the visibility mode of the outline.
+    pub fn with_mode(mut self, mode: OutlineMode) -> Self {
+        self.mode = mode;
+        self
+    }
+}
+
+/// Render pass, that draws colored outlines around groups of scene nodes. Objects of every group
+/// are rendered into an offscreen mask first, then a full-screen edge detection pass draws the
+/// outline around the mask on top of the final (tone-mapped) frame.
+///
+/// ## Example
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::{color::Color, pool::Handle},
+/// #     renderer::{
+/// #         framework::error::FrameworkError,
+/// #         outline::{OutlineGroup, OutlineMode, OutlineRenderPass},
+/// #         Renderer,
+/// #     },
+/// #     scene::{node::Node, Scene},
+/// # };
+/// # use std::{any::TypeId, cell::RefCell, rc::Rc};
+/// # struct MyPlugin;
+/// fn add_outline(
+///     renderer: &mut Renderer,
+///     scene: Handle<Scene>,
+///     chest: Handle<Node>,
+///     enemy: Handle<Node>,
+/// ) -> Result<Rc<RefCell<OutlineRenderPass>>, FrameworkError> {
+///     let mut pass = OutlineRenderPass::new(&*renderer.server, TypeId::of::<MyPlugin>())?;
+///     pass.scene_handle = scene;
+///     // Interactable objects are outlined only when visible.
+///     pass.groups.push(OutlineGroup::new(Color::GREEN).with_nodes([chest]));
+///     // Enemies are visible through walls.
+///     pass.groups.push(
+///         OutlineGroup::new(Color::RED)
+///             .with_width(3.0)
+///             .with_mode(OutlineMode::SeeThrough)
+///             .with_nodes([enemy]),
+///     );
+///     let pass = Rc::new(RefCell::new(pass));
+///     renderer.add_render_pass(pass.clone());
+///     Ok(pass)
+/// }
+/// ```
+pub struct OutlineRenderPass {
+    framebuffer: GpuFrameBuffer,
+    quad: GpuGeometryBuffer,
+    shader: RenderPassContainer,
+    source_type_id: TypeId,
+    /// A handle of the scene, which objects should be outlined. [`Handle::NONE`] means that the
+    /// outline will be drawn for every scene, which is rarely needed, because the same node
+    /// handles belong to different objects in different scenes.
+    pub scene_handle: Handle<Scene>,
+    /// A list of outline groups, each group is drawn with its own color, width and mode.
+    pub groups: Vec<OutlineGroup>,
+}
+
+impl OutlineRenderPass {
+    fn create_frame_buffer(
+        server: &dyn GraphicsServer,
+        width: usize,
+        height: usize,
+    ) -> Result<GpuFrameBuffer, FrameworkError> {
+        let width = width.max(1);
+        let height = height.max(1);
+        server.create_frame_buffer(
+            Some(Attachment {
+                kind: AttachmentKind::DepthStencil,
+                texture: server.create_2d_render_target(PixelKind::D24S8, width, height)?,
+            }),
+            vec![Attachment {
+                kind: AttachmentKind::Color,
+                texture: server.create_2d_render_target(PixelKind::RGBA8, width, height)?,
+            }],
+        )
+    }
+
+    /// Creates a new outline render pass. `source_type_id` must be a type id of a plugin, that
+    /// holds this render pass (see [`SceneRenderPass::source_type_id`] for more info).
+    pub fn new(
+        server: &dyn GraphicsServer,
+        source_type_id: TypeId,
+    ) -> Result<Self, FrameworkError> {
+        Ok(Self {
+            framebuffer: Self::create_frame_buffer(server, 1, 1)?,
+            quad: GpuGeometryBuffer::from_surface_data(
+                &SurfaceData::make_unit_xy_quad(),
+                BufferUsage::StaticDraw,
+                server,
+            )?,
+            shader: RenderPassContainer::from_str(server, include_str!("shaders/outline.shader"))?,
+            source_type_id,
+            scene_handle: Default::default(),
+            groups: Default::default(),
+        })
+    }
+}
+
+impl SceneRenderPass for OutlineRenderPass {
+    fn on_ldr_render(
+        &mut self,
+        ctx: SceneRenderPassContext,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        let mut stats = RenderPassStatistics::default();
+
+        if self.scene_handle.is_some() && self.scene_handle != ctx.scene_handle
+            || self.groups.iter().all(|group| group.nodes.is_empty())
+        {
+            return Ok(stats);
+        }
+
+        // The mask has the same size as the G-Buffer, so their depths could be compared directly.
+        let width = ctx.viewport.w().max(1) as usize;
+        let height = ctx.viewport.h().max(1) as usize;
+        if let GpuTextureKind::Rectangle {
+            width: mask_width,
+            height: mask_height,
+        } = self.framebuffer.color_attachments()[0].texture.kind()
+        {
+            if mask_width != width || mask_height != height {
+                self.framebuffer = Self::create_frame_buffer(ctx.server, width, height)?;
+            }
+        }
+        let mask_viewport = Rect::new(0, 0, width as i32, height as i32);
+
+        let render_pass_name = ImmutableString::new("Forward");
+        let observer_info = ObserverInfo {
+            observer_position: ctx.camera.global_position(),
+            z_near: ctx.camera.projection().z_near(),
+            z_far: ctx.camera.projection().z_far(),
+            view_matrix: ctx.camera.view_matrix(),
+            projection_matrix: ctx.camera.projection_matrix(),
+        };
+        let frustum = ctx.camera.frustum();
+        let frame_matrix = make_viewport_matrix(ctx.viewport);
+
+        for group in self.groups.iter() {
+            if group.nodes.is_empty() {
+                continue;
+            }
+
+            // Draw the objects of the group into the mask first.
+            let mut storage = RenderDataBundleStorage::new_empty(observer_info.clone());
+            let mut render_context = RenderContext {
+                elapsed_time: ctx.elapsed_time,
+                observer_info: &observer_info,
+                frustum: Some(&frustum),
+                storage: &mut storage,
+                graph: &ctx.scene.graph,
+                render_pass_name: &render_pass_name,
+                dynamic_surface_cache: ctx.dynamic_surface_cache,
+            };
+            for &root in group.nodes.iter() {
+                if ctx.scene.graph.is_valid_handle(root) {
+                    for (_, node) in ctx.scene.graph.traverse_iter(root) {
+                        node.collect_render_data(&mut render_context);
+                    }
+                }
+            }
+            storage.sort();
+
+            self.framebuffer
+                .clear(mask_viewport, Some(Color::TRANSPARENT), Some(1.0), None);
+
+            stats += storage.render_to_frame_buffer(
+                ctx.server,
+                ctx.geometry_cache,
+                ctx.shader_cache,
+                |_| true,
+                |_| true,
+                BundleRenderContext {
+                    texture_cache: ctx.texture_cache,
+                    render_pass_name: &render_pass_name,
+                    frame_buffer: &self.framebuffer,
+                    viewport: mask_viewport,
+                    uniform_memory_allocator: ctx.uniform_memory_allocator,
+                    use_pom: false,
+                    light_position: &Default::default(),
+                    ambient_light: Default::default(),
+                    scene_depth: Some(ctx.depth_texture),
+                    fog: None,
+                    light_clusters: None,
+                    lights_2d: None,
+                    fallback_resources: ctx.fallback_resources,
+                },
+            )?;
+
+            // Then draw the outline around the mask on top of the frame.
+            let color = group.color.as_frgba();
+            let outline_width = group.width.clamp(1.0, MAX_OUTLINE_WIDTH);
+            let see_through = group.mode == OutlineMode::SeeThrough;
+            let properties = PropertyGroup::from([
+                property("worldViewProjection", &frame_matrix),
+                property("color", &color),
+                property("width", &outline_width),
+                property("seeThrough", &see_through),
+            ]);
+            let sampler = &ctx.fallback_resources.nearest_clamp_sampler;
+            let material = RenderMaterial::from([
+                binding(
+                    "maskTexture",
+                    (&self.framebuffer.color_attachments()[0].texture, sampler),
+                ),
+                binding(
+                    "maskDepthTexture",
+                    (
+                        &self.framebuffer.depth_attachment().unwrap().texture,
+                        sampler,
+                    ),
+                ),
+                binding("sceneDepthTexture", (ctx.depth_texture, sampler)),
+                binding("properties", &properties),
+            ]);
+
+            stats += self.shader.run_pass(
+                1,
+                &ImmutableString::new("Primary"),
+                ctx.framebuffer,
+                &self.quad,
+                ctx.viewport,
+                &material,
+                ctx.uniform_buffer_cache,
+                Default::default(),
+                None,
+            )?;
+        }
+
+        Ok(stats)
+    }
+
+    fn source_type_id(&self) -> TypeId {
+        self.source_type_id
+    }
+}
@@ -0,0 +1,295 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Renderer of custom material-based post effects (see [`crate::scene::post_processing`]). The
+//! material is drawn on a full-screen quad using `PostEffect` render pass of its shader, the frame
+//! before the effect and the G-Buffer textures are provided via built-in resources.
+
+use crate::{
+    core::{
+        algebra::Vector2,
+        arrayvec::ArrayVec,
+        err_once,
+        log::Log,
+        math::{Matrix4Ext, Rect},
+        sstorage::ImmutableString,
+    },
+    material::{self, MaterialResource},
+    renderer::{
+        bundle::{write_shader_values, write_with_material},
+        cache::{shader::ShaderCache, texture::TextureCache, uniform::UniformBufferCache},
+        framework::{
+            error::FrameworkError,
+            framebuffer::{
+                Attachment, AttachmentKind, GpuFrameBuffer, ResourceBindGroup, ResourceBinding,
+            },
+            geometry_buffer::GpuGeometryBuffer,
+            gpu_program::ShaderResourceKind,
+            gpu_texture::{GpuTexture, PixelKind},
+            server::GraphicsServer,
+            uniform::StaticUniformBuffer,
+            ElementRange,
+        },
+        gbuffer::GBuffer,
+        make_viewport_matrix, FallbackResources, RenderPassStatistics,
+    },
+    scene::camera::Camera,
+};
+
+pub struct PostEffectRenderer {
+    framebuffers: [GpuFrameBuffer; 2],
+    current: usize,
+}
+
+pub(crate) struct PostEffectRenderContext<'a> {
+    pub server: &'a dyn GraphicsServer,
+    pub quad: &'a GpuGeometryBuffer,
+    pub viewport: Rect<i32>,
+    pub camera: &'a Camera,
+    pub elapsed_time: f32,
+    pub gbuffer: &'a GBuffer,
+    pub material: &'a MaterialResource,
+    /// The frame before the effect.
+    pub frame_texture: &'a GpuTexture,
+    pub shader_cache: &'a mut ShaderCache,
+    pub texture_cache: &'a mut TextureCache,
+    pub uniform_buffer_cache: &'a mut UniformBufferCache,
+    pub fallback_resources: &'a FallbackResources,
+}
+
+impl PostEffectRenderer {
+    pub fn new(
+        server: &dyn GraphicsServer,
+        width: usize,
+        height: usize,
+    ) -> Result<Self, FrameworkError> {
+        let make_framebuffer = || {
+            server.create_frame_buffer(
+                None,
+                vec![Attachment {
+                    kind: AttachmentKind::Color,
+                    texture: server.create_2d_render_target(PixelKind::RGBA16F, width, height)?,
+                }],
+            )
+        };
+
+        Ok(Self {
+            framebuffers: [make_framebuffer()?, make_framebuffer()?],
+            current: 0,
+        })
+    }
+
+    /// Returns the high dynamic range frame produced by the last effect rendered by
+    /// [`Self::render_hdr`].
+    pub fn result(&self) -> &GpuTexture {
+        &self.framebuffers[self.current].color_attachments()[0].texture
+    }
+
+    /// Applies the effect to the high dynamic range frame. The frame buffers are ping-ponged, so
+    /// the result of the previous effect could be used as the input of the next one.
+    pub(crate) fn render_hdr(
+        &mut self,
+        ctx: PostEffectRenderContext,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        let next = (self.current + 1) % self.framebuffers.len();
+        let stats = render_material_effect(ctx, &self.framebuffers[next])?;
+        self.current = next;
+        Ok(stats)
+    }
+}
+
+/// Draws the material of the effect on a full-screen quad in the given frame buffer.
+pub(crate) fn render_material_effect(
+    ctx: PostEffectRenderContext,
+    frame_buffer: &GpuFrameBuffer,
+) -> Result<RenderPassStatistics, FrameworkError> {
+    let PostEffectRenderContext {
+        server,
+        quad,
+        viewport,
+        camera,
+        elapsed_time,
+        gbuffer,
+        material,
+        frame_texture,
+        shader_cache,
+        texture_cache,
+        uniform_buffer_cache,
+        fallback_resources,
+    } = ctx;
+
+    let mut stats = RenderPassStatistics::default();
+
+    let mut material_state = material.state();
+    let Some(material) = material_state.data() else {
+        err_once!(
+            material.key() as usize,
+            "Unable to use post effect material {}, because it is in invalid state \
+            (failed to load or still loading)!",
+            material_state.kind()
+        );
+        return Ok(stats);
+    };
+
+    let Some(shader_set) = shader_cache.get(server, material.shader()) else {
+        return Ok(stats);
+    };
+
+    let Some(render_pass) = shader_set
+        .render_passes
+        .get(&ImmutableString::new("PostEffect"))
+    else {
+        err_once!(
+            material.shader().key() as usize,
+            "There's no PostEffect render pass in the shader of a post effect material!"
+        );
+        return Ok(stats);
+    };
+
+    let inv_view = camera.inv_view_matrix().unwrap_or_default();
+    let view_projection = camera.view_projection_matrix();
+    let z_near = camera.projection().z_near();
+    let z_far = camera.projection().z_far();
+    let mut bindings = ArrayVec::<ResourceBinding, 32>::new();
+    for resource_definition in shader_set.resources.iter() {
+        let binding = resource_definition.binding;
+        let nearest_clamp_sampler = &fallback_resources.nearest_clamp_sampler;
+
+        match resource_definition.name.as_str() {
+            "fyrox_sceneColor" => bindings.push(ResourceBinding::texture(
+                frame_texture,
+                &fallback_resources.linear_clamp_sampler,
+                binding,
+            )),
+            "fyrox_sceneDepth" => bindings.push(ResourceBinding::texture(
+                gbuffer.depth(),
+                nearest_clamp_sampler,
+                binding,
+            )),
+            "fyrox_gbufferDiffuse" => bindings.push(ResourceBinding::texture(
+                gbuffer.diffuse_texture(),
+                nearest_clamp_sampler,
+                binding,
+            )),
+            "fyrox_gbufferNormal" => bindings.push(ResourceBinding::texture(
+                gbuffer.normal_texture(),
+                nearest_clamp_sampler,
+                binding,
+            )),
+            "fyrox_gbufferMaterial" => bindings.push(ResourceBinding::texture(
+                gbuffer.material_texture(),
+                nearest_clamp_sampler,
+                binding,
+            )),
+            "fyrox_cameraData" => bindings.push(ResourceBinding::Buffer {
+                buffer: uniform_buffer_cache.write(
+                    StaticUniformBuffer::<512>::new()
+                        .with(&view_projection)
+                        .with(&camera.global_position())
+                        .with(&inv_view.up())
+                        .with(&inv_view.side())
+                        .with(&z_near)
+                        .with(&z_far)
+                        .with(&(z_far - z_near)),
+                )?,
+                binding,
+                data_usage: Default::default(),
+            }),
+            "fyrox_postEffectData" => bindings.push(ResourceBinding::Buffer {
+                buffer: uniform_buffer_cache.write(
+                    StaticUniformBuffer::<512>::new()
+                        .with(&make_viewport_matrix(viewport))
+                        .with(&view_projection.try_inverse().unwrap_or_default())
+                        .with(&Vector2::new(viewport.w() as f32, viewport.h() as f32))
+                        .with(&elapsed_time),
+                )?,
+                binding,
+                data_usage: Default::default(),
+            }),
+            _ => match resource_definition.kind {
+                ShaderResourceKind::Texture { fallback, .. } => {
+                    let fallback = (
+                        fallback_resources.sampler_fallback(fallback),
+                        &fallback_resources.linear_wrap_sampler,
+                    );
+
+                    let (texture, sampler) =
+                        match material.binding_ref(resource_definition.name.clone()) {
+                            Some(material::MaterialResourceBinding::Texture(texture_binding)) => {
+                                texture_binding
+                                    .value
+                                    .as_ref()
+                                    .and_then(|t| {
+                                        texture_cache
+                                            .get(server, t)
+                                            .map(|t| (&t.gpu_texture, &t.gpu_sampler))
+                                    })
+                                    .unwrap_or(fallback)
+                            }
+                            Some(binding) => {
+                                Log::err(format!(
+                                    "Unable to use texture binding {}, types mismatch! Expected \
+                                {:?} got {:?}",
+                                    resource_definition.name, resource_definition.kind, binding
+                                ));
+                                fallback
+                            }
+                            None => fallback,
+                        };
+
+                    bindings.push(ResourceBinding::texture(texture, sampler, binding));
+                }
+                ShaderResourceKind::PropertyGroup(ref shader_property_group) => {
+                    let mut buf = StaticUniformBuffer::<16384>::new();
+                    if let Some(material_property_group) =
+                        material.property_group_ref(resource_definition.name.clone())
+                    {
+                        write_with_material(
+                            shader_property_group,
+                            material_property_group,
+                            |c, n| c.property_ref(n.clone()).map(|p| p.as_ref()),
+                            &mut buf,
+                        );
+                    } else {
+                        write_shader_values(shader_property_group, &mut buf)
+                    }
+                    bindings.push(ResourceBinding::Buffer {
+                        buffer: uniform_buffer_cache.write(buf)?,
+                        binding,
+                        data_usage: Default::default(),
+                    });
+                }
+            },
+        }
+    }
+
+    stats += frame_buffer.draw(
+        quad,
+        viewport,
+        &render_pass.program,
+        &render_pass.draw_params,
+        &[ResourceBindGroup {
+            bindings: &bindings,
+        }],
+        ElementRange::Full,
+    )?;
+
+    Ok(stats)
+}
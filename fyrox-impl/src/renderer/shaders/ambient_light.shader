@@ -16,11 +16,80 @@
             kind: Texture(kind: Sampler2D, fallback: White),
             binding: 2
         ),
+        (
+            name: "depthTexture",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 3
+        ),
+        (
+            name: "normalTexture",
+            kind: Texture(kind: Sampler2D, fallback: Normal),
+            binding: 4
+        ),
+        (
+            name: "materialTexture",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 5
+        ),
+        (
+            name: "brdfLut",
+            kind: Texture(kind: Sampler2D, fallback: Black),
+            binding: 6
+        ),
+        (
+            name: "environmentIrradiance",
+            kind: Texture(kind: SamplerCube, fallback: Black),
+            binding: 7
+        ),
+        (
+            name: "environmentSpecular",
+            kind: Texture(kind: SamplerCube, fallback: Black),
+            binding: 8
+        ),
+        (
+            name: "probe0Irradiance",
+            kind: Texture(kind: SamplerCube, fallback: Black),
+            binding: 9
+        ),
+        (
+            name: "probe0Specular",
+            kind: Texture(kind: SamplerCube, fallback: Black),
+            binding: 10
+        ),
+        (
+            name: "probe1Irradiance",
+            kind: Texture(kind: SamplerCube, fallback: Black),
+            binding: 11
+        ),
+        (
+            name: "probe1Specular",
+            kind: Texture(kind: SamplerCube, fallback: Black),
+            binding: 12
+        ),
+        (
+            name: "probe2Irradiance",
+            kind: Texture(kind: SamplerCube, fallback: Black),
+            binding: 13
+        ),
+        (
+            name: "probe2Specular",
+            kind: Texture(kind: SamplerCube, fallback: Black),
+            binding: 14
+        ),
         (
             name: "properties",
             kind: PropertyGroup([
                 (name: "worldViewProjection", kind: Matrix4()),
                 (name: "ambientColor", kind: Vector4()),
+                (name: "invViewProj", kind: Matrix4()),
+                (name: "cameraPosition", kind: Vector3()),
+                (name: "probeCount", kind: Int()),
+                (name: "probeWorld", kind: Matrix4Array(max_len: 3, value: [])),
+                (name: "probeInvWorld", kind: Matrix4Array(max_len: 3, value: [])),
+                // xyz - half extents of a box or radius of a sphere (x), w - 0 for box, 1 for sphere.
+                (name: "probeShape", kind: Vector4Array(max_len: 3, value: [])),
+                // x - blend distance, y - box projection flag, z - intensity.
+                (name: "probeParams", kind: Vector4Array(max_len: 3, value: [])),
             ]),
             binding: 0
         ),
@@ -80,15 +149,138 @@
                     out vec4 FragColor;
                     in vec2 texCoord;
 
+                    // Returns the weight of a probe at the given point (in local space of the probe). The weight
+                    // smoothly fades out to zero near the boundary of the influence volume.
+                    float ProbeWeight(int index, vec3 localPosition)
+                    {
+                        vec4 shape = properties.probeShape[index];
+                        float distanceInside;
+                        if (shape.w > 0.5) {
+                            distanceInside = shape.x - length(localPosition);
+                        } else {
+                            vec3 distances = shape.xyz - abs(localPosition);
+                            distanceInside = min(distances.x, min(distances.y, distances.z));
+                        }
+                        float blendDistance = properties.probeParams[index].x;
+                        if (blendDistance <= 0.0) {
+                            return distanceInside >= 0.0 ? 1.0 : 0.0;
+                        }
+                        return clamp(distanceInside / blendDistance, 0.0, 1.0);
+                    }
+
+                    // Returns the direction to sample the cube map of a probe with. When box projection is
+                    // enabled, the reflection ray is intersected with the influence volume, and the direction
+                    // from the center of the probe to the intersection point is used.
+                    vec3 ProbeDirection(int index, vec3 localPosition, vec3 reflection)
+                    {
+                        if (properties.probeParams[index].y < 0.5) {
+                            return reflection;
+                        }
+
+                        vec3 localDirection = normalize(mat3(properties.probeInvWorld[index]) * reflection);
+                        vec4 shape = properties.probeShape[index];
+                        float t;
+                        if (shape.w > 0.5) {
+                            float b = dot(localPosition, localDirection);
+                            float c = dot(localPosition, localPosition) - shape.x * shape.x;
+                            t = -b + sqrt(max(b * b - c, 0.0));
+                        } else {
+                            vec3 first = (shape.xyz - localPosition) / localDirection;
+                            vec3 second = (-shape.xyz - localPosition) / localDirection;
+                            vec3 furthest = max(first, second);
+                            t = min(furthest.x, min(furthest.y, furthest.z));
+                        }
+                        vec3 localIntersection = localPosition + localDirection * t;
+                        return mat3(properties.probeWorld[index]) * localIntersection;
+                    }
+
+                    // Amount of mip levels of prefiltered specular maps, the last one corresponds to the
+                    // maximum roughness.
+                    const float specularMipCount = 6.0;
+
+                    vec3 SampleProbeIrradiance(int index, vec3 direction)
+                    {
+                        // Samplers cannot be indexed with non-constant expressions.
+                        if (index == 0) {
+                            return texture(probe0Irradiance, direction).rgb;
+                        } else if (index == 1) {
+                            return texture(probe1Irradiance, direction).rgb;
+                        } else {
+                            return texture(probe2Irradiance, direction).rgb;
+                        }
+                    }
+
+                    vec3 SampleProbeSpecular(int index, vec3 direction, float lod)
+                    {
+                        if (index == 0) {
+                            return textureLod(probe0Specular, direction, lod).rgb;
+                        } else if (index == 1) {
+                            return textureLod(probe1Specular, direction, lod).rgb;
+                        } else {
+                            return textureLod(probe2Specular, direction, lod).rgb;
+                        }
+                    }
+
                     void main()
                     {
                         float ambientOcclusion = texture(aoSampler, texCoord).r;
                         vec4 ambientPixel = texture(ambientTexture, texCoord);
-                        FragColor = (properties.ambientColor + ambientPixel) * S_SRGBToLinear(texture(diffuseTexture, texCoord));
+                        vec4 diffuseColor = S_SRGBToLinear(texture(diffuseTexture, texCoord));
+                        FragColor = (properties.ambientColor + ambientPixel) * diffuseColor;
                         FragColor.rgb *= ambientOcclusion;
                         FragColor.a = ambientPixel.a;
 
-                        // TODO: Implement IBL.
+                        float depth = texture(depthTexture, texCoord).r;
+                        if (depth >= 1.0) {
+                            return;
+                        }
+
+                        // Image-based lighting.
+                        vec3 material = texture(materialTexture, texCoord).rgb;
+                        float metallic = material.x;
+                        float roughness = material.y;
+                        float occlusion = ambientOcclusion * material.z;
+
+                        vec3 worldPosition = S_UnProject(vec3(texCoord, depth), properties.invViewProj);
+                        vec3 N = normalize(texture(normalTexture, texCoord).xyz * 2.0 - 1.0);
+                        vec3 V = normalize(properties.cameraPosition - worldPosition);
+                        vec3 R = reflect(-V, N);
+                        float lod = roughness * (specularMipCount - 1.0);
+
+                        vec3 irradiance = vec3(0.0);
+                        vec3 specular = vec3(0.0);
+                        float totalWeight = 0.0;
+                        for (int i = 0; i < properties.probeCount; ++i) {
+                            vec3 localPosition = (properties.probeInvWorld[i] * vec4(worldPosition, 1.0)).xyz;
+                            float weight = ProbeWeight(i, localPosition);
+                            if (weight > 0.0) {
+                                float intensity = weight * properties.probeParams[i].z;
+                                vec3 direction = ProbeDirection(i, localPosition, R);
+                                irradiance += intensity * SampleProbeIrradiance(i, N);
+                                specular += intensity * SampleProbeSpecular(i, direction, lod);
+                                totalWeight += weight;
+                            }
+                        }
+                        if (totalWeight > 1.0) {
+                            irradiance /= totalWeight;
+                            specular /= totalWeight;
+                            totalWeight = 1.0;
+                        }
+                        // The rest is taken from the environment map of the camera.
+                        float environmentWeight = 1.0 - totalWeight;
+                        irradiance += environmentWeight * texture(environmentIrradiance, N).rgb;
+                        specular += environmentWeight * textureLod(environmentSpecular, R, lod).rgb;
+
+                        vec3 F0 = mix(vec3(0.04), diffuseColor.rgb, metallic);
+                        float NdotV = max(dot(N, V), 0.0);
+                        vec3 F = S_FresnelSchlickRoughness(NdotV, F0, roughness);
+                        vec2 brdf = texture(brdfLut, vec2(NdotV, roughness)).rg;
+
+                        vec3 kD = (1.0 - F) * (1.0 - metallic);
+                        vec3 diffuse = kD * irradiance * diffuseColor.rgb;
+                        specular *= F0 * brdf.x + brdf.y;
+
+                        FragColor.rgb += (diffuse + specular) * occlusion;
                     }
                 "#,
         )
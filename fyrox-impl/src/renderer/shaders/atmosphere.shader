@@ -0,0 +1,233 @@
+(
+    name: "Atmosphere",
+    resources: [
+        (
+            name: "properties",
+            kind: PropertyGroup([
+                (name: "worldViewProjection", kind: Matrix4()),
+                (name: "sunDirection", kind: Vector3()),
+                (name: "rayleighScattering", kind: Vector3()),
+                (name: "groundColor", kind: Vector3()),
+                (name: "cloudColor", kind: Vector3()),
+                (name: "cloudOffset", kind: Vector2()),
+                (name: "faceIndex", kind: Int()),
+                (name: "faceSize", kind: Float()),
+                (name: "sunIntensity", kind: Float()),
+                (name: "sunDiskRadius", kind: Float()),
+                (name: "planetRadius", kind: Float()),
+                (name: "atmosphereRadius", kind: Float()),
+                (name: "observerAltitude", kind: Float()),
+                (name: "rayleighScaleHeight", kind: Float()),
+                (name: "mieScattering", kind: Float()),
+                (name: "mieScaleHeight", kind: Float()),
+                (name: "mieAnisotropy", kind: Float()),
+                (name: "cloudCoverage", kind: Float()),
+                (name: "cloudOpacity", kind: Float()),
+                (name: "cloudAltitude", kind: Float()),
+                (name: "cloudScale", kind: Float()),
+                (name: "cloudsEnabled", kind: Bool()),
+            ]),
+            binding: 0
+        ),
+    ],
+    passes: [
+        (
+            name: "Primary",
+
+            draw_parameters: DrawParameters(
+                cull_face: None,
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: None,
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+                scissor_box: None
+            ),
+
+            vertex_shader:
+                r#"
+                    layout (location = 0) in vec3 vertexPosition;
+
+                    void main()
+                    {
+                        gl_Position = properties.worldViewProjection * vec4(vertexPosition, 1.0);
+                    }
+                "#,
+
+            fragment_shader:
+                r#"
+                    out vec4 FragColor;
+
+                    const int PRIMARY_STEPS = 16;
+                    const int LIGHT_STEPS = 8;
+                    // Aerosols absorb some light too, so their extinction is a bit larger than scattering.
+                    const float MIE_EXTINCTION_FACTOR = 1.11;
+                    // The sun disk is brighter than the sun light, otherwise it will not be visible on
+                    // the bright sky. It is not physically correct, but the correct value produces
+                    // bright spots in prefiltered reflections.
+                    const float SUN_DISK_BRIGHTNESS = 10.0;
+
+                    // Returns distances to the intersections of the ray with a sphere at the origin, the
+                    // first component is larger than the second if there is no intersection.
+                    vec2 RaySphere(vec3 origin, vec3 direction, float radius)
+                    {
+                        float b = dot(origin, direction);
+                        float c = dot(origin, origin) - radius * radius;
+                        float d = b * b - c;
+                        if (d < 0.0) {
+                            return vec2(1.0, -1.0);
+                        }
+                        d = sqrt(d);
+                        return vec2(-b - d, -b + d);
+                    }
+
+                    bool HitsPlanet(vec3 origin, vec3 direction)
+                    {
+                        vec2 hit = RaySphere(origin, direction, properties.planetRadius);
+                        return hit.x <= hit.y && hit.x > 0.0;
+                    }
+
+                    // Returns density of air molecules (x) and aerosols (y) at the given point.
+                    vec2 Density(vec3 position)
+                    {
+                        float height = max(length(position) - properties.planetRadius, 0.0);
+                        return exp(-height / vec2(properties.rayleighScaleHeight, properties.mieScaleHeight));
+                    }
+
+                    vec3 Transmittance(vec2 opticalDepth)
+                    {
+                        return exp(-(properties.rayleighScattering * opticalDepth.x +
+                            vec3(properties.mieScattering * MIE_EXTINCTION_FACTOR * opticalDepth.y)));
+                    }
+
+                    // Optical depth between the given point and the top of the atmosphere towards the sun.
+                    vec2 SunOpticalDepth(vec3 position)
+                    {
+                        float distance = RaySphere(position, properties.sunDirection, properties.atmosphereRadius).y;
+                        float stepSize = max(distance, 0.0) / float(LIGHT_STEPS);
+                        vec2 opticalDepth = vec2(0.0);
+                        for (int i = 0; i < LIGHT_STEPS; ++i) {
+                            vec3 samplePosition = position + properties.sunDirection * ((float(i) + 0.5) * stepSize);
+                            opticalDepth += Density(samplePosition) * stepSize;
+                        }
+                        return opticalDepth;
+                    }
+
+                    float Hash(vec2 p)
+                    {
+                        return fract(sin(dot(p, vec2(127.1, 311.7))) * 43758.5453);
+                    }
+
+                    float ValueNoise(vec2 p)
+                    {
+                        vec2 i = floor(p);
+                        vec2 f = fract(p);
+                        vec2 u = f * f * (3.0 - 2.0 * f);
+                        return mix(mix(Hash(i), Hash(i + vec2(1.0, 0.0)), u.x),
+                                   mix(Hash(i + vec2(0.0, 1.0)), Hash(i + vec2(1.0, 1.0)), u.x), u.y);
+                    }
+
+                    float CloudNoise(vec2 p)
+                    {
+                        float value = 0.0;
+                        float amplitude = 0.5;
+                        for (int i = 0; i < 5; ++i) {
+                            value += amplitude * ValueNoise(p);
+                            p *= 2.03;
+                            amplitude *= 0.5;
+                        }
+                        return value;
+                    }
+
+                    void main()
+                    {
+                        vec2 uv = gl_FragCoord.xy / properties.faceSize;
+                        vec3 direction = S_CubeFaceDirection(properties.faceIndex, uv);
+                        vec3 sunDirection = properties.sunDirection;
+                        vec3 origin = vec3(0.0, properties.planetRadius + properties.observerAltitude, 0.0);
+
+                        float rayLength = max(RaySphere(origin, direction, properties.atmosphereRadius).y, 0.0);
+                        vec2 groundHit = RaySphere(origin, direction, properties.planetRadius);
+                        bool hitsGround = groundHit.x <= groundHit.y && groundHit.x > 0.0;
+                        if (hitsGround) {
+                            rayLength = groundHit.x;
+                        }
+
+                        // Single scattering: integrate the light, that is scattered towards the observer
+                        // along the view ray.
+                        float stepSize = rayLength / float(PRIMARY_STEPS);
+                        vec2 opticalDepth = vec2(0.0);
+                        vec3 rayleigh = vec3(0.0);
+                        vec3 mie = vec3(0.0);
+                        for (int i = 0; i < PRIMARY_STEPS; ++i) {
+                            vec3 samplePosition = origin + direction * ((float(i) + 0.5) * stepSize);
+                            vec2 density = Density(samplePosition) * stepSize;
+                            opticalDepth += density;
+                            if (HitsPlanet(samplePosition, sunDirection)) {
+                                continue;
+                            }
+                            vec3 transmittance = Transmittance(opticalDepth + SunOpticalDepth(samplePosition));
+                            rayleigh += density.x * transmittance;
+                            mie += density.y * transmittance;
+                        }
+
+                        float mu = dot(direction, sunDirection);
+                        float rayleighPhase = 3.0 / (16.0 * PI) * (1.0 + mu * mu);
+                        float g = properties.mieAnisotropy;
+                        float g2 = g * g;
+                        float miePhase = 3.0 / (8.0 * PI) * ((1.0 - g2) * (1.0 + mu * mu)) /
+                            ((2.0 + g2) * pow(max(1.0 + g2 - 2.0 * g * mu, 0.0001), 1.5));
+
+                        vec3 color = properties.sunIntensity * (rayleigh * properties.rayleighScattering * rayleighPhase +
+                            mie * properties.mieScattering * miePhase);
+                        vec3 viewTransmittance = Transmittance(opticalDepth);
+
+                        if (hitsGround) {
+                            vec3 groundPosition = origin + direction * rayLength;
+                            float nDotL = max(dot(normalize(groundPosition), sunDirection), 0.0);
+                            vec3 sunLight = properties.sunIntensity * Transmittance(SunOpticalDepth(groundPosition));
+                            color += viewTransmittance * properties.groundColor * sunLight * nDotL / PI;
+                        } else {
+                            float diskRadius = properties.sunDiskRadius;
+                            if (diskRadius > 0.0) {
+                                float disk = smoothstep(cos(diskRadius), cos(diskRadius * 0.9), mu);
+                                color += viewTransmittance * properties.sunIntensity * SUN_DISK_BRIGHTNESS * disk;
+                            }
+
+                            if (properties.cloudsEnabled && direction.y > 0.0) {
+                                // The layer is considered flat, which is good enough for clouds that are
+                                // much lower than the top of the atmosphere.
+                                float distance = properties.cloudAltitude / direction.y;
+                                vec2 cloudPosition = (direction.xz * distance + properties.cloudOffset) / properties.cloudScale;
+                                float coverage = clamp(properties.cloudCoverage, 0.0, 1.0);
+                                float cloudDensity = smoothstep(1.0 - coverage, 1.0 - coverage + 0.3, CloudNoise(cloudPosition));
+                                // Fade the clouds out near the horizon, where the flat layer is too coarse.
+                                cloudDensity *= properties.cloudOpacity * smoothstep(0.0, 0.1, direction.y);
+
+                                vec3 cloudPoint = origin + direction * distance;
+                                vec3 sunLight = properties.sunIntensity * Transmittance(SunOpticalDepth(cloudPoint));
+                                // Clouds are brighter when looking towards the sun, because of forward scattering.
+                                float cloudPhase = mix(0.5, 1.5, max(mu, 0.0));
+                                vec3 cloudLight = properties.cloudColor * sunLight * cloudPhase *
+                                    max(sunDirection.y, 0.05) / PI;
+                                color = mix(color, cloudLight, cloudDensity);
+                            }
+                        }
+
+                        FragColor = vec4(color, 1.0);
+                    }
+                "#,
+        )
+    ]
+)
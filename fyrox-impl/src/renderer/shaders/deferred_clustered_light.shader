@@ -0,0 +1,151 @@
+(
+    name: "DeferredClusteredLight",
+    resources: [
+        (
+            name: "depthTexture",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 0
+        ),
+        (
+            name: "colorTexture",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 1
+        ),
+        (
+            name: "normalTexture",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 2
+        ),
+        (
+            name: "materialTexture",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 3
+        ),
+        (
+            name: "clustersTexture",
+            kind: Texture(kind: USampler2D, fallback: White),
+            binding: 4
+        ),
+        (
+            name: "lightsTexture",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 5
+        ),
+        (
+            name: "properties",
+            kind: PropertyGroup([
+                (name: "worldViewProjection", kind: Matrix4()),
+                (name: "invViewProj", kind: Matrix4()),
+                (name: "viewMatrix", kind: Matrix4()),
+                (name: "clusterViewProjection", kind: Matrix4()),
+                (name: "cameraPosition", kind: Vector3()),
+                (name: "zNear", kind: Float()),
+                (name: "zFar", kind: Float()),
+            ]),
+            binding: 0
+        ),
+    ],
+    passes: [
+        (
+            name: "Primary",
+
+            draw_parameters: DrawParameters(
+                cull_face: None,
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: None,
+                blend: Some(BlendParameters(
+                    func: BlendFunc(
+                        sfactor: One,
+                        dfactor: One,
+                        alpha_sfactor: One,
+                        alpha_dfactor: One,
+                    ),
+                    equation: BlendEquation(
+                        rgb: Add,
+                        alpha: Add
+                    )
+                )),
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+                scissor_box: None
+            ),
+
+            vertex_shader:
+                r#"
+                    layout (location = 0) in vec3 vertexPosition;
+                    layout (location = 1) in vec2 vertexTexCoord;
+
+                    out vec2 texCoord;
+
+                    void main()
+                    {
+                        gl_Position = properties.worldViewProjection * vec4(vertexPosition, 1.0);
+                        texCoord = vertexTexCoord;
+                    }
+                "#,
+
+            fragment_shader:
+                r#"
+                    in vec2 texCoord;
+                    out vec4 FragColor;
+
+                    void main()
+                    {
+                        float depth = texture(depthTexture, texCoord).r;
+                        if (depth >= 1.0) {
+                            // Nothing to light in the background.
+                            discard;
+                        }
+
+                        vec3 material = texture(materialTexture, texCoord).rgb;
+                        vec3 fragmentPosition = S_UnProject(vec3(texCoord, depth), properties.invViewProj);
+                        vec4 diffuseColor = texture(colorTexture, texCoord);
+
+                        TPBRContext ctx;
+                        ctx.albedo = S_SRGBToLinear(diffuseColor).rgb;
+                        ctx.fragmentNormal = normalize(texture(normalTexture, texCoord).xyz * 2.0 - 1.0);
+                        ctx.metallic = material.x;
+                        ctx.roughness = material.y;
+                        ctx.viewVector = normalize(properties.cameraPosition - fragmentPosition);
+
+                        int clusterIndex = S_ClusterIndex(
+                            fragmentPosition, properties.viewMatrix, properties.clusterViewProjection,
+                            properties.zNear, properties.zFar);
+                        ivec2 range = S_ClusterLightRange(clustersTexture, clusterIndex);
+
+                        vec3 lighting = vec3(0.0);
+                        for (int i = 0; i < range.y; ++i) {
+                            TClusteredLight light = S_FetchClusteredLight(
+                                lightsTexture, S_FetchClusteredLightIndex(clustersTexture, range.x + i));
+                            if (light.shadedSeparately) {
+                                continue;
+                            }
+
+                            vec3 fragmentToLight;
+                            float attenuation = S_ClusteredLightAttenuation(light, fragmentPosition, fragmentToLight);
+                            if (attenuation <= 0.0) {
+                                continue;
+                            }
+
+                            ctx.fragmentToLight = fragmentToLight;
+                            ctx.lightColor = light.color;
+                            lighting += attenuation * S_PBR_CalculateLight(ctx);
+                        }
+
+                        FragColor = vec4(lighting, diffuseColor.a);
+                    }
+                "#,
+        )
+    ]
+)
@@ -42,6 +42,7 @@
                 (name: "worldViewProjection", kind: Matrix4()),
                 (name: "viewMatrix", kind: Matrix4()),
                 (name: "invViewProj", kind: Matrix4()),
+                (name: "viewProjection", kind: Matrix4()),
                 (name: "lightViewProjMatrices", kind: Matrix4Array(max_len: 3, value: [])),
                 (name: "lightColor", kind: Vector4()),
                 (name: "lightDirection", kind: Vector3()),
@@ -52,6 +53,12 @@
                 (name: "softShadows", kind: Bool()),
                 (name: "shadowMapInvSize", kind: Float()),
                 (name: "cascadeDistances", kind: FloatArray(max_len: 3, value: [])),
+                (name: "penumbraScales", kind: FloatArray(max_len: 3, value: [])),
+                (name: "pcss", kind: Bool()),
+                (name: "contactShadows", kind: Bool()),
+                (name: "contactShadowsDistance", kind: Float()),
+                (name: "contactShadowsThickness", kind: Float()),
+                (name: "contactShadowsSteps", kind: Int()),
             ]),
             binding: 0
         ),
@@ -112,8 +119,11 @@
                     out vec4 FragColor;
 
                     // Returns **inverted** shadow factor where 1 - fully bright, 0 - fully in shadow.
-                    float CsmGetShadow(in sampler2D sampler, in vec3 fragmentPosition, in mat4 lightViewProjMatrix)
+                    float CsmGetShadow(in sampler2D sampler, in vec3 fragmentPosition, in mat4 lightViewProjMatrix, float penumbraScale)
                     {
+                        if (properties.pcss) {
+                            return S_DirectionalShadowFactorPCSS(properties.shadowsEnabled, properties.shadowBias, fragmentPosition, lightViewProjMatrix, properties.shadowMapInvSize, penumbraScale, sampler);
+                        }
                         return S_SpotShadowFactor(properties.shadowsEnabled, properties.softShadows, properties.shadowBias, fragmentPosition, lightViewProjMatrix, properties.shadowMapInvSize, sampler);
                     }
 
@@ -139,11 +149,18 @@
 
                         float shadow = 1.0;
                         if (fragmentZViewSpace <= properties.cascadeDistances[0]) {
-                            shadow = CsmGetShadow(shadowCascade0, fragmentPosition, properties.lightViewProjMatrices[0]);
+                            shadow = CsmGetShadow(shadowCascade0, fragmentPosition, properties.lightViewProjMatrices[0], properties.penumbraScales[0]);
                         } else if (fragmentZViewSpace <= properties.cascadeDistances[1]) {
-                            shadow = CsmGetShadow(shadowCascade1, fragmentPosition, properties.lightViewProjMatrices[1]);
+                            shadow = CsmGetShadow(shadowCascade1, fragmentPosition, properties.lightViewProjMatrices[1], properties.penumbraScales[1]);
                         } else if (fragmentZViewSpace <= properties.cascadeDistances[2]) {
-                            shadow = CsmGetShadow(shadowCascade2, fragmentPosition, properties.lightViewProjMatrices[2]);
+                            shadow = CsmGetShadow(shadowCascade2, fragmentPosition, properties.lightViewProjMatrices[2], properties.penumbraScales[2]);
+                        }
+
+                        if (properties.shadowsEnabled && properties.contactShadows) {
+                            shadow *= S_ContactShadow(
+                                depthTexture, fragmentPosition, ctx.fragmentToLight, properties.cameraPosition,
+                                properties.viewProjection, properties.invViewProj, properties.contactShadowsDistance,
+                                properties.contactShadowsSteps, properties.contactShadowsThickness);
                         }
 
                         FragColor = shadow * vec4(properties.lightIntensity * lighting, diffuseColor.a);
@@ -31,6 +31,7 @@
             kind: PropertyGroup([
                 (name: "worldViewProjection", kind: Matrix4()),
                 (name: "invViewProj", kind: Matrix4()),
+                (name: "viewProjection", kind: Matrix4()),
                 (name: "lightColor", kind: Vector4()),
                 (name: "lightPos", kind: Vector3()),
                 (name: "cameraPosition", kind: Vector3()),
@@ -40,6 +41,12 @@
                 (name: "shadowAlpha", kind: Float()),
                 (name: "softShadows", kind: Bool()),
                 (name: "shadowsEnabled", kind: Bool()),
+                (name: "lightSize", kind: Float()),
+                (name: "pcss", kind: Bool()),
+                (name: "contactShadows", kind: Bool()),
+                (name: "contactShadowsDistance", kind: Float()),
+                (name: "contactShadowsThickness", kind: Float()),
+                (name: "contactShadowsSteps", kind: Int()),
             ]),
             binding: 0
         ),
@@ -126,8 +133,20 @@
 
                         float distanceAttenuation = S_LightDistanceAttenuation(distance, properties.lightRadius);
 
-                        float shadow = S_PointShadow(
-                            properties.shadowsEnabled, properties.softShadows, distance, properties.shadowBias, ctx.fragmentToLight, pointShadowTexture);
+                        float shadow;
+                        if (properties.pcss) {
+                            shadow = S_PointShadowPCSS(
+                                properties.shadowsEnabled, distance, properties.shadowBias, properties.lightSize, ctx.fragmentToLight, pointShadowTexture);
+                        } else {
+                            shadow = S_PointShadow(
+                                properties.shadowsEnabled, properties.softShadows, distance, properties.shadowBias, ctx.fragmentToLight, pointShadowTexture);
+                        }
+                        if (properties.shadowsEnabled && properties.contactShadows) {
+                            shadow *= S_ContactShadow(
+                                depthTexture, fragmentPosition, ctx.fragmentToLight, properties.cameraPosition,
+                                properties.viewProjection, properties.invViewProj, properties.contactShadowsDistance,
+                                properties.contactShadowsSteps, properties.contactShadowsThickness);
+                        }
                         float finalShadow = mix(1.0, shadow, properties.shadowAlpha);
 
                         FragColor = vec4(properties.lightIntensity * distanceAttenuation * finalShadow * lighting, diffuseColor.a);
@@ -37,6 +37,7 @@
                 (name: "worldViewProjection", kind: Matrix4()),
                 (name: "lightViewProjMatrix", kind: Matrix4()),
                 (name: "invViewProj", kind: Matrix4()),
+                (name: "viewProjection", kind: Matrix4()),
                 (name: "lightPos", kind: Vector3()),
                 (name: "lightColor", kind: Vector4()),
                 (name: "cameraPosition", kind: Vector3()),
@@ -51,6 +52,14 @@
                 (name: "cookieEnabled", kind: Bool()),
                 (name: "shadowsEnabled", kind: Bool()),
                 (name: "softShadows", kind: Bool()),
+                (name: "lightSize", kind: Float()),
+                (name: "lightZNear", kind: Float()),
+                (name: "lightZFar", kind: Float()),
+                (name: "pcss", kind: Bool()),
+                (name: "contactShadows", kind: Bool()),
+                (name: "contactShadowsDistance", kind: Float()),
+                (name: "contactShadowsThickness", kind: Float()),
+                (name: "contactShadowsSteps", kind: Int()),
             ]),
             binding: 0
         ),
@@ -139,9 +148,23 @@
                         float spotAngleCos = dot(properties.lightDirection, ctx.fragmentToLight);
                         float coneFactor = smoothstep(properties.halfConeAngleCos, properties.halfHotspotConeAngleCos, spotAngleCos);
 
-                        float shadow = S_SpotShadowFactor(
-                            properties.shadowsEnabled, properties.softShadows, properties.shadowBias, fragmentPosition,
-                            properties.lightViewProjMatrix, properties.shadowMapInvSize, spotShadowTexture);
+                        float shadow;
+                        if (properties.pcss) {
+                            shadow = S_SpotShadowFactorPCSS(
+                                properties.shadowsEnabled, properties.shadowBias, fragmentPosition,
+                                properties.lightViewProjMatrix, properties.shadowMapInvSize, properties.lightSize,
+                                properties.lightZNear, properties.lightZFar, spotShadowTexture);
+                        } else {
+                            shadow = S_SpotShadowFactor(
+                                properties.shadowsEnabled, properties.softShadows, properties.shadowBias, fragmentPosition,
+                                properties.lightViewProjMatrix, properties.shadowMapInvSize, spotShadowTexture);
+                        }
+                        if (properties.shadowsEnabled && properties.contactShadows) {
+                            shadow *= S_ContactShadow(
+                                depthTexture, fragmentPosition, ctx.fragmentToLight, properties.cameraPosition,
+                                properties.viewProjection, properties.invViewProj, properties.contactShadowsDistance,
+                                properties.contactShadowsSteps, properties.contactShadowsThickness);
+                        }
                         float finalShadow = mix(1.0, shadow, properties.shadowAlpha);
 
                         vec4 cookieAttenuation = vec4(1.0);
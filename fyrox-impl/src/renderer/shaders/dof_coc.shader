@@ -0,0 +1,88 @@
+(
+    name: "DepthOfFieldCoc",
+    resources: [
+        (
+            name: "frameTexture",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 0
+        ),
+        (
+            name: "depthTexture",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 1
+        ),
+        (
+            name: "properties",
+            kind: PropertyGroup([
+                (name: "worldViewProjection", kind: Matrix4()),
+                (name: "inverseProjectionMatrix", kind: Matrix4()),
+                (name: "viewportOrigin", kind: Vector2()),
+                (name: "viewportSize", kind: Vector2()),
+                (name: "inverseFrameSize", kind: Vector2()),
+                (name: "focalDistance", kind: Float()),
+                (name: "cocScale", kind: Float()),
+                (name: "maxRadius", kind: Float()),
+            ]),
+            binding: 0
+        ),
+    ],
+    passes: [
+        (
+            name: "Primary",
+
+            draw_parameters: DrawParameters(
+                cull_face: None,
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: None,
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+                scissor_box: None
+            ),
+
+            vertex_shader:
+                r#"
+                    layout (location = 0) in vec3 vertexPosition;
+
+                    void main()
+                    {
+                        gl_Position = properties.worldViewProjection * vec4(vertexPosition, 1.0);
+                    }
+                "#,
+
+            fragment_shader:
+                r#"
+                    out vec4 FragColor;
+
+                    void main()
+                    {
+                        vec2 uv = gl_FragCoord.xy * properties.inverseFrameSize;
+                        float depth = texture(depthTexture, uv).r;
+                        vec2 screenPos = (gl_FragCoord.xy - properties.viewportOrigin) / properties.viewportSize;
+                        vec3 viewPosition = S_UnProject(vec3(screenPos, depth), properties.inverseProjectionMatrix);
+                        float viewDistance = max(-viewPosition.z, 0.0001);
+
+                        // Signed radius of the circle of confusion in pixels, it is negative for the near
+                        // field and positive for the far field.
+                        float coc = properties.cocScale * (1.0 - properties.focalDistance / viewDistance);
+
+                        FragColor = vec4(
+                            texture(frameTexture, uv).rgb,
+                            clamp(coc, -properties.maxRadius, properties.maxRadius)
+                        );
+                    }
+                "#,
+        )
+    ]
+)
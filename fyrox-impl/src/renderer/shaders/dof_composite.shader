@@ -0,0 +1,88 @@
+(
+    name: "DepthOfFieldComposite",
+    resources: [
+        (
+            name: "frameTexture",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 0
+        ),
+        (
+            name: "cocTexture",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 1
+        ),
+        (
+            name: "farTexture",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 2
+        ),
+        (
+            name: "nearTexture",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 3
+        ),
+        (
+            name: "properties",
+            kind: PropertyGroup([
+                (name: "worldViewProjection", kind: Matrix4()),
+                (name: "inverseFrameSize", kind: Vector2()),
+            ]),
+            binding: 0
+        ),
+    ],
+    passes: [
+        (
+            name: "Primary",
+
+            draw_parameters: DrawParameters(
+                cull_face: None,
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: None,
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+                scissor_box: None
+            ),
+
+            vertex_shader:
+                r#"
+                    layout (location = 0) in vec3 vertexPosition;
+
+                    void main()
+                    {
+                        gl_Position = properties.worldViewProjection * vec4(vertexPosition, 1.0);
+                    }
+                "#,
+
+            fragment_shader:
+                r#"
+                    out vec4 FragColor;
+
+                    void main()
+                    {
+                        vec2 uv = gl_FragCoord.xy * properties.inverseFrameSize;
+                        vec4 sharp = texture(frameTexture, uv);
+                        float coc = texture(cocTexture, uv).a;
+
+                        vec3 color = mix(sharp.rgb, texture(farTexture, uv).rgb, smoothstep(0.5, 1.5, coc));
+
+                        vec4 nearField = texture(nearTexture, uv);
+                        color = mix(color, nearField.rgb, nearField.a);
+
+                        FragColor = vec4(color, sharp.a);
+                    }
+                "#,
+        )
+    ]
+)
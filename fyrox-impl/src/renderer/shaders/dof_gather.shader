@@ -0,0 +1,129 @@
+(
+    name: "DepthOfFieldGather",
+    resources: [
+        (
+            name: "cocTexture",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 0
+        ),
+        (
+            name: "properties",
+            kind: PropertyGroup([
+                (name: "worldViewProjection", kind: Matrix4()),
+                (name: "viewportMin", kind: Vector2()),
+                (name: "viewportMax", kind: Vector2()),
+                (name: "inverseFrameSize", kind: Vector2()),
+                (name: "maxRadius", kind: Float()),
+            ]),
+            binding: 0
+        ),
+    ],
+    passes: [
+        (
+            name: "Primary",
+
+            draw_parameters: DrawParameters(
+                cull_face: None,
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: None,
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+                scissor_box: None
+            ),
+
+            vertex_shader:
+                r#"
+                    layout (location = 0) in vec3 vertexPosition;
+
+                    void main()
+                    {
+                        gl_Position = properties.worldViewProjection * vec4(vertexPosition, 1.0);
+                    }
+                "#,
+
+            fragment_shader:
+                r#"
+                    layout(location = 0) out vec4 farColor;
+                    layout(location = 1) out vec4 nearColor;
+
+                    const int SAMPLE_COUNT = 48;
+                    const float GOLDEN_ANGLE = 2.39996323;
+
+                    // Samples a disk of the given radius using a golden angle spiral, it gives
+                    // uniformly distributed samples with round bokeh shape.
+                    vec2 DiskOffset(int i, float radius, out float sampleDistance)
+                    {
+                        sampleDistance = sqrt((float(i) + 0.5) / float(SAMPLE_COUNT)) * radius;
+                        float angle = float(i) * GOLDEN_ANGLE;
+                        return vec2(cos(angle), sin(angle)) * sampleDistance;
+                    }
+
+                    // Returns how much a blurred pixel with the given circle of confusion covers a
+                    // pixel at the given distance from it.
+                    float Coverage(float coc, float sampleDistance)
+                    {
+                        return clamp(coc - sampleDistance + 1.0, 0.0, 1.0);
+                    }
+
+                    void main()
+                    {
+                        vec2 uv = gl_FragCoord.xy * properties.inverseFrameSize;
+                        vec4 center = texture(cocTexture, uv);
+
+                        // Far field. The size of the kernel is defined by the circle of confusion
+                        // of the current pixel and only the samples that cover the current pixel
+                        // contribute, so sharp foreground does not leak into blurred background.
+                        vec4 farSum = vec4(center.rgb, 1.0);
+                        if (center.a > 0.5) {
+                            for (int i = 0; i < SAMPLE_COUNT; ++i) {
+                                float sampleDistance;
+                                vec2 offset = DiskOffset(i, center.a, sampleDistance);
+                                vec2 sampleUv = clamp(uv + offset * properties.inverseFrameSize, properties.viewportMin, properties.viewportMax);
+                                vec4 s = texture(cocTexture, sampleUv);
+                                float weight = Coverage(max(s.a, 0.0), sampleDistance);
+                                farSum += vec4(s.rgb * weight, weight);
+                            }
+                        }
+                        farColor = vec4(farSum.rgb / farSum.a, 1.0);
+
+                        // Near field. Blurred foreground spreads over everything behind it, so the
+                        // samples are gathered in the maximum radius. Each sample adds its share of
+                        // the area of its circle of confusion to the coverage of the current pixel.
+                        vec4 nearSum = vec4(0.0);
+                        float coverage = 0.0;
+                        float maxRadius = properties.maxRadius;
+                        for (int i = 0; i < SAMPLE_COUNT; ++i) {
+                            float sampleDistance;
+                            vec2 offset = DiskOffset(i, maxRadius, sampleDistance);
+                            vec2 sampleUv = clamp(uv + offset * properties.inverseFrameSize, properties.viewportMin, properties.viewportMax);
+                            vec4 s = texture(cocTexture, sampleUv);
+                            float sampleCoc = -s.a;
+                            if (sampleCoc > 0.5) {
+                                float weight = Coverage(sampleCoc, sampleDistance);
+                                nearSum += vec4(s.rgb * weight, weight);
+                                float density = maxRadius * maxRadius / (float(SAMPLE_COUNT) * sampleCoc * sampleCoc);
+                                coverage += weight * min(density, 1.0);
+                            }
+                        }
+                        if (nearSum.a > 0.0) {
+                            nearColor = vec4(nearSum.rgb / nearSum.a, clamp(coverage, 0.0, 1.0));
+                        } else {
+                            nearColor = vec4(0.0);
+                        }
+                    }
+                "#,
+        )
+    ]
+)
@@ -0,0 +1,115 @@
+(
+    name: "Fog",
+    resources: [
+        (
+            name: "depthTexture",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 0
+        ),
+        (
+            name: "environmentTexture",
+            kind: Texture(kind: SamplerCube, fallback: Black),
+            binding: 1
+        ),
+        (
+            name: "volumeTexture",
+            kind: Texture(kind: Sampler2D, fallback: Black),
+            binding: 2
+        ),
+        (
+            name: "properties",
+            kind: PropertyGroup([
+                (name: "worldViewProjection", kind: Matrix4()),
+                (name: "invViewProj", kind: Matrix4()),
+                (name: "cameraPosition", kind: Vector3()),
+                (name: "mode", kind: Int()),
+                (name: "color", kind: Vector4()),
+                (name: "params", kind: Vector4()),
+                (name: "useEnvironment", kind: Bool()),
+                (name: "environmentLod", kind: Float()),
+                (name: "volumetric", kind: Bool()),
+                (name: "volumetricDistance", kind: Float()),
+            ]),
+            binding: 0
+        ),
+    ],
+    passes: [
+        (
+            name: "Primary",
+
+            draw_parameters: DrawParameters(
+                cull_face: None,
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: false,
+                ),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: None,
+                // Result = Fog + Frame * Transmittance
+                blend: Some(BlendParameters(
+                    func: BlendFunc(
+                        sfactor: One,
+                        dfactor: SrcAlpha,
+                        alpha_sfactor: Zero,
+                        alpha_dfactor: One,
+                    ),
+                    equation: BlendEquation(
+                        rgb: Add,
+                        alpha: Add
+                    )
+                )),
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+                scissor_box: None
+            ),
+
+            vertex_shader:
+                r#"
+                    layout (location = 0) in vec3 vertexPosition;
+                    layout (location = 1) in vec2 vertexTexCoord;
+
+                    out vec2 texCoord;
+
+                    void main()
+                    {
+                        texCoord = vertexTexCoord;
+                        gl_Position = properties.worldViewProjection * vec4(vertexPosition, 1.0);
+                    }
+                "#,
+
+            fragment_shader:
+                r#"
+                    out vec4 FragColor;
+
+                    in vec2 texCoord;
+
+                    void main() {
+                        // Sky is treated as if it was located at the far clipping plane.
+                        float depth = texture(depthTexture, texCoord).r;
+                        vec3 position = S_UnProject(vec3(texCoord, depth), properties.invViewProj);
+                        vec3 toPosition = position - properties.cameraPosition;
+
+                        float opacity = min(S_FogOpacity(properties.mode, properties.params, properties.cameraPosition, position), properties.color.a);
+                        vec3 color = properties.useEnvironment
+                            ? textureLod(environmentTexture, normalize(toPosition), properties.environmentLod).rgb
+                            : properties.color.rgb;
+
+                        vec4 scattering = vec4(0.0, 0.0, 0.0, 1.0);
+                        if (properties.volumetric) {
+                            scattering = S_SampleVolumetricFog(volumeTexture, texCoord, length(toPosition), properties.volumetricDistance);
+                        }
+
+                        // Same as Internal_ApplySceneFog, but the frame color is provided by the blending.
+                        FragColor = vec4(color * opacity * scattering.a + scattering.rgb, (1.0 - opacity) * scattering.a);
+                    }
+                "#,
+        )
+    ]
+)
@@ -31,6 +31,7 @@
                 (name: "maxLuminance", kind: Float()),
                 (name: "autoExposure", kind: Bool()),
                 (name: "fixedExposure", kind: Float()),
+                (name: "bloomIntensity", kind: Float()),
             ]),
             binding: 0
         ),
@@ -92,7 +93,7 @@
                     void main() {
                         vec4 hdrColor = texture(hdrSampler, texCoord);
 
-                        hdrColor += texture(bloomSampler, texCoord);
+                        hdrColor += properties.bloomIntensity * texture(bloomSampler, texCoord);
 
                         float luminance = texture(lumSampler, vec2(0.5, 0.5)).r;
 
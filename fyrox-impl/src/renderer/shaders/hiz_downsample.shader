@@ -0,0 +1,81 @@
+(
+    name: "HiZDownsample",
+    resources: [
+        (
+            name: "sourceTexture",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 0
+        ),
+        (
+            name: "properties",
+            kind: PropertyGroup([
+                (name: "worldViewProjection", kind: Matrix4()),
+                (name: "destinationSize", kind: Vector2()),
+            ]),
+            binding: 0
+        ),
+    ],
+    passes: [
+        (
+            name: "Primary",
+
+            draw_parameters: DrawParameters(
+                cull_face: None,
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: None,
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+                scissor_box: None
+            ),
+
+            vertex_shader:
+                r#"
+                    layout (location = 0) in vec3 vertexPosition;
+
+                    void main()
+                    {
+                        gl_Position = properties.worldViewProjection * vec4(vertexPosition, 1.0);
+                    }
+                "#,
+
+            fragment_shader:
+                r#"
+                    out float outDepth;
+
+                    void main() {
+                        ivec2 sourceSize = textureSize(sourceTexture, 0);
+                        ivec2 destinationSize = ivec2(properties.destinationSize);
+                        ivec2 destination = ivec2(gl_FragCoord.xy);
+
+                        // Every destination texel covers a range of source texels, the range is
+                        // not always 2x2 when the source size is odd.
+                        ivec2 begin = destination * sourceSize / destinationSize;
+                        ivec2 end = max(begin, (destination + 1) * sourceSize / destinationSize - 1);
+                        end = min(end, min(begin + 2, sourceSize - 1));
+
+                        // Keep the farthest depth, so an object that is behind every texel of the
+                        // covered area is guaranteed to be occluded.
+                        float maxDepth = 0.0;
+                        for (int y = begin.y; y <= end.y; ++y) {
+                            for (int x = begin.x; x <= end.x; ++x) {
+                                maxDepth = max(maxDepth, texelFetch(sourceTexture, ivec2(x, y), 0).r);
+                            }
+                        }
+                        outDepth = maxDepth;
+                    }
+                "#,
+        )
+    ]
+)
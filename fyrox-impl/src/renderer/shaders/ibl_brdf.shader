@@ -0,0 +1,97 @@
+(
+    name: "IblBrdf",
+    resources: [
+        (
+            name: "properties",
+            kind: PropertyGroup([
+                (name: "worldViewProjection", kind: Matrix4()),
+                (name: "lutSize", kind: Float()),
+            ]),
+            binding: 0
+        ),
+    ],
+    passes: [
+        (
+            name: "Primary",
+
+            draw_parameters: DrawParameters(
+                cull_face: None,
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: None,
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+                scissor_box: None
+            ),
+
+            vertex_shader:
+                r#"
+                    layout (location = 0) in vec3 vertexPosition;
+
+                    void main()
+                    {
+                        gl_Position = properties.worldViewProjection * vec4(vertexPosition, 1.0);
+                    }
+                "#,
+
+            fragment_shader:
+                r#"
+                    out vec4 FragColor;
+
+                    const uint sampleCount = 512u;
+
+                    // Geometry term of the split-sum approximation, it uses different remapping of the
+                    // roughness than the analytical lights.
+                    float GeometrySchlickGGX(float NdotV, float roughness)
+                    {
+                        float a = roughness * roughness;
+                        float k = a / 2.0;
+                        return NdotV / (NdotV * (1.0 - k) + k);
+                    }
+
+                    void main()
+                    {
+                        vec2 uv = gl_FragCoord.xy / properties.lutSize;
+                        float NdotV = max(uv.x, 0.001);
+                        float roughness = uv.y;
+
+                        vec3 V = vec3(sqrt(1.0 - NdotV * NdotV), 0.0, NdotV);
+                        vec3 N = vec3(0.0, 0.0, 1.0);
+
+                        float scale = 0.0;
+                        float bias = 0.0;
+                        for (uint i = 0u; i < sampleCount; ++i) {
+                            vec2 Xi = S_Hammersley(i, sampleCount);
+                            vec3 H = S_ImportanceSampleGGX(Xi, N, roughness);
+                            vec3 L = normalize(2.0 * dot(V, H) * H - V);
+
+                            float NdotL = max(L.z, 0.0);
+                            float NdotH = max(H.z, 0.0);
+                            float VdotH = max(dot(V, H), 0.0);
+
+                            if (NdotL > 0.0) {
+                                float G = GeometrySchlickGGX(NdotV, roughness) * GeometrySchlickGGX(NdotL, roughness);
+                                float visibility = (G * VdotH) / (NdotH * NdotV);
+                                float Fc = pow(1.0 - VdotH, 5.0);
+                                scale += (1.0 - Fc) * visibility;
+                                bias += Fc * visibility;
+                            }
+                        }
+
+                        FragColor = vec4(scale / float(sampleCount), bias / float(sampleCount), 0.0, 1.0);
+                    }
+                "#,
+        )
+    ]
+)
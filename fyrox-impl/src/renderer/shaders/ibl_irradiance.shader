@@ -0,0 +1,87 @@
+(
+    name: "IblIrradiance",
+    resources: [
+        (
+            name: "sourceTexture",
+            kind: Texture(kind: SamplerCube, fallback: Black),
+            binding: 0
+        ),
+        (
+            name: "properties",
+            kind: PropertyGroup([
+                (name: "worldViewProjection", kind: Matrix4()),
+                (name: "faceIndex", kind: Int()),
+                (name: "faceSize", kind: Float()),
+                (name: "sourceLod", kind: Float()),
+            ]),
+            binding: 0
+        ),
+    ],
+    passes: [
+        (
+            name: "Primary",
+
+            draw_parameters: DrawParameters(
+                cull_face: None,
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: None,
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+                scissor_box: None
+            ),
+
+            vertex_shader:
+                r#"
+                    layout (location = 0) in vec3 vertexPosition;
+
+                    void main()
+                    {
+                        gl_Position = properties.worldViewProjection * vec4(vertexPosition, 1.0);
+                    }
+                "#,
+
+            fragment_shader:
+                r#"
+                    out vec4 FragColor;
+
+                    void main()
+                    {
+                        vec2 uv = gl_FragCoord.xy / properties.faceSize;
+                        vec3 N = S_CubeFaceDirection(properties.faceIndex, uv);
+
+                        vec3 up = abs(N.y) < 0.999 ? vec3(0.0, 1.0, 0.0) : vec3(0.0, 0.0, 1.0);
+                        vec3 right = normalize(cross(up, N));
+                        up = cross(N, right);
+
+                        // Cosine-weighted integral over the hemisphere around the normal.
+                        const float sampleDelta = 0.05;
+                        vec3 irradiance = vec3(0.0);
+                        float sampleCount = 0.0;
+                        for (float phi = 0.0; phi < 2.0 * PI; phi += sampleDelta) {
+                            for (float theta = 0.0; theta < 0.5 * PI; theta += sampleDelta) {
+                                vec3 tangentSample = vec3(sin(theta) * cos(phi), sin(theta) * sin(phi), cos(theta));
+                                vec3 direction = tangentSample.x * right + tangentSample.y * up + tangentSample.z * N;
+                                vec3 radiance = textureLod(sourceTexture, direction, properties.sourceLod).rgb;
+                                irradiance += radiance * cos(theta) * sin(theta);
+                                sampleCount += 1.0;
+                            }
+                        }
+
+                        FragColor = vec4(PI * irradiance / sampleCount, 1.0);
+                    }
+                "#,
+        )
+    ]
+)
@@ -0,0 +1,105 @@
+(
+    name: "IblPrefilter",
+    resources: [
+        (
+            name: "sourceTexture",
+            kind: Texture(kind: SamplerCube, fallback: Black),
+            binding: 0
+        ),
+        (
+            name: "properties",
+            kind: PropertyGroup([
+                (name: "worldViewProjection", kind: Matrix4()),
+                (name: "faceIndex", kind: Int()),
+                (name: "faceSize", kind: Float()),
+                (name: "sourceSize", kind: Float()),
+                (name: "sourceLod", kind: Float()),
+                (name: "roughness", kind: Float()),
+            ]),
+            binding: 0
+        ),
+    ],
+    passes: [
+        (
+            name: "Primary",
+
+            draw_parameters: DrawParameters(
+                cull_face: None,
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: None,
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+                scissor_box: None
+            ),
+
+            vertex_shader:
+                r#"
+                    layout (location = 0) in vec3 vertexPosition;
+
+                    void main()
+                    {
+                        gl_Position = properties.worldViewProjection * vec4(vertexPosition, 1.0);
+                    }
+                "#,
+
+            fragment_shader:
+                r#"
+                    out vec4 FragColor;
+
+                    const uint sampleCount = 256u;
+
+                    void main()
+                    {
+                        vec2 uv = gl_FragCoord.xy / properties.faceSize;
+                        vec3 N = S_CubeFaceDirection(properties.faceIndex, uv);
+
+                        float roughness = properties.roughness;
+                        if (roughness <= 0.0) {
+                            FragColor = vec4(textureLod(sourceTexture, N, properties.sourceLod).rgb, 1.0);
+                            return;
+                        }
+
+                        // The view direction is assumed to be equal to the normal. The samples are taken from
+                        // the mip level that matches the solid angle of a sample, this removes the aliasing
+                        // of bright spots of the environment.
+                        vec3 V = N;
+                        float texelSolidAngle = 4.0 * PI / (6.0 * properties.sourceSize * properties.sourceSize);
+
+                        vec3 color = vec3(0.0);
+                        float totalWeight = 0.0;
+                        for (uint i = 0u; i < sampleCount; ++i) {
+                            vec2 Xi = S_Hammersley(i, sampleCount);
+                            vec3 H = S_ImportanceSampleGGX(Xi, N, roughness);
+                            vec3 L = normalize(2.0 * dot(V, H) * H - V);
+
+                            float NdotL = dot(N, L);
+                            if (NdotL > 0.0) {
+                                float NdotH = max(dot(N, H), 0.0);
+                                float HdotV = max(dot(H, V), 0.0);
+                                float pdf = S_DistributionGGX(N, H, roughness) * NdotH / (4.0 * HdotV) + 0.0001;
+                                float sampleSolidAngle = 1.0 / (float(sampleCount) * pdf + 0.0001);
+                                float lod = max(0.5 * log2(sampleSolidAngle / texelSolidAngle) + 1.0, 0.0);
+
+                                color += textureLod(sourceTexture, L, lod).rgb * NdotL;
+                                totalWeight += NdotL;
+                            }
+                        }
+
+                        FragColor = vec4(color / max(totalWeight, 0.0001), 1.0);
+                    }
+                "#,
+        )
+    ]
+)
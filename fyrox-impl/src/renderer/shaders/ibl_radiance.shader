@@ -0,0 +1,84 @@
+(
+    name: "IblRadiance",
+    resources: [
+        (
+            name: "sourceTexture",
+            kind: Texture(kind: SamplerCube, fallback: Black),
+            binding: 0
+        ),
+        (
+            name: "properties",
+            kind: PropertyGroup([
+                (name: "worldViewProjection", kind: Matrix4()),
+                (name: "faceIndex", kind: Int()),
+                (name: "faceSize", kind: Float()),
+                (name: "tapCount", kind: Int()),
+                (name: "decodeSrgb", kind: Bool()),
+            ]),
+            binding: 0
+        ),
+    ],
+    passes: [
+        (
+            name: "Primary",
+
+            draw_parameters: DrawParameters(
+                cull_face: None,
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: None,
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+                scissor_box: None
+            ),
+
+            vertex_shader:
+                r#"
+                    layout (location = 0) in vec3 vertexPosition;
+
+                    void main()
+                    {
+                        gl_Position = properties.worldViewProjection * vec4(vertexPosition, 1.0);
+                    }
+                "#,
+
+            fragment_shader:
+                r#"
+                    out vec4 FragColor;
+
+                    void main()
+                    {
+                        // Average a grid of taps over the footprint of the texel, otherwise the minified
+                        // environment will be noisy.
+                        vec2 texel = floor(gl_FragCoord.xy);
+                        int tapCount = properties.tapCount;
+                        vec3 color = vec3(0.0);
+                        for (int y = 0; y < tapCount; ++y) {
+                            for (int x = 0; x < tapCount; ++x) {
+                                vec2 offset = (vec2(x, y) + 0.5) / float(tapCount);
+                                vec2 uv = (texel + offset) / properties.faceSize;
+                                vec3 direction = S_CubeFaceDirection(properties.faceIndex, uv);
+                                vec4 tap = textureLod(sourceTexture, direction, 0.0);
+                                if (properties.decodeSrgb) {
+                                    tap = S_SRGBToLinear(tap);
+                                }
+                                color += tap.rgb;
+                            }
+                        }
+                        FragColor = vec4(color / float(tapCount * tapCount), 1.0);
+                    }
+                "#,
+        )
+    ]
+)
@@ -0,0 +1,147 @@
+(
+    name: "MotionBlur",
+    resources: [
+        (
+            name: "frameTexture",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 0
+        ),
+        (
+            name: "velocityTexture",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 1
+        ),
+        (
+            name: "neighborMaxTexture",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 2
+        ),
+        (
+            name: "properties",
+            kind: PropertyGroup([
+                (name: "worldViewProjection", kind: Matrix4()),
+                (name: "viewportMin", kind: Vector2()),
+                (name: "viewportMax", kind: Vector2()),
+                (name: "inverseFrameSize", kind: Vector2()),
+                (name: "tileSize", kind: Int()),
+                (name: "sampleCount", kind: Int()),
+            ]),
+            binding: 0
+        ),
+    ],
+    passes: [
+        (
+            name: "Primary",
+
+            draw_parameters: DrawParameters(
+                cull_face: None,
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: None,
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+                scissor_box: None
+            ),
+
+            vertex_shader:
+                r#"
+                    layout (location = 0) in vec3 vertexPosition;
+
+                    void main()
+                    {
+                        gl_Position = properties.worldViewProjection * vec4(vertexPosition, 1.0);
+                    }
+                "#,
+
+            fragment_shader:
+                r#"
+                    out vec4 FragColor;
+
+                    // Depth range (in world units) in which the surfaces are considered to be at the
+                    // same depth.
+                    const float SoftDepthExtent = 0.1;
+
+                    // Returns 1.0 if the first surface is in front of the second one.
+                    float SoftDepthCompare(float a, float b)
+                    {
+                        return clamp(1.0 - (a - b) / SoftDepthExtent, 0.0, 1.0);
+                    }
+
+                    float Cone(float offsetLength, float radiusLength)
+                    {
+                        return clamp(1.0 - offsetLength / radiusLength, 0.0, 1.0);
+                    }
+
+                    float Cylinder(float offsetLength, float radiusLength)
+                    {
+                        return 1.0 - smoothstep(0.95 * radiusLength, 1.05 * radiusLength, offsetLength);
+                    }
+
+                    float InterleavedGradientNoise(vec2 position)
+                    {
+                        return fract(52.9829189 * fract(dot(position, vec2(0.06711056, 0.00583715))));
+                    }
+
+                    void main()
+                    {
+                        vec2 uv = gl_FragCoord.xy * properties.inverseFrameSize;
+                        vec4 color = texture(frameTexture, uv);
+
+                        vec2 neighborMax = texelFetch(neighborMaxTexture, ivec2(gl_FragCoord.xy) / properties.tileSize, 0).xy;
+                        if (length(neighborMax) < 0.5) {
+                            // Nothing moves around, keep the pixel sharp.
+                            FragColor = color;
+                            return;
+                        }
+
+                        vec3 center = texture(velocityTexture, uv).xyz;
+                        float centerLength = max(length(center.xy), 0.5);
+                        float centerDepth = center.z;
+
+                        float totalWeight = 1.0 / centerLength;
+                        vec3 sum = color.rgb * totalWeight;
+
+                        // Samples are taken along the dominant velocity of the neighbourhood, their
+                        // positions are jittered to replace banding with noise.
+                        float jitter = InterleavedGradientNoise(gl_FragCoord.xy) - 0.5;
+                        int sampleCount = max(properties.sampleCount, 1);
+                        for (int i = 0; i < sampleCount; ++i) {
+                            float t = mix(-1.0, 1.0, (float(i) + 0.5 + jitter) / float(sampleCount));
+                            vec2 offset = neighborMax * t;
+                            float offsetLength = length(offset);
+                            vec2 sampleUV = clamp(uv + offset * properties.inverseFrameSize, properties.viewportMin, properties.viewportMax);
+
+                            vec3 sampleVelocity = texture(velocityTexture, sampleUV).xyz;
+                            float sampleLength = max(length(sampleVelocity.xy), 0.5);
+                            float sampleDepth = sampleVelocity.z;
+
+                            // A sample in front contributes if its blur covers the pixel, a sample
+                            // behind contributes if it is seen through the blur of the pixel, and
+                            // the samples at the same depth blend together.
+                            float front = SoftDepthCompare(sampleDepth, centerDepth);
+                            float back = SoftDepthCompare(centerDepth, sampleDepth);
+                            float weight = front * Cone(offsetLength, sampleLength)
+                                + back * Cone(offsetLength, centerLength)
+                                + 2.0 * Cylinder(offsetLength, sampleLength) * Cylinder(offsetLength, centerLength);
+
+                            sum += texture(frameTexture, sampleUV).rgb * weight;
+                            totalWeight += weight;
+                        }
+
+                        FragColor = vec4(sum / totalWeight, color.a);
+                    }
+                "#,
+        )
+    ]
+)
@@ -0,0 +1,85 @@
+(
+    name: "MotionBlurNeighborMax",
+    resources: [
+        (
+            name: "tileMaxTexture",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 0
+        ),
+        (
+            name: "properties",
+            kind: PropertyGroup([
+                (name: "worldViewProjection", kind: Matrix4()),
+                (name: "tileViewportOrigin", kind: Vector2()),
+                (name: "tileViewportSize", kind: Vector2()),
+            ]),
+            binding: 0
+        ),
+    ],
+    passes: [
+        (
+            name: "Primary",
+
+            draw_parameters: DrawParameters(
+                cull_face: None,
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: None,
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+                scissor_box: None
+            ),
+
+            vertex_shader:
+                r#"
+                    layout (location = 0) in vec3 vertexPosition;
+
+                    void main()
+                    {
+                        gl_Position = properties.worldViewProjection * vec4(vertexPosition, 1.0);
+                    }
+                "#,
+
+            fragment_shader:
+                r#"
+                    out vec4 FragColor;
+
+                    void main()
+                    {
+                        ivec2 tile = ivec2(gl_FragCoord.xy);
+                        ivec2 tileMin = ivec2(properties.tileViewportOrigin);
+                        ivec2 tileMax = ivec2(properties.tileViewportOrigin + properties.tileViewportSize) - 1;
+
+                        // A pixel could be covered by the blur of any object from the neighbouring
+                        // tiles, because the blur radius never exceeds the size of a tile.
+                        vec2 maxRadius = vec2(0.0);
+                        float maxLength = 0.0;
+                        for (int y = -1; y <= 1; ++y) {
+                            for (int x = -1; x <= 1; ++x) {
+                                ivec2 neighbor = clamp(tile + ivec2(x, y), tileMin, tileMax);
+                                vec2 radius = texelFetch(tileMaxTexture, neighbor, 0).xy;
+                                float radiusLength = dot(radius, radius);
+                                if (radiusLength > maxLength) {
+                                    maxLength = radiusLength;
+                                    maxRadius = radius;
+                                }
+                            }
+                        }
+
+                        FragColor = vec4(maxRadius, 0.0, 1.0);
+                    }
+                "#,
+        )
+    ]
+)
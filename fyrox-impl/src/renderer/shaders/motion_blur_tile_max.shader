@@ -0,0 +1,86 @@
+(
+    name: "MotionBlurTileMax",
+    resources: [
+        (
+            name: "velocityTexture",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 0
+        ),
+        (
+            name: "properties",
+            kind: PropertyGroup([
+                (name: "worldViewProjection", kind: Matrix4()),
+                (name: "viewportOrigin", kind: Vector2()),
+                (name: "viewportSize", kind: Vector2()),
+                (name: "tileSize", kind: Int()),
+            ]),
+            binding: 0
+        ),
+    ],
+    passes: [
+        (
+            name: "Primary",
+
+            draw_parameters: DrawParameters(
+                cull_face: None,
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: None,
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+                scissor_box: None
+            ),
+
+            vertex_shader:
+                r#"
+                    layout (location = 0) in vec3 vertexPosition;
+
+                    void main()
+                    {
+                        gl_Position = properties.worldViewProjection * vec4(vertexPosition, 1.0);
+                    }
+                "#,
+
+            fragment_shader:
+                r#"
+                    out vec4 FragColor;
+
+                    void main()
+                    {
+                        ivec2 tile = ivec2(gl_FragCoord.xy);
+                        ivec2 viewportBegin = ivec2(properties.viewportOrigin);
+                        ivec2 viewportEnd = ivec2(properties.viewportOrigin + properties.viewportSize);
+                        ivec2 begin = max(tile * properties.tileSize, viewportBegin);
+                        ivec2 end = min((tile + 1) * properties.tileSize, viewportEnd);
+
+                        // Find the dominant (the longest) velocity in the tile.
+                        vec2 maxRadius = vec2(0.0);
+                        float maxLength = 0.0;
+                        for (int y = begin.y; y < end.y; ++y) {
+                            for (int x = begin.x; x < end.x; ++x) {
+                                vec2 radius = texelFetch(velocityTexture, ivec2(x, y), 0).xy;
+                                float radiusLength = dot(radius, radius);
+                                if (radiusLength > maxLength) {
+                                    maxLength = radiusLength;
+                                    maxRadius = radius;
+                                }
+                            }
+                        }
+
+                        FragColor = vec4(maxRadius, 0.0, 1.0);
+                    }
+                "#,
+        )
+    ]
+)
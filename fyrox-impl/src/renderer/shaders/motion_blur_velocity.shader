@@ -0,0 +1,103 @@
+(
+    name: "MotionBlurVelocity",
+    resources: [
+        (
+            name: "velocityTexture",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 0
+        ),
+        (
+            name: "depthTexture",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 1
+        ),
+        (
+            name: "properties",
+            kind: PropertyGroup([
+                (name: "worldViewProjection", kind: Matrix4()),
+                (name: "invViewProj", kind: Matrix4()),
+                (name: "prevViewProj", kind: Matrix4()),
+                (name: "inverseProjectionMatrix", kind: Matrix4()),
+                (name: "viewportOrigin", kind: Vector2()),
+                (name: "viewportSize", kind: Vector2()),
+                (name: "inverseFrameSize", kind: Vector2()),
+                (name: "velocityScale", kind: Float()),
+                (name: "maxRadius", kind: Float()),
+            ]),
+            binding: 0
+        ),
+    ],
+    passes: [
+        (
+            name: "Primary",
+
+            draw_parameters: DrawParameters(
+                cull_face: None,
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: None,
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+                scissor_box: None
+            ),
+
+            vertex_shader:
+                r#"
+                    layout (location = 0) in vec3 vertexPosition;
+
+                    void main()
+                    {
+                        gl_Position = properties.worldViewProjection * vec4(vertexPosition, 1.0);
+                    }
+                "#,
+
+            fragment_shader:
+                r#"
+                    out vec4 FragColor;
+
+                    void main()
+                    {
+                        vec2 uv = gl_FragCoord.xy * properties.inverseFrameSize;
+                        float depth = texture(depthTexture, uv).r;
+                        vec2 screenPos = (gl_FragCoord.xy - properties.viewportOrigin) / properties.viewportSize;
+
+                        // Velocity in the viewport texture coordinates.
+                        vec2 velocity;
+                        vec4 objectVelocity = texture(velocityTexture, uv);
+                        if (objectVelocity.a > 0.5) {
+                            velocity = objectVelocity.xy;
+                        } else {
+                            // No object velocity, reproject using depth and camera matrices.
+                            vec3 worldPosition = S_UnProject(vec3(screenPos, depth), properties.invViewProj);
+                            vec3 prevScreenPos = S_Project(worldPosition, properties.prevViewProj);
+                            velocity = screenPos - prevScreenPos.xy;
+                        }
+
+                        // The pixel is smeared in both directions of its motion, so the blur radius is
+                        // a half of the distance (in pixels) passed while the shutter is open.
+                        vec2 radius = 0.5 * velocity * properties.viewportSize * properties.velocityScale;
+                        float radiusLength = length(radius);
+                        if (radiusLength > properties.maxRadius) {
+                            radius *= properties.maxRadius / radiusLength;
+                        }
+
+                        vec3 viewPosition = S_UnProject(vec3(screenPos, depth), properties.inverseProjectionMatrix);
+                        float viewDistance = clamp(-viewPosition.z, 0.0, 60000.0);
+
+                        FragColor = vec4(radius, viewDistance, 1.0);
+                    }
+                "#,
+        )
+    ]
+)
@@ -0,0 +1,130 @@
+(
+    name: "Outline",
+    resources: [
+        (
+            name: "maskTexture",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 0
+        ),
+        (
+            name: "maskDepthTexture",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 1
+        ),
+        (
+            name: "sceneDepthTexture",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 2
+        ),
+        (
+            name: "properties",
+            kind: PropertyGroup([
+                (name: "worldViewProjection", kind: Matrix4()),
+                (name: "color", kind: Vector4()),
+                (name: "width", kind: Float()),
+                (name: "seeThrough", kind: Bool()),
+            ]),
+            binding: 0
+        ),
+    ],
+    passes: [
+        (
+            name: "Primary",
+
+            draw_parameters: DrawParameters(
+                cull_face: None,
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: None,
+                blend: Some(BlendParameters(
+                    func: BlendFunc(
+                        sfactor: SrcAlpha,
+                        dfactor: OneMinusSrcAlpha,
+                        alpha_sfactor: SrcAlpha,
+                        alpha_dfactor: OneMinusSrcAlpha,
+                    ),
+                    equation: BlendEquation(
+                        rgb: Add,
+                        alpha: Add
+                    )
+                )),
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+                scissor_box: None
+            ),
+
+            vertex_shader:
+                r#"
+                    layout(location = 0) in vec3 vertexPosition;
+                    layout(location = 1) in vec2 vertexTexCoord;
+
+                    out vec2 texCoord;
+
+                    void main()
+                    {
+                        texCoord = vertexTexCoord;
+                        gl_Position = properties.worldViewProjection * vec4(vertexPosition, 1.0);
+                    }
+                "#,
+
+            fragment_shader:
+                r#"
+                    // Must match MAX_OUTLINE_WIDTH in the outline render pass.
+                    const int MAX_WIDTH = 16;
+
+                    layout (location = 0) out vec4 outColor;
+
+                    in vec2 texCoord;
+
+                    // Returns 1.0 if the given pixel is covered by an outlined object, that is
+                    // visible (or the outline is see-through), 0.0 - otherwise.
+                    float Coverage(ivec2 pixel, ivec2 size)
+                    {
+                        pixel = clamp(pixel, ivec2(0), size - 1);
+                        if (texelFetch(maskTexture, pixel, 0).a <= 0.0) {
+                            return 0.0;
+                        }
+                        if (properties.seeThrough) {
+                            return 1.0;
+                        }
+                        float objectDepth = texelFetch(maskDepthTexture, pixel, 0).r;
+                        float sceneDepth = texelFetch(sceneDepthTexture, pixel, 0).r;
+                        return objectDepth <= sceneDepth + 0.0001 ? 1.0 : 0.0;
+                    }
+
+                    void main() {
+                        ivec2 size = textureSize(maskTexture, 0);
+                        ivec2 pixel = ivec2(texCoord * vec2(size));
+
+                        // The outline is drawn outside of the objects only.
+                        if (Coverage(pixel, size) > 0.0) {
+                            discard;
+                        }
+
+                        float width = properties.width;
+                        int radius = min(int(ceil(width)), MAX_WIDTH);
+                        for (int y = -radius; y <= radius; ++y) {
+                            for (int x = -radius; x <= radius; ++x) {
+                                if (length(vec2(x, y)) <= width && Coverage(pixel + ivec2(x, y), size) > 0.0) {
+                                    outColor = properties.color;
+                                    return;
+                                }
+                            }
+                        }
+
+                        discard;
+                    }
+                "#,
+        )
+    ]
+)
@@ -10,6 +10,7 @@
             name: "properties",
             kind: PropertyGroup([
                 (name: "worldViewProjection", kind: Matrix4()),
+                (name: "decodeSrgb", kind: Bool()),
             ]),
             binding: 0
         ),
@@ -60,7 +61,8 @@
 
                     void main()
                     {
-                        FragColor = S_SRGBToLinear(texture(cubemapTexture, texCoord));
+                        vec4 color = texture(cubemapTexture, texCoord);
+                        FragColor = properties.decodeSrgb ? S_SRGBToLinear(color) : color;
                     }
                 "#,
         )
@@ -0,0 +1,166 @@
+(
+    name: "ScreenSpaceReflections",
+    resources: [
+        (
+            name: "depthTexture",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 0
+        ),
+        (
+            name: "normalTexture",
+            kind: Texture(kind: Sampler2D, fallback: Normal),
+            binding: 1
+        ),
+        (
+            name: "materialTexture",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 2
+        ),
+        (
+            name: "frameTexture",
+            kind: Texture(kind: Sampler2D, fallback: Black),
+            binding: 3
+        ),
+        (
+            name: "properties",
+            kind: PropertyGroup([
+                (name: "worldViewProjection", kind: Matrix4()),
+                (name: "projectionMatrix", kind: Matrix4()),
+                (name: "inverseProjectionMatrix", kind: Matrix4()),
+                (name: "viewMatrix", kind: Matrix3()),
+                (name: "maxSteps", kind: Int()),
+                (name: "maxDistance", kind: Float()),
+                (name: "thickness", kind: Float()),
+                (name: "maxRoughness", kind: Float()),
+            ]),
+            binding: 0
+        ),
+    ],
+    passes: [
+        (
+            name: "Primary",
+
+            draw_parameters: DrawParameters(
+                cull_face: None,
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: None,
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+                scissor_box: None
+            ),
+
+            vertex_shader:
+                r#"
+                    layout (location = 0) in vec3 vertexPosition;
+                    layout (location = 1) in vec2 vertexTexCoord;
+
+                    out vec2 texCoord;
+
+                    void main()
+                    {
+                        texCoord = vertexTexCoord;
+                        gl_Position = properties.worldViewProjection * vec4(vertexPosition, 1.0);
+                    }
+                "#,
+
+            fragment_shader:
+                r#"
+                    // Amount of steps of binary search that refines intersection point.
+                    const int refinementSteps = 6;
+
+                    out vec4 FragColor;
+
+                    in vec2 texCoord;
+
+                    vec3 GetViewSpacePosition(vec2 screenCoord) {
+                        return S_UnProject(vec3(screenCoord, texture(depthTexture, screenCoord).r), properties.inverseProjectionMatrix);
+                    }
+
+                    // Returns signed distance along view axis between the scene surface and the given
+                    // point. Positive value means that the point is behind the surface.
+                    float DepthDelta(vec3 point, out vec2 screenCoord) {
+                        screenCoord = S_Project(point, properties.projectionMatrix).xy;
+                        return GetViewSpacePosition(screenCoord).z - point.z;
+                    }
+
+                    void main() {
+                        float depth = texture(depthTexture, texCoord).r;
+                        if (depth >= 1.0) {
+                            // Nothing to reflect on the background.
+                            FragColor = vec4(0.0);
+                            return;
+                        }
+
+                        vec3 fragPos = S_UnProject(vec3(texCoord, depth), properties.inverseProjectionMatrix);
+                        vec3 worldSpaceNormal = normalize(texture(normalTexture, texCoord).xyz * 2.0 - 1.0);
+                        vec3 viewSpaceNormal = normalize(properties.viewMatrix * worldSpaceNormal);
+                        vec3 reflected = normalize(reflect(normalize(fragPos), viewSpaceNormal));
+
+                        float roughness = texture(materialTexture, texCoord).g;
+                        if (roughness > properties.maxRoughness) {
+                            FragColor = vec4(0.0);
+                            return;
+                        }
+
+                        float stepSize = properties.maxDistance / float(max(properties.maxSteps, 1));
+                        // Offset the origin a bit to prevent self-intersection.
+                        vec3 rayPos = fragPos + viewSpaceNormal * 0.02;
+                        vec2 hitCoord = vec2(0.0);
+                        float hit = 0.0;
+                        for (int i = 0; i < properties.maxSteps; ++i) {
+                            rayPos += reflected * stepSize;
+
+                            vec2 screenCoord;
+                            float delta = DepthDelta(rayPos, screenCoord);
+
+                            if (screenCoord.x < 0.0 || screenCoord.x > 1.0 || screenCoord.y < 0.0 || screenCoord.y > 1.0) {
+                                break;
+                            }
+
+                            if (delta > 0.0 && delta < properties.thickness) {
+                                // Refine intersection point using binary search between last two points.
+                                vec3 begin = rayPos - reflected * stepSize;
+                                vec3 end = rayPos;
+                                hitCoord = screenCoord;
+                                for (int j = 0; j < refinementSteps; ++j) {
+                                    vec3 middle = (begin + end) * 0.5;
+                                    vec2 middleCoord;
+                                    if (DepthDelta(middle, middleCoord) > 0.0) {
+                                        end = middle;
+                                        hitCoord = middleCoord;
+                                    } else {
+                                        begin = middle;
+                                    }
+                                }
+
+                                // Fade out reflections near screen edges and at the end of the ray to
+                                // hide the transition to the reflections of the ambient lighting.
+                                vec2 edge = abs(hitCoord * 2.0 - 1.0);
+                                float edgeFade = 1.0 - smoothstep(0.8, 1.0, max(edge.x, edge.y));
+                                float distanceFade = 1.0 - float(i) / float(properties.maxSteps);
+                                hit = edgeFade * distanceFade;
+                                break;
+                            }
+                        }
+
+                        // Rays that missed the scene geometry contribute nothing, the reflections of the
+                        // environment (or reflection probes) are already added by the ambient lighting.
+                        vec3 sceneColor = texture(frameTexture, hitCoord).rgb;
+                        FragColor = vec4(sceneColor * hit, hit);
+                    }
+                "#,
+        )
+    ]
+)
@@ -0,0 +1,138 @@
+(
+    name: "ScreenSpaceReflectionsComposite",
+    resources: [
+        (
+            name: "reflectionTexture",
+            kind: Texture(kind: Sampler2D, fallback: Black),
+            binding: 0
+        ),
+        (
+            name: "blurredReflectionTexture",
+            kind: Texture(kind: Sampler2D, fallback: Black),
+            binding: 1
+        ),
+        (
+            name: "depthTexture",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 2
+        ),
+        (
+            name: "diffuseTexture",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 3
+        ),
+        (
+            name: "normalTexture",
+            kind: Texture(kind: Sampler2D, fallback: Normal),
+            binding: 4
+        ),
+        (
+            name: "materialTexture",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 5
+        ),
+        (
+            name: "properties",
+            kind: PropertyGroup([
+                (name: "worldViewProjection", kind: Matrix4()),
+                (name: "inverseProjectionMatrix", kind: Matrix4()),
+                (name: "viewMatrix", kind: Matrix3()),
+                (name: "maxRoughness", kind: Float()),
+                (name: "useBlur", kind: Bool()),
+            ]),
+            binding: 0
+        ),
+    ],
+    passes: [
+        (
+            name: "Primary",
+
+            draw_parameters: DrawParameters(
+                cull_face: None,
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: false,
+                ),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: None,
+                blend: Some(BlendParameters(
+                    func: BlendFunc(
+                        sfactor: One,
+                        dfactor: One,
+                        alpha_sfactor: One,
+                        alpha_dfactor: One,
+                    ),
+                    equation: BlendEquation(
+                        rgb: Add,
+                        alpha: Add
+                    )
+                )),
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+                scissor_box: None
+            ),
+
+            vertex_shader:
+                r#"
+                    layout (location = 0) in vec3 vertexPosition;
+                    layout (location = 1) in vec2 vertexTexCoord;
+
+                    out vec2 texCoord;
+
+                    void main()
+                    {
+                        texCoord = vertexTexCoord;
+                        gl_Position = properties.worldViewProjection * vec4(vertexPosition, 1.0);
+                    }
+                "#,
+
+            fragment_shader:
+                r#"
+                    out vec4 FragColor;
+
+                    in vec2 texCoord;
+
+                    void main() {
+                        float depth = texture(depthTexture, texCoord).r;
+                        if (depth >= 1.0) {
+                            discard;
+                        }
+
+                        vec3 material = texture(materialTexture, texCoord).rgb;
+                        float metallic = material.x;
+                        float roughness = material.y;
+                        float ao = material.z;
+
+                        vec3 fragPos = S_UnProject(vec3(texCoord, depth), properties.inverseProjectionMatrix);
+                        vec3 viewSpaceNormal = normalize(properties.viewMatrix * (texture(normalTexture, texCoord).xyz * 2.0 - 1.0));
+                        float NdotV = max(dot(viewSpaceNormal, -normalize(fragPos)), 0.0);
+
+                        // Rough surfaces use blurred reflections, the blur amount grows with roughness.
+                        vec3 sharp = texture(reflectionTexture, texCoord).rgb;
+                        vec3 reflection = sharp;
+                        if (properties.useBlur) {
+                            vec3 blurred = texture(blurredReflectionTexture, texCoord).rgb;
+                            reflection = mix(sharp, blurred, clamp(roughness / max(properties.maxRoughness, 0.001), 0.0, 1.0));
+                        }
+
+                        // Fresnel-Schlick with roughness, so rough dielectrics do not look like mirrors at
+                        // grazing angles.
+                        vec3 albedo = S_SRGBToLinear(texture(diffuseTexture, texCoord)).rgb;
+                        vec3 F0 = mix(vec3(0.04), albedo, metallic);
+                        vec3 F = F0 + (max(vec3(1.0 - roughness), F0) - F0) * pow(clamp(1.0 - NdotV, 0.0, 1.0), 5.0);
+
+                        float glossiness = 1.0 - roughness;
+
+                        FragColor = vec4(reflection * F * glossiness * glossiness * ao, 0.0);
+                    }
+                "#,
+        )
+    ]
+)
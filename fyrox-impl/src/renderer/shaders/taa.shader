@@ -0,0 +1,184 @@
+(
+    name: "TAA",
+    resources: [
+        (
+            name: "currentTexture",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 0
+        ),
+        (
+            name: "historyTexture",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 1
+        ),
+        (
+            name: "velocityTexture",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 2
+        ),
+        (
+            name: "depthTexture",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 3
+        ),
+        (
+            name: "properties",
+            kind: PropertyGroup([
+                (name: "worldViewProjection", kind: Matrix4()),
+                (name: "invViewProj", kind: Matrix4()),
+                (name: "prevViewProj", kind: Matrix4()),
+                (name: "viewportOrigin", kind: Vector2()),
+                (name: "viewportSize", kind: Vector2()),
+                (name: "inverseFrameSize", kind: Vector2()),
+                (name: "blendFactor", kind: Float()),
+                (name: "historyValid", kind: Bool()),
+            ]),
+            binding: 0
+        ),
+    ],
+    passes: [
+        (
+            name: "Primary",
+
+            draw_parameters: DrawParameters(
+                cull_face: None,
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: None,
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+                scissor_box: None
+            ),
+
+            vertex_shader:
+                r#"
+                    layout (location = 0) in vec3 vertexPosition;
+
+                    void main()
+                    {
+                        gl_Position = properties.worldViewProjection * vec4(vertexPosition, 1.0);
+                    }
+                "#,
+
+            fragment_shader:
+                r#"
+                    out vec4 FragColor;
+
+                    vec3 RGBToYCoCg(vec3 c)
+                    {
+                        return vec3(
+                            0.25 * c.r + 0.5 * c.g + 0.25 * c.b,
+                            0.5 * c.r - 0.5 * c.b,
+                            -0.25 * c.r + 0.5 * c.g - 0.25 * c.b
+                        );
+                    }
+
+                    vec3 YCoCgToRGB(vec3 c)
+                    {
+                        float t = c.x - c.z;
+                        return vec3(t + c.y, c.x + c.z, t - c.y);
+                    }
+
+                    // Clips the color towards the center of the box, this gives less ghosting than
+                    // simple clamping.
+                    vec3 ClipToAABB(vec3 color, vec3 minimum, vec3 maximum)
+                    {
+                        vec3 center = 0.5 * (maximum + minimum);
+                        vec3 extents = 0.5 * (maximum - minimum) + 0.0001;
+                        vec3 offset = color - center;
+                        vec3 units = abs(offset / extents);
+                        float maxUnit = max(units.x, max(units.y, units.z));
+                        if (maxUnit > 1.0) {
+                            return center + offset / maxUnit;
+                        }
+                        return color;
+                    }
+
+                    void main()
+                    {
+                        vec2 inverseFrameSize = properties.inverseFrameSize;
+                        vec2 uv = gl_FragCoord.xy * inverseFrameSize;
+
+                        vec4 current = texture(currentTexture, uv);
+
+                        // Gather color statistics of the neighbourhood and find the closest to
+                        // the camera pixel, its velocity gives better edges of moving objects.
+                        vec3 m1 = vec3(0.0);
+                        vec3 m2 = vec3(0.0);
+                        vec3 minColor = vec3(1.0e9);
+                        vec3 maxColor = vec3(-1.0e9);
+                        float closestDepth = 1.0;
+                        vec2 closestUV = uv;
+                        for (int y = -1; y <= 1; ++y) {
+                            for (int x = -1; x <= 1; ++x) {
+                                vec2 sampleUV = uv + vec2(x, y) * inverseFrameSize;
+                                vec3 color = RGBToYCoCg(texture(currentTexture, sampleUV).rgb);
+                                m1 += color;
+                                m2 += color * color;
+                                minColor = min(minColor, color);
+                                maxColor = max(maxColor, color);
+                                float depth = texture(depthTexture, sampleUV).r;
+                                if (depth < closestDepth) {
+                                    closestDepth = depth;
+                                    closestUV = sampleUV;
+                                }
+                            }
+                        }
+
+                        // Variance clipping makes the box tighter.
+                        vec3 mean = m1 / 9.0;
+                        vec3 sigma = sqrt(max(m2 / 9.0 - mean * mean, vec3(0.0)));
+                        minColor = max(minColor, mean - 1.25 * sigma);
+                        maxColor = min(maxColor, mean + 1.25 * sigma);
+
+                        // Velocity in the viewport texture coordinates.
+                        vec2 velocity;
+                        vec4 objectVelocity = texture(velocityTexture, closestUV);
+                        if (objectVelocity.a > 0.5) {
+                            velocity = objectVelocity.xy;
+                        } else {
+                            // No object velocity, reproject using depth and camera matrices.
+                            vec2 screenPos = (closestUV / inverseFrameSize - properties.viewportOrigin) / properties.viewportSize;
+                            vec3 worldPosition = S_UnProject(vec3(screenPos, closestDepth), properties.invViewProj);
+                            vec3 prevScreenPos = S_Project(worldPosition, properties.prevViewProj);
+                            velocity = screenPos - prevScreenPos.xy;
+                        }
+                        vec2 historyUV = uv - velocity * properties.viewportSize * inverseFrameSize;
+
+                        vec2 historyMin = properties.viewportOrigin * inverseFrameSize;
+                        vec2 historyMax = (properties.viewportOrigin + properties.viewportSize) * inverseFrameSize;
+                        if (!properties.historyValid
+                            || any(lessThan(historyUV, historyMin))
+                            || any(greaterThan(historyUV, historyMax))) {
+                            FragColor = current;
+                            return;
+                        }
+
+                        vec3 history = texture(historyTexture, historyUV).rgb;
+                        history = YCoCgToRGB(ClipToAABB(RGBToYCoCg(history), minColor, maxColor));
+
+                        // Weight the samples by inverse luminance to suppress flickering of very
+                        // bright pixels in HDR.
+                        float currentWeight = (1.0 - properties.blendFactor) / (1.0 + S_Luminance(current.rgb));
+                        float historyWeight = properties.blendFactor / (1.0 + S_Luminance(history));
+
+                        FragColor = vec4(
+                            (current.rgb * currentWeight + history * historyWeight) / (currentWeight + historyWeight),
+                            current.a
+                        );
+                    }
+                "#,
+        )
+    ]
+)
@@ -0,0 +1,82 @@
+(
+    name: "Velocity",
+    resources: [
+        (
+            name: "properties",
+            kind: PropertyGroup([
+                (name: "viewProjection", kind: Matrix4()),
+                (name: "currentViewProjection", kind: Matrix4()),
+                (name: "prevViewProjection", kind: Matrix4()),
+            ]),
+            binding: 0
+        ),
+        (
+            name: "instances",
+            kind: PropertyGroup([
+                (name: "worldMatrices", kind: Matrix4Array(max_len: 127, value: [])),
+                (name: "prevWorldMatrices", kind: Matrix4Array(max_len: 127, value: [])),
+            ]),
+            binding: 1
+        ),
+    ],
+    passes: [
+        (
+            name: "Primary",
+
+            draw_parameters: DrawParameters(
+                cull_face: Some(Back),
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: Some(LessOrEqual),
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+                scissor_box: None
+            ),
+
+            vertex_shader:
+                r#"
+                    layout (location = 0) in vec3 vertexPosition;
+
+                    out vec4 currentPosition;
+                    out vec4 prevPosition;
+
+                    void main()
+                    {
+                        vec4 worldPosition = instances.worldMatrices[gl_InstanceID] * vec4(vertexPosition, 1.0);
+                        vec4 prevWorldPosition = instances.prevWorldMatrices[gl_InstanceID] * vec4(vertexPosition, 1.0);
+                        currentPosition = properties.currentViewProjection * worldPosition;
+                        prevPosition = properties.prevViewProjection * prevWorldPosition;
+                        gl_Position = properties.viewProjection * worldPosition;
+                    }
+                "#,
+
+            fragment_shader:
+                r#"
+                    in vec4 currentPosition;
+                    in vec4 prevPosition;
+
+                    out vec4 FragColor;
+
+                    void main()
+                    {
+                        vec2 current = currentPosition.xy / currentPosition.w;
+                        vec2 prev = prevPosition.xy / prevPosition.w;
+                        // Velocity is stored in texture coordinates space, alpha marks pixels that
+                        // have per-object velocity.
+                        FragColor = vec4((current - prev) * 0.5, 0.0, 1.0);
+                    }
+                "#,
+        )
+    ]
+)
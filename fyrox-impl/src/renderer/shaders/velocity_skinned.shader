@@ -0,0 +1,116 @@
+(
+    name: "VelocitySkinned",
+    resources: [
+        (
+            name: "properties",
+            kind: PropertyGroup([
+                (name: "worldViewProjection", kind: Matrix4()),
+                (name: "currentWorldViewProjection", kind: Matrix4()),
+                (name: "prevWorldViewProjection", kind: Matrix4()),
+            ]),
+            binding: 0
+        ),
+        (
+            name: "boneMatrices",
+            kind: PropertyGroup([
+                (name: "matrices", kind: Matrix4Array(max_len: 255, value: [])),
+            ]),
+            binding: 1
+        ),
+        (
+            name: "prevBoneMatrices",
+            kind: PropertyGroup([
+                (name: "matrices", kind: Matrix4Array(max_len: 255, value: [])),
+            ]),
+            binding: 2
+        ),
+    ],
+    passes: [
+        (
+            name: "Primary",
+
+            draw_parameters: DrawParameters(
+                cull_face: Some(Back),
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: Some(LessOrEqual),
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+                scissor_box: None
+            ),
+
+            vertex_shader:
+                r#"
+                    layout (location = 0) in vec3 vertexPosition;
+                    layout (location = 4) in vec4 boneWeights;
+                    layout (location = 5) in vec4 boneIndices;
+
+                    out vec4 currentPosition;
+                    out vec4 prevPosition;
+
+                    vec4 Skin(mat4 m0, mat4 m1, mat4 m2, mat4 m3, vec4 position)
+                    {
+                        return m0 * position * boneWeights.x
+                            + m1 * position * boneWeights.y
+                            + m2 * position * boneWeights.z
+                            + m3 * position * boneWeights.w;
+                    }
+
+                    void main()
+                    {
+                        vec4 position = vec4(vertexPosition, 1.0);
+
+                        int i0 = int(boneIndices.x);
+                        int i1 = int(boneIndices.y);
+                        int i2 = int(boneIndices.z);
+                        int i3 = int(boneIndices.w);
+
+                        vec4 currentLocalPosition = Skin(
+                            boneMatrices.matrices[i0],
+                            boneMatrices.matrices[i1],
+                            boneMatrices.matrices[i2],
+                            boneMatrices.matrices[i3],
+                            position
+                        );
+                        vec4 prevLocalPosition = Skin(
+                            prevBoneMatrices.matrices[i0],
+                            prevBoneMatrices.matrices[i1],
+                            prevBoneMatrices.matrices[i2],
+                            prevBoneMatrices.matrices[i3],
+                            position
+                        );
+
+                        currentPosition = properties.currentWorldViewProjection * currentLocalPosition;
+                        prevPosition = properties.prevWorldViewProjection * prevLocalPosition;
+                        gl_Position = properties.worldViewProjection * currentLocalPosition;
+                    }
+                "#,
+
+            fragment_shader:
+                r#"
+                    in vec4 currentPosition;
+                    in vec4 prevPosition;
+
+                    out vec4 FragColor;
+
+                    void main()
+                    {
+                        vec2 current = currentPosition.xy / currentPosition.w;
+                        vec2 prev = prevPosition.xy / prevPosition.w;
+                        FragColor = vec4((current - prev) * 0.5, 0.0, 1.0);
+                    }
+                "#,
+        )
+    ]
+)
@@ -0,0 +1,87 @@
+(
+    name: "VolumetricFogIntegration",
+    resources: [
+        (
+            name: "scatteringTexture",
+            kind: Texture(kind: Sampler2D, fallback: Black),
+            binding: 0
+        ),
+        (
+            name: "properties",
+            kind: PropertyGroup([
+                (name: "worldViewProjection", kind: Matrix4()),
+                (name: "maxDistance", kind: Float()),
+            ]),
+            binding: 0
+        ),
+    ],
+    passes: [
+        (
+            name: "Primary",
+
+            draw_parameters: DrawParameters(
+                cull_face: None,
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: None,
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+                scissor_box: None
+            ),
+
+            vertex_shader:
+                r#"
+                    layout (location = 0) in vec3 vertexPosition;
+
+                    void main()
+                    {
+                        gl_Position = properties.worldViewProjection * vec4(vertexPosition, 1.0);
+                    }
+                "#,
+
+            fragment_shader:
+                r#"
+                    out vec4 FragColor;
+
+                    void main() {
+                        ivec2 gridSize = ivec2(S_FROXEL_GRID_WIDTH, S_FROXEL_GRID_HEIGHT);
+                        ivec2 texel = ivec2(gl_FragCoord.xy);
+                        ivec2 tile = texel / gridSize;
+                        ivec2 froxel = texel % gridSize;
+                        int slice = tile.y * S_FROXEL_ATLAS_COLUMNS + tile.x;
+
+                        // March from the observer to the far boundary of the current slice.
+                        vec3 scattering = vec3(0.0);
+                        float transmittance = 1.0;
+                        for (int i = 0; i <= slice; ++i) {
+                            ivec2 sliceTile = ivec2(i % S_FROXEL_ATLAS_COLUMNS, i / S_FROXEL_ATLAS_COLUMNS);
+                            vec4 froxelData = texelFetch(scatteringTexture, sliceTile * gridSize + froxel, 0);
+
+                            float t0 = float(i) / float(S_FROXEL_GRID_DEPTH);
+                            float t1 = float(i + 1) / float(S_FROXEL_GRID_DEPTH);
+                            float thickness = properties.maxDistance * (t1 * t1 - t0 * t0);
+                            float extinction = max(froxelData.a, 0.00001);
+                            float sliceTransmittance = exp(-extinction * thickness);
+
+                            // Energy-conserving integration of the in-scattered light over the slice.
+                            scattering += transmittance * froxelData.rgb * (1.0 - sliceTransmittance) / extinction;
+                            transmittance *= sliceTransmittance;
+                        }
+
+                        FragColor = vec4(scattering, transmittance);
+                    }
+                "#,
+        )
+    ]
+)
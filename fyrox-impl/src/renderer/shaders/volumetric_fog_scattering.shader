@@ -0,0 +1,130 @@
+(
+    name: "VolumetricFogScattering",
+    resources: [
+        (
+            name: "shadowCascade0",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 0
+        ),
+        (
+            name: "shadowCascade1",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 1
+        ),
+        (
+            name: "shadowCascade2",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 2
+        ),
+        (
+            name: "properties",
+            kind: PropertyGroup([
+                (name: "worldViewProjection", kind: Matrix4()),
+                (name: "invViewProj", kind: Matrix4()),
+                (name: "viewMatrix", kind: Matrix4()),
+                (name: "cameraPosition", kind: Vector3()),
+                (name: "maxDistance", kind: Float()),
+                (name: "density", kind: Float()),
+                (name: "albedo", kind: Vector3()),
+                (name: "anisotropy", kind: Float()),
+                (name: "ambientColor", kind: Vector3()),
+                (name: "lightEnabled", kind: Bool()),
+                (name: "lightDirection", kind: Vector3()),
+                (name: "lightColor", kind: Vector3()),
+                (name: "shadowsEnabled", kind: Bool()),
+                (name: "shadowBias", kind: Float()),
+                (name: "lightViewProjMatrices", kind: Matrix4Array(max_len: 3, value: [])),
+                (name: "cascadeDistances", kind: FloatArray(max_len: 3, value: [])),
+            ]),
+            binding: 0
+        ),
+    ],
+    passes: [
+        (
+            name: "Primary",
+
+            draw_parameters: DrawParameters(
+                cull_face: None,
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: None,
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+                scissor_box: None
+            ),
+
+            vertex_shader:
+                r#"
+                    layout (location = 0) in vec3 vertexPosition;
+
+                    void main()
+                    {
+                        gl_Position = properties.worldViewProjection * vec4(vertexPosition, 1.0);
+                    }
+                "#,
+
+            fragment_shader:
+                r#"
+                    out vec4 FragColor;
+
+                    float CsmGetShadow(in sampler2D sampler, in vec3 position, in mat4 lightViewProjMatrix)
+                    {
+                        return S_SpotShadowFactor(properties.shadowsEnabled, false, properties.shadowBias, position, lightViewProjMatrix, 0.0, sampler);
+                    }
+
+                    // Henyey-Greenstein phase function.
+                    float Phase(float cosTheta, float g)
+                    {
+                        float g2 = g * g;
+                        return (1.0 - g2) / (4.0 * PI * pow(max(1.0 + g2 - 2.0 * g * cosTheta, 0.0001), 1.5));
+                    }
+
+                    void main() {
+                        // Find a froxel, that corresponds to the current texel of the atlas.
+                        ivec2 texel = ivec2(gl_FragCoord.xy);
+                        ivec2 tile = texel / ivec2(S_FROXEL_GRID_WIDTH, S_FROXEL_GRID_HEIGHT);
+                        int slice = tile.y * S_FROXEL_ATLAS_COLUMNS + tile.x;
+                        vec2 uv = (vec2(texel % ivec2(S_FROXEL_GRID_WIDTH, S_FROXEL_GRID_HEIGHT)) + 0.5)
+                            / vec2(S_FROXEL_GRID_WIDTH, S_FROXEL_GRID_HEIGHT);
+
+                        vec3 nearPoint = S_UnProject(vec3(uv, 0.0), properties.invViewProj);
+                        vec3 farPoint = S_UnProject(vec3(uv, 1.0), properties.invViewProj);
+                        vec3 direction = normalize(farPoint - nearPoint);
+                        float t = (float(slice) + 0.5) / float(S_FROXEL_GRID_DEPTH);
+                        vec3 position = properties.cameraPosition + direction * properties.maxDistance * t * t;
+
+                        vec3 light = properties.ambientColor;
+                        if (properties.lightEnabled) {
+                            float zViewSpace = abs((properties.viewMatrix * vec4(position, 1.0)).z);
+
+                            float shadow = 1.0;
+                            if (zViewSpace <= properties.cascadeDistances[0]) {
+                                shadow = CsmGetShadow(shadowCascade0, position, properties.lightViewProjMatrices[0]);
+                            } else if (zViewSpace <= properties.cascadeDistances[1]) {
+                                shadow = CsmGetShadow(shadowCascade1, position, properties.lightViewProjMatrices[1]);
+                            } else if (zViewSpace <= properties.cascadeDistances[2]) {
+                                shadow = CsmGetShadow(shadowCascade2, position, properties.lightViewProjMatrices[2]);
+                            }
+
+                            float cosTheta = dot(direction, properties.lightDirection);
+                            light += shadow * Phase(cosTheta, properties.anisotropy) * properties.lightColor;
+                        }
+
+                        // In-scattered light (rgb) and extinction (a).
+                        FragColor = vec4(properties.density * properties.albedo * light, properties.density);
+                    }
+                "#,
+        )
+    ]
+)
@@ -55,6 +55,8 @@ pub struct Cascade {
     pub frame_buffer: GpuFrameBuffer,
     pub view_proj_matrix: Matrix4<f32>,
     pub z_far: f32,
+    /// Size of the orthographic volume of the cascade in light space.
+    pub extents: Vector3<f32>,
 }
 
 impl Cascade {
@@ -82,6 +84,7 @@ impl Cascade {
             )?,
             view_proj_matrix: Default::default(),
             z_far: 0.0,
+            extents: Default::default(),
         })
     }
 
@@ -245,6 +248,7 @@ impl CsmRenderer {
             let light_view_projection = cascade_projection_matrix * light_view_matrix;
             self.cascades[i].view_proj_matrix = light_view_projection;
             self.cascades[i].z_far = z_far;
+            self.cascades[i].extents = aabb.max - aabb.min;
 
             let viewport = Rect::new(0, 0, self.size as i32, self.size as i32);
             let framebuffer = &self.cascades[i].frame_buffer;
@@ -284,6 +288,9 @@ impl CsmRenderer {
                     fallback_resources,
                     ambient_light: Color::WHITE, // TODO
                     scene_depth: None,
+                    fog: None,
+                    light_clusters: None,
+                    lights_2d: None,
                 },
             )?;
         }
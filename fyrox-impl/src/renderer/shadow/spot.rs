@@ -174,6 +174,9 @@ impl SpotShadowMapRenderer {
                 fallback_resources,
                 ambient_light: Color::WHITE, // TODO
                 scene_depth: None,
+                fog: None,
+                light_clusters: None,
+                lights_2d: None,
             },
         )?;
 
@@ -0,0 +1,359 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Software implementations of the built-in shaders, that could be used with the
+//! [`SoftwareGraphicsServer`] to render user interface, texture blits and simple forward-shaded
+//! meshes without a GPU. See [`register_built_in_shaders`] docs for more info.
+
+use crate::{
+    core::algebra::{Matrix4, Vector2, Vector3, Vector4},
+    material::shader::ShaderDefinition,
+    renderer::framework::{
+        software::{
+            server::SoftwareGraphicsServer,
+            shader::{ShaderContext, SoftwareShader, Varyings, VertexInput},
+        },
+        uniform::UniformReader,
+    },
+};
+
+fn varyings<const N: usize>(values: [f32; N]) -> Varyings {
+    values.into_iter().collect()
+}
+
+fn blit() -> SoftwareShader {
+    SoftwareShader::new(
+        |ctx, vertex| {
+            let world_view_projection = ctx.uniforms(0).read::<Matrix4<f32>>().unwrap_or_default();
+            let tex_coord = vertex.attribute(1);
+            (
+                world_view_projection * vertex.position(0),
+                varyings([tex_coord.x, tex_coord.y]),
+            )
+                .into()
+        },
+        |ctx, fragment| {
+            let tex_coord = Vector2::new(fragment.varyings[0], fragment.varyings[1]);
+            Some(ctx.sample(0, tex_coord))
+        },
+    )
+}
+
+fn ui_vertex_position(ctx: &ShaderContext, vertex: &VertexInput) -> Vector4<f32> {
+    let world_view_projection = ctx.uniforms(0).read::<Matrix4<f32>>().unwrap_or_default();
+    let position = vertex.attribute(0);
+    world_view_projection * Vector4::new(position.x, position.y, 0.0, 1.0)
+}
+
+struct UiProperties {
+    solid_color: Vector4<f32>,
+    gradient_colors: Vec<Vector4<f32>>,
+    gradient_stops: Vec<f32>,
+    gradient_origin: Vector2<f32>,
+    gradient_end: Vector2<f32>,
+    resolution: Vector2<f32>,
+    bounds_min: Vector2<f32>,
+    bounds_max: Vector2<f32>,
+    is_font: bool,
+    opacity: f32,
+    brush_type: i32,
+    gradient_point_count: i32,
+}
+
+impl UiProperties {
+    fn read(mut reader: UniformReader) -> Option<Self> {
+        reader.read::<Matrix4<f32>>()?;
+        Some(Self {
+            solid_color: reader.read()?,
+            gradient_colors: reader.read_slice(16)?,
+            gradient_stops: reader.read_slice(16)?,
+            gradient_origin: reader.read()?,
+            gradient_end: reader.read()?,
+            resolution: reader.read()?,
+            bounds_min: reader.read()?,
+            bounds_max: reader.read()?,
+            is_font: reader.read()?,
+            opacity: reader.read()?,
+            brush_type: reader.read()?,
+            gradient_point_count: reader.read()?,
+        })
+    }
+
+    fn brush_color(&self, frag_coord: Vector2<f32>) -> Vector4<f32> {
+        let size = self.bounds_max - self.bounds_min;
+        let local_position = (Vector2::new(frag_coord.x, self.resolution.y - frag_coord.y)
+            - self.bounds_min)
+            .component_div(&size);
+
+        if self.brush_type == 0 {
+            return self.solid_color;
+        }
+
+        let t = if self.brush_type == 1 {
+            let ab = self.gradient_end - self.gradient_origin;
+            ((local_position - self.gradient_origin).dot(&ab) / ab.dot(&ab)).clamp(0.0, 1.0)
+        } else if self.brush_type == 2 {
+            (local_position - self.gradient_origin)
+                .norm()
+                .clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        let count = self.gradient_point_count.clamp(0, 15) as usize;
+        let current = (0..count)
+            .rev()
+            .find(|i| t > self.gradient_stops[*i])
+            .unwrap_or_default();
+        let next = (current + 1).min(count);
+        let delta = self.gradient_stops[next] - self.gradient_stops[current];
+        let mix_factor = (t - self.gradient_stops[current]) / delta;
+        self.gradient_colors[current].lerp(&self.gradient_colors[next], mix_factor)
+    }
+}
+
+fn ui_primary() -> SoftwareShader {
+    SoftwareShader::new(
+        |ctx, vertex| {
+            let tex_coord = vertex.attribute(1);
+            let color = vertex.attribute(2);
+            (
+                ui_vertex_position(ctx, vertex),
+                varyings([tex_coord.x, tex_coord.y, color.x, color.y, color.z, color.w]),
+            )
+                .into()
+        },
+        |ctx, fragment| {
+            let properties = UiProperties::read(ctx.uniforms(0))?;
+            let varyings = fragment.varyings;
+            let tex_coord = Vector2::new(varyings[0], varyings[1]);
+            let color = Vector4::new(varyings[2], varyings[3], varyings[4], varyings[5]);
+
+            let mut frag_color = properties.brush_color(fragment.position.xy());
+            let diffuse_color = ctx.sample(0, tex_coord);
+            if properties.is_font {
+                frag_color.w *= diffuse_color.x;
+            } else {
+                frag_color.component_mul_assign(&diffuse_color);
+            }
+            frag_color.w *= properties.opacity;
+            Some(frag_color.component_mul(&color))
+        },
+    )
+}
+
+fn ui_clip() -> SoftwareShader {
+    SoftwareShader::new(
+        |ctx, vertex| (ui_vertex_position(ctx, vertex), Varyings::new()).into(),
+        |_, _| Some(Vector4::repeat(1.0)),
+    )
+}
+
+fn standard_forward() -> SoftwareShader {
+    SoftwareShader::new(
+        |ctx, vertex| {
+            let mut instance_data = ctx.uniforms(1);
+            let _world_matrix = instance_data.read::<Matrix4<f32>>();
+            let world_view_projection = instance_data.read::<Matrix4<f32>>().unwrap_or_default();
+            // Instanced draw calls store world matrices of the instances separately, the stub
+            // buffer of non-instanced draw calls contains identity matrices.
+            let instance_matrix = ctx
+                .uniforms(7)
+                .read_slice::<Matrix4<f32>>(ShaderDefinition::MAX_INSTANCES_PER_DRAW_CALL)
+                .and_then(|matrices| matrices.get(vertex.instance_index()).copied())
+                .unwrap_or_else(Matrix4::identity);
+            let tex_coord = vertex.attribute(1);
+            (
+                world_view_projection * instance_matrix * vertex.position(0),
+                varyings([tex_coord.x, tex_coord.y]),
+            )
+                .into()
+        },
+        |ctx, fragment| {
+            let mut properties = ctx.uniforms(0);
+            properties.read::<Vector2<f32>>()?; // texCoordScale
+            properties.read::<u32>()?; // layerIndex
+            properties.read::<Vector3<f32>>()?; // emissionStrength
+            let diffuse_color = properties.read::<Vector4<f32>>()?;
+            let tex_coord = Vector2::new(fragment.varyings[0], fragment.varyings[1]);
+            Some(diffuse_color.component_mul(&ctx.sample(0, tex_coord)))
+        },
+    )
+}
+
+/// Registers software implementations of a subset of the built-in shaders in the given server.
+/// Currently, the following programs are supported:
+///
+/// - `Blit_Primary` - texture blitting.
+/// - `UserInterface_Primary` and `UserInterface_Clip` - user interface rendering, including
+/// gradient brushes, fonts and clipping geometry.
+/// - `StandardShader_Forward` - unlit forward shading of meshes with the standard shader (diffuse
+/// color and diffuse texture). Blend shapes and skinning are not supported.
+///
+/// All the other programs are ignored by the server, which means that scenes could only be rendered
+/// partially (lighting, post-effects, etc. will be missing).
+pub fn register_built_in_shaders(server: &SoftwareGraphicsServer) {
+    server.register_shader("Blit_Primary", blit());
+    server.register_shader("UserInterface_Primary", ui_primary());
+    server.register_shader("UserInterface_Clip", ui_clip());
+    server.register_shader("StandardShader_Forward", standard_forward());
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        asset::{io::FsResourceIo, manager::ResourceManager, untyped::ResourceKind},
+        core::{
+            algebra::{Matrix4, Vector2, Vector3},
+            color::Color,
+            math::Rect,
+            pool::Handle,
+        },
+        gui::{
+            brush::Brush,
+            draw::{CommandTexture, Draw, DrawingContext},
+            style::{resource::StyleResource, Style},
+        },
+        material::{Material, MaterialResource},
+        renderer::{
+            framework::{
+                framebuffer::ReadTarget, gpu_texture::PixelKind,
+                software::server::SoftwareGraphicsServer,
+            },
+            software::register_built_in_shaders,
+            Renderer,
+        },
+        resource::texture::{TextureResource, TextureResourceExtension},
+        scene::{
+            base::BaseBuilder,
+            camera::CameraBuilder,
+            mesh::{
+                surface::{SurfaceBuilder, SurfaceData, SurfaceResource},
+                MeshBuilder, RenderPath,
+            },
+            transform::TransformBuilder,
+            Scene,
+        },
+    };
+    use std::sync::Arc;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_render_ui_with_software_server() {
+        let server = SoftwareGraphicsServer::new((8, 8));
+        register_built_in_shaders(&server);
+        let resource_manager =
+            ResourceManager::new(Arc::new(FsResourceIo), Arc::new(Default::default()));
+        let mut renderer = Renderer::new(server.clone(), (8, 8), &resource_manager).unwrap();
+
+        let mut drawing_context =
+            DrawingContext::new(StyleResource::new_embedded(Style::dark_style()));
+        // Top-left quarter of the screen.
+        drawing_context.push_rect_filled(&Rect::new(0.0, 0.0, 4.0, 4.0), None);
+        drawing_context.commit(
+            Rect::new(0.0, 0.0, 8.0, 8.0),
+            Brush::Solid(Color::RED),
+            CommandTexture::None,
+            None,
+        );
+
+        let render_target = TextureResource::new_render_target(8, 8);
+        renderer
+            .render_ui_to_texture(
+                render_target.clone(),
+                Vector2::new(8.0, 8.0),
+                &drawing_context,
+                Color::BLUE,
+                PixelKind::RGBA8,
+            )
+            .unwrap();
+
+        let pixels = renderer.ui_frame_buffers[&render_target.key()]
+            .read_pixels(ReadTarget::Color(0))
+            .unwrap();
+        // Rows are stored from bottom to top.
+        for (i, pixel) in pixels.chunks(4).enumerate() {
+            let (x, y) = (i % 8, i / 8);
+            let expected = if x < 4 && y >= 4 {
+                [255, 0, 0, 255]
+            } else {
+                [0, 0, 255, 255]
+            };
+            assert_eq!(pixel, expected, "pixel at ({x}, {y})");
+        }
+    }
+
+    #[test]
+    fn test_render_instanced_forward_meshes_with_software_server() {
+        let server = SoftwareGraphicsServer::new((16, 16));
+        register_built_in_shaders(&server);
+        let resource_manager =
+            ResourceManager::new(Arc::new(FsResourceIo), Arc::new(Default::default()));
+        let mut renderer = Renderer::new(server.clone(), (16, 16), &resource_manager).unwrap();
+
+        let mut scene = Scene::new();
+        CameraBuilder::new(BaseBuilder::new()).build(&mut scene.graph);
+        // Both meshes share the same surface and material, so they're drawn in a single
+        // instanced draw call.
+        let data = SurfaceResource::new_embedded(SurfaceData::make_cube(Matrix4::identity()));
+        let material =
+            MaterialResource::new_ok(Uuid::new_v4(), ResourceKind::Embedded, Material::standard());
+        for x in [-1.5, 1.5] {
+            MeshBuilder::new(
+                BaseBuilder::new().with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(x, 0.0, 5.0))
+                        .build(),
+                ),
+            )
+            .with_surfaces(vec![SurfaceBuilder::new(data.clone())
+                .with_material(material.clone())
+                .build()])
+            .with_render_path(RenderPath::Forward)
+            .build(&mut scene.graph);
+        }
+        scene
+            .graph
+            .update(Vector2::new(16.0, 16.0), 0.0, Default::default());
+
+        renderer
+            .render_scene(Handle::new(0, 1), &scene, 0.0, 0.0)
+            .unwrap();
+
+        let pixels = renderer.scene_data_map[&Handle::new(0, 1)]
+            .hdr_scene_framebuffer
+            .read_pixels(ReadTarget::Color(0))
+            .unwrap();
+        // RGBA16F pixels, the corner of the frame is not covered by any mesh.
+        let pixels = pixels.chunks(8).collect::<Vec<_>>();
+        let background = pixels[0];
+        let covered = |columns: std::ops::Range<usize>| {
+            pixels
+                .iter()
+                .enumerate()
+                .filter(|(i, pixel)| columns.contains(&(i % 16)) && **pixel != background)
+                .count()
+        };
+        // Each mesh must be drawn at its own position, one on each side of the frame.
+        assert!(covered(0..8) > 0);
+        assert!(covered(8..16) > 0);
+        assert_eq!(covered(0..8), covered(8..16));
+    }
+}
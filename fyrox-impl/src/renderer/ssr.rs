@@ -0,0 +1,255 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Screen-space reflections. Reflection rays are marched in view space against the depth of the
+//! G-Buffer, the lit frame is sampled at the hit points. Rays that missed the scene geometry (or
+//! left the screen) fall back to the environment reflections (the environment map of the camera
+//! or reflection probes), which are added by the ambient lighting pass. The result is blurred for
+//! rough surfaces and then added to the frame.
+
+use crate::{
+    core::{
+        algebra::Matrix4,
+        color::Color,
+        math::{Matrix4Ext, Rect},
+        sstorage::ImmutableString,
+    },
+    renderer::{
+        bloom::blur::GaussianBlur,
+        cache::{
+            shader::{binding, property, PropertyGroup, RenderMaterial, RenderPassContainer},
+            uniform::UniformBufferCache,
+        },
+        framework::{
+            error::FrameworkError,
+            framebuffer::{Attachment, AttachmentKind, GpuFrameBuffer},
+            geometry_buffer::GpuGeometryBuffer,
+            gpu_texture::{GpuTexture, PixelKind},
+            server::GraphicsServer,
+        },
+        gbuffer::GBuffer,
+        make_viewport_matrix, FallbackResources, RenderPassStatistics, SsrSettings,
+    },
+    scene::camera::Camera,
+};
+
+pub struct ScreenSpaceReflectionsRenderer {
+    shader: RenderPassContainer,
+    composite_shader: RenderPassContainer,
+    framebuffer: GpuFrameBuffer,
+    blur: GaussianBlur,
+    width: usize,
+    height: usize,
+}
+
+pub(crate) struct SsrRenderContext<'a> {
+    pub quad: &'a GpuGeometryBuffer,
+    pub viewport: Rect<i32>,
+    pub camera: &'a Camera,
+    /// Projection matrix that was used to fill the G-Buffer (it is jittered when TAA is enabled).
+    pub projection_matrix: Matrix4<f32>,
+    pub gbuffer: &'a GBuffer,
+    /// Frame buffer with the lit scene. Its color attachment is used as the source of the
+    /// reflected colors and the reflections are added to it.
+    pub frame_buffer: &'a GpuFrameBuffer,
+    pub settings: &'a SsrSettings,
+    pub uniform_buffer_cache: &'a mut UniformBufferCache,
+    pub fallback_resources: &'a FallbackResources,
+}
+
+impl ScreenSpaceReflectionsRenderer {
+    pub fn new(
+        server: &dyn GraphicsServer,
+        width: usize,
+        height: usize,
+    ) -> Result<Self, FrameworkError> {
+        Ok(Self {
+            shader: RenderPassContainer::from_str(server, include_str!("shaders/ssr.shader"))?,
+            composite_shader: RenderPassContainer::from_str(
+                server,
+                include_str!("shaders/ssr_composite.shader"),
+            )?,
+            framebuffer: server.create_frame_buffer(
+                None,
+                vec![Attachment {
+                    kind: AttachmentKind::Color,
+                    texture: server.create_2d_render_target(PixelKind::RGBA16F, width, height)?,
+                }],
+            )?,
+            blur: GaussianBlur::new(server, width, height, PixelKind::RGBA16F)?,
+            width,
+            height,
+        })
+    }
+
+    /// Returns the reflections (without blur) premultiplied by the confidence of the screen-space
+    /// hit, which is stored in the alpha channel.
+    pub fn reflection_map(&self) -> &GpuTexture {
+        &self.framebuffer.color_attachments()[0].texture
+    }
+
+    pub(crate) fn render(
+        &mut self,
+        ctx: SsrRenderContext,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        let SsrRenderContext {
+            quad,
+            viewport,
+            camera,
+            projection_matrix,
+            gbuffer,
+            frame_buffer,
+            settings,
+            uniform_buffer_cache,
+            fallback_resources,
+        } = ctx;
+
+        let mut stats = RenderPassStatistics::default();
+
+        let ssr_viewport = Rect::new(0, 0, self.width as i32, self.height as i32);
+        self.framebuffer
+            .clear(ssr_viewport, Some(Color::from_rgba(0, 0, 0, 0)), None, None);
+
+        let frame_texture = &frame_buffer.color_attachments()[0].texture;
+        let inv_projection = projection_matrix.try_inverse().unwrap_or_default();
+        let view_matrix = camera.view_matrix().basis();
+        let max_steps = settings.max_steps as i32;
+
+        let frame_matrix = make_viewport_matrix(ssr_viewport);
+        let properties = PropertyGroup::from([
+            property("worldViewProjection", &frame_matrix),
+            property("projectionMatrix", &projection_matrix),
+            property("inverseProjectionMatrix", &inv_projection),
+            property("viewMatrix", &view_matrix),
+            property("maxSteps", &max_steps),
+            property("maxDistance", &settings.max_distance),
+            property("thickness", &settings.thickness),
+            property("maxRoughness", &settings.max_roughness),
+        ]);
+        let material = RenderMaterial::from([
+            binding(
+                "depthTexture",
+                (gbuffer.depth(), &fallback_resources.nearest_clamp_sampler),
+            ),
+            binding(
+                "normalTexture",
+                (
+                    gbuffer.normal_texture(),
+                    &fallback_resources.nearest_clamp_sampler,
+                ),
+            ),
+            binding(
+                "materialTexture",
+                (
+                    gbuffer.material_texture(),
+                    &fallback_resources.nearest_clamp_sampler,
+                ),
+            ),
+            binding(
+                "frameTexture",
+                (frame_texture, &fallback_resources.linear_clamp_sampler),
+            ),
+            binding("properties", &properties),
+        ]);
+
+        stats += self.shader.run_pass(
+            1,
+            &ImmutableString::new("Primary"),
+            &self.framebuffer,
+            quad,
+            ssr_viewport,
+            &material,
+            uniform_buffer_cache,
+            Default::default(),
+            None,
+        )?;
+
+        if settings.blur {
+            stats += self.blur.render(
+                quad,
+                self.reflection_map(),
+                uniform_buffer_cache,
+                fallback_resources,
+            )?;
+        }
+
+        let frame_matrix = make_viewport_matrix(viewport);
+        let properties = PropertyGroup::from([
+            property("worldViewProjection", &frame_matrix),
+            property("inverseProjectionMatrix", &inv_projection),
+            property("viewMatrix", &view_matrix),
+            property("maxRoughness", &settings.max_roughness),
+            property("useBlur", &settings.blur),
+        ]);
+        let material = RenderMaterial::from([
+            binding(
+                "reflectionTexture",
+                (
+                    self.reflection_map(),
+                    &fallback_resources.linear_clamp_sampler,
+                ),
+            ),
+            binding(
+                "blurredReflectionTexture",
+                (self.blur.result(), &fallback_resources.linear_clamp_sampler),
+            ),
+            binding(
+                "depthTexture",
+                (gbuffer.depth(), &fallback_resources.nearest_clamp_sampler),
+            ),
+            binding(
+                "diffuseTexture",
+                (
+                    gbuffer.diffuse_texture(),
+                    &fallback_resources.nearest_clamp_sampler,
+                ),
+            ),
+            binding(
+                "normalTexture",
+                (
+                    gbuffer.normal_texture(),
+                    &fallback_resources.nearest_clamp_sampler,
+                ),
+            ),
+            binding(
+                "materialTexture",
+                (
+                    gbuffer.material_texture(),
+                    &fallback_resources.nearest_clamp_sampler,
+                ),
+            ),
+            binding("properties", &properties),
+        ]);
+
+        stats += self.composite_shader.run_pass(
+            1,
+            &ImmutableString::new("Primary"),
+            frame_buffer,
+            quad,
+            viewport,
+            &material,
+            uniform_buffer_cache,
+            Default::default(),
+            None,
+        )?;
+
+        Ok(stats)
+    }
+}
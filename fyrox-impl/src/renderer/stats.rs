@@ -39,6 +39,8 @@ pub struct LightingStatistics {
     pub spot_shadow_maps_rendered: usize,
     /// How many directional lights were rendered.
     pub directional_lights_rendered: usize,
+    /// How many point and spot lights were rendered by the clustered lighting pass.
+    pub clustered_lights_rendered: usize,
 }
 
 impl AddAssign for LightingStatistics {
@@ -49,6 +51,7 @@ impl AddAssign for LightingStatistics {
         self.spot_shadow_maps_rendered += rhs.spot_shadow_maps_rendered;
         self.directional_lights_rendered += rhs.directional_lights_rendered;
         self.csm_rendered += rhs.csm_rendered;
+        self.clustered_lights_rendered += rhs.clustered_lights_rendered;
     }
 }
 
@@ -60,12 +63,14 @@ impl Display for LightingStatistics {
             \tPoint Lights: {}\n\
             \tSpot Lights: {}\n\
             \tDirectional Lights: {}\n\
+            \tClustered Lights: {}\n\
             \tPoint Shadow Maps: {}\n\
             \tSpot Shadow Maps: {}\n\
             \tSpot Shadow Maps: {}\n",
             self.point_lights_rendered,
             self.spot_lights_rendered,
             self.directional_lights_rendered,
+            self.clustered_lights_rendered,
             self.point_shadow_maps_rendered,
             self.spot_shadow_maps_rendered,
             self.csm_rendered
@@ -73,6 +78,34 @@ impl Display for LightingStatistics {
     }
 }
 
+/// Occlusion culling statistics.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct OcclusionStatistics {
+    /// How many objects were tested for occlusion.
+    pub objects_tested: usize,
+    /// How many objects were not rendered, because they were occluded by other objects.
+    pub objects_culled: usize,
+}
+
+impl AddAssign for OcclusionStatistics {
+    fn add_assign(&mut self, rhs: Self) {
+        self.objects_tested += rhs.objects_tested;
+        self.objects_culled += rhs.objects_culled;
+    }
+}
+
+impl Display for OcclusionStatistics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Occlusion Statistics:\n\
+            \tObjects Tested: {}\n\
+            \tObjects Culled: {}\n",
+            self.objects_tested, self.objects_culled
+        )
+    }
+}
+
 /// Renderer statistics for a scene.
 #[derive(Debug, Copy, Clone, Default)]
 pub struct SceneStatistics {
@@ -82,6 +115,8 @@ pub struct SceneStatistics {
     pub lighting: LightingStatistics,
     /// Shows how many draw calls was made and how many triangles were rendered.
     pub geometry: RenderPassStatistics,
+    /// Shows how many objects were culled by occlusion culling.
+    pub occlusion: OcclusionStatistics,
 }
 
 impl Display for SceneStatistics {
@@ -90,8 +125,9 @@ impl Display for SceneStatistics {
             f,
             "{}\n\
             {}\n\
+            {}\n\
             {}\n",
-            self.geometry, self.lighting, self.pipeline
+            self.geometry, self.lighting, self.occlusion, self.pipeline
         )
     }
 }
@@ -120,6 +156,12 @@ impl AddAssign<LightingStatistics> for SceneStatistics {
     }
 }
 
+impl AddAssign<OcclusionStatistics> for SceneStatistics {
+    fn add_assign(&mut self, rhs: OcclusionStatistics) {
+        self.occlusion += rhs;
+    }
+}
+
 /// Renderer statistics for one frame, also includes current frames per second
 /// amount.
 #[derive(Debug, Copy, Clone)]
@@ -130,6 +172,8 @@ pub struct Statistics {
     pub lighting: LightingStatistics,
     /// Shows how many draw calls was made and how many triangles were rendered.
     pub geometry: RenderPassStatistics,
+    /// Shows how many objects were culled by occlusion culling.
+    pub occlusion: OcclusionStatistics,
     /// Real time consumed to render frame. Time given in **seconds**.
     pub pure_frame_time: f32,
     /// Total time renderer took to process single frame, usually includes
@@ -156,6 +200,7 @@ impl std::ops::AddAssign<SceneStatistics> for Statistics {
         self.pipeline += rhs.pipeline;
         self.lighting += rhs.lighting;
         self.geometry += rhs.geometry;
+        self.occlusion += rhs.occlusion;
     }
 }
 
@@ -166,6 +211,7 @@ impl Display for Statistics {
         let capped_frame_time = self.capped_frame_time * 1000.0;
         let geometry_stats = &self.geometry;
         let lighting_stats = &self.lighting;
+        let occlusion_stats = &self.occlusion;
         let pipeline_stats = &self.pipeline;
         let texture_cache_size = self.texture_cache_size;
         let geometry_cache_size = self.geometry_cache_size;
@@ -178,6 +224,7 @@ impl Display for Statistics {
             Capped Frame Time: {capped_frame_time:.2} ms\n\
             {geometry_stats}\n\
             {lighting_stats}\n\
+            {occlusion_stats}\n\
             {pipeline_stats}\n\
             Texture Cache Size: {texture_cache_size}\n\
             Geometry Cache Size: {geometry_cache_size}\n\
@@ -0,0 +1,195 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Temporal anti-aliasing. Every frame is rendered with a slightly different sub-pixel offset of
+//! the projection matrix (see [`Camera::jittered_projection_matrix`]), then it is blended with
+//! the accumulated result of the previous frames. The previous result is reprojected using the
+//! velocity buffer and clipped to the color range of the neighbourhood of each pixel to reduce
+//! ghosting.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Vector2},
+        math::Rect,
+        sstorage::ImmutableString,
+    },
+    renderer::{
+        cache::{
+            shader::{binding, property, PropertyGroup, RenderMaterial, RenderPassContainer},
+            uniform::UniformBufferCache,
+        },
+        framework::{
+            error::FrameworkError,
+            framebuffer::{Attachment, AttachmentKind, GpuFrameBuffer},
+            geometry_buffer::GpuGeometryBuffer,
+            gpu_texture::{GpuTexture, PixelKind},
+            server::GraphicsServer,
+        },
+        make_viewport_matrix, FallbackResources, RenderPassStatistics,
+    },
+    scene::camera::Camera,
+};
+
+pub struct TemporalAntiAliasingRenderer {
+    shader: RenderPassContainer,
+    history: [GpuFrameBuffer; 2],
+    current: usize,
+    rendered: bool,
+    history_valid: bool,
+    width: usize,
+    height: usize,
+}
+
+pub(crate) struct TaaRenderContext<'a> {
+    pub quad: &'a GpuGeometryBuffer,
+    pub viewport: Rect<i32>,
+    pub camera: &'a Camera,
+    pub frame_texture: &'a GpuTexture,
+    pub depth_texture: &'a GpuTexture,
+    pub velocity_texture: &'a GpuTexture,
+    pub uniform_buffer_cache: &'a mut UniformBufferCache,
+    pub fallback_resources: &'a FallbackResources,
+}
+
+impl TemporalAntiAliasingRenderer {
+    /// Weight of the accumulated history in the final color.
+    const BLEND_FACTOR: f32 = 0.9;
+
+    pub fn new(
+        server: &dyn GraphicsServer,
+        width: usize,
+        height: usize,
+    ) -> Result<Self, FrameworkError> {
+        let make_history = || {
+            server.create_frame_buffer(
+                None,
+                vec![Attachment {
+                    kind: AttachmentKind::Color,
+                    texture: server.create_2d_render_target(PixelKind::RGBA16F, width, height)?,
+                }],
+            )
+        };
+        Ok(Self {
+            shader: RenderPassContainer::from_str(server, include_str!("shaders/taa.shader"))?,
+            history: [make_history()?, make_history()?],
+            current: 0,
+            rendered: false,
+            history_valid: false,
+            width,
+            height,
+        })
+    }
+
+    /// Returns anti-aliased frame. The content is valid only if temporal anti-aliasing is enabled.
+    pub fn result(&self) -> &GpuTexture {
+        &self.history[self.current].color_attachments()[0].texture
+    }
+
+    /// Discards accumulated history, the next frame will be used as is. Use this method when the
+    /// camera "teleports" (on camera cuts, for example), otherwise the previous image will be
+    /// visible for a few frames.
+    pub fn reset_history(&mut self) {
+        self.history_valid = false;
+        self.rendered = false;
+    }
+
+    /// Swaps the history buffers, must be called once per frame before rendering any camera.
+    pub(crate) fn begin_frame(&mut self) {
+        self.history_valid = self.rendered;
+        self.rendered = false;
+        self.current = 1 - self.current;
+    }
+
+    pub(crate) fn render(
+        &mut self,
+        ctx: TaaRenderContext,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        let mut statistics = RenderPassStatistics::default();
+
+        let TaaRenderContext {
+            quad,
+            viewport,
+            camera,
+            frame_texture,
+            depth_texture,
+            velocity_texture,
+            uniform_buffer_cache,
+            fallback_resources,
+        } = ctx;
+
+        let frame_matrix = make_viewport_matrix(viewport);
+        let inv_view_projection = camera
+            .view_projection_matrix()
+            .try_inverse()
+            .unwrap_or_else(Matrix4::identity);
+        let prev_view_projection = camera.prev_view_projection_matrix();
+        let viewport_origin = Vector2::new(viewport.x() as f32, viewport.y() as f32);
+        let viewport_size = Vector2::new(viewport.w() as f32, viewport.h() as f32);
+        let inverse_frame_size = Vector2::new(1.0 / self.width as f32, 1.0 / self.height as f32);
+        let blend_factor = Self::BLEND_FACTOR;
+        let history_valid = self.history_valid;
+        let properties = PropertyGroup::from([
+            property("worldViewProjection", &frame_matrix),
+            property("invViewProj", &inv_view_projection),
+            property("prevViewProj", &prev_view_projection),
+            property("viewportOrigin", &viewport_origin),
+            property("viewportSize", &viewport_size),
+            property("inverseFrameSize", &inverse_frame_size),
+            property("blendFactor", &blend_factor),
+            property("historyValid", &history_valid),
+        ]);
+        let history = &self.history[1 - self.current].color_attachments()[0].texture;
+        let material = RenderMaterial::from([
+            binding(
+                "currentTexture",
+                (frame_texture, &fallback_resources.nearest_clamp_sampler),
+            ),
+            binding(
+                "historyTexture",
+                (history, &fallback_resources.linear_clamp_sampler),
+            ),
+            binding(
+                "velocityTexture",
+                (velocity_texture, &fallback_resources.nearest_clamp_sampler),
+            ),
+            binding(
+                "depthTexture",
+                (depth_texture, &fallback_resources.nearest_clamp_sampler),
+            ),
+            binding("properties", &properties),
+        ]);
+
+        statistics += self.shader.run_pass(
+            1,
+            &ImmutableString::new("Primary"),
+            &self.history[self.current],
+            quad,
+            viewport,
+            &material,
+            uniform_buffer_cache,
+            Default::default(),
+            None,
+        )?;
+
+        self.rendered = true;
+
+        Ok(statistics)
+    }
+}
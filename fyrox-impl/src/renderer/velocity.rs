@@ -0,0 +1,313 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Velocity buffer contains screen-space motion of every pixel between the previous and the
+//! current frames. It is used by temporal anti-aliasing to find where a pixel was in the previous
+//! frame. Only deferred geometry writes its velocity to the buffer, pixels that weren't touched
+//! by any object are reprojected using depth and the camera matrices.
+//!
+//! Velocity is rendered by a separate position-only pass right after the G-buffer pass, instead of
+//! an extra G-buffer attachment, so material shaders (including custom ones) do not need to know
+//! anything about previous transforms. Rigid instances are drawn using instancing, skinned ones
+//! are drawn one by one, just like in the G-buffer pass.
+
+use crate::{
+    core::{algebra::Matrix4, color::Color, math::Rect, pool::Handle, sstorage::ImmutableString},
+    graph::BaseSceneGraph,
+    renderer::{
+        bundle::RenderDataBundleStorage,
+        cache::{
+            shader::{binding, property, PropertyGroup, RenderMaterial, RenderPassContainer},
+            uniform::UniformBufferCache,
+        },
+        framework::{
+            error::FrameworkError,
+            framebuffer::{Attachment, AttachmentKind, GpuFrameBuffer},
+            geometry_buffer::GpuGeometryBuffer,
+            gpu_texture::{GpuTexture, PixelKind},
+            server::GraphicsServer,
+            CompareFunc, DrawParameters, ElementRange,
+        },
+        GeometryCache, RenderPassStatistics, ShaderCache,
+    },
+    scene::{camera::Camera, graph::Graph, mesh::RenderPath, node::Node},
+};
+use fxhash::FxHashMap;
+
+/// Max amount of instances per instanced draw call, it must match the size of the matrix arrays
+/// in the velocity shader.
+const MAX_INSTANCES_PER_DRAW_CALL: usize = 127;
+
+#[derive(Default)]
+struct InstanceHistory {
+    world_transform: Matrix4<f32>,
+    bone_matrices: Vec<Matrix4<f32>>,
+}
+
+type CameraHistory = FxHashMap<(Handle<Node>, u64), InstanceHistory>;
+
+struct InstanceTransforms {
+    element_range: ElementRange,
+    world_transform: Matrix4<f32>,
+    prev_world_transform: Matrix4<f32>,
+}
+
+pub struct VelocityBufferRenderer {
+    framebuffer: GpuFrameBuffer,
+    shader: RenderPassContainer,
+    skinned_shader: RenderPassContainer,
+    // The same instance could be seen by multiple cameras in the same frame, so every camera
+    // has its own history.
+    history: FxHashMap<Handle<Node>, CameraHistory>,
+}
+
+impl VelocityBufferRenderer {
+    pub fn new(
+        server: &dyn GraphicsServer,
+        depth_stencil: GpuTexture,
+        width: usize,
+        height: usize,
+    ) -> Result<Self, FrameworkError> {
+        Ok(Self {
+            framebuffer: server.create_frame_buffer(
+                Some(Attachment {
+                    kind: AttachmentKind::DepthStencil,
+                    texture: depth_stencil,
+                }),
+                vec![Attachment {
+                    kind: AttachmentKind::Color,
+                    texture: server.create_2d_render_target(PixelKind::RGBA16F, width, height)?,
+                }],
+            )?,
+            shader: RenderPassContainer::from_str(server, include_str!("shaders/velocity.shader"))?,
+            skinned_shader: RenderPassContainer::from_str(
+                server,
+                include_str!("shaders/velocity_skinned.shader"),
+            )?,
+            history: Default::default(),
+        })
+    }
+
+    pub fn velocity_texture(&self) -> &GpuTexture {
+        &self.framebuffer.color_attachments()[0].texture
+    }
+
+    fn draw_instances(
+        &self,
+        instances: &[InstanceTransforms],
+        properties: &PropertyGroup<3>,
+        geometry: &GpuGeometryBuffer,
+        viewport: Rect<i32>,
+        draw_params: &DrawParameters,
+        uniform_buffer_cache: &mut UniformBufferCache,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        let mut statistics = RenderPassStatistics::default();
+
+        for chunk in instances.chunk_by(|a, b| a.element_range == b.element_range) {
+            for chunk in chunk.chunks(MAX_INSTANCES_PER_DRAW_CALL) {
+                let world_matrices = chunk
+                    .iter()
+                    .map(|instance| instance.world_transform)
+                    .collect::<Vec<_>>();
+                let prev_world_matrices = chunk
+                    .iter()
+                    .map(|instance| instance.prev_world_transform)
+                    .collect::<Vec<_>>();
+                let instance_properties = PropertyGroup::from([
+                    property("worldMatrices", world_matrices.as_slice()),
+                    property("prevWorldMatrices", prev_world_matrices.as_slice()),
+                ]);
+                let material = RenderMaterial::from([
+                    binding("properties", properties),
+                    binding("instances", &instance_properties),
+                ]);
+                statistics += self.shader.run_pass(
+                    chunk.len(),
+                    &ImmutableString::new("Primary"),
+                    &self.framebuffer,
+                    geometry,
+                    viewport,
+                    &material,
+                    uniform_buffer_cache,
+                    chunk[0].element_range,
+                    Some(draw_params),
+                )?;
+            }
+        }
+
+        Ok(statistics)
+    }
+
+    pub(crate) fn render(
+        &mut self,
+        server: &dyn GraphicsServer,
+        camera_handle: Handle<Node>,
+        camera: &Camera,
+        graph: &Graph,
+        viewport: Rect<i32>,
+        bundle_storage: &RenderDataBundleStorage,
+        geometry_cache: &mut GeometryCache,
+        shader_cache: &mut ShaderCache,
+        render_pass_name: &ImmutableString,
+        uniform_buffer_cache: &mut UniformBufferCache,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        let mut statistics = RenderPassStatistics::default();
+
+        self.framebuffer
+            .clear(viewport, Some(Color::from_rgba(0, 0, 0, 0)), None, None);
+
+        // Forget about the cameras that no longer exist.
+        self.history.retain(|handle, _| {
+            graph
+                .try_get(*handle)
+                .is_some_and(|node| node.cast::<Camera>().is_some())
+        });
+
+        // The instances that weren't rendered in this frame are forgotten.
+        let prev_history = self.history.remove(&camera_handle).unwrap_or_default();
+        let mut history = CameraHistory::default();
+
+        let view_projection = camera.jittered_view_projection_matrix();
+        let current_view_projection = camera.view_projection_matrix();
+        let prev_view_projection = camera.prev_view_projection_matrix();
+        let primary_pass_name = ImmutableString::new("Primary");
+        let properties = PropertyGroup::from([
+            property("viewProjection", &view_projection),
+            property("currentViewProjection", &current_view_projection),
+            property("prevViewProjection", &prev_view_projection),
+        ]);
+
+        let mut instances = Vec::new();
+        for bundle in bundle_storage.bundles.iter() {
+            if bundle.render_path != RenderPath::Deferred {
+                continue;
+            }
+
+            // Take the culling mode from the material, the rest of the state is fixed.
+            let cull_face = {
+                let material_state = bundle.material.state();
+                let Some(material) = material_state.data_ref() else {
+                    continue;
+                };
+                let Some(render_pass) = shader_cache
+                    .get(server, material.shader())
+                    .and_then(|shader_set| shader_set.render_passes.get(render_pass_name))
+                else {
+                    continue;
+                };
+                render_pass.draw_params.cull_face
+            };
+            let draw_params = DrawParameters {
+                cull_face,
+                depth_write: false,
+                depth_test: Some(CompareFunc::LessOrEqual),
+                ..Default::default()
+            };
+
+            let Ok(geometry) = geometry_cache.get(server, &bundle.data, bundle.time_to_live) else {
+                continue;
+            };
+
+            instances.clear();
+            for instance in bundle.instances.iter() {
+                let key = (instance.node_handle, bundle.data.key());
+
+                // Instances without a node (batched or generated geometry) have no history.
+                let prev = if instance.node_handle.is_some() {
+                    prev_history.get(&key)
+                } else {
+                    None
+                };
+                let prev_world_transform =
+                    prev.map_or(instance.world_transform, |prev| prev.world_transform);
+
+                if instance.bone_matrices.is_empty() {
+                    // Rigid instances are drawn later, using instancing.
+                    instances.push(InstanceTransforms {
+                        element_range: instance.element_range,
+                        world_transform: instance.world_transform,
+                        prev_world_transform,
+                    });
+                } else {
+                    let prev_bone_matrices = prev
+                        .filter(|prev| prev.bone_matrices.len() == instance.bone_matrices.len())
+                        .map_or(instance.bone_matrices.as_slice(), |prev| {
+                            prev.bone_matrices.as_slice()
+                        });
+
+                    let world_view_projection = view_projection * instance.world_transform;
+                    let current_world_view_projection =
+                        current_view_projection * instance.world_transform;
+                    let prev_world_view_projection = prev_view_projection * prev_world_transform;
+                    let skinned_properties = PropertyGroup::from([
+                        property("worldViewProjection", &world_view_projection),
+                        property("currentWorldViewProjection", &current_world_view_projection),
+                        property("prevWorldViewProjection", &prev_world_view_projection),
+                    ]);
+                    let bone_matrices = PropertyGroup::from([property(
+                        "matrices",
+                        instance.bone_matrices.as_slice(),
+                    )]);
+                    let prev_bone_matrices =
+                        PropertyGroup::from([property("matrices", prev_bone_matrices)]);
+                    let material = RenderMaterial::from([
+                        binding("properties", &skinned_properties),
+                        binding("boneMatrices", &bone_matrices),
+                        binding("prevBoneMatrices", &prev_bone_matrices),
+                    ]);
+                    statistics += self.skinned_shader.run_pass(
+                        1,
+                        &primary_pass_name,
+                        &self.framebuffer,
+                        geometry,
+                        viewport,
+                        &material,
+                        uniform_buffer_cache,
+                        instance.element_range,
+                        Some(&draw_params),
+                    )?;
+                }
+
+                if instance.node_handle.is_some() {
+                    history.insert(
+                        key,
+                        InstanceHistory {
+                            world_transform: instance.world_transform,
+                            bone_matrices: instance.bone_matrices.to_vec(),
+                        },
+                    );
+                }
+            }
+
+            statistics += self.draw_instances(
+                &instances,
+                &properties,
+                geometry,
+                viewport,
+                &draw_params,
+                uniform_buffer_cache,
+            )?;
+        }
+
+        self.history.insert(camera_handle, history);
+
+        Ok(statistics)
+    }
+}
@@ -107,6 +107,30 @@
             ]),
             binding: 5
         ),
+        (
+            name: "fyrox_fogEnvironment",
+            kind: Texture(kind: SamplerCube, fallback: Black),
+            binding: 8
+        ),
+        (
+            name: "fyrox_volumetricFog",
+            kind: Texture(kind: Sampler2D, fallback: Black),
+            binding: 9
+        ),
+        (
+            name: "fyrox_fogData",
+            kind: PropertyGroup([
+                // Autogenerated
+            ]),
+            binding: 6
+        ),
+        (
+            name: "fyrox_instanceMatrices",
+            kind: PropertyGroup([
+                // Autogenerated
+            ]),
+            binding: 7
+        ),
     ],
 
     passes: [
@@ -201,6 +225,12 @@
                         localTangent = inputTangent;
                     }
 
+                    // Per-instance transform of instanced draw calls, it is identity otherwise.
+                    mat4 instanceMatrix = fyrox_instanceMatrices.matrices[gl_InstanceID];
+                    localPosition = instanceMatrix * localPosition;
+                    localNormal = mat3(instanceMatrix) * localNormal;
+                    localTangent = mat3(instanceMatrix) * localTangent;
+
                     mat3 nm = mat3(fyrox_instanceData.worldMatrix);
                     normal = normalize(nm * localNormal);
                     tangent = normalize(nm * localTangent);
@@ -345,8 +375,13 @@
                     {
                         localPosition = inputPosition;
                     }
+
+                    // Per-instance transform of instanced draw calls, it is identity otherwise.
+                    mat4 instanceMatrix = fyrox_instanceMatrices.matrices[gl_InstanceID];
+                    localPosition = instanceMatrix * localPosition;
                     gl_Position = fyrox_instanceData.worldViewProjection * localPosition;
                     texCoord = vertexTexCoord;
+                    position = (fyrox_instanceData.worldMatrix * localPosition).xyz;
                 }
                "#,
 
@@ -354,11 +389,13 @@
                r#"
                 out vec4 FragColor;
 
+                in vec3 position;
                 in vec2 texCoord;
 
                 void main()
                 {
                     FragColor = properties.diffuseColor * texture(diffuseTexture, texCoord);
+                    FragColor.rgb = S_ApplySceneFog(FragColor.rgb, position);
                 }
                "#,
         ),
@@ -426,6 +463,10 @@
                         localPosition = inputPosition;
                     }
 
+                    // Per-instance transform of instanced draw calls, it is identity otherwise.
+                    mat4 instanceMatrix = fyrox_instanceMatrices.matrices[gl_InstanceID];
+                    localPosition = instanceMatrix * localPosition;
+
                     gl_Position = fyrox_instanceData.worldViewProjection * localPosition;
                     texCoord = vertexTexCoord;
                 }
@@ -505,6 +546,10 @@
                         localPosition = inputPosition;
                     }
 
+                    // Per-instance transform of instanced draw calls, it is identity otherwise.
+                    mat4 instanceMatrix = fyrox_instanceMatrices.matrices[gl_InstanceID];
+                    localPosition = instanceMatrix * localPosition;
+
                     gl_Position = fyrox_instanceData.worldViewProjection * localPosition;
                     texCoord = vertexTexCoord;
                 }
@@ -585,6 +630,10 @@
                         localPosition = inputPosition;
                     }
 
+                    // Per-instance transform of instanced draw calls, it is identity otherwise.
+                    mat4 instanceMatrix = fyrox_instanceMatrices.matrices[gl_InstanceID];
+                    localPosition = instanceMatrix * localPosition;
+
                     gl_Position = fyrox_instanceData.worldViewProjection * localPosition;
                     worldPosition = (fyrox_instanceData.worldMatrix * localPosition).xyz;
                     texCoord = vertexTexCoord;
@@ -0,0 +1,242 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Procedural sky, that simulates scattering of the sun light in the atmosphere of a planet. It
+//! allows to make time-of-day cycles without any textures. For more info see [`Atmosphere`].
+
+use crate::{
+    core::{
+        algebra::{Vector2, Vector3},
+        color::Color,
+        pool::Handle,
+        reflect::prelude::*,
+        visitor::prelude::*,
+    },
+    graph::SceneGraph,
+    scene::{graph::Graph, light::directional::DirectionalLight, node::Node},
+};
+
+/// A thin layer of clouds at some altitude above the observer. Clouds are generated procedurally
+/// and lit by the sun.
+#[derive(Visit, Clone, PartialEq, Debug, Reflect)]
+pub struct CloudLayer {
+    /// Whether the clouds are enabled or not. Default is `false`.
+    pub enabled: bool,
+
+    /// Fraction of the sky that is covered by the clouds.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.01)]
+    pub coverage: f32,
+
+    /// Maximum opacity of the clouds.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.01)]
+    pub opacity: f32,
+
+    /// Altitude of the layer above the observer, in kilometers.
+    #[reflect(min_value = 0.01, step = 0.1)]
+    pub altitude: f32,
+
+    /// Typical size of a cloud, in kilometers.
+    #[reflect(min_value = 0.01, step = 0.1)]
+    pub scale: f32,
+
+    /// Velocity of the clouds along world X and Z axes, in meters per second.
+    pub wind: Vector2<f32>,
+
+    /// Color of the clouds, it is multiplied with the incoming sun light.
+    pub color: Color,
+}
+
+impl Default for CloudLayer {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            coverage: 0.5,
+            opacity: 0.9,
+            altitude: 2.0,
+            scale: 3.0,
+            wind: Vector2::new(10.0, 0.0),
+            color: Color::WHITE,
+        }
+    }
+}
+
+/// Physically based model of a planet atmosphere. The color of the sky is calculated by simulating
+/// single scattering of the sun light on air molecules (Rayleigh scattering, which makes the sky
+/// blue and sunsets red) and on larger particles such as dust and water droplets (Mie scattering,
+/// which produces the halo around the sun).
+///
+/// When enabled, the sky is rendered into a cube map, that replaces skyboxes of the cameras of the
+/// scene. The same cube map is used as the environment for ambient lighting and reflections of the
+/// cameras, that have no environment map of their own, and as the sky color of the fog (see
+/// [`crate::scene::fog::Fog::use_skybox_color`]). The cube map is re-rendered only when the
+/// parameters of the atmosphere (including the sun direction) change, so slowly moving sun is
+/// relatively cheap.
+///
+/// All distances are in kilometers, scattering coefficients are per kilometer.
+///
+/// # Example
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::pool::Handle,
+/// #     scene::{atmosphere::Atmosphere, node::Node, Scene},
+/// # };
+/// fn enable_sky(scene: &mut Scene, sun: Handle<Node>) {
+///     scene.rendering_options.atmosphere = Atmosphere {
+///         enabled: true,
+///         // The sky will follow the rotation of this directional light.
+///         sun,
+///         ..Default::default()
+///     };
+/// }
+/// ```
+#[derive(Visit, Clone, PartialEq, Debug, Reflect)]
+pub struct Atmosphere {
+    /// Whether the atmosphere is enabled or not. Default is `false`.
+    pub enabled: bool,
+
+    /// A handle of a directional light, that represents the sun. If set, the direction towards
+    /// the sun is taken from the light, so rotating the light changes the time of day. Otherwise,
+    /// [`Self::sun_direction`] is used.
+    pub sun: Handle<Node>,
+
+    /// Direction towards the sun in world space, it is used when [`Self::sun`] is not set.
+    pub sun_direction: Vector3<f32>,
+
+    /// Intensity of the sun light, that enters the atmosphere.
+    #[reflect(min_value = 0.0, step = 0.1)]
+    pub sun_intensity: f32,
+
+    /// Angular radius of the sun disk, in radians. Zero hides the disk.
+    #[reflect(min_value = 0.0, max_value = 0.1, step = 0.001)]
+    pub sun_disk_radius: f32,
+
+    /// Radius of the planet.
+    #[reflect(min_value = 1.0, step = 1.0)]
+    pub planet_radius: f32,
+
+    /// Thickness of the atmosphere above the ground.
+    #[reflect(min_value = 0.1, step = 1.0)]
+    pub atmosphere_height: f32,
+
+    /// Altitude of the observer above the ground.
+    #[reflect(min_value = 0.0, step = 0.1)]
+    pub observer_altitude: f32,
+
+    /// Scattering coefficients of air molecules for red, green and blue light.
+    pub rayleigh_scattering: Vector3<f32>,
+
+    /// Altitude at which the density of air molecules is `1/e` of the density at the ground.
+    #[reflect(min_value = 0.01, step = 0.1)]
+    pub rayleigh_scale_height: f32,
+
+    /// Scattering coefficient of aerosols. The larger the value, the hazier the sky.
+    #[reflect(min_value = 0.0, step = 0.001)]
+    pub mie_scattering: f32,
+
+    /// Altitude at which the density of aerosols is `1/e` of the density at the ground.
+    #[reflect(min_value = 0.01, step = 0.1)]
+    pub mie_scale_height: f32,
+
+    /// Defines how much of the light is scattered forward by aerosols, the larger the value, the
+    /// smaller and brighter the halo around the sun.
+    #[reflect(min_value = 0.0, max_value = 0.99, step = 0.01)]
+    pub mie_anisotropy: f32,
+
+    /// Color of the ground, which is visible below the horizon.
+    pub ground_color: Color,
+
+    /// Cloud layer settings. See [`CloudLayer`] docs for more info.
+    pub clouds: CloudLayer,
+}
+
+impl Default for Atmosphere {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            sun: Handle::NONE,
+            sun_direction: Vector3::new(0.0, 0.5, 0.866),
+            sun_intensity: 20.0,
+            sun_disk_radius: 0.01,
+            planet_radius: 6360.0,
+            atmosphere_height: 60.0,
+            observer_altitude: 0.1,
+            rayleigh_scattering: Vector3::new(0.0058, 0.0135, 0.0331),
+            rayleigh_scale_height: 8.0,
+            mie_scattering: 0.004,
+            mie_scale_height: 1.2,
+            mie_anisotropy: 0.8,
+            ground_color: Color::opaque(60, 60, 60),
+            clouds: Default::default(),
+        }
+    }
+}
+
+impl Atmosphere {
+    /// Returns normalized direction towards the sun. It is taken from the directional light
+    /// specified by [`Self::sun`] (if any), otherwise [`Self::sun_direction`] is used.
+    pub fn calculate_sun_direction(&self, graph: &Graph) -> Vector3<f32> {
+        graph
+            .try_get_of_type::<DirectionalLight>(self.sun)
+            .and_then(|light| light.up_vector().try_normalize(f32::EPSILON))
+            .or_else(|| self.sun_direction.try_normalize(f32::EPSILON))
+            .unwrap_or_else(Vector3::y)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::{UnitQuaternion, Vector3},
+        scene::{
+            atmosphere::Atmosphere,
+            base::BaseBuilder,
+            graph::Graph,
+            light::{directional::DirectionalLightBuilder, BaseLightBuilder},
+            transform::TransformBuilder,
+        },
+    };
+
+    #[test]
+    fn test_sun_direction() {
+        let mut graph = Graph::new();
+        let mut atmosphere = Atmosphere {
+            sun_direction: Vector3::new(0.0, 2.0, 0.0),
+            ..Default::default()
+        };
+        assert_eq!(atmosphere.calculate_sun_direction(&graph), Vector3::y());
+
+        // The light emits along its up axis, which is the direction towards the sun.
+        atmosphere.sun = DirectionalLightBuilder::new(BaseLightBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_rotation(UnitQuaternion::from_axis_angle(
+                        &Vector3::x_axis(),
+                        std::f32::consts::FRAC_PI_2,
+                    ))
+                    .build(),
+            ),
+        ))
+        .build(&mut graph);
+        graph.update_hierarchical_data();
+        let direction = atmosphere.calculate_sun_direction(&graph);
+        assert!((direction - Vector3::z()).norm() < 1e-5);
+    }
+}
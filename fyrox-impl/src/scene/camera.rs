@@ -48,6 +48,7 @@ use crate::{
         debug::SceneDrawingContext,
         graph::Graph,
         node::{Node, NodeTrait, UpdateContext},
+        post_processing::PostProcessingStack,
     },
 };
 
@@ -332,6 +333,67 @@ impl Default for Exposure {
     }
 }
 
+/// Depth of field settings of a camera. The focus model mimics a physical camera with a thin lens:
+/// objects at the focal distance are sharp and everything else is blurred. The blur is stronger
+/// for wider apertures (lower f-numbers) and longer focal lengths. The focal length affects only
+/// the blur, it does not change the field of view of the camera. All the properties could be
+/// animated using property animation tracks (for example, `depth_of_field.focal_distance`).
+#[derive(Visit, Copy, Clone, PartialEq, Debug, Reflect)]
+pub struct DepthOfField {
+    /// Whether the depth of field is enabled or not. Default is `false`.
+    pub enabled: bool,
+    /// Distance (in meters) from the camera to the plane in focus. Default is 10.0.
+    #[reflect(min_value = 0.0, step = 0.1)]
+    pub focal_distance: f32,
+    /// Aperture of the lens as f-number (f-stop). Lower values give shallower depth of field.
+    /// Default is 2.8.
+    #[reflect(min_value = 0.1, step = 0.1)]
+    pub aperture: f32,
+    /// Focal length of the lens (in millimeters). Default is 50.0.
+    #[reflect(min_value = 1.0, step = 1.0)]
+    pub focal_length: f32,
+    /// Maximum radius (in pixels) of the blur. It limits the cost of the effect. Default is 16.0.
+    #[reflect(min_value = 0.0, step = 1.0)]
+    pub max_blur_radius: f32,
+}
+
+uuid_provider!(DepthOfField = "c0af5fc4-dc82-45da-8024-57bc37f8b86d");
+
+impl Default for DepthOfField {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            focal_distance: 10.0,
+            aperture: 2.8,
+            focal_length: 50.0,
+            max_blur_radius: 16.0,
+        }
+    }
+}
+
+impl DepthOfField {
+    /// Height (in millimeters) of the sensor of the camera. It matches the height of the
+    /// full-frame sensor.
+    pub const SENSOR_HEIGHT: f32 = 24.0;
+
+    /// Returns the diameter (in millimeters) of the circle of confusion on the sensor for the
+    /// given distance (in meters) from the camera. The value is negative for objects closer than
+    /// the focal distance and positive for the farther ones.
+    pub fn circle_of_confusion(&self, distance: f32) -> f32 {
+        self.coc_scale() * (1.0 - self.focal_distance / distance.max(f32::EPSILON))
+    }
+
+    /// Returns the diameter (in millimeters) of the circle of confusion of the objects at
+    /// infinity. [`Self::circle_of_confusion`] could be written as
+    /// `coc_scale * (1 - focal_distance / distance)`.
+    pub fn coc_scale(&self) -> f32 {
+        let focal_length = self.focal_length.max(1.0);
+        // Focus could not be closer than the focal length.
+        let focal_distance = (self.focal_distance * 1000.0).max(focal_length + 1.0);
+        focal_length * focal_length / (self.aperture.max(0.1) * (focal_distance - focal_length))
+    }
+}
+
 /// Camera allows you to see world from specific point in world. You must have at least one camera in
 /// your scene to see anything.
 ///
@@ -346,6 +408,12 @@ impl Default for Exposure {
 /// Skybox is a cube around the camera with six textures forming seamless "sky". It could be anything,
 /// starting from simple blue sky and ending with outer space.
 ///
+/// ## Post-processing
+///
+/// Each camera has its own post-processing stack, which is an ordered list of full-screen effects
+/// (bloom, FXAA or custom material-based effects) applied to the rendered frame. See
+/// [`PostProcessingStack`] docs for more info.
+///
 /// ## Multiple cameras
 ///
 /// Fyrox supports multiple cameras per scene, it means that you can create split screen games, make
@@ -384,6 +452,14 @@ pub struct Camera {
     #[reflect(setter = "set_color_grading_enabled")]
     color_grading_enabled: InheritableVariable<bool>,
 
+    #[visit(optional)]
+    #[reflect(setter = "set_depth_of_field")]
+    depth_of_field: InheritableVariable<DepthOfField>,
+
+    #[visit(optional)]
+    #[reflect(setter = "set_post_processing")]
+    post_processing: InheritableVariable<PostProcessingStack>,
+
     #[visit(skip)]
     #[reflect(hidden)]
     view_matrix: Matrix4<f32>,
@@ -391,6 +467,22 @@ pub struct Camera {
     #[visit(skip)]
     #[reflect(hidden)]
     projection_matrix: Matrix4<f32>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    jittered_projection_matrix: Matrix4<f32>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    prev_view_projection_matrix: Matrix4<f32>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    jitter: Vector2<f32>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    jitter_index: usize,
 }
 
 impl Deref for Camera {
@@ -441,17 +533,47 @@ pub enum FitParameters {
     },
 }
 
+/// Returns an element of Halton low-discrepancy sequence with the given base.
+fn halton(mut index: usize, base: usize) -> f32 {
+    let mut f = 1.0;
+    let mut r = 0.0;
+    while index > 0 {
+        f /= base as f32;
+        r += f * (index % base) as f32;
+        index /= base;
+    }
+    r
+}
+
 impl Camera {
+    /// Amount of unique sub-pixel offsets of the jittered projection matrix. See
+    /// [`Self::jittered_projection_matrix`] for more info.
+    pub const JITTER_SEQUENCE_LENGTH: usize = 8;
+
     /// Explicitly calculates view and projection matrices. Normally, you should not call
     /// this method, it will be called automatically when new frame starts.
     #[inline]
     pub fn calculate_matrices(&mut self, frame_size: Vector2<f32>) {
+        self.prev_view_projection_matrix = self.view_projection_matrix();
+
         let pos = self.base.global_position();
         let look = self.base.look_vector();
         let up = self.base.up_vector();
 
         self.view_matrix = Matrix4::look_at_rh(&Point3::from(pos), &Point3::from(pos + look), &up);
         self.projection_matrix = self.projection.matrix(frame_size);
+
+        // Sub-pixel offset in [-0.5; 0.5] range (in pixels) from Halton (2, 3) sequence. The offset
+        // is then converted to normalized device coordinates.
+        self.jitter_index = (self.jitter_index + 1) % Self::JITTER_SEQUENCE_LENGTH;
+        let viewport = self.viewport_pixels(frame_size);
+        self.jitter = Vector2::new(
+            2.0 * (halton(self.jitter_index + 1, 2) - 0.5) / viewport.w() as f32,
+            2.0 * (halton(self.jitter_index + 1, 3) - 0.5) / viewport.h() as f32,
+        );
+        self.jittered_projection_matrix =
+            Matrix4::new_translation(&Vector3::new(self.jitter.x, self.jitter.y, 0.0))
+                * self.projection_matrix;
     }
 
     /// Sets new viewport in resolution-independent format. In other words
@@ -511,6 +633,36 @@ impl Camera {
         self.view_matrix
     }
 
+    /// Returns current projection matrix with a sub-pixel offset applied. The offset changes every
+    /// frame and repeats every [`Self::JITTER_SEQUENCE_LENGTH`] frames. This matrix is used by the
+    /// renderer when temporal anti-aliasing is enabled, so every frame samples slightly different
+    /// points of each pixel.
+    #[inline]
+    pub fn jittered_projection_matrix(&self) -> Matrix4<f32> {
+        self.jittered_projection_matrix
+    }
+
+    /// Returns current view-projection matrix with a sub-pixel offset applied. See
+    /// [`Self::jittered_projection_matrix`] for more info.
+    #[inline]
+    pub fn jittered_view_projection_matrix(&self) -> Matrix4<f32> {
+        self.jittered_projection_matrix * self.view_matrix
+    }
+
+    /// Returns current sub-pixel offset of the jittered projection matrix in normalized device
+    /// coordinates.
+    #[inline]
+    pub fn projection_jitter(&self) -> Vector2<f32> {
+        self.jitter
+    }
+
+    /// Returns view-projection matrix (without jitter) of the previous frame. It is used to
+    /// reproject pixels of the current frame to the previous one.
+    #[inline]
+    pub fn prev_view_projection_matrix(&self) -> Matrix4<f32> {
+        self.prev_view_projection_matrix
+    }
+
     /// Returns inverse view matrix.
     #[inline]
     pub fn inv_view_matrix(&self) -> Option<Matrix4<f32>> {
@@ -736,6 +888,36 @@ impl Camera {
     pub fn exposure(&self) -> Exposure {
         *self.exposure
     }
+
+    /// Sets new depth of field settings. See [`DepthOfField`] docs for more info.
+    pub fn set_depth_of_field(&mut self, depth_of_field: DepthOfField) -> DepthOfField {
+        self.depth_of_field
+            .set_value_and_mark_modified(depth_of_field)
+    }
+
+    /// Returns current depth of field settings.
+    pub fn depth_of_field(&self) -> DepthOfField {
+        *self.depth_of_field
+    }
+
+    /// Sets new post-processing stack. See [`PostProcessingStack`] docs for more info.
+    pub fn set_post_processing(
+        &mut self,
+        post_processing: PostProcessingStack,
+    ) -> PostProcessingStack {
+        self.post_processing
+            .set_value_and_mark_modified(post_processing)
+    }
+
+    /// Returns a reference to current post-processing stack.
+    pub fn post_processing(&self) -> &PostProcessingStack {
+        &self.post_processing
+    }
+
+    /// Returns a mutable reference to current post-processing stack.
+    pub fn post_processing_mut(&mut self) -> &mut PostProcessingStack {
+        self.post_processing.get_value_mut_and_mark_modified()
+    }
 }
 
 impl ConstructorProvider<Node, Graph> for Camera {
@@ -1084,6 +1266,8 @@ pub struct CameraBuilder {
     exposure: Exposure,
     color_grading_lut: Option<ColorGradingLut>,
     color_grading_enabled: bool,
+    depth_of_field: DepthOfField,
+    post_processing: PostProcessingStack,
     projection: Projection,
 }
 
@@ -1102,6 +1286,8 @@ impl CameraBuilder {
             exposure: Exposure::Manual(std::f32::consts::E),
             color_grading_lut: None,
             color_grading_enabled: false,
+            depth_of_field: Default::default(),
+            post_processing: Default::default(),
             projection: Projection::default(),
         }
     }
@@ -1172,6 +1358,18 @@ impl CameraBuilder {
         self
     }
 
+    /// Sets desired depth of field settings.
+    pub fn with_depth_of_field(mut self, depth_of_field: DepthOfField) -> Self {
+        self.depth_of_field = depth_of_field;
+        self
+    }
+
+    /// Sets desired post-processing stack.
+    pub fn with_post_processing(mut self, post_processing: PostProcessingStack) -> Self {
+        self.post_processing = post_processing;
+        self
+    }
+
     /// Sets desired projection mode.
     pub fn with_projection(mut self, projection: Projection) -> Self {
         self.projection = projection;
@@ -1189,6 +1387,10 @@ impl CameraBuilder {
             // recalculated before rendering.
             view_matrix: Matrix4::identity(),
             projection_matrix: Matrix4::identity(),
+            jittered_projection_matrix: Matrix4::identity(),
+            prev_view_projection_matrix: Matrix4::identity(),
+            jitter: Default::default(),
+            jitter_index: 0,
             sky_box: InheritableVariable::new_modified(match self.skybox {
                 SkyBoxKind::Builtin => Some(SkyBoxKind::built_in_skybox().clone()),
                 SkyBoxKind::None => None,
@@ -1198,6 +1400,8 @@ impl CameraBuilder {
             exposure: self.exposure.into(),
             color_grading_lut: self.color_grading_lut.into(),
             color_grading_enabled: self.color_grading_enabled.into(),
+            depth_of_field: self.depth_of_field.into(),
+            post_processing: self.post_processing.into(),
         }
     }
 
@@ -1605,3 +1809,49 @@ impl SkyBox {
         self.back.clone()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::ImmutableString,
+        generic_animation::value::{BoundValue, TrackValue, ValueBinding, ValueType},
+        scene::{
+            base::BaseBuilder,
+            camera::{CameraBuilder, DepthOfField},
+        },
+    };
+
+    #[test]
+    fn test_depth_of_field() {
+        let depth_of_field = DepthOfField {
+            enabled: true,
+            focal_distance: 5.0,
+            ..Default::default()
+        };
+        assert_eq!(depth_of_field.circle_of_confusion(5.0), 0.0);
+        assert!(depth_of_field.circle_of_confusion(2.0) < 0.0);
+        assert!(depth_of_field.circle_of_confusion(20.0) > 0.0);
+        assert!(depth_of_field.circle_of_confusion(1000.0) < depth_of_field.coc_scale());
+
+        // Wider aperture gives stronger blur.
+        let wide = DepthOfField {
+            aperture: 1.4,
+            ..depth_of_field
+        };
+        assert!(wide.circle_of_confusion(20.0) > depth_of_field.circle_of_confusion(20.0));
+
+        // Focus must be animatable using property tracks.
+        let mut camera = CameraBuilder::new(BaseBuilder::new())
+            .with_depth_of_field(depth_of_field)
+            .build_camera();
+        let value = BoundValue {
+            binding: ValueBinding::Property {
+                name: ImmutableString::new("depth_of_field.focal_distance"),
+                value_type: ValueType::F32,
+            },
+            value: TrackValue::Real(2.5),
+        };
+        value.apply_to_object(&mut camera, "depth_of_field.focal_distance", ValueType::F32);
+        assert_eq!(camera.depth_of_field().focal_distance, 2.5);
+    }
+}
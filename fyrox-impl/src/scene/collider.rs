@@ -24,7 +24,7 @@
 use crate::scene::node::constructor::NodeConstructor;
 use crate::{
     core::{
-        algebra::Vector3,
+        algebra::{Matrix4, Point3, UnitQuaternion, Vector3},
         log::Log,
         math::aabb::AxisAlignedBoundingBox,
         num_traits::{NumCast, One, ToPrimitive, Zero},
@@ -41,6 +41,10 @@ use crate::{
             physics::{CoefficientCombineRule, ContactPair, IntersectionPair, PhysicsWorld},
             Graph,
         },
+        mesh::{
+            buffer::{VertexAttributeUsage, VertexReadTrait},
+            Mesh,
+        },
         node::{Node, NodeTrait, SyncContext},
         rigidbody::RigidBody,
         Scene,
@@ -51,7 +55,10 @@ use fyrox_core::uuid_provider;
 
 use fyrox_graph::constructor::ConstructorProvider;
 use fyrox_graph::{BaseSceneGraph, SceneGraphNode};
-use rapier3d::geometry::{self, ColliderHandle};
+use rapier3d::{
+    geometry::{self, ColliderHandle},
+    parry::transformation::vhacd::{VHACDParameters, VHACD},
+};
 use std::fmt::Write;
 use std::{
     cell::Cell,
@@ -223,6 +230,188 @@ pub struct ConvexPolyhedronShape {
     pub geometry_source: GeometrySource,
 }
 
+/// Arbitrary convex shape defined by a set of points. The actual shape is the convex hull of the
+/// points. Usually such shapes are produced by [`CompoundShape::convex_decomposition`].
+#[derive(Default, Clone, Debug, Visit, Reflect, PartialEq)]
+pub struct ConvexHullShape {
+    /// Points of the convex hull in local coordinates of the collider. Points are hidden from the
+    /// editor, because they're not meant to be edited by hand.
+    #[reflect(hidden)]
+    pub points: Vec<Vector3<f32>>,
+}
+
+/// A convex shape, that could be used as a part of a [`CompoundShape`]. Composite shapes (trimesh,
+/// heightfield, compound, etc.) cannot be nested into a compound shape.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect, AsRefStr, EnumString, VariantNames)]
+pub enum CompoundSubShape {
+    /// See [`BallShape`] docs.
+    Ball(BallShape),
+    /// See [`CylinderShape`] docs.
+    Cylinder(CylinderShape),
+    /// See [`ConeShape`] docs.
+    Cone(ConeShape),
+    /// See [`CuboidShape`] docs.
+    Cuboid(CuboidShape),
+    /// See [`CapsuleShape`] docs.
+    Capsule(CapsuleShape),
+    /// See [`SegmentShape`] docs.
+    Segment(SegmentShape),
+    /// See [`TriangleShape`] docs.
+    Triangle(TriangleShape),
+    /// See [`ConvexHullShape`] docs.
+    ConvexHull(ConvexHullShape),
+}
+
+uuid_provider!(CompoundSubShape = "a4f3b2c1-7e6d-4c5b-9a08-1f2e3d4c5b6a");
+
+impl Default for CompoundSubShape {
+    fn default() -> Self {
+        Self::Cuboid(Default::default())
+    }
+}
+
+/// A part of a [`CompoundShape`], that holds a sub-shape and its local transform.
+#[derive(Default, Clone, Debug, Visit, Reflect, PartialEq)]
+pub struct CompoundShapeChild {
+    /// Position of the sub-shape in local coordinates of the collider.
+    pub position: Vector3<f32>,
+    /// Rotation of the sub-shape in local coordinates of the collider.
+    pub rotation: UnitQuaternion<f32>,
+    /// The actual sub-shape.
+    pub shape: CompoundSubShape,
+}
+
+uuid_provider!(CompoundShapeChild = "5d0c1c9b-4f1e-4f55-8f5a-3b7d0f3e2a61");
+
+/// A shape that consists of multiple sub-shapes with their own local transforms. It is the
+/// preferred way of building complex (concave) shapes for dynamic rigid bodies, because triangle
+/// meshes do not have volume and collide poorly with each other.
+#[derive(Default, Clone, Debug, Visit, Reflect, PartialEq)]
+pub struct CompoundShape {
+    /// A set of sub-shapes of the compound shape.
+    pub children: Vec<CompoundShapeChild>,
+}
+
+/// Parameters of approximate convex decomposition (V-HACD). See
+/// [`CompoundShape::convex_decomposition`] for more info.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConvexDecompositionParameters {
+    /// Resolution of voxelization of the source mesh. Higher values give more precise results,
+    /// but decomposition takes more time. Default is 64.
+    pub resolution: u32,
+    /// Maximum allowed concavity of each part in `[0.0; 1.0]` range. Lower values produce more
+    /// parts. Default is 0.01.
+    pub concavity: f32,
+    /// Maximum amount of convex parts, that will be produced. Default is 1024.
+    pub max_convex_hulls: u32,
+    /// Controls the granularity of the search for the best clipping plane. Default is 4.
+    pub plane_downsampling: u32,
+    /// Controls the precision of the convex hulls generation during clipping plane selection.
+    /// Default is 4.
+    pub convex_hull_downsampling: u32,
+}
+
+impl Default for ConvexDecompositionParameters {
+    fn default() -> Self {
+        Self {
+            resolution: 64,
+            concavity: 0.01,
+            max_convex_hulls: 1024,
+            plane_downsampling: 4,
+            convex_hull_downsampling: 4,
+        }
+    }
+}
+
+impl CompoundShape {
+    /// Performs approximate convex decomposition (V-HACD) of the given triangle mesh and creates
+    /// a compound shape that consists of a set of [`ConvexHullShape`]s. The result is baked, which
+    /// means that the decomposition is done only once and the shape could be saved in a scene.
+    /// This allows you to use arbitrary meshes as shapes for dynamic rigid bodies, which is not
+    /// possible with trimesh shapes.
+    pub fn convex_decomposition(
+        vertices: &[Vector3<f32>],
+        triangles: &[[u32; 3]],
+        parameters: &ConvexDecompositionParameters,
+    ) -> Self {
+        if vertices.is_empty() || triangles.is_empty() {
+            return Default::default();
+        }
+
+        let points = vertices
+            .iter()
+            .map(|v| Point3::from(*v))
+            .collect::<Vec<_>>();
+
+        let vhacd_parameters = VHACDParameters {
+            resolution: parameters.resolution,
+            concavity: parameters.concavity,
+            max_convex_hulls: parameters.max_convex_hulls,
+            plane_downsampling: parameters.plane_downsampling,
+            convex_hull_downsampling: parameters.convex_hull_downsampling,
+            ..Default::default()
+        };
+
+        let decomposition = VHACD::decompose(&vhacd_parameters, &points, triangles, true);
+
+        Self {
+            children: decomposition
+                .compute_exact_convex_hulls(&points, triangles)
+                .into_iter()
+                .filter(|(hull_points, _)| !hull_points.is_empty())
+                .map(|(hull_points, _)| CompoundShapeChild {
+                    position: Default::default(),
+                    rotation: Default::default(),
+                    shape: CompoundSubShape::ConvexHull(ConvexHullShape {
+                        points: hull_points.into_iter().map(|p| p.coords).collect(),
+                    }),
+                })
+                .collect(),
+        }
+    }
+
+    /// Performs approximate convex decomposition (V-HACD) of every surface of the given mesh. Each
+    /// vertex of the mesh will be transformed using the given transform, it could be used to bake
+    /// the scale of the mesh or to move vertices to the local space of a collider. Surfaces
+    /// without vertex positions are ignored. See [`Self::convex_decomposition`] for more info.
+    pub fn convex_decomposition_of_mesh(
+        mesh: &Mesh,
+        transform: &Matrix4<f32>,
+        parameters: &ConvexDecompositionParameters,
+    ) -> Self {
+        let mut vertices = Vec::new();
+        let mut triangles = Vec::new();
+
+        for surface in mesh.surfaces() {
+            let data = surface.data();
+            let data = data.data_ref();
+
+            // Surfaces without positions are skipped as a whole, otherwise the indices of the
+            // triangles would point to wrong vertices.
+            let Ok(surface_vertices) = data
+                .vertex_buffer
+                .iter()
+                .map(|vertex| {
+                    vertex
+                        .read_3_f32(VertexAttributeUsage::Position)
+                        .map(|position| transform.transform_point(&Point3::from(position)).coords)
+                })
+                .collect::<Result<Vec<_>, _>>()
+            else {
+                continue;
+            };
+
+            let base = vertices.len() as u32;
+            vertices.extend(surface_vertices);
+            for triangle in data.geometry_buffer.iter() {
+                triangles.push(triangle.0.map(|i| base + i));
+            }
+        }
+
+        Self::convex_decomposition(&vertices, &triangles, parameters)
+    }
+}
+
 /// A set of bits used for pairwise collision filtering.
 #[derive(Clone, Copy, Default, PartialEq, Reflect, Eq)]
 pub struct BitMask(pub u32);
@@ -490,6 +679,8 @@ pub enum ColliderShape {
     Heightfield(HeightfieldShape),
     /// See [`ConvexPolyhedronShape`] docs.
     Polyhedron(ConvexPolyhedronShape),
+    /// See [`CompoundShape`] docs.
+    Compound(CompoundShape),
 }
 
 uuid_provider!(ColliderShape = "2e627337-71ea-4b33-a5f1-be697f705a86");
@@ -576,6 +767,11 @@ impl ColliderShape {
     pub fn heightfield(geometry_source: GeometrySource) -> Self {
         Self::Heightfield(HeightfieldShape { geometry_source })
     }
+
+    /// Initializes a compound shape defined by a set of sub-shapes with their local transforms.
+    pub fn compound(children: Vec<CompoundShapeChild>) -> Self {
+        Self::Compound(CompoundShape { children })
+    }
 }
 
 /// Collider is a geometric entity that can be attached to a rigid body to allow participate it
@@ -1045,6 +1241,9 @@ impl NodeTrait for Collider {
                     );
                 }
             }
+            ColliderShape::Compound(compound) if compound.children.is_empty() => {
+                message += "Compound shape has no sub-shapes!";
+            }
             _ => (),
         }
 
@@ -183,6 +183,51 @@ pub struct TileMapShape {
     pub layer_name: ImmutableString,
 }
 
+/// A convex shape, that could be used as a part of a [`CompoundShape`]. Composite shapes (trimesh,
+/// heightfield, tile map, compound) cannot be nested into a compound shape.
+#[derive(Clone, Debug, Visit, Reflect, AsRefStr, PartialEq, EnumString, VariantNames)]
+pub enum CompoundSubShape {
+    /// See [`BallShape`] docs.
+    Ball(BallShape),
+    /// See [`CuboidShape`] docs.
+    Cuboid(CuboidShape),
+    /// See [`CapsuleShape`] docs.
+    Capsule(CapsuleShape),
+    /// See [`SegmentShape`] docs.
+    Segment(SegmentShape),
+    /// See [`TriangleShape`] docs.
+    Triangle(TriangleShape),
+}
+
+uuid_provider!(CompoundSubShape = "6b2e9d47-1c3a-4f85-b0d6-2e8f4a7c9b13");
+
+impl Default for CompoundSubShape {
+    fn default() -> Self {
+        Self::Cuboid(Default::default())
+    }
+}
+
+/// A part of a [`CompoundShape`], that holds a sub-shape and its local transform.
+#[derive(Default, Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct CompoundShapeChild {
+    /// Position of the sub-shape in local coordinates of the collider.
+    pub position: Vector2<f32>,
+    /// Rotation angle (in radians) of the sub-shape in local coordinates of the collider.
+    pub rotation: f32,
+    /// The actual sub-shape.
+    pub shape: CompoundSubShape,
+}
+
+uuid_provider!(CompoundShapeChild = "c8d1f5e2-9a4b-4e73-8d06-5b2a7f1e3c94");
+
+/// A shape that consists of multiple sub-shapes with their own local transforms. It is the
+/// preferred way of building complex (concave) shapes for dynamic rigid bodies.
+#[derive(Default, Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct CompoundShape {
+    /// A set of sub-shapes of the compound shape.
+    pub children: Vec<CompoundShapeChild>,
+}
+
 /// Possible collider shapes.
 #[derive(Clone, Debug, Visit, Reflect, AsRefStr, PartialEq, EnumString, VariantNames)]
 pub enum ColliderShape {
@@ -202,6 +247,8 @@ pub enum ColliderShape {
     Heightfield(HeightfieldShape),
     /// See [`TileMapShape`] docs.
     TileMap(TileMapShape),
+    /// See [`CompoundShape`] docs.
+    Compound(CompoundShape),
 }
 
 uuid_provider!(ColliderShape = "4615485f-f8db-4405-b4a5-437e74b3f5b8");
@@ -264,6 +311,11 @@ impl ColliderShape {
     pub fn heightfield(geometry_source: GeometrySource) -> Self {
         Self::Heightfield(HeightfieldShape { geometry_source })
     }
+
+    /// Initializes a compound shape defined by a set of sub-shapes with their local transforms.
+    pub fn compound(children: Vec<CompoundShapeChild>) -> Self {
+        Self::Compound(CompoundShape { children })
+    }
 }
 
 /// Collider is a geometric entity that can be attached to a rigid body to allow participate it
@@ -735,6 +787,9 @@ impl NodeTrait for Collider {
                     );
                 }
             }
+            ColliderShape::Compound(compound) if compound.children.is_empty() => {
+                message += "Compound shape has no sub-shapes!";
+            }
             _ => (),
         }
 
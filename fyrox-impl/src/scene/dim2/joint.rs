@@ -23,9 +23,10 @@
 use crate::scene::node::constructor::NodeConstructor;
 use crate::{
     core::{
-        algebra::{Isometry2, Matrix4, UnitComplex, Vector2},
+        algebra::{Isometry2, Matrix4, UnitComplex, Vector2, Vector3},
+        color::Color,
         log::Log,
-        math::{aabb::AxisAlignedBoundingBox, m4x4_approx_eq},
+        math::{aabb::AxisAlignedBoundingBox, m4x4_approx_eq, Matrix4Ext},
         pool::Handle,
         reflect::prelude::*,
         type_traits::prelude::*,
@@ -35,8 +36,10 @@ use crate::{
     },
     scene::{
         base::{Base, BaseBuilder},
+        debug::SceneDrawingContext,
         dim2::rigidbody::RigidBody,
         graph::Graph,
+        joint::{draw_linear_limits, draw_sector, GenericJointAxis, LIMITS_GIZMO_SIZE},
         node::{Node, NodeTrait, SyncContext},
         Scene,
     },
@@ -108,6 +111,87 @@ impl Default for PrismaticJoint {
     }
 }
 
+/// Rope joint limits the max distance between the anchors of two rigid bodies, while allowing any
+/// relative rotations and any relative movement within that distance. The anchors of both bodies
+/// are located at the position of the joint at the moment of binding.
+#[derive(Clone, Debug, Visit, PartialEq, Reflect)]
+pub struct RopeJoint {
+    /// Max distance between the anchors of the bodies.
+    #[reflect(
+        description = "Max distance between the anchors of the bodies.",
+        min_value = 0.0
+    )]
+    pub max_distance: f32,
+}
+
+impl Default for RopeJoint {
+    fn default() -> Self {
+        Self { max_distance: 1.0 }
+    }
+}
+
+/// Spring joint pulls the anchors of two rigid bodies towards each other (or pushes them apart) to
+/// keep the distance between them equal to the rest length, while allowing any relative rotations.
+/// The anchors of both bodies are located at the position of the joint at the moment of binding.
+#[derive(Clone, Debug, Visit, PartialEq, Reflect)]
+pub struct SpringJoint {
+    /// Distance between the anchors of the bodies at which the spring is relaxed.
+    #[reflect(
+        description = "Distance between the anchors of the bodies at which the spring is relaxed.",
+        min_value = 0.0
+    )]
+    pub rest_length: f32,
+
+    /// Stiffness of the spring, the larger the value, the stronger the spring.
+    #[reflect(
+        description = "Stiffness of the spring, the larger the value, the stronger the spring.",
+        min_value = 0.0
+    )]
+    pub stiffness: f32,
+
+    /// Damping of the spring, it defines how fast oscillations of the spring fade out.
+    #[reflect(
+        description = "Damping of the spring, it defines how fast oscillations of the spring fade out.",
+        min_value = 0.0
+    )]
+    pub damping: f32,
+}
+
+impl Default for SpringJoint {
+    fn default() -> Self {
+        Self {
+            rest_length: 1.0,
+            stiffness: 10.0,
+            damping: 1.0,
+        }
+    }
+}
+
+/// Generic joint allows you to configure every degree of freedom (two translational and one
+/// rotational) of relative motion of two rigid bodies individually. Every axis could be locked,
+/// limited or driven by a motor. All the axes are defined in the local coordinate system of the
+/// joint. By default, all the translational axes are locked and the rotational axis is free,
+/// which makes it identical to [`BallJoint`].
+#[derive(Clone, Debug, Visit, PartialEq, Reflect)]
+pub struct GenericJoint {
+    /// Translation along local X axis of the joint.
+    pub x: GenericJointAxis,
+    /// Translation along local Y axis of the joint.
+    pub y: GenericJointAxis,
+    /// Rotation of the bodies relative to each other.
+    pub angular: GenericJointAxis,
+}
+
+impl Default for GenericJoint {
+    fn default() -> Self {
+        Self {
+            x: GenericJointAxis::locked(),
+            y: GenericJointAxis::locked(),
+            angular: Default::default(),
+        }
+    }
+}
+
 /// The exact kind of the joint.
 #[derive(Clone, Debug, PartialEq, Visit, Reflect, AsRefStr, EnumString, VariantNames)]
 pub enum JointParams {
@@ -117,6 +201,12 @@ pub enum JointParams {
     FixedJoint(FixedJoint),
     /// See [`PrismaticJoint`] for more info.
     PrismaticJoint(PrismaticJoint),
+    /// See [`RopeJoint`] for more info.
+    RopeJoint(RopeJoint),
+    /// See [`SpringJoint`] for more info.
+    SpringJoint(SpringJoint),
+    /// See [`GenericJoint`] for more info.
+    GenericJoint(GenericJoint),
 }
 
 uuid_provider!(JointParams = "e1fa2015-3ea3-47bb-8ad3-d408559c9643");
@@ -306,6 +396,24 @@ impl ConstructorProvider<Node, Graph> for Joint {
                     .build_node()
                     .into()
             })
+            .with_variant("Rope Joint 2D", |_| {
+                JointBuilder::new(BaseBuilder::new().with_name("Rope Joint 2D"))
+                    .with_params(JointParams::RopeJoint(Default::default()))
+                    .build_node()
+                    .into()
+            })
+            .with_variant("Spring Joint 2D", |_| {
+                JointBuilder::new(BaseBuilder::new().with_name("Spring Joint 2D"))
+                    .with_params(JointParams::SpringJoint(Default::default()))
+                    .build_node()
+                    .into()
+            })
+            .with_variant("Generic Joint 2D", |_| {
+                JointBuilder::new(BaseBuilder::new().with_name("Generic Joint 2D"))
+                    .with_params(JointParams::GenericJoint(Default::default()))
+                    .build_node()
+                    .into()
+            })
             .with_group("Physics 2D")
     }
 }
@@ -349,6 +457,54 @@ impl NodeTrait for Joint {
         }
     }
 
+    fn debug_draw(&self, ctx: &mut SceneDrawingContext) {
+        let transform = self.global_transform();
+
+        ctx.draw_transform(transform * Matrix4::new_scaling(LIMITS_GIZMO_SIZE));
+
+        match &*self.params {
+            JointParams::BallJoint(v) => {
+                if v.limits_enabled {
+                    draw_sector(ctx, transform, &v.limits_angles, Color::ORANGE);
+                }
+            }
+            JointParams::FixedJoint(_) => {}
+            JointParams::PrismaticJoint(v) => {
+                if v.limits_enabled {
+                    draw_linear_limits(ctx, &transform, 0, &v.limits);
+                }
+            }
+            JointParams::RopeJoint(v) => {
+                ctx.draw_circle(
+                    Vector3::default(),
+                    v.max_distance,
+                    32,
+                    Matrix4::new_translation(&transform.position()),
+                    Color::ORANGE,
+                );
+            }
+            JointParams::SpringJoint(v) => {
+                ctx.draw_circle(
+                    Vector3::default(),
+                    v.rest_length,
+                    32,
+                    Matrix4::new_translation(&transform.position()),
+                    Color::CYAN,
+                );
+            }
+            JointParams::GenericJoint(v) => {
+                for (axis, params) in [(0, &v.x), (1, &v.y)] {
+                    if !params.locked && params.limits_enabled {
+                        draw_linear_limits(ctx, &transform, axis, &params.limits);
+                    }
+                }
+                if !v.angular.locked && v.angular.limits_enabled {
+                    draw_sector(ctx, transform, &v.angular.limits, Color::ORANGE);
+                }
+            }
+        }
+    }
+
     fn validate(&self, scene: &Scene) -> Result<(), String> {
         if scene.graph.typed_ref(self.body1()).is_none() {
             return Err("2D Joint has invalid or unassigned handle to a \
@@ -0,0 +1,510 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! 2D light sources. Unlike 3D lights, they do not use the deferred renderer and affect only 2D
+//! nodes (such as [`super::rectangle::Rectangle`] and [`crate::scene::tilemap::TileMap`]) that
+//! use the standard 2D shaders. 2D lights light everything in their radius in XY plane, the Z
+//! coordinate is ignored.
+//!
+//! # Shadows
+//!
+//! 2D lights could cast shadows from occluders (see [`super::occluder::Occluder`] and
+//! [`crate::scene::tilemap::TileMap::set_occluder_layer`]). The shadows are calculated on CPU
+//! every frame, so keep amount of shadow-casting lights and occluders reasonable.
+//!
+//! # Normal maps
+//!
+//! Standard 2D shaders use `normalTexture` of the material to make the lighting bumpy. The
+//! normals are compared with the direction to the light source, that is elevated by
+//! [`BaseLight2D::height`] above the XY plane.
+
+use crate::{
+    core::{
+        color::Color,
+        math::aabb::AxisAlignedBoundingBox,
+        pool::Handle,
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        uuid::{uuid, Uuid},
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    scene::{
+        base::{Base, BaseBuilder},
+        debug::SceneDrawingContext,
+        graph::Graph,
+        node::{constructor::NodeConstructor, Node, NodeTrait},
+    },
+};
+use fyrox_graph::{constructor::ConstructorProvider, BaseSceneGraph};
+use std::ops::{Deref, DerefMut};
+
+/// Common properties of 2D light sources.
+#[derive(Debug, Reflect, Clone, Visit)]
+pub struct BaseLight2D {
+    base: Base,
+
+    #[reflect(setter = "set_color")]
+    color: InheritableVariable<Color>,
+
+    #[reflect(min_value = 0.0, step = 0.1)]
+    #[reflect(setter = "set_intensity")]
+    intensity: InheritableVariable<f32>,
+
+    #[reflect(min_value = 0.0, step = 0.1)]
+    #[reflect(setter = "set_radius")]
+    radius: InheritableVariable<f32>,
+
+    #[reflect(min_value = 0.0, step = 0.1)]
+    #[reflect(setter = "set_height")]
+    height: InheritableVariable<f32>,
+
+    #[reflect(setter = "set_cast_shadows")]
+    cast_shadows: InheritableVariable<bool>,
+}
+
+impl Deref for BaseLight2D {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for BaseLight2D {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl Default for BaseLight2D {
+    fn default() -> Self {
+        BaseLight2DBuilder::new(BaseBuilder::new()).build()
+    }
+}
+
+impl BaseLight2D {
+    /// Sets color of the light, alpha component of the color is ignored.
+    pub fn set_color(&mut self, color: Color) -> Color {
+        self.color.set_value_and_mark_modified(color)
+    }
+
+    /// Returns current color of the light.
+    pub fn color(&self) -> Color {
+        *self.color
+    }
+
+    /// Sets new intensity of the light. Default is 1.0.
+    pub fn set_intensity(&mut self, intensity: f32) -> f32 {
+        self.intensity.set_value_and_mark_modified(intensity)
+    }
+
+    /// Returns current intensity of the light.
+    pub fn intensity(&self) -> f32 {
+        *self.intensity
+    }
+
+    /// Sets the distance at which the light fades out completely. Default is 5.0.
+    pub fn set_radius(&mut self, radius: f32) -> f32 {
+        self.radius.set_value_and_mark_modified(radius.abs())
+    }
+
+    /// Returns the distance at which the light fades out completely.
+    pub fn radius(&self) -> f32 {
+        *self.radius
+    }
+
+    /// Sets the elevation of the light above XY plane, it is used only for normal mapping. Lower
+    /// values make the bumps more prominent. Default is 1.0.
+    pub fn set_height(&mut self, height: f32) -> f32 {
+        self.height.set_value_and_mark_modified(height.abs())
+    }
+
+    /// Returns the elevation of the light above XY plane.
+    pub fn height(&self) -> f32 {
+        *self.height
+    }
+
+    /// Defines whether the light should cast shadows from 2D occluders or not. Default is
+    /// `false`.
+    pub fn set_cast_shadows(&mut self, cast_shadows: bool) -> bool {
+        self.cast_shadows.set_value_and_mark_modified(cast_shadows)
+    }
+
+    /// Returns `true` if the light casts shadows from 2D occluders.
+    pub fn cast_shadows(&self) -> bool {
+        *self.cast_shadows
+    }
+}
+
+/// Allows you to build [`BaseLight2D`] in declarative manner.
+pub struct BaseLight2DBuilder {
+    base_builder: BaseBuilder,
+    color: Color,
+    intensity: f32,
+    radius: f32,
+    height: f32,
+    cast_shadows: bool,
+}
+
+impl BaseLight2DBuilder {
+    /// Creates new builder instance.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            color: Color::WHITE,
+            intensity: 1.0,
+            radius: 5.0,
+            height: 1.0,
+            cast_shadows: false,
+        }
+    }
+
+    /// Sets desired color of the light.
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Sets desired intensity of the light.
+    pub fn with_intensity(mut self, intensity: f32) -> Self {
+        self.intensity = intensity;
+        self
+    }
+
+    /// Sets desired radius of the light.
+    pub fn with_radius(mut self, radius: f32) -> Self {
+        self.radius = radius;
+        self
+    }
+
+    /// Sets desired elevation of the light above XY plane.
+    pub fn with_height(mut self, height: f32) -> Self {
+        self.height = height;
+        self
+    }
+
+    /// Sets whether the light should cast shadows or not.
+    pub fn with_cast_shadows(mut self, cast_shadows: bool) -> Self {
+        self.cast_shadows = cast_shadows;
+        self
+    }
+
+    /// Creates new instance of base 2D light.
+    pub fn build(self) -> BaseLight2D {
+        BaseLight2D {
+            base: self.base_builder.build_base(),
+            color: self.color.into(),
+            intensity: self.intensity.into(),
+            radius: self.radius.into(),
+            height: self.height.into(),
+            cast_shadows: self.cast_shadows.into(),
+        }
+    }
+}
+
+fn light_bounding_box(base_light: &BaseLight2D) -> AxisAlignedBoundingBox {
+    let radius = base_light.radius();
+    AxisAlignedBoundingBox::from_min_max(
+        [-radius, -radius, 0.0].into(),
+        [radius, radius, 0.0].into(),
+    )
+}
+
+/// 2D point light emits light in all directions in XY plane.
+#[derive(Debug, Default, Reflect, Clone, Visit, ComponentProvider)]
+#[reflect(derived_type = "Node")]
+pub struct PointLight {
+    #[component(include)]
+    base_light: BaseLight2D,
+}
+
+impl Deref for PointLight {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base_light.base
+    }
+}
+
+impl DerefMut for PointLight {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base_light.base
+    }
+}
+
+impl TypeUuidProvider for PointLight {
+    fn type_uuid() -> Uuid {
+        uuid!("4a4bd0c7-40f5-4b38-8d7b-3f8ff5b1b6d0")
+    }
+}
+
+impl PointLight {
+    /// Returns a reference to base light.
+    pub fn base_light_ref(&self) -> &BaseLight2D {
+        &self.base_light
+    }
+
+    /// Returns a reference to base light.
+    pub fn base_light_mut(&mut self) -> &mut BaseLight2D {
+        &mut self.base_light
+    }
+}
+
+impl ConstructorProvider<Node, Graph> for PointLight {
+    fn constructor() -> NodeConstructor {
+        NodeConstructor::new::<Self>()
+            .with_variant("Point Light 2D", |_| {
+                PointLightBuilder::new(BaseLight2DBuilder::new(
+                    BaseBuilder::new().with_name("PointLight2D"),
+                ))
+                .build_node()
+                .into()
+            })
+            .with_group("2D")
+    }
+}
+
+impl NodeTrait for PointLight {
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        light_bounding_box(&self.base_light)
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        // Discard scaling part, light emission distance does not affected by scaling.
+        self.local_bounding_box()
+            .transform(&self.global_transform_without_scaling())
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+
+    fn debug_draw(&self, ctx: &mut SceneDrawingContext) {
+        ctx.draw_circle(
+            Default::default(),
+            self.base_light.radius(),
+            30,
+            self.global_transform_without_scaling(),
+            Color::GREEN,
+        );
+    }
+}
+
+/// Allows you to build 2D point light in declarative manner.
+pub struct PointLightBuilder {
+    base_light_builder: BaseLight2DBuilder,
+}
+
+impl PointLightBuilder {
+    /// Creates new builder instance.
+    pub fn new(base_light_builder: BaseLight2DBuilder) -> Self {
+        Self { base_light_builder }
+    }
+
+    /// Builds new instance of 2D point light.
+    pub fn build_point_light(self) -> PointLight {
+        PointLight {
+            base_light: self.base_light_builder.build(),
+        }
+    }
+
+    /// Builds new instance of 2D point light node.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_point_light())
+    }
+
+    /// Builds new instance of 2D point light and adds it to the graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Node> {
+        graph.add_node(self.build_node())
+    }
+}
+
+/// 2D spot light emits light in a cone in XY plane. The cone is directed along the local X axis
+/// of the node, rotate the node around Z axis to change the direction of the light.
+#[derive(Debug, Reflect, Clone, Visit, ComponentProvider)]
+#[reflect(derived_type = "Node")]
+pub struct SpotLight {
+    #[component(include)]
+    base_light: BaseLight2D,
+
+    #[reflect(min_value = 0.0, max_value = 6.28, step = 0.1)]
+    #[reflect(setter = "set_hotspot_cone_angle")]
+    hotspot_cone_angle: InheritableVariable<f32>,
+
+    #[reflect(min_value = 0.0, max_value = 6.28, step = 0.1)]
+    #[reflect(setter = "set_falloff_angle_delta")]
+    falloff_angle_delta: InheritableVariable<f32>,
+}
+
+impl Deref for SpotLight {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base_light.base
+    }
+}
+
+impl DerefMut for SpotLight {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base_light.base
+    }
+}
+
+impl TypeUuidProvider for SpotLight {
+    fn type_uuid() -> Uuid {
+        uuid!("b1b0b8b5-6c5e-4f64-9a53-8f6f1f1c0f62")
+    }
+}
+
+impl Default for SpotLight {
+    fn default() -> Self {
+        SpotLightBuilder::new(BaseLight2DBuilder::new(BaseBuilder::new())).build_spot_light()
+    }
+}
+
+impl SpotLight {
+    /// Returns a reference to base light.
+    pub fn base_light_ref(&self) -> &BaseLight2D {
+        &self.base_light
+    }
+
+    /// Returns a reference to base light.
+    pub fn base_light_mut(&mut self) -> &mut BaseLight2D {
+        &mut self.base_light
+    }
+
+    /// Sets the angle (in radians) of the cone, in which the light has full intensity.
+    pub fn set_hotspot_cone_angle(&mut self, cone_angle: f32) -> f32 {
+        self.hotspot_cone_angle
+            .set_value_and_mark_modified(cone_angle.abs())
+    }
+
+    /// Returns the angle (in radians) of the cone, in which the light has full intensity.
+    pub fn hotspot_cone_angle(&self) -> f32 {
+        *self.hotspot_cone_angle
+    }
+
+    /// Sets the angle (in radians), at which the light fades out from the hotspot cone.
+    pub fn set_falloff_angle_delta(&mut self, delta: f32) -> f32 {
+        self.falloff_angle_delta.set_value_and_mark_modified(delta)
+    }
+
+    /// Returns the angle (in radians), at which the light fades out from the hotspot cone.
+    pub fn falloff_angle_delta(&self) -> f32 {
+        *self.falloff_angle_delta
+    }
+
+    /// Returns the full angle (in radians) of the cone of the light.
+    pub fn full_cone_angle(&self) -> f32 {
+        *self.hotspot_cone_angle + *self.falloff_angle_delta
+    }
+}
+
+impl ConstructorProvider<Node, Graph> for SpotLight {
+    fn constructor() -> NodeConstructor {
+        NodeConstructor::new::<Self>()
+            .with_variant("Spot Light 2D", |_| {
+                SpotLightBuilder::new(BaseLight2DBuilder::new(
+                    BaseBuilder::new().with_name("SpotLight2D"),
+                ))
+                .build_node()
+                .into()
+            })
+            .with_group("2D")
+    }
+}
+
+impl NodeTrait for SpotLight {
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        light_bounding_box(&self.base_light)
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.local_bounding_box()
+            .transform(&self.global_transform_without_scaling())
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+
+    fn debug_draw(&self, ctx: &mut SceneDrawingContext) {
+        let half_cone_angle = self.full_cone_angle() * 0.5;
+        ctx.draw_circle_segment(
+            Default::default(),
+            self.base_light.radius(),
+            30,
+            -half_cone_angle,
+            half_cone_angle,
+            self.global_transform_without_scaling(),
+            Color::GREEN,
+        );
+    }
+}
+
+/// Allows you to build 2D spot light in declarative manner.
+pub struct SpotLightBuilder {
+    base_light_builder: BaseLight2DBuilder,
+    hotspot_cone_angle: f32,
+    falloff_angle_delta: f32,
+}
+
+impl SpotLightBuilder {
+    /// Creates new builder instance.
+    pub fn new(base_light_builder: BaseLight2DBuilder) -> Self {
+        Self {
+            base_light_builder,
+            hotspot_cone_angle: 90.0f32.to_radians(),
+            falloff_angle_delta: 10.0f32.to_radians(),
+        }
+    }
+
+    /// Sets desired hotspot cone angle (in radians).
+    pub fn with_hotspot_cone_angle(mut self, angle: f32) -> Self {
+        self.hotspot_cone_angle = angle;
+        self
+    }
+
+    /// Sets desired falloff angle delta (in radians).
+    pub fn with_falloff_angle_delta(mut self, delta: f32) -> Self {
+        self.falloff_angle_delta = delta;
+        self
+    }
+
+    /// Builds new instance of 2D spot light.
+    pub fn build_spot_light(self) -> SpotLight {
+        SpotLight {
+            base_light: self.base_light_builder.build(),
+            hotspot_cone_angle: self.hotspot_cone_angle.into(),
+            falloff_angle_delta: self.falloff_angle_delta.into(),
+        }
+    }
+
+    /// Builds new instance of 2D spot light node.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_spot_light())
+    }
+
+    /// Builds new instance of 2D spot light and adds it to the graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Node> {
+        graph.add_node(self.build_node())
+    }
+}
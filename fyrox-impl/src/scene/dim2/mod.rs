@@ -23,6 +23,8 @@
 
 pub mod collider;
 pub mod joint;
+pub mod light;
+pub mod occluder;
 pub mod physics;
 pub mod rectangle;
 pub mod rigidbody;
@@ -0,0 +1,260 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Occluder is a 2D shape, that blocks the light of 2D light sources. See [`Occluder`] docs for
+//! more info.
+
+use crate::{
+    core::{
+        algebra::{Point3, Vector2},
+        color::Color,
+        math::aabb::AxisAlignedBoundingBox,
+        pool::Handle,
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        uuid::{uuid, Uuid},
+        uuid_provider,
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    scene::{
+        base::{Base, BaseBuilder},
+        debug::{Line, SceneDrawingContext},
+        graph::Graph,
+        node::{constructor::NodeConstructor, Node, NodeTrait},
+    },
+};
+use fyrox_graph::{constructor::ConstructorProvider, BaseSceneGraph};
+use std::ops::{Deref, DerefMut};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+/// Shape of an [`Occluder`] in local coordinates of the node. All the shapes are closed.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect, AsRefStr, EnumString, VariantNames)]
+pub enum OccluderShape {
+    /// Rectangle centered at the origin of the node.
+    Rectangle {
+        /// Full size of the rectangle.
+        size: Vector2<f32>,
+    },
+    /// Circle centered at the origin of the node. It is approximated by a polygon.
+    Circle {
+        /// Radius of the circle.
+        #[reflect(min_value = 0.0, step = 0.1)]
+        radius: f32,
+        /// Amount of edges of the polygon, that approximates the circle.
+        #[reflect(min_value = 3.0)]
+        segments: u32,
+    },
+    /// Arbitrary polygon. The last point is connected with the first one.
+    Polygon {
+        /// Points of the polygon.
+        points: Vec<Vector2<f32>>,
+    },
+}
+
+uuid_provider!(OccluderShape = "0a8b8f0e-1b8f-4c0e-9d59-4e1c7d7b5e25");
+
+impl Default for OccluderShape {
+    fn default() -> Self {
+        Self::Rectangle {
+            size: Vector2::new(1.0, 1.0),
+        }
+    }
+}
+
+impl OccluderShape {
+    /// Returns the points of the closed outline of the shape in local coordinates.
+    pub fn outline(&self) -> Vec<Vector2<f32>> {
+        match self {
+            Self::Rectangle { size } => {
+                let half = size.scale(0.5);
+                vec![
+                    Vector2::new(-half.x, -half.y),
+                    Vector2::new(half.x, -half.y),
+                    Vector2::new(half.x, half.y),
+                    Vector2::new(-half.x, half.y),
+                ]
+            }
+            Self::Circle { radius, segments } => {
+                let segments = (*segments).max(3);
+                (0..segments)
+                    .map(|i| {
+                        let angle = i as f32 / segments as f32 * std::f32::consts::TAU;
+                        Vector2::new(angle.cos(), angle.sin()).scale(*radius)
+                    })
+                    .collect()
+            }
+            Self::Polygon { points } => points.clone(),
+        }
+    }
+}
+
+/// Occluder is a 2D shape, that blocks the light of 2D light sources (see
+/// [`super::light::BaseLight2D::set_cast_shadows`]). The shape lies in the XY plane of the node,
+/// so the occluder could be moved, rotated and scaled as any other node. Occluders do not have
+/// any visual representation, usually they're attached to sprites or physical bodies.
+///
+/// Tile maps could generate occluders from the colliders of their tiles, see
+/// [`crate::scene::tilemap::TileMap::set_occluder_layer`].
+#[derive(Debug, Default, Reflect, Clone, Visit, ComponentProvider)]
+#[reflect(derived_type = "Node")]
+pub struct Occluder {
+    base: Base,
+
+    #[reflect(setter = "set_shape")]
+    shape: InheritableVariable<OccluderShape>,
+}
+
+impl Deref for Occluder {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for Occluder {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl TypeUuidProvider for Occluder {
+    fn type_uuid() -> Uuid {
+        uuid!("d3f7a7a8-6f0b-4d0e-8f7e-5b0c1a3e2f4d")
+    }
+}
+
+impl Occluder {
+    /// Sets new shape of the occluder.
+    pub fn set_shape(&mut self, shape: OccluderShape) -> OccluderShape {
+        self.shape.set_value_and_mark_modified(shape)
+    }
+
+    /// Returns a reference to the current shape of the occluder.
+    pub fn shape(&self) -> &OccluderShape {
+        &self.shape
+    }
+
+    /// Appends the world-space edges of the occluder to the given list.
+    pub fn collect_edges(&self, edges: &mut Vec<[Vector2<f32>; 2]>) {
+        let transform = self.global_transform();
+        let points = self
+            .shape
+            .outline()
+            .into_iter()
+            .map(|p| {
+                transform
+                    .transform_point(&Point3::new(p.x, p.y, 0.0))
+                    .xy()
+                    .coords
+            })
+            .collect::<Vec<_>>();
+        if points.len() < 2 {
+            return;
+        }
+        for (i, begin) in points.iter().enumerate() {
+            edges.push([*begin, points[(i + 1) % points.len()]]);
+        }
+    }
+}
+
+impl ConstructorProvider<Node, Graph> for Occluder {
+    fn constructor() -> NodeConstructor {
+        NodeConstructor::new::<Self>()
+            .with_variant("Occluder 2D", |_| {
+                OccluderBuilder::new(BaseBuilder::new().with_name("Occluder2D"))
+                    .build_node()
+                    .into()
+            })
+            .with_group("2D")
+    }
+}
+
+impl NodeTrait for Occluder {
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        let mut bounding_box = AxisAlignedBoundingBox::collapsed();
+        for point in self.shape.outline() {
+            bounding_box.add_point(point.to_homogeneous());
+        }
+        bounding_box
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.local_bounding_box()
+            .transform(&self.global_transform())
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+
+    fn debug_draw(&self, ctx: &mut SceneDrawingContext) {
+        let mut edges = Vec::new();
+        self.collect_edges(&mut edges);
+        for [begin, end] in edges {
+            ctx.add_line(Line {
+                begin: begin.to_homogeneous(),
+                end: end.to_homogeneous(),
+                color: Color::ORANGE,
+            });
+        }
+    }
+}
+
+/// Allows you to build occluders in declarative manner.
+pub struct OccluderBuilder {
+    base_builder: BaseBuilder,
+    shape: OccluderShape,
+}
+
+impl OccluderBuilder {
+    /// Creates new builder instance.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            shape: Default::default(),
+        }
+    }
+
+    /// Sets desired shape of the occluder.
+    pub fn with_shape(mut self, shape: OccluderShape) -> Self {
+        self.shape = shape;
+        self
+    }
+
+    /// Builds new instance of the occluder.
+    pub fn build_occluder(self) -> Occluder {
+        Occluder {
+            base: self.base_builder.build_base(),
+            shape: self.shape.into(),
+        }
+    }
+
+    /// Builds new instance of the occluder node.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_occluder())
+    }
+
+    /// Builds new instance of the occluder and adds it to the graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Node> {
+        graph.add_node(self.build_node())
+    }
+}
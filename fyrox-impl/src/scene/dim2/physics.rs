@@ -37,18 +37,24 @@ use crate::{
         visitor::prelude::*,
         BiDirHashMap, ImmutableString,
     },
-    graph::{BaseSceneGraph, SceneGraphNode},
+    graph::{BaseSceneGraph, SceneGraph, SceneGraphNode},
     scene::{
         self,
         collider::{self},
         debug::SceneDrawingContext,
         dim2::{
-            self, collider::ColliderShape, collider::TileMapShape, joint::JointLocalFrames,
-            joint::JointParams, rigidbody::ApplyAction,
+            self,
+            collider::{ColliderShape, CompoundSubShape, TileMapShape},
+            joint::JointLocalFrames,
+            joint::JointParams,
+            rigidbody::ApplyAction,
         },
         graph::{
             isometric_global_transform,
-            physics::{FeatureId, IntegrationParameters, PhysicsPerformanceStatistics},
+            physics::{
+                joint_map_from_vec, joint_map_to_vec, CharacterAutostep, FeatureId,
+                IntegrationParameters, PhysicsPerformanceStatistics, PhysicsSnapshot,
+            },
             Graph, NodePool,
         },
         node::{Node, NodeTrait},
@@ -59,9 +65,9 @@ pub use rapier2d::geometry::shape::*;
 use rapier2d::{
     dynamics::{
         CCDSolver, GenericJoint, GenericJointBuilder, ImpulseJointHandle, ImpulseJointSet,
-        IslandManager, JointAxesMask, JointAxis, MultibodyJointHandle, MultibodyJointSet,
-        RigidBody, RigidBodyActivation, RigidBodyBuilder, RigidBodyHandle, RigidBodySet,
-        RigidBodyType,
+        IslandManager, JointAxesMask, JointAxis, MotorModel, MultibodyJointHandle,
+        MultibodyJointSet, RigidBody, RigidBodyActivation, RigidBodyBuilder, RigidBodyHandle,
+        RigidBodySet, RigidBodyType,
     },
     geometry::{
         Collider, ColliderBuilder, ColliderHandle, ColliderSet, Cuboid, DefaultBroadPhase,
@@ -328,9 +334,27 @@ fn convert_joint_params(
         JointParams::BallJoint(_) => JointAxesMask::LOCKED_REVOLUTE_AXES,
         JointParams::FixedJoint(_) => JointAxesMask::LOCKED_FIXED_AXES,
         JointParams::PrismaticJoint(_) => JointAxesMask::LOCKED_PRISMATIC_AXES,
+        JointParams::RopeJoint(_) | JointParams::SpringJoint(_) => JointAxesMask::empty(),
+        JointParams::GenericJoint(ref v) => [
+            (JointAxesMask::LIN_X, &v.x),
+            (JointAxesMask::LIN_Y, &v.y),
+            (JointAxesMask::ANG_X, &v.angular),
+        ]
+        .into_iter()
+        .filter(|(_, axis)| axis.locked)
+        .fold(JointAxesMask::empty(), |mask, (axis_mask, _)| {
+            mask | axis_mask
+        }),
+    };
+
+    let coupled_axes = match params {
+        // Distance between the anchors is measured along the LinX axis.
+        JointParams::RopeJoint(_) | JointParams::SpringJoint(_) => JointAxesMask::LIN_AXES,
+        _ => JointAxesMask::empty(),
     };
 
     let mut joint = GenericJointBuilder::new(locked_axis)
+        .coupled_axes(coupled_axes)
         .local_frame1(local_frame1)
         .local_frame2(local_frame2)
         .build();
@@ -350,6 +374,40 @@ fn convert_joint_params(
                 joint.set_limits(JointAxis::LinX, [v.limits.start, v.limits.end]);
             }
         }
+        scene::dim2::joint::JointParams::RopeJoint(v) => {
+            joint.set_limits(JointAxis::LinX, [0.0, v.max_distance]);
+        }
+        scene::dim2::joint::JointParams::SpringJoint(v) => {
+            joint
+                .set_motor_position(JointAxis::LinX, v.rest_length, v.stiffness, v.damping)
+                .set_motor_model(JointAxis::LinX, MotorModel::ForceBased);
+        }
+        scene::dim2::joint::JointParams::GenericJoint(v) => {
+            for (axis, params) in [
+                (JointAxis::LinX, &v.x),
+                (JointAxis::LinY, &v.y),
+                (JointAxis::AngX, &v.angular),
+            ] {
+                if params.locked {
+                    continue;
+                }
+                if params.limits_enabled {
+                    joint.set_limits(axis, [params.limits.start, params.limits.end]);
+                }
+                let motor = &params.motor;
+                if motor.enabled {
+                    joint
+                        .set_motor(
+                            axis,
+                            motor.target_position,
+                            motor.target_velocity,
+                            motor.stiffness,
+                            motor.damping,
+                        )
+                        .set_motor_max_force(axis, motor.max_force);
+                }
+            }
+        }
     }
 
     joint
@@ -431,6 +489,42 @@ fn collider_shape_into_native_shape(
             tile_map,
             layer_name: collider_layer_name,
         }) => tile_map_to_collider_shape(tile_map, owner_inv_transform, nodes, collider_layer_name),
+        ColliderShape::Compound(compound) => {
+            let parts = compound
+                .children
+                .iter()
+                .map(|child| {
+                    let isometry = Isometry2::new(child.position, child.rotation);
+                    (isometry, compound_sub_shape_into_native_shape(&child.shape))
+                })
+                .collect::<Vec<_>>();
+            if parts.is_empty() {
+                None
+            } else {
+                Some(SharedShape::compound(parts))
+            }
+        }
+    }
+}
+
+// Converts a part of compound shape into a shared shape.
+fn compound_sub_shape_into_native_shape(shape: &CompoundSubShape) -> SharedShape {
+    match shape {
+        CompoundSubShape::Ball(ball) => SharedShape::ball(ball.radius),
+        CompoundSubShape::Cuboid(cuboid) => SharedShape(Arc::new(Cuboid::new(cuboid.half_extents))),
+        CompoundSubShape::Capsule(capsule) => SharedShape::capsule(
+            Point2::from(capsule.begin),
+            Point2::from(capsule.end),
+            capsule.radius,
+        ),
+        CompoundSubShape::Segment(segment) => {
+            SharedShape::segment(Point2::from(segment.begin), Point2::from(segment.end))
+        }
+        CompoundSubShape::Triangle(triangle) => SharedShape::triangle(
+            Point2::from(triangle.a),
+            Point2::from(triangle.b),
+            Point2::from(triangle.c),
+        ),
     }
 }
 
@@ -593,6 +687,188 @@ pub struct TOI {
     pub status: collider::TOIStatus,
 }
 
+impl From<rapier2d::parry::query::ShapeCastHit> for TOI {
+    fn from(toi: rapier2d::parry::query::ShapeCastHit) -> Self {
+        Self {
+            toi: toi.time_of_impact,
+            witness1: toi.witness1,
+            witness2: toi.witness2,
+            normal1: toi.normal1,
+            normal2: toi.normal2,
+            status: toi.status.into(),
+        }
+    }
+}
+
+/// Kinematic character controller for 2D physics. It moves a kinematic rigid body with a collider
+/// (a character) through the physics world, handling slopes, stairs and snapping to the ground.
+/// See [`crate::scene::graph::physics::CharacterController`] docs for more info.
+#[derive(Clone, Visit, Reflect, Debug, PartialEq)]
+#[visit(optional)]
+pub struct CharacterController {
+    /// The direction that goes "up", it is used to tell floors, walls and ceilings apart.
+    #[reflect(
+        description = "The direction that goes \"up\", it is used to tell floors, walls \
+    and ceilings apart."
+    )]
+    pub up: Vector2<f32>,
+
+    /// A small gap (in meters) to preserve between the character and its surroundings.
+    #[reflect(
+        min_value = 0.0,
+        description = "A small gap (in meters) to preserve between the character and its \
+        surroundings."
+    )]
+    pub offset: f32,
+
+    /// Whether the character should slide along obstacles instead of stopping when hitting them.
+    #[reflect(
+        description = "Whether the character should slide along obstacles instead of stopping \
+        when hitting them."
+    )]
+    pub slide: bool,
+
+    /// The maximum angle (in radians) between the floor normal and the up vector, that the
+    /// character is able to climb.
+    #[reflect(
+        min_value = 0.0,
+        max_value = 1.5708,
+        description = "The maximum angle (in radians) between the floor normal and the up \
+        vector, that the character is able to climb."
+    )]
+    pub max_slope_climb_angle: f32,
+
+    /// The minimum angle (in radians) between the floor normal and the up vector, after which the
+    /// character starts to slide down automatically.
+    #[reflect(
+        min_value = 0.0,
+        max_value = 1.5708,
+        description = "The minimum angle (in radians) between the floor normal and the up \
+        vector, after which the character starts to slide down automatically."
+    )]
+    pub min_slope_slide_angle: f32,
+
+    /// Stairs climbing settings. `None` disables automatic stepping.
+    #[reflect(description = "Stairs climbing settings. `None` disables automatic stepping.")]
+    pub autostep: Option<CharacterAutostep>,
+
+    /// The maximum distance (in meters) to the ground, at which the character is snapped to it.
+    /// `None` disables snapping.
+    #[reflect(
+        min_value = 0.0,
+        description = "The maximum distance (in meters) to the ground, at which the character is \
+        snapped to it. `None` disables snapping."
+    )]
+    pub snap_to_ground: Option<f32>,
+
+    /// Increase this value if the character gets stuck when sliding against surfaces.
+    #[reflect(
+        min_value = 0.0,
+        description = "Increase this value if the character gets stuck when sliding against surfaces."
+    )]
+    pub normal_nudge_factor: f32,
+}
+
+impl Default for CharacterController {
+    fn default() -> Self {
+        Self {
+            up: Vector2::y(),
+            offset: 0.01,
+            slide: true,
+            max_slope_climb_angle: 45.0f32.to_radians(),
+            min_slope_slide_angle: 45.0f32.to_radians(),
+            autostep: None,
+            snap_to_ground: Some(0.2),
+            normal_nudge_factor: 1.0e-4,
+        }
+    }
+}
+
+/// A collision between a character and its surroundings, that happened while moving it.
+#[derive(Copy, Clone, Debug)]
+pub struct CharacterCollision {
+    /// A handle of the collider, that was hit by the character.
+    pub collider: Handle<Node>,
+    /// The position of the character at the moment of the collision.
+    pub character_position: Isometry2<f32>,
+    /// The part of the movement, that was done before the collision.
+    pub translation_applied: Vector2<f32>,
+    /// The part of the movement, that was left after the collision.
+    pub translation_remaining: Vector2<f32>,
+    /// The contact geometry of the collision. The first shape is the collider, that was hit.
+    pub toi: TOI,
+}
+
+/// The actual movement of a character, computed by a [`CharacterController`].
+#[derive(Clone, Debug, Default)]
+pub struct CharacterMovement {
+    /// The translation, that can be applied to the character without going through obstacles.
+    pub translation: Vector2<f32>,
+    /// `true` if the character touches the ground after the movement.
+    pub grounded: bool,
+    /// `true` if the character slides down a slope, that is steeper than
+    /// [`CharacterController::min_slope_slide_angle`].
+    pub is_sliding_down_slope: bool,
+    /// Every collision that happened during the movement.
+    pub collisions: Vec<CharacterCollision>,
+}
+
+impl CharacterController {
+    fn to_native(&self) -> rapier2d::control::KinematicCharacterController {
+        rapier2d::control::KinematicCharacterController {
+            up: UnitVector2::try_new(self.up, f32::EPSILON).unwrap_or_else(Vector2::y_axis),
+            offset: rapier2d::control::CharacterLength::Absolute(self.offset),
+            slide: self.slide,
+            autostep: self
+                .autostep
+                .map(|autostep| rapier2d::control::CharacterAutostep {
+                    max_height: rapier2d::control::CharacterLength::Absolute(autostep.max_height),
+                    min_width: rapier2d::control::CharacterLength::Absolute(autostep.min_width),
+                    include_dynamic_bodies: autostep.include_dynamic_bodies,
+                }),
+            max_slope_climb_angle: self.max_slope_climb_angle,
+            min_slope_slide_angle: self.min_slope_slide_angle,
+            snap_to_ground: self
+                .snap_to_ground
+                .map(rapier2d::control::CharacterLength::Absolute),
+            normal_nudge_factor: self.normal_nudge_factor,
+        }
+    }
+
+    /// Moves the given 2D rigid body by the desired translation, sliding it along obstacles,
+    /// climbing stairs and slopes according to the settings of the controller. The rigid body
+    /// must be [`crate::scene::rigidbody::RigidBodyType::KinematicPositionBased`] and must have a
+    /// 2D collider as its child. The movement is applied on the next physics step. Returns `None`
+    /// if the rigid body does not exist, has no collider or wasn't yet added to the physics world.
+    pub fn move_body(
+        &self,
+        graph: &mut Graph,
+        rigid_body: Handle<Node>,
+        desired_translation: Vector2<f32>,
+        dt: f32,
+    ) -> Option<CharacterMovement> {
+        let (position, movement) = {
+            let graph = &*graph;
+            let body = graph.try_get_of_type::<dim2::rigidbody::RigidBody>(rigid_body)?;
+            let collider = body.children().iter().copied().find(|child| {
+                graph
+                    .try_get_of_type::<dim2::collider::Collider>(*child)
+                    .is_some()
+            })?;
+            let position = *graph.physics2d.bodies.get(body.native.get())?.translation();
+            let movement =
+                graph
+                    .physics2d
+                    .move_character(graph, self, collider, desired_translation, dt)?;
+            (position, movement)
+        };
+        graph
+            .try_get_mut_of_type::<dim2::rigidbody::RigidBody>(rigid_body)?
+            .set_next_kinematic_translation(position + movement.translation);
+        Some(movement)
+    }
+}
+
 impl PhysicsWorld {
     /// Creates a new instance of the physics world.
     pub(crate) fn new() -> Self {
@@ -877,30 +1153,86 @@ impl PhysicsWorld {
             .map(|(handle, toi)| {
                 (
                     Handle::decode_from_u128(self.colliders.get(handle).unwrap().user_data),
-                    TOI {
-                        toi: toi.time_of_impact,
-                        witness1: toi.witness1,
-                        witness2: toi.witness2,
-                        normal1: toi.normal1,
-                        normal2: toi.normal2,
-                        status: toi.status.into(),
-                    },
+                    toi.into(),
                 )
             })
     }
 
+    /// Computes the movement of a character, represented by the given 2D collider, using the
+    /// given controller. The collider itself is not moved, use [`CharacterController::move_body`]
+    /// to move a kinematic rigid body. Sensors, the rigid body of the collider and colliders, that
+    /// are not in the collision groups of the collider, are ignored. Returns `None` if the
+    /// collider does not exist or wasn't yet added to the physics world.
+    pub fn move_character(
+        &self,
+        graph: &Graph,
+        controller: &CharacterController,
+        collider: Handle<Node>,
+        desired_translation: Vector2<f32>,
+        dt: f32,
+    ) -> Option<CharacterMovement> {
+        let collider_handle = graph
+            .try_get_of_type::<dim2::collider::Collider>(collider)?
+            .native
+            .get();
+        let native_collider = self.colliders.get(collider_handle)?;
+
+        let mut filter = rapier2d::pipeline::QueryFilter::new()
+            .exclude_sensors()
+            .exclude_collider(collider_handle)
+            .groups(native_collider.collision_groups());
+        if let Some(parent) = native_collider.parent() {
+            filter = filter.exclude_rigid_body(parent);
+        }
+
+        let query = self.query.borrow();
+
+        let mut collisions = Vec::new();
+        let movement = controller.to_native().move_shape(
+            dt,
+            &self.bodies,
+            &self.colliders,
+            &query,
+            native_collider.shape(),
+            native_collider.position(),
+            desired_translation,
+            filter,
+            |collision| {
+                if let Some(hit_collider) = self.colliders.get(collision.handle) {
+                    collisions.push(CharacterCollision {
+                        collider: Handle::decode_from_u128(hit_collider.user_data),
+                        character_position: collision.character_pos,
+                        translation_applied: collision.translation_applied,
+                        translation_remaining: collision.translation_remaining,
+                        toi: collision.hit.into(),
+                    })
+                }
+            },
+        );
+
+        Some(CharacterMovement {
+            translation: movement.translation,
+            grounded: movement.grounded,
+            is_sliding_down_slope: movement.is_sliding_down_slope,
+            collisions,
+        })
+    }
+
     pub(crate) fn set_rigid_body_position(
         &mut self,
         rigid_body: &scene::dim2::rigidbody::RigidBody,
         new_global_transform: &Matrix4<f32>,
     ) {
         if let Some(native) = self.bodies.get_mut(rigid_body.native.get()) {
-            native.set_position(
-                isometry_from_global_transform(new_global_transform),
-                // Do not wake up body, it is too expensive and must be done **only** by explicit
-                // `wake_up` call!
-                false,
-            );
+            let new_position = isometry_from_global_transform(new_global_transform);
+            if native.position() != &new_position {
+                native.set_position(
+                    new_position,
+                    // Do not wake up body, it is too expensive and must be done **only** by explicit
+                    // `wake_up` call!
+                    false,
+                );
+            }
         }
     }
 
@@ -937,6 +1269,9 @@ impl PhysicsWorld {
                             .local_transform_mut()
                             .set_position(new_local_position)
                             .set_rotation(new_local_rotation);
+                        rigid_body.synced_global_transform.set(Some(
+                            parent_transform * rigid_body.local_transform().matrix(),
+                        ));
                     }
 
                     rigid_body
@@ -1356,8 +1691,82 @@ impl PhysicsWorld {
             .contact_pairs()
             .filter_map(|c| ContactPair::from_native(c, self))
     }
+
+    /// Captures the current state of the simulation into a snapshot, that could be restored later
+    /// using [`Self::restore`].
+    pub fn snapshot(&self) -> Result<PhysicsSnapshot, VisitError> {
+        PhysicsSnapshot::from_state(&(
+            &self.islands,
+            &self.broad_phase,
+            &self.narrow_phase,
+            &self.bodies,
+            &self.colliders,
+            &self.joints.set,
+            joint_map_to_vec(&self.joints.map),
+            &self.multibody_joints.set,
+            joint_map_to_vec(&self.multibody_joints.map),
+            &self.ccd_solver,
+            &*self.query.borrow(),
+        ))
+    }
+
+    /// Restores the state of the simulation from the given snapshot. Native handles of bodies,
+    /// colliders and joints are restored as well, which means that the snapshot must be restored
+    /// into the same scene it was taken from, and the set of physical entities of the scene must
+    /// be the same as it was at the moment of taking the snapshot. Scene nodes will receive the
+    /// restored state on the next update of the scene.
+    ///
+    /// Transform changes made to scene nodes of rigid bodies before restoring a snapshot are
+    /// applied on top of the restored state on the next update of the graph.
+    pub fn restore(&mut self, snapshot: &PhysicsSnapshot) -> Result<(), VisitError> {
+        let (
+            islands,
+            broad_phase,
+            narrow_phase,
+            bodies,
+            colliders,
+            joints,
+            joint_map,
+            multibody_joints,
+            multibody_joint_map,
+            ccd_solver,
+            query,
+        ): NativeState = snapshot.to_state()?;
+
+        self.islands = islands;
+        self.broad_phase = broad_phase;
+        self.narrow_phase = narrow_phase;
+        self.bodies = bodies;
+        self.colliders = colliders;
+        self.joints = Container {
+            set: joints,
+            map: joint_map_from_vec(joint_map),
+        };
+        self.multibody_joints = Container {
+            set: multibody_joints,
+            map: joint_map_from_vec(multibody_joint_map),
+        };
+        self.ccd_solver = ccd_solver;
+        *self.query.borrow_mut() = query;
+
+        Ok(())
+    }
 }
 
+type NativeState = (
+    IslandManager,
+    DefaultBroadPhase,
+    NarrowPhase,
+    RigidBodySet,
+    ColliderSet,
+    ImpulseJointSet,
+    Vec<(ImpulseJointHandle, u128)>,
+    MultibodyJointSet,
+    Vec<(MultibodyJointHandle, u128)>,
+    CCDSolver,
+    QueryPipeline,
+);
+
 impl Default for PhysicsWorld {
     fn default() -> Self {
         Self::new()
@@ -1369,3 +1778,283 @@ impl Debug for PhysicsWorld {
         write!(f, "PhysicsWorld")
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::{Vector2, Vector3},
+            pool::Handle,
+            visitor::{Visit, Visitor},
+        },
+        scene::{
+            base::BaseBuilder,
+            dim2::{
+                collider::{ColliderBuilder, ColliderShape, CompoundShapeChild, CompoundSubShape},
+                joint::{GenericJoint, JointBuilder, JointParams, RopeJoint, SpringJoint},
+                physics::CharacterController,
+                rigidbody::RigidBodyBuilder,
+            },
+            graph::{physics::PhysicsSnapshot, Graph},
+            joint::GenericJointAxis,
+            node::Node,
+            rigidbody::RigidBodyType,
+            transform::TransformBuilder,
+        },
+    };
+
+    fn create_body(
+        graph: &mut Graph,
+        body_type: RigidBodyType,
+        position: Vector2<f32>,
+        shape: ColliderShape,
+    ) -> (Handle<Node>, Handle<Node>) {
+        let collider = ColliderBuilder::new(BaseBuilder::new())
+            .with_shape(shape)
+            .build(graph);
+        let body = RigidBodyBuilder::new(
+            BaseBuilder::new()
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(position.x, position.y, 0.0))
+                        .build(),
+                )
+                .with_children(&[collider]),
+        )
+        .with_body_type(body_type)
+        .build(graph);
+        (body, collider)
+    }
+
+    #[test]
+    fn test_character_controller_2d() {
+        let mut graph = Graph::new();
+
+        create_body(
+            &mut graph,
+            RigidBodyType::Static,
+            Vector2::new(0.0, -0.5),
+            ColliderShape::cuboid(10.0, 0.5),
+        );
+        let (_, wall) = create_body(
+            &mut graph,
+            RigidBodyType::Static,
+            Vector2::new(2.0, 2.0),
+            ColliderShape::cuboid(0.5, 2.0),
+        );
+        let (character, _) = create_body(
+            &mut graph,
+            RigidBodyType::KinematicPositionBased,
+            Vector2::new(0.0, 0.26),
+            ColliderShape::cuboid(0.25, 0.25),
+        );
+
+        let dt = 1.0 / 60.0;
+        graph.update(Vector2::new(800.0, 600.0), dt, Default::default());
+        graph.update(Vector2::new(800.0, 600.0), dt, Default::default());
+
+        // The character must stop in front of the wall.
+        let movement = CharacterController::default()
+            .move_body(&mut graph, character, Vector2::new(5.0, 0.0), dt)
+            .unwrap();
+        assert!(movement.grounded);
+        assert!(movement.translation.x > 1.0 && movement.translation.x < 1.25);
+        assert!(movement
+            .collisions
+            .iter()
+            .any(|collision| collision.collider == wall));
+
+        graph.update(Vector2::new(800.0, 600.0), dt, Default::default());
+        let position = **graph[character].local_transform().position();
+        assert!((position.x - movement.translation.x).abs() < 1.0e-4);
+    }
+
+    #[test]
+    fn test_compound_shape_2d() {
+        let mut graph = Graph::new();
+
+        create_body(
+            &mut graph,
+            RigidBodyType::Static,
+            Vector2::new(0.0, -0.5),
+            ColliderShape::cuboid(20.0, 0.5),
+        );
+        let dumbbell = ColliderShape::compound(
+            [-1.0, 1.0]
+                .into_iter()
+                .map(|x| CompoundShapeChild {
+                    position: Vector2::new(x, 0.0),
+                    rotation: 0.0,
+                    shape: CompoundSubShape::Cuboid(Default::default()),
+                })
+                .collect(),
+        );
+        let (dumbbell, _) = create_body(
+            &mut graph,
+            RigidBodyType::Dynamic,
+            Vector2::new(0.0, 2.0),
+            dumbbell,
+        );
+
+        let dt = 1.0 / 60.0;
+        for _ in 0..180 {
+            graph.update(Vector2::new(800.0, 600.0), dt, Default::default());
+        }
+
+        // The body must rest on the ground.
+        let position = **graph[dumbbell].local_transform().position();
+        assert!((position.y - 0.5).abs() < 0.1, "{position:?}");
+    }
+
+    #[test]
+    fn test_snapshot_2d() {
+        let mut graph = Graph::new();
+
+        create_body(
+            &mut graph,
+            RigidBodyType::Static,
+            Vector2::new(0.0, -0.5),
+            ColliderShape::cuboid(10.0, 0.5),
+        );
+        let mut bodies = (0..5)
+            .map(|i| {
+                create_body(
+                    &mut graph,
+                    RigidBodyType::Dynamic,
+                    Vector2::new(0.05 * i as f32, 0.5 + 1.1 * i as f32),
+                    ColliderShape::cuboid(0.5, 0.5),
+                )
+                .0
+            })
+            .collect::<Vec<_>>();
+
+        // A pendulum to check that the internal state of joints is restored too.
+        let (anchor, _) = create_body(
+            &mut graph,
+            RigidBodyType::Static,
+            Vector2::new(5.0, 5.0),
+            ColliderShape::ball(0.1),
+        );
+        let (bob, _) = create_body(
+            &mut graph,
+            RigidBodyType::Dynamic,
+            Vector2::new(6.0, 5.0),
+            ColliderShape::ball(0.25),
+        );
+        JointBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(5.0, 5.0, 0.0))
+                    .build(),
+            ),
+        )
+        .with_params(JointParams::BallJoint(Default::default()))
+        .with_body1(anchor.transmute())
+        .with_body2(bob.transmute())
+        .build(&mut graph);
+        bodies.push(bob);
+
+        let dt = 1.0 / 60.0;
+        let simulate = |graph: &mut Graph| {
+            for _ in 0..30 {
+                graph.update(Vector2::new(800.0, 600.0), dt, Default::default());
+            }
+            bodies
+                .iter()
+                .map(|body| **graph[*body].local_transform().position())
+                .collect::<Vec<_>>()
+        };
+
+        simulate(&mut graph);
+
+        let mut visitor = Visitor::new();
+        graph
+            .physics2d
+            .snapshot()
+            .unwrap()
+            .visit("Snapshot", &mut visitor)
+            .unwrap();
+        let data = visitor.save_binary_to_vec().unwrap();
+        let mut visitor = Visitor::load_binary_from_memory(&data).unwrap();
+        let mut snapshot = PhysicsSnapshot::default();
+        snapshot.visit("Snapshot", &mut visitor).unwrap();
+
+        let expected = simulate(&mut graph);
+        graph.physics2d.restore(&snapshot).unwrap();
+        assert_eq!(simulate(&mut graph), expected);
+
+        assert!(graph
+            .physics2d
+            .restore(&PhysicsSnapshot::default())
+            .is_err());
+    }
+
+    #[test]
+    fn test_joints_2d() {
+        let mut graph = Graph::new();
+
+        let mut make_pendulum = |x: f32, params: JointParams| {
+            let anchor_position = Vector2::new(x, 5.0);
+            let (anchor, _) = create_body(
+                &mut graph,
+                RigidBodyType::Static,
+                anchor_position,
+                ColliderShape::ball(0.1),
+            );
+            let (bob, _) = create_body(
+                &mut graph,
+                RigidBodyType::Dynamic,
+                anchor_position,
+                ColliderShape::ball(0.25),
+            );
+            JointBuilder::new(
+                BaseBuilder::new().with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(anchor_position.x, 5.0, 0.0))
+                        .build(),
+                ),
+            )
+            .with_params(params)
+            .with_body1(anchor.transmute())
+            .with_body2(bob.transmute())
+            .with_contacts_enabled(false)
+            .build(&mut graph);
+            bob
+        };
+
+        let rope = make_pendulum(0.0, JointParams::RopeJoint(RopeJoint { max_distance: 1.0 }));
+        let spring = make_pendulum(
+            3.0,
+            JointParams::SpringJoint(SpringJoint {
+                rest_length: 1.0,
+                stiffness: 1000.0,
+                damping: 10.0,
+            }),
+        );
+        let slider = make_pendulum(
+            6.0,
+            JointParams::GenericJoint(GenericJoint {
+                x: GenericJointAxis::locked(),
+                y: GenericJointAxis {
+                    limits_enabled: true,
+                    limits: -0.5..0.5,
+                    ..Default::default()
+                },
+                angular: GenericJointAxis::locked(),
+            }),
+        );
+
+        let dt = 1.0 / 60.0;
+        for _ in 0..300 {
+            graph.update(Vector2::new(800.0, 600.0), dt, Default::default());
+        }
+
+        let position = |handle: Handle<Node>| **graph[handle].local_transform().position();
+
+        assert!((position(rope).y - 4.0).abs() < 0.1);
+        assert!((position(spring).y - 4.0).abs() < 0.1);
+        let slider_position = position(slider);
+        assert!((slider_position.y - 4.5).abs() < 0.05);
+        assert!((slider_position.x - 6.0).abs() < 0.01);
+    }
+}
@@ -142,6 +142,11 @@ pub struct RigidBody {
     #[visit(skip)]
     #[reflect(hidden)]
     pub(crate) native: Cell<RigidBodyHandle>,
+    // Global transform the node got from the last sync with the physics. Such transform must not
+    // be written back to the physics, it would only add rounding errors to the simulation.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pub(crate) synced_global_transform: Cell<Option<Matrix4<f32>>>,
 
     #[visit(skip)]
     #[reflect(hidden)]
@@ -172,6 +177,7 @@ impl Default for RigidBody {
             dominance: Default::default(),
             gravity_scale: InheritableVariable::new_modified(1.0),
             native: Cell::new(RigidBodyHandle::invalid()),
+            synced_global_transform: Default::default(),
             actions: Default::default(),
             reset_forces: Default::default(),
         }
@@ -211,6 +217,7 @@ impl Clone for RigidBody {
             gravity_scale: self.gravity_scale.clone(),
             // Do not copy. The copy will have its own native representation.
             native: Cell::new(RigidBodyHandle::invalid()),
+            synced_global_transform: Default::default(),
             actions: Default::default(),
             reset_forces: self.reset_forces.clone(),
         }
@@ -497,7 +504,9 @@ impl NodeTrait for RigidBody {
         new_global_transform: &Matrix4<f32>,
         context: &mut SyncContext,
     ) {
-        if !m4x4_approx_eq(new_global_transform, &self.global_transform()) {
+        if !m4x4_approx_eq(new_global_transform, &self.global_transform())
+            && self.synced_global_transform.get().as_ref() != Some(new_global_transform)
+        {
             context
                 .physics2d
                 .set_rigid_body_position(self, new_global_transform);
@@ -664,6 +673,7 @@ impl RigidBodyBuilder {
             dominance: self.dominance.into(),
             gravity_scale: self.gravity_scale.into(),
             native: Cell::new(RigidBodyHandle::invalid()),
+            synced_global_transform: Default::default(),
             actions: Default::default(),
             reset_forces: Default::default(),
         }
@@ -0,0 +1,277 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Fog is a scene-wide effect, that blends distant objects with some color to simulate light
+//! extinction in the atmosphere. For more info see [`Fog`].
+
+use crate::core::{
+    algebra::Vector3, color::Color, reflect::prelude::*, uuid_provider, visitor::prelude::*,
+};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+/// Defines how the opacity of the fog depends on the distance between the observer and a point.
+#[derive(Visit, Copy, Clone, PartialEq, Debug, Reflect, AsRefStr, EnumString, VariantNames)]
+pub enum FogMode {
+    /// Opacity of the fog grows linearly from zero at `start` distance to the maximum opacity at
+    /// `end` distance.
+    Linear {
+        /// Distance at which the fog starts.
+        #[reflect(min_value = 0.0, step = 0.1)]
+        start: f32,
+        /// Distance at which the fog reaches its maximum opacity.
+        #[reflect(min_value = 0.0, step = 0.1)]
+        end: f32,
+    },
+    /// Opacity of the fog is `1 - exp(-density * distance)`.
+    Exponential {
+        /// Density of the fog. The larger the value, the thicker the fog.
+        #[reflect(min_value = 0.0, step = 0.001)]
+        density: f32,
+    },
+    /// Density of the fog exponentially decreases with the height, which makes the fog to
+    /// accumulate in lowlands. Opacity of the fog is calculated by integrating the density along
+    /// the view ray.
+    ExponentialHeight {
+        /// Density of the fog at the base height.
+        #[reflect(min_value = 0.0, step = 0.001)]
+        density: f32,
+        /// World-space height (Y coordinate) at which the fog has the specified density.
+        #[reflect(step = 0.1)]
+        base_height: f32,
+        /// Defines how fast the density decreases with the height. The larger the value, the
+        /// thinner the fog layer.
+        #[reflect(min_value = 0.0, step = 0.001)]
+        height_falloff: f32,
+    },
+}
+
+uuid_provider!(FogMode = "b1f2bc39-2b8d-4d2a-9a57-bb0b1d2b1b4f");
+
+impl Default for FogMode {
+    fn default() -> Self {
+        Self::Exponential { density: 0.02 }
+    }
+}
+
+impl FogMode {
+    /// Calculates the opacity of the fog between the observer and the given point, the result is
+    /// in `[0; 1]` range.
+    pub fn opacity(&self, observer: Vector3<f32>, point: Vector3<f32>) -> f32 {
+        let distance = observer.metric_distance(&point);
+        let opacity = match *self {
+            Self::Linear { start, end } => (distance - start) / (end - start).max(f32::EPSILON),
+            Self::Exponential { density } => 1.0 - (-density * distance).exp(),
+            Self::ExponentialHeight {
+                density,
+                base_height,
+                height_falloff,
+            } => {
+                let delta_height = height_falloff * (point.y - observer.y);
+                let height_factor = if delta_height.abs() > 0.0001 {
+                    (1.0 - (-delta_height).exp()) / delta_height
+                } else {
+                    1.0
+                };
+                let integral = density
+                    * distance
+                    * (-height_falloff * (observer.y - base_height)).exp()
+                    * height_factor;
+                1.0 - (-integral).exp()
+            }
+        };
+        opacity.clamp(0.0, 1.0)
+    }
+}
+
+/// Volumetric fog is a participating media, that scatters the light of the scene towards the
+/// observer. Unlike the regular fog, it is lit by the main directional light of the scene (and
+/// takes its shadows into account), which produces light shafts. It is applied in addition to
+/// the regular fog.
+///
+/// The fog is computed in a frustum-aligned voxel grid (froxels) that covers the first
+/// [`VolumetricFog::distance`] units of the view frustum, so the effect does not depend on the
+/// screen resolution.
+#[derive(Visit, Clone, PartialEq, Debug, Reflect)]
+pub struct VolumetricFog {
+    /// Whether the volumetric fog is enabled or not. Default is `false`.
+    pub enabled: bool,
+
+    /// Density of the media. The larger the value, the more light is scattered and absorbed.
+    #[reflect(min_value = 0.0, step = 0.001)]
+    pub density: f32,
+
+    /// Color of the media, it is multiplied with the incoming light.
+    pub albedo: Color,
+
+    /// Defines the direction in which the light is scattered. Positive values scatter the light
+    /// forward (so the light shafts are most visible when looking towards the light source),
+    /// negative values scatter it backwards, zero scatters it uniformly.
+    #[reflect(min_value = -0.99, max_value = 0.99, step = 0.01)]
+    pub anisotropy: f32,
+
+    /// Max distance from the observer, covered by the volumetric fog.
+    #[reflect(min_value = 1.0, step = 1.0)]
+    pub distance: f32,
+}
+
+impl Default for VolumetricFog {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            density: 0.02,
+            albedo: Color::WHITE,
+            anisotropy: 0.3,
+            distance: 64.0,
+        }
+    }
+}
+
+/// Fog blends scene objects with some color depending on the distance to the observer. It is
+/// applied to both opaque and transparent objects (standard shaders apply the fog in their forward
+/// passes, custom shaders could do the same using `fyrox_fogData` built-in property group). 2D
+/// materials (rectangles and tile maps) are not affected by the fog.
+///
+/// # Example
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::color::Color,
+/// #     scene::{
+/// #         fog::{Fog, FogMode},
+/// #         Scene,
+/// #     },
+/// # };
+/// fn add_morning_mist(scene: &mut Scene) {
+///     scene.rendering_options.fog = Fog {
+///         enabled: true,
+///         mode: FogMode::ExponentialHeight {
+///             density: 0.05,
+///             base_height: 0.0,
+///             height_falloff: 0.2,
+///         },
+///         color: Color::opaque(200, 205, 215),
+///         ..Default::default()
+///     };
+/// }
+/// ```
+#[derive(Visit, Clone, PartialEq, Debug, Reflect)]
+pub struct Fog {
+    /// Whether the fog is enabled or not. Default is `false`.
+    pub enabled: bool,
+
+    /// Defines how the opacity of the fog depends on the distance. See [`FogMode`] docs for more
+    /// info.
+    pub mode: FogMode,
+
+    /// Color of the fog.
+    pub color: Color,
+
+    /// If `true`, the color of the fog will be taken from the (blurred) skybox of the camera in the
+    /// direction of view, which blends distant objects with the sky nicely. [`Self::color`] is used
+    /// if the camera has no skybox.
+    pub use_skybox_color: bool,
+
+    /// Maximum opacity of the fog, could be used to keep distant objects partially visible.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.01)]
+    pub max_opacity: f32,
+
+    /// Volumetric fog settings. See [`VolumetricFog`] docs for more info.
+    pub volumetric: VolumetricFog,
+}
+
+impl Default for Fog {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            mode: Default::default(),
+            color: Color::opaque(180, 190, 200),
+            use_skybox_color: false,
+            max_opacity: 1.0,
+            volumetric: Default::default(),
+        }
+    }
+}
+
+impl Fog {
+    /// Calculates the opacity of the fog between the observer and the given point, taking
+    /// [`Self::max_opacity`] into account. Returns zero if the fog is disabled.
+    pub fn opacity(&self, observer: Vector3<f32>, point: Vector3<f32>) -> f32 {
+        if self.enabled {
+            self.mode.opacity(observer, point).min(self.max_opacity)
+        } else {
+            0.0
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::Vector3,
+        scene::fog::{Fog, FogMode},
+    };
+
+    #[test]
+    fn test_fog_opacity() {
+        let observer = Vector3::new(0.0, 1.0, 0.0);
+        let point = Vector3::new(0.0, 1.0, 10.0);
+
+        let linear = FogMode::Linear {
+            start: 5.0,
+            end: 15.0,
+        };
+        assert_eq!(linear.opacity(observer, point), 0.5);
+        assert_eq!(linear.opacity(observer, observer), 0.0);
+
+        let exponential = FogMode::Exponential { density: 0.1 };
+        assert!((exponential.opacity(observer, point) - (1.0 - (-1.0f32).exp())).abs() < 1e-6);
+
+        // Horizontal ray at the base height must match exponential fog with the same density.
+        let height = FogMode::ExponentialHeight {
+            density: 0.1,
+            base_height: 1.0,
+            height_falloff: 0.5,
+        };
+        assert!(
+            (height.opacity(observer, point) - exponential.opacity(observer, point)).abs() < 1e-6
+        );
+        // The fog is thinner above the base height.
+        let above = Vector3::new(0.0, 5.0, 0.0);
+        assert!(
+            height.opacity(above, above + Vector3::z() * 10.0)
+                < exponential.opacity(observer, point)
+        );
+
+        let fog = Fog {
+            enabled: true,
+            mode: exponential,
+            max_opacity: 0.25,
+            ..Default::default()
+        };
+        assert_eq!(fog.opacity(observer, point), 0.25);
+        assert_eq!(
+            Fog {
+                enabled: false,
+                ..fog
+            }
+            .opacity(observer, point),
+            0.0
+        );
+    }
+}
@@ -31,16 +31,16 @@ use crate::{
         log::{Log, MessageKind},
         math::Matrix4Ext,
         parking_lot::Mutex,
-        pool::Handle,
+        pool::{Handle, Pool},
         reflect::prelude::*,
         uuid_provider,
         variable::{InheritableVariable, VariableFlags},
-        visitor::prelude::*,
+        visitor::{prelude::*, BinaryBlob},
         BiDirHashMap,
     },
     scene::{
         self,
-        collider::{self, ColliderShape, GeometrySource},
+        collider::{self, ColliderShape, CompoundSubShape, ConvexHullShape, GeometrySource},
         debug::SceneDrawingContext,
         graph::{isometric_global_transform, Graph, NodePool},
         joint::{JointLocalFrames, JointParams},
@@ -51,10 +51,12 @@ use crate::{
         node::{Node, NodeTrait},
         rigidbody::{self, ApplyAction, RigidBodyMassPropertiesType},
         terrain::{Chunk, Terrain},
+        vehicle::{Vehicle, WheelState},
     },
     utils::raw_mesh::{RawMeshBuilder, RawVertex},
 };
 use rapier3d::{
+    control::{DynamicRayCastVehicleController, WheelTuning},
     dynamics::{
         CCDSolver, GenericJoint, GenericJointBuilder, ImpulseJointHandle, ImpulseJointSet,
         IslandManager, JointAxesMask, MultibodyJointHandle, MultibodyJointSet, RigidBody,
@@ -66,7 +68,7 @@ use rapier3d::{
     },
     parry::{query::ShapeCastOptions, shape::HeightField},
     pipeline::{DebugRenderPipeline, EventHandler, PhysicsPipeline, QueryPipeline},
-    prelude::{HeightFieldCellStatus, JointAxis, MassProperties},
+    prelude::{HeightFieldCellStatus, JointAxis, MassProperties, MotorModel},
 };
 use std::{
     cell::{Cell, RefCell},
@@ -79,7 +81,7 @@ use std::{
 };
 use strum_macros::{AsRefStr, EnumString, VariantNames};
 
-use fyrox_graph::{BaseSceneGraph, SceneGraphNode};
+use fyrox_graph::{BaseSceneGraph, SceneGraph, SceneGraphNode};
 pub use rapier3d::geometry::shape::*;
 
 /// Shape-dependent identifier.
@@ -439,19 +441,74 @@ where
     map: BiDirHashMap<A, Handle<Node>>,
 }
 
+/// Native representation of a [`Vehicle`].
+pub(crate) type NativeVehicle = DynamicRayCastVehicleController;
+
+fn make_native_vehicle(chassis: RigidBodyHandle, vehicle: &Vehicle) -> NativeVehicle {
+    let mut native = DynamicRayCastVehicleController::new(chassis);
+    // Y - up, Z - forward, like everywhere else in the engine.
+    native.index_up_axis = 1;
+    native.index_forward_axis = 2;
+    for wheel in vehicle.wheels() {
+        let native_wheel = native.add_wheel(
+            Point3::from(wheel.connection_point),
+            wheel.direction,
+            wheel.axle,
+            wheel.suspension_rest_length,
+            wheel.radius,
+            &WheelTuning {
+                suspension_stiffness: wheel.suspension_stiffness,
+                suspension_compression: wheel.suspension_compression,
+                suspension_damping: wheel.suspension_damping,
+                max_suspension_travel: wheel.max_suspension_travel,
+                side_friction_stiffness: wheel.side_friction_stiffness,
+                friction_slip: wheel.friction_slip,
+                max_suspension_force: wheel.max_suspension_force,
+            },
+        );
+        native_wheel.steering = wheel.steering;
+        native_wheel.engine_force = wheel.engine_force;
+        native_wheel.brake = wheel.brake;
+    }
+    native
+}
+
 fn convert_joint_params(
     params: scene::joint::JointParams,
     local_frame1: Isometry3<f32>,
     local_frame2: Isometry3<f32>,
 ) -> GenericJoint {
     let locked_axis = match params {
-        JointParams::BallJoint(_) => JointAxesMask::LOCKED_SPHERICAL_AXES,
+        JointParams::BallJoint(_) | JointParams::ConeTwistJoint(_) => {
+            JointAxesMask::LOCKED_SPHERICAL_AXES
+        }
         JointParams::FixedJoint(_) => JointAxesMask::LOCKED_FIXED_AXES,
         JointParams::PrismaticJoint(_) => JointAxesMask::LOCKED_PRISMATIC_AXES,
         JointParams::RevoluteJoint(_) => JointAxesMask::LOCKED_REVOLUTE_AXES,
+        JointParams::RopeJoint(_) | JointParams::SpringJoint(_) => JointAxesMask::empty(),
+        JointParams::GenericJoint(ref v) => [
+            (JointAxesMask::LIN_X, &v.x),
+            (JointAxesMask::LIN_Y, &v.y),
+            (JointAxesMask::LIN_Z, &v.z),
+            (JointAxesMask::ANG_X, &v.angular_x),
+            (JointAxesMask::ANG_Y, &v.angular_y),
+            (JointAxesMask::ANG_Z, &v.angular_z),
+        ]
+        .into_iter()
+        .filter(|(_, axis)| axis.locked)
+        .fold(JointAxesMask::empty(), |mask, (axis_mask, _)| {
+            mask | axis_mask
+        }),
+    };
+
+    let coupled_axes = match params {
+        // Distance between the anchors is measured along the LinX axis.
+        JointParams::RopeJoint(_) | JointParams::SpringJoint(_) => JointAxesMask::LIN_AXES,
+        _ => JointAxesMask::empty(),
     };
 
     let mut joint = GenericJointBuilder::new(locked_axis)
+        .coupled_axes(coupled_axes)
         .local_frame1(local_frame1)
         .local_frame2(local_frame2)
         .build();
@@ -488,6 +545,48 @@ fn convert_joint_params(
                 joint.set_limits(JointAxis::AngX, [v.limits.start, v.limits.end]);
             }
         }
+        scene::joint::JointParams::ConeTwistJoint(v) => {
+            joint.set_limits(JointAxis::AngX, [v.twist_limits.start, v.twist_limits.end]);
+            joint.set_limits(JointAxis::AngY, [-v.swing_y_span, v.swing_y_span]);
+            joint.set_limits(JointAxis::AngZ, [-v.swing_z_span, v.swing_z_span]);
+        }
+        scene::joint::JointParams::RopeJoint(v) => {
+            joint.set_limits(JointAxis::LinX, [0.0, v.max_distance]);
+        }
+        scene::joint::JointParams::SpringJoint(v) => {
+            joint
+                .set_motor_position(JointAxis::LinX, v.rest_length, v.stiffness, v.damping)
+                .set_motor_model(JointAxis::LinX, MotorModel::ForceBased);
+        }
+        scene::joint::JointParams::GenericJoint(v) => {
+            for (axis, params) in [
+                (JointAxis::LinX, &v.x),
+                (JointAxis::LinY, &v.y),
+                (JointAxis::LinZ, &v.z),
+                (JointAxis::AngX, &v.angular_x),
+                (JointAxis::AngY, &v.angular_y),
+                (JointAxis::AngZ, &v.angular_z),
+            ] {
+                if params.locked {
+                    continue;
+                }
+                if params.limits_enabled {
+                    joint.set_limits(axis, [params.limits.start, params.limits.end]);
+                }
+                let motor = &params.motor;
+                if motor.enabled {
+                    joint
+                        .set_motor(
+                            axis,
+                            motor.target_position,
+                            motor.target_velocity,
+                            motor.stiffness,
+                            motor.damping,
+                        )
+                        .set_motor_max_force(axis, motor.max_force);
+                }
+            }
+        }
     }
 
     joint
@@ -803,6 +902,72 @@ fn collider_shape_into_native_shape(
             .try_borrow(polyhedron.geometry_source.0)
             .and_then(|n| n.cast::<Mesh>())
             .map(|mesh| make_polyhedron_shape(owner_inv_global_transform, mesh)),
+        ColliderShape::Compound(compound) => {
+            let parts = compound
+                .children
+                .iter()
+                .filter_map(|child| {
+                    let shape = compound_sub_shape_into_native_shape(&child.shape)?;
+                    let isometry = Isometry3 {
+                        rotation: child.rotation,
+                        translation: Translation3 {
+                            vector: child.position,
+                        },
+                    };
+                    Some((isometry, shape))
+                })
+                .collect::<Vec<_>>();
+            if parts.is_empty() {
+                None
+            } else {
+                Some(SharedShape::compound(parts))
+            }
+        }
+    }
+}
+
+fn make_convex_hull(convex_hull: &ConvexHullShape) -> Option<SharedShape> {
+    let points = convex_hull
+        .points
+        .iter()
+        .map(|p| Point3::from(*p))
+        .collect::<Vec<_>>();
+    let shape = SharedShape::convex_hull(&points);
+    if shape.is_none() {
+        Log::warn(format!(
+            "Unable to build a convex hull of {} points, the points are degenerate.",
+            points.len()
+        ));
+    }
+    shape
+}
+
+// Converts a part of compound shape into a shared shape.
+fn compound_sub_shape_into_native_shape(shape: &CompoundSubShape) -> Option<SharedShape> {
+    match shape {
+        CompoundSubShape::Ball(ball) => Some(SharedShape::ball(ball.radius)),
+        CompoundSubShape::Cylinder(cylinder) => {
+            Some(SharedShape::cylinder(cylinder.half_height, cylinder.radius))
+        }
+        CompoundSubShape::Cone(cone) => Some(SharedShape::cone(cone.half_height, cone.radius)),
+        CompoundSubShape::Cuboid(cuboid) => {
+            Some(SharedShape(Arc::new(Cuboid::new(cuboid.half_extents))))
+        }
+        CompoundSubShape::Capsule(capsule) => Some(SharedShape::capsule(
+            Point3::from(capsule.begin),
+            Point3::from(capsule.end),
+            capsule.radius,
+        )),
+        CompoundSubShape::Segment(segment) => Some(SharedShape::segment(
+            Point3::from(segment.begin),
+            Point3::from(segment.end),
+        )),
+        CompoundSubShape::Triangle(triangle) => Some(SharedShape::triangle(
+            Point3::from(triangle.a),
+            Point3::from(triangle.b),
+            Point3::from(triangle.c),
+        )),
+        CompoundSubShape::ConvexHull(convex_hull) => make_convex_hull(convex_hull),
     }
 }
 
@@ -978,6 +1143,54 @@ impl Default for IntegrationParameters {
     }
 }
 
+/// A serialized state of a physics world, that could be used to rewind the simulation back in
+/// time (for example, for rollback networking) or to store exact physical state in a save file.
+/// See [`PhysicsWorld::snapshot`] and [`PhysicsWorld::restore`] for more info.
+///
+/// The snapshot holds the state of rigid bodies (poses, velocities, sleeping state), colliders,
+/// joints (including their internal solver state) and the contact graph with its warm-start
+/// impulses. Restoring a snapshot and stepping the world again produces bit-for-bit identical
+/// results, as long as the same build of the engine is used. The data format is not guaranteed
+/// to be stable between engine versions.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PhysicsSnapshot {
+    data: Vec<u8>,
+}
+
+impl PhysicsSnapshot {
+    pub(crate) fn from_state<T: serde::Serialize>(state: &T) -> Result<Self, VisitError> {
+        bincode::serialize(state)
+            .map(|data| Self { data })
+            .map_err(|err| {
+                VisitError::User(format!("Unable to capture physics snapshot. Reason: {err}"))
+            })
+    }
+
+    pub(crate) fn to_state<T: serde::de::DeserializeOwned>(&self) -> Result<T, VisitError> {
+        bincode::deserialize(&self.data).map_err(|err| {
+            VisitError::User(format!("Unable to restore physics snapshot. Reason: {err}"))
+        })
+    }
+
+    /// Returns the size of the serialized state in bytes.
+    pub fn size_in_bytes(&self) -> usize {
+        self.data.len()
+    }
+}
+
+impl Visit for PhysicsSnapshot {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        let mut region = visitor.enter_region(name)?;
+
+        BinaryBlob {
+            vec: &mut self.data,
+        }
+        .visit("Data", &mut region)?;
+
+        Ok(())
+    }
+}
+
 /// Physics world is responsible for physics simulation in the engine. There is a very few public
 /// methods, mostly for ray casting. You should add physical entities using scene graph nodes, such
 /// as RigidBody, Collider, Joint.
@@ -1035,6 +1248,10 @@ pub struct PhysicsWorld {
     #[visit(skip)]
     #[reflect(hidden)]
     multibody_joints: Container<MultibodyJointSet, MultibodyJointHandle>,
+    // A container of ray cast vehicle controllers.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    vehicles: Pool<NativeVehicle>,
     // Event handler collects info about contacts and proximity events.
     #[visit(skip)]
     #[reflect(hidden)]
@@ -1129,6 +1346,247 @@ pub struct TOI {
     pub status: collider::TOIStatus,
 }
 
+impl From<rapier3d::parry::query::ShapeCastHit> for TOI {
+    fn from(toi: rapier3d::parry::query::ShapeCastHit) -> Self {
+        Self {
+            toi: toi.time_of_impact,
+            witness1: toi.witness1,
+            witness2: toi.witness2,
+            normal1: toi.normal1,
+            normal2: toi.normal2,
+            status: toi.status.into(),
+        }
+    }
+}
+
+/// Automatic climbing of small obstacles (stairs, curbs, etc.) by a character controller.
+#[derive(Copy, Clone, Visit, Reflect, Debug, PartialEq)]
+#[visit(optional)]
+pub struct CharacterAutostep {
+    /// The maximum height (in meters) of an obstacle, that the character can step on.
+    #[reflect(
+        min_value = 0.0,
+        description = "The maximum height (in meters) of an obstacle, that the character can step on."
+    )]
+    pub max_height: f32,
+
+    /// The minimum width (in meters) of free space, that must be available on top of an obstacle
+    /// for the character to step on it.
+    #[reflect(
+        min_value = 0.0,
+        description = "The minimum width (in meters) of free space, that must be available on top \
+        of an obstacle for the character to step on it."
+    )]
+    pub min_width: f32,
+
+    /// Whether the character can step on dynamic rigid bodies.
+    #[reflect(description = "Whether the character can step on dynamic rigid bodies.")]
+    pub include_dynamic_bodies: bool,
+}
+
+uuid_provider!(CharacterAutostep = "e3a1f0c2-6b7d-4d5e-8f93-1a2b4c6d8e0f");
+
+impl Default for CharacterAutostep {
+    fn default() -> Self {
+        Self {
+            max_height: 0.3,
+            min_width: 0.2,
+            include_dynamic_bodies: true,
+        }
+    }
+}
+
+/// Kinematic character controller. It moves a kinematic rigid body with a collider (a character)
+/// through the physics world, handling slopes, stairs and snapping to the ground, instead of
+/// letting the physics engine to push the character around. It is a set of settings, the actual
+/// movement is done by [`Self::move_body`] (or [`PhysicsWorld::move_character`], if only the
+/// corrected movement is needed).
+///
+/// ## Example
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::{algebra::Vector3, pool::Handle},
+/// #     scene::{graph::{Graph, physics::CharacterController}, node::Node},
+/// # };
+/// fn move_player(graph: &mut Graph, player_body: Handle<Node>, velocity: Vector3<f32>, dt: f32) {
+///     let controller = CharacterController::default();
+///     if let Some(movement) = controller.move_body(graph, player_body, velocity.scale(dt), dt) {
+///         for collision in movement.collisions {
+///             println!("Bumped into {}", collision.collider);
+///         }
+///     }
+/// }
+/// ```
+#[derive(Clone, Visit, Reflect, Debug, PartialEq)]
+#[visit(optional)]
+pub struct CharacterController {
+    /// The direction that goes "up", it is used to tell floors, walls and ceilings apart.
+    #[reflect(
+        description = "The direction that goes \"up\", it is used to tell floors, walls \
+    and ceilings apart."
+    )]
+    pub up: Vector3<f32>,
+
+    /// A small gap (in meters) to preserve between the character and its surroundings. It should
+    /// be small, but not too small to avoid numerical issues.
+    #[reflect(
+        min_value = 0.0,
+        description = "A small gap (in meters) to preserve between the character and its \
+        surroundings."
+    )]
+    pub offset: f32,
+
+    /// Whether the character should slide along obstacles instead of stopping when hitting them.
+    #[reflect(
+        description = "Whether the character should slide along obstacles instead of stopping \
+        when hitting them."
+    )]
+    pub slide: bool,
+
+    /// The maximum angle (in radians) between the floor normal and the up vector, that the
+    /// character is able to climb.
+    #[reflect(
+        min_value = 0.0,
+        max_value = 1.5708,
+        description = "The maximum angle (in radians) between the floor normal and the up \
+        vector, that the character is able to climb."
+    )]
+    pub max_slope_climb_angle: f32,
+
+    /// The minimum angle (in radians) between the floor normal and the up vector, after which the
+    /// character starts to slide down automatically.
+    #[reflect(
+        min_value = 0.0,
+        max_value = 1.5708,
+        description = "The minimum angle (in radians) between the floor normal and the up \
+        vector, after which the character starts to slide down automatically."
+    )]
+    pub min_slope_slide_angle: f32,
+
+    /// Stairs climbing settings. `None` disables automatic stepping.
+    #[reflect(description = "Stairs climbing settings. `None` disables automatic stepping.")]
+    pub autostep: Option<CharacterAutostep>,
+
+    /// The maximum distance (in meters) to the ground, at which the character is snapped to it.
+    /// It prevents the character from "flying" when it moves down slopes or stairs. `None`
+    /// disables snapping.
+    #[reflect(
+        min_value = 0.0,
+        description = "The maximum distance (in meters) to the ground, at which the character is \
+        snapped to it. `None` disables snapping."
+    )]
+    pub snap_to_ground: Option<f32>,
+
+    /// Increase this value if the character gets stuck when sliding against surfaces. It should
+    /// remain small, because it may introduce artificial "bumps" when sliding along flat surfaces.
+    #[reflect(
+        min_value = 0.0,
+        description = "Increase this value if the character gets stuck when sliding against surfaces."
+    )]
+    pub normal_nudge_factor: f32,
+}
+
+impl Default for CharacterController {
+    fn default() -> Self {
+        Self {
+            up: Vector3::y(),
+            offset: 0.01,
+            slide: true,
+            max_slope_climb_angle: 45.0f32.to_radians(),
+            min_slope_slide_angle: 45.0f32.to_radians(),
+            autostep: None,
+            snap_to_ground: Some(0.2),
+            normal_nudge_factor: 1.0e-4,
+        }
+    }
+}
+
+/// A collision between a character and its surroundings, that happened while moving it.
+#[derive(Copy, Clone, Debug)]
+pub struct CharacterCollision {
+    /// A handle of the collider, that was hit by the character.
+    pub collider: Handle<Node>,
+    /// The position of the character at the moment of the collision.
+    pub character_position: Isometry3<f32>,
+    /// The part of the movement, that was done before the collision.
+    pub translation_applied: Vector3<f32>,
+    /// The part of the movement, that was left after the collision.
+    pub translation_remaining: Vector3<f32>,
+    /// The contact geometry of the collision. The first shape is the collider, that was hit.
+    pub toi: TOI,
+}
+
+/// The actual movement of a character, computed by a [`CharacterController`].
+#[derive(Clone, Debug, Default)]
+pub struct CharacterMovement {
+    /// The translation, that can be applied to the character without going through obstacles.
+    pub translation: Vector3<f32>,
+    /// `true` if the character touches the ground after the movement.
+    pub grounded: bool,
+    /// `true` if the character slides down a slope, that is steeper than
+    /// [`CharacterController::min_slope_slide_angle`].
+    pub is_sliding_down_slope: bool,
+    /// Every collision that happened during the movement.
+    pub collisions: Vec<CharacterCollision>,
+}
+
+impl CharacterController {
+    fn to_native(&self) -> rapier3d::control::KinematicCharacterController {
+        rapier3d::control::KinematicCharacterController {
+            up: UnitVector3::try_new(self.up, f32::EPSILON).unwrap_or_else(Vector3::y_axis),
+            offset: rapier3d::control::CharacterLength::Absolute(self.offset),
+            slide: self.slide,
+            autostep: self
+                .autostep
+                .map(|autostep| rapier3d::control::CharacterAutostep {
+                    max_height: rapier3d::control::CharacterLength::Absolute(autostep.max_height),
+                    min_width: rapier3d::control::CharacterLength::Absolute(autostep.min_width),
+                    include_dynamic_bodies: autostep.include_dynamic_bodies,
+                }),
+            max_slope_climb_angle: self.max_slope_climb_angle,
+            min_slope_slide_angle: self.min_slope_slide_angle,
+            snap_to_ground: self
+                .snap_to_ground
+                .map(rapier3d::control::CharacterLength::Absolute),
+            normal_nudge_factor: self.normal_nudge_factor,
+        }
+    }
+
+    /// Moves the given rigid body by the desired translation, sliding it along obstacles,
+    /// climbing stairs and slopes according to the settings of the controller. The rigid body
+    /// must be [`rigidbody::RigidBodyType::KinematicPositionBased`] and must have a collider as
+    /// its child. The movement is applied on the next physics step. Returns `None` if the rigid
+    /// body does not exist, has no collider or wasn't yet added to the physics world.
+    pub fn move_body(
+        &self,
+        graph: &mut Graph,
+        rigid_body: Handle<Node>,
+        desired_translation: Vector3<f32>,
+        dt: f32,
+    ) -> Option<CharacterMovement> {
+        let (position, movement) = {
+            let graph = &*graph;
+            let body = graph.try_get_of_type::<rigidbody::RigidBody>(rigid_body)?;
+            let collider = body.children().iter().copied().find(|child| {
+                graph
+                    .try_get_of_type::<collider::Collider>(*child)
+                    .is_some()
+            })?;
+            let position = *graph.physics.bodies.get(body.native.get())?.translation();
+            let movement =
+                graph
+                    .physics
+                    .move_character(graph, self, collider, desired_translation, dt)?;
+            (position, movement)
+        };
+        graph
+            .try_get_mut_of_type::<rigidbody::RigidBody>(rigid_body)?
+            .set_next_kinematic_translation(position + movement.translation);
+        Some(movement)
+    }
+}
+
 impl PhysicsWorld {
     /// Creates a new instance of the physics world.
     pub(super) fn new() -> Self {
@@ -1151,6 +1609,7 @@ impl PhysicsWorld {
                 set: MultibodyJointSet::new(),
                 map: Default::default(),
             },
+            vehicles: Default::default(),
             event_handler: Box::new(()),
             query: RefCell::new(Default::default()),
             performance_statistics: Default::default(),
@@ -1195,6 +1654,22 @@ impl PhysicsWorld {
 
             let mut query = self.query.borrow_mut();
 
+            for vehicle in self.vehicles.iter_mut() {
+                // Controller panics if there's no chassis.
+                if self.bodies.get(vehicle.chassis).is_some() {
+                    let filter = rapier3d::pipeline::QueryFilter::new()
+                        .exclude_rigid_body(vehicle.chassis)
+                        .exclude_sensors();
+                    vehicle.update_vehicle(
+                        integration_parameters.dt,
+                        &mut self.bodies,
+                        &self.colliders,
+                        &query,
+                        filter,
+                    );
+                }
+            }
+
             self.pipeline.step(
                 &self.gravity,
                 &integration_parameters,
@@ -1266,6 +1741,12 @@ impl PhysicsWorld {
         }
     }
 
+    pub(crate) fn remove_vehicle(&mut self, handle: Handle<NativeVehicle>) {
+        if self.vehicles.is_valid_handle(handle) {
+            self.vehicles.free(handle);
+        }
+    }
+
     /// Draws physics world. Very useful for debugging, it allows you to see where are
     /// rigid bodies, which colliders they have and so on.
     pub fn draw(&self, context: &mut SceneDrawingContext) {
@@ -1413,30 +1894,86 @@ impl PhysicsWorld {
             .map(|(handle, toi)| {
                 (
                     Handle::decode_from_u128(self.colliders.get(handle).unwrap().user_data),
-                    TOI {
-                        toi: toi.time_of_impact,
-                        witness1: toi.witness1,
-                        witness2: toi.witness2,
-                        normal1: toi.normal1,
-                        normal2: toi.normal2,
-                        status: toi.status.into(),
-                    },
+                    toi.into(),
                 )
             })
     }
 
+    /// Computes the movement of a character, represented by the given collider, using the given
+    /// controller. The collider itself is not moved, use [`CharacterController::move_body`] to
+    /// move a kinematic rigid body. Sensors, the rigid body of the collider and colliders, that
+    /// are not in the collision groups of the collider, are ignored. Returns `None` if the
+    /// collider does not exist or wasn't yet added to the physics world.
+    pub fn move_character(
+        &self,
+        graph: &Graph,
+        controller: &CharacterController,
+        collider: Handle<Node>,
+        desired_translation: Vector3<f32>,
+        dt: f32,
+    ) -> Option<CharacterMovement> {
+        let collider_handle = graph
+            .try_get_of_type::<collider::Collider>(collider)?
+            .native
+            .get();
+        let native_collider = self.colliders.get(collider_handle)?;
+
+        let mut filter = rapier3d::pipeline::QueryFilter::new()
+            .exclude_sensors()
+            .exclude_collider(collider_handle)
+            .groups(native_collider.collision_groups());
+        if let Some(parent) = native_collider.parent() {
+            filter = filter.exclude_rigid_body(parent);
+        }
+
+        let query = self.query.borrow();
+
+        let mut collisions = Vec::new();
+        let movement = controller.to_native().move_shape(
+            dt,
+            &self.bodies,
+            &self.colliders,
+            &query,
+            native_collider.shape(),
+            native_collider.position(),
+            desired_translation,
+            filter,
+            |collision| {
+                if let Some(hit_collider) = self.colliders.get(collision.handle) {
+                    collisions.push(CharacterCollision {
+                        collider: Handle::decode_from_u128(hit_collider.user_data),
+                        character_position: collision.character_pos,
+                        translation_applied: collision.translation_applied,
+                        translation_remaining: collision.translation_remaining,
+                        toi: collision.hit.into(),
+                    })
+                }
+            },
+        );
+
+        Some(CharacterMovement {
+            translation: movement.translation,
+            grounded: movement.grounded,
+            is_sliding_down_slope: movement.is_sliding_down_slope,
+            collisions,
+        })
+    }
+
     pub(crate) fn set_rigid_body_position(
         &mut self,
         rigid_body: &scene::rigidbody::RigidBody,
         new_global_transform: &Matrix4<f32>,
     ) {
         if let Some(native) = self.bodies.get_mut(rigid_body.native.get()) {
-            native.set_position(
-                isometry_from_global_transform(new_global_transform),
-                // Do not wake up body, it is too expensive and must be done **only** by explicit
-                // `wake_up` call!
-                false,
-            );
+            let new_position = isometry_from_global_transform(new_global_transform);
+            if native.position() != &new_position {
+                native.set_position(
+                    new_position,
+                    // Do not wake up body, it is too expensive and must be done **only** by explicit
+                    // `wake_up` call!
+                    false,
+                );
+            }
         }
     }
 
@@ -1476,6 +2013,9 @@ impl PhysicsWorld {
                             .local_transform_mut()
                             .set_position(new_local_position)
                             .set_rotation(new_local_rotation);
+                        rigid_body.synced_global_transform.set(Some(
+                            parent_transform * rigid_body.local_transform().matrix(),
+                        ));
                     }
 
                     rigid_body
@@ -1940,6 +2480,108 @@ impl PhysicsWorld {
         }
     }
 
+    pub(crate) fn sync_to_vehicle_node(
+        &mut self,
+        nodes: &NodePool,
+        handle: Handle<Node>,
+        vehicle: &Vehicle,
+    ) {
+        let chassis = nodes
+            .typed_ref(vehicle.chassis())
+            .map(|body| body.native.get())
+            .filter(|native| self.bodies.get(*native).is_some());
+
+        let (true, Some(chassis)) = (vehicle.is_globally_enabled(), chassis) else {
+            self.remove_vehicle(vehicle.native.get());
+            vehicle.native.set(Handle::NONE);
+            return;
+        };
+
+        if let Some(native) = self.vehicles.try_borrow_mut(vehicle.native.get()) {
+            native.chassis = chassis;
+
+            if native.wheels().len() == vehicle.wheels.len() {
+                vehicle.wheels.try_sync_model(|wheels| {
+                    for (native_wheel, wheel) in native.wheels_mut().iter_mut().zip(wheels) {
+                        native_wheel.chassis_connection_point_cs =
+                            Point3::from(wheel.connection_point);
+                        native_wheel.direction_cs = wheel.direction;
+                        native_wheel.axle_cs = wheel.axle;
+                        native_wheel.suspension_rest_length = wheel.suspension_rest_length;
+                        native_wheel.max_suspension_travel = wheel.max_suspension_travel;
+                        native_wheel.radius = wheel.radius;
+                        native_wheel.suspension_stiffness = wheel.suspension_stiffness;
+                        native_wheel.damping_compression = wheel.suspension_compression;
+                        native_wheel.damping_relaxation = wheel.suspension_damping;
+                        native_wheel.max_suspension_force = wheel.max_suspension_force;
+                        native_wheel.friction_slip = wheel.friction_slip;
+                        native_wheel.side_friction_stiffness = wheel.side_friction_stiffness;
+                        native_wheel.steering = wheel.steering;
+                        native_wheel.engine_force = wheel.engine_force;
+                        native_wheel.brake = wheel.brake;
+                    }
+                });
+            } else {
+                // Wheels were added or removed, the controller must be re-created.
+                *native = make_native_vehicle(chassis, vehicle);
+                vehicle.wheels.try_sync_model(|_| {});
+            }
+        } else {
+            let native_handle = self.vehicles.spawn(make_native_vehicle(chassis, vehicle));
+            vehicle.native.set(native_handle);
+
+            Log::writeln(
+                MessageKind::Information,
+                format!(
+                    "Native vehicle was created for node {} ({})",
+                    vehicle.name(),
+                    handle
+                ),
+            );
+        }
+    }
+
+    pub(crate) fn sync_vehicle_node(&self, vehicle: &mut Vehicle) {
+        let Some(native) = self.vehicles.try_borrow(vehicle.native.get()) else {
+            vehicle.wheel_states.clear();
+            vehicle.speed = 0.0;
+            return;
+        };
+        let Some(chassis) = self.bodies.get(native.chassis) else {
+            return;
+        };
+
+        let chassis_position = chassis.position();
+
+        vehicle.speed = native.current_vehicle_speed;
+        vehicle.wheel_states.clear();
+        vehicle
+            .wheel_states
+            .extend(native.wheels().iter().map(|wheel| {
+                let info = wheel.raycast_info();
+                let direction = chassis_position.rotation * wheel.direction_cs;
+                let position = chassis_position * wheel.chassis_connection_point_cs
+                    + direction * info.suspension_length;
+                let steering = UnitQuaternion::from_scaled_axis(-direction * wheel.steering);
+                let spin = UnitVector3::try_new(wheel.axle_cs, f32::EPSILON)
+                    .map(|axle| UnitQuaternion::from_axis_angle(&axle, wheel.rotation))
+                    .unwrap_or_default();
+                WheelState {
+                    position: position.coords,
+                    rotation: steering * chassis_position.rotation * spin,
+                    suspension_length: info.suspension_length,
+                    in_contact: info.is_in_contact,
+                    contact_point: info.contact_point_ws.coords,
+                    contact_normal: info.contact_normal_ws,
+                    ground: info
+                        .ground_object
+                        .and_then(|collider| self.colliders.get(collider))
+                        .map(|collider| Handle::decode_from_u128(collider.user_data))
+                        .unwrap_or_default(),
+                }
+            }));
+    }
+
     /// Intersections checks between regular colliders and sensor colliders
     pub(crate) fn intersections_with(
         &self,
@@ -1974,6 +2616,103 @@ impl PhysicsWorld {
             .contact_pairs()
             .filter_map(|c| ContactPair::from_native(c, self))
     }
+
+    /// Captures the current state of the simulation into a snapshot, that could be restored later
+    /// using [`Self::restore`]. Ray cast vehicle controllers are not included in the snapshot, their
+    /// state is recomputed on every step from the state of their chassis (except the visual wheel
+    /// rotation).
+    pub fn snapshot(&self) -> Result<PhysicsSnapshot, VisitError> {
+        PhysicsSnapshot::from_state(&(
+            &self.islands,
+            &self.broad_phase,
+            &self.narrow_phase,
+            &self.bodies,
+            &self.colliders,
+            &self.joints.set,
+            joint_map_to_vec(&self.joints.map),
+            &self.multibody_joints.set,
+            joint_map_to_vec(&self.multibody_joints.map),
+            &self.ccd_solver,
+            &*self.query.borrow(),
+        ))
+    }
+
+    /// Restores the state of the simulation from the given snapshot. Native handles of bodies,
+    /// colliders and joints are restored as well, which means that the snapshot must be restored
+    /// into the same scene it was taken from, and the set of physical entities of the scene must
+    /// be the same as it was at the moment of taking the snapshot. Scene nodes will receive the
+    /// restored state on the next update of the scene.
+    ///
+    /// Transform changes made to scene nodes of rigid bodies before restoring a snapshot are
+    /// applied on top of the restored state on the next update of the graph.
+    pub fn restore(&mut self, snapshot: &PhysicsSnapshot) -> Result<(), VisitError> {
+        let (
+            islands,
+            broad_phase,
+            narrow_phase,
+            bodies,
+            colliders,
+            joints,
+            joint_map,
+            multibody_joints,
+            multibody_joint_map,
+            ccd_solver,
+            query,
+        ): NativeState = snapshot.to_state()?;
+
+        self.islands = islands;
+        self.broad_phase = broad_phase;
+        self.narrow_phase = narrow_phase;
+        self.bodies = bodies;
+        self.colliders = colliders;
+        self.joints = Container {
+            set: joints,
+            map: joint_map_from_vec(joint_map),
+        };
+        self.multibody_joints = Container {
+            set: multibody_joints,
+            map: joint_map_from_vec(multibody_joint_map),
+        };
+        self.ccd_solver = ccd_solver;
+        *self.query.borrow_mut() = query;
+
+        Ok(())
+    }
+}
+
+type NativeState = (
+    IslandManager,
+    DefaultBroadPhase,
+    NarrowPhase,
+    RigidBodySet,
+    ColliderSet,
+    ImpulseJointSet,
+    Vec<(ImpulseJointHandle, u128)>,
+    MultibodyJointSet,
+    Vec<(MultibodyJointHandle, u128)>,
+    CCDSolver,
+    QueryPipeline,
+);
+
+pub(crate) fn joint_map_to_vec<A>(map: &BiDirHashMap<A, Handle<Node>>) -> Vec<(A, u128)>
+where
+    A: Hash + Eq + Clone,
+{
+    map.forward_map()
+        .iter()
+        .map(|(joint, node)| (joint.clone(), node.encode_to_u128()))
+        .collect()
+}
+
+pub(crate) fn joint_map_from_vec<A>(vec: Vec<(A, u128)>) -> BiDirHashMap<A, Handle<Node>>
+where
+    A: Hash + Eq + Clone,
+{
+    let mut map = BiDirHashMap::default();
+    for (joint, node) in vec {
+        map.insert(joint, Handle::decode_from_u128(node));
+    }
+    map
 }
 
 impl Default for PhysicsWorld {
@@ -1987,3 +2726,411 @@ impl Debug for PhysicsWorld {
         write!(f, "PhysicsWorld")
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::{UnitQuaternion, Vector2, Vector3},
+            pool::Handle,
+            visitor::{Visit, Visitor},
+        },
+        scene::{
+            base::BaseBuilder,
+            collider::{
+                ColliderBuilder, ColliderShape, CompoundShape, CompoundShapeChild,
+                CompoundSubShape, ConvexDecompositionParameters,
+            },
+            graph::{
+                physics::{CharacterController, PhysicsSnapshot},
+                Graph,
+            },
+            joint::{
+                ConeTwistJoint, GenericJoint, GenericJointAxis, JointBuilder, JointParams,
+                RopeJoint, SpringJoint,
+            },
+            node::Node,
+            rigidbody::{RigidBodyBuilder, RigidBodyType},
+            transform::TransformBuilder,
+            vehicle::{Vehicle, VehicleBuilder, Wheel},
+        },
+    };
+
+    fn create_body(
+        graph: &mut Graph,
+        body_type: RigidBodyType,
+        position: Vector3<f32>,
+        shape: ColliderShape,
+    ) -> (Handle<Node>, Handle<Node>) {
+        let collider = ColliderBuilder::new(BaseBuilder::new())
+            .with_shape(shape)
+            .build(graph);
+        let body = RigidBodyBuilder::new(
+            BaseBuilder::new()
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(position)
+                        .build(),
+                )
+                .with_children(&[collider]),
+        )
+        .with_body_type(body_type)
+        .build(graph);
+        (body, collider)
+    }
+
+    #[test]
+    fn test_character_controller() {
+        let mut graph = Graph::new();
+
+        create_body(
+            &mut graph,
+            RigidBodyType::Static,
+            Vector3::new(0.0, -0.5, 0.0),
+            ColliderShape::cuboid(10.0, 0.5, 10.0),
+        );
+        let (_, wall) = create_body(
+            &mut graph,
+            RigidBodyType::Static,
+            Vector3::new(2.0, 2.0, 0.0),
+            ColliderShape::cuboid(0.5, 2.0, 10.0),
+        );
+        let (character, _) = create_body(
+            &mut graph,
+            RigidBodyType::KinematicPositionBased,
+            Vector3::new(0.0, 0.26, 0.0),
+            ColliderShape::cuboid(0.25, 0.25, 0.25),
+        );
+
+        let dt = 1.0 / 60.0;
+        graph.update(Vector2::new(800.0, 600.0), dt, Default::default());
+        graph.update(Vector2::new(800.0, 600.0), dt, Default::default());
+
+        // The character must stop in front of the wall.
+        let movement = CharacterController::default()
+            .move_body(&mut graph, character, Vector3::new(5.0, 0.0, 0.0), dt)
+            .unwrap();
+        assert!(movement.grounded);
+        assert!(movement.translation.x > 1.0 && movement.translation.x < 1.25);
+        assert!(movement
+            .collisions
+            .iter()
+            .any(|collision| collision.collider == wall));
+
+        graph.update(Vector2::new(800.0, 600.0), dt, Default::default());
+        let position = **graph[character].local_transform().position();
+        assert!((position.x - movement.translation.x).abs() < 1.0e-4);
+    }
+
+    #[test]
+    fn test_vehicle() {
+        let mut graph = Graph::new();
+
+        create_body(
+            &mut graph,
+            RigidBodyType::Static,
+            Vector3::new(0.0, -0.5, 0.0),
+            ColliderShape::cuboid(50.0, 0.5, 50.0),
+        );
+        let (chassis, _) = create_body(
+            &mut graph,
+            RigidBodyType::Dynamic,
+            Vector3::new(0.0, 0.6, 0.0),
+            ColliderShape::cuboid(0.9, 0.2, 1.8),
+        );
+
+        let wheels = [(-0.9, 1.5), (0.9, 1.5), (-0.9, -1.5), (0.9, -1.5)]
+            .into_iter()
+            .map(|(x, z)| Wheel {
+                connection_point: Vector3::new(x, 0.0, z),
+                suspension_stiffness: 40.0,
+                ..Default::default()
+            })
+            .collect();
+        let vehicle = VehicleBuilder::new(BaseBuilder::new())
+            .with_chassis(chassis.transmute())
+            .with_wheels(wheels)
+            .build(&mut graph);
+
+        let dt = 1.0 / 60.0;
+        for _ in 0..120 {
+            graph.update(Vector2::new(800.0, 600.0), dt, Default::default());
+        }
+
+        // The suspension must hold the chassis above the ground.
+        let position = **graph[chassis].local_transform().position();
+        assert!(position.y > 0.3);
+        let states = graph[vehicle].cast::<Vehicle>().unwrap().wheel_states();
+        assert_eq!(states.len(), 4);
+        assert!(states.iter().all(|state| state.in_contact));
+        assert!(states.iter().all(|state| state.position.y > 0.0));
+
+        for wheel in graph[vehicle].cast_mut::<Vehicle>().unwrap().wheels_mut() {
+            wheel.engine_force = 20.0;
+        }
+        for _ in 0..60 {
+            graph.update(Vector2::new(800.0, 600.0), dt, Default::default());
+        }
+
+        let vehicle_ref = graph[vehicle].cast::<Vehicle>().unwrap();
+        assert!(vehicle_ref.speed().abs() > 0.1);
+        let new_position = **graph[chassis].local_transform().position();
+        assert!(new_position.z > position.z + 0.1);
+    }
+
+    #[test]
+    fn test_compound_shape() {
+        let mut graph = Graph::new();
+
+        create_body(
+            &mut graph,
+            RigidBodyType::Static,
+            Vector3::new(0.0, -0.5, 0.0),
+            ColliderShape::cuboid(20.0, 0.5, 20.0),
+        );
+
+        let dumbbell = ColliderShape::compound(
+            [-1.0, 1.0]
+                .into_iter()
+                .map(|x| CompoundShapeChild {
+                    position: Vector3::new(x, 0.0, 0.0),
+                    rotation: Default::default(),
+                    shape: CompoundSubShape::Cuboid(Default::default()),
+                })
+                .collect(),
+        );
+        let (dumbbell, _) = create_body(
+            &mut graph,
+            RigidBodyType::Dynamic,
+            Vector3::new(0.0, 2.0, 0.0),
+            dumbbell,
+        );
+
+        // Unit cube.
+        let vertices = (0..8)
+            .map(|i| {
+                Vector3::new(
+                    (i & 1) as f32 - 0.5,
+                    ((i >> 1) & 1) as f32 - 0.5,
+                    ((i >> 2) & 1) as f32 - 0.5,
+                )
+            })
+            .collect::<Vec<_>>();
+        let triangles = [
+            [0, 2, 1],
+            [1, 2, 3],
+            [4, 5, 6],
+            [5, 7, 6],
+            [0, 1, 4],
+            [1, 5, 4],
+            [2, 6, 3],
+            [3, 6, 7],
+            [0, 4, 2],
+            [2, 4, 6],
+            [1, 3, 5],
+            [3, 7, 5],
+        ];
+        let decomposition = CompoundShape::convex_decomposition(
+            &vertices,
+            &triangles,
+            &ConvexDecompositionParameters::default(),
+        );
+        assert!(!decomposition.children.is_empty());
+        assert!(decomposition
+            .children
+            .iter()
+            .all(|child| matches!(child.shape, CompoundSubShape::ConvexHull(_))));
+        let (cube, _) = create_body(
+            &mut graph,
+            RigidBodyType::Dynamic,
+            Vector3::new(5.0, 2.0, 0.0),
+            ColliderShape::Compound(decomposition),
+        );
+
+        let dt = 1.0 / 60.0;
+        for _ in 0..180 {
+            graph.update(Vector2::new(800.0, 600.0), dt, Default::default());
+        }
+
+        // Both bodies must rest on the ground.
+        for body in [dumbbell, cube] {
+            let position = **graph[body].local_transform().position();
+            assert!((position.y - 0.5).abs() < 0.1, "{position:?}");
+        }
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let mut graph = Graph::new();
+
+        create_body(
+            &mut graph,
+            RigidBodyType::Static,
+            Vector3::new(0.0, -0.5, 0.0),
+            ColliderShape::cuboid(10.0, 0.5, 10.0),
+        );
+        let mut bodies = (0..5)
+            .map(|i| {
+                create_body(
+                    &mut graph,
+                    RigidBodyType::Dynamic,
+                    Vector3::new(0.05 * i as f32, 0.5 + 1.1 * i as f32, 0.0),
+                    ColliderShape::cuboid(0.5, 0.5, 0.5),
+                )
+                .0
+            })
+            .collect::<Vec<_>>();
+
+        // A pendulum to check that the internal state of joints is restored too.
+        let (anchor, _) = create_body(
+            &mut graph,
+            RigidBodyType::Static,
+            Vector3::new(5.0, 5.0, 0.0),
+            ColliderShape::ball(0.1),
+        );
+        let (bob, _) = create_body(
+            &mut graph,
+            RigidBodyType::Dynamic,
+            Vector3::new(6.0, 5.0, 0.0),
+            ColliderShape::ball(0.25),
+        );
+        JointBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(5.0, 5.0, 0.0))
+                    .build(),
+            ),
+        )
+        .with_params(JointParams::BallJoint(Default::default()))
+        .with_body1(anchor.transmute())
+        .with_body2(bob.transmute())
+        .build(&mut graph);
+        bodies.push(bob);
+
+        let dt = 1.0 / 60.0;
+        let simulate = |graph: &mut Graph| {
+            for _ in 0..30 {
+                graph.update(Vector2::new(800.0, 600.0), dt, Default::default());
+            }
+            bodies
+                .iter()
+                .map(|body| {
+                    let transform = graph[*body].local_transform();
+                    (**transform.position(), **transform.rotation())
+                })
+                .collect::<Vec<(Vector3<f32>, UnitQuaternion<f32>)>>()
+        };
+
+        simulate(&mut graph);
+
+        // Pass the snapshot through the visitor to check serialization as well.
+        let mut visitor = Visitor::new();
+        graph
+            .physics
+            .snapshot()
+            .unwrap()
+            .visit("Snapshot", &mut visitor)
+            .unwrap();
+        let data = visitor.save_binary_to_vec().unwrap();
+        let mut visitor = Visitor::load_binary_from_memory(&data).unwrap();
+        let mut snapshot = PhysicsSnapshot::default();
+        snapshot.visit("Snapshot", &mut visitor).unwrap();
+        assert_ne!(snapshot.size_in_bytes(), 0);
+
+        let expected = simulate(&mut graph);
+        graph.physics.restore(&snapshot).unwrap();
+        assert_eq!(simulate(&mut graph), expected);
+    }
+
+    #[test]
+    fn test_joints() {
+        let mut graph = Graph::new();
+
+        let mut make_pendulum = |x: f32, bob_offset: Vector3<f32>, params: JointParams| {
+            let anchor_position = Vector3::new(x, 5.0, 0.0);
+            let (anchor, _) = create_body(
+                &mut graph,
+                RigidBodyType::Static,
+                anchor_position,
+                ColliderShape::ball(0.1),
+            );
+            let (bob, _) = create_body(
+                &mut graph,
+                RigidBodyType::Dynamic,
+                anchor_position + bob_offset,
+                ColliderShape::ball(0.25),
+            );
+            JointBuilder::new(
+                BaseBuilder::new().with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(anchor_position)
+                        .build(),
+                ),
+            )
+            .with_params(params)
+            .with_body1(anchor.transmute())
+            .with_body2(bob.transmute())
+            .with_contacts_enabled(false)
+            .build(&mut graph);
+            bob
+        };
+
+        let rope = make_pendulum(
+            0.0,
+            Vector3::default(),
+            JointParams::RopeJoint(RopeJoint { max_distance: 1.0 }),
+        );
+        let spring = make_pendulum(
+            3.0,
+            Vector3::default(),
+            JointParams::SpringJoint(SpringJoint {
+                rest_length: 1.0,
+                stiffness: 1000.0,
+                damping: 10.0,
+            }),
+        );
+        let slider = make_pendulum(
+            6.0,
+            Vector3::default(),
+            JointParams::GenericJoint(GenericJoint {
+                x: GenericJointAxis::locked(),
+                y: GenericJointAxis {
+                    limits_enabled: true,
+                    limits: -0.5..0.5,
+                    ..Default::default()
+                },
+                z: GenericJointAxis::locked(),
+                angular_x: GenericJointAxis::locked(),
+                angular_y: GenericJointAxis::locked(),
+                angular_z: GenericJointAxis::locked(),
+            }),
+        );
+        let cone = make_pendulum(
+            9.0,
+            Vector3::new(1.0, 0.0, 0.0),
+            JointParams::ConeTwistJoint(ConeTwistJoint {
+                swing_y_span: 30.0f32.to_radians(),
+                swing_z_span: 30.0f32.to_radians(),
+                twist_limits: -0.1..0.1,
+            }),
+        );
+
+        let dt = 1.0 / 60.0;
+        for _ in 0..300 {
+            graph.update(Vector2::new(800.0, 600.0), dt, Default::default());
+        }
+
+        let position = |handle: Handle<Node>| **graph[handle].local_transform().position();
+
+        // The rope must hold the bob at its max distance from the anchor.
+        assert!((position(rope).y - 4.0).abs() < 0.1);
+        // Stiff spring must hold the bob close to its rest length.
+        assert!((position(spring).y - 4.0).abs() < 0.1);
+        // The slider must move only down along Y axis to its limit.
+        let slider_position = position(slider);
+        assert!((slider_position.y - 4.5).abs() < 0.05);
+        assert!((slider_position.x - 6.0).abs() < 0.01);
+        // The swing cone must stop the bob way above the lowest point.
+        assert!(position(cone).y > 4.4);
+    }
+}
@@ -23,9 +23,10 @@
 use crate::scene::node::constructor::NodeConstructor;
 use crate::{
     core::{
-        algebra::Matrix4,
+        algebra::{Matrix3, Matrix4, Point3, UnitVector3},
+        color::Color,
         log::Log,
-        math::{aabb::AxisAlignedBoundingBox, m4x4_approx_eq},
+        math::{aabb::AxisAlignedBoundingBox, m4x4_approx_eq, Matrix4Ext},
         pool::Handle,
         reflect::prelude::*,
         type_traits::prelude::*,
@@ -35,6 +36,7 @@ use crate::{
     },
     scene::{
         base::{Base, BaseBuilder},
+        debug::{Line, SceneDrawingContext},
         graph::Graph,
         node::{Node, NodeTrait, SyncContext},
         rigidbody::RigidBody,
@@ -161,6 +163,230 @@ impl Default for RevoluteJoint {
     }
 }
 
+/// Cone-twist joint is a ball joint with its relative rotations restricted by a cone around local
+/// X axis of the joint (swing) and by a range of angles around the same axis (twist). It is the most
+/// common joint for ragdolls: shoulders, hips, neck, etc. The swing cone is approximated by its
+/// angular limits around local Y and Z axes of the joint.
+#[derive(Clone, Debug, Visit, PartialEq, Reflect)]
+pub struct ConeTwistJoint {
+    /// Max angle of rotation around local Y axis of the joint (in radians). It defines one of the
+    /// half-angles of the swing cone.
+    #[reflect(
+        description = "Max angle of rotation around local Y axis of the joint (in radians).",
+        min_value = 0.0,
+        max_value = 3.1415
+    )]
+    pub swing_y_span: f32,
+
+    /// Max angle of rotation around local Z axis of the joint (in radians). It defines one of the
+    /// half-angles of the swing cone.
+    #[reflect(
+        description = "Max angle of rotation around local Z axis of the joint (in radians).",
+        min_value = 0.0,
+        max_value = 3.1415
+    )]
+    pub swing_z_span: f32,
+
+    /// Allowed angle range around local X axis of the joint (in radians).
+    #[reflect(description = "Allowed angle range around local X axis of the joint (in radians).")]
+    pub twist_limits: Range<f32>,
+}
+
+impl Default for ConeTwistJoint {
+    fn default() -> Self {
+        Self {
+            swing_y_span: std::f32::consts::FRAC_PI_4,
+            swing_z_span: std::f32::consts::FRAC_PI_4,
+            twist_limits: -std::f32::consts::FRAC_PI_4..std::f32::consts::FRAC_PI_4,
+        }
+    }
+}
+
+/// Rope joint limits the max distance between the anchors of two rigid bodies, while allowing any
+/// relative rotations and any relative movement within that distance. The anchors of both bodies
+/// are located at the position of the joint at the moment of binding.
+#[derive(Clone, Debug, Visit, PartialEq, Reflect)]
+pub struct RopeJoint {
+    /// Max distance between the anchors of the bodies.
+    #[reflect(
+        description = "Max distance between the anchors of the bodies.",
+        min_value = 0.0
+    )]
+    pub max_distance: f32,
+}
+
+impl Default for RopeJoint {
+    fn default() -> Self {
+        Self { max_distance: 1.0 }
+    }
+}
+
+/// Spring joint pulls the anchors of two rigid bodies towards each other (or pushes them apart) to
+/// keep the distance between them equal to the rest length, while allowing any relative rotations.
+/// The anchors of both bodies are located at the position of the joint at the moment of binding.
+#[derive(Clone, Debug, Visit, PartialEq, Reflect)]
+pub struct SpringJoint {
+    /// Distance between the anchors of the bodies at which the spring is relaxed.
+    #[reflect(
+        description = "Distance between the anchors of the bodies at which the spring is relaxed.",
+        min_value = 0.0
+    )]
+    pub rest_length: f32,
+
+    /// Stiffness of the spring, the larger the value, the stronger the spring.
+    #[reflect(
+        description = "Stiffness of the spring, the larger the value, the stronger the spring.",
+        min_value = 0.0
+    )]
+    pub stiffness: f32,
+
+    /// Damping of the spring, it defines how fast oscillations of the spring fade out.
+    #[reflect(
+        description = "Damping of the spring, it defines how fast oscillations of the spring fade out.",
+        min_value = 0.0
+    )]
+    pub damping: f32,
+}
+
+impl Default for SpringJoint {
+    fn default() -> Self {
+        Self {
+            rest_length: 1.0,
+            stiffness: 10.0,
+            damping: 1.0,
+        }
+    }
+}
+
+/// A motor of a joint axis, that drives relative motion of the bodies along (or around) the axis
+/// towards the target position and velocity.
+#[derive(Clone, Debug, Visit, PartialEq, Reflect)]
+pub struct JointMotor {
+    /// Whether the motor is enabled or not. Default is `false`.
+    #[reflect(description = "Whether the motor is enabled or not.")]
+    pub enabled: bool,
+
+    /// Target relative position (or angle in radians) of the bodies along the axis.
+    #[reflect(description = "Target relative position (or angle in radians) of the bodies.")]
+    pub target_position: f32,
+
+    /// Target relative velocity (linear or angular) of the bodies along the axis.
+    #[reflect(description = "Target relative velocity (linear or angular) of the bodies.")]
+    pub target_velocity: f32,
+
+    /// Defines how strong the motor tries to reach the target position.
+    #[reflect(
+        description = "Defines how strong the motor tries to reach the target position.",
+        min_value = 0.0
+    )]
+    pub stiffness: f32,
+
+    /// Defines how strong the motor tries to reach the target velocity.
+    #[reflect(
+        description = "Defines how strong the motor tries to reach the target velocity.",
+        min_value = 0.0
+    )]
+    pub damping: f32,
+
+    /// Max force (or torque) the motor can apply.
+    #[reflect(
+        description = "Max force (or torque) the motor can apply.",
+        min_value = 0.0
+    )]
+    pub max_force: f32,
+}
+
+impl Default for JointMotor {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            target_position: 0.0,
+            target_velocity: 0.0,
+            stiffness: 0.0,
+            damping: 1.0,
+            max_force: f32::MAX,
+        }
+    }
+}
+
+/// Parameters of a single axis of [`GenericJoint`].
+#[derive(Clone, Debug, Visit, PartialEq, Reflect)]
+pub struct GenericJointAxis {
+    /// Whether any relative motion of the bodies along (or around) the axis is forbidden or not.
+    #[reflect(
+        description = "Whether any relative motion of the bodies along (or around) the axis is forbidden or not."
+    )]
+    pub locked: bool,
+
+    /// Whether the limits of the axis are enabled or not. Has no effect for locked axes.
+    #[reflect(description = "Whether the limits of the axis are enabled or not.")]
+    pub limits_enabled: bool,
+
+    /// Allowed relative position (or angle in radians) range of the bodies along the axis.
+    #[reflect(
+        description = "Allowed relative position (or angle in radians) range of the bodies along the axis."
+    )]
+    pub limits: Range<f32>,
+
+    /// A motor of the axis. Has no effect for locked axes.
+    pub motor: JointMotor,
+}
+
+impl Default for GenericJointAxis {
+    fn default() -> Self {
+        Self {
+            locked: false,
+            limits_enabled: false,
+            limits: -std::f32::consts::PI..std::f32::consts::PI,
+            motor: Default::default(),
+        }
+    }
+}
+
+impl GenericJointAxis {
+    /// Creates new locked axis.
+    pub fn locked() -> Self {
+        Self {
+            locked: true,
+            ..Default::default()
+        }
+    }
+}
+
+/// Generic joint allows you to configure every degree of freedom (three translational and three
+/// rotational) of relative motion of two rigid bodies individually. Every axis could be locked,
+/// limited or driven by a motor. All the axes are defined in the local coordinate system of the
+/// joint. By default, all the translational axes are locked and all the rotational axes are free,
+/// which makes it identical to [`BallJoint`].
+#[derive(Clone, Debug, Visit, PartialEq, Reflect)]
+pub struct GenericJoint {
+    /// Translation along local X axis of the joint.
+    pub x: GenericJointAxis,
+    /// Translation along local Y axis of the joint.
+    pub y: GenericJointAxis,
+    /// Translation along local Z axis of the joint.
+    pub z: GenericJointAxis,
+    /// Rotation around local X axis of the joint.
+    pub angular_x: GenericJointAxis,
+    /// Rotation around local Y axis of the joint.
+    pub angular_y: GenericJointAxis,
+    /// Rotation around local Z axis of the joint.
+    pub angular_z: GenericJointAxis,
+}
+
+impl Default for GenericJoint {
+    fn default() -> Self {
+        Self {
+            x: GenericJointAxis::locked(),
+            y: GenericJointAxis::locked(),
+            z: GenericJointAxis::locked(),
+            angular_x: Default::default(),
+            angular_y: Default::default(),
+            angular_z: Default::default(),
+        }
+    }
+}
+
 /// The exact kind of the joint.
 #[derive(Clone, Debug, PartialEq, Visit, Reflect, AsRefStr, EnumString, VariantNames)]
 pub enum JointParams {
@@ -172,6 +398,14 @@ pub enum JointParams {
     PrismaticJoint(PrismaticJoint),
     /// See [`RevoluteJoint`] for more info.
     RevoluteJoint(RevoluteJoint),
+    /// See [`ConeTwistJoint`] for more info.
+    ConeTwistJoint(ConeTwistJoint),
+    /// See [`RopeJoint`] for more info.
+    RopeJoint(RopeJoint),
+    /// See [`SpringJoint`] for more info.
+    SpringJoint(SpringJoint),
+    /// See [`GenericJoint`] for more info.
+    GenericJoint(GenericJoint),
 }
 
 uuid_provider!(JointParams = "a3e09303-9de4-4123-9492-05e27f29aaa3");
@@ -212,6 +446,121 @@ impl JointLocalFrames {
     }
 }
 
+/// Size of the gizmos that are used to visualize joint limits.
+pub(crate) const LIMITS_GIZMO_SIZE: f32 = 0.5;
+
+/// Draws a sector of a circle in the XY plane of the given transform. Zero angle corresponds to the
+/// X axis.
+pub(crate) fn draw_sector(
+    ctx: &mut SceneDrawingContext,
+    transform: Matrix4<f32>,
+    angles: &Range<f32>,
+    color: Color,
+) {
+    ctx.draw_circle_segment(
+        Vector3::default(),
+        LIMITS_GIZMO_SIZE,
+        32,
+        angles.start,
+        angles.end,
+        transform,
+        color,
+    );
+    for angle in [angles.start, angles.end] {
+        ctx.add_line(Line {
+            begin: transform.position(),
+            end: transform
+                .transform_point(&Point3::new(
+                    angle.cos() * LIMITS_GIZMO_SIZE,
+                    angle.sin() * LIMITS_GIZMO_SIZE,
+                    0.0,
+                ))
+                .coords,
+            color,
+        });
+    }
+}
+
+/// Returns a basis, which columns are the two axes of the plane of rotation around the given axis
+/// and the axis itself.
+fn rotation_plane_basis(axis: usize) -> Matrix4<f32> {
+    let (u, v, n) = match axis {
+        0 => (Vector3::y(), Vector3::z(), Vector3::x()),
+        1 => (Vector3::z(), Vector3::x(), Vector3::y()),
+        _ => (Vector3::x(), Vector3::y(), Vector3::z()),
+    };
+    Matrix3::from_columns(&[u, v, n]).to_homogeneous()
+}
+
+fn draw_angular_limits(
+    ctx: &mut SceneDrawingContext,
+    transform: &Matrix4<f32>,
+    axis: usize,
+    limits: &Range<f32>,
+) {
+    draw_sector(
+        ctx,
+        transform * rotation_plane_basis(axis),
+        limits,
+        Color::ORANGE,
+    );
+}
+
+pub(crate) fn draw_linear_limits(
+    ctx: &mut SceneDrawingContext,
+    transform: &Matrix4<f32>,
+    axis: usize,
+    limits: &Range<f32>,
+) {
+    let mut direction = Vector3::default();
+    direction[axis] = 1.0;
+    let begin = transform.transform_point(&Point3::from(direction.scale(limits.start)));
+    let end = transform.transform_point(&Point3::from(direction.scale(limits.end)));
+    ctx.add_line(Line {
+        begin: begin.coords,
+        end: end.coords,
+        color: Color::ORANGE,
+    });
+}
+
+fn draw_swing_cone(
+    ctx: &mut SceneDrawingContext,
+    transform: &Matrix4<f32>,
+    swing_y_span: f32,
+    swing_z_span: f32,
+) {
+    let segments = 32;
+    let rim = (0..=segments)
+        .map(|i| {
+            let phi = i as f32 / segments as f32 * std::f32::consts::TAU;
+            // Swing angle is interpolated between the spans along an ellipse.
+            let angle = swing_y_span * swing_z_span
+                / ((swing_z_span * phi.cos()).powi(2) + (swing_y_span * phi.sin()).powi(2))
+                    .sqrt()
+                    .max(f32::EPSILON);
+            let swing_axis = UnitVector3::new_normalize(Vector3::new(0.0, phi.cos(), phi.sin()));
+            let direction = UnitQuaternion::from_axis_angle(&swing_axis, angle)
+                * Vector3::x().scale(LIMITS_GIZMO_SIZE);
+            transform.transform_point(&Point3::from(direction)).coords
+        })
+        .collect::<Vec<_>>();
+
+    for (i, pair) in rim.windows(2).enumerate() {
+        ctx.add_line(Line {
+            begin: pair[0],
+            end: pair[1],
+            color: Color::ORANGE,
+        });
+        if i % (segments / 4) == 0 {
+            ctx.add_line(Line {
+                begin: transform.position(),
+                end: pair[0],
+                color: Color::ORANGE,
+            });
+        }
+    }
+}
+
 /// Joint is used to restrict motion of two rigid bodies. There are numerous examples of joints in
 /// real life: door hinge, ball joints in human arms, etc.
 #[derive(Visit, Reflect, Debug, ComponentProvider)]
@@ -384,6 +733,30 @@ impl ConstructorProvider<Node, Graph> for Joint {
                     .build_node()
                     .into()
             })
+            .with_variant("Cone-Twist Joint", |_| {
+                JointBuilder::new(BaseBuilder::new().with_name("Cone-Twist Joint"))
+                    .with_params(JointParams::ConeTwistJoint(Default::default()))
+                    .build_node()
+                    .into()
+            })
+            .with_variant("Rope Joint", |_| {
+                JointBuilder::new(BaseBuilder::new().with_name("Rope Joint"))
+                    .with_params(JointParams::RopeJoint(Default::default()))
+                    .build_node()
+                    .into()
+            })
+            .with_variant("Spring Joint", |_| {
+                JointBuilder::new(BaseBuilder::new().with_name("Spring Joint"))
+                    .with_params(JointParams::SpringJoint(Default::default()))
+                    .build_node()
+                    .into()
+            })
+            .with_variant("Generic Joint", |_| {
+                JointBuilder::new(BaseBuilder::new().with_name("Generic Joint"))
+                    .with_params(JointParams::GenericJoint(Default::default()))
+                    .build_node()
+                    .into()
+            })
             .with_group("Physics")
     }
 }
@@ -427,6 +800,59 @@ impl NodeTrait for Joint {
         }
     }
 
+    fn debug_draw(&self, ctx: &mut SceneDrawingContext) {
+        let transform = self.global_transform();
+
+        ctx.draw_transform(transform * Matrix4::new_scaling(LIMITS_GIZMO_SIZE));
+
+        match &*self.params {
+            JointParams::BallJoint(v) => {
+                for (axis, enabled, limits) in [
+                    (0, v.x_limits_enabled, &v.x_limits_angles),
+                    (1, v.y_limits_enabled, &v.y_limits_angles),
+                    (2, v.z_limits_enabled, &v.z_limits_angles),
+                ] {
+                    if enabled {
+                        draw_angular_limits(ctx, &transform, axis, limits);
+                    }
+                }
+            }
+            JointParams::FixedJoint(_) => {}
+            JointParams::PrismaticJoint(v) => {
+                if v.limits_enabled {
+                    draw_linear_limits(ctx, &transform, 0, &v.limits);
+                }
+            }
+            JointParams::RevoluteJoint(v) => {
+                if v.limits_enabled {
+                    draw_angular_limits(ctx, &transform, 0, &v.limits);
+                }
+            }
+            JointParams::ConeTwistJoint(v) => {
+                draw_angular_limits(ctx, &transform, 0, &v.twist_limits);
+                draw_swing_cone(ctx, &transform, v.swing_y_span, v.swing_z_span);
+            }
+            JointParams::RopeJoint(v) => {
+                ctx.draw_wire_sphere(transform.position(), v.max_distance, 32, Color::ORANGE);
+            }
+            JointParams::SpringJoint(v) => {
+                ctx.draw_wire_sphere(transform.position(), v.rest_length, 32, Color::CYAN);
+            }
+            JointParams::GenericJoint(v) => {
+                for (axis, params) in [(0, &v.x), (1, &v.y), (2, &v.z)] {
+                    if !params.locked && params.limits_enabled {
+                        draw_linear_limits(ctx, &transform, axis, &params.limits);
+                    }
+                }
+                for (axis, params) in [(0, &v.angular_x), (1, &v.angular_y), (2, &v.angular_z)] {
+                    if !params.locked && params.limits_enabled {
+                        draw_angular_limits(ctx, &transform, axis, &params.limits);
+                    }
+                }
+            }
+        }
+    }
+
     fn validate(&self, scene: &Scene) -> Result<(), String> {
         if scene.graph.typed_ref(self.body1()).is_none() {
             return Err("3D Joint has invalid or unassigned handle to a \
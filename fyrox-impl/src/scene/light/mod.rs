@@ -64,6 +64,9 @@ pub const DEFAULT_SCATTER_G: f32 = 0.03;
 /// significant value and you'll clearly see light volume with such settings.
 pub const DEFAULT_SCATTER_B: f32 = 0.03;
 
+/// Default size of a light source, see [`BaseLight::set_source_size`] for more info.
+pub const DEFAULT_SOURCE_SIZE: f32 = 0.1;
+
 /// Light scene node. It contains common properties of light such as color,
 /// scattering factor (per color channel) and other useful properties. Exact
 /// behavior defined by specific light kind.
@@ -84,6 +87,11 @@ pub struct BaseLight {
     #[reflect(min_value = 0.0, step = 0.1)]
     #[reflect(setter = "set_intensity")]
     intensity: InheritableVariable<f32>,
+
+    #[visit(optional)]
+    #[reflect(min_value = 0.0, step = 0.01)]
+    #[reflect(setter = "set_source_size")]
+    source_size: InheritableVariable<f32>,
 }
 
 impl Deref for BaseLight {
@@ -112,6 +120,7 @@ impl Default for BaseLight {
             )),
             scatter_enabled: InheritableVariable::new_modified(true),
             intensity: InheritableVariable::new_modified(1.0),
+            source_size: InheritableVariable::new_modified(DEFAULT_SOURCE_SIZE),
         }
     }
 }
@@ -179,6 +188,23 @@ impl BaseLight {
     pub fn is_scatter_enabled(&self) -> bool {
         *self.scatter_enabled
     }
+
+    /// Sets the size of the light source. It is used by percentage-closer soft shadows (see
+    /// [`crate::renderer::QualitySettings::spot_pcss`]): the bigger the light source, the wider
+    /// the penumbra of its shadows. The penumbra grows with the distance between an occluder and
+    /// a receiver, so contact points stay sharp. For point and spot lights it is the diameter of
+    /// the light source in world units, for directional lights it is the angular diameter of the
+    /// light source in radians.
+    #[inline]
+    pub fn set_source_size(&mut self, size: f32) -> f32 {
+        self.source_size.set_value_and_mark_modified(size.max(0.0))
+    }
+
+    /// Returns the size of the light source. See [`Self::set_source_size`] for more info.
+    #[inline]
+    pub fn source_size(&self) -> f32 {
+        *self.source_size
+    }
 }
 
 /// Light scene node builder. Provides easy declarative way of creating light scene
@@ -189,6 +215,7 @@ pub struct BaseLightBuilder {
     scatter_factor: Vector3<f32>,
     scatter_enabled: bool,
     intensity: f32,
+    source_size: f32,
 }
 
 impl BaseLightBuilder {
@@ -203,6 +230,7 @@ impl BaseLightBuilder {
             scatter_factor: Vector3::new(DEFAULT_SCATTER_R, DEFAULT_SCATTER_G, DEFAULT_SCATTER_B),
             scatter_enabled: true,
             intensity: 1.0,
+            source_size: DEFAULT_SOURCE_SIZE,
         }
     }
 
@@ -230,6 +258,12 @@ impl BaseLightBuilder {
         self
     }
 
+    /// Sets the size of the light source. See [`BaseLight::set_source_size`] for more info.
+    pub fn with_source_size(mut self, size: f32) -> Self {
+        self.source_size = size;
+        self
+    }
+
     /// Creates new instance of base light.
     pub fn build(self) -> BaseLight {
         BaseLight {
@@ -238,6 +272,7 @@ impl BaseLightBuilder {
             scatter: self.scatter_factor.into(),
             scatter_enabled: self.scatter_enabled.into(),
             intensity: self.intensity.into(),
+            source_size: self.source_size.into(),
         }
     }
 }
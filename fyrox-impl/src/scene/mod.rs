@@ -26,12 +26,14 @@
 
 pub mod accel;
 pub mod animation;
+pub mod atmosphere;
 pub mod base;
 pub mod camera;
 pub mod collider;
 pub mod debug;
 pub mod decal;
 pub mod dim2;
+pub mod fog;
 pub mod graph;
 pub mod joint;
 pub mod light;
@@ -40,6 +42,8 @@ pub mod navmesh;
 pub mod node;
 pub mod particle_system;
 pub mod pivot;
+pub mod post_processing;
+pub mod probe;
 pub mod ragdoll;
 pub mod rigidbody;
 pub mod sound;
@@ -47,6 +51,7 @@ pub mod sprite;
 pub mod terrain;
 pub mod tilemap;
 pub mod transform;
+pub mod vehicle;
 
 use crate::renderer::framework::PolygonFillMode;
 use crate::{
@@ -64,9 +69,11 @@ use crate::{
     graph::NodeHandleMap,
     resource::texture::TextureResource,
     scene::{
+        atmosphere::Atmosphere,
         base::BaseBuilder,
         camera::Camera,
         debug::SceneDrawingContext,
+        fog::Fog,
         graph::{Graph, GraphPerformanceStatistics, GraphUpdateSwitches},
         navmesh::NavigationalMeshBuilder,
         node::Node,
@@ -183,8 +190,17 @@ pub struct SceneRenderingOptions {
     /// [`PolygonFillMode::Line`] could be used to render the scene in wireframe mode.
     pub polygon_rasterization_mode: PolygonFillMode,
 
-    /// Color of the ambient lighting.
+    /// Color of the ambient lighting. It is used by 3D scenes as well as 2D scenes, where it is
+    /// the base lighting for the areas that are not lit by 2D lights (see [`crate::scene::dim2::light`]).
     pub ambient_lighting_color: Color,
+
+    /// Fog settings of the scene. See [`Fog`] docs for more info.
+    #[visit(optional)]
+    pub fog: Fog,
+
+    /// Procedural sky of the scene. See [`Atmosphere`] docs for more info.
+    #[visit(optional)]
+    pub atmosphere: Atmosphere,
 }
 
 impl Default for SceneRenderingOptions {
@@ -194,6 +210,8 @@ impl Default for SceneRenderingOptions {
             clear_color: None,
             polygon_rasterization_mode: Default::default(),
             ambient_lighting_color: Color::opaque(100, 100, 100),
+            fog: Default::default(),
+            atmosphere: Default::default(),
         }
     }
 }
@@ -205,6 +223,8 @@ impl Clone for SceneRenderingOptions {
             clear_color: self.clear_color,
             polygon_rasterization_mode: self.polygon_rasterization_mode,
             ambient_lighting_color: self.ambient_lighting_color,
+            fog: self.fog.clone(),
+            atmosphere: self.atmosphere.clone(),
         }
     }
 }
@@ -33,6 +33,7 @@ use crate::scene::{
     node::Node,
     particle_system::ParticleSystem,
     pivot::Pivot,
+    probe::ReflectionProbe,
     ragdoll::Ragdoll,
     sound::{listener::Listener, Sound},
     sprite::Sprite,
@@ -53,6 +54,9 @@ pub fn new_node_constructor_container() -> NodeConstructorContainer {
 
     container.add::<dim2::collider::Collider>();
     container.add::<dim2::joint::Joint>();
+    container.add::<dim2::light::PointLight>();
+    container.add::<dim2::light::SpotLight>();
+    container.add::<dim2::occluder::Occluder>();
     container.add::<Rectangle>();
     container.add::<dim2::rigidbody::RigidBody>();
     container.add::<DirectionalLight>();
@@ -67,6 +71,7 @@ pub fn new_node_constructor_container() -> NodeConstructorContainer {
     container.add::<Decal>();
     container.add::<scene::joint::Joint>();
     container.add::<Pivot>();
+    container.add::<ReflectionProbe>();
     container.add::<scene::rigidbody::RigidBody>();
     container.add::<Sprite>();
     container.add::<Terrain>();
@@ -75,6 +80,7 @@ pub fn new_node_constructor_container() -> NodeConstructorContainer {
     container.add::<NavigationalMesh>();
     container.add::<Ragdoll>();
     container.add::<TileMap>();
+    container.add::<scene::vehicle::Vehicle>();
 
     container
 }
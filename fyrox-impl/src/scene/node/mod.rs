@@ -54,6 +54,7 @@ use crate::{
         navmesh::NavigationalMesh,
         particle_system::ParticleSystem,
         pivot::Pivot,
+        probe::ReflectionProbe,
         ragdoll::Ragdoll,
         sound::{context::SoundContext, listener::Listener, Sound},
         sprite::Sprite,
@@ -507,6 +508,7 @@ impl Node {
     define_is_as!(Sprite  => fn is_sprite, fn as_sprite, fn as_sprite_mut);
     define_is_as!(Terrain  => fn is_terrain, fn as_terrain, fn as_terrain_mut);
     define_is_as!(Decal => fn is_decal, fn as_decal, fn as_decal_mut);
+    define_is_as!(ReflectionProbe => fn is_reflection_probe, fn as_reflection_probe, fn as_reflection_probe_mut);
     define_is_as!(Rectangle => fn is_rectangle, fn as_rectangle, fn as_rectangle_mut);
     define_is_as!(scene::rigidbody::RigidBody  => fn is_rigid_body, fn as_rigid_body, fn as_rigid_body_mut);
     define_is_as!(scene::collider::Collider => fn is_collider, fn as_collider, fn as_collider_mut);
@@ -0,0 +1,174 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Post-processing stack is an ordered list of full-screen effects, that are applied to the
+//! rendered frame of a camera. See [`PostProcessingStack`] docs for more info.
+
+use crate::{
+    core::{reflect::prelude::*, uuid_provider, visitor::prelude::*},
+    material::MaterialResource,
+};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+/// Defines the moment of the frame, at which a post effect is applied.
+#[derive(
+    Visit, Copy, Clone, Default, PartialEq, Eq, Debug, Reflect, AsRefStr, EnumString, VariantNames,
+)]
+pub enum PostEffectStage {
+    /// The effect is applied to the linear high dynamic range frame before tone mapping (which
+    /// also performs eye adaptation and color grading).
+    #[default]
+    Hdr,
+    /// The effect is applied to the low dynamic range (sRGB) frame after tone mapping.
+    Ldr,
+}
+
+uuid_provider!(PostEffectStage = "0f3a4b4e-3c76-4c55-9d7b-52bb2a2f9a61");
+
+/// Bloom makes bright parts of the frame to glow.
+#[derive(Visit, Copy, Clone, PartialEq, Debug, Reflect)]
+pub struct BloomEffect {
+    /// Pixels with luminance above the threshold will glow. Default is 1.0.
+    #[reflect(min_value = 0.0, step = 0.1)]
+    pub threshold: f32,
+    /// Multiplier of the glow, that is added to the frame. Default is 1.0.
+    #[reflect(min_value = 0.0, step = 0.1)]
+    pub intensity: f32,
+}
+
+uuid_provider!(BloomEffect = "8a0e8a6c-0b38-4bb1-a6b4-4cbb3f3c8a44");
+
+impl Default for BloomEffect {
+    fn default() -> Self {
+        Self {
+            threshold: 1.0,
+            intensity: 1.0,
+        }
+    }
+}
+
+/// Custom post effect, that is defined by a material. The shader of the material must have a pass
+/// called `PostEffect`, which is drawn on a full-screen quad. Besides the resources of the material,
+/// the pass can use the following built-in resources: `fyrox_sceneColor` (the frame before the
+/// effect), `fyrox_sceneDepth`, `fyrox_gbufferDiffuse`, `fyrox_gbufferNormal`,
+/// `fyrox_gbufferMaterial`, `fyrox_cameraData` and `fyrox_postEffectData`. See
+/// [`crate::material::shader`] docs for more info about them.
+#[derive(Visit, Clone, Default, PartialEq, Debug, Reflect)]
+pub struct MaterialEffect {
+    /// Material of the effect.
+    pub material: MaterialResource,
+    /// Defines whether the effect is applied to the frame before or after tone mapping.
+    pub stage: PostEffectStage,
+}
+
+uuid_provider!(MaterialEffect = "e8d2b7c2-6a1d-4e8f-8a3b-0f6a6d0a7c15");
+
+/// A single effect of the [`PostProcessingStack`].
+#[derive(Visit, Clone, PartialEq, Debug, Reflect, AsRefStr, EnumString, VariantNames)]
+pub enum PostEffect {
+    /// Bloom effect. It is applied before tone mapping, only the first bloom of the stack is used.
+    /// The effect could be disabled globally using
+    /// [`crate::renderer::QualitySettings::use_bloom`].
+    Bloom(BloomEffect),
+    /// Fast approximate anti-aliasing. It is applied after tone mapping and could be disabled
+    /// globally using [`crate::renderer::QualitySettings::fxaa`].
+    Fxaa,
+    /// Custom effect defined by a material.
+    Material(MaterialEffect),
+}
+
+uuid_provider!(PostEffect = "1c1e5f0a-92f4-4b0e-8a16-5a1a3d9f0d2e");
+
+impl Default for PostEffect {
+    fn default() -> Self {
+        Self::Bloom(Default::default())
+    }
+}
+
+impl PostEffect {
+    /// Returns the stage of the frame, at which the effect is applied.
+    pub fn stage(&self) -> PostEffectStage {
+        match self {
+            Self::Bloom(_) => PostEffectStage::Hdr,
+            Self::Fxaa => PostEffectStage::Ldr,
+            Self::Material(effect) => effect.stage,
+        }
+    }
+}
+
+/// Post-processing stack is an ordered list of effects, that are applied to the rendered frame of
+/// a camera. The frame passes through two stages: the effects of [`PostEffectStage::Hdr`] stage
+/// are applied (in order of the list) to the high dynamic range frame right after temporal
+/// anti-aliasing, motion blur and depth of field, then the frame is tone mapped (see
+/// [`crate::scene::camera::Exposure`] and color grading settings of the camera) and the effects of
+/// [`PostEffectStage::Ldr`] stage are applied in order of the list.
+///
+/// Default stack contains bloom and FXAA, remove them from the list to disable these effects for
+/// the camera.
+///
+/// ## Example
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     material::MaterialResource,
+/// #     scene::post_processing::{
+/// #         BloomEffect, MaterialEffect, PostEffect, PostEffectStage, PostProcessingStack,
+/// #     },
+/// # };
+/// fn make_stack(vignette: MaterialResource) -> PostProcessingStack {
+///     PostProcessingStack {
+///         effects: vec![
+///             PostEffect::Bloom(BloomEffect {
+///                 threshold: 2.0,
+///                 intensity: 0.5,
+///             }),
+///             PostEffect::Fxaa,
+///             PostEffect::Material(MaterialEffect {
+///                 material: vignette,
+///                 stage: PostEffectStage::Ldr,
+///             }),
+///         ],
+///     }
+/// }
+/// ```
+#[derive(Visit, Clone, PartialEq, Debug, Reflect)]
+pub struct PostProcessingStack {
+    /// Ordered list of the effects.
+    pub effects: Vec<PostEffect>,
+}
+
+uuid_provider!(PostProcessingStack = "5d3bb0f1-2f0f-4d55-9a1e-7a8c7f7f5d3b");
+
+impl Default for PostProcessingStack {
+    fn default() -> Self {
+        Self {
+            effects: vec![PostEffect::Bloom(Default::default()), PostEffect::Fxaa],
+        }
+    }
+}
+
+impl PostProcessingStack {
+    /// Returns an iterator over the effects of the given stage in order of the stack.
+    pub fn stage_effects(&self, stage: PostEffectStage) -> impl Iterator<Item = &PostEffect> {
+        self.effects
+            .iter()
+            .filter(move |effect| effect.stage() == stage)
+    }
+}
@@ -0,0 +1,429 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Reflection probe is a node that captures surroundings into a cube map, which is then used for
+//! reflections of the objects inside its influence volume.
+//!
+//! For more info see [`ReflectionProbe`]
+
+use crate::{
+    core::{
+        algebra::Vector3,
+        color::Color,
+        math::{aabb::AxisAlignedBoundingBox, Matrix4Ext},
+        pool::Handle,
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        uuid::{uuid, Uuid},
+        uuid_provider,
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    resource::texture::TextureResource,
+    scene::{
+        base::{Base, BaseBuilder},
+        debug::SceneDrawingContext,
+        graph::Graph,
+        node::{constructor::NodeConstructor, Node, NodeTrait},
+    },
+};
+use fyrox_graph::{constructor::ConstructorProvider, BaseSceneGraph};
+use std::ops::{Deref, DerefMut};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+/// Shape of the influence volume of a reflection probe. Sizes are defined in local coordinates
+/// of the probe, so they're affected by its scale.
+#[derive(Visit, Copy, Clone, PartialEq, Debug, Reflect, AsRefStr, EnumString, VariantNames)]
+pub enum ReflectionProbeShape {
+    /// Oriented box with the given half extents.
+    Box {
+        /// Half size of the box along each axis.
+        #[reflect(min_value = 0.0, step = 0.1)]
+        half_extents: Vector3<f32>,
+    },
+    /// Sphere with the given radius.
+    Sphere {
+        /// Radius of the sphere.
+        #[reflect(min_value = 0.0, step = 0.1)]
+        radius: f32,
+    },
+}
+
+uuid_provider!(ReflectionProbeShape = "0b2b6e2c-54c2-4c4f-8b8c-6f0bd9a7f5b1");
+
+impl Default for ReflectionProbeShape {
+    fn default() -> Self {
+        Self::Box {
+            half_extents: Vector3::new(5.0, 5.0, 5.0),
+        }
+    }
+}
+
+impl ReflectionProbeShape {
+    /// Returns local-space bounding box of the shape.
+    pub fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        match self {
+            Self::Box { half_extents } => {
+                AxisAlignedBoundingBox::from_min_max(-*half_extents, *half_extents)
+            }
+            Self::Sphere { radius } => AxisAlignedBoundingBox::from_radius(*radius),
+        }
+    }
+}
+
+/// Reflection probe captures its surroundings into a cube map, that is used for reflections and
+/// diffuse ambient lighting (image-based lighting) of every opaque object in its influence volume.
+/// Use probes for indoor areas to prevent them from reflecting the environment of the camera
+/// (usually the sky).
+///
+/// # Baking
+///
+/// The content of the probe is static, it must be baked using
+/// [`crate::renderer::Renderer::bake_reflection_probe`], which renders the scene from the position
+/// of the probe into a cube map and saves it as a texture resource. The texture is then assigned to
+/// the probe and saved with the scene as a regular external resource.
+///
+/// # Blending
+///
+/// Objects, that are inside multiple probes, use weighted blend of the reflections of these probes.
+/// The weight of each probe smoothly fades out to zero at the boundary of its influence volume,
+/// the width of the fade area is defined by [`ReflectionProbe::blend_distance`]. The environment
+/// map of the camera is used for the rest of the weight (if any). Only three visible probes, that
+/// are closest to the camera, are used in a frame.
+///
+/// # Parallax correction
+///
+/// By default, a cube map is sampled as if it was infinitely far, which is fine for outdoor areas
+/// but looks wrong in rooms. Box projection intersects reflection rays with the influence volume
+/// and samples the cube map using the direction to the intersection point, which makes reflections
+/// of the walls of a room much more plausible. The volume should match the room bounds for the best
+/// results.
+///
+/// # Example
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::{algebra::Vector3, pool::Handle},
+/// #     scene::{
+/// #         base::BaseBuilder,
+/// #         graph::Graph,
+/// #         node::Node,
+/// #         probe::{ReflectionProbeBuilder, ReflectionProbeShape},
+/// #     },
+/// # };
+/// fn create_room_probe(graph: &mut Graph) -> Handle<Node> {
+///     ReflectionProbeBuilder::new(BaseBuilder::new())
+///         .with_shape(ReflectionProbeShape::Box {
+///             half_extents: Vector3::new(4.0, 2.0, 3.0),
+///         })
+///         .with_box_projection(true)
+///         .build(graph)
+/// }
+/// ```
+#[derive(Debug, Visit, Clone, Reflect, ComponentProvider)]
+#[reflect(derived_type = "Node")]
+pub struct ReflectionProbe {
+    base: Base,
+
+    #[reflect(setter = "set_shape")]
+    shape: InheritableVariable<ReflectionProbeShape>,
+
+    #[reflect(min_value = 0.0, step = 0.1)]
+    #[reflect(setter = "set_blend_distance")]
+    blend_distance: InheritableVariable<f32>,
+
+    #[reflect(setter = "set_box_projection")]
+    box_projection: InheritableVariable<bool>,
+
+    #[reflect(min_value = 0.0, step = 0.1)]
+    #[reflect(setter = "set_intensity")]
+    intensity: InheritableVariable<f32>,
+
+    #[reflect(min_value = 1.0, max_value = 4096.0)]
+    #[reflect(setter = "set_resolution")]
+    resolution: InheritableVariable<u32>,
+
+    #[reflect(min_value = 0.0, step = 0.01)]
+    #[reflect(setter = "set_z_near")]
+    z_near: InheritableVariable<f32>,
+
+    #[reflect(min_value = 0.0, step = 0.1)]
+    #[reflect(setter = "set_z_far")]
+    z_far: InheritableVariable<f32>,
+
+    #[reflect(setter = "set_environment")]
+    environment: InheritableVariable<Option<TextureResource>>,
+}
+
+impl Default for ReflectionProbe {
+    fn default() -> Self {
+        ReflectionProbeBuilder::new(BaseBuilder::new()).build_reflection_probe()
+    }
+}
+
+impl Deref for ReflectionProbe {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for ReflectionProbe {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl TypeUuidProvider for ReflectionProbe {
+    fn type_uuid() -> Uuid {
+        uuid!("8d8d6d5e-2f4a-4b7e-9d0c-3c5b1a2f7e41")
+    }
+}
+
+impl ReflectionProbe {
+    /// Sets new shape of the influence volume.
+    pub fn set_shape(&mut self, shape: ReflectionProbeShape) -> ReflectionProbeShape {
+        self.shape.set_value_and_mark_modified(shape)
+    }
+
+    /// Returns current shape of the influence volume.
+    pub fn shape(&self) -> ReflectionProbeShape {
+        *self.shape
+    }
+
+    /// Sets the width (in local coordinates) of the area near the boundary of the influence volume,
+    /// in which the probe fades out. Zero means that there is no fading at all.
+    pub fn set_blend_distance(&mut self, distance: f32) -> f32 {
+        self.blend_distance
+            .set_value_and_mark_modified(distance.max(0.0))
+    }
+
+    /// Returns the width of the fade area of the influence volume.
+    pub fn blend_distance(&self) -> f32 {
+        *self.blend_distance
+    }
+
+    /// Enables or disables parallax correction of reflections. See the docs of [`ReflectionProbe`]
+    /// for more info.
+    pub fn set_box_projection(&mut self, enabled: bool) -> bool {
+        self.box_projection.set_value_and_mark_modified(enabled)
+    }
+
+    /// Returns `true` if the parallax correction is enabled, `false` - otherwise.
+    pub fn is_box_projection_enabled(&self) -> bool {
+        *self.box_projection
+    }
+
+    /// Sets a multiplier for the reflections of the probe.
+    pub fn set_intensity(&mut self, intensity: f32) -> f32 {
+        self.intensity
+            .set_value_and_mark_modified(intensity.max(0.0))
+    }
+
+    /// Returns the multiplier for the reflections of the probe.
+    pub fn intensity(&self) -> f32 {
+        *self.intensity
+    }
+
+    /// Sets the size (in pixels) of each face of the cube map that will be baked.
+    pub fn set_resolution(&mut self, resolution: u32) -> u32 {
+        self.resolution
+            .set_value_and_mark_modified(resolution.max(1))
+    }
+
+    /// Returns the size (in pixels) of each face of the cube map that will be baked.
+    pub fn resolution(&self) -> u32 {
+        *self.resolution
+    }
+
+    /// Sets the distance to the near clipping plane that will be used when baking the probe.
+    pub fn set_z_near(&mut self, z_near: f32) -> f32 {
+        self.z_near.set_value_and_mark_modified(z_near)
+    }
+
+    /// Returns the distance to the near clipping plane that will be used when baking the probe.
+    pub fn z_near(&self) -> f32 {
+        *self.z_near
+    }
+
+    /// Sets the distance to the far clipping plane that will be used when baking the probe.
+    pub fn set_z_far(&mut self, z_far: f32) -> f32 {
+        self.z_far.set_value_and_mark_modified(z_far)
+    }
+
+    /// Returns the distance to the far clipping plane that will be used when baking the probe.
+    pub fn z_far(&self) -> f32 {
+        *self.z_far
+    }
+
+    /// Sets new cube map of the probe. Usually there's no need to call this method manually, the
+    /// texture is assigned when the probe is baked. The texture must be a cube texture
+    /// ([`crate::resource::texture::TextureKind::Cube`]) in sRGB color space, otherwise it will be
+    /// ignored by the renderer.
+    pub fn set_environment(
+        &mut self,
+        environment: Option<TextureResource>,
+    ) -> Option<TextureResource> {
+        self.environment.set_value_and_mark_modified(environment)
+    }
+
+    /// Returns current cube map of the probe.
+    pub fn environment(&self) -> Option<&TextureResource> {
+        self.environment.as_ref()
+    }
+}
+
+impl ConstructorProvider<Node, Graph> for ReflectionProbe {
+    fn constructor() -> NodeConstructor {
+        NodeConstructor::new::<Self>().with_variant("Reflection Probe", |_| {
+            ReflectionProbeBuilder::new(BaseBuilder::new().with_name("ReflectionProbe"))
+                .build_node()
+                .into()
+        })
+    }
+}
+
+impl NodeTrait for ReflectionProbe {
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.shape.local_bounding_box()
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.local_bounding_box()
+            .transform(&self.global_transform())
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+
+    fn debug_draw(&self, ctx: &mut SceneDrawingContext) {
+        match *self.shape {
+            ReflectionProbeShape::Box { .. } => ctx.draw_oob(
+                &self.shape.local_bounding_box(),
+                self.global_transform(),
+                Color::GREEN,
+            ),
+            ReflectionProbeShape::Sphere { radius } => {
+                let scale = self.global_transform().basis().column(0).norm();
+                ctx.draw_wire_sphere(self.global_position(), radius * scale, 30, Color::GREEN)
+            }
+        }
+    }
+}
+
+/// Allows you to create a reflection probe in a declarative manner.
+pub struct ReflectionProbeBuilder {
+    base_builder: BaseBuilder,
+    shape: ReflectionProbeShape,
+    blend_distance: f32,
+    box_projection: bool,
+    intensity: f32,
+    resolution: u32,
+    z_near: f32,
+    z_far: f32,
+    environment: Option<TextureResource>,
+}
+
+impl ReflectionProbeBuilder {
+    /// Creates a new instance of the builder.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            shape: Default::default(),
+            blend_distance: 1.0,
+            box_projection: false,
+            intensity: 1.0,
+            resolution: 256,
+            z_near: 0.025,
+            z_far: 128.0,
+            environment: None,
+        }
+    }
+
+    /// Sets desired shape of the influence volume.
+    pub fn with_shape(mut self, shape: ReflectionProbeShape) -> Self {
+        self.shape = shape;
+        self
+    }
+
+    /// Sets desired width of the fade area of the influence volume.
+    pub fn with_blend_distance(mut self, distance: f32) -> Self {
+        self.blend_distance = distance;
+        self
+    }
+
+    /// Enables or disables parallax correction.
+    pub fn with_box_projection(mut self, enabled: bool) -> Self {
+        self.box_projection = enabled;
+        self
+    }
+
+    /// Sets desired multiplier for the reflections of the probe.
+    pub fn with_intensity(mut self, intensity: f32) -> Self {
+        self.intensity = intensity;
+        self
+    }
+
+    /// Sets desired size of each face of the cube map that will be baked.
+    pub fn with_resolution(mut self, resolution: u32) -> Self {
+        self.resolution = resolution;
+        self
+    }
+
+    /// Sets desired clipping planes that will be used when baking the probe.
+    pub fn with_clipping_planes(mut self, z_near: f32, z_far: f32) -> Self {
+        self.z_near = z_near;
+        self.z_far = z_far;
+        self
+    }
+
+    /// Sets desired (already baked) cube map.
+    pub fn with_environment(mut self, environment: TextureResource) -> Self {
+        self.environment = Some(environment);
+        self
+    }
+
+    /// Creates new reflection probe.
+    pub fn build_reflection_probe(self) -> ReflectionProbe {
+        ReflectionProbe {
+            base: self.base_builder.build_base(),
+            shape: self.shape.into(),
+            blend_distance: self.blend_distance.max(0.0).into(),
+            box_projection: self.box_projection.into(),
+            intensity: self.intensity.into(),
+            resolution: self.resolution.max(1).into(),
+            z_near: self.z_near.into(),
+            z_far: self.z_far.into(),
+            environment: self.environment.into(),
+        }
+    }
+
+    /// Creates new reflection probe node.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_reflection_probe())
+    }
+
+    /// Creates new instance of reflection probe node and puts it in the given graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Node> {
+        graph.add_node(self.build_node())
+    }
+}
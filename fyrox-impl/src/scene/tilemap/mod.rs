@@ -37,7 +37,7 @@ pub use autotile::*;
 use brush::*;
 pub use data::*;
 pub use effect::*;
-use fxhash::FxHashSet;
+use fxhash::{FxHashMap, FxHashSet};
 use fyrox_core::{
     math::{frustum::Frustum, plane::Plane, ray::Ray},
     parking_lot::Mutex,
@@ -957,6 +957,9 @@ pub struct TileMap {
     pub tiles: InheritableVariable<Option<TileMapDataResource>>,
     tile_scale: InheritableVariable<Vector2<f32>>,
     active_brush: InheritableVariable<Option<TileMapBrushResource>>,
+    /// Name of the tile set collider layer that is used to generate 2D light occluders. Empty
+    /// name means that the tile map does not cast 2D shadows.
+    occluder_layer: InheritableVariable<ImmutableString>,
     /// Temporary space to store which tiles are invisible during `collect_render_data`.
     /// This is part of how [`TileMapEffect`] can prevent a tile from being rendered.
     #[reflect(hidden)]
@@ -984,6 +987,7 @@ impl Visit for TileMap {
         self.tile_set.visit("TileSet", &mut region)?;
         self.tile_scale.visit("TileScale", &mut region)?;
         self.active_brush.visit("ActiveBrush", &mut region)?;
+        let _ = self.occluder_layer.visit("OccluderLayer", &mut region);
         match version {
             0 => {
                 let mut tiles = InheritableVariable::new_non_modified(Tiles::default());
@@ -1205,6 +1209,86 @@ impl TileMap {
         self.tile_scale.set_value_and_mark_modified(tile_scale);
     }
 
+    /// Sets the name of the tile set collider layer that is used to generate 2D light occluders.
+    /// Every tile that has a collider in this layer will block light of shadow casting 2D lights
+    /// (see [`crate::scene::dim2::light`]). Empty name disables occluder generation.
+    #[inline]
+    pub fn set_occluder_layer(&mut self, occluder_layer: ImmutableString) {
+        self.occluder_layer
+            .set_value_and_mark_modified(occluder_layer);
+    }
+
+    /// Returns the name of the tile set collider layer that is used to generate 2D light occluders.
+    #[inline]
+    pub fn occluder_layer(&self) -> &ImmutableString {
+        &self.occluder_layer
+    }
+
+    /// Collects world-space outline edges of the tiles that have a collider in the occluder layer
+    /// (see [`Self::set_occluder_layer`]). Edges shared by adjacent tiles are removed, so only
+    /// the outer outline of the collider shapes is left.
+    pub fn collect_occluder_edges(&self, edges: &mut Vec<[Vector2<f32>; 2]>) {
+        if self.occluder_layer.is_empty() {
+            return;
+        }
+        let Some(tile_set_resource) = self.tile_set() else {
+            return;
+        };
+        let tile_set = tile_set_resource.data_ref();
+        let Some(tile_set) = tile_set.as_loaded_ref() else {
+            return;
+        };
+        let Some(collider_uuid) = tile_set.collider_name_to_uuid(&self.occluder_layer) else {
+            return;
+        };
+        let Some(tiles) = self.tiles() else {
+            return;
+        };
+        let tiles = tiles.data_ref();
+        let Some(tiles) = tiles.as_loaded_ref() else {
+            return;
+        };
+
+        let tile_scale = self.tile_scale();
+        let transform = self.global_transform()
+            * Matrix4::new_nonuniform_scaling(&Vector3::new(-tile_scale.x, tile_scale.y, 1.0));
+
+        let mut vertices = Vec::new();
+        let mut triangles = Vec::new();
+        for (position, handle) in tiles.iter() {
+            let Some(tile_definition) = tile_set.get_tile_data(handle.into()) else {
+                continue;
+            };
+            if let Some(collider) = tile_definition.colliders.get(&collider_uuid) {
+                let position = position.cast::<f32>().to_homogeneous();
+                collider.build_collider_shape(&transform, position, &mut vertices, &mut triangles);
+            }
+        }
+
+        // Interior edges are shared by two triangles (either of the same tile or of adjacent
+        // tiles), they must not cast shadows, so count every edge and keep the unique ones only.
+        let key = |p: Vector2<f32>| ((p.x * 1024.0).round() as i64, (p.y * 1024.0).round() as i64);
+        let mut edge_counts = FxHashMap::default();
+        for triangle in triangles.iter() {
+            for (a, b) in [(0, 1), (1, 2), (2, 0)] {
+                let a = vertices[triangle[a] as usize].coords;
+                let b = vertices[triangle[b] as usize].coords;
+                let (ka, kb) = (key(a), key(b));
+                if ka == kb {
+                    continue;
+                }
+                let edge_key = if ka < kb { (ka, kb) } else { (kb, ka) };
+                edge_counts.entry(edge_key).or_insert((0usize, [a, b])).0 += 1;
+            }
+        }
+        edges.extend(
+            edge_counts
+                .into_values()
+                .filter(|(count, _)| count % 2 == 1)
+                .map(|(_, edge)| edge),
+        );
+    }
+
     /// Inserts a tile in the tile map. Returns previous tile, located at the same position as
     /// the new one (if any).
     #[inline]
@@ -1333,6 +1417,7 @@ impl Default for TileMap {
             tiles: Default::default(),
             tile_scale: Vector2::repeat(1.0).into(),
             active_brush: Default::default(),
+            occluder_layer: Default::default(),
             hidden_tiles: Mutex::default(),
             before_effects: Vec::default(),
             after_effects: Vec::default(),
@@ -1348,6 +1433,7 @@ impl Clone for TileMap {
             tiles: self.tiles.clone(),
             tile_scale: self.tile_scale.clone(),
             active_brush: self.active_brush.clone(),
+            occluder_layer: self.occluder_layer.clone(),
             hidden_tiles: Mutex::default(),
             before_effects: self.before_effects.clone(),
             after_effects: self.after_effects.clone(),
@@ -1488,6 +1574,7 @@ pub struct TileMapBuilder {
     tile_set: Option<TileSetResource>,
     tiles: TileMapData,
     tile_scale: Vector2<f32>,
+    occluder_layer: ImmutableString,
     before_effects: Vec<TileMapEffectRef>,
     after_effects: Vec<TileMapEffectRef>,
 }
@@ -1500,6 +1587,7 @@ impl TileMapBuilder {
             tile_set: None,
             tiles: TileMapData::default(),
             tile_scale: Vector2::repeat(1.0),
+            occluder_layer: Default::default(),
             before_effects: Default::default(),
             after_effects: Default::default(),
         }
@@ -1525,6 +1613,13 @@ impl TileMapBuilder {
         self
     }
 
+    /// Sets the name of the tile set collider layer that will be used to generate 2D light
+    /// occluders. See [`TileMap::set_occluder_layer`] for more info.
+    pub fn with_occluder_layer(mut self, occluder_layer: ImmutableString) -> Self {
+        self.occluder_layer = occluder_layer;
+        self
+    }
+
     /// Adds an effect to the tile map which will run before the tiles render.
     pub fn with_before_effect(mut self, effect: TileMapEffectRef) -> Self {
         self.before_effects.push(effect);
@@ -1550,6 +1645,7 @@ impl TileMapBuilder {
             .into(),
             tile_scale: self.tile_scale.into(),
             active_brush: Default::default(),
+            occluder_layer: self.occluder_layer.into(),
             hidden_tiles: Mutex::default(),
             before_effects: self.before_effects,
             after_effects: self.after_effects,
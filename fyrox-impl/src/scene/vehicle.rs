@@ -0,0 +1,468 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Vehicle is a ray cast based vehicle controller, that drives a rigid body (chassis) using a set
+//! of wheels. See [`Vehicle`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::{UnitQuaternion, Vector3},
+        log::Log,
+        math::aabb::AxisAlignedBoundingBox,
+        pool::Handle,
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        uuid::{uuid, Uuid},
+        uuid_provider,
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    scene::{
+        base::{Base, BaseBuilder},
+        graph::{physics::NativeVehicle, Graph},
+        node::{constructor::NodeConstructor, Node, NodeTrait, SyncContext, UpdateContext},
+        rigidbody::RigidBody,
+        Scene,
+    },
+};
+use fyrox_graph::constructor::ConstructorProvider;
+use fyrox_graph::{BaseSceneGraph, SceneGraph};
+use std::{
+    cell::Cell,
+    ops::{Deref, DerefMut},
+};
+
+/// A wheel of a [`Vehicle`]. Every wheel casts a ray from its connection point along the
+/// suspension direction to find the ground and applies suspension, friction, engine and brake
+/// forces to the chassis.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect)]
+#[visit(optional)]
+pub struct Wheel {
+    /// The point, where the suspension of the wheel is attached to the chassis. It is given in
+    /// the local coordinates of the chassis rigid body.
+    #[reflect(
+        description = "The point, where the suspension of the wheel is attached to the chassis \
+        (in the local coordinates of the chassis)."
+    )]
+    pub connection_point: Vector3<f32>,
+
+    /// The direction of the suspension in the local coordinates of the chassis. Usually it points
+    /// down.
+    #[reflect(
+        description = "The direction of the suspension in the local coordinates of the \
+    chassis. Usually it points down."
+    )]
+    pub direction: Vector3<f32>,
+
+    /// The rotation axis of the wheel in the local coordinates of the chassis. By default it is
+    /// `-X`, which makes positive engine force to move the vehicle along its local `+Z` axis.
+    #[reflect(
+        description = "The rotation axis of the wheel in the local coordinates of the chassis."
+    )]
+    pub axle: Vector3<f32>,
+
+    /// The length of the suspension, when it is not loaded.
+    #[reflect(
+        min_value = 0.0,
+        description = "The length of the suspension, when it is not loaded."
+    )]
+    pub suspension_rest_length: f32,
+
+    /// The maximum distance the suspension can be compressed or stretched from its rest length.
+    #[reflect(
+        min_value = 0.0,
+        description = "The maximum distance the suspension can be compressed or stretched from \
+        its rest length."
+    )]
+    pub max_suspension_travel: f32,
+
+    /// The radius of the wheel.
+    #[reflect(min_value = 0.0, description = "The radius of the wheel.")]
+    pub radius: f32,
+
+    /// The stiffness of the suspension. Increase it, if the suspension does not hold the chassis
+    /// well.
+    #[reflect(
+        min_value = 0.0,
+        description = "The stiffness of the suspension. Increase it, if the suspension does not \
+        hold the chassis well."
+    )]
+    pub suspension_stiffness: f32,
+
+    /// The damping of the suspension when it is compressed. Increase it, if the suspension
+    /// oscillates too much.
+    #[reflect(
+        min_value = 0.0,
+        description = "The damping of the suspension when it is compressed."
+    )]
+    pub suspension_compression: f32,
+
+    /// The damping of the suspension when it is stretched back. Increase it, if the suspension
+    /// oscillates too much.
+    #[reflect(
+        min_value = 0.0,
+        description = "The damping of the suspension when it is stretched back."
+    )]
+    pub suspension_damping: f32,
+
+    /// The maximum force, that the suspension can apply to the chassis.
+    #[reflect(
+        min_value = 0.0,
+        description = "The maximum force, that the suspension can apply to the chassis."
+    )]
+    pub max_suspension_force: f32,
+
+    /// Defines the traction of the tire. The larger the value, the more instantaneous braking
+    /// will happen (with the risk of flipping the vehicle, if it is too strong).
+    #[reflect(min_value = 0.0, description = "Defines the traction of the tire.")]
+    pub friction_slip: f32,
+
+    /// The multiplier of the side friction between the tire and the ground.
+    #[reflect(
+        min_value = 0.0,
+        description = "The multiplier of the side friction between the tire and the ground."
+    )]
+    pub side_friction_stiffness: f32,
+
+    /// The steering angle (in radians) of the wheel.
+    #[reflect(description = "The steering angle (in radians) of the wheel.")]
+    pub steering: f32,
+
+    /// The force, that the engine applies to the wheel to move the vehicle forward (or backward,
+    /// if negative).
+    #[reflect(
+        description = "The force, that the engine applies to the wheel to move the vehicle \
+        forward (or backward, if negative)."
+    )]
+    pub engine_force: f32,
+
+    /// The braking force of the wheel.
+    #[reflect(min_value = 0.0, description = "The braking force of the wheel.")]
+    pub brake: f32,
+}
+
+uuid_provider!(Wheel = "0d6c7e5a-41b3-4b0f-9a1e-7c2f8d3b6a94");
+
+impl Default for Wheel {
+    fn default() -> Self {
+        Self {
+            connection_point: Default::default(),
+            direction: -Vector3::y(),
+            axle: -Vector3::x(),
+            suspension_rest_length: 0.3,
+            max_suspension_travel: 5.0,
+            radius: 0.3,
+            suspension_stiffness: 5.88,
+            suspension_compression: 0.83,
+            suspension_damping: 0.88,
+            max_suspension_force: 6000.0,
+            friction_slip: 10.5,
+            side_friction_stiffness: 1.0,
+            steering: 0.0,
+            engine_force: 0.0,
+            brake: 0.0,
+        }
+    }
+}
+
+/// The actual state of a [`Wheel`] after the last physics step. It can be used to place visual
+/// meshes of the wheels.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WheelState {
+    /// The position of the center of the wheel in world coordinates.
+    pub position: Vector3<f32>,
+    /// The orientation of the wheel in world coordinates. It includes the rotation of the
+    /// chassis, steering and spinning of the wheel around its axle.
+    pub rotation: UnitQuaternion<f32>,
+    /// The current length of the suspension.
+    pub suspension_length: f32,
+    /// `true` if the wheel touches the ground.
+    pub in_contact: bool,
+    /// The point, where the wheel touches the ground, in world coordinates. Valid only if
+    /// [`Self::in_contact`] is `true`.
+    pub contact_point: Vector3<f32>,
+    /// The normal of the ground at the contact point, in world coordinates. Valid only if
+    /// [`Self::in_contact`] is `true`.
+    pub contact_normal: Vector3<f32>,
+    /// A handle of the collider, that the wheel touches.
+    pub ground: Handle<Node>,
+}
+
+/// Vehicle is a ray cast based vehicle controller. It drives a dynamic rigid body (chassis)
+/// using a set of [`Wheel`]s. Every physics step each wheel casts a ray against the physics world
+/// to find the ground and applies suspension, friction, engine and brake forces to the chassis.
+/// Such vehicles are much more stable at high speeds than the ones made of joints, because the
+/// wheels are not simulated as separate rigid bodies.
+///
+/// The forward direction of the vehicle is the local Z axis of the chassis and the up direction
+/// is the local Y axis. Actual positions and orientations of the wheels could be fetched using
+/// [`Self::wheel_states`] after the physics step, which is useful to place visual meshes of the
+/// wheels.
+///
+/// ## Example
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::{algebra::Vector3, pool::Handle},
+/// #     scene::{
+/// #         base::BaseBuilder,
+/// #         graph::Graph,
+/// #         node::Node,
+/// #         rigidbody::RigidBody,
+/// #         vehicle::{VehicleBuilder, Wheel},
+/// #     },
+/// # };
+/// fn create_car(graph: &mut Graph, chassis: Handle<RigidBody>) -> Handle<Node> {
+///     let wheels = [(-1.0, 1.5), (1.0, 1.5), (-1.0, -1.5), (1.0, -1.5)]
+///         .into_iter()
+///         .map(|(x, z)| Wheel {
+///             connection_point: Vector3::new(x, 0.0, z),
+///             ..Default::default()
+///         })
+///         .collect();
+///
+///     VehicleBuilder::new(BaseBuilder::new())
+///         .with_chassis(chassis)
+///         .with_wheels(wheels)
+///         .build(graph)
+/// }
+/// ```
+#[derive(Visit, Reflect, Debug, ComponentProvider)]
+#[reflect(derived_type = "Node")]
+pub struct Vehicle {
+    base: Base,
+
+    #[reflect(setter = "set_chassis")]
+    pub(crate) chassis: InheritableVariable<Handle<RigidBody>>,
+
+    #[reflect(setter = "set_wheels")]
+    pub(crate) wheels: InheritableVariable<Vec<Wheel>>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pub(crate) wheel_states: Vec<WheelState>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pub(crate) speed: f32,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pub(crate) native: Cell<Handle<NativeVehicle>>,
+}
+
+impl Default for Vehicle {
+    fn default() -> Self {
+        Self {
+            base: Default::default(),
+            chassis: Default::default(),
+            wheels: Default::default(),
+            wheel_states: Default::default(),
+            speed: 0.0,
+            native: Cell::new(Handle::NONE),
+        }
+    }
+}
+
+impl Deref for Vehicle {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for Vehicle {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl Clone for Vehicle {
+    fn clone(&self) -> Self {
+        Self {
+            base: self.base.clone(),
+            chassis: self.chassis.clone(),
+            wheels: self.wheels.clone(),
+            wheel_states: self.wheel_states.clone(),
+            speed: self.speed,
+            // Do not copy. The copy will have its own native representation.
+            native: Cell::new(Handle::NONE),
+        }
+    }
+}
+
+impl TypeUuidProvider for Vehicle {
+    fn type_uuid() -> Uuid {
+        uuid!("8f0a4f4e-9d7b-4a52-b6a3-2e5c1d9f7b08")
+    }
+}
+
+impl Vehicle {
+    /// Sets the chassis of the vehicle. The handle should point to a dynamic rigid body node,
+    /// otherwise the vehicle will have no effect!
+    pub fn set_chassis(&mut self, handle: Handle<RigidBody>) -> Handle<RigidBody> {
+        self.chassis.set_value_and_mark_modified(handle)
+    }
+
+    /// Returns current chassis of the vehicle.
+    pub fn chassis(&self) -> Handle<RigidBody> {
+        *self.chassis
+    }
+
+    /// Sets new wheels of the vehicle.
+    pub fn set_wheels(&mut self, wheels: Vec<Wheel>) -> Vec<Wheel> {
+        self.wheels.set_value_and_mark_modified(wheels)
+    }
+
+    /// Returns a shared reference to the wheels of the vehicle.
+    pub fn wheels(&self) -> &[Wheel] {
+        &self.wheels
+    }
+
+    /// Returns a mutable reference to the wheels of the vehicle. Use it to control the vehicle
+    /// by changing [`Wheel::steering`], [`Wheel::engine_force`] and [`Wheel::brake`].
+    pub fn wheels_mut(&mut self) -> &mut [Wheel] {
+        self.wheels.get_value_mut_and_mark_modified()
+    }
+
+    /// Returns the actual state of the wheels after the last physics step. The states are in
+    /// the same order as the wheels. It is empty, if the vehicle wasn't yet simulated.
+    pub fn wheel_states(&self) -> &[WheelState] {
+        &self.wheel_states
+    }
+
+    /// Returns the current forward speed of the vehicle (in m/s). It is negative, if the vehicle
+    /// moves backward.
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+}
+
+impl ConstructorProvider<Node, Graph> for Vehicle {
+    fn constructor() -> NodeConstructor {
+        NodeConstructor::new::<Self>()
+            .with_variant("Vehicle", |_| {
+                VehicleBuilder::new(BaseBuilder::new().with_name("Vehicle"))
+                    .build_node()
+                    .into()
+            })
+            .with_group("Physics")
+    }
+}
+
+impl NodeTrait for Vehicle {
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.base.local_bounding_box()
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.base.world_bounding_box()
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+
+    fn on_removed_from_graph(&mut self, graph: &mut Graph) {
+        graph.physics.remove_vehicle(self.native.get());
+        self.native.set(Handle::NONE);
+
+        Log::info(format!(
+            "Native vehicle was removed for node: {}",
+            self.name()
+        ));
+    }
+
+    fn sync_native(&self, self_handle: Handle<Node>, context: &mut SyncContext) {
+        context
+            .physics
+            .sync_to_vehicle_node(context.nodes, self_handle, self);
+    }
+
+    fn update(&mut self, context: &mut UpdateContext) {
+        context.physics.sync_vehicle_node(self);
+    }
+
+    fn validate(&self, scene: &Scene) -> Result<(), String> {
+        if scene.graph.typed_ref(self.chassis()).is_none() {
+            return Err(
+                "Vehicle has invalid or unassigned handle to a chassis rigid body, \
+            the vehicle will not operate!"
+                    .to_string(),
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Vehicle builder allows you to build Vehicle node in a declarative manner.
+pub struct VehicleBuilder {
+    base_builder: BaseBuilder,
+    chassis: Handle<RigidBody>,
+    wheels: Vec<Wheel>,
+}
+
+impl VehicleBuilder {
+    /// Creates a new vehicle builder instance.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            chassis: Default::default(),
+            wheels: Default::default(),
+        }
+    }
+
+    /// Sets desired chassis of the vehicle. This handle should be a handle to a dynamic rigid
+    /// body node, otherwise the vehicle will have no effect!
+    pub fn with_chassis(mut self, chassis: Handle<RigidBody>) -> Self {
+        self.chassis = chassis;
+        self
+    }
+
+    /// Sets desired wheels of the vehicle.
+    pub fn with_wheels(mut self, wheels: Vec<Wheel>) -> Self {
+        self.wheels = wheels;
+        self
+    }
+
+    /// Creates new Vehicle node, but does not add it to the graph.
+    pub fn build_vehicle(self) -> Vehicle {
+        Vehicle {
+            base: self.base_builder.build_base(),
+            chassis: self.chassis.into(),
+            wheels: self.wheels.into(),
+            wheel_states: Default::default(),
+            speed: 0.0,
+            native: Cell::new(Handle::NONE),
+        }
+    }
+
+    /// Creates new Vehicle node, but does not add it to the graph.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_vehicle())
+    }
+
+    /// Creates new Vehicle node and adds it to the graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Node> {
+        graph.add_node(self.build_node())
+    }
+}
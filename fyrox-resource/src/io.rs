@@ -21,7 +21,14 @@
 //! Provides an interface for IO operations that a resource loader will use, this facilliates
 //! things such as loading assets within archive files
 
+mod archive;
+mod layered;
+
+pub use archive::{
+    ArchiveCompression, ArchiveEntry, ArchiveIndex, ArchivePacker, ArchiveResourceIo,
+};
 use fyrox_core::io::FileError;
+pub use layered::{LayeredResourceIo, ResourceIoLayer};
 use std::fs::File;
 use std::future::{ready, Future};
 use std::io::{BufReader, Write};
@@ -146,6 +153,14 @@ pub trait ResourceIo: Send + Sync + 'static {
 
     /// Used to check whether a path is a dir
     fn is_dir<'a>(&'a self, path: &'a Path) -> ResourceIoFuture<'a, bool>;
+
+    /// Returns a name of the source (for example, a layer of [`LayeredResourceIo`]) from which the
+    /// file at the given path was loaded the last time.
+    ///
+    /// Default implementation returns `None`, which means that the resource IO has a single source.
+    fn file_source(&self, #[allow(unused)] path: &Path) -> Option<String> {
+        None
+    }
 }
 
 /// Standard resource IO provider that uses the file system to
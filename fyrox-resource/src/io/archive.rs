@@ -0,0 +1,702 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Packed archive resource IO. See [`ArchiveResourceIo`] and [`ArchivePacker`] docs for more info.
+
+use crate::{
+    core::{
+        append_extension,
+        byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt},
+        io::FileError,
+        parking_lot::Mutex,
+    },
+    io::{FileReader, ResourceIo, ResourceIoFuture},
+    metadata::ResourceMetadata,
+    registry::{RegistryContainer, ResourceRegistry},
+};
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::{Debug, Formatter},
+    fs::File,
+    future::ready,
+    io::{BufReader, BufWriter, Cursor, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+const MAGIC: [u8; 8] = *b"FYRXPACK";
+const VERSION: u32 = 1;
+
+/// Compression method of a single archive entry.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ArchiveCompression {
+    /// The entry is stored as is.
+    #[default]
+    None,
+    /// The entry is compressed using Deflate algorithm.
+    Deflate,
+}
+
+impl ArchiveCompression {
+    fn id(self) -> u8 {
+        match self {
+            ArchiveCompression::None => 0,
+            ArchiveCompression::Deflate => 1,
+        }
+    }
+
+    fn from_id(id: u8) -> Result<Self, FileError> {
+        match id {
+            0 => Ok(ArchiveCompression::None),
+            1 => Ok(ArchiveCompression::Deflate),
+            _ => Err(FileError::Custom(format!(
+                "Unknown archive compression method {id}!"
+            ))),
+        }
+    }
+}
+
+/// A record in the archive index, that describes where the data of a file is located in the
+/// archive and how it is stored.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArchiveEntry {
+    /// Offset (in bytes) of the entry data from the beginning of the archive.
+    pub offset: u64,
+    /// Size (in bytes) of the entry data in the archive.
+    pub stored_size: u64,
+    /// Size (in bytes) of the entry data after decompression.
+    pub size: u64,
+    /// Compression method of the entry data.
+    pub compression: ArchiveCompression,
+}
+
+/// Index of an archive, that contains a map `Path -> Entry` for every file in the archive as well as
+/// a set of all directories. All paths are normalized using [`ResourceRegistry::normalize_path`].
+#[derive(Clone, Debug, Default)]
+pub struct ArchiveIndex {
+    files: BTreeMap<PathBuf, ArchiveEntry>,
+    directories: BTreeSet<PathBuf>,
+}
+
+impl ArchiveIndex {
+    fn insert(&mut self, path: PathBuf, entry: ArchiveEntry) {
+        let mut parent = path.parent();
+        while let Some(dir) = parent {
+            if !self.directories.insert(dir.to_path_buf()) {
+                break;
+            }
+            parent = dir.parent();
+        }
+        self.files.insert(path, entry);
+    }
+
+    /// Tries to find an entry for the given (normalized) path.
+    pub fn entry(&self, path: &Path) -> Option<&ArchiveEntry> {
+        self.files.get(path)
+    }
+
+    /// Returns an iterator over every file in the archive.
+    pub fn files(&self) -> impl Iterator<Item = (&Path, &ArchiveEntry)> {
+        self.files
+            .iter()
+            .map(|(path, entry)| (path.as_path(), entry))
+    }
+
+    /// Returns `true` if the given (normalized) path is a file in the archive.
+    pub fn is_file(&self, path: &Path) -> bool {
+        self.files.contains_key(path)
+    }
+
+    /// Returns `true` if the given (normalized) path is a directory in the archive. An empty path
+    /// is the root directory of the archive.
+    pub fn is_dir(&self, path: &Path) -> bool {
+        path.as_os_str().is_empty() || self.directories.contains(path)
+    }
+
+    /// Returns the total amount of files in the archive.
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Returns `true` if the archive has no files, `false` - otherwise.
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    fn read(reader: &mut dyn FileReader) -> Result<Self, FileError> {
+        let mut magic = [0; 8];
+        reader.read_exact(&mut magic)?;
+        if magic != MAGIC {
+            return Err(FileError::Custom(
+                "The file is not a resource archive!".to_string(),
+            ));
+        }
+
+        let version = reader.read_u32::<LittleEndian>()?;
+        if version != VERSION {
+            return Err(FileError::Custom(format!(
+                "Unsupported resource archive version {version}! Expected {VERSION}."
+            )));
+        }
+
+        let index_offset = reader.read_u64::<LittleEndian>()?;
+        reader.seek(SeekFrom::Start(index_offset))?;
+
+        let mut index = Self::default();
+        let count = reader.read_u64::<LittleEndian>()?;
+        for _ in 0..count {
+            let path_len = reader.read_u32::<LittleEndian>()?;
+            let mut path = vec![0; path_len as usize];
+            reader.read_exact(&mut path)?;
+            let path = String::from_utf8(path).map_err(|err| {
+                FileError::Custom(format!(
+                    "Invalid path in the resource archive! Reason: {err}"
+                ))
+            })?;
+            let entry = ArchiveEntry {
+                offset: reader.read_u64::<LittleEndian>()?,
+                stored_size: reader.read_u64::<LittleEndian>()?,
+                size: reader.read_u64::<LittleEndian>()?,
+                compression: ArchiveCompression::from_id(reader.read_u8()?)?,
+            };
+            index.insert(PathBuf::from(path), entry);
+        }
+        Ok(index)
+    }
+
+    fn write(&self, writer: &mut dyn Write) -> Result<(), FileError> {
+        writer.write_u64::<LittleEndian>(self.files.len() as u64)?;
+        for (path, entry) in self.files.iter() {
+            let path = path.to_string_lossy();
+            writer.write_u32::<LittleEndian>(path.len() as u32)?;
+            writer.write_all(path.as_bytes())?;
+            writer.write_u64::<LittleEndian>(entry.offset)?;
+            writer.write_u64::<LittleEndian>(entry.stored_size)?;
+            writer.write_u64::<LittleEndian>(entry.size)?;
+            writer.write_u8(entry.compression.id())?;
+        }
+        Ok(())
+    }
+}
+
+/// Resource IO that serves every file from a single indexed archive file, created by
+/// [`ArchivePacker`]. The archive is read-only, so every write operation will fail.
+///
+/// Paths in the archive are relative to the working directory of the game (for example
+/// `data/textures/grass.png`), which means that resource paths stay the same regardless of whether
+/// the game uses [`super::FsResourceIo`] or an archive.
+///
+/// ```rust,no_run
+/// # use std::sync::Arc;
+/// # use fyrox_resource::{io::ArchiveResourceIo, manager::ResourceManager};
+/// let io = ArchiveResourceIo::open(ArchiveResourceIo::DEFAULT_PATH).unwrap();
+/// let resource_manager = ResourceManager::new(Arc::new(io), Default::default());
+/// ```
+pub struct ArchiveResourceIo {
+    index: ArchiveIndex,
+    reader: Mutex<Box<dyn FileReader>>,
+    working_dir: Option<PathBuf>,
+}
+
+impl Debug for ArchiveResourceIo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ArchiveResourceIo")
+            .field("index", &self.index)
+            .finish()
+    }
+}
+
+impl ArchiveResourceIo {
+    /// Default path of the archive with game assets.
+    pub const DEFAULT_PATH: &'static str = "./data.pak";
+
+    /// Creates new archive resource IO from the given reader. The reader must be positioned at the
+    /// beginning of the archive.
+    pub fn new(mut reader: Box<dyn FileReader>) -> Result<Self, FileError> {
+        let index = ArchiveIndex::read(&mut *reader)?;
+        Ok(Self {
+            index,
+            reader: Mutex::new(reader),
+            working_dir: std::env::current_dir()
+                .ok()
+                .map(ResourceRegistry::normalize_path),
+        })
+    }
+
+    /// Opens an archive at the given path in the file system.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, FileError> {
+        Self::new(Box::new(BufReader::new(File::open(path)?)))
+    }
+
+    /// Creates new archive resource IO from the archive bytes stored in memory.
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Self, FileError> {
+        Self::new(Box::new(Cursor::new(bytes)))
+    }
+
+    /// Returns a reference to the index of the archive.
+    pub fn index(&self) -> &ArchiveIndex {
+        &self.index
+    }
+
+    fn archive_path(&self, path: &Path) -> PathBuf {
+        let path = ResourceRegistry::normalize_path(path);
+        if path.has_root() {
+            if let Some(relative) = self
+                .working_dir
+                .as_ref()
+                .and_then(|working_dir| path.strip_prefix(working_dir).ok())
+            {
+                return relative.to_path_buf();
+            }
+        }
+        path
+    }
+
+    fn read_entry(&self, path: &Path) -> Result<Vec<u8>, FileError> {
+        let path = self.archive_path(path);
+        let entry = self.index.entry(&path).ok_or_else(|| {
+            FileError::Custom(format!(
+                "There's no file {} in the resource archive!",
+                path.display()
+            ))
+        })?;
+
+        let mut stored = vec![0; entry.stored_size as usize];
+        {
+            let mut reader = self.reader.lock();
+            reader.seek(SeekFrom::Start(entry.offset))?;
+            reader.read_exact(&mut stored)?;
+        }
+
+        match entry.compression {
+            ArchiveCompression::None => Ok(stored),
+            ArchiveCompression::Deflate => {
+                let mut data = Vec::with_capacity(entry.size as usize);
+                DeflateDecoder::new(stored.as_slice()).read_to_end(&mut data)?;
+                Ok(data)
+            }
+        }
+    }
+
+    fn children(&self, dir: &Path, recursive: bool) -> Vec<PathBuf> {
+        let is_child = |path: &Path| {
+            if recursive {
+                path != dir && path.starts_with(dir)
+            } else {
+                path.parent() == Some(dir)
+            }
+        };
+        self.index
+            .directories
+            .iter()
+            .chain(self.index.files.keys())
+            .filter(|path| is_child(path))
+            .cloned()
+            .collect()
+    }
+
+    fn read_only_error(path: &Path) -> FileError {
+        FileError::Custom(format!(
+            "Unable to modify {}, because resource archive is read-only!",
+            path.display()
+        ))
+    }
+}
+
+impl ResourceIo for ArchiveResourceIo {
+    fn load_file<'a>(&'a self, path: &'a Path) -> ResourceIoFuture<'a, Result<Vec<u8>, FileError>> {
+        Box::pin(ready(self.read_entry(path)))
+    }
+
+    fn write_file<'a>(
+        &'a self,
+        path: &'a Path,
+        _data: Vec<u8>,
+    ) -> ResourceIoFuture<'a, Result<(), FileError>> {
+        Box::pin(ready(Err(Self::read_only_error(path))))
+    }
+
+    fn write_file_sync(&self, path: &Path, _data: &[u8]) -> Result<(), FileError> {
+        Err(Self::read_only_error(path))
+    }
+
+    fn move_file<'a>(
+        &'a self,
+        source: &'a Path,
+        _dest: &'a Path,
+    ) -> ResourceIoFuture<'a, Result<(), FileError>> {
+        Box::pin(ready(Err(Self::read_only_error(source))))
+    }
+
+    fn canonicalize_path<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> ResourceIoFuture<'a, Result<PathBuf, FileError>> {
+        let archive_path = self.archive_path(path);
+        let result = match self.working_dir.as_ref() {
+            Some(working_dir) if !archive_path.has_root() => Ok(working_dir.join(archive_path)),
+            _ => Ok(archive_path),
+        };
+        Box::pin(ready(result))
+    }
+
+    fn read_directory<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> ResourceIoFuture<'a, Result<Box<dyn Iterator<Item = PathBuf> + Send>, FileError>> {
+        let dir = self.archive_path(path);
+        let result = if self.index.is_dir(&dir) {
+            let iter: Box<dyn Iterator<Item = PathBuf> + Send> =
+                Box::new(self.children(&dir, false).into_iter());
+            Ok(iter)
+        } else {
+            Err(FileError::Custom(format!(
+                "There's no directory {} in the resource archive!",
+                dir.display()
+            )))
+        };
+        Box::pin(ready(result))
+    }
+
+    fn walk_directory<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> ResourceIoFuture<'a, Result<Box<dyn Iterator<Item = PathBuf> + Send>, FileError>> {
+        let dir = self.archive_path(path);
+        let result = if self.index.is_dir(&dir) {
+            // Mimic `walkdir` behaviour, which yields the root directory as well.
+            let mut paths = vec![dir.clone()];
+            paths.extend(self.children(&dir, true));
+            let iter: Box<dyn Iterator<Item = PathBuf> + Send> = Box::new(paths.into_iter());
+            Ok(iter)
+        } else {
+            Err(FileError::Custom(format!(
+                "There's no directory {} in the resource archive!",
+                dir.display()
+            )))
+        };
+        Box::pin(ready(result))
+    }
+
+    fn exists<'a>(&'a self, path: &'a Path) -> ResourceIoFuture<'a, bool> {
+        let path = self.archive_path(path);
+        Box::pin(ready(self.index.is_file(&path) || self.index.is_dir(&path)))
+    }
+
+    fn is_file<'a>(&'a self, path: &'a Path) -> ResourceIoFuture<'a, bool> {
+        Box::pin(ready(self.index.is_file(&self.archive_path(path))))
+    }
+
+    fn is_dir<'a>(&'a self, path: &'a Path) -> ResourceIoFuture<'a, bool> {
+        Box::pin(ready(self.index.is_dir(&self.archive_path(path))))
+    }
+}
+
+enum PackerSource {
+    File(PathBuf),
+    Memory(Vec<u8>),
+}
+
+/// Packer creates resource archives, that can be read by [`ArchiveResourceIo`]. It collects a set
+/// of files (from the file system or from memory) and then writes them into a single file with an
+/// index at the end.
+///
+/// Every entry is compressed using the compression method of the packer, unless compressed data is
+/// larger than the source data (which is typical for already compressed formats such as `png` or
+/// `ogg`). In this case the entry is stored as is.
+///
+/// ```rust,no_run
+/// # use fyrox_resource::{
+/// #     io::{ArchiveCompression, ArchivePacker, ArchiveResourceIo},
+/// #     registry::ResourceRegistry,
+/// # };
+/// let mut packer = ArchivePacker::new().with_compression(ArchiveCompression::Deflate);
+/// packer.add_folder("./data/", &|path| path.extension().is_none_or(|ext| ext != "log")).unwrap();
+/// packer.add_registry(ResourceRegistry::DEFAULT_PATH).unwrap();
+/// packer.write_to_file(ArchiveResourceIo::DEFAULT_PATH).unwrap();
+/// ```
+#[derive(Default)]
+pub struct ArchivePacker {
+    files: BTreeMap<PathBuf, PackerSource>,
+    compression: ArchiveCompression,
+}
+
+impl ArchivePacker {
+    /// Creates new empty packer without compression.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the desired compression method of the archive entries.
+    pub fn with_compression(mut self, compression: ArchiveCompression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Adds a file from the file system. The file will be stored in the archive at the normalized
+    /// `path`. The file is read only when the archive is written.
+    pub fn add_file(&mut self, path: impl AsRef<Path>) {
+        let path = path.as_ref();
+        self.files.insert(
+            ResourceRegistry::normalize_path(path),
+            PackerSource::File(path.to_path_buf()),
+        );
+    }
+
+    /// Adds the given data to the archive and stores it at the normalized `path`.
+    pub fn add_data(&mut self, path: impl AsRef<Path>, data: Vec<u8>) {
+        self.files.insert(
+            ResourceRegistry::normalize_path(path),
+            PackerSource::Memory(data),
+        );
+    }
+
+    /// Recursively adds every file from the given folder, that passes the given filter. The filter
+    /// is applied to files only, subfolders are always visited. The path of the folder should be
+    /// relative to the working directory of the game. Returns the amount of added files.
+    pub fn add_folder<F>(
+        &mut self,
+        folder: impl AsRef<Path>,
+        filter: &F,
+    ) -> Result<usize, FileError>
+    where
+        F: Fn(&Path) -> bool,
+    {
+        let mut count = 0;
+        for entry in std::fs::read_dir(folder)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                count += self.add_folder(&path, filter)?;
+            } else if filter(&path) {
+                self.add_file(&path);
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    /// Adds the resource registry at the given path and every resource registered in it (along with
+    /// its metadata) to the archive.
+    pub fn add_registry(&mut self, registry_path: impl AsRef<Path>) -> Result<(), FileError> {
+        let registry_path = registry_path.as_ref();
+        let registry = ron::de::from_bytes::<RegistryContainer>(&std::fs::read(registry_path)?)
+            .map_err(|err| {
+                FileError::Custom(format!(
+                    "Unable to deserialize the resource registry. Reason: {err:?}"
+                ))
+            })?;
+        self.add_file(registry_path);
+        for path in registry.values() {
+            self.add_file(path);
+            let metadata_path = append_extension(path, ResourceMetadata::EXTENSION);
+            if metadata_path.exists() {
+                self.add_file(metadata_path);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the amount of files added to the packer.
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Returns `true` if no files were added to the packer, `false` - otherwise.
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    fn compress(&self, data: Vec<u8>) -> Result<(Vec<u8>, ArchiveCompression), FileError> {
+        match self.compression {
+            ArchiveCompression::None => Ok((data, ArchiveCompression::None)),
+            ArchiveCompression::Deflate => {
+                let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(&data)?;
+                let compressed = encoder.finish()?;
+                if compressed.len() < data.len() {
+                    Ok((compressed, ArchiveCompression::Deflate))
+                } else {
+                    Ok((data, ArchiveCompression::None))
+                }
+            }
+        }
+    }
+
+    /// Writes the archive to the given writer and returns its index.
+    pub fn write<W>(self, writer: &mut W) -> Result<ArchiveIndex, FileError>
+    where
+        W: Write + Seek,
+    {
+        let start = writer.stream_position()?;
+        writer.write_all(&MAGIC)?;
+        writer.write_u32::<LittleEndian>(VERSION)?;
+        let index_offset_position = writer.stream_position()?;
+        // Index offset is unknown at this point, it will be written later.
+        writer.write_u64::<LittleEndian>(0)?;
+
+        let mut index = ArchiveIndex::default();
+        for (path, source) in self.files.iter() {
+            let data = match source {
+                PackerSource::File(fs_path) => std::fs::read(fs_path)?,
+                PackerSource::Memory(data) => data.clone(),
+            };
+            let size = data.len() as u64;
+            let (stored, compression) = self.compress(data)?;
+            let offset = writer.stream_position()? - start;
+            writer.write_all(&stored)?;
+            index.insert(
+                path.clone(),
+                ArchiveEntry {
+                    offset,
+                    stored_size: stored.len() as u64,
+                    size,
+                    compression,
+                },
+            );
+        }
+
+        let index_offset = writer.stream_position()? - start;
+        index.write(writer)?;
+        let end = writer.stream_position()?;
+        writer.seek(SeekFrom::Start(index_offset_position))?;
+        writer.write_u64::<LittleEndian>(index_offset)?;
+        writer.seek(SeekFrom::Start(end))?;
+        writer.flush()?;
+
+        Ok(index)
+    }
+
+    /// Writes the archive to a file at the given path.
+    pub fn write_to_file(self, path: impl AsRef<Path>) -> Result<ArchiveIndex, FileError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write(&mut writer)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::futures::executor::block_on;
+
+    fn make_archive(compression: ArchiveCompression) -> ArchiveResourceIo {
+        let mut packer = ArchivePacker::new().with_compression(compression);
+        packer.add_data("./data/textures/grass.png", vec![1, 2, 3]);
+        packer.add_data("data/scene.rgs", vec![7; 1024]);
+        packer.add_data("data\\sounds\\step.ogg", vec![4, 5]);
+        let mut cursor = Cursor::new(Vec::new());
+        packer.write(&mut cursor).unwrap();
+        ArchiveResourceIo::from_bytes(cursor.into_inner()).unwrap()
+    }
+
+    #[test]
+    fn test_load_file() {
+        for compression in [ArchiveCompression::None, ArchiveCompression::Deflate] {
+            let io = make_archive(compression);
+            assert_eq!(
+                block_on(io.load_file(Path::new("data/textures/grass.png"))).unwrap(),
+                vec![1, 2, 3]
+            );
+            assert_eq!(
+                block_on(io.load_file(Path::new("./data/scene.rgs"))).unwrap(),
+                vec![7; 1024]
+            );
+            assert!(block_on(io.load_file(Path::new("data/foo.bar"))).is_err());
+        }
+    }
+
+    #[test]
+    fn test_per_entry_compression() {
+        let io = make_archive(ArchiveCompression::Deflate);
+        let index = io.index();
+        assert_eq!(
+            index
+                .entry(Path::new("data/scene.rgs"))
+                .unwrap()
+                .compression,
+            ArchiveCompression::Deflate
+        );
+        // Too small to benefit from compression.
+        assert_eq!(
+            index
+                .entry(Path::new("data/textures/grass.png"))
+                .unwrap()
+                .compression,
+            ArchiveCompression::None
+        );
+    }
+
+    #[test]
+    fn test_directories() {
+        let io = make_archive(ArchiveCompression::None);
+        assert!(block_on(io.is_dir(Path::new("./data"))));
+        assert!(block_on(io.is_dir(Path::new("data/sounds"))));
+        assert!(block_on(io.is_file(Path::new("data/sounds/step.ogg"))));
+        assert!(block_on(io.exists(Path::new("data/textures"))));
+        assert!(!block_on(io.exists(Path::new("data/models"))));
+
+        let mut children = block_on(io.read_directory(Path::new("data")))
+            .unwrap()
+            .collect::<Vec<_>>();
+        children.sort();
+        assert_eq!(
+            children,
+            vec![
+                PathBuf::from("data/scene.rgs"),
+                PathBuf::from("data/sounds"),
+                PathBuf::from("data/textures"),
+            ]
+        );
+
+        let walked = block_on(io.walk_directory(Path::new("."))).unwrap().count();
+        // Root + 3 directories + 3 files.
+        assert_eq!(walked, 7);
+    }
+
+    #[test]
+    fn test_add_folder_filter() {
+        let root = std::env::temp_dir().join("fyrox_archive_add_folder");
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("textures/terrain")).unwrap();
+        std::fs::write(root.join("scene.rgs"), [1]).unwrap();
+        std::fs::write(root.join("log.txt"), [2]).unwrap();
+        std::fs::write(root.join("textures/terrain/grass.png"), [3]).unwrap();
+
+        // The filter must not prevent the packer from visiting subfolders.
+        let mut packer = ArchivePacker::new();
+        let count = packer
+            .add_folder(&root, &|path| {
+                path.extension()
+                    .is_some_and(|ext| ext == "rgs" || ext == "png")
+            })
+            .unwrap();
+        assert_eq!(count, 2);
+        assert_eq!(packer.len(), 2);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_read_only() {
+        let io = make_archive(ArchiveCompression::None);
+        assert!(io
+            .write_file_sync(Path::new("data/scene.rgs"), &[1, 2, 3])
+            .is_err());
+    }
+}
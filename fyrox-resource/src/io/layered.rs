@@ -0,0 +1,489 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Layered resource IO. See [`LayeredResourceIo`] docs for more info.
+
+use crate::{
+    core::{futures::executor::block_on, io::FileError, parking_lot::Mutex},
+    io::{FileReader, ResourceIo, ResourceIoFuture},
+    registry::ResourceRegistry,
+};
+use fxhash::FxHashMap;
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// A single layer of [`LayeredResourceIo`]. It is a resource IO with a name and an optional root
+/// folder. The root folder is prepended to every relative path before passing it to the resource
+/// IO of the layer, which allows to use a folder (for example `mods/my_mod`) as an overlay of
+/// the working directory.
+pub struct ResourceIoLayer {
+    name: String,
+    root: PathBuf,
+    io: Arc<dyn ResourceIo>,
+}
+
+impl ResourceIoLayer {
+    /// Creates new layer with the given name and resource IO.
+    pub fn new(name: impl Into<String>, io: Arc<dyn ResourceIo>) -> Self {
+        Self {
+            name: name.into(),
+            root: Default::default(),
+            io,
+        }
+    }
+
+    /// Sets the root folder of the layer.
+    pub fn with_root(mut self, root: impl AsRef<Path>) -> Self {
+        self.root = root.as_ref().to_path_buf();
+        self
+    }
+
+    /// Returns the name of the layer.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the root folder of the layer.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Returns the resource IO of the layer.
+    pub fn io(&self) -> &Arc<dyn ResourceIo> {
+        &self.io
+    }
+
+    async fn full_path(&self, path: &Path) -> PathBuf {
+        if self.root.as_os_str().is_empty() || path.starts_with(&self.root) {
+            return path.to_path_buf();
+        }
+        if !path.is_absolute() {
+            return self.root.join(path);
+        }
+        // Canonical paths of the files of the layer are made to look like they are in the working
+        // directory (see `canonicalize_path`), so they must be moved back into the root folder.
+        match self.io.canonicalize_path(Path::new(".")).await {
+            Ok(working_dir) => match path.strip_prefix(&working_dir) {
+                Ok(relative) => self.root.join(relative),
+                Err(_) => path.to_path_buf(),
+            },
+            Err(_) => path.to_path_buf(),
+        }
+    }
+}
+
+/// Maps a path returned by a layer when iterating over the `full_dir` back to the form of the
+/// `requested_dir`, so the same files from different layers will have the same paths.
+fn remap_path(requested_dir: &Path, full_dir: &Path, path: PathBuf) -> PathBuf {
+    let suffix = path
+        .strip_prefix(full_dir)
+        .map(|suffix| suffix.to_path_buf());
+    let suffix = suffix.or_else(|_| {
+        ResourceRegistry::normalize_path(&path)
+            .strip_prefix(ResourceRegistry::normalize_path(full_dir))
+            .map(|suffix| suffix.to_path_buf())
+    });
+    match suffix {
+        Ok(suffix) if suffix.as_os_str().is_empty() => requested_dir.to_path_buf(),
+        Ok(suffix) => requested_dir.join(suffix),
+        Err(_) => path,
+    }
+}
+
+fn not_found(path: &Path) -> FileError {
+    FileError::Io(std::io::Error::new(
+        std::io::ErrorKind::NotFound,
+        format!(
+            "{} does not exist in any resource IO layer!",
+            path.display()
+        ),
+    ))
+}
+
+/// Resource IO that stacks multiple resource IOs (layers) with priority order. It could be used to
+/// override base assets of a game by mods, patches or DLCs without touching the original files.
+///
+/// Every lookup is resolved to the layer with the highest priority, that has the requested path.
+/// Layers added last have the highest priority. [`ResourceIo::read_directory`] and
+/// [`ResourceIo::walk_directory`] return the merged view of all the layers. Every write operation
+/// is performed on the layer with the highest priority.
+///
+/// Use [`crate::manager::ResourceManager::resource_layer`] to find out from which layer a resource
+/// was loaded.
+///
+/// ```rust,no_run
+/// # use std::sync::Arc;
+/// # use fyrox_resource::{
+/// #     io::{ArchiveResourceIo, FsResourceIo, LayeredResourceIo, ResourceIoLayer},
+/// #     manager::ResourceManager,
+/// # };
+/// let io = LayeredResourceIo::new()
+///     .with_layer(ResourceIoLayer::new("Base", Arc::new(FsResourceIo)))
+///     .with_layer(ResourceIoLayer::new(
+///         "Patch",
+///         Arc::new(ArchiveResourceIo::open("patch.pak").unwrap()),
+///     ))
+///     .with_layer(ResourceIoLayer::new("MyMod", Arc::new(FsResourceIo)).with_root("mods/my_mod"));
+/// let resource_manager = ResourceManager::new(Arc::new(io), Default::default());
+/// ```
+#[derive(Default)]
+pub struct LayeredResourceIo {
+    layers: Vec<ResourceIoLayer>,
+    sources: Mutex<FxHashMap<PathBuf, usize>>,
+}
+
+impl LayeredResourceIo {
+    /// Creates new resource IO without any layers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a new layer on top of the existing ones, which makes it the highest priority layer.
+    pub fn with_layer(mut self, layer: ResourceIoLayer) -> Self {
+        self.add_layer(layer);
+        self
+    }
+
+    /// Adds a new layer on top of the existing ones, which makes it the highest priority layer.
+    pub fn add_layer(&mut self, layer: ResourceIoLayer) {
+        self.layers.push(layer);
+    }
+
+    /// Returns a slice of all the layers, sorted from the lowest priority to the highest.
+    pub fn layers(&self) -> &[ResourceIoLayer] {
+        &self.layers
+    }
+
+    fn layers_by_priority(&self) -> impl Iterator<Item = (usize, &ResourceIoLayer)> {
+        self.layers.iter().enumerate().rev()
+    }
+
+    fn top_layer(&self, path: &Path) -> Result<&ResourceIoLayer, FileError> {
+        self.layers.last().ok_or_else(|| not_found(path))
+    }
+
+    async fn find_layer(&self, path: &Path, files_only: bool) -> Option<(usize, PathBuf)> {
+        for (index, layer) in self.layers_by_priority() {
+            let full_path = layer.full_path(path).await;
+            let found = if files_only {
+                layer.io.is_file(&full_path).await
+            } else {
+                layer.io.exists(&full_path).await
+            };
+            if found {
+                return Some((index, full_path));
+            }
+        }
+        None
+    }
+
+    fn remember_source(&self, path: &Path, layer: usize) {
+        self.sources
+            .lock()
+            .insert(ResourceRegistry::normalize_path(path), layer);
+    }
+
+    async fn merged_directory(
+        &self,
+        path: &Path,
+        recursive: bool,
+    ) -> Result<Box<dyn Iterator<Item = PathBuf> + Send>, FileError> {
+        let mut merged = BTreeSet::new();
+        let mut any_found = false;
+        for layer in self.layers.iter() {
+            let full_path = layer.full_path(path).await;
+            if !layer.io.is_dir(&full_path).await {
+                continue;
+            }
+            let iter = if recursive {
+                layer.io.walk_directory(&full_path).await
+            } else {
+                layer.io.read_directory(&full_path).await
+            };
+            if let Ok(iter) = iter {
+                any_found = true;
+                merged.extend(iter.map(|entry| remap_path(path, &full_path, entry)));
+            }
+        }
+        if any_found {
+            let iter: Box<dyn Iterator<Item = PathBuf> + Send> = Box::new(merged.into_iter());
+            Ok(iter)
+        } else {
+            Err(not_found(path))
+        }
+    }
+}
+
+impl ResourceIo for LayeredResourceIo {
+    fn load_file<'a>(&'a self, path: &'a Path) -> ResourceIoFuture<'a, Result<Vec<u8>, FileError>> {
+        Box::pin(async move {
+            let (index, full_path) = self
+                .find_layer(path, true)
+                .await
+                .ok_or_else(|| not_found(path))?;
+            let data = self.layers[index].io.load_file(&full_path).await?;
+            self.remember_source(path, index);
+            Ok(data)
+        })
+    }
+
+    fn write_file<'a>(
+        &'a self,
+        path: &'a Path,
+        data: Vec<u8>,
+    ) -> ResourceIoFuture<'a, Result<(), FileError>> {
+        Box::pin(async move {
+            let layer = self.top_layer(path)?;
+            layer
+                .io
+                .write_file(&layer.full_path(path).await, data)
+                .await
+        })
+    }
+
+    fn write_file_sync(&self, path: &Path, data: &[u8]) -> Result<(), FileError> {
+        let layer = self.top_layer(path)?;
+        layer
+            .io
+            .write_file_sync(&block_on(layer.full_path(path)), data)
+    }
+
+    fn move_file<'a>(
+        &'a self,
+        source: &'a Path,
+        dest: &'a Path,
+    ) -> ResourceIoFuture<'a, Result<(), FileError>> {
+        Box::pin(async move {
+            let (index, full_source) = self
+                .find_layer(source, false)
+                .await
+                .ok_or_else(|| not_found(source))?;
+            let layer = &self.layers[index];
+            layer
+                .io
+                .move_file(&full_source, &layer.full_path(dest).await)
+                .await
+        })
+    }
+
+    fn canonicalize_path<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> ResourceIoFuture<'a, Result<PathBuf, FileError>> {
+        Box::pin(async move {
+            let (index, full_path) = self
+                .find_layer(path, false)
+                .await
+                .ok_or_else(|| not_found(path))?;
+            let layer = &self.layers[index];
+            let canonical_path = layer.io.canonicalize_path(&full_path).await?;
+            if full_path == path {
+                return Ok(canonical_path);
+            }
+            // The path is in a layer with a root folder, make it look like it is in the working
+            // directory, so every layer will have the same canonical paths.
+            let canonical_root = layer.io.canonicalize_path(&layer.root).await?;
+            let canonical_working_dir = layer.io.canonicalize_path(Path::new(".")).await?;
+            match canonical_path.strip_prefix(&canonical_root) {
+                Ok(relative) => Ok(canonical_working_dir.join(relative)),
+                Err(_) => Ok(canonical_path),
+            }
+        })
+    }
+
+    fn read_directory<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> ResourceIoFuture<'a, Result<Box<dyn Iterator<Item = PathBuf> + Send>, FileError>> {
+        Box::pin(self.merged_directory(path, false))
+    }
+
+    fn walk_directory<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> ResourceIoFuture<'a, Result<Box<dyn Iterator<Item = PathBuf> + Send>, FileError>> {
+        Box::pin(self.merged_directory(path, true))
+    }
+
+    fn file_reader<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> ResourceIoFuture<'a, Result<Box<dyn FileReader>, FileError>> {
+        Box::pin(async move {
+            let (index, full_path) = self
+                .find_layer(path, true)
+                .await
+                .ok_or_else(|| not_found(path))?;
+            let reader = self.layers[index].io.file_reader(&full_path).await?;
+            self.remember_source(path, index);
+            Ok(reader)
+        })
+    }
+
+    fn exists<'a>(&'a self, path: &'a Path) -> ResourceIoFuture<'a, bool> {
+        Box::pin(async move { self.find_layer(path, false).await.is_some() })
+    }
+
+    fn is_file<'a>(&'a self, path: &'a Path) -> ResourceIoFuture<'a, bool> {
+        Box::pin(async move { self.find_layer(path, true).await.is_some() })
+    }
+
+    fn is_dir<'a>(&'a self, path: &'a Path) -> ResourceIoFuture<'a, bool> {
+        Box::pin(async move {
+            for layer in self.layers.iter() {
+                if layer.io.is_dir(&layer.full_path(path).await).await {
+                    return true;
+                }
+            }
+            false
+        })
+    }
+
+    fn file_source(&self, path: &Path) -> Option<String> {
+        self.sources
+            .lock()
+            .get(&ResourceRegistry::normalize_path(path))
+            .and_then(|index| self.layers.get(*index))
+            .map(|layer| layer.name.clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::io::{ArchivePacker, ArchiveResourceIo};
+    use std::io::Cursor;
+
+    fn make_archive(files: &[(&str, &[u8])]) -> Arc<dyn ResourceIo> {
+        let mut packer = ArchivePacker::new();
+        for (path, data) in files {
+            packer.add_data(path, data.to_vec());
+        }
+        let mut cursor = Cursor::new(Vec::new());
+        packer.write(&mut cursor).unwrap();
+        Arc::new(ArchiveResourceIo::from_bytes(cursor.into_inner()).unwrap())
+    }
+
+    fn make_layered_io() -> LayeredResourceIo {
+        LayeredResourceIo::new()
+            .with_layer(ResourceIoLayer::new(
+                "Base",
+                make_archive(&[
+                    ("data/a.txt", b"base a"),
+                    ("data/b.txt", b"base b"),
+                    ("data/sub/c.txt", b"base c"),
+                ]),
+            ))
+            .with_layer(ResourceIoLayer::new(
+                "Mod",
+                make_archive(&[("data/b.txt", b"mod b"), ("data/sub/d.txt", b"mod d")]),
+            ))
+    }
+
+    #[test]
+    fn test_priority() {
+        let io = make_layered_io();
+        assert_eq!(
+            block_on(io.load_file(Path::new("data/a.txt"))).unwrap(),
+            b"base a"
+        );
+        assert_eq!(
+            block_on(io.load_file(Path::new("data/b.txt"))).unwrap(),
+            b"mod b"
+        );
+        assert!(block_on(io.load_file(Path::new("data/e.txt"))).is_err());
+        assert_eq!(io.file_source(Path::new("data/a.txt")).unwrap(), "Base");
+        assert_eq!(io.file_source(Path::new("./data/b.txt")).unwrap(), "Mod");
+        assert!(io.file_source(Path::new("data/sub/c.txt")).is_none());
+    }
+
+    #[test]
+    fn test_merged_directories() {
+        let io = make_layered_io();
+        assert!(block_on(io.is_dir(Path::new("data/sub"))));
+        assert!(block_on(io.is_file(Path::new("data/sub/d.txt"))));
+
+        let entries = block_on(io.read_directory(Path::new("data")))
+            .unwrap()
+            .collect::<Vec<_>>();
+        assert_eq!(
+            entries,
+            vec![
+                PathBuf::from("data/a.txt"),
+                PathBuf::from("data/b.txt"),
+                PathBuf::from("data/sub"),
+            ]
+        );
+
+        let entries = block_on(io.walk_directory(Path::new("data/sub")))
+            .unwrap()
+            .collect::<Vec<_>>();
+        assert_eq!(
+            entries,
+            vec![
+                PathBuf::from("data/sub"),
+                PathBuf::from("data/sub/c.txt"),
+                PathBuf::from("data/sub/d.txt"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_layer_root() {
+        let io = LayeredResourceIo::new()
+            .with_layer(ResourceIoLayer::new(
+                "Base",
+                make_archive(&[("data/a.txt", b"base a")]),
+            ))
+            .with_layer(
+                ResourceIoLayer::new("Mod", make_archive(&[("mods/foo/data/a.txt", b"mod a")]))
+                    .with_root("mods/foo"),
+            );
+        assert_eq!(
+            block_on(io.load_file(Path::new("data/a.txt"))).unwrap(),
+            b"mod a"
+        );
+        let entries = block_on(io.read_directory(Path::new("data")))
+            .unwrap()
+            .collect::<Vec<_>>();
+        assert_eq!(entries, vec![PathBuf::from("data/a.txt")]);
+    }
+
+    #[test]
+    fn test_layer_root_canonical_path() {
+        let io = LayeredResourceIo::new()
+            .with_layer(ResourceIoLayer::new(
+                "Base",
+                make_archive(&[("data/a.txt", b"base a")]),
+            ))
+            .with_layer(
+                ResourceIoLayer::new("Mod", make_archive(&[("mods/foo/data/a.txt", b"mod a")]))
+                    .with_root("mods/foo"),
+            );
+        let canonical_path = block_on(io.canonicalize_path(Path::new("data/a.txt"))).unwrap();
+        assert!(canonical_path.is_absolute());
+        assert!(block_on(io.is_file(&canonical_path)));
+        assert_eq!(block_on(io.load_file(&canonical_path)).unwrap(), b"mod a");
+        assert_eq!(io.file_source(&canonical_path).unwrap(), "Mod");
+    }
+}
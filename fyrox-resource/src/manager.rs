@@ -271,6 +271,12 @@ impl ResourceManager {
         self.state().resource_path(resource)
     }
 
+    /// Tries to fetch a name of the resource IO layer from which the given resource was loaded.
+    /// See [`ResourceManagerState::resource_layer`] docs for more info.
+    pub fn resource_layer(&self, resource: &UntypedResource) -> Option<String> {
+        self.state().resource_layer(resource)
+    }
+
     /// Same as [`Self::request`], but returns untyped resource.
     pub fn request_untyped<P>(&self, path: P) -> UntypedResource
     where
@@ -781,6 +787,17 @@ impl ResourceManagerState {
         }
     }
 
+    /// Tries to fetch a name of the resource IO layer (see [`crate::io::LayeredResourceIo`]) from
+    /// which the given resource was loaded. The name may be missing in a few cases:
+    ///
+    /// 1) The resource path is unknown (see [`Self::resource_path`] for more info).
+    /// 2) The resource IO does not have multiple layers.
+    /// 3) The resource was not loaded from the resource IO (for example, it is embedded or built-in).
+    pub fn resource_layer(&self, resource: &UntypedResource) -> Option<String> {
+        let path = self.resource_path(resource)?;
+        self.resource_io.file_source(&path)
+    }
+
     /// Adds a new resource loader of the given type.
     pub fn add_loader<T: ResourceLoader>(&self, loader: T) -> Option<T> {
         self.loaders.lock().set(loader)
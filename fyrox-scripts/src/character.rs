@@ -0,0 +1,314 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Kinematic character controller script is used to create characters, that walk on the ground, climb stairs and slopes
+//! and jump. See [`KinematicCharacterController`] docs for more info and usage examples.
+//!
+//! The script works with 3D physics only. 2D games could use [`fyrox::scene::dim2::physics::CharacterController`]
+//! directly in their own scripts.
+
+use fyrox::{
+    core::{
+        algebra::Vector3, impl_component_provider, reflect::prelude::*, uuid_provider,
+        variable::InheritableVariable, visitor::prelude::*,
+    },
+    event::{ElementState, Event, WindowEvent},
+    gui::{key::KeyBinding, message::KeyCode},
+    scene::graph::physics::CharacterController,
+    script::{ScriptContext, ScriptTrait},
+    utils,
+};
+
+/// Kinematic character controller script moves a character via keyboard keys using the character controller of the
+/// physics world. To use it, create a rigid body with [`fyrox::scene::rigidbody::RigidBodyType::KinematicPositionBased`]
+/// type, add a collider (capsule is the best choice) as its child and assign the script to the rigid body. The character
+/// moves in the direction the rigid body is facing, so rotate it (or its parent) to turn the character. Gravity is taken
+/// from the physics world.
+#[derive(Visit, Reflect, Debug, Clone)]
+pub struct KinematicCharacterController {
+    #[reflect(
+        description = "Settings of the character controller (slopes, stairs, snapping to the ground)."
+    )]
+    #[visit(optional)]
+    pub controller: InheritableVariable<CharacterController>,
+
+    #[reflect(
+        description = "Walking speed of the character (in m/s).",
+        min_value = 0.0
+    )]
+    #[visit(optional)]
+    pub speed: InheritableVariable<f32>,
+
+    #[reflect(
+        description = "Initial vertical speed of a jump (in m/s).",
+        min_value = 0.0
+    )]
+    #[visit(optional)]
+    pub jump_speed: InheritableVariable<f32>,
+
+    #[reflect(description = "A key, that corresponds to forward movement.")]
+    #[visit(optional)]
+    pub move_forward_key: InheritableVariable<KeyBinding>,
+
+    #[reflect(description = "A key, that corresponds to backward movement.")]
+    #[visit(optional)]
+    pub move_backward_key: InheritableVariable<KeyBinding>,
+
+    #[reflect(description = "A key, that corresponds to left movement.")]
+    #[visit(optional)]
+    pub move_left_key: InheritableVariable<KeyBinding>,
+
+    #[reflect(description = "A key, that corresponds to right movement.")]
+    #[visit(optional)]
+    pub move_right_key: InheritableVariable<KeyBinding>,
+
+    #[reflect(description = "A key, that corresponds to jump.")]
+    #[visit(optional)]
+    pub jump_key: InheritableVariable<KeyBinding>,
+
+    #[reflect(hidden)]
+    #[visit(optional)]
+    pub vertical_speed: InheritableVariable<f32>,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    pub grounded: bool,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    pub move_forward: bool,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    pub move_backward: bool,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    pub move_left: bool,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    pub move_right: bool,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    pub jump: bool,
+}
+
+impl Default for KinematicCharacterController {
+    fn default() -> Self {
+        Self {
+            controller: CharacterController {
+                autostep: Some(Default::default()),
+                ..Default::default()
+            }
+            .into(),
+            speed: 4.0.into(),
+            jump_speed: 5.0.into(),
+            move_forward_key: KeyBinding::Some(KeyCode::KeyW).into(),
+            move_backward_key: KeyBinding::Some(KeyCode::KeyS).into(),
+            move_left_key: KeyBinding::Some(KeyCode::KeyA).into(),
+            move_right_key: KeyBinding::Some(KeyCode::KeyD).into(),
+            jump_key: KeyBinding::Some(KeyCode::Space).into(),
+            vertical_speed: Default::default(),
+            grounded: false,
+            move_forward: false,
+            move_backward: false,
+            move_left: false,
+            move_right: false,
+            jump: false,
+        }
+    }
+}
+
+impl_component_provider!(KinematicCharacterController);
+uuid_provider!(KinematicCharacterController = "5b8f3a0e-2d4c-4f7e-9a61-c3e8d27b14f5");
+
+impl ScriptTrait for KinematicCharacterController {
+    fn on_os_event(&mut self, event: &Event<()>, _context: &mut ScriptContext) {
+        if let Event::WindowEvent {
+            event: WindowEvent::KeyboardInput { event, .. },
+            ..
+        } = event
+        {
+            for (binding, state) in [
+                (&self.move_forward_key, &mut self.move_forward),
+                (&self.move_backward_key, &mut self.move_backward),
+                (&self.move_left_key, &mut self.move_left),
+                (&self.move_right_key, &mut self.move_right),
+                (&self.jump_key, &mut self.jump),
+            ] {
+                if let KeyBinding::Some(key_code) = **binding {
+                    if utils::translate_key_from_ui(key_code) == event.physical_key {
+                        *state = event.state == ElementState::Pressed;
+                    }
+                }
+            }
+        }
+    }
+
+    fn on_update(&mut self, context: &mut ScriptContext) {
+        let graph = &mut context.scene.graph;
+        let this = &graph[context.handle];
+
+        let up = self
+            .controller
+            .up
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_else(Vector3::y);
+
+        let mut direction = Vector3::default();
+        if self.move_forward {
+            direction += this.look_vector();
+        }
+        if self.move_backward {
+            direction -= this.look_vector();
+        }
+        if self.move_left {
+            direction += this.side_vector();
+        }
+        if self.move_right {
+            direction -= this.side_vector();
+        }
+
+        // Walk along the ground only, vertical movement is defined by jumps and gravity.
+        direction -= up.scale(direction.dot(&up));
+        let horizontal_velocity = direction
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_default()
+            .scale(*self.speed);
+
+        if self.grounded && self.jump {
+            *self.vertical_speed = *self.jump_speed;
+        }
+        *self.vertical_speed += graph.physics.gravity.dot(&up) * context.dt;
+
+        let desired_translation =
+            (horizontal_velocity + up.scale(*self.vertical_speed)).scale(context.dt);
+
+        if let Some(movement) =
+            self.controller
+                .move_body(graph, context.handle, desired_translation, context.dt)
+        {
+            self.grounded = movement.grounded;
+
+            if self.grounded && *self.vertical_speed < 0.0 {
+                *self.vertical_speed = 0.0;
+            } else if *self.vertical_speed > 0.0 {
+                // Hitting a ceiling stops the jump.
+                let actual_vertical_speed = movement.translation.dot(&up) / context.dt;
+                *self.vertical_speed = actual_vertical_speed.clamp(0.0, *self.vertical_speed);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::character::KinematicCharacterController;
+    use fyrox::{
+        asset::{io::FsResourceIo, manager::ResourceManager},
+        core::{algebra::Vector3, task::TaskPool},
+        engine::{ApplicationLoopController, Engine, EngineInitParams, SerializationContext},
+        gui::constructor::new_widget_constructor_container,
+        scene::{
+            base::BaseBuilder,
+            collider::{ColliderBuilder, ColliderShape},
+            rigidbody::{RigidBodyBuilder, RigidBodyType},
+            transform::TransformBuilder,
+            Scene,
+        },
+    };
+    use std::{cell::Cell, sync::Arc};
+
+    #[test]
+    fn test_character_walks_and_lands() {
+        let task_pool = Arc::new(TaskPool::new());
+        let mut engine = Engine::new(EngineInitParams {
+            graphics_context_params: Default::default(),
+            serialization_context: Arc::new(SerializationContext::new()),
+            widget_constructors: Arc::new(new_widget_constructor_container()),
+            resource_manager: ResourceManager::new(Arc::new(FsResourceIo), task_pool.clone()),
+            task_pool,
+        })
+        .unwrap();
+
+        let mut scene = Scene::new();
+        // The ground, its top is at zero height.
+        RigidBodyBuilder::new(
+            BaseBuilder::new()
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(0.0, -0.5, 0.0))
+                        .build(),
+                )
+                .with_children(&[ColliderBuilder::new(BaseBuilder::new())
+                    .with_shape(ColliderShape::cuboid(50.0, 0.5, 50.0))
+                    .build(&mut scene.graph)]),
+        )
+        .with_body_type(RigidBodyType::Static)
+        .build(&mut scene.graph);
+        // The character is in the air and the forward key is held down.
+        let character = RigidBodyBuilder::new(
+            BaseBuilder::new()
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(0.0, 2.0, 0.0))
+                        .build(),
+                )
+                .with_children(&[ColliderBuilder::new(BaseBuilder::new())
+                    .with_shape(ColliderShape::capsule_y(0.5, 0.3))
+                    .build(&mut scene.graph)])
+                .with_script(KinematicCharacterController {
+                    move_forward: true,
+                    ..Default::default()
+                }),
+        )
+        .with_body_type(RigidBodyType::KinematicPositionBased)
+        .build(&mut scene.graph);
+
+        let scene = engine.scenes.add(scene);
+        engine.register_scripted_scene(scene);
+
+        let running = Cell::new(true);
+        let mut lag = 0.0;
+        for _ in 0..120 {
+            engine.update(
+                1.0 / 60.0,
+                ApplicationLoopController::Headless { running: &running },
+                &mut lag,
+                Default::default(),
+            );
+        }
+
+        let body = &engine.scenes[scene].graph[character];
+        let position = body.global_position();
+        // The character walks along its look vector with the speed of 4 m/s.
+        assert!(position.z > 6.0, "{position:?}");
+        assert!(position.x.abs() < 0.01, "{position:?}");
+        // The bottom of the capsule is on the ground.
+        assert!((position.y - 0.8).abs() < 0.05, "{position:?}");
+        let script = body
+            .try_get_script::<KinematicCharacterController>()
+            .unwrap();
+        assert!(script.grounded);
+        assert_eq!(*script.vertical_speed, 0.0);
+    }
+}
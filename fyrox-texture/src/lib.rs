@@ -329,16 +329,36 @@ impl ResourceData for Texture {
             | TexturePixelKind::R32F
             | TexturePixelKind::R16F => return Err(Box::new(TextureError::UnsupportedFormat)),
         };
-        if let TextureKind::Rectangle { width, height } = self.kind {
-            Ok(image::save_buffer(
+        match self.kind {
+            TextureKind::Rectangle { width, height } => Ok(image::save_buffer(
                 path,
                 self.bytes.as_ref(),
                 width,
                 height,
                 color_type,
-            )?)
-        } else {
-            Err(Box::new(TextureError::UnsupportedFormat))
+            )?),
+            TextureKind::Cube { width, height } => {
+                // Common image formats cannot store cube maps, so DDS is used instead.
+                let format = match self.pixel_kind {
+                    TexturePixelKind::RGBA8 => D3DFormat::A8B8G8R8,
+                    TexturePixelKind::R8 => D3DFormat::L8,
+                    TexturePixelKind::R16 => D3DFormat::L16,
+                    _ => return Err(Box::new(TextureError::UnsupportedFormat)),
+                };
+                let mut dds = ddsfile::Dds::new_d3d(ddsfile::NewD3dParams {
+                    height,
+                    width,
+                    depth: None,
+                    format,
+                    mipmap_levels: Some(self.mip_count),
+                    caps2: Some(Caps2::CUBEMAP | Caps2::CUBEMAP_ALLFACES),
+                })?;
+                dds.data = self.bytes.to_vec();
+                let mut file = std::fs::File::create(path)?;
+                dds.write(&mut file)?;
+                Ok(())
+            }
+            _ => Err(Box::new(TextureError::UnsupportedFormat)),
         }
     }
 
@@ -1462,6 +1482,7 @@ impl Texture {
                 D3DFormat::L16 => TexturePixelKind::R16,
                 D3DFormat::R8G8B8 => TexturePixelKind::RGB8,
                 D3DFormat::A8L8 => TexturePixelKind::RG8,
+                D3DFormat::A8B8G8R8 => TexturePixelKind::RGBA8,
                 D3DFormat::A8R8G8B8 => {
                     // // ARGB8 -> RGBA8
                     // assert_eq!(bytes.len() % 4, 0);